@@ -1,11 +1,11 @@
 use crate::prelude::*;
+use crate::rules;
 use bevy::prelude::*;
-use rand::Rng;
 use serde::{Deserialize, Serialize};
-use std::num::NonZero;
 use std::ops::Range;
 
-#[derive(Component, Clone, Serialize, Deserialize)]
+#[derive(Component, Clone, Reflect, Serialize, Deserialize)]
+#[reflect(Component, Clone, Serialize, Deserialize)]
 pub struct Attack {
     /// The range of damage they can do.
     pub(super) damage: Range<u32>,
@@ -19,6 +19,18 @@ impl Attack {
         Self { damage, hit_chance }
     }
 
+    /// The range of damage they can do.
+    #[inline]
+    pub fn damage(&self) -> &Range<u32> {
+        &self.damage
+    }
+
+    /// The chance the actor has to hit when they attack.
+    #[inline]
+    pub fn hit_chance(&self) -> f32 {
+        self.hit_chance
+    }
+
     pub fn from_name(name: ActorName) -> Self {
         use ActorName as A;
 
@@ -30,6 +42,12 @@ impl Attack {
             A::Ogre => (30..61, 0.6),
             A::Goblin => (15..31, 0.8),
             A::Skeleton => (30..51, 0.8),
+            A::GoblinWarlord => (25..41, 0.85),
+            A::BoneKnight => (35..56, 0.8),
+            A::OgreTyrant => (45..71, 0.65),
+            A::Construct => (25..41, 0.9),
+            A::Archer => (20..36, 0.75),
+            A::Shaman => (20..36, 0.8),
             A::UnknownJim => (0..1, 0.0),
         };
 
@@ -41,33 +59,68 @@ impl Attack {
             A::Ogre => (3000..6100, 1.0),
             A::Goblin => (1500..10000, 1.0),
             A::Skeleton => (3000..5100, 1.0),
+            A::GoblinWarlord => (2500..10000, 1.0),
+            A::BoneKnight => (3500..5600, 1.0),
+            A::OgreTyrant => (4500..7100, 1.0),
+            A::Construct => (2500..4100, 1.0),
+            A::Archer => (2000..3600, 1.0),
+            A::Shaman => (2000..3600, 1.0),
             A::UnknownJim => (0..u32::MAX, 0.0),
         };
 
         Self::new(damage, hit_chance)
     }
 
-    /// Simulates an attack using the rng and returns the
-    /// amount of damage done, or if the attack missed.
-    pub fn conduct(&self, rng: &mut impl Rng) -> AttackDamage {
-        rng.random_bool(self.hit_chance as f64)
-            .then(|| rng.random_range(self.damage.clone()))
-            .and_then(|d| NonZero::<u32>::new(d))
-            .map(|d| AttackDamage::Hit(d))
-            .unwrap_or(AttackDamage::Miss)
+    /// The attack as the rules see it, with the damage scaled by `multiplier`.
+    pub fn strike(&self, multiplier: f32) -> rules::Strike {
+        rules::Strike {
+            damage: self.damage.clone(),
+            hit_chance: self.hit_chance,
+            multiplier,
+            crit_chance: rules::CRIT_CHANCE,
+        }
     }
 }
 
-/// The damage done by an attack. An attack that does 0 damage is considered a miss.
-#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
-pub enum AttackDamage {
-    Hit(NonZero<u32>),
-    Miss,
+/// How an actor's attacks reach their targets.
+#[derive(Component, Debug, Default, Hash, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum AttackPattern {
+    /// Walks up to the middle of the room to hit one target.
+    #[default]
+    Melee,
+    /// Hits one target without leaving their place.
+    Ranged,
+    /// Hits every foe at once without leaving their place, for less damage.
+    Area,
+}
+
+impl AttackPattern {
+    pub fn from_name(name: ActorName) -> Self {
+        match name {
+            ActorName::Archer => Self::Ranged,
+            ActorName::Shaman => Self::Area,
+            _ => Self::Melee,
+        }
+    }
+
+    /// Whether the actor walks to the middle of the room before attacking.
+    pub fn moves_to_center(&self) -> bool {
+        *self == Self::Melee
+    }
+
+    /// The damage each target takes from a hit of `damage`.
+    pub fn spread_damage(&self, damage: u32) -> u32 {
+        match self {
+            Self::Melee | Self::Ranged => damage,
+            Self::Area => rules::area_damage(damage),
+        }
+    }
 }
 
 /// The chance the actor has to block an attack in combat.
 /// Should be between 0.0 and 1.0
-#[derive(Component, Deref, DerefMut, Clone, Copy, Serialize, Deserialize)]
+#[derive(Component, Deref, DerefMut, Clone, Copy, Reflect, Serialize, Deserialize)]
+#[reflect(Component, Clone, Serialize, Deserialize)]
 #[repr(transparent)]
 pub struct BlockChance(pub f32);
 
@@ -89,12 +142,30 @@ impl AttackSpeed {
             A::Ogre => 2,
             A::Goblin => 5,
             A::Skeleton => 3,
+            A::GoblinWarlord => 6,
+            A::BoneKnight => 4,
+            A::OgreTyrant => 3,
+            A::Construct => 1,
+            A::Archer => 4,
+            A::Shaman => 3,
             A::UnknownJim => 1,
         })
     }
 }
 
 impl BlockChance {
+    /// The chance to block, doubled while the actor is defending.
+    pub fn while_defending(&self, defending: bool) -> f32 {
+        rules::block_chance(self.0, defending)
+    }
+
+    pub fn guard(&self, defending: bool) -> rules::Guard {
+        rules::Guard {
+            block_chance: self.0,
+            defending,
+        }
+    }
+
     pub fn from_name(name: ActorName) -> Self {
         use ActorName as A;
         Self(match name {
@@ -104,6 +175,12 @@ impl BlockChance {
             A::Ogre => 0.2,
             A::Goblin => 0.4,
             A::Skeleton => 0.2,
+            A::GoblinWarlord => 0.4,
+            A::BoneKnight => 0.35,
+            A::OgreTyrant => 0.25,
+            A::Construct => 0.3,
+            A::Archer => 0.15,
+            A::Shaman => 0.15,
             A::UnknownJim => 0.1,
         })
     }
@@ -0,0 +1,147 @@
+//! The experience heroes earn by defeating enemies, and the levels it brings.
+
+use crate::prelude::*;
+use bevy::prelude::*;
+
+/// Heroes stop gaining levels here.
+pub const MAX_LEVEL: u32 = 10;
+
+/// How much experience a hero has, and the level it has brought them to.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Experience {
+    pub level: u32,
+    /// Experience earned towards the next level.
+    pub xp: u32,
+}
+
+impl Default for Experience {
+    fn default() -> Self {
+        Self { level: 1, xp: 0 }
+    }
+}
+
+impl Experience {
+    /// The experience needed to go from this level to the next.
+    pub fn to_next_level(&self) -> u32 {
+        20 * self.level
+    }
+
+    /// Adds experience, returning how many levels were gained.
+    pub fn gain(&mut self, xp: u32) -> u32 {
+        if self.level >= MAX_LEVEL {
+            return 0;
+        }
+
+        self.xp += xp;
+        let mut levels = 0;
+        while self.level < MAX_LEVEL && self.xp >= self.to_next_level() {
+            self.xp -= self.to_next_level();
+            self.level += 1;
+            levels += 1;
+        }
+        if self.level >= MAX_LEVEL {
+            self.xp = 0;
+        }
+
+        levels
+    }
+
+    /// The experience given for defeating the enemy.
+    pub fn for_kill(name: ActorName) -> u32 {
+        use ActorName as A;
+        match name {
+            A::Goblin => 10,
+            A::Skeleton => 15,
+            A::Ogre => 25,
+            A::Construct => 20,
+            A::Archer => 15,
+            A::Shaman => 20,
+            A::GoblinWarlord | A::BoneKnight | A::OgreTyrant => 60,
+            A::Warrior | A::Priestess | A::Theif | A::UnknownJim => 0,
+        }
+    }
+}
+
+/// Makes an actor stronger for one level gained.
+pub fn level_up_stats(health: &mut Health, attack: &mut Attack, speed: &mut AttackSpeed) {
+    health.raise_max((health.max().get() / 10).max(1));
+
+    let grow = |stat: u32| stat + (stat / 10).max(1);
+    *attack = Attack::new(
+        grow(attack.damage.start)..grow(attack.damage.end),
+        attack.hit_chance,
+    );
+
+    speed.0 += 1;
+}
+
+/// Triggered on an actor to give them experience.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct GainExperience(pub u32);
+
+#[allow(clippy::type_complexity)]
+pub fn gain_experience(
+    gain: Trigger<GainExperience>,
+    mut actors: Query<(
+        &mut Experience,
+        &mut Health,
+        &mut Attack,
+        &mut AttackSpeed,
+        &ActorName,
+        Option<&HeroName>,
+    )>,
+    mut notifications: ResMut<Notifications>,
+) {
+    let Ok((mut experience, mut health, mut attack, mut speed, name, hero_name)) =
+        actors.get_mut(gain.target())
+    else {
+        return;
+    };
+
+    let levels = experience.gain(gain.0);
+    for _ in 0..levels {
+        level_up_stats(&mut health, &mut attack, &mut speed);
+    }
+
+    if levels > 0 {
+        let name = hero_name.map_or_else(|| name.to_string(), |hero| hero.0.clone());
+        notifications.success(format!("{name} reached level {}!", experience.level));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::num::NonZero;
+
+    #[test]
+    fn experience_carries_over_levels() {
+        let mut experience = Experience::default();
+
+        assert_eq!(experience.gain(15), 0);
+        assert_eq!(experience.gain(15), 1);
+        assert_eq!(experience, Experience { level: 2, xp: 10 });
+
+        // Enough for two levels at once.
+        assert_eq!(experience.gain(90), 2);
+        assert_eq!(experience, Experience { level: 4, xp: 0 });
+
+        assert_eq!(experience.gain(10_000), MAX_LEVEL - 4);
+        assert_eq!(experience.gain(100), 0);
+        assert_eq!(experience.xp, 0);
+    }
+
+    #[test]
+    fn levels_make_heroes_stronger() {
+        let mut health = Health::with_current(NonZero::new(50), NonZero::new(100).unwrap());
+        let mut attack = Attack::new(30..61, 0.6);
+        let mut speed = AttackSpeed(4);
+
+        level_up_stats(&mut health, &mut attack, &mut speed);
+
+        assert_eq!(health.max().get(), 110);
+        assert_eq!(health.current().unwrap().get(), 60);
+        assert_eq!(*attack.damage(), 33..67);
+        assert_eq!(speed.0, 5);
+    }
+}
@@ -50,6 +50,12 @@ impl HealthBundle {
             A::Ogre => 200,
             A::Goblin => 70,
             A::Skeleton => 100,
+            A::GoblinWarlord => 260,
+            A::BoneKnight => 320,
+            A::OgreTyrant => 400,
+            A::Construct => 160,
+            A::Archer => 80,
+            A::Shaman => 90,
             A::UnknownJim => 1,
         };
 
@@ -195,6 +201,25 @@ impl Health {
         debug_assert!(self.current.is_none_or(|curr| curr <= self.max));
     }
 
+    /// Raises the max health, healing the actor by the same amount if they are alive.
+    #[inline]
+    pub fn raise_max(&mut self, amount: u32) {
+        self.max = self.max.saturating_add(amount);
+        if let Some(ref mut curr) = self.current {
+            *curr = curr.saturating_add(amount).min(self.max);
+        }
+    }
+
+    /// Lowers the max health, never below 1, and keeps the current health under it.
+    #[inline]
+    pub fn lower_max(&mut self, amount: u32) {
+        self.max =
+            NonZero::new(self.max.get().saturating_sub(amount)).unwrap_or(NonZero::<u32>::MIN);
+        if let Some(ref mut curr) = self.current {
+            *curr = (*curr).min(self.max);
+        }
+    }
+
     /// Kill the actor nomatter what
     #[inline]
     pub fn kill(&mut self) {
@@ -1,15 +1,17 @@
 mod attack;
+mod experience;
 mod health;
+mod status;
 
 pub use attack::*;
+pub use experience::*;
 pub use health::*;
+pub use status::*;
 
 use crate::prelude::*;
 use bevy::prelude::*;
-use rand::Rng;
 use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
-#[cfg(feature = "sqlite")]
 use std::num::NonZero;
 use strum::{Display, EnumIter};
 
@@ -27,6 +29,8 @@ pub struct ActorBundle {
     pub transform: Transform,
     pub animation: AnimationBundle,
     pub block_chance: BlockChance,
+    pub experience: Experience,
+    pub pattern: AttackPattern,
 }
 
 impl ActorBundle {
@@ -36,8 +40,21 @@ impl ActorBundle {
         team: Team,
         transform: Transform,
         alive: bool,
+        difficulty: Difficulty,
     ) -> Self {
         let mut health = HealthBundle::from_name(name);
+        let mut attack = Attack::from_name(name);
+
+        if team == Team::Enemy {
+            let max = difficulty.scale_enemy_stat(health.health.max().get());
+            health = HealthBundle::new(NonZero::new(max).unwrap());
+
+            let min = difficulty.scale_enemy_stat(attack.damage().start);
+            let max = difficulty
+                .scale_enemy_stat(attack.damage().end)
+                .max(min + 1);
+            attack = Attack::new(min..max, attack.hit_chance());
+        }
 
         if !alive {
             health.health.kill();
@@ -48,69 +65,139 @@ impl ActorBundle {
             name,
             team,
             health,
-            attack: Attack::from_name(name),
+            attack,
             speed: AttackSpeed::from_name(name),
             transform,
             animation: AnimationBundle::from_name(asset_server, name),
             block_chance: BlockChance::from_name(name),
+            experience: Experience::default(),
+            pattern: AttackPattern::from_name(name),
         }
     }
 }
 
 #[derive(Component)]
+#[require(StatusEffects, AttackPattern)]
 pub struct Actor;
 
+/// How many heroes are in the player's party.
+pub const PARTY_SIZE: usize = 3;
+
+/// The name the player gave a party member, shown in place of their [`ActorName`].
+#[derive(Component, Debug, Clone, PartialEq, Eq, Deref)]
+pub struct HeroName(pub String);
+
+/// Where a party member is in the party, which decides their place in the HUD.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PartySlot(pub usize);
+
+/// Where an enemy was placed in its room, so a suspended fight can find it again.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EnemySlot(pub usize);
+
 #[cfg(feature = "sqlite")]
+#[allow(clippy::type_complexity)]
 pub fn save_actors(
-    components: Query<(&ActorName, &Team, &Health, &Attack, &AttackSpeed)>,
+    In(kind): In<SaveKind>,
+    components: Query<(
+        &ActorName,
+        &Team,
+        Ref<Health>,
+        Ref<Attack>,
+        Ref<AttackSpeed>,
+        Ref<Experience>,
+        Option<&HeroName>,
+        Option<&PartySlot>,
+    )>,
     save_info: Res<SaveGame>,
     db: NonSend<Database>,
 ) -> Result<(), DatabaseError> {
     let game_id = save_info.game_id;
 
-    db.connection.execute(
-        "DELETE FROM PlayerActor WHERE game_id = :game_id",
-        (game_id.0,),
-    )?;
+    if kind == SaveKind::Full {
+        db.connection.execute(
+            "DELETE FROM PlayerActor WHERE game_id = :game_id",
+            (game_id.0,),
+        )?;
+    }
 
-    let query = r#"
+    let update = r#"
+        UPDATE PlayerActor
+            SET health_max = :health_max,
+                health_curr = :health_curr,
+                attack_damage_min = :attack_damage_min,
+                attack_damage_max = :attack_damage_max,
+                hit_chance = :hit_chance,
+                attack_speed = :attack_speed,
+                level = :level,
+                xp = :xp
+            WHERE name = :name AND display_name = :display_name AND game_id = :game;
+    "#;
+    let insert = r#"
         INSERT INTO PlayerActor(
-            name,
-            game_id,
             health_max,
             health_curr,
             attack_damage_min,
             attack_damage_max,
             hit_chance,
-            attack_speed
+            attack_speed,
+            level,
+            xp,
+            name,
+            display_name,
+            game_id
         )
         VALUES(
-            :name,
-            :game,
             :health_max,
             :health_curr,
             :attack_damage_min,
             :attack_damage_max,
             :hit_chance,
-            :attack_speed
+            :attack_speed,
+            :level,
+            :xp,
+            :name,
+            :display_name,
+            :game
         );
     "#;
-    let mut statement = db.connection.prepare(query)?;
+    let mut update = db.connection.prepare(update)?;
+    let mut insert = db.connection.prepare(insert)?;
+
+    // Saved in party order, so they load back in the same places.
+    let mut components: Vec<_> = components
+        .iter()
+        .filter(|(_, team, ..)| **team == Team::Player)
+        .collect();
+    components.sort_by_key(|(.., slot)| slot.copied());
 
-    for (name, team, health, attack, speed) in components {
-        let Team::Player = team else {
+    for (name, _, health, attack, speed, experience, hero_name, _) in components {
+        let changed = health.is_changed()
+            || attack.is_changed()
+            || speed.is_changed()
+            || experience.is_changed();
+        if kind == SaveKind::Incremental && !changed {
             continue;
-        };
-        statement.execute((
-            name.to_string(),
-            *game_id,
+        }
+
+        let row = (
             health.max(),
             health.current(),
             attack.damage.start,
             attack.damage.end,
             attack.hit_chance,
             speed.0,
-        ))?;
+            experience.level,
+            experience.xp,
+            name.to_string(),
+            hero_name.map_or_else(|| name.to_string(), |hero| hero.0.clone()),
+            *game_id,
+        );
+
+        // Actors saved before only need their stats updated.
+        if kind == SaveKind::Full || update.execute(row.clone())? == 0 {
+            insert.execute(row)?;
+        }
     }
 
     Ok(())
@@ -132,8 +219,12 @@ pub fn load_actors(
                 attack_damage_max,
                 attack_damage_min,
                 attack_speed,
-                hit_chance
-            FROM PlayerActor WHERE PlayerActor.game_id = :game;
+                hit_chance,
+                display_name,
+                level,
+                xp
+            FROM PlayerActor WHERE PlayerActor.game_id = :game
+            ORDER BY rowid;
         "#;
 
     db.connection
@@ -155,26 +246,77 @@ pub fn load_actors(
             let transform = Transform::IDENTITY;
             let animation = AnimationBundle::from_name(&asset_server, name);
             let block_chance = BlockChance::from_name(name);
+            let experience = Experience {
+                level: row.get("level")?,
+                xp: row.get("xp")?,
+            };
 
-            Ok(ActorBundle {
-                actor: Actor,
-                name,
-                team: Team::Player,
-                health,
-                attack,
-                speed,
-                transform,
-                animation,
-                block_chance,
-            })
+            let display_name = row.get::<_, String>("display_name")?;
+            let hero_name = if display_name.is_empty() {
+                HeroName(name.to_string())
+            } else {
+                HeroName(display_name)
+            };
+
+            Ok((
+                ActorBundle {
+                    actor: Actor,
+                    name,
+                    team: Team::Player,
+                    health,
+                    attack,
+                    speed,
+                    transform,
+                    animation,
+                    block_chance,
+                    experience,
+                    pattern: AttackPattern::from_name(name),
+                },
+                hero_name,
+            ))
         })?
-        .for_each(|actor| {
-            commands.spawn(actor.unwrap());
+        .enumerate()
+        .for_each(|(slot, actor)| {
+            let (actor, hero_name) = actor.unwrap();
+            commands.spawn((actor, hero_name, PartySlot(slot)));
         });
 
     Ok(())
 }
 
+/// Keeps the hover tooltip of each actor up to date with their stats.
+#[allow(clippy::type_complexity)]
+pub fn sync_actor_tooltips(
+    mut commands: Commands,
+    actors: Query<
+        (
+            Entity,
+            &ActorName,
+            Option<&HeroName>,
+            &Health,
+            &Attack,
+            Option<&Experience>,
+        ),
+        Or<(Added<Actor>, Changed<Health>, Changed<Experience>)>,
+    >,
+) {
+    for (entity, name, hero_name, health, attack, experience) in actors.iter() {
+        let current = health.current().map(|h| h.get()).unwrap_or(0);
+        let mut title = hero_name.map_or_else(|| name.to_string(), |hero| hero.0.clone());
+        if let Some(experience) = experience.filter(|_| hero_name.is_some()) {
+            title = format!("{title} (Level {})", experience.level);
+        }
+        let tooltip = Tooltip::new(title).with_body(format!(
+            "Health: {current}/{}\nDamage: {}-{}\nHit Chance: {:.0}%",
+            health.max(),
+            attack.damage.start,
+            attack.damage.end.saturating_sub(1),
+            attack.hit_chance * 100.0,
+        ));
+        commands.entity(entity).insert(tooltip);
+    }
+}
+
 /// The team the actor is in for combat.
 #[derive(
     Component, Debug, Hash, PartialEq, Eq, Clone, Copy, Serialize, Deserialize, EnumIter, Display,
@@ -199,39 +341,51 @@ pub enum ActorName {
     Goblin,
     Ogre,
     Skeleton,
+    #[strum(to_string = "Goblin Warlord")]
+    GoblinWarlord,
+    #[strum(to_string = "Bone Knight")]
+    BoneKnight,
+    #[strum(to_string = "Ogre Tyrant")]
+    OgreTyrant,
+    /// An old guard of stone, only found in ruins.
+    Construct,
+    /// A skeleton that shoots from the back.
+    Archer,
+    /// A goblin whose hexes hit the whole party at once.
+    Shaman,
     #[strum(to_string = "Unknown Jim")]
     UnknownJim,
 }
 
 impl ActorName {
-    pub fn get_enemies(rng: &mut impl Rng) -> Box<[ActorName]> {
-        let mon = rng.random_range(0..10);
-        let mut enemies: Vec<ActorName> = Vec::new();
-
-        if mon < 1 {
-            // I know that's not how you do it but I'll fix it laterElijah. Ok I'm sorry
-            for _ in 0..3 {
-                enemies.push(Self::get_rand_enemy(rng));
-            }
-        } else if mon < 3 {
-            for _ in 0..2 {
-                enemies.push(Self::get_rand_enemy(rng));
-            }
-        } else {
-            enemies.push(Self::get_rand_enemy(rng));
-        }
+    /// The heroes the player can choose their party from.
+    pub const HEROES: [ActorName; 3] = [ActorName::Warrior, ActorName::Priestess, ActorName::Theif];
+
+    /// The bosses that guard the pillars.
+    pub const GUARDIANS: [ActorName; 3] = [
+        ActorName::GoblinWarlord,
+        ActorName::BoneKnight,
+        ActorName::OgreTyrant,
+    ];
 
-        enemies.into()
+    /// The guardian of the pillar room with the given seed.
+    pub fn guardian(rng_seed: u64) -> ActorName {
+        Self::GUARDIANS[(rng_seed % Self::GUARDIANS.len() as u64) as usize]
     }
 
-    pub fn get_rand_enemy(rng: &mut impl Rng) -> ActorName {
-        let idx = rng.random_range(0..3);
+    pub fn is_guardian(&self) -> bool {
+        Self::GUARDIANS.contains(self)
+    }
 
-        match idx {
-            0 => ActorName::Goblin,
-            1 => ActorName::Ogre,
-            2 => ActorName::Skeleton,
-            _ => unreachable!(),
+    /// The actor whose sprites this one is drawn with,
+    /// as guardians are bigger versions of the regular enemies
+    /// and the others are tinted versions of them.
+    pub fn sprite_name(&self) -> ActorName {
+        match self {
+            ActorName::GoblinWarlord | ActorName::Shaman => ActorName::Goblin,
+            ActorName::BoneKnight | ActorName::Archer => ActorName::Skeleton,
+            ActorName::OgreTyrant | ActorName::Construct => ActorName::Ogre,
+            name => *name,
         }
     }
 }
@@ -244,4 +398,33 @@ pub enum SpecialAction {
     CrushingBlow,
     #[strum(to_string = "Surprise Attack")]
     SurpriseAttack,
+    #[strum(to_string = "Sweeping Strike")]
+    SweepingStrike,
+    #[strum(to_string = "Charge Up")]
+    ChargeUp,
+}
+
+impl SpecialAction {
+    /// The special move of each hero, guardian and Construct, other enemies don't have one.
+    pub fn from_name(name: ActorName) -> Option<Self> {
+        match name {
+            ActorName::Warrior => Some(Self::CrushingBlow),
+            ActorName::Priestess => Some(Self::HealTarget),
+            ActorName::Theif => Some(Self::SurpriseAttack),
+            name if name.is_guardian() => Some(Self::SweepingStrike),
+            ActorName::Construct => Some(Self::ChargeUp),
+            _ => None,
+        }
+    }
+
+    /// What the special move does, in a line.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::HealTarget => "Heals the most hurt party member for 15 to 29 health.",
+            Self::CrushingBlow => "Strikes an enemy for 20% more damage.",
+            Self::SurpriseAttack => "Attacks an enemy, then acts again right away.",
+            Self::SweepingStrike => "Strikes every foe at once for a little over half damage.",
+            Self::ChargeUp => "Winds up a blow that lands three turns later for double damage.",
+        }
+    }
 }
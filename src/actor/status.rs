@@ -0,0 +1,175 @@
+//! Effects that stay on an actor for a few turns of combat, like poison.
+
+use crate::prelude::*;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter};
+
+/// The most times one kind of effect can stack on an actor.
+pub const MAX_STATUS_STACKS: u32 = 3;
+/// The damage a stack of poison does at the end of each turn.
+pub const POISON_DAMAGE: u32 = 4;
+/// How much of the incoming damage each stack of shield takes.
+pub const SHIELD_REDUCTION: f32 = 0.25;
+
+#[derive(
+    Debug, Hash, PartialEq, Eq, Clone, Copy, EnumIter, Display, Reflect, Serialize, Deserialize,
+)]
+pub enum StatusKind {
+    /// Hurts at the end of each of the actor's turns.
+    Poison,
+    /// The actor misses their next turn.
+    Stun,
+    /// Takes some of the damage from each hit.
+    Shield,
+}
+
+impl StatusKind {
+    /// The short label shown next to the health bars.
+    pub fn label(&self) -> &'static str {
+        match self {
+            StatusKind::Poison => "PSN",
+            StatusKind::Stun => "STN",
+            StatusKind::Shield => "SHD",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            StatusKind::Poison => Color::srgb_u8(0x5f, 0xa8, 0x3c),
+            StatusKind::Stun => Color::srgb_u8(0xe0, 0xc0, 0x3a),
+            StatusKind::Shield => Color::srgb_u8(0x4a, 0x8f, 0xd4),
+        }
+    }
+
+    /// What an attack from `attacker` can leave on its target, for how many turns,
+    /// and how likely that is.
+    pub fn from_attack(attacker: ActorName, special: bool) -> Option<(Self, u32, f64)> {
+        use ActorName as A;
+        match (attacker, special) {
+            (A::Goblin | A::GoblinWarlord, false) => Some((Self::Poison, 3, 0.3)),
+            (A::Shaman, false) => Some((Self::Poison, 2, 0.25)),
+            (A::Ogre | A::OgreTyrant, false) => Some((Self::Stun, 1, 0.2)),
+            (A::GoblinWarlord, true) => Some((Self::Poison, 3, 0.6)),
+            (A::BoneKnight, true) => Some((Self::Stun, 1, 0.3)),
+            (A::OgreTyrant, true) => Some((Self::Stun, 1, 0.4)),
+            (A::Warrior, true) => Some((Self::Stun, 1, 0.5)),
+            (A::Priestess, true) => Some((Self::Shield, 2, 1.0)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Reflect, Serialize, Deserialize)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    pub stacks: u32,
+    /// How many more of the actor's turns this lasts.
+    pub turns: u32,
+}
+
+/// Every effect currently on an actor.
+#[derive(Component, Debug, Default, Clone, PartialEq, Reflect, Serialize, Deserialize)]
+#[reflect(Component, Default, Clone, Serialize, Deserialize)]
+pub struct StatusEffects(Vec<StatusEffect>);
+
+impl StatusEffects {
+    /// Adds a stack of the effect, lasting at least `turns` more turns.
+    pub fn apply(&mut self, kind: StatusKind, turns: u32) {
+        match self.0.iter_mut().find(|effect| effect.kind == kind) {
+            Some(effect) => {
+                effect.stacks = (effect.stacks + 1).min(MAX_STATUS_STACKS);
+                effect.turns = effect.turns.max(turns);
+            }
+            None => self.0.push(StatusEffect {
+                kind,
+                stacks: 1,
+                turns,
+            }),
+        }
+    }
+
+    pub fn stacks(&self, kind: StatusKind) -> u32 {
+        self.0
+            .iter()
+            .find(|effect| effect.kind == kind)
+            .map_or(0, |effect| effect.stacks)
+    }
+
+    pub fn is_stunned(&self) -> bool {
+        self.stacks(StatusKind::Stun) > 0
+    }
+
+    /// The damage left of a hit after the shield takes its share.
+    pub fn shielded(&self, damage: u32) -> u32 {
+        let reduction = SHIELD_REDUCTION * self.stacks(StatusKind::Shield) as f32;
+        (damage as f32 * (1.0 - reduction).max(0.0)).round() as u32
+    }
+
+    /// Counts down every effect at the end of the actor's turn,
+    /// returning the poison damage to deal.
+    pub fn end_turn(&mut self) -> u32 {
+        let poison = POISON_DAMAGE * self.stacks(StatusKind::Poison);
+
+        for effect in self.0.iter_mut() {
+            effect.turns = effect.turns.saturating_sub(1);
+        }
+        self.0.retain(|effect| effect.turns > 0);
+
+        poison
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &StatusEffect> {
+        self.0.iter()
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn effects_stack_up_to_the_cap() {
+        let mut effects = StatusEffects::default();
+        for _ in 0..5 {
+            effects.apply(StatusKind::Poison, 2);
+        }
+        effects.apply(StatusKind::Poison, 4);
+
+        assert_eq!(effects.stacks(StatusKind::Poison), MAX_STATUS_STACKS);
+        assert_eq!(effects.iter().next().unwrap().turns, 4);
+        assert_eq!(effects.stacks(StatusKind::Stun), 0);
+    }
+
+    #[test]
+    fn effects_tick_and_expire() {
+        let mut effects = StatusEffects::default();
+        effects.apply(StatusKind::Poison, 2);
+        effects.apply(StatusKind::Poison, 2);
+        effects.apply(StatusKind::Stun, 1);
+        assert!(effects.is_stunned());
+
+        assert_eq!(effects.end_turn(), POISON_DAMAGE * 2);
+        assert!(!effects.is_stunned());
+        assert_eq!(effects.end_turn(), POISON_DAMAGE * 2);
+        assert_eq!(effects.end_turn(), 0);
+        assert_eq!(effects, StatusEffects::default());
+    }
+
+    #[test]
+    fn shield_takes_part_of_each_hit() {
+        let mut effects = StatusEffects::default();
+        assert_eq!(effects.shielded(40), 40);
+
+        effects.apply(StatusKind::Shield, 2);
+        assert_eq!(effects.shielded(40), 30);
+
+        effects.apply(StatusKind::Shield, 2);
+        effects.apply(StatusKind::Shield, 2);
+        assert_eq!(effects.shielded(40), 10);
+    }
+}
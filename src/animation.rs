@@ -4,6 +4,13 @@ use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
 
 const NORMAL_TICK_SPEED: usize = 1;
+/// How much bigger guardians are drawn than the enemies they are based on.
+const GUARDIAN_SCALE: f32 = 1.4;
+const GUARDIAN_TINT: Color = Color::srgb(1.0, 0.7, 0.7);
+/// Constructs are drawn as ogres, grayed out to look like stone.
+const CONSTRUCT_TINT: Color = Color::srgb(0.6, 0.6, 0.65);
+const ARCHER_TINT: Color = Color::srgb(0.8, 0.9, 0.7);
+const SHAMAN_TINT: Color = Color::srgb(0.75, 0.7, 1.0);
 
 pub struct AnimationPlugin;
 
@@ -17,12 +24,18 @@ impl Plugin for AnimationPlugin {
         embed_asset!(app, "assets/sprites/Skeleton.png");
         embed_asset!(app, "assets/sprites/Unknown Jim.png");
         app.init_resource::<AnimationFrameTimer>()
-            .add_systems(Update, execute_animations);
+            .register_effect::<HurtFlash>()
+            .add_systems(Update, (execute_animations, play_clips, fade_hurt_flashes))
+            .add_observer(play_health_clips);
     }
 }
 
 /// The number of seconds the per AnimationFrameTimer trigger.
 pub const ANIMATION_FRAME_TIMER_SECONDS: f32 = 0.5;
+/// How far through the attack clip the blow lands.
+pub const ATTACK_IMPACT_FRACTION: f32 = 0.6;
+const HURT_FLASH_SECONDS: f32 = 0.25;
+const HURT_FLASH_COLOR: Color = Color::srgb(1.0, 0.3, 0.3);
 
 #[derive(Resource, Deref, DerefMut, Reflect)]
 #[reflect(Resource, Default)]
@@ -57,6 +70,7 @@ impl AnimationBundle {
 pub struct AnimationConfigs {
     /// The normal animation
     normal: AnimationConfig,
+    attack: AnimationConfig,
     damaged: AnimationConfig,
     dead: AnimationConfig,
     active: ActiveAnimation,
@@ -68,6 +82,7 @@ impl AnimationConfigs {
     pub fn from_name(name: ActorName) -> Self {
         Self {
             normal: AnimationConfig::from_name(ActiveAnimation::Normal, name),
+            attack: AnimationConfig::from_name(ActiveAnimation::Attack, name),
             damaged: AnimationConfig::from_name(ActiveAnimation::Damaged, name),
             dead: AnimationConfig::from_name(ActiveAnimation::Dead, name),
             active: ActiveAnimation::Normal,
@@ -80,11 +95,27 @@ impl AnimationConfigs {
         use ActiveAnimation as A;
         match self.active {
             A::Normal => &self.normal,
+            A::Attack => &self.attack,
             A::Damaged => &self.damaged,
             A::Dead => &self.dead,
         }
     }
 
+    pub fn active(&self) -> ActiveAnimation {
+        self.active
+    }
+
+    /// Switches to the clip and starts it from its first frame.
+    /// Clips other than [`ActiveAnimation::Normal`] play once, see [`play_clips`].
+    pub fn play(&mut self, clip: ActiveAnimation, sprite: &mut Sprite) {
+        self.active = clip;
+        self.tick_count = 0;
+
+        if let Some(atlas) = &mut sprite.texture_atlas {
+            atlas.index = self.current().first_sprite_index;
+        }
+    }
+
     /// Ticks the animation counter and
     /// returns true if the animation should progress
     pub fn tick(&mut self) {
@@ -102,11 +133,193 @@ impl AnimationConfigs {
 
 #[derive(Debug, Hash, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
 pub enum ActiveAnimation {
+    /// The idle loop.
     Normal,
+    Attack,
+    /// Getting hurt.
     Damaged,
+    /// Falling over, after which the last frame is held.
     Dead,
 }
 
+impl ActiveAnimation {
+    /// How long the clip plays once for, or `None` for the looping idle clip.
+    pub fn clip_seconds(&self) -> Option<f32> {
+        match self {
+            ActiveAnimation::Normal => None,
+            ActiveAnimation::Attack => Some(0.45),
+            ActiveAnimation::Damaged => Some(0.3),
+            ActiveAnimation::Dead => Some(0.6),
+        }
+    }
+}
+
+/// Triggered on an actor as their clips play.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnimationEvent {
+    /// The attack clip reached the moment the blow lands,
+    /// which is when combat deals the damage.
+    Impact,
+    /// A clip that plays once has finished.
+    Finished(ActiveAnimation),
+}
+
+/// Times a clip that plays once. Removed when the clip is done.
+#[derive(Component, Deref, DerefMut)]
+pub struct ClipTimer(Timer);
+
+/// Starts a clip that plays once on an actor.
+pub fn start_clip(
+    commands: &mut Commands,
+    entity: Entity,
+    clip: ActiveAnimation,
+    config: &mut AnimationConfigs,
+    sprite: &mut Sprite,
+) {
+    config.play(clip, sprite);
+
+    match clip.clip_seconds() {
+        Some(seconds) => {
+            commands
+                .entity(entity)
+                .insert(ClipTimer(Timer::from_seconds(seconds, TimerMode::Once)));
+        }
+        None => {
+            commands.entity(entity).remove::<ClipTimer>();
+        }
+    }
+}
+
+/// The frame of the clip to show `fraction` of the way through it.
+fn clip_frame(config: &AnimationConfig, fraction: f32) -> usize {
+    let frames = config.last_sprite_index - config.first_sprite_index + 1;
+    let frame = (fraction * frames as f32) as usize;
+    config.first_sprite_index + frame.min(frames - 1)
+}
+
+/// Whether the blow lands between two points in the attack clip.
+fn crosses_impact(before: f32, after: f32) -> bool {
+    before < ATTACK_IMPACT_FRACTION && after >= ATTACK_IMPACT_FRACTION
+}
+
+fn play_clips(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut clips: Query<(Entity, &mut ClipTimer, &mut AnimationConfigs, &mut Sprite)>,
+) {
+    for (entity, mut timer, mut config, mut sprite) in clips.iter_mut() {
+        let before = timer.fraction();
+        timer.tick(time.delta());
+        let after = timer.fraction();
+        let clip = config.active();
+
+        if let Some(atlas) = &mut sprite.texture_atlas {
+            atlas.index = clip_frame(config.current(), after);
+        }
+
+        if clip == ActiveAnimation::Attack && crosses_impact(before, after) {
+            commands.entity(entity).trigger(AnimationEvent::Impact);
+        }
+
+        if timer.finished() {
+            commands.entity(entity).remove::<ClipTimer>();
+            if clip != ActiveAnimation::Dead {
+                config.play(ActiveAnimation::Normal, &mut sprite);
+            }
+            commands
+                .entity(entity)
+                .trigger(AnimationEvent::Finished(clip));
+        }
+    }
+}
+
+/// A short red flash on an actor that was just hurt.
+#[derive(Component)]
+pub struct HurtFlash {
+    timer: Timer,
+    /// The color to go back to after the flash.
+    base: Color,
+}
+
+impl VisualEffect for HurtFlash {
+    const KIND: EffectKind = EffectKind::Flash;
+}
+
+/// Plays the hurt and death clips as actors' health changes.
+fn play_health_clips(
+    change: Trigger<HealthChange>,
+    mut commands: Commands,
+    mut actors: Query<(&mut AnimationConfigs, &mut Sprite, Option<&HurtFlash>)>,
+    budget: EffectsBudget,
+) {
+    let entity = change.target();
+    let Ok((mut config, mut sprite, flash)) = actors.get_mut(entity) else {
+        return;
+    };
+
+    match change.event() {
+        HealthChange::Damaged => {
+            start_clip(
+                &mut commands,
+                entity,
+                ActiveAnimation::Damaged,
+                &mut config,
+                &mut sprite,
+            );
+
+            if let Some(budgeted) = budget.allow(EffectKind::Flash) {
+                // Keep the color from before any flash that is still playing.
+                let base = flash.map_or(sprite.color, |flash| flash.base);
+                let alpha = base.alpha();
+                sprite.color = base
+                    .mix(&HURT_FLASH_COLOR, budgeted.scale())
+                    .with_alpha(alpha);
+                commands.entity(entity).insert((
+                    HurtFlash {
+                        timer: Timer::from_seconds(HURT_FLASH_SECONDS, TimerMode::Once),
+                        base,
+                    },
+                    budgeted,
+                ));
+            }
+        }
+        HealthChange::Killed => {
+            start_clip(
+                &mut commands,
+                entity,
+                ActiveAnimation::Dead,
+                &mut config,
+                &mut sprite,
+            );
+        }
+        HealthChange::Revived => {
+            start_clip(
+                &mut commands,
+                entity,
+                ActiveAnimation::Normal,
+                &mut config,
+                &mut sprite,
+            );
+        }
+        HealthChange::Healed => {}
+    }
+}
+
+fn fade_hurt_flashes(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut flashes: Query<(Entity, &mut HurtFlash, &mut Sprite)>,
+) {
+    for (entity, mut flash, mut sprite) in flashes.iter_mut() {
+        flash.timer.tick(time.delta());
+
+        if flash.timer.finished() {
+            sprite.color = flash.base;
+            commands.entity(entity).remove::<(HurtFlash, Budgeted)>();
+        }
+    }
+}
+
 /// The config for automating animation
 #[derive(Clone, Serialize, Deserialize)]
 pub struct AnimationConfig {
@@ -128,30 +341,47 @@ impl AnimationConfig {
         match (active, name) {
             // TODO: Make Real stats Self stats accurate (I copied it from Theif)
             (A::Normal, C::Warrior) => Self::new(0, 1),
+            (A::Attack, C::Warrior) => Self::new(0, 1),
             (A::Damaged, C::Warrior) => Self::new(0, 1),
             (A::Dead, C::Warrior) => Self::new(0, 1),
 
             (A::Normal, C::Priestess) => Self::new(0, 1),
+            (A::Attack, C::Priestess) => Self::new(0, 1),
             (A::Damaged, C::Priestess) => Self::new(0, 1),
             (A::Dead, C::Priestess) => Self::new(0, 1),
 
             (A::Normal, C::Theif) => Self::new(0, 1),
+            (A::Attack, C::Theif) => Self::new(0, 1),
             (A::Damaged, C::Theif) => Self::new(0, 1),
             (A::Dead, C::Theif) => Self::new(0, 1),
 
             (A::Normal, C::Ogre) => Self::new(0, 1),
+            (A::Attack, C::Ogre) => Self::new(0, 1),
             (A::Damaged, C::Ogre) => Self::new(0, 1),
             (A::Dead, C::Ogre) => Self::new(0, 1),
 
             (A::Normal, C::Goblin) => Self::new(0, 1),
+            (A::Attack, C::Goblin) => Self::new(0, 1),
             (A::Damaged, C::Goblin) => Self::new(0, 1),
             (A::Dead, C::Goblin) => Self::new(0, 1),
 
             (A::Normal, C::Skeleton) => Self::new(0, 1),
+            (A::Attack, C::Skeleton) => Self::new(0, 1),
             (A::Damaged, C::Skeleton) => Self::new(0, 1),
             (A::Dead, C::Skeleton) => Self::new(0, 1),
 
+            (
+                _,
+                C::GoblinWarlord
+                | C::BoneKnight
+                | C::OgreTyrant
+                | C::Construct
+                | C::Archer
+                | C::Shaman,
+            ) => Self::from_name(active, name.sprite_name()),
+
             (A::Normal, C::UnknownJim) => Self::new(0, 3),
+            (A::Attack, C::UnknownJim) => Self::new(0, 3),
             (A::Damaged, C::UnknownJim) => Self::new(4, 4),
             (A::Dead, C::UnknownJim) => Self::new(8, 8),
         }
@@ -168,11 +398,22 @@ pub fn name_to_sprite(asset_server: &AssetServer, name: ActorName) -> Sprite {
         index: 0,
     };
 
-    Sprite::from_atlas_image(asset, atlas)
+    let mut sprite = Sprite::from_atlas_image(asset, atlas);
+    if name.is_guardian() {
+        sprite.color = GUARDIAN_TINT;
+        sprite.custom_size = Some(name_to_sprite_size(name).as_vec2() * GUARDIAN_SCALE);
+    }
+    match name {
+        ActorName::Construct => sprite.color = CONSTRUCT_TINT,
+        ActorName::Archer => sprite.color = ARCHER_TINT,
+        ActorName::Shaman => sprite.color = SHAMAN_TINT,
+        _ => {}
+    }
+    sprite
 }
 
 pub fn name_to_sprite_path(name: ActorName) -> String {
-    format!("embedded://assets/sprites/{}.png", name)
+    format!("embedded://assets/sprites/{}.png", name.sprite_name())
 }
 
 pub fn name_to_sprite_size(name: ActorName) -> UVec2 {
@@ -184,6 +425,9 @@ pub fn name_to_sprite_size(name: ActorName) -> UVec2 {
         A::Ogre => UVec2::new(32, 60),
         A::Goblin => UVec2::new(32, 60),
         A::Skeleton => UVec2::new(32, 60),
+        A::GoblinWarlord | A::BoneKnight | A::OgreTyrant | A::Construct | A::Archer | A::Shaman => {
+            name_to_sprite_size(name.sprite_name())
+        }
         A::UnknownJim => UVec2::new(32, 60),
     }
 }
@@ -197,6 +441,9 @@ pub fn name_to_atlas_layout(name: ActorName) -> TextureAtlasLayout {
         A::Ogre => (2, 1),
         A::Goblin => (2, 1),
         A::Skeleton => (2, 1),
+        A::GoblinWarlord | A::BoneKnight | A::OgreTyrant | A::Construct | A::Archer | A::Shaman => {
+            return name_to_atlas_layout(name.sprite_name());
+        }
         A::UnknownJim => (4, 2),
     };
 
@@ -206,7 +453,7 @@ pub fn name_to_atlas_layout(name: ActorName) -> TextureAtlasLayout {
 pub fn execute_animations(
     time: Res<Time>,
     mut frame_timer: ResMut<AnimationFrameTimer>,
-    mut query: Query<(&mut AnimationConfigs, &mut Sprite)>,
+    mut query: Query<(&mut AnimationConfigs, &mut Sprite), Without<ClipTimer>>,
 ) {
     frame_timer.tick(time.delta());
 
@@ -220,6 +467,11 @@ pub fn execute_animations(
             continue;
         }
 
+        // The dead stay down.
+        if config.active() == ActiveAnimation::Dead {
+            continue;
+        }
+
         let config = config.current();
 
         let Some(atlas) = &mut sprite.texture_atlas else {
@@ -233,3 +485,67 @@ pub fn execute_animations(
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn clips_step_through_their_frames() {
+        let config = AnimationConfig::new(4, 7);
+
+        assert_eq!(clip_frame(&config, 0.0), 4);
+        assert_eq!(clip_frame(&config, 0.5), 6);
+        assert_eq!(clip_frame(&config, 1.0), 7);
+        assert_eq!(clip_frame(&AnimationConfig::new(8, 8), 0.7), 8);
+    }
+
+    #[derive(Resource, Default)]
+    struct SeenEvents(Vec<AnimationEvent>);
+
+    #[test]
+    fn attack_lands_once_then_goes_back_to_idle() {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .init_resource::<SeenEvents>()
+            .add_systems(Update, play_clips)
+            .add_observer(
+                |event: Trigger<AnimationEvent>, mut seen: ResMut<SeenEvents>| {
+                    seen.0.push(*event.event());
+                },
+            );
+
+        let mut config = AnimationConfigs::from_name(ActorName::Goblin);
+        let mut sprite = Sprite::default();
+        config.play(ActiveAnimation::Attack, &mut sprite);
+        let seconds = ActiveAnimation::Attack.clip_seconds().unwrap();
+        let actor = app
+            .world_mut()
+            .spawn((
+                config,
+                sprite,
+                ClipTimer(Timer::from_seconds(seconds, TimerMode::Once)),
+            ))
+            .id();
+
+        let steps = 10;
+        for _ in 0..steps + 2 {
+            app.world_mut()
+                .resource_mut::<Time>()
+                .advance_by(Duration::from_secs_f32(seconds / steps as f32));
+            app.update();
+        }
+
+        assert_eq!(
+            app.world().resource::<SeenEvents>().0,
+            [
+                AnimationEvent::Impact,
+                AnimationEvent::Finished(ActiveAnimation::Attack)
+            ]
+        );
+        assert!(app.world().get::<ClipTimer>(actor).is_none());
+        let config = app.world().get::<AnimationConfigs>(actor).unwrap();
+        assert_eq!(config.active(), ActiveAnimation::Normal);
+    }
+}
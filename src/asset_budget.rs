@@ -0,0 +1,176 @@
+//! A debug panel with the memory used by loaded images and the tile counts
+//! of each tilemap, warning when they go over budget. Toggled with F6.
+
+use crate::prelude::*;
+use crate::sky::SkyTileMapMarker;
+use bevy::ecs::entity::Entities;
+use bevy::input::common_conditions::input_just_pressed;
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::*;
+use std::time::Duration;
+
+const BUDGET_TOGGLE_KEY: KeyCode = KeyCode::F6;
+const BUDGET_REFRESH_INTERVAL: Duration = Duration::from_millis(500);
+const BUDGET_Z_INDEX: i32 = 200;
+const OVER_BUDGET_COLOR: Color = Color::srgb(0.92, 0.44, 0.44);
+
+const IMAGE_MEMORY_BUDGET: usize = 64 * 1024 * 1024;
+const ENTITY_BUDGET: u32 = 20_000;
+const TILEMAP_TILE_BUDGET: usize = 5_000;
+
+pub struct AssetBudgetPlugin;
+
+impl Plugin for AssetBudgetPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BudgetRefresh>()
+            .add_systems(Startup, spawn_budget_panel)
+            .add_systems(
+                Update,
+                (
+                    toggle_budget_panel.run_if(input_just_pressed(BUDGET_TOGGLE_KEY)),
+                    update_budget_panel,
+                ),
+            );
+    }
+}
+
+#[derive(Resource, Default)]
+struct BudgetRefresh {
+    since_refresh: Duration,
+    /// So each budget is only warned about once each time it goes over.
+    warned: Vec<String>,
+}
+
+#[derive(Component)]
+struct BudgetPanel;
+
+fn spawn_budget_panel(mut commands: Commands, style: Res<Style>) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(4.0),
+            left: Val::Px(4.0),
+            padding: UiRect::all(Val::Px(4.0)),
+            flex_direction: FlexDirection::Column,
+            ..default()
+        },
+        BackgroundColor(style.background_color),
+        GlobalZIndex(BUDGET_Z_INDEX),
+        Visibility::Hidden,
+        Pickable::IGNORE,
+        BudgetPanel,
+    ));
+}
+
+fn toggle_budget_panel(mut panel: Query<&mut Visibility, With<BudgetPanel>>) {
+    for mut visibility in panel.iter_mut() {
+        visibility.toggle_visible_hidden();
+    }
+}
+
+fn format_bytes(bytes: usize) -> String {
+    format!("{:.1} MiB", bytes as f64 / (1024.0 * 1024.0))
+}
+
+fn update_budget_panel(
+    mut commands: Commands,
+    mut refresh: ResMut<BudgetRefresh>,
+    panel: Query<(Entity, &Visibility), With<BudgetPanel>>,
+    time: Res<Time<Real>>,
+    style: Res<Style>,
+    images: Res<Assets<Image>>,
+    atlases: Res<Assets<TextureAtlasLayout>>,
+    entities: &Entities,
+    tilemaps: Query<(
+        Entity,
+        &TileStorage,
+        Has<MapTilemap>,
+        Has<RoomTilemap>,
+        Has<SkyTileMapMarker>,
+    )>,
+) {
+    refresh.since_refresh += time.delta();
+    if refresh.since_refresh < BUDGET_REFRESH_INTERVAL {
+        return;
+    }
+    refresh.since_refresh = Duration::ZERO;
+
+    let image_bytes: usize = images
+        .iter()
+        .filter_map(|(_, image)| image.data.as_ref())
+        .map(Vec::len)
+        .sum();
+
+    // (text, over budget)
+    let mut lines = vec![
+        (
+            format!(
+                "Images: {} using {} / {}",
+                images.len(),
+                format_bytes(image_bytes),
+                format_bytes(IMAGE_MEMORY_BUDGET)
+            ),
+            image_bytes > IMAGE_MEMORY_BUDGET,
+        ),
+        (format!("Atlas layouts: {}", atlases.len()), false),
+        (
+            format!("Entities: {} / {ENTITY_BUDGET}", entities.len()),
+            entities.len() > ENTITY_BUDGET,
+        ),
+    ];
+
+    for (entity, storage, map, room, sky) in tilemaps.iter() {
+        let name = match (map, room, sky) {
+            (true, _, _) => "Map".to_string(),
+            (_, true, _) => "Room".to_string(),
+            (_, _, true) => "Sky".to_string(),
+            _ => format!("Tilemap {entity}"),
+        };
+        let tiles = storage.iter().flatten().count();
+
+        lines.push((
+            format!("{name} tiles: {tiles} / {TILEMAP_TILE_BUDGET}"),
+            tiles > TILEMAP_TILE_BUDGET,
+        ));
+    }
+
+    for (line, over) in lines.iter() {
+        let budget = line.split(':').next().unwrap_or_default().to_string();
+        let warned = refresh.warned.contains(&budget);
+        if *over && !warned {
+            warn!("Over budget! {line}");
+            refresh.warned.push(budget);
+        } else if !*over && warned {
+            refresh.warned.retain(|b| *b != budget);
+        }
+    }
+
+    for (entity, visibility) in panel.iter() {
+        if *visibility == Visibility::Hidden {
+            continue;
+        }
+
+        commands
+            .entity(entity)
+            .despawn_related::<Children>()
+            .with_children(|builder| {
+                for (line, over) in lines.iter() {
+                    let color = if *over {
+                        OVER_BUDGET_COLOR
+                    } else {
+                        style.text_color
+                    };
+
+                    builder.spawn((
+                        Text::new(line.clone()),
+                        TextFont {
+                            font_size: 16.0,
+                            ..default()
+                        },
+                        TextColor(color),
+                        Pickable::IGNORE,
+                    ));
+                }
+            });
+    }
+}
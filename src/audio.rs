@@ -0,0 +1,135 @@
+//! The sound effects and their volume settings.
+//!
+//! There are no audio files yet, so the effects are played as simple tones.
+//! There is no music until there are real tracks to play.
+
+use crate::prelude::*;
+use crate::settings::{SETTINGS_DB_TABLE, setting_settled};
+use bevy::audio::Volume as AudioVolume;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+pub struct AudioPlugin;
+
+impl Plugin for AudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Sfx>()
+            .add_systems(PreStartup, setup_volume)
+            .add_systems(Startup, setup_sfx)
+            .add_systems(
+                Update,
+                (
+                    play_sfx,
+                    apply_volume.run_if(resource_changed::<Volume>),
+                    volume_sync.run_if(setting_settled::<Volume>),
+                ),
+            );
+    }
+}
+
+/// Which volume slider a sound is under.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Channel {
+    Sfx,
+}
+
+/// The volume of each channel, from 0 to 1.
+/// Every channel is also scaled by the master volume.
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Volume {
+    pub master: f32,
+    pub sfx: f32,
+}
+
+impl Default for Volume {
+    fn default() -> Self {
+        Self {
+            master: 1.0,
+            sfx: 0.8,
+        }
+    }
+}
+
+impl Volume {
+    /// The volume a sound on the channel should play at.
+    pub fn of(&self, channel: Channel) -> AudioVolume {
+        let volume = match channel {
+            Channel::Sfx => self.sfx,
+        };
+
+        AudioVolume::Linear(self.master * volume)
+    }
+
+    fn from_database(db: &Database) -> Self {
+        let default = Self::default();
+        let get = |key, default: f32| db.get_kv(SETTINGS_DB_TABLE, key, default).clamp(0.0, 1.0);
+
+        Self {
+            master: get("master_volume", default.master),
+            sfx: get("sfx_volume", default.sfx),
+        }
+    }
+
+    fn to_database(self, db: &Database) -> Result<(), crate::database::SetKvError> {
+        db.set_kv(SETTINGS_DB_TABLE, "master_volume", self.master)?;
+        db.set_kv(SETTINGS_DB_TABLE, "sfx_volume", self.sfx)?;
+
+        Ok(())
+    }
+}
+
+fn setup_volume(mut commands: Commands, database: NonSend<Database>) {
+    commands.insert_resource(Volume::from_database(&database));
+}
+
+fn volume_sync(database: NonSend<Database>, volume: Res<Volume>) -> Result {
+    volume.to_database(&database).map_err(GameError::from)?;
+    Ok(())
+}
+
+fn apply_volume(volume: Res<Volume>, mut sinks: Query<(&mut AudioSink, &Channel)>) {
+    for (mut sink, channel) in sinks.iter_mut() {
+        sink.set_volume(volume.of(*channel));
+    }
+}
+
+/// A sound effect to play.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Sfx {
+    Hit,
+    Miss,
+}
+
+#[derive(Resource)]
+struct SfxHandles {
+    hit: Handle<Pitch>,
+    miss: Handle<Pitch>,
+}
+
+fn setup_sfx(mut commands: Commands, mut pitches: ResMut<Assets<Pitch>>) {
+    commands.insert_resource(SfxHandles {
+        hit: pitches.add(Pitch::new(660.0, Duration::from_millis(90))),
+        miss: pitches.add(Pitch::new(150.0, Duration::from_millis(160))),
+    });
+}
+
+fn play_sfx(
+    mut commands: Commands,
+    mut events: EventReader<Sfx>,
+    handles: Res<SfxHandles>,
+    volume: Res<Volume>,
+) {
+    for sfx in events.read() {
+        let handle = match sfx {
+            Sfx::Hit => handles.hit.clone(),
+            Sfx::Miss => handles.miss.clone(),
+        };
+
+        commands.spawn((
+            AudioPlayer(handle),
+            PlaybackSettings::DESPAWN.with_volume(volume.of(Channel::Sfx)),
+            Channel::Sfx,
+        ));
+    }
+}
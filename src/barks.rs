@@ -0,0 +1,337 @@
+//! Short lines the party says over their heads when something happens to them,
+//! like getting low on health, finding a Pillar or walking into a room nobody has seen.
+//!
+//! The lines are kept in `assets/dialogue/barks.ron`, and who says which is picked by
+//! the seed of the room the party is in. How often the party speaks up is set in the display settings.
+
+use crate::prelude::*;
+use crate::room::CurrentRoom;
+use crate::settings::SETTINGS_DB_TABLE;
+use bevy::text::TextBounds;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use strum::Display;
+
+const BARKS_RON: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/assets/dialogue/barks.ron"
+));
+
+/// Mixed into the room's seed, so barks don't follow the room's events.
+const BARK_RNG_STREAM: u64 = 0xba4c_5e1f_0d1a_7095;
+/// How long a bubble stays up for.
+const BUBBLE_SECONDS: f32 = 2.5;
+/// Where a bubble sits, above the actor's center.
+const BUBBLE_HEIGHT: f32 = 80.0;
+const BUBBLE_WIDTH: f32 = 180.0;
+const BUBBLE_PADDING: f32 = 8.0;
+const BUBBLE_FONT_SIZE: f32 = 16.0;
+/// Roughly how wide a character of the bubble's font is, to size the bubble before the text is laid out.
+const BUBBLE_CHAR_WIDTH: f32 = BUBBLE_FONT_SIZE * 0.55;
+const BUBBLE_LINE_HEIGHT: f32 = BUBBLE_FONT_SIZE * 1.2;
+const BUBBLE_MAX_LINES: f32 = 4.0;
+const BUBBLE_LAYER: f32 = 11.0;
+/// A hero's health has to drop to this fraction of their max before they complain about it.
+const LOW_HEALTH_FRACTION: f32 = 0.25;
+
+pub struct BarksPlugin;
+
+impl Plugin for BarksPlugin {
+    fn build(&self, app: &mut App) {
+        let pool: BarkPool = ron::from_str(BARKS_RON).expect("barks.ron should be valid");
+
+        app.insert_resource(pool)
+            .add_systems(PreStartup, setup_bark_frequency)
+            .add_systems(
+                Update,
+                bark_frequency_sync.run_if(
+                    resource_changed::<BarkFrequency>.and(not(resource_added::<BarkFrequency>)),
+                ),
+            )
+            .add_systems(Update, follow_speakers.run_if(in_state(AppState::Game)))
+            .add_observer(bark_on_low_health)
+            .add_observer(spawn_bubble);
+    }
+}
+
+/// What set a bark off, each with its own lines in the pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BarkContext {
+    LowHealth,
+    PillarFound,
+    DarkRoom,
+}
+
+/// Triggered for a party member to say something about what just happened.
+/// Without a speaker, one of the living heroes is picked.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bark {
+    pub context: BarkContext,
+    pub speaker: Option<Entity>,
+}
+
+impl Bark {
+    pub fn new(context: BarkContext) -> Self {
+        Self {
+            context,
+            speaker: None,
+        }
+    }
+}
+
+/// Every line the party can say, loaded from `assets/dialogue/barks.ron`.
+#[derive(Resource, Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct BarkPool {
+    low_health: Vec<String>,
+    pillar_found: Vec<String>,
+    dark_room: Vec<String>,
+}
+
+impl BarkPool {
+    pub fn lines(&self, context: BarkContext) -> &[String] {
+        match context {
+            BarkContext::LowHealth => &self.low_health,
+            BarkContext::PillarFound => &self.pillar_found,
+            BarkContext::DarkRoom => &self.dark_room,
+        }
+    }
+
+    /// Picks one of the lines for the context, if there are any.
+    pub fn pick(&self, context: BarkContext, rng: &mut impl Rng) -> Option<&str> {
+        let lines = self.lines(context);
+        match lines.len() {
+            0 => None,
+            len => Some(&lines[rng.random_range(0..len)]),
+        }
+    }
+}
+
+/// How often the party speaks up, set in the display settings.
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Display)]
+pub enum BarkFrequency {
+    Off,
+    #[default]
+    Sometimes,
+    Always,
+}
+
+impl BarkFrequency {
+    const DB_KEY: &str = "bark_frequency";
+
+    /// The setting after this one, for cycling through them with a button.
+    pub fn next(self) -> Self {
+        match self {
+            BarkFrequency::Off => BarkFrequency::Sometimes,
+            BarkFrequency::Sometimes => BarkFrequency::Always,
+            BarkFrequency::Always => BarkFrequency::Off,
+        }
+    }
+
+    /// The chance of a bark being said at all.
+    pub fn chance(self) -> f64 {
+        match self {
+            BarkFrequency::Off => 0.0,
+            BarkFrequency::Sometimes => 0.4,
+            BarkFrequency::Always => 1.0,
+        }
+    }
+}
+
+/// The random source for a bark in the room, so the same room gets the same barks.
+pub fn bark_rng(room_seed: u64, context: BarkContext, speaker: Option<PartySlot>) -> RandomSource {
+    let speaker = speaker.map_or(0, |PartySlot(slot)| slot as u64 + 1);
+    RandomSource::seed_from_u64(
+        room_seed ^ BARK_RNG_STREAM ^ (context as u64).wrapping_mul(0x100) ^ speaker,
+    )
+}
+
+/// How big a bubble has to be to fit the line.
+fn bubble_size(line: &str) -> Vec2 {
+    let text_width = BUBBLE_WIDTH - 2.0 * BUBBLE_PADDING;
+    let line_width = line.chars().count() as f32 * BUBBLE_CHAR_WIDTH;
+    let lines = (line_width / text_width)
+        .ceil()
+        .clamp(1.0, BUBBLE_MAX_LINES);
+    let width = line_width.min(text_width) + 2.0 * BUBBLE_PADDING;
+    Vec2::new(width, lines * BUBBLE_LINE_HEIGHT + 2.0 * BUBBLE_PADDING)
+}
+
+/// A speech bubble over a party member, following them until it runs out.
+#[derive(Component)]
+struct SpeechBubble {
+    speaker: Entity,
+    timer: Timer,
+}
+
+/// Set on a hero once they have complained about their health, until they are healed up again.
+#[derive(Component)]
+struct BarkedLowHealth;
+
+fn setup_bark_frequency(mut commands: Commands, database: NonSend<Database>) {
+    let frequency = database.get_kv(
+        SETTINGS_DB_TABLE,
+        BarkFrequency::DB_KEY,
+        BarkFrequency::default(),
+    );
+    commands.insert_resource(frequency);
+}
+
+fn bark_frequency_sync(database: NonSend<Database>, frequency: Res<BarkFrequency>) -> Result {
+    database
+        .set_kv(SETTINGS_DB_TABLE, BarkFrequency::DB_KEY, *frequency)
+        .map_err(GameError::from)?;
+    Ok(())
+}
+
+fn is_low_health(health: &Health) -> bool {
+    health.current().is_some_and(|current| {
+        current.get() as f32 <= health.max().get() as f32 * LOW_HEALTH_FRACTION
+    })
+}
+
+fn bark_on_low_health(
+    change: Trigger<HealthChange>,
+    mut commands: Commands,
+    heroes: Query<(&Health, Has<BarkedLowHealth>), With<PartySlot>>,
+) {
+    let hero = change.target();
+    let Ok((health, barked)) = heroes.get(hero) else {
+        return;
+    };
+
+    match (is_low_health(health), barked) {
+        (true, false) => {
+            commands.entity(hero).insert(BarkedLowHealth);
+            commands.trigger(Bark {
+                context: BarkContext::LowHealth,
+                speaker: Some(hero),
+            });
+        }
+        (false, true) => {
+            commands.entity(hero).remove::<BarkedLowHealth>();
+        }
+        _ => {}
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn spawn_bubble(
+    bark: Trigger<Bark>,
+    mut commands: Commands,
+    style: Res<Style>,
+    pool: Res<BarkPool>,
+    frequency: Res<BarkFrequency>,
+    room: Option<Single<&RoomInfo, With<CurrentRoom>>>,
+    party: Query<(Entity, &PartySlot, &Health)>,
+    bubbles: Query<(Entity, &SpeechBubble)>,
+) {
+    let Some(room) = room else {
+        return;
+    };
+    let mut heroes: Vec<(Entity, PartySlot)> = party
+        .iter()
+        .filter(|(_, _, health)| health.is_alive())
+        .map(|(entity, slot, _)| (entity, *slot))
+        .collect();
+    heroes.sort_by_key(|(_, slot)| *slot);
+
+    let speaker = bark
+        .speaker
+        .and_then(|speaker| heroes.iter().find(|(hero, _)| *hero == speaker).copied());
+    let mut rng = bark_rng(room.rng_seed, bark.context, speaker.map(|(_, slot)| slot));
+    if !rng.random_bool(frequency.chance()) {
+        return;
+    }
+
+    let Some((speaker, _)) = speaker.or_else(|| match heroes.len() {
+        0 => None,
+        len => Some(heroes[rng.random_range(0..len)]),
+    }) else {
+        return;
+    };
+    let Some(line) = pool.pick(bark.context, &mut rng) else {
+        return;
+    };
+
+    for (entity, bubble) in bubbles.iter() {
+        if bubble.speaker == speaker {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    let size = bubble_size(line);
+    commands.spawn((
+        SpeechBubble {
+            speaker,
+            timer: Timer::from_seconds(BUBBLE_SECONDS, TimerMode::Once),
+        },
+        Sprite::from_color(style.background_color, size),
+        Transform::default(),
+        Visibility::Hidden,
+        Pickable::IGNORE,
+        StateScoped(AppState::Game),
+        children![(
+            Text2d::new(line),
+            style.font(BUBBLE_FONT_SIZE),
+            TextColor(style.text_color),
+            TextLayout::new_with_justify(JustifyText::Center),
+            TextBounds::new_horizontal(BUBBLE_WIDTH - 2.0 * BUBBLE_PADDING),
+            Transform::from_xyz(0.0, 0.0, 0.1),
+            Pickable::IGNORE,
+        )],
+    ));
+}
+
+fn follow_speakers(
+    mut commands: Commands,
+    time: Res<Time>,
+    speakers: Query<(&GlobalTransform, &Health)>,
+    mut bubbles: Query<(Entity, &mut SpeechBubble, &mut Transform, &mut Visibility)>,
+) {
+    for (entity, mut bubble, mut transform, mut visibility) in bubbles.iter_mut() {
+        bubble.timer.tick(time.delta());
+        let speaker = speakers
+            .get(bubble.speaker)
+            .ok()
+            .filter(|(_, health)| health.is_alive());
+        let Some((speaker, _)) = speaker.filter(|_| !bubble.timer.finished()) else {
+            commands.entity(entity).despawn();
+            continue;
+        };
+
+        transform.translation =
+            speaker.translation().with_z(BUBBLE_LAYER) + Vec3::Y * BUBBLE_HEIGHT;
+        *visibility = Visibility::Inherited;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn every_context_has_lines_picked_by_the_room() {
+        let pool: BarkPool = ron::from_str(BARKS_RON).unwrap();
+        for context in [
+            BarkContext::LowHealth,
+            BarkContext::PillarFound,
+            BarkContext::DarkRoom,
+        ] {
+            assert!(!pool.lines(context).is_empty(), "no lines for {context:?}");
+
+            let pick = |seed| {
+                pool.pick(context, &mut bark_rng(seed, context, None))
+                    .map(str::to_string)
+            };
+            assert_eq!(pick(42), pick(42));
+            assert!((0..20).any(|seed| pick(seed) != pick(42)));
+
+            for line in pool.lines(context) {
+                let line_width = line.chars().count() as f32 * BUBBLE_CHAR_WIDTH;
+                let fits = BUBBLE_MAX_LINES * (BUBBLE_WIDTH - 2.0 * BUBBLE_PADDING);
+                assert!(line_width <= fits, "{line:?} doesn't fit in a bubble");
+                assert!(bubble_size(line).x <= BUBBLE_WIDTH);
+            }
+        }
+    }
+}
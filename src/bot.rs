@@ -0,0 +1,368 @@
+//! A bot that plays the game on its own, from the main menu to the victory screen.
+//! Enabled with the `--bot` flag, and used as an end to end regression test.
+
+use crate::game::{
+    ActingActor, ActingActorAction, Action, CombatState, Energy, ExitPrompt, GameState, HexPuzzle,
+    TurnOrder, create_attack_menu, go_through_door,
+};
+use crate::menu::MenuState;
+use crate::menu::new_game::{NewGameState, start_generating_world};
+use crate::prelude::*;
+use crate::room::{CurrentRoom, EntranceDirection};
+use bevy::prelude::*;
+use bevy_ecs_tilemap::helpers::hex_grid::neighbors::HexNeighbors;
+use bevy_ecs_tilemap::prelude::*;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+pub const BOT_SEED: u64 = 0xb07_5eed;
+const BOT_MAX_TURNS: u32 = 2000;
+const BOT_TIME_SCALE: f32 = 100.0;
+/// The health fraction under which the priestess heals instead of attacking.
+const BOT_HEAL_THRESHOLD: f32 = 0.5;
+
+pub struct BotPlugin {
+    pub seed: u64,
+    pub max_turns: u32,
+    pub time_scale: f32,
+}
+
+impl Default for BotPlugin {
+    fn default() -> Self {
+        Self {
+            seed: BOT_SEED,
+            max_turns: BOT_MAX_TURNS,
+            time_scale: BOT_TIME_SCALE,
+        }
+    }
+}
+
+impl Plugin for BotPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(BotSettings {
+            seed: self.seed,
+            max_turns: self.max_turns,
+        })
+        .insert_resource(TimeScale(self.time_scale))
+        .init_resource::<BotProgress>()
+        .add_systems(OnEnter(MenuState::Main), bot_start_new_game)
+        .add_systems(OnEnter(NewGameState::Main), bot_generate_world)
+        .add_systems(
+            OnEnter(CombatState::SpawnMenu),
+            bot_choose_action.after(create_attack_menu),
+        )
+        .add_systems(OnEnter(CombatState::EndOfTurn), bot_count_turn)
+        .add_systems(OnEnter(GameState::EnterRoom), bot_count_room)
+        .add_systems(Update, bot_navigate.run_if(in_state(GameState::Navigation)))
+        .add_systems(
+            Update,
+            bot_solve_puzzle.run_if(in_state(GameState::Puzzle).and(resource_exists::<HexPuzzle>)),
+        )
+        .add_systems(OnEnter(GameState::Shrine), bot_leave_shrine)
+        .add_systems(OnEnter(GameState::Stairs), bot_leave_shrine)
+        .add_systems(OnEnter(GameState::Victory), bot_finish(true))
+        .add_systems(OnEnter(GameState::GameOver), bot_finish(false));
+    }
+}
+
+#[derive(Resource)]
+pub struct BotSettings {
+    pub seed: u64,
+    pub max_turns: u32,
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct BotProgress {
+    pub turns: u32,
+    pub rooms: u32,
+    pub won: Option<bool>,
+}
+
+fn bot_start_new_game(mut next_state: ResMut<NextState<MenuState>>) {
+    next_state.set(MenuState::NewGame);
+}
+
+fn bot_generate_world(
+    mut commands: Commands,
+    mut next_state: ResMut<NextState<NewGameState>>,
+    settings: Res<BotSettings>,
+    #[cfg(feature = "sqlite")] db: NonSend<Database>,
+) {
+    info!("Bot generating world with seed {:x}", settings.seed);
+
+    start_generating_world(
+        &mut commands,
+        &mut next_state,
+        #[cfg(feature = "sqlite")]
+        &db,
+        settings.seed,
+        Difficulty::default(),
+        GameRules::default(),
+        "",
+    );
+}
+
+/// Heals with the priestess when someone is hurt and she has the energy,
+/// otherwise attacks the weakest enemy.
+/// Skips the turn if there is no enemy left standing to attack.
+fn bot_choose_action(
+    mut commands: Commands,
+    mut next_state: ResMut<NextState<CombatState>>,
+    queue: Res<TurnOrder>,
+    active_actor: Single<(&Team, &ActorName, Option<&Energy>), With<ActingActor>>,
+    actor_q: Query<(Entity, &Health, &Team)>,
+) {
+    let (team, name, energy) = *active_actor;
+
+    let weakest = |same_team: bool| {
+        queue
+            .queue()
+            .iter()
+            .filter_map(|entity| actor_q.get(*entity).ok())
+            .filter(|(_, health, target_team)| {
+                health.is_alive() && (**target_team == *team) == same_team
+            })
+            .min_by_key(|(_, health, _)| health.current().map(|h| h.get()).unwrap_or(0))
+    };
+
+    let heal_target = (*name == ActorName::Priestess && Energy::can_afford_special(energy))
+        .then(|| weakest(true))
+        .flatten()
+        .filter(|(_, health, _)| {
+            let current = health.current().map(|h| h.get()).unwrap_or(0);
+            (current as f32) < health.max().get() as f32 * BOT_HEAL_THRESHOLD
+        });
+
+    let action = match (heal_target, weakest(false)) {
+        (Some((target, _, _)), _) => Action::SpecialAction { target },
+        (None, Some((target, _, _))) => Action::Attack { target },
+        (None, None) => Action::SkipTurn,
+    };
+
+    commands.insert_resource(ActingActorAction(action));
+    next_state.set(CombatState::PerformAction);
+}
+
+fn bot_count_turn(
+    mut progress: ResMut<BotProgress>,
+    settings: Res<BotSettings>,
+    mut exit: EventWriter<AppExit>,
+) {
+    progress.turns += 1;
+    if progress.turns > settings.max_turns {
+        error!("Bot took more than {} turns!", settings.max_turns);
+        exit.write(AppExit::error());
+    }
+}
+
+fn bot_count_room(mut progress: ResMut<BotProgress>) {
+    progress.rooms += 1;
+}
+
+/// Walks toward the closest pillar, or back to the entrance once they are all collected.
+/// There it leaves through the exit prompt, which only asks once the game can be won.
+fn bot_navigate(
+    mut commands: Commands,
+    current_room: Single<(Entity, &TilePos), With<CurrentRoom>>,
+    map: Single<(&TilemapSize, &TileStorage), With<MapTilemap>>,
+    info_q: Query<&RoomInfo>,
+    exit_prompt: Query<(), With<ExitPrompt>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let (map_size, map_storage) = *map;
+    let room_at = |pos: &TilePos| {
+        map_storage
+            .checked_get(pos)
+            .and_then(|entity| info_q.get(entity).ok())
+    };
+
+    let pillars_left = map_storage
+        .iter()
+        .flatten()
+        .filter_map(|entity| info_q.get(*entity).ok())
+        .any(|info| info.r_type == RoomType::Pillar && !info.cleared);
+
+    let is_goal = |info: &RoomInfo| match pillars_left {
+        true => info.r_type == RoomType::Pillar && !info.cleared,
+        false => info.r_type == RoomType::Entrance,
+    };
+
+    // Back at the entrance with every pillar, so answer Leave like the player would.
+    if !pillars_left && room_at(current_room.1).is_some_and(is_goal) {
+        if exit_prompt.is_empty() {
+            error!("Bot brought back every pillar, but was not asked to leave!");
+            exit.write(AppExit::error());
+        } else {
+            next_state.set(GameState::Victory);
+        }
+        return;
+    }
+
+    let Some(dir) = next_step(current_room.1, map_size, room_at, is_goal) else {
+        error!("Bot could not find a path to its goal!");
+        return;
+    };
+
+    go_through_door(
+        &mut commands,
+        *current_room,
+        map_storage,
+        dir,
+        &mut next_state,
+    );
+}
+
+/// Presses the fewest tiles that solve the puzzle.
+fn bot_solve_puzzle(mut puzzle: ResMut<HexPuzzle>, mut next_state: ResMut<NextState<GameState>>) {
+    for tile in puzzle.solution() {
+        puzzle.press(tile);
+    }
+    next_state.set(GameState::Navigation);
+}
+
+/// Walks past shrines and stairs, offerings and other floors would only make runs harder to compare.
+fn bot_leave_shrine(mut next_state: ResMut<NextState<GameState>>) {
+    next_state.set(GameState::Navigation);
+}
+
+/// How much the bot wants to avoid walking through a room.
+fn room_cost(info: &RoomInfo) -> u32 {
+    match info.r_type {
+        _ if info.cleared => 1,
+        RoomType::Combat(_) => 8,
+        RoomType::Pillar => 12,
+        RoomType::Pit(_) => 4,
+        _ => 1,
+    }
+}
+
+/// Finds the cheapest path to a goal room, and returns the first door to take.
+fn next_step<'a>(
+    start: &TilePos,
+    map_size: &TilemapSize,
+    room_at: impl Fn(&TilePos) -> Option<&'a RoomInfo>,
+    is_goal: impl Fn(&RoomInfo) -> bool,
+) -> Option<EntranceDirection> {
+    let key = |pos: &TilePos| (pos.x, pos.y);
+
+    let mut first_step: HashMap<(u32, u32), EntranceDirection> = HashMap::new();
+    let mut cost: HashMap<(u32, u32), u32> = HashMap::from([(key(start), 0)]);
+    let mut frontier = BinaryHeap::from([Reverse((0, start.x, start.y))]);
+
+    while let Some(Reverse((dist, x, y))) = frontier.pop() {
+        let pos = TilePos { x, y };
+        if cost.get(&key(&pos)).is_some_and(|c| *c < dist) {
+            continue;
+        }
+
+        if pos != *start && room_at(&pos).is_some_and(&is_goal) {
+            return first_step.get(&key(&pos)).copied();
+        }
+
+        let neighbors = HexNeighbors::<TilePos>::get_neighboring_positions_standard(&pos, map_size);
+        for (neighbor, dir) in neighbors.iter().zip(EntranceDirection::ALL) {
+            let Some(info) = room_at(neighbor) else {
+                continue;
+            };
+
+            let next = dist + room_cost(info);
+            if cost.get(&key(neighbor)).is_some_and(|c| *c <= next) {
+                continue;
+            }
+
+            cost.insert(key(neighbor), next);
+            let step = first_step.get(&key(&pos)).copied().unwrap_or(dir);
+            first_step.insert(key(neighbor), step);
+            frontier.push(Reverse((next, neighbor.x, neighbor.y)));
+        }
+    }
+
+    None
+}
+
+fn bot_finish(won: bool) -> impl Fn(ResMut<BotProgress>, EventWriter<AppExit>) {
+    move |mut progress, mut exit| {
+        progress.won = Some(won);
+        if won {
+            info!(
+                "Bot won after {} turns in {} rooms",
+                progress.turns, progress.rooms
+            );
+            exit.write(AppExit::Success);
+        } else {
+            error!(
+                "Bot lost after {} turns in {} rooms",
+                progress.turns, progress.rooms
+            );
+            exit.write(AppExit::error());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::add_game_plugins;
+    use bevy::app::PluginsState;
+    use bevy::log::LogPlugin;
+    use bevy::render::RenderPlugin;
+    use bevy::render::settings::WgpuSettings;
+    use bevy::time::TimeUpdateStrategy;
+    use bevy::window::ExitCondition;
+    use bevy::winit::WinitPlugin;
+    use std::time::Duration;
+
+    const MAX_FRAMES: usize = 200_000;
+
+    #[test]
+    pub fn plays_to_victory() {
+        let mut app = App::new();
+
+        app.add_plugins(
+            DefaultPlugins
+                .set(RenderPlugin {
+                    render_creation: WgpuSettings {
+                        backends: None,
+                        ..default()
+                    }
+                    .into(),
+                    ..default()
+                })
+                .set(WindowPlugin {
+                    primary_window: None,
+                    exit_condition: ExitCondition::DontExit,
+                    close_when_requested: false,
+                })
+                .disable::<WinitPlugin>()
+                .disable::<LogPlugin>(),
+        );
+
+        app.insert_non_send_resource(Database::open_in_memory().unwrap())
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+                1.0 / 60.0,
+            )));
+
+        add_game_plugins(&mut app);
+        app.add_plugins(BotPlugin::default());
+
+        while app.plugins_state() == PluginsState::Adding {
+            bevy::tasks::tick_global_task_pools_on_main_thread();
+        }
+        app.finish();
+        app.cleanup();
+
+        for _ in 0..MAX_FRAMES {
+            app.update();
+
+            if let Some(exit) = app.should_exit() {
+                let progress = app.world().resource::<BotProgress>();
+                assert_eq!(progress.won, Some(true), "Bot did not win: {progress:?}");
+                assert_eq!(exit, AppExit::Success);
+                return;
+            }
+        }
+
+        panic!("Bot did not finish within {MAX_FRAMES} frames");
+    }
+}
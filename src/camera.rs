@@ -1,20 +1,71 @@
+use crate::game::{GameState, PauseState};
 use crate::generate_map::WORLD_MAP_ORIGIN;
+use crate::prelude::*;
+use crate::room::EntranceDirection;
+use crate::settings::{SETTINGS_DB_TABLE, setting_settled};
+use bevy::input::mouse::AccumulatedMouseMotion;
 use bevy::prelude::*;
 use bevy::render::{
     camera::RenderTarget,
     render_asset::RenderAssetUsages,
     render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages},
 };
+use bevy::window::PrimaryWindow;
+use serde::{Deserialize, Serialize};
+use std::num::NonZero;
+use strum::Display;
 
 pub const CAMERA_DEFAULT_SCALE: f32 = 1.00;
 pub const CAMERA_MAP_SCALE: f32 = 2.0;
+/// How far behind the party the camera starts when they walk into a room,
+/// so it slides in after them from the room they left.
+const ROOM_SLIDE_DISTANCE: f32 = 320.0;
+/// Close enough to the room's center to stop sliding.
+const ROOM_SLIDE_SNAP: f32 = 0.5;
+/// How far a full strength shake throws the camera.
+const SHAKE_MAX_OFFSET: f32 = 14.0;
+/// Even a scratch gives the camera a little nudge.
+const SHAKE_MIN_STRENGTH: f32 = 0.3;
+/// How much of a full strength shake dies down each second.
+const SHAKE_DECAY: f32 = 2.5;
+/// How far the camera can be panned away from the room's center.
+const PAN_LIMIT: f32 = 320.0;
+/// How fast edge panning moves the camera at the slowest and fastest speed settings, per second.
+const PAN_SPEEDS: std::ops::RangeInclusive<f32> = 150.0..=900.0;
+/// The least and most of the window that can be left out of edge panning.
+const DEAD_ZONES: std::ops::RangeInclusive<f32> = 0.5..=0.98;
 
 /// The plugin to enable the camera
 pub struct CameraPlugin;
 
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, camera_setup);
+        app.add_systems(PreStartup, (setup_camera_smoothing, setup_camera_panning))
+            .add_systems(Startup, camera_setup)
+            .add_systems(
+                Update,
+                camera_smoothing_sync.run_if(
+                    resource_changed::<CameraSmoothing>.and(not(resource_added::<CameraSmoothing>)),
+                ),
+            )
+            .add_systems(
+                Update,
+                camera_panning_sync.run_if(setting_settled::<CameraPanning>),
+            )
+            .add_systems(
+                OnEnter(GameState::EnterRoom),
+                (slide_into_room, reset_camera_pan),
+            )
+            .add_systems(
+                Update,
+                (edge_pan_camera, drag_pan_camera)
+                    .before(follow_room)
+                    .run_if(in_state(PauseState::Running)),
+            )
+            .add_systems(Update, follow_room.run_if(in_state(AppState::Game)))
+            .add_systems(OnExit(AppState::Game), center_camera)
+            .register_effect::<CameraShake>()
+            .add_observer(shake_camera);
     }
 }
 
@@ -26,11 +77,347 @@ pub struct MainCameraMarker;
 #[derive(Component)]
 pub struct MapCameraMarker;
 
+/// How smoothly the camera follows the party into the next room, set in the display settings.
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Display)]
+pub enum CameraSmoothing {
+    Off,
+    #[default]
+    Quick,
+    Gentle,
+}
+
+impl CameraSmoothing {
+    const DB_KEY: &str = "camera_smoothing";
+
+    /// The setting after this one, for cycling through them with a button.
+    pub fn next(self) -> Self {
+        match self {
+            CameraSmoothing::Off => CameraSmoothing::Quick,
+            CameraSmoothing::Quick => CameraSmoothing::Gentle,
+            CameraSmoothing::Gentle => CameraSmoothing::Off,
+        }
+    }
+
+    /// How fast the camera closes in on the room, as the fraction of the way
+    /// left per second it covers. `None` to cut straight to it.
+    pub fn rate(self) -> Option<f32> {
+        match self {
+            CameraSmoothing::Off => None,
+            CameraSmoothing::Quick => Some(12.0),
+            CameraSmoothing::Gentle => Some(5.0),
+        }
+    }
+}
+
+/// How the camera can be moved around the room, set in the display settings.
+/// Dragging is done with [`Control::PanCamera`], so its button is set in the controls.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CameraPanning {
+    /// Whether holding the cursor near the edge of the window pans the camera that way.
+    pub edge_pan: bool,
+    /// How much of the window, across its middle, the cursor can be in without panning,
+    /// from 0 for the least to 1 for the most. See [`DEAD_ZONES`].
+    pub dead_zone: f32,
+    /// How fast edge panning moves the camera, from 0 to 1.
+    pub speed: f32,
+}
+
+impl Default for CameraPanning {
+    fn default() -> Self {
+        Self {
+            edge_pan: false,
+            dead_zone: 0.5,
+            speed: 0.5,
+        }
+    }
+}
+
+impl CameraPanning {
+    const DB_KEY: &str = "camera_panning";
+
+    /// How far edge panning moves the camera each second.
+    pub fn pixels_per_second(&self) -> f32 {
+        along(&PAN_SPEEDS, self.speed)
+    }
+
+    /// Which way and how hard the cursor at `cursor` pans the camera, in a window of `size`.
+    /// Each axis goes from -1 to 1, growing the further past the dead zone the cursor is.
+    pub fn edge_direction(&self, cursor: Vec2, size: Vec2) -> Vec2 {
+        let dead_zone = along(&DEAD_ZONES, self.dead_zone);
+        // From -1 to 1 across the window, with up being positive like in the world.
+        let centered = (cursor / size.max(Vec2::ONE)) * 2.0 - 1.0;
+        let centered = Vec2::new(centered.x, -centered.y);
+
+        let push = |along: f32| {
+            let past = (along.abs() - dead_zone).max(0.0) / (1.0 - dead_zone);
+            past.min(1.0) * along.signum()
+        };
+        Vec2::new(push(centered.x), push(centered.y))
+    }
+}
+
+/// The value `fraction` of the way through `range`.
+fn along(range: &std::ops::RangeInclusive<f32>, fraction: f32) -> f32 {
+    range.start() + (range.end() - range.start()) * fraction.clamp(0.0, 1.0)
+}
+
+/// How far the player has moved the camera away from the room's center.
+#[derive(Component, Default, Debug, Clone, Copy, Deref, DerefMut)]
+pub struct CameraPan(pub Vec2);
+
+impl CameraPan {
+    /// Moves the camera by `by`, keeping it near the room.
+    pub fn nudge(&mut self, by: Vec2) {
+        self.0 = (self.0 + by).clamp_length_max(PAN_LIMIT);
+    }
+}
+
+/// The door the party last went through, so the camera can follow them into the next room.
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct RoomApproach(pub EntranceDirection);
+
+fn setup_camera_smoothing(mut commands: Commands, database: NonSend<Database>) {
+    let smoothing = database.get_kv(
+        SETTINGS_DB_TABLE,
+        CameraSmoothing::DB_KEY,
+        CameraSmoothing::default(),
+    );
+    commands.insert_resource(smoothing);
+}
+
+fn setup_camera_panning(mut commands: Commands, database: NonSend<Database>) {
+    let panning = database.get_kv(
+        SETTINGS_DB_TABLE,
+        CameraPanning::DB_KEY,
+        CameraPanning::default(),
+    );
+    commands.insert_resource(panning);
+}
+
+fn camera_panning_sync(database: NonSend<Database>, panning: Res<CameraPanning>) -> Result {
+    database
+        .set_kv(SETTINGS_DB_TABLE, CameraPanning::DB_KEY, *panning)
+        .map_err(GameError::from)?;
+    Ok(())
+}
+
+fn camera_smoothing_sync(database: NonSend<Database>, smoothing: Res<CameraSmoothing>) -> Result {
+    database
+        .set_kv(SETTINGS_DB_TABLE, CameraSmoothing::DB_KEY, *smoothing)
+        .map_err(GameError::from)?;
+    Ok(())
+}
+
+/// Where the camera is after following the room's center for `delta` seconds.
+pub fn approach(current: Vec2, target: Vec2, rate: f32, delta: f32) -> Vec2 {
+    let next = target + (current - target) * (-rate * delta).exp();
+    if next.distance(target) < ROOM_SLIDE_SNAP {
+        target
+    } else {
+        next
+    }
+}
+
+/// How far the camera still is from the room's center, sliding in after the party.
+#[derive(Component, Default, Debug, Clone, Copy, Deref, DerefMut)]
+pub struct RoomSlide(pub Vec2);
+
+/// Triggered to shake the main camera, as hard as `strength` from 0 to 1.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct ShakeCamera {
+    pub strength: f32,
+}
+
+impl ShakeCamera {
+    /// A shake for an actor losing `damage` of their `max` health,
+    /// so a scratch barely moves the camera and a big hit rattles it.
+    pub fn from_damage(damage: u32, max: NonZero<u32>) -> Self {
+        let fraction = damage as f32 / max.get() as f32;
+        Self {
+            strength: (SHAKE_MIN_STRENGTH + fraction).min(1.0),
+        }
+    }
+}
+
+/// How much the main camera is shaking, dying down over time.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct CameraShake {
+    trauma: f32,
+}
+
+impl VisualEffect for CameraShake {
+    const KIND: EffectKind = EffectKind::Shake;
+}
+
+impl CameraShake {
+    /// How far the camera is thrown off at the time.
+    /// Squaring the trauma lets small shakes stay small while big ones still kick.
+    pub fn offset(&self, seconds: f32) -> Vec2 {
+        let jitter = Vec2::new(
+            (seconds * 47.0).sin() * (seconds * 13.0).cos(),
+            (seconds * 53.0).cos() * (seconds * 17.0).sin(),
+        );
+        jitter * self.trauma.powi(2) * SHAKE_MAX_OFFSET
+    }
+}
+
+/// Moves the camera back toward the room the party came from, to slide after them.
+fn slide_into_room(
+    mut commands: Commands,
+    approach: Option<Res<RoomApproach>>,
+    smoothing: Res<CameraSmoothing>,
+    mut camera: Query<&mut RoomSlide, With<MainCameraMarker>>,
+) {
+    let Some(approach) = approach else {
+        return;
+    };
+    commands.remove_resource::<RoomApproach>();
+    if smoothing.rate().is_none() {
+        return;
+    }
+
+    for mut slide in camera.iter_mut() {
+        slide.0 = -approach.0.screen_direction() * ROOM_SLIDE_DISTANCE;
+    }
+}
+
+/// Brings the camera back over the room, as each room is walked into.
+fn reset_camera_pan(mut camera: Query<&mut CameraPan, With<MainCameraMarker>>) {
+    for mut pan in camera.iter_mut() {
+        pan.0 = Vec2::ZERO;
+    }
+}
+
+/// Pans the camera toward whichever edge of the window the cursor is held near.
+fn edge_pan_camera(
+    time: Res<Time>,
+    panning: Res<CameraPanning>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    mut camera: Query<&mut CameraPan, With<MainCameraMarker>>,
+) {
+    if !panning.edge_pan {
+        return;
+    }
+    let Some((cursor, size)) = window
+        .single()
+        .ok()
+        .and_then(|window| Some((window.cursor_position()?, window.size())))
+    else {
+        return;
+    };
+
+    let direction = panning.edge_direction(cursor, size);
+    if direction == Vec2::ZERO {
+        return;
+    }
+    for mut pan in camera.iter_mut() {
+        pan.nudge(direction * panning.pixels_per_second() * time.delta_secs());
+    }
+}
+
+/// Drags the camera along with the cursor while [`Control::PanCamera`] is held.
+fn drag_pan_camera(
+    key: Res<ControlState>,
+    motion: Res<AccumulatedMouseMotion>,
+    mut camera: Query<(&mut CameraPan, &Projection), With<MainCameraMarker>>,
+) {
+    if !key.pressed(Control::PanCamera) || motion.delta == Vec2::ZERO {
+        return;
+    }
+
+    for (mut pan, projection) in camera.iter_mut() {
+        let scale = match projection {
+            Projection::Orthographic(ortho) => ortho.scale,
+            _ => 1.0,
+        };
+        // The cursor moves down the screen as y grows, the other way to the world.
+        pan.nudge(Vec2::new(-motion.delta.x, motion.delta.y) * scale);
+    }
+}
+
+fn shake_camera(
+    shake: Trigger<ShakeCamera>,
+    mut commands: Commands,
+    budget: EffectsBudget,
+    camera: Query<(Entity, Option<&CameraShake>), With<MainCameraMarker>>,
+) {
+    let Some(budgeted) = budget.allow(EffectKind::Shake) else {
+        return;
+    };
+
+    for (entity, current) in camera.iter() {
+        let trauma = shake.strength.clamp(0.0, 1.0) * budgeted.scale();
+        let trauma = current.map_or(trauma, |current| current.trauma.max(trauma));
+        commands
+            .entity(entity)
+            .insert((CameraShake { trauma }, budgeted));
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn follow_room(
+    mut commands: Commands,
+    time: Res<Time>,
+    real_time: Res<Time<Real>>,
+    smoothing: Res<CameraSmoothing>,
+    mut camera: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut RoomSlide,
+            &CameraPan,
+            Option<&mut CameraShake>,
+        ),
+        With<MainCameraMarker>,
+    >,
+) {
+    for (entity, mut transform, mut slide, pan, shake) in camera.iter_mut() {
+        if slide.0 != Vec2::ZERO {
+            slide.0 = smoothing.rate().map_or(Vec2::ZERO, |rate| {
+                approach(slide.0, Vec2::ZERO, rate, time.delta_secs())
+            });
+        }
+
+        // Shakes run on real time, so they still play out while a hit-stop holds the game.
+        let mut offset = Vec2::ZERO;
+        if let Some(mut shake) = shake {
+            offset = shake.offset(real_time.elapsed_secs());
+            shake.trauma -= SHAKE_DECAY * real_time.delta_secs();
+            if shake.trauma <= 0.0 {
+                commands.entity(entity).remove::<(CameraShake, Budgeted)>();
+            }
+        }
+
+        let translation = slide.0 + pan.0 + offset;
+        if transform.translation.truncate() != translation {
+            transform.translation = translation.extend(transform.translation.z);
+        }
+    }
+}
+
+fn center_camera(
+    mut commands: Commands,
+    mut camera: Query<
+        (Entity, &mut Transform, &mut RoomSlide, &mut CameraPan),
+        With<MainCameraMarker>,
+    >,
+) {
+    for (entity, mut transform, mut slide, mut pan) in camera.iter_mut() {
+        slide.0 = Vec2::ZERO;
+        pan.0 = Vec2::ZERO;
+        transform.translation = Vec3::ZERO.with_z(transform.translation.z);
+        commands.entity(entity).remove::<(CameraShake, Budgeted)>();
+    }
+}
+
 /// Sets up the main camera and it's settings
 fn camera_setup(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
     commands.spawn((
         MainCameraMarker,
         Camera2d,
+        RoomSlide::default(),
+        CameraPan::default(),
         Projection::Orthographic(OrthographicProjection {
             scaling_mode: bevy::render::camera::ScalingMode::WindowSize,
             ..OrthographicProjection::default_2d()
@@ -68,6 +455,7 @@ fn camera_setup(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
             justify_self: JustifySelf::End,
             ..default()
         },
+        HudPart(HudElement::Minimap),
     ));
 
     commands.spawn((
@@ -86,3 +474,91 @@ fn camera_setup(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
         Transform::from_translation(WORLD_MAP_ORIGIN),
     ));
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn the_camera_settles_on_the_room() {
+        let start = -EntranceDirection::North.screen_direction() * ROOM_SLIDE_DISTANCE;
+        let rate = CameraSmoothing::Quick.rate().unwrap();
+
+        let mut position = start;
+        let mut frames = 0;
+        while position != Vec2::ZERO {
+            let next = approach(position, Vec2::ZERO, rate, 1.0 / 60.0);
+            assert!(next.length() < position.length());
+            position = next;
+            frames += 1;
+        }
+        assert!((10..60).contains(&frames), "took {frames} frames");
+
+        let gentle = approach(
+            start,
+            Vec2::ZERO,
+            CameraSmoothing::Gentle.rate().unwrap(),
+            0.1,
+        );
+        let quick = approach(start, Vec2::ZERO, rate, 0.1);
+        assert!(gentle.length() > quick.length());
+    }
+
+    #[test]
+    fn bigger_hits_shake_harder() {
+        let max = NonZero::new(40).unwrap();
+        let scratch = ShakeCamera::from_damage(1, max).strength;
+        let big_hit = ShakeCamera::from_damage(30, max).strength;
+        assert!(scratch > 0.0 && scratch < big_hit);
+        assert_eq!(ShakeCamera::from_damage(100, max).strength, 1.0);
+
+        let offset = |trauma: f32| {
+            let shake = CameraShake { trauma };
+            (0..30)
+                .map(|step| shake.offset(step as f32 * 0.05).length())
+                .fold(0.0, f32::max)
+        };
+        assert!(offset(scratch) < offset(big_hit));
+        assert!(offset(1.0) <= SHAKE_MAX_OFFSET * 2.0_f32.sqrt());
+    }
+
+    #[test]
+    fn the_cursor_pans_past_the_dead_zone() {
+        let size = Vec2::new(800.0, 600.0);
+        let panning = CameraPanning {
+            edge_pan: true,
+            dead_zone: 0.0,
+            speed: 1.0,
+        };
+        assert_eq!(panning.pixels_per_second(), *PAN_SPEEDS.end());
+
+        assert_eq!(panning.edge_direction(size / 2.0, size), Vec2::ZERO);
+        assert_eq!(
+            panning.edge_direction(Vec2::new(600.0, 300.0), size),
+            Vec2::ZERO
+        );
+        assert_eq!(
+            panning.edge_direction(Vec2::ZERO, size),
+            Vec2::new(-1.0, 1.0)
+        );
+        assert_eq!(
+            panning.edge_direction(Vec2::new(800.0, 300.0), size),
+            Vec2::X
+        );
+
+        let halfway = panning.edge_direction(Vec2::new(700.0, 300.0), size);
+        assert!(halfway.x > 0.0 && halfway.x < 1.0 && halfway.y == 0.0);
+        let wider = CameraPanning {
+            dead_zone: 1.0,
+            ..panning
+        };
+        assert_eq!(
+            wider.edge_direction(Vec2::new(700.0, 300.0), size),
+            Vec2::ZERO
+        );
+
+        let mut pan = CameraPan::default();
+        pan.nudge(Vec2::new(PAN_LIMIT * 3.0, 0.0));
+        assert_eq!(pan.0, Vec2::new(PAN_LIMIT, 0.0));
+    }
+}
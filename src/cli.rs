@@ -0,0 +1,324 @@
+//! Command line flags to jump straight into a game, for quick iteration and scripted demos.
+
+#[cfg(feature = "debug")]
+use crate::generate_map::{MapLayout, PresetLayout};
+use crate::menu::MenuState;
+use crate::menu::new_game::{NewGameState, random_seed, start_generating_world};
+use crate::prelude::*;
+use bevy::prelude::*;
+#[cfg(feature = "debug")]
+use std::path::PathBuf;
+use thiserror::Error;
+
+pub const USAGE: &str = "\
+Usage: a-hex-befalls [OPTIONS]
+
+Options:
+    --seed <HEX>       Start a new game with the world seed
+    --new-game         Start a new game with a random seed
+    --load <ID>        Load the save game with the id
+    --skip-menu        Continue the last save, or start a new game if there are none
+    --layout <PATH>    Start a new game on a layout from the map editor, in debug builds
+    --windowed <WxH>   Open the window at the given size
+    --bot              Let the bot play a new game
+    --help             Print this message";
+
+#[derive(Resource, Clone, Debug, Default, PartialEq)]
+pub struct CliArgs {
+    pub seed: Option<u64>,
+    pub new_game: bool,
+    pub load: Option<i64>,
+    pub windowed: Option<(u32, u32)>,
+    pub skip_menu: bool,
+    pub bot: bool,
+    pub help: bool,
+    /// A layout exported by the map editor to play instead of a generated map.
+    #[cfg(feature = "debug")]
+    pub layout: Option<PathBuf>,
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum CliError {
+    #[error("Unknown argument `{0}`")]
+    UnknownArgument(String),
+    #[error("Missing value for `{0}`")]
+    MissingValue(&'static str),
+    #[error("Invalid value `{value}` for `{flag}`")]
+    InvalidValue { flag: &'static str, value: String },
+    #[error("`--new-game` and `--seed` can't be used with `--load`")]
+    Conflict,
+}
+
+/// What the game should do once it gets to the main menu.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StartAction {
+    NewGame(Option<u64>),
+    Load(i64),
+    Continue,
+    /// Start a new game on the layout in [`CliArgs::layout`].
+    #[cfg(feature = "debug")]
+    PlayLayout,
+}
+
+impl CliArgs {
+    /// Parses the arguments, without the program name.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, CliError> {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            let mut value = |flag: &'static str| args.next().ok_or(CliError::MissingValue(flag));
+
+            match arg.as_str() {
+                "--seed" => {
+                    let seed = value("--seed")?;
+                    parsed.seed = Some(
+                        u64::from_str_radix(&seed, 16)
+                            .map_err(|_| invalid("--seed", seed.clone()))?,
+                    );
+                }
+                "--new-game" => parsed.new_game = true,
+                "--load" => {
+                    let id = value("--load")?;
+                    parsed.load = Some(id.parse().map_err(|_| invalid("--load", id.clone()))?);
+                }
+                "--windowed" => {
+                    let size = value("--windowed")?;
+                    parsed.windowed =
+                        Some(parse_size(&size).ok_or_else(|| invalid("--windowed", size.clone()))?);
+                }
+                "--skip-menu" => parsed.skip_menu = true,
+                #[cfg(feature = "debug")]
+                "--layout" => parsed.layout = Some(value("--layout")?.into()),
+                "--bot" => parsed.bot = true,
+                "--help" | "-h" => parsed.help = true,
+                _ => return Err(CliError::UnknownArgument(arg)),
+            }
+        }
+
+        if parsed.load.is_some() && (parsed.new_game || parsed.seed.is_some()) {
+            return Err(CliError::Conflict);
+        }
+
+        Ok(parsed)
+    }
+
+    pub fn start_action(&self) -> Option<StartAction> {
+        #[cfg(feature = "debug")]
+        if self.layout.is_some() {
+            return Some(StartAction::PlayLayout);
+        }
+
+        if let Some(id) = self.load {
+            Some(StartAction::Load(id))
+        } else if self.new_game || self.seed.is_some() {
+            Some(StartAction::NewGame(self.seed))
+        } else if self.skip_menu {
+            Some(StartAction::Continue)
+        } else {
+            None
+        }
+    }
+}
+
+fn invalid(flag: &'static str, value: String) -> CliError {
+    CliError::InvalidValue { flag, value }
+}
+
+/// Parses a window size like `1280x720`.
+fn parse_size(size: &str) -> Option<(u32, u32)> {
+    let (width, height) = size.split_once(['x', 'X'])?;
+    let size = (width.parse().ok()?, height.parse().ok()?);
+
+    (size.0 > 0 && size.1 > 0).then_some(size)
+}
+
+/// Starts or loads a game from the command line flags.
+pub struct CliPlugin(pub CliArgs);
+
+impl Plugin for CliPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.0.clone());
+
+        let Some(action) = self.0.start_action() else {
+            return;
+        };
+
+        app.insert_resource(PendingStart(action))
+            .add_systems(
+                OnEnter(MenuState::Main),
+                cli_start.run_if(resource_exists::<PendingStart>),
+            )
+            .add_systems(
+                OnEnter(NewGameState::Main),
+                cli_generate_world.run_if(resource_exists::<PendingNewGame>),
+            );
+    }
+}
+
+/// The start action that hasn't been taken yet, so it only happens once.
+#[derive(Resource)]
+struct PendingStart(StartAction);
+
+#[derive(Resource)]
+struct PendingNewGame(Option<u64>);
+
+fn cli_start(
+    mut commands: Commands,
+    start: Res<PendingStart>,
+    mut next_state: ResMut<NextState<MenuState>>,
+    #[cfg(feature = "sqlite")] db: NonSend<Database>,
+    #[cfg(any(feature = "sqlite", feature = "debug"))] mut notifications: ResMut<Notifications>,
+    #[cfg(feature = "debug")] args: Res<CliArgs>,
+) {
+    commands.remove_resource::<PendingStart>();
+
+    let seed = match start.0 {
+        StartAction::NewGame(seed) => seed,
+        #[cfg(feature = "sqlite")]
+        StartAction::Load(id) => {
+            let exists = SaveGameInfo::get_all(&db)
+                .map(|saves| saves.iter().any(|save| save.id.0 == id))
+                .unwrap_or(false);
+
+            if exists {
+                load_save(&mut commands, &db, GameID(id));
+            } else {
+                warn!("No save game with id {id}");
+                notifications.error(format!("No save game with id {id}"));
+            }
+            return;
+        }
+        #[cfg(feature = "sqlite")]
+        StartAction::Continue => {
+            let last = SaveGameInfo::get_all(&db)
+                .ok()
+                .and_then(|saves| saves.iter().max_by_key(|save| save.last_saved).cloned());
+
+            if let Some(save) = last {
+                load_save(&mut commands, &db, save.id);
+                return;
+            }
+            None
+        }
+        #[cfg(feature = "debug")]
+        StartAction::PlayLayout => {
+            let path = args.layout.clone().unwrap_or_default();
+            let layout = std::fs::read_to_string(&path)
+                .map_err(|err| err.to_string())
+                .and_then(|ron| MapLayout::from_ron(&ron).map_err(|err| err.to_string()));
+
+            match layout {
+                Ok(layout) => commands.insert_resource(PresetLayout(layout)),
+                Err(err) => {
+                    warn!("Failed to load the layout at {}: {err}", path.display());
+                    notifications.error(format!("Failed to load the layout: {err}"));
+                    return;
+                }
+            }
+            None
+        }
+        #[cfg(not(feature = "sqlite"))]
+        StartAction::Load(_) | StartAction::Continue => {
+            warn!("Saves are not supported in this build");
+            None
+        }
+    };
+
+    commands.insert_resource(PendingNewGame(seed));
+    next_state.set(MenuState::NewGame);
+}
+
+#[cfg(feature = "sqlite")]
+fn load_save(commands: &mut Commands, db: &Database, id: GameID) {
+    info!("Loading save game {}", id.0);
+
+    let save = SaveGame::load(db, id);
+    commands.insert_resource(save.difficulty);
+    commands.insert_resource(save);
+    commands.run_system_cached(crate::saving::load_game);
+}
+
+fn cli_generate_world(
+    mut commands: Commands,
+    pending: Res<PendingNewGame>,
+    mut next_state: ResMut<NextState<NewGameState>>,
+    #[cfg(feature = "sqlite")] db: NonSend<Database>,
+    #[cfg(feature = "debug")] preset: Option<Res<PresetLayout>>,
+) {
+    let seed = pending.0.unwrap_or_else(random_seed);
+    info!("Generating world with seed {seed:x}");
+
+    let rules = GameRules::default();
+    // The map has to be the size of the layout played on it.
+    #[cfg(feature = "debug")]
+    let rules = GameRules {
+        shape: preset.map_or(rules.shape, |preset| preset.0.shape),
+        ..rules
+    };
+
+    commands.remove_resource::<PendingNewGame>();
+    start_generating_world(
+        &mut commands,
+        &mut next_state,
+        #[cfg(feature = "sqlite")]
+        &db,
+        seed,
+        Difficulty::default(),
+        rules,
+        "",
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<CliArgs, CliError> {
+        CliArgs::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn parses_every_flag() {
+        let args = parse(&["--seed", "b075eed", "--windowed", "1280x720", "--skip-menu"]).unwrap();
+
+        assert_eq!(
+            args,
+            CliArgs {
+                seed: Some(0xb07_5eed),
+                windowed: Some((1280, 720)),
+                skip_menu: true,
+                ..default()
+            }
+        );
+        assert_eq!(
+            args.start_action(),
+            Some(StartAction::NewGame(Some(0xb07_5eed)))
+        );
+
+        let args = parse(&["--load", "3"]).unwrap();
+        assert_eq!(args.start_action(), Some(StartAction::Load(3)));
+        assert_eq!(parse(&[]).unwrap().start_action(), None);
+    }
+
+    #[test]
+    fn rejects_bad_arguments() {
+        assert_eq!(parse(&["--seed"]), Err(CliError::MissingValue("--seed")));
+        assert!(matches!(
+            parse(&["--windowed", "1280"]),
+            Err(CliError::InvalidValue { .. })
+        ));
+        assert!(matches!(
+            parse(&["--windowed", "0x720"]),
+            Err(CliError::InvalidValue { .. })
+        ));
+        assert_eq!(
+            parse(&["--fullscreen"]),
+            Err(CliError::UnknownArgument("--fullscreen".into()))
+        );
+        assert_eq!(
+            parse(&["--new-game", "--load", "1"]),
+            Err(CliError::Conflict)
+        );
+    }
+}
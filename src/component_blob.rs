@@ -0,0 +1,221 @@
+//! Components saved through reflection, so new ones persist without new columns in the database.
+//!
+//! Each component registered with [`PersistComponent::persist_component`] is written for every
+//! party member as RON in the `ComponentBlob` table, under its type path and the member's
+//! [`PartySlot`], and put back on them when the game is loaded.
+
+use crate::prelude::*;
+use bevy::prelude::*;
+use bevy::reflect::GetTypeRegistration;
+use bevy::reflect::serde::{TypedReflectDeserializer, TypedReflectSerializer};
+use serde::de::DeserializeSeed;
+use std::any::TypeId;
+
+pub struct ComponentBlobPlugin;
+
+impl Plugin for ComponentBlobPlugin {
+    fn build(&self, app: &mut App) {
+        // Health and Attack have their own columns in the `PlayerActor` table.
+        app.persist_component::<BlockChance>()
+            .persist_component::<StatusEffects>();
+    }
+}
+
+/// The components written to the `ComponentBlob` table, in the order they were registered.
+#[derive(Resource, Default, Debug, Clone)]
+pub struct PersistedComponents(Vec<TypeId>);
+
+pub trait PersistComponent {
+    /// Saves the component with each party member, and loads it back onto them.
+    /// It has to reflect `Component`, and `Serialize` and `Deserialize` if it has them.
+    fn persist_component<T: Component + GetTypeRegistration>(&mut self) -> &mut Self;
+}
+
+impl PersistComponent for App {
+    fn persist_component<T: Component + GetTypeRegistration>(&mut self) -> &mut Self {
+        self.register_type::<T>()
+            .init_resource::<PersistedComponents>();
+        self.world_mut()
+            .resource_mut::<PersistedComponents>()
+            .0
+            .push(TypeId::of::<T>());
+        self
+    }
+}
+
+/// Writes the persisted components of every party member.
+/// There are only a few small ones, so they are all written every save rather than when changed.
+pub fn save_component_blobs(world: &mut World) -> Result<(), DatabaseError> {
+    let (Some(persisted), Some(registry)) = (
+        world.get_resource::<PersistedComponents>(),
+        world.get_resource::<AppTypeRegistry>(),
+    ) else {
+        return Ok(());
+    };
+    let registry = registry.read();
+    let game_id = world.resource::<SaveGame>().game_id;
+    let db = world.non_send_resource::<Database>();
+
+    db.connection.execute(
+        "DELETE FROM ComponentBlob WHERE game_id = :game_id",
+        (game_id.0,),
+    )?;
+    let mut insert = db
+        .connection
+        .prepare("INSERT INTO ComponentBlob(game_id,slot,component,data) VALUES(?1, ?2, ?3, ?4)")?;
+
+    for entity in world.iter_entities() {
+        let Some(PartySlot(slot)) = entity.get::<PartySlot>() else {
+            continue;
+        };
+
+        for registration in persisted.0.iter().filter_map(|id| registry.get(*id)) {
+            let Some(component) = registration
+                .data::<ReflectComponent>()
+                .and_then(|reflect| reflect.reflect(entity))
+            else {
+                continue;
+            };
+
+            let serializer = TypedReflectSerializer::new(component.as_partial_reflect(), &registry);
+            insert.execute((
+                game_id.0,
+                *slot,
+                registration.type_info().type_path(),
+                ron::to_string(&serializer).unwrap(),
+            ))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Puts the persisted components back onto the party members they were saved with.
+/// Any that are no longer persisted, or no longer fit their type, are left out.
+pub fn load_component_blobs(world: &mut World) -> Result<(), DatabaseError> {
+    let (Some(persisted), Some(registry)) = (
+        world.get_resource::<PersistedComponents>().cloned(),
+        world.get_resource::<AppTypeRegistry>().cloned(),
+    ) else {
+        return Ok(());
+    };
+    let registry = registry.read();
+    let game_id = world.resource::<SaveGame>().game_id;
+
+    let blobs: Vec<(usize, String, String)> = world
+        .non_send_resource::<Database>()
+        .connection
+        .prepare("SELECT slot,component,data FROM ComponentBlob WHERE game_id = :game_id")?
+        .query_map((game_id.0,), |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+        })?
+        .collect::<Result<_, _>>()?;
+
+    let party: Vec<(Entity, usize)> = world
+        .iter_entities()
+        .filter_map(|entity| entity.get::<PartySlot>().map(|slot| (entity.id(), slot.0)))
+        .collect();
+
+    for (slot, component, data) in blobs {
+        let Some(registration) = registry
+            .get_with_type_path(&component)
+            .filter(|registration| persisted.0.contains(&registration.type_id()))
+        else {
+            warn!("Skipped saved component `{component}`, as it is no longer saved");
+            continue;
+        };
+        let (Some(reflect), Some((entity, _))) = (
+            registration.data::<ReflectComponent>(),
+            party.iter().find(|(_, s)| *s == slot),
+        ) else {
+            continue;
+        };
+
+        let value = ron::Deserializer::from_str(&data)
+            .map_err(|err| err.to_string())
+            .and_then(|mut deserializer| {
+                TypedReflectDeserializer::new(registration, &registry)
+                    .deserialize(&mut deserializer)
+                    .map_err(|err| err.to_string())
+            });
+        match value {
+            Ok(value) => reflect.insert(&mut world.entity_mut(*entity), &*value, &registry),
+            Err(err) => warn!("Failed to load saved component `{component}` with: {err}"),
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::num::NonZero;
+
+    fn test_world() -> World {
+        let mut world = World::new();
+        let db = Database::open_in_memory().unwrap();
+        world.insert_resource(SaveGame::new(
+            &db,
+            0xb10b,
+            Difficulty::default(),
+            GameRules::default(),
+            "",
+        ));
+        world.insert_non_send_resource(db);
+
+        let registry = AppTypeRegistry::default();
+        registry.write().register::<Health>();
+        registry.write().register::<StatusEffects>();
+        world.insert_resource(registry);
+        world.insert_resource(PersistedComponents(vec![
+            TypeId::of::<Health>(),
+            TypeId::of::<StatusEffects>(),
+        ]));
+
+        world
+    }
+
+    #[test]
+    fn components_load_back_onto_their_party_member() {
+        let mut world = test_world();
+        let mut effects = StatusEffects::default();
+        effects.apply(StatusKind::Poison, 3);
+        effects.apply(StatusKind::Shield, 2);
+        world.spawn((
+            PartySlot(1),
+            Health::with_current(NonZero::new(7), NonZero::new(40).unwrap()),
+            effects.clone(),
+        ));
+        world.spawn((PartySlot(0), Health::new(NonZero::new(10).unwrap())));
+        save_component_blobs(&mut world).unwrap();
+
+        world.clear_entities();
+        let first = world.spawn(PartySlot(0)).id();
+        let second = world.spawn(PartySlot(1)).id();
+        load_component_blobs(&mut world).unwrap();
+
+        let health = world.get::<Health>(second).unwrap();
+        assert_eq!(
+            (health.current(), health.max().get()),
+            (NonZero::new(7), 40)
+        );
+        assert_eq!(world.get::<StatusEffects>(second), Some(&effects));
+        assert_eq!(world.get::<Health>(first).unwrap().max().get(), 10);
+        assert_eq!(world.get::<StatusEffects>(first), None);
+    }
+
+    #[test]
+    fn components_no_longer_persisted_are_skipped() {
+        let mut world = test_world();
+        world.spawn((PartySlot(0), Health::new(NonZero::new(10).unwrap())));
+        save_component_blobs(&mut world).unwrap();
+
+        world.clear_entities();
+        world.insert_resource(PersistedComponents(vec![TypeId::of::<StatusEffects>()]));
+        let hero = world.spawn(PartySlot(0)).id();
+        load_component_blobs(&mut world).unwrap();
+
+        assert!(world.get::<Health>(hero).is_none());
+    }
+}
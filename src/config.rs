@@ -0,0 +1,165 @@
+//! An optional `config.ron` in the data directory that lets power users
+//! override game internals. Anything missing from the file keeps its default.
+//!
+//! Settings stored in the database, like keybinds and the style,
+//! are still set in game and always win over this file.
+
+use crate::animation::ANIMATION_FRAME_TIMER_SECONDS;
+use crate::database::get_default_db_directory;
+use crate::prelude::*;
+use bevy::prelude::*;
+use serde::Deserialize;
+use std::path::Path;
+
+pub const CONFIG_FILE_NAME: &str = "config.ron";
+
+pub struct ConfigPlugin;
+
+impl Plugin for ConfigPlugin {
+    fn build(&self, app: &mut App) {
+        let path = get_default_db_directory().join(CONFIG_FILE_NAME);
+        let (config, errors) = Config::load(&path);
+
+        app.insert_resource(config)
+            .insert_resource(ConfigErrors(errors))
+            .add_systems(Startup, (report_config_errors, apply_config));
+    }
+}
+
+/// Internals that can be overridden by `config.ron`.
+#[derive(Resource, Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// The spacing of the room tiles, in pixels.
+    pub tile_size: Vec2,
+    /// How many times faster the actors animate.
+    pub actor_speed: f32,
+    /// Mixed into the seed of every room's events, so the same
+    /// world can play out differently.
+    pub event_rng_stream: u64,
+    /// Prepare the rooms behind each door while navigating.
+    pub prefetch_rooms: bool,
+    /// Save only what changed since the last save.
+    pub incremental_saves: bool,
+    /// Pillars are taken by clicking them once their guardian is beaten,
+    /// and the guardian returns if they are left for too long.
+    /// The guardian respawn timer only applies with this on.
+    pub manual_pillar_pickup: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            tile_size: Vec2::new(TILE_SIZE.x, TILE_SIZE.y),
+            actor_speed: 1.0,
+            event_rng_stream: 0,
+            prefetch_rooms: true,
+            incremental_saves: true,
+            manual_pillar_pickup: false,
+        }
+    }
+}
+
+impl Config {
+    /// Loads the config, returning the default if there is no file.
+    /// Invalid values are replaced with their defaults and returned as errors.
+    pub fn load(path: &Path) -> (Self, Vec<String>) {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => (Self::default(), vec![]),
+            Err(err) => (
+                Self::default(),
+                vec![format!("Failed to read {}: {err}", path.display())],
+            ),
+        }
+    }
+
+    pub fn parse(text: &str) -> (Self, Vec<String>) {
+        match ron::from_str::<Self>(text) {
+            Ok(config) => config.validate(),
+            Err(err) => (
+                Self::default(),
+                vec![format!("Invalid {CONFIG_FILE_NAME}: {err}")],
+            ),
+        }
+    }
+
+    fn validate(mut self) -> (Self, Vec<String>) {
+        let default = Self::default();
+        let mut errors = vec![];
+
+        if !(self.tile_size.is_finite() && self.tile_size.min_element() > 0.0) {
+            errors.push(format!(
+                "{CONFIG_FILE_NAME}: tile_size must be positive, got {}",
+                self.tile_size
+            ));
+            self.tile_size = default.tile_size;
+        }
+
+        if !(self.actor_speed.is_finite() && self.actor_speed > 0.0) {
+            errors.push(format!(
+                "{CONFIG_FILE_NAME}: actor_speed must be positive, got {}",
+                self.actor_speed
+            ));
+            self.actor_speed = default.actor_speed;
+        }
+
+        (self, errors)
+    }
+}
+
+/// The problems found loading the config, shown once the game starts.
+#[derive(Resource)]
+struct ConfigErrors(Vec<String>);
+
+fn report_config_errors(
+    mut errors: ResMut<ConfigErrors>,
+    mut notifications: ResMut<Notifications>,
+) {
+    for error in errors.0.drain(..) {
+        warn!("{error}");
+        notifications.error(error);
+    }
+}
+
+fn apply_config(config: Res<Config>, mut frame_timer: ResMut<AnimationFrameTimer>) {
+    frame_timer.set_duration(std::time::Duration::from_secs_f32(
+        ANIMATION_FRAME_TIMER_SECONDS / config.actor_speed,
+    ));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn missing_fields_keep_defaults() {
+        let (config, errors) = Config::parse("(prefetch_rooms: false, event_rng_stream: 7)");
+
+        assert!(errors.is_empty());
+        assert_eq!(
+            config,
+            Config {
+                prefetch_rooms: false,
+                event_rng_stream: 7,
+                ..default()
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_values_fall_back() {
+        let (config, errors) = Config::parse("(tile_size: (0.0, 52.0), actor_speed: -2.0)");
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!(config, Config::default());
+    }
+
+    #[test]
+    fn unknown_fields_are_errors() {
+        let (config, errors) = Config::parse("(tile_sise: (48.0, 52.0))");
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(config, Config::default());
+    }
+}
@@ -0,0 +1,193 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+
+const CONTEXT_MENU_WIDTH: f32 = 180.0;
+const CONTEXT_MENU_Z_INDEX: i32 = 150;
+const DISABLED_TEXT_ALPHA: f32 = 0.4;
+
+pub struct ContextMenuPlugin;
+
+impl Plugin for ContextMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_observer(open_context_menu)
+            .add_systems(
+                OnExit(AppState::Menu),
+                despawn_filtered::<With<ContextMenuRoot>>,
+            )
+            .add_systems(
+                OnExit(AppState::Game),
+                despawn_filtered::<With<ContextMenuRoot>>,
+            );
+    }
+}
+
+/// Opens a menu at the cursor when the entity is right clicked.
+///
+/// When an entry is chosen, [`ContextMenuSelect`] is triggered on the entity
+/// with the entry's id, so the owner handles it with an observer.
+#[derive(Component, Clone, Default, Debug)]
+pub struct ContextMenu(pub Vec<ContextMenuItem>);
+
+impl ContextMenu {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn item(mut self, id: &'static str, label: impl Into<String>) -> Self {
+        self.0.push(ContextMenuItem {
+            id,
+            label: label.into(),
+            enabled: true,
+        });
+        self
+    }
+
+    /// Adds an entry that is shown, but cannot be chosen.
+    pub fn disabled_item(mut self, id: &'static str, label: impl Into<String>) -> Self {
+        self.0.push(ContextMenuItem {
+            id,
+            label: label.into(),
+            enabled: false,
+        });
+        self
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ContextMenuItem {
+    pub id: &'static str,
+    pub label: String,
+    pub enabled: bool,
+}
+
+/// Triggered on the owner of a [`ContextMenu`] when an entry is chosen.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct ContextMenuSelect {
+    pub id: &'static str,
+}
+
+/// The full screen backdrop of an open menu.
+/// Clicking anywhere on it closes the menu.
+#[derive(Component)]
+pub struct ContextMenuRoot;
+
+#[derive(Component)]
+struct ContextMenuEntry {
+    owner: Entity,
+    id: &'static str,
+}
+
+fn open_context_menu(
+    mut click: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    menus: Query<&ContextMenu>,
+    open: Query<Entity, With<ContextMenuRoot>>,
+    style: Res<Style>,
+) {
+    if click.button != PointerButton::Secondary {
+        return;
+    }
+
+    let owner = click.target();
+    let Ok(ContextMenu(items)) = menus.get(owner) else {
+        return;
+    };
+    click.propagate(false);
+
+    for entity in open.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let position = click.pointer_location.position;
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+            GlobalZIndex(CONTEXT_MENU_Z_INDEX),
+            ContextMenuRoot,
+        ))
+        .observe(close_on_click)
+        .with_children(|builder| {
+            builder
+                .spawn((
+                    Node {
+                        position_type: PositionType::Absolute,
+                        left: Val::Px(position.x),
+                        top: Val::Px(position.y),
+                        width: Val::Px(CONTEXT_MENU_WIDTH),
+                        flex_direction: FlexDirection::Column,
+                        padding: UiRect::all(Val::Px(4.0)),
+                        row_gap: Val::Px(2.0),
+                        ..default()
+                    },
+                    BackgroundColor(style.background_color),
+                ))
+                .with_children(|builder| {
+                    for item in items.iter() {
+                        let text_color = if item.enabled {
+                            style.text_color
+                        } else {
+                            style.text_color.with_alpha(DISABLED_TEXT_ALPHA)
+                        };
+
+                        let mut entry = builder.spawn((
+                            Node {
+                                width: Val::Percent(100.0),
+                                padding: UiRect::px(8.0, 8.0, 2.0, 2.0),
+                                ..default()
+                            },
+                            BackgroundColor(style.button_color),
+                            children![(
+                                Text::new(item.label.clone()),
+                                style.font(28.0),
+                                TextColor(text_color),
+                                Pickable::IGNORE,
+                            )],
+                        ));
+
+                        if item.enabled {
+                            entry
+                                .insert((Button, ContextMenuEntry { owner, id: item.id }))
+                                .observe(select_entry);
+                        } else {
+                            entry.observe(stop_event_propagate::<Pointer<Click>>);
+                        }
+                    }
+                });
+        });
+}
+
+fn select_entry(
+    mut click: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    entries: Query<&ContextMenuEntry>,
+    open: Query<Entity, With<ContextMenuRoot>>,
+) {
+    click.propagate(false);
+
+    if click.button != PointerButton::Primary {
+        return;
+    }
+
+    let Ok(ContextMenuEntry { owner, id }) = entries.get(click.target()) else {
+        return;
+    };
+
+    for entity in open.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    if let Ok(mut owner) = commands.get_entity(*owner) {
+        owner.trigger(ContextMenuSelect { id });
+    }
+}
+
+fn close_on_click(mut click: Trigger<Pointer<Click>>, mut commands: Commands) {
+    click.propagate(false);
+    commands.entity(click.target()).despawn();
+}
@@ -1,6 +1,8 @@
 use crate::embed_asset;
 use crate::prelude::*;
 use bevy::ecs::hierarchy::ChildSpawnerCommands;
+use bevy::input::gamepad::GamepadInput;
+use bevy::input::mouse::{AccumulatedMouseScroll, MouseScrollUnit};
 use bevy::{input::InputSystem, prelude::*};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
@@ -8,6 +10,11 @@ use std::iter::IntoIterator;
 
 const KEYBINDS_DB_TABLE: &str = "Keybinds";
 
+/// How far a gamepad axis has to be pushed before it counts as pressed.
+pub const AXIS_PRESS_THRESHOLD: f32 = 0.5;
+/// How many pixels of scrolling count as one line, for mice that scroll smoothly.
+const PIXELS_PER_SCROLL_LINE: f32 = 20.0;
+
 pub struct ControlsPlugin;
 
 impl Plugin for ControlsPlugin {
@@ -17,6 +24,8 @@ impl Plugin for ControlsPlugin {
         app.add_systems(PreStartup, setup_controls)
             .init_resource::<ControlState>()
             .init_resource::<ButtonInput<Input>>()
+            .init_resource::<AxisState>()
+            .init_resource::<LastInputDevice>()
             .add_systems(
                 PreUpdate,
                 (update_input_state, update_control_state)
@@ -25,8 +34,11 @@ impl Plugin for ControlsPlugin {
             )
             .add_systems(
                 Update,
-                controls_sync
-                    .run_if(resource_changed::<Controls>.and(not(resource_added::<Controls>))),
+                (
+                    controls_sync
+                        .run_if(resource_changed::<Controls>.and(not(resource_added::<Controls>))),
+                    update_keybind_hints,
+                ),
             );
     }
 }
@@ -57,6 +69,13 @@ impl ControlState {
         self.pressed.contains_key(&input)
     }
 
+    /// How far the `input` is pressed, or `0.0` if it isn't.
+    ///
+    /// Buttons are always fully pressed, axes are pressed as far as they are pushed.
+    pub fn value(&self, input: Control) -> f32 {
+        self.pressed.get(&input).copied().unwrap_or(0.0)
+    }
+
     /// Returns `true` if any item in `inputs` has been pressed.
     pub fn any_pressed(&self, inputs: impl IntoIterator<Item = Control>) -> bool {
         inputs.into_iter().any(|it| self.pressed(it))
@@ -174,12 +193,46 @@ impl ControlState {
     }
 }
 
+/// The kind of device the player last pressed something on.
+/// Used to show the matching glyphs for keybinds.
+#[derive(Resource, Default, Debug, Hash, PartialEq, Eq, Clone, Copy)]
+pub enum LastInputDevice {
+    #[default]
+    KeyboardMouse,
+    Gamepad,
+}
+
+impl Display for LastInputDevice {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            LastInputDevice::KeyboardMouse => write!(f, "Keyboard & Mouse"),
+            LastInputDevice::Gamepad => write!(f, "Gamepad"),
+        }
+    }
+}
+
+/// How far each axis input is pushed this frame.
+/// Only the ones pushed far enough to count as pressed are kept.
+#[derive(Resource, Default, Debug, Deref, DerefMut)]
+pub struct AxisState(HashMap<Input, f32>);
+
+impl AxisState {
+    /// Keeps the furthest push of `input`, for when multiple gamepads move the same axis.
+    fn push(&mut self, input: Input, magnitude: f32) {
+        let value = self.0.entry(input).or_default();
+        *value = value.max(magnitude);
+    }
+}
+
 /// This function isn't ideal, but I don't know if there
 /// is a better way to do it with how we need.
 fn update_input_state(
     mut input_state: ResMut<ButtonInput<Input>>,
+    mut axis_state: ResMut<AxisState>,
+    mut last_device: ResMut<LastInputDevice>,
     keyboard: Res<ButtonInput<KeyCode>>,
     mouse: Res<ButtonInput<MouseButton>>,
+    scroll: Res<AccumulatedMouseScroll>,
     gamepad: Query<&Gamepad>,
 ) {
     input_state.bypass_change_detection().clear();
@@ -209,11 +262,61 @@ fn update_input_state(
             input_state.release(Input::Gamepad(*released));
         }
     }
+
+    axis_state.clear();
+
+    let scroll_lines = match scroll.unit {
+        MouseScrollUnit::Line => scroll.delta,
+        MouseScrollUnit::Pixel => scroll.delta / PIXELS_PER_SCROLL_LINE,
+    };
+    for (axis, value) in [
+        (MouseWheelAxis::X, scroll_lines.x),
+        (MouseWheelAxis::Y, scroll_lines.y),
+    ] {
+        for direction in [AxisDirection::Positive, AxisDirection::Negative] {
+            let magnitude = direction.magnitude(value);
+            if magnitude > 0.0 {
+                axis_state.push(Input::MouseWheelAxis(axis, direction), magnitude);
+            }
+        }
+    }
+
+    for gamepad in gamepad.iter() {
+        for (input, value) in gamepad.analog().all_axes_and_values() {
+            let GamepadInput::Axis(axis) = input else {
+                continue;
+            };
+            for direction in [AxisDirection::Positive, AxisDirection::Negative] {
+                let magnitude = direction.magnitude(value);
+                if magnitude >= AXIS_PRESS_THRESHOLD {
+                    axis_state.push(Input::GamepadAxis(*axis, direction), magnitude);
+                }
+            }
+        }
+    }
+
+    // Axes stay pressed while they are pushed far enough, and are released once they fall back.
+    let fallen_back: Vec<Input> = input_state
+        .get_pressed()
+        .filter(|input| input.is_axis() && !axis_state.contains_key(input))
+        .copied()
+        .collect();
+    for input in fallen_back {
+        input_state.release(input);
+    }
+    for input in axis_state.keys() {
+        input_state.press(*input);
+    }
+
+    if let Some(device) = input_state.get_just_pressed().last().map(Input::device) {
+        last_device.set_if_neq(device);
+    }
 }
 
-fn update_control_state(
+pub(crate) fn update_control_state(
     mut control_state: ResMut<ControlState>,
     input_state: Res<ButtonInput<Input>>,
+    axis_state: Res<AxisState>,
     controls: Res<Controls>,
 ) {
     // Avoid clearing if it's not empty to ensure change detection is not triggered.
@@ -223,11 +326,16 @@ fn update_control_state(
         let keybind = keybind.into_iter().filter_map(|k| k);
 
         let pressed = input_state.any_pressed(keybind.clone());
-        let just_pressed = input_state.any_just_pressed(keybind.clone());
-        let just_released = input_state.any_just_released(keybind);
-
-        if just_pressed {
-            control_state.press(control, 1.0);
+        let just_released = input_state.any_just_released(keybind.clone());
+
+        if pressed {
+            // The value follows how far the axes are pushed while held,
+            // and is full if any button bound to it is down.
+            let value = keybind
+                .filter(|input| input_state.pressed(*input))
+                .map(|input| axis_state.get(&input).copied().unwrap_or(1.0))
+                .fold(0.0, f32::max);
+            control_state.press(control, value);
         }
 
         if just_released && !pressed {
@@ -246,6 +354,33 @@ impl Keybind {
     }
 }
 
+/// Shows the inputs bound to a control for the [`LastInputDevice`],
+/// and keeps them up to date when either changes.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct KeybindHint(pub Control);
+
+fn update_keybind_hints(
+    mut commands: Commands,
+    hints: Query<(Entity, Ref<KeybindHint>)>,
+    controls: Res<Controls>,
+    device: Res<LastInputDevice>,
+    style: Res<Style>,
+) {
+    let refresh_all = controls.is_changed() || device.is_changed();
+
+    for (entity, hint) in hints.iter() {
+        if !refresh_all && !hint.is_added() {
+            continue;
+        }
+
+        let keybind = Keybind(hint.0, controls.get_control(hint.0));
+        commands
+            .entity(entity)
+            .despawn_related::<Children>()
+            .with_children(|builder| style.display_keybind_for(builder, &keybind, *device));
+    }
+}
+
 const TEXT_COLOR: Color = Color::srgb_u8(0xe0, 0xde, 0xf4);
 
 pub fn input_to_screen(style: &Style, builder: &mut ChildSpawnerCommands, input: &Option<Input>) {
@@ -270,7 +405,7 @@ pub fn input_to_screen(style: &Style, builder: &mut ChildSpawnerCommands, input:
 /// The number of keybinds associated with a given control.
 /// When changed, the update must be in the database
 /// so that we sync all of them correctly.
-const INPUT_LIST_LEN: usize = 2;
+const INPUT_LIST_LEN: usize = 3;
 /// An individual set of inputs for a keybind
 pub type InputList = [Option<Input>; INPUT_LIST_LEN];
 
@@ -278,9 +413,25 @@ pub type InputList = [Option<Input>; INPUT_LIST_LEN];
 pub enum Input {
     Keyboard(KeyCode),
     Mouse(MouseButton),
-    MouseWheelAxis(MouseWheelAxis),
+    MouseWheelAxis(MouseWheelAxis, AxisDirection),
     Gamepad(GamepadButton),
-    GamepadAxis(GamepadAxis),
+    GamepadAxis(GamepadAxis, AxisDirection),
+}
+
+impl Input {
+    pub fn device(&self) -> LastInputDevice {
+        match self {
+            Input::Keyboard(_) | Input::Mouse(_) | Input::MouseWheelAxis(..) => {
+                LastInputDevice::KeyboardMouse
+            }
+            Input::Gamepad(_) | Input::GamepadAxis(..) => LastInputDevice::Gamepad,
+        }
+    }
+
+    /// Whether this is one way along an axis, instead of a button.
+    pub fn is_axis(&self) -> bool {
+        matches!(self, Input::MouseWheelAxis(..) | Input::GamepadAxis(..))
+    }
 }
 
 #[derive(Debug, Hash, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
@@ -289,9 +440,37 @@ pub enum MouseWheelAxis {
     Y,
 }
 
+/// Which way along an axis an input is bound,
+/// so each half of a stick can be bound to a different control.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum AxisDirection {
+    Positive,
+    Negative,
+}
+
+impl AxisDirection {
+    /// The direction the axis `value` is pushed in.
+    pub fn of(value: f32) -> Self {
+        if value < 0.0 {
+            AxisDirection::Negative
+        } else {
+            AxisDirection::Positive
+        }
+    }
+
+    /// How far the axis `value` is pushed in this direction, `0.0` if it is pushed the other way.
+    pub fn magnitude(self, value: f32) -> f32 {
+        match self {
+            AxisDirection::Positive => value.max(0.0),
+            AxisDirection::Negative => (-value).max(0.0),
+        }
+    }
+}
+
 // sometimes, you just have to do this...
 impl std::fmt::Display for Input {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        use AxisDirection as D;
         use GamepadAxis as GA;
         use GamepadButton as G;
         use Input as I;
@@ -503,8 +682,10 @@ impl std::fmt::Display for Input {
             I::Mouse(M::Back) => write!(f, "MOUSE BACK"),
             I::Mouse(M::Forward) => write!(f, "MOUSE FORWARD"),
             I::Mouse(M::Other(other)) => write!(f, "MOUSE BUTTON {}", other),
-            I::MouseWheelAxis(MA::X) => write!(f, "MOUSE WHEEL X AXIS"),
-            I::MouseWheelAxis(MA::Y) => write!(f, "MOUSE WHEEL Y AXIS"),
+            I::MouseWheelAxis(MA::X, D::Positive) => write!(f, "SCROLL RIGHT"),
+            I::MouseWheelAxis(MA::X, D::Negative) => write!(f, "SCROLL LEFT"),
+            I::MouseWheelAxis(MA::Y, D::Positive) => write!(f, "SCROLL UP"),
+            I::MouseWheelAxis(MA::Y, D::Negative) => write!(f, "SCROLL DOWN"),
             I::Gamepad(G::South) => write!(f, "GAMEPAD SOUTH"),
             I::Gamepad(G::East) => write!(f, "GAMEPAD EAST"),
             I::Gamepad(G::North) => write!(f, "GAMEPAD NORTH"),
@@ -525,13 +706,20 @@ impl std::fmt::Display for Input {
             I::Gamepad(G::DPadLeft) => write!(f, "DPAD LEFT"),
             I::Gamepad(G::DPadRight) => write!(f, "DPAD RIGHT"),
             I::Gamepad(G::Other(other)) => write!(f, "GAMEPAD BUTTON {other}"),
-            I::GamepadAxis(GA::LeftStickX) => write!(f, "GAMEPAD LEFT STICK X"),
-            I::GamepadAxis(GA::LeftStickY) => write!(f, "GAMEPAD LEFT STICK Y"),
-            I::GamepadAxis(GA::LeftZ) => write!(f, "GAMPAD LEFT STICK Z"),
-            I::GamepadAxis(GA::RightStickX) => write!(f, "GAMEPAD RIGHT STICK X"),
-            I::GamepadAxis(GA::RightStickY) => write!(f, "GAMEPAD RIGHT STICK Y"),
-            I::GamepadAxis(GA::RightZ) => write!(f, "GAMEPAD RIGHT STICK Z"),
-            I::GamepadAxis(GA::Other(other)) => write!(f, "GAMEPAD AXIS {other}"),
+            I::GamepadAxis(GA::LeftStickX, D::Positive) => write!(f, "LEFT STICK RIGHT"),
+            I::GamepadAxis(GA::LeftStickX, D::Negative) => write!(f, "LEFT STICK LEFT"),
+            I::GamepadAxis(GA::LeftStickY, D::Positive) => write!(f, "LEFT STICK UP"),
+            I::GamepadAxis(GA::LeftStickY, D::Negative) => write!(f, "LEFT STICK DOWN"),
+            I::GamepadAxis(GA::LeftZ, D::Positive) => write!(f, "LEFT STICK Z+"),
+            I::GamepadAxis(GA::LeftZ, D::Negative) => write!(f, "LEFT STICK Z-"),
+            I::GamepadAxis(GA::RightStickX, D::Positive) => write!(f, "RIGHT STICK RIGHT"),
+            I::GamepadAxis(GA::RightStickX, D::Negative) => write!(f, "RIGHT STICK LEFT"),
+            I::GamepadAxis(GA::RightStickY, D::Positive) => write!(f, "RIGHT STICK UP"),
+            I::GamepadAxis(GA::RightStickY, D::Negative) => write!(f, "RIGHT STICK DOWN"),
+            I::GamepadAxis(GA::RightZ, D::Positive) => write!(f, "RIGHT STICK Z+"),
+            I::GamepadAxis(GA::RightZ, D::Negative) => write!(f, "RIGHT STICK Z-"),
+            I::GamepadAxis(GA::Other(other), D::Positive) => write!(f, "GAMEPAD AXIS {other}+"),
+            I::GamepadAxis(GA::Other(other), D::Negative) => write!(f, "GAMEPAD AXIS {other}-"),
         }
     }
 }
@@ -547,6 +735,7 @@ pub struct Controls {
     pub zoom_out: InputList,
     pub pause: InputList,
     pub select: InputList,
+    pub pan_camera: InputList,
 }
 
 impl Controls {
@@ -560,6 +749,7 @@ impl Controls {
             Control::ZoomOut => &mut self.zoom_out,
             Control::Pause => &mut self.pause,
             Control::Select => &mut self.select,
+            Control::PanCamera => &mut self.pan_camera,
         }
     }
 
@@ -573,6 +763,7 @@ impl Controls {
             Control::ZoomOut => self.zoom_out,
             Control::Pause => self.pause,
             Control::Select => self.select,
+            Control::PanCamera => self.pan_camera,
         }
     }
 
@@ -588,8 +779,8 @@ impl Controls {
         self.get_control_mut(control)[entry] = bind;
     }
 
-    pub fn reset_control(&mut self, control: Control) {
-        *self.get_control_mut(control) = match control {
+    pub fn default_control(control: Control) -> InputList {
+        match control {
             Control::MoveUp => DEFAULT_UP_CONTROLS,
             Control::MoveDown => DEFAULT_DOWN_CONTROLS,
             Control::MoveLeft => DEFAULT_LEFT_CONTROLS,
@@ -598,28 +789,51 @@ impl Controls {
             Control::ZoomOut => DEFAULT_ZOOM_OUT_CONTROLS,
             Control::Pause => DEFAULT_PAUSE_CONTROLS,
             Control::Select => DEFAULT_SELECT_CONTROLS,
+            Control::PanCamera => DEFAULT_PAN_CAMERA_CONTROLS,
         }
     }
 
+    pub fn reset_control(&mut self, control: Control) {
+        *self.get_control_mut(control) = Self::default_control(control);
+    }
+
     pub fn reset_control_part(&mut self, control: Control, i: usize) {
         assert!(i < INPUT_LIST_LEN);
 
-        self.get_control_mut(control)[i] = match control {
-            Control::MoveUp => DEFAULT_UP_CONTROLS,
-            Control::MoveDown => DEFAULT_DOWN_CONTROLS,
-            Control::MoveLeft => DEFAULT_LEFT_CONTROLS,
-            Control::MoveRight => DEFAULT_RIGHT_CONTROLS,
-            Control::ZoomIn => DEFAULT_ZOOM_IN_CONTROLS,
-            Control::ZoomOut => DEFAULT_ZOOM_OUT_CONTROLS,
-            Control::Pause => DEFAULT_PAUSE_CONTROLS,
-            Control::Select => DEFAULT_SELECT_CONTROLS,
-        }[i];
+        self.get_control_mut(control)[i] = Self::default_control(control)[i];
     }
 
     pub fn reset_controls(&mut self) {
         *self = default();
     }
 
+    /// Whether any control can be pressed with a gamepad.
+    pub fn has_gamepad_bindings(&self) -> bool {
+        self.clone().into_iter().any(|Keybind(_, inputs)| {
+            inputs
+                .iter()
+                .flatten()
+                .any(|input| input.device() == LastInputDevice::Gamepad)
+        })
+    }
+
+    /// Puts back the default gamepad bindings, over whatever is in their slots.
+    pub fn apply_default_gamepad_bindings(&mut self) {
+        let mut control = Some(Control::default());
+        while let Some(current) = control {
+            let defaults = Self::default_control(current);
+            let inputs = self.get_control_mut(current);
+
+            for (input, default) in inputs.iter_mut().zip(defaults) {
+                if default.is_some_and(|d| d.device() == LastInputDevice::Gamepad) {
+                    *input = default;
+                }
+            }
+
+            control = current.next();
+        }
+    }
+
     // TODO: Do this in a single transaction maybe? (don't know if it matters)
     fn from_database(db: &Database) -> Self {
         Self {
@@ -631,6 +845,7 @@ impl Controls {
             zoom_out: db.get_kv(KEYBINDS_DB_TABLE, "zoom_out", DEFAULT_ZOOM_OUT_CONTROLS),
             pause: db.get_kv(KEYBINDS_DB_TABLE, "pause", DEFAULT_PAUSE_CONTROLS),
             select: db.get_kv(KEYBINDS_DB_TABLE, "select", DEFAULT_SELECT_CONTROLS),
+            pan_camera: db.get_kv(KEYBINDS_DB_TABLE, "pan_camera", DEFAULT_PAN_CAMERA_CONTROLS),
         }
     }
 
@@ -644,6 +859,7 @@ impl Controls {
         db.set_kv(KEYBINDS_DB_TABLE, "zoom_out", self.zoom_out)?;
         db.set_kv(KEYBINDS_DB_TABLE, "pause", self.pause)?;
         db.set_kv(KEYBINDS_DB_TABLE, "select", self.select)?;
+        db.set_kv(KEYBINDS_DB_TABLE, "pan_camera", self.pan_camera)?;
 
         Ok(())
     }
@@ -660,6 +876,7 @@ impl Default for Controls {
             zoom_out: DEFAULT_ZOOM_OUT_CONTROLS,
             pause: DEFAULT_PAUSE_CONTROLS,
             select: DEFAULT_SELECT_CONTROLS,
+            pan_camera: DEFAULT_PAN_CAMERA_CONTROLS,
         }
     }
 }
@@ -696,6 +913,7 @@ impl Iterator for ControlsIter {
                 Control::ZoomOut => Keybind(Control::ZoomOut, self.controls.zoom_out),
                 Control::Pause => Keybind(Control::Pause, self.controls.pause),
                 Control::Select => Keybind(Control::Select, self.controls.select),
+                Control::PanCamera => Keybind(Control::PanCamera, self.controls.pan_camera),
             };
 
             self.current = control.next();
@@ -716,6 +934,8 @@ pub enum Control {
     ZoomOut,
     Pause,
     Select,
+    /// Held to drag the camera around, see [`CameraPanning`](crate::camera::CameraPanning).
+    PanCamera,
 }
 
 impl Control {
@@ -728,7 +948,8 @@ impl Control {
             Control::ZoomIn => Some(Control::ZoomOut),
             Control::ZoomOut => Some(Control::Pause),
             Control::Pause => Some(Control::Select),
-            Control::Select => None,
+            Control::Select => Some(Control::PanCamera),
+            Control::PanCamera => None,
         }
     }
 
@@ -742,6 +963,7 @@ impl Control {
             Control::ZoomOut => "Zoom Out",
             Control::Pause => "Pause",
             Control::Select => "Select",
+            Control::PanCamera => "Pan Camera",
         }
     }
 }
@@ -756,35 +978,93 @@ impl Display for Control {
 const DEFAULT_UP_CONTROLS: InputList = [
     Some(Input::Keyboard(KeyCode::ArrowUp)),
     Some(Input::Keyboard(KeyCode::KeyW)),
+    Some(Input::Gamepad(GamepadButton::DPadUp)),
 ];
 const DEFAULT_DOWN_CONTROLS: InputList = [
     Some(Input::Keyboard(KeyCode::ArrowDown)),
     Some(Input::Keyboard(KeyCode::KeyS)),
+    Some(Input::Gamepad(GamepadButton::DPadDown)),
 ];
 const DEFAULT_LEFT_CONTROLS: InputList = [
     Some(Input::Keyboard(KeyCode::ArrowLeft)),
     Some(Input::Keyboard(KeyCode::KeyA)),
+    Some(Input::Gamepad(GamepadButton::DPadLeft)),
 ];
 const DEFAULT_RIGHT_CONTROLS: InputList = [
     Some(Input::Keyboard(KeyCode::ArrowRight)),
     Some(Input::Keyboard(KeyCode::KeyD)),
+    Some(Input::Gamepad(GamepadButton::DPadRight)),
+];
+const DEFAULT_ZOOM_IN_CONTROLS: InputList = [
+    Some(Input::Keyboard(KeyCode::Comma)),
+    None,
+    Some(Input::Gamepad(GamepadButton::RightTrigger2)),
+];
+const DEFAULT_ZOOM_OUT_CONTROLS: InputList = [
+    Some(Input::Keyboard(KeyCode::Period)),
+    None,
+    Some(Input::Gamepad(GamepadButton::LeftTrigger2)),
 ];
-const DEFAULT_ZOOM_IN_CONTROLS: InputList = [Some(Input::Keyboard(KeyCode::Comma)), None];
-const DEFAULT_ZOOM_OUT_CONTROLS: InputList = [Some(Input::Keyboard(KeyCode::Period)), None];
 const DEFAULT_PAUSE_CONTROLS: InputList = [
     Some(Input::Keyboard(KeyCode::Escape)),
     Some(Input::Keyboard(KeyCode::CapsLock)),
+    Some(Input::Gamepad(GamepadButton::Start)),
 ];
 const DEFAULT_SELECT_CONTROLS: InputList = [
     Some(Input::Mouse(MouseButton::Left)),
     Some(Input::Keyboard(KeyCode::KeyE)),
+    Some(Input::Gamepad(GamepadButton::South)),
 ];
+const DEFAULT_PAN_CAMERA_CONTROLS: InputList =
+    [Some(Input::Mouse(MouseButton::Middle)), None, None];
 
-fn controls_sync(database: NonSend<Database>, controls: Res<Controls>) {
-    match controls.to_database(&database) {
-        Ok(()) => {}
-        Err(err) => {
-            warn!("Failed to sync controls to database with: {err}");
-        }
-    };
+fn controls_sync(database: NonSend<Database>, controls: Res<Controls>) -> Result {
+    controls.to_database(&database).map_err(GameError::from)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+
+    #[test]
+    fn axes_press_controls_as_far_as_they_are_pushed() {
+        let stick_up = Input::GamepadAxis(GamepadAxis::LeftStickY, AxisDirection::Positive);
+        let mut controls = Controls::default();
+        controls.set_control(Control::MoveUp, 1, Some(stick_up));
+
+        let mut world = World::new();
+        world.insert_resource(controls);
+        world.init_resource::<ControlState>();
+        world.init_resource::<ButtonInput<Input>>();
+        world.init_resource::<AxisState>();
+
+        world.resource_mut::<AxisState>().push(stick_up, 0.6);
+        world.resource_mut::<ButtonInput<Input>>().press(stick_up);
+        world.run_system_once(update_control_state).unwrap();
+        let state = world.resource::<ControlState>();
+        assert!(state.just_pressed(Control::MoveUp));
+        assert_eq!(state.value(Control::MoveUp), 0.6);
+        assert!(!state.pressed(Control::MoveDown));
+
+        // Pushing further moves the value along without pressing it again.
+        world.resource_mut::<AxisState>().push(stick_up, 0.9);
+        world.run_system_once(update_control_state).unwrap();
+        let state = world.resource::<ControlState>();
+        assert!(!state.just_pressed(Control::MoveUp));
+        assert_eq!(state.value(Control::MoveUp), 0.9);
+
+        world.resource_mut::<AxisState>().clear();
+        world.resource_mut::<ButtonInput<Input>>().release(stick_up);
+        world.run_system_once(update_control_state).unwrap();
+        assert!(world.resource::<ControlState>().just_released(Control::MoveUp));
+    }
+
+    #[test]
+    fn axis_directions_split_the_axis() {
+        assert_eq!(AxisDirection::of(-0.3), AxisDirection::Negative);
+        assert_eq!(AxisDirection::Negative.magnitude(-0.3), 0.3);
+        assert_eq!(AxisDirection::Positive.magnitude(-0.3), 0.0);
+    }
 }
@@ -0,0 +1,205 @@
+//! Numbers that float up from actors in combat, showing the damage, healing, misses and blocks,
+//! and the loot that floats up out of opened chests.
+
+use crate::camera::ShakeCamera;
+use crate::game::GameState;
+use crate::prelude::*;
+use bevy::prelude::*;
+use std::num::NonZero;
+
+/// How long a popup floats for.
+const POPUP_SECONDS: f32 = 1.0;
+/// How far a popup floats up over its life.
+const POPUP_RISE: f32 = 40.0;
+/// Where a popup starts, above the actor's center.
+const POPUP_HEIGHT: f32 = 50.0;
+/// How far apart popups on the same actor at the same time are.
+const POPUP_STACK_SPACING: f32 = 22.0;
+const POPUP_LAYER: f32 = 10.0;
+const DAMAGE_COLOR: Color = Color::srgb(0.95, 0.3, 0.25);
+const HEAL_COLOR: Color = Color::srgb(0.4, 0.85, 0.4);
+const GOLD_COLOR: Color = Color::srgb(0.95, 0.8, 0.3);
+
+pub struct DamagePopupPlugin;
+
+impl Plugin for DamagePopupPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, float_popups.run_if(in_state(AppState::Game)))
+            .add_observer(track_shown_health)
+            .add_observer(popup_health_change)
+            .add_observer(spawn_popup);
+    }
+}
+
+/// Triggered on an actor to float the outcome of an action above them.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DamagePopup {
+    Damage(u32),
+    Heal(u32),
+    Miss,
+    Blocked,
+    Gold(u32),
+    Loot(Item),
+}
+
+impl DamagePopup {
+    pub fn text(&self) -> String {
+        match self {
+            DamagePopup::Damage(amount) => format!("-{amount}"),
+            DamagePopup::Heal(amount) => format!("+{amount}"),
+            DamagePopup::Miss => "MISS".to_string(),
+            DamagePopup::Blocked => "BLOCKED".to_string(),
+            DamagePopup::Gold(amount) => format!("+{amount} gold"),
+            DamagePopup::Loot(item) => item.to_string(),
+        }
+    }
+
+    fn color(&self, style: &Style) -> Color {
+        match self {
+            DamagePopup::Damage(_) => DAMAGE_COLOR,
+            DamagePopup::Heal(_) => HEAL_COLOR,
+            DamagePopup::Miss | DamagePopup::Blocked => style.text_color,
+            DamagePopup::Gold(_) => GOLD_COLOR,
+            DamagePopup::Loot(item) => item.color(),
+        }
+    }
+}
+
+/// The health an actor's last popup was for, to work out how much the next one changed it by.
+#[derive(Component, Debug, Clone, Copy)]
+struct ShownHealth(u32);
+
+#[derive(Component)]
+struct FloatingPopup {
+    /// Who the popup is over, so popups on the same actor don't overlap.
+    target: Entity,
+    timer: Timer,
+    start: Vec3,
+}
+
+/// How far a popup has floated up and how visible it is, `progress` of the way through its life.
+fn popup_motion(progress: f32) -> (f32, f32) {
+    let progress = progress.clamp(0.0, 1.0);
+    // Slows down as it rises, and only starts fading halfway.
+    let rise = POPUP_RISE * (1.0 - (1.0 - progress).powi(2));
+    let alpha = (2.0 - 2.0 * progress).min(1.0);
+    (rise, alpha)
+}
+
+fn current_health(health: &Health) -> u32 {
+    health.current().map_or(0, NonZero::get)
+}
+
+fn track_shown_health(
+    added: Trigger<OnAdd, Health>,
+    mut commands: Commands,
+    health: Query<&Health>,
+) {
+    if let Ok(health) = health.get(added.target()) {
+        commands
+            .entity(added.target())
+            .insert(ShownHealth(current_health(health)));
+    }
+}
+
+/// Shows how much damage or healing each change in health was, while in combat.
+fn popup_health_change(
+    change: Trigger<HealthChange>,
+    mut commands: Commands,
+    mut actors: Query<(&Health, &mut ShownHealth)>,
+    game_state: Option<Res<State<GameState>>>,
+) {
+    let Ok((health, mut shown)) = actors.get_mut(change.target()) else {
+        return;
+    };
+
+    let current = current_health(health);
+    let before = std::mem::replace(&mut shown.0, current);
+    // Pits hurt outside of combat, so the camera shakes for any damage.
+    if current < before {
+        commands.trigger(ShakeCamera::from_damage(before - current, health.max()));
+    }
+    if game_state.is_none_or(|state| *state.get() != GameState::Combat) {
+        return;
+    }
+
+    let popup = match current.cmp(&before) {
+        std::cmp::Ordering::Less => DamagePopup::Damage(before - current),
+        std::cmp::Ordering::Greater => DamagePopup::Heal(current - before),
+        std::cmp::Ordering::Equal => return,
+    };
+    commands.trigger_targets(popup, change.target());
+}
+
+fn spawn_popup(
+    popup: Trigger<DamagePopup>,
+    mut commands: Commands,
+    style: Res<Style>,
+    targets: Query<&GlobalTransform>,
+    popups: Query<&FloatingPopup>,
+) {
+    let target = popup.target();
+    let Ok(transform) = targets.get(target) else {
+        return;
+    };
+
+    let stacked = popups.iter().filter(|popup| popup.target == target).count();
+    let start = transform.translation().with_z(POPUP_LAYER)
+        + Vec3::Y * (POPUP_HEIGHT + POPUP_STACK_SPACING * stacked as f32);
+
+    commands.spawn((
+        FloatingPopup {
+            target,
+            timer: Timer::from_seconds(POPUP_SECONDS, TimerMode::Once),
+            start,
+        },
+        Text2d::new(popup.text()),
+        style.font(28.0),
+        TextColor(popup.color(&style)),
+        Transform::from_translation(start),
+        Pickable::IGNORE,
+        StateScoped(AppState::Game),
+    ));
+}
+
+fn float_popups(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut popups: Query<(Entity, &mut FloatingPopup, &mut Transform, &mut TextColor)>,
+) {
+    for (entity, mut popup, mut transform, mut color) in popups.iter_mut() {
+        popup.timer.tick(time.delta());
+        if popup.timer.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        let (rise, alpha) = popup_motion(popup.timer.fraction());
+        transform.translation = popup.start + Vec3::Y * rise;
+        color.0 = color.0.with_alpha(alpha);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn popups_show_what_happened() {
+        assert_eq!(DamagePopup::Damage(12).text(), "-12");
+        assert_eq!(DamagePopup::Heal(7).text(), "+7");
+        assert_eq!(DamagePopup::Blocked.text(), "BLOCKED");
+        assert_eq!(DamagePopup::Gold(30).text(), "+30 gold");
+    }
+
+    #[test]
+    fn popups_rise_then_fade() {
+        assert_eq!(popup_motion(0.0), (0.0, 1.0));
+        assert_eq!(popup_motion(0.5).1, 1.0);
+        assert_eq!(popup_motion(1.0), (POPUP_RISE, 0.0));
+
+        let (early, _) = popup_motion(0.25);
+        let (late, _) = popup_motion(0.75);
+        assert!(early > POPUP_RISE / 4.0 && late < POPUP_RISE);
+    }
+}
@@ -11,18 +11,70 @@ mod sqlite_backend;
 pub use sqlite_backend::*;
 
 use bevy::prelude::*;
-#[cfg(feature = "sqlite")]
 use std::path::PathBuf;
 
 pub struct DatabasePlugin;
 
 impl Plugin for DatabasePlugin {
     fn build(&self, app: &mut App) {
-        app.insert_non_send_resource(
-            Database::open()
-                .inspect_err(|e| error!("Failed to open database with: {e}"))
-                .unwrap(),
-        );
+        // Tests and the bot insert their own database before the plugin is added.
+        if app.world().get_non_send_resource::<Database>().is_none() {
+            app.insert_non_send_resource(
+                Database::open()
+                    .inspect_err(|e| error!("Failed to open database with: {e}"))
+                    .unwrap(),
+            );
+        }
+
+        app.init_resource::<GarbageReport>()
+            .add_systems(Startup, collect_garbage);
+    }
+}
+
+/// How many rows the last garbage collection removed.
+/// These are left behind by cancelled world generations and crashes.
+#[derive(Resource, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct GarbageReport {
+    /// Saves that never finished generating, so they can't be loaded.
+    pub unfinished_saves: usize,
+    pub party_members: usize,
+    pub rooms: usize,
+    pub items: usize,
+    pub journal_entries: usize,
+    pub run_stats: usize,
+    pub component_blobs: usize,
+}
+
+impl GarbageReport {
+    pub fn total(&self) -> usize {
+        self.unfinished_saves
+            + self.party_members
+            + self.rooms
+            + self.items
+            + self.journal_entries
+            + self.run_stats
+            + self.component_blobs
+    }
+}
+
+pub fn collect_garbage(mut report: ResMut<GarbageReport>, db: NonSend<Database>) {
+    match db.collect_garbage() {
+        Ok(removed) => {
+            if removed.total() > 0 {
+                info!(
+                    "Removed {} unfinished saves, {} party members, {} rooms, {} items, {} journal entries, {} run stats and {} saved components left without a save",
+                    removed.unfinished_saves,
+                    removed.party_members,
+                    removed.rooms,
+                    removed.items,
+                    removed.journal_entries,
+                    removed.run_stats,
+                    removed.component_blobs
+                );
+            }
+            *report = removed;
+        }
+        Err(err) => warn!("Failed to collect database garbage with: {err}"),
     }
 }
 
@@ -37,8 +89,7 @@ pub trait ToDatabase {
 
 /// Gets the default database path in the user's home directory
 /// This isn't only for sqlite, but for anything that needs it.
-#[cfg(feature = "sqlite")]
-fn get_default_db_directory() -> PathBuf {
+pub fn get_default_db_directory() -> PathBuf {
     let project_dir =
         directories::ProjectDirs::from("com", "TeamCounterSpell", "A-Hex-Befalls-The-Hexagons");
     match project_dir.as_ref().map(|d| d.config_dir()) {
@@ -14,9 +14,10 @@ use thiserror::Error;
 
 pub type Error = rusqlite::Error;
 
-type Version = i64;
+pub type Version = i64;
 
-const DB_VERSION: Version = 12;
+/// The version of the schema, which exported saves are checked against.
+pub const DB_VERSION: Version = 39;
 
 const ADD_SCHEMA: &str = formatcp!(
     "
@@ -38,6 +39,11 @@ const ADD_SCHEMA: &str = formatcp!(
         value ANY NOT NULL
     ) STRICT;
 
+    CREATE TABLE Settings(
+        key   TEXT PRIMARY KEY,
+        value ANY NOT NULL
+    ) STRICT;
+
     CREATE TABLE SaveGame(
         game_id        INTEGER PRIMARY KEY AUTOINCREMENT,
         created        TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
@@ -46,8 +52,24 @@ const ADD_SCHEMA: &str = formatcp!(
         current_room_x INTEGER DEFAULT NULL,
         current_room_y INTEGER DEFAULT NULL,
         pillar_count   INTEGER DEFAULT 0,
-        FOREIGN KEY(game_id, current_room_x, current_room_y)
-            REFERENCES RoomInfo(game_id, position_x, position_y)
+        difficulty     TEXT NOT NULL DEFAULT 'Normal',
+        name           TEXT NOT NULL DEFAULT '',
+        gold           INTEGER NOT NULL DEFAULT 0,
+        hardcore       INTEGER NOT NULL DEFAULT 0,
+        suspended_combat TEXT DEFAULT NULL,
+        corruption     TEXT DEFAULT NULL,
+        game_mode      TEXT NOT NULL DEFAULT 'Pillars',
+        challenges     TEXT NOT NULL DEFAULT '()',
+        floor          INTEGER NOT NULL DEFAULT 0,
+        generation     TEXT NOT NULL DEFAULT 'Paths',
+        map_radius     INTEGER NOT NULL DEFAULT 5,
+        pillars        INTEGER NOT NULL DEFAULT 4,
+        room_weights   TEXT NOT NULL DEFAULT '()',
+        last_autosaved TEXT DEFAULT NULL,
+        checksum       TEXT DEFAULT NULL,
+        hex_shards     INTEGER NOT NULL DEFAULT 0,
+        FOREIGN KEY(game_id, floor, current_room_x, current_room_y)
+            REFERENCES RoomInfo(game_id, floor, position_x, position_y)
             DEFERRABLE INITIALLY DEFERRED
     ) STRICT;
 
@@ -59,7 +81,10 @@ const ADD_SCHEMA: &str = formatcp!(
         attack_damage_min INTEGER NOT NULL,
         attack_damage_max INTEGER NOT NULL,
         attack_speed      INTEGER NOT NULL,
-        hit_chance        REAL NOT NULL
+        hit_chance        REAL NOT NULL,
+        display_name      TEXT NOT NULL DEFAULT '',
+        level             INTEGER NOT NULL DEFAULT 1,
+        xp                INTEGER NOT NULL DEFAULT 0
     ) STRICT;
 
     CREATE TABLE RoomInfo(
@@ -69,12 +94,66 @@ const ADD_SCHEMA: &str = formatcp!(
         cleared    INTEGER NOT NULL,
         r_type     TEXT    NOT NULL,
         rng_seed   INTEGER NOT NULL,
-        PRIMARY KEY(game_id, position_x, position_y)
+        discovered INTEGER NOT NULL DEFAULT 1,
+        floor      INTEGER NOT NULL DEFAULT 0,
+        guardian_respawn INTEGER DEFAULT NULL,
+        chest_opened     INTEGER NOT NULL DEFAULT 0,
+        PRIMARY KEY(game_id, floor, position_x, position_y)
     ) STRICT;
 
     CREATE TABLE Item(
-        game_id INTEGER NOT NULL REFERENCES SaveGame(game_id) DEFERRABLE INITIALLY DEFERRED,
-        type    Text    NOT NULL
+        game_id     INTEGER NOT NULL REFERENCES SaveGame(game_id) DEFERRABLE INITIALLY DEFERRED,
+        type        Text    NOT NULL,
+        equipped_by INTEGER DEFAULT NULL
+    ) STRICT;
+
+    CREATE TABLE JournalEntry(
+        game_id    INTEGER NOT NULL REFERENCES SaveGame(game_id) DEFERRABLE INITIALLY DEFERRED,
+        position_x INTEGER NOT NULL,
+        position_y INTEGER NOT NULL,
+        r_type     TEXT    NOT NULL,
+        loot       TEXT    NOT NULL DEFAULT '[]',
+        cleared_at TEXT    NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        floor      INTEGER NOT NULL DEFAULT 0,
+        PRIMARY KEY(game_id, floor, position_x, position_y)
+    ) STRICT;
+
+    CREATE TABLE RunStats(
+        game_id         INTEGER PRIMARY KEY REFERENCES SaveGame(game_id) DEFERRABLE INITIALLY DEFERRED,
+        rooms_explored  INTEGER NOT NULL DEFAULT 0,
+        enemies_killed  INTEGER NOT NULL DEFAULT 0,
+        damage_dealt    INTEGER NOT NULL DEFAULT 0,
+        damage_taken    INTEGER NOT NULL DEFAULT 0,
+        pits_triggered  INTEGER NOT NULL DEFAULT 0,
+        items_used      INTEGER NOT NULL DEFAULT 0,
+        turns_taken     INTEGER NOT NULL DEFAULT 0
+    ) STRICT;
+
+    CREATE TABLE ComponentBlob(
+        game_id   INTEGER NOT NULL REFERENCES SaveGame(game_id) DEFERRABLE INITIALLY DEFERRED,
+        slot      INTEGER NOT NULL,
+        component TEXT    NOT NULL,
+        data      TEXT    NOT NULL,
+        PRIMARY KEY(game_id, slot, component)
+    ) STRICT;
+
+    CREATE TABLE DailyRun(
+        game_id        INTEGER PRIMARY KEY,
+        date           TEXT    NOT NULL,
+        world_seed     INTEGER NOT NULL,
+        outcome        TEXT    NOT NULL,
+        rooms_explored INTEGER NOT NULL DEFAULT 0,
+        enemies_killed INTEGER NOT NULL DEFAULT 0,
+        damage_taken   INTEGER NOT NULL DEFAULT 0,
+        turns_taken    INTEGER NOT NULL DEFAULT 0
+    ) STRICT;
+
+    CREATE TABLE ChallengeRun(
+        game_id        INTEGER PRIMARY KEY,
+        challenge      TEXT    NOT NULL,
+        outcome        TEXT    NOT NULL,
+        rooms_explored INTEGER NOT NULL DEFAULT 0,
+        turns_taken    INTEGER NOT NULL DEFAULT 0
     ) STRICT;
 
     COMMIT;
@@ -169,6 +248,17 @@ impl Database {
         Ok(db)
     }
 
+    /// Opens a fresh database that only lives in memory,
+    /// so the user's own data is never touched.
+    pub fn open_in_memory() -> Result<Self, OpenError> {
+        let db = Self {
+            connection: Connection::open_in_memory()?,
+        };
+        db.connection.execute_batch(ADD_SCHEMA)?;
+
+        Ok(db)
+    }
+
     pub fn get_kv<T>(&self, table: &str, key: &str, default: T) -> T
     where
         T: Serialize + DeserializeOwned + Clone,
@@ -207,6 +297,36 @@ impl Database {
 
         Ok(())
     }
+
+    /// Removes saves that never finished generating,
+    /// and every row whose save no longer exists.
+    pub fn collect_garbage(&self) -> Result<GarbageReport, Error> {
+        let tx = self.connection.unchecked_transaction()?;
+
+        let unfinished_saves =
+            tx.execute("DELETE FROM SaveGame WHERE current_room_x IS NULL", ())?;
+
+        let remove_orphans = |table: &str| {
+            tx.execute(
+                &format!("DELETE FROM {table} WHERE game_id NOT IN (SELECT game_id FROM SaveGame)"),
+                (),
+            )
+        };
+
+        let report = GarbageReport {
+            unfinished_saves,
+            party_members: remove_orphans("PlayerActor")?,
+            rooms: remove_orphans("RoomInfo")?,
+            items: remove_orphans("Item")?,
+            journal_entries: remove_orphans("JournalEntry")?,
+            run_stats: remove_orphans("RunStats")?,
+            component_blobs: remove_orphans("ComponentBlob")?,
+        };
+
+        tx.commit()?;
+
+        Ok(report)
+    }
 }
 
 #[derive(Error, Debug)]
@@ -278,7 +398,7 @@ pub enum ValidateSchemaError {
     Error(#[from] Error),
 }
 
-const _: () = assert!(DB_VERSION == 12, "UPDATE VALIDATE SCRIPT");
+const _: () = assert!(DB_VERSION == 39, "UPDATE VALIDATE SCRIPT");
 fn validate_schema(db: &Database) -> Result<(), ValidateSchemaError> {
     db.connection
         .execute_batch("PRAGMA integrity_check; PRAGMA optimize; PRAGMA journal_mode=WAL;")?;
@@ -288,6 +408,7 @@ fn validate_schema(db: &Database) -> Result<(), ValidateSchemaError> {
     validate_table(db, "Version", &[("version", "INTEGER")])?;
     validate_table(db, "Keybinds", &[("key", "TEXT"), ("value", "TEXT")])?;
     validate_table(db, "Style", &[("key", "TEXT"), ("value", "ANY")])?;
+    validate_table(db, "Settings", &[("key", "TEXT"), ("value", "ANY")])?;
     validate_table(
         db,
         "SaveGame",
@@ -299,6 +420,22 @@ fn validate_schema(db: &Database) -> Result<(), ValidateSchemaError> {
             ("current_room_x", "INTEGER"),
             ("current_room_y", "INTEGER"),
             ("pillar_count", "INTEGER"),
+            ("difficulty", "TEXT"),
+            ("name", "TEXT"),
+            ("gold", "INTEGER"),
+            ("hardcore", "INTEGER"),
+            ("suspended_combat", "TEXT"),
+            ("corruption", "TEXT"),
+            ("game_mode", "TEXT"),
+            ("challenges", "TEXT"),
+            ("floor", "INTEGER"),
+            ("generation", "TEXT"),
+            ("map_radius", "INTEGER"),
+            ("pillars", "INTEGER"),
+            ("room_weights", "TEXT"),
+            ("last_autosaved", "TEXT"),
+            ("checksum", "TEXT"),
+            ("hex_shards", "INTEGER"),
         ],
     )?;
     validate_table(
@@ -313,6 +450,9 @@ fn validate_schema(db: &Database) -> Result<(), ValidateSchemaError> {
             ("attack_damage_max", "INTEGER"),
             ("attack_speed", "INTEGER"),
             ("hit_chance", "REAL"),
+            ("display_name", "TEXT"),
+            ("level", "INTEGER"),
+            ("xp", "INTEGER"),
         ],
     )?;
     validate_table(
@@ -325,9 +465,79 @@ fn validate_schema(db: &Database) -> Result<(), ValidateSchemaError> {
             ("cleared", "INTEGER"),
             ("r_type", "TEXT"),
             ("rng_seed", "INTEGER"),
+            ("discovered", "INTEGER"),
+            ("floor", "INTEGER"),
+            ("guardian_respawn", "INTEGER"),
+            ("chest_opened", "INTEGER"),
+        ],
+    )?;
+    validate_table(
+        db,
+        "Item",
+        &[game_id, ("type", "TEXT"), ("equipped_by", "INTEGER")],
+    )?;
+    validate_table(
+        db,
+        "JournalEntry",
+        &[
+            game_id,
+            ("position_x", "INTEGER"),
+            ("position_y", "INTEGER"),
+            ("r_type", "TEXT"),
+            ("loot", "TEXT"),
+            ("cleared_at", "TEXT"),
+            ("floor", "INTEGER"),
+        ],
+    )?;
+    validate_table(
+        db,
+        "RunStats",
+        &[
+            game_id,
+            ("rooms_explored", "INTEGER"),
+            ("enemies_killed", "INTEGER"),
+            ("damage_dealt", "INTEGER"),
+            ("damage_taken", "INTEGER"),
+            ("pits_triggered", "INTEGER"),
+            ("items_used", "INTEGER"),
+            ("turns_taken", "INTEGER"),
+        ],
+    )?;
+    validate_table(
+        db,
+        "ComponentBlob",
+        &[
+            game_id,
+            ("slot", "INTEGER"),
+            ("component", "TEXT"),
+            ("data", "TEXT"),
+        ],
+    )?;
+    validate_table(
+        db,
+        "DailyRun",
+        &[
+            game_id,
+            ("date", "TEXT"),
+            ("world_seed", "INTEGER"),
+            ("outcome", "TEXT"),
+            ("rooms_explored", "INTEGER"),
+            ("enemies_killed", "INTEGER"),
+            ("damage_taken", "INTEGER"),
+            ("turns_taken", "INTEGER"),
+        ],
+    )?;
+    validate_table(
+        db,
+        "ChallengeRun",
+        &[
+            game_id,
+            ("challenge", "TEXT"),
+            ("outcome", "TEXT"),
+            ("rooms_explored", "INTEGER"),
+            ("turns_taken", "INTEGER"),
         ],
     )?;
-    validate_table(db, "Item", &[game_id, ("type", "TEXT")])?;
 
     Ok(())
 }
@@ -414,12 +624,15 @@ pub enum MigrationError {
 
 const MIN_VERSION_MIGRATEABLE: Version = 11;
 /// Make sure the migrations are set up properly
-const _: () = assert!(DB_VERSION == 12, "UPDATE THE MIGRATION SCRIPT");
+const _: () = assert!(DB_VERSION == 39, "UPDATE THE MIGRATION SCRIPT");
 
 /// MAINTENANCE: UPDATE EVERY DATABASE UPDGRADE
 fn migrate_database(db: &Database, from: Version) -> Result<(), MigrationError> {
     assert!((MIN_VERSION_MIGRATEABLE..DB_VERSION).contains(&from));
 
+    // Some migrations rebuild tables that others reference, which has to be done with
+    // foreign keys off. This can't be changed inside of a transaction.
+    db.connection.execute_batch("PRAGMA foreign_keys = OFF")?;
     db.connection.execute_batch("BEGIN TRANSACTION")?;
 
     let mut from = from;
@@ -429,6 +642,141 @@ fn migrate_database(db: &Database, from: Version) -> Result<(), MigrationError>
         from = 12;
     }
 
+    if from == 12 {
+        db.connection.execute_batch(MIGRATE_FROM_12_TO_13)?;
+        from = 13;
+    }
+
+    if from == 13 {
+        db.connection.execute_batch(MIGRATE_FROM_13_TO_14)?;
+        from = 14;
+    }
+
+    if from == 14 {
+        db.connection.execute_batch(MIGRATE_FROM_14_TO_15)?;
+        from = 15;
+    }
+
+    if from == 15 {
+        db.connection.execute_batch(MIGRATE_FROM_15_TO_16)?;
+        from = 16;
+    }
+
+    if from == 16 {
+        db.connection.execute_batch(MIGRATE_FROM_16_TO_17)?;
+        from = 17;
+    }
+
+    if from == 17 {
+        db.connection.execute_batch(MIGRATE_FROM_17_TO_18)?;
+        from = 18;
+    }
+
+    if from == 18 {
+        db.connection.execute_batch(MIGRATE_FROM_18_TO_19)?;
+        from = 19;
+    }
+
+    if from == 19 {
+        db.connection.execute_batch(MIGRATE_FROM_19_TO_20)?;
+        from = 20;
+    }
+
+    if from == 20 {
+        db.connection.execute_batch(MIGRATE_FROM_20_TO_21)?;
+        from = 21;
+    }
+
+    if from == 21 {
+        db.connection.execute_batch(MIGRATE_FROM_21_TO_22)?;
+        from = 22;
+    }
+
+    if from == 22 {
+        db.connection.execute_batch(MIGRATE_FROM_22_TO_23)?;
+        from = 23;
+    }
+
+    if from == 23 {
+        db.connection.execute_batch(MIGRATE_FROM_23_TO_24)?;
+        from = 24;
+    }
+
+    if from == 24 {
+        db.connection.execute_batch(MIGRATE_FROM_24_TO_25)?;
+        from = 25;
+    }
+
+    if from == 25 {
+        db.connection.execute_batch(MIGRATE_FROM_25_TO_26)?;
+        from = 26;
+    }
+
+    if from == 26 {
+        db.connection.execute_batch(MIGRATE_FROM_26_TO_27)?;
+        from = 27;
+    }
+
+    if from == 27 {
+        db.connection.execute_batch(MIGRATE_FROM_27_TO_28)?;
+        from = 28;
+    }
+
+    if from == 28 {
+        db.connection.execute_batch(MIGRATE_FROM_28_TO_29)?;
+        from = 29;
+    }
+
+    if from == 29 {
+        db.connection.execute_batch(MIGRATE_FROM_29_TO_30)?;
+        from = 30;
+    }
+
+    if from == 30 {
+        db.connection.execute_batch(MIGRATE_FROM_30_TO_31)?;
+        from = 31;
+    }
+
+    if from == 31 {
+        db.connection.execute_batch(MIGRATE_FROM_31_TO_32)?;
+        from = 32;
+    }
+
+    if from == 32 {
+        db.connection.execute_batch(MIGRATE_FROM_32_TO_33)?;
+        from = 33;
+    }
+
+    if from == 33 {
+        db.connection.execute_batch(MIGRATE_FROM_33_TO_34)?;
+        from = 34;
+    }
+
+    if from == 34 {
+        db.connection.execute_batch(MIGRATE_FROM_34_TO_35)?;
+        from = 35;
+    }
+
+    if from == 35 {
+        db.connection.execute_batch(MIGRATE_FROM_35_TO_36)?;
+        from = 36;
+    }
+
+    if from == 36 {
+        db.connection.execute_batch(MIGRATE_FROM_36_TO_37)?;
+        from = 37;
+    }
+
+    if from == 37 {
+        db.connection.execute_batch(MIGRATE_FROM_37_TO_38)?;
+        from = 38;
+    }
+
+    if from == 38 {
+        db.connection.execute_batch(MIGRATE_FROM_38_TO_39)?;
+        from = 39;
+    }
+
     assert_eq!(
         from, DB_VERSION,
         "Failed to find migration script to migrate fully."
@@ -441,6 +789,7 @@ fn migrate_database(db: &Database, from: Version) -> Result<(), MigrationError>
     );
 
     db.connection.execute_batch("COMMIT")?;
+    db.connection.execute_batch("PRAGMA foreign_keys = ON")?;
 
     Ok(())
 }
@@ -450,9 +799,306 @@ const MIGRATE_FROM_11_TO_12: &str = "
     ALTER TABLE SaveGame ADD COLUMN pillar_count INTEGER DEFAULT 0;
 ";
 
+/// Keybinds went from 2 to 3 inputs per control.
+/// The new slot is filled with the default gamepad binding.
+const MIGRATE_FROM_12_TO_13: &str = "
+    UPDATE Version SET version = 13;
+    UPDATE Keybinds SET value = substr(value, 1, length(value) - 1) || ',' || CASE key
+        WHEN 'move_up'    THEN 'Some(Gamepad(DPadUp))'
+        WHEN 'move_down'  THEN 'Some(Gamepad(DPadDown))'
+        WHEN 'move_left'  THEN 'Some(Gamepad(DPadLeft))'
+        WHEN 'move_right' THEN 'Some(Gamepad(DPadRight))'
+        WHEN 'zoom_in'    THEN 'Some(Gamepad(RightTrigger2))'
+        WHEN 'zoom_out'   THEN 'Some(Gamepad(LeftTrigger2))'
+        WHEN 'pause'      THEN 'Some(Gamepad(Start))'
+        WHEN 'select'     THEN 'Some(Gamepad(South))'
+        ELSE 'None'
+    END || ')'
+    WHERE value LIKE '(%)';
+";
+
+/// Adds a table for general settings, like the volume.
+const MIGRATE_FROM_13_TO_14: &str = "
+    UPDATE Version SET version = 14;
+    CREATE TABLE Settings(
+        key   TEXT PRIMARY KEY,
+        value ANY NOT NULL
+    ) STRICT;
+";
+
+/// Adds fog of war to the map.
+/// Rooms in existing saves were all visible, so they start discovered.
+const MIGRATE_FROM_14_TO_15: &str = "
+    UPDATE Version SET version = 15;
+    ALTER TABLE RoomInfo ADD COLUMN discovered INTEGER NOT NULL DEFAULT 1;
+";
+
+/// Lets the player name their party members.
+/// Existing party members keep their class as their name.
+const MIGRATE_FROM_15_TO_16: &str = "
+    UPDATE Version SET version = 16;
+    ALTER TABLE PlayerActor ADD COLUMN display_name TEXT NOT NULL DEFAULT '';
+    UPDATE PlayerActor SET display_name = name;
+";
+
+/// Each save remembers the difficulty it was started on.
+/// Games from before difficulties existed were all on normal.
+const MIGRATE_FROM_16_TO_17: &str = "
+    UPDATE Version SET version = 17;
+    ALTER TABLE SaveGame ADD COLUMN difficulty TEXT NOT NULL DEFAULT 'Normal';
+";
+
+/// Lets the player name their worlds. Older worlds are left unnamed.
+const MIGRATE_FROM_17_TO_18: &str = "
+    UPDATE Version SET version = 18;
+    ALTER TABLE SaveGame ADD COLUMN name TEXT NOT NULL DEFAULT '';
+";
+
+/// Defeated enemies drop gold, which is kept with the save.
+const MIGRATE_FROM_18_TO_19: &str = "
+    UPDATE Version SET version = 19;
+    ALTER TABLE SaveGame ADD COLUMN gold INTEGER NOT NULL DEFAULT 0;
+";
+
+/// Heroes gain experience and levels, which are kept with the party.
+/// Their saved stats already include any levels, so older heroes start at level 1.
+const MIGRATE_FROM_19_TO_20: &str = "
+    UPDATE Version SET version = 20;
+    ALTER TABLE PlayerActor ADD COLUMN level INTEGER NOT NULL DEFAULT 1;
+    ALTER TABLE PlayerActor ADD COLUMN xp INTEGER NOT NULL DEFAULT 0;
+";
+
+/// Fights can be saved part way through, and hardcore games only let them be loaded once.
+/// Older games are not hardcore.
+const MIGRATE_FROM_20_TO_21: &str = "
+    UPDATE Version SET version = 21;
+    ALTER TABLE SaveGame ADD COLUMN hardcore INTEGER NOT NULL DEFAULT 0;
+    ALTER TABLE SaveGame ADD COLUMN suspended_combat TEXT DEFAULT NULL;
+";
+
+/// Games can be started with pillar corruption, which is off for older games.
+const MIGRATE_FROM_21_TO_22: &str = "
+    UPDATE Version SET version = 22;
+    ALTER TABLE SaveGame ADD COLUMN corruption TEXT DEFAULT NULL;
+";
+
+/// Games are won by the condition of the mode they were started in.
+/// Older games are all played for the pillars.
+const MIGRATE_FROM_22_TO_23: &str = "
+    UPDATE Version SET version = 23;
+    ALTER TABLE SaveGame ADD COLUMN game_mode TEXT NOT NULL DEFAULT 'Pillars';
+";
+
+/// Games record the challenges they were started with, older games had none.
+const MIGRATE_FROM_23_TO_24: &str = "
+    UPDATE Version SET version = 24;
+    ALTER TABLE SaveGame ADD COLUMN challenges TEXT NOT NULL DEFAULT '()';
+";
+
+/// Rooms are written to a journal as they are cleared. Older games start with an empty one.
+const MIGRATE_FROM_24_TO_25: &str = "
+    UPDATE Version SET version = 25;
+    CREATE TABLE JournalEntry(
+        game_id    INTEGER NOT NULL REFERENCES SaveGame(game_id) DEFERRABLE INITIALLY DEFERRED,
+        position_x INTEGER NOT NULL,
+        position_y INTEGER NOT NULL,
+        r_type     TEXT    NOT NULL,
+        loot       TEXT    NOT NULL DEFAULT '[]',
+        cleared_at TEXT    NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        PRIMARY KEY(game_id, position_x, position_y)
+    ) STRICT;
+";
+
+/// Games keep count of how they were played. Older games start counting from nothing.
+const MIGRATE_FROM_25_TO_26: &str = "
+    UPDATE Version SET version = 26;
+    CREATE TABLE RunStats(
+        game_id         INTEGER PRIMARY KEY REFERENCES SaveGame(game_id) DEFERRABLE INITIALLY DEFERRED,
+        rooms_explored  INTEGER NOT NULL DEFAULT 0,
+        enemies_killed  INTEGER NOT NULL DEFAULT 0,
+        damage_dealt    INTEGER NOT NULL DEFAULT 0,
+        damage_taken    INTEGER NOT NULL DEFAULT 0,
+        pits_triggered  INTEGER NOT NULL DEFAULT 0,
+        items_used      INTEGER NOT NULL DEFAULT 0,
+        turns_taken     INTEGER NOT NULL DEFAULT 0
+    ) STRICT;
+";
+
+/// Maps have floors, and the rooms of each are kept by their floor.
+/// Older games are all on the first floor.
+///
+/// SQLite can't change the keys of a table, so both tables are rebuilt with the floor in them.
+const MIGRATE_FROM_26_TO_27: &str = "
+    UPDATE Version SET version = 27;
+
+    CREATE TABLE RoomInfoWithFloor(
+        game_id    INTEGER NOT NULL REFERENCES SaveGame(game_id) DEFERRABLE INITIALLY DEFERRED,
+        position_x INTEGER NOT NULL,
+        position_y INTEGER NOT NULL,
+        cleared    INTEGER NOT NULL,
+        r_type     TEXT    NOT NULL,
+        rng_seed   INTEGER NOT NULL,
+        discovered INTEGER NOT NULL DEFAULT 1,
+        floor      INTEGER NOT NULL DEFAULT 0,
+        PRIMARY KEY(game_id, floor, position_x, position_y)
+    ) STRICT;
+    INSERT INTO RoomInfoWithFloor(game_id, position_x, position_y, cleared, r_type, rng_seed, discovered)
+        SELECT game_id, position_x, position_y, cleared, r_type, rng_seed, discovered FROM RoomInfo;
+    DROP TABLE RoomInfo;
+    ALTER TABLE RoomInfoWithFloor RENAME TO RoomInfo;
+
+    CREATE TABLE SaveGameWithFloor(
+        game_id        INTEGER PRIMARY KEY AUTOINCREMENT,
+        created        TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        last_saved     TEXT NOT NULL,
+        world_seed     INTEGER NOT NULL,
+        current_room_x INTEGER DEFAULT NULL,
+        current_room_y INTEGER DEFAULT NULL,
+        pillar_count   INTEGER DEFAULT 0,
+        difficulty     TEXT NOT NULL DEFAULT 'Normal',
+        name           TEXT NOT NULL DEFAULT '',
+        gold           INTEGER NOT NULL DEFAULT 0,
+        hardcore       INTEGER NOT NULL DEFAULT 0,
+        suspended_combat TEXT DEFAULT NULL,
+        corruption     TEXT DEFAULT NULL,
+        game_mode      TEXT NOT NULL DEFAULT 'Pillars',
+        challenges     TEXT NOT NULL DEFAULT '()',
+        floor          INTEGER NOT NULL DEFAULT 0,
+        FOREIGN KEY(game_id, floor, current_room_x, current_room_y)
+            REFERENCES RoomInfo(game_id, floor, position_x, position_y)
+            DEFERRABLE INITIALLY DEFERRED
+    ) STRICT;
+    INSERT INTO SaveGameWithFloor(
+        game_id, created, last_saved, world_seed, current_room_x, current_room_y, pillar_count,
+        difficulty, name, gold, hardcore, suspended_combat, corruption, game_mode, challenges
+    )
+        SELECT
+            game_id, created, last_saved, world_seed, current_room_x, current_room_y, pillar_count,
+            difficulty, name, gold, hardcore, suspended_combat, corruption, game_mode, challenges
+        FROM SaveGame;
+    DROP TABLE SaveGame;
+    ALTER TABLE SaveGameWithFloor RENAME TO SaveGame;
+";
+
+/// Games record how their floors are laid out. Older games all followed paths.
+const MIGRATE_FROM_27_TO_28: &str = "
+    UPDATE Version SET version = 28;
+    ALTER TABLE SaveGame ADD COLUMN generation TEXT NOT NULL DEFAULT 'Paths';
+";
+
+/// Games record the size of their map, their pillars and their mix of rooms.
+/// Older games all had the same.
+const MIGRATE_FROM_28_TO_29: &str = "
+    UPDATE Version SET version = 29;
+    ALTER TABLE SaveGame ADD COLUMN map_radius INTEGER NOT NULL DEFAULT 5;
+    ALTER TABLE SaveGame ADD COLUMN pillars INTEGER NOT NULL DEFAULT 4;
+    ALTER TABLE SaveGame ADD COLUMN room_weights TEXT NOT NULL DEFAULT '()';
+";
+
+/// Beaten guardians can return while their pillar is left in the room, with `manual_pillar_pickup`.
+/// Pillars were always collected straight away before, so none are waiting on one.
+const MIGRATE_FROM_29_TO_30: &str = "
+    UPDATE Version SET version = 30;
+    ALTER TABLE RoomInfo ADD COLUMN guardian_respawn INTEGER DEFAULT NULL;
+";
+
+/// Games record when they were last autosaved. Older games never were.
+const MIGRATE_FROM_30_TO_31: &str = "
+    UPDATE Version SET version = 31;
+    ALTER TABLE SaveGame ADD COLUMN last_autosaved TEXT DEFAULT NULL;
+";
+
+/// Components saved through reflection are kept for each party member by their type.
+const MIGRATE_FROM_31_TO_32: &str = "
+    UPDATE Version SET version = 32;
+    CREATE TABLE ComponentBlob(
+        game_id   INTEGER NOT NULL REFERENCES SaveGame(game_id) DEFERRABLE INITIALLY DEFERRED,
+        slot      INTEGER NOT NULL,
+        component TEXT    NOT NULL,
+        data      TEXT    NOT NULL,
+        PRIMARY KEY(game_id, slot, component)
+    ) STRICT;
+";
+
+/// Games keep a checksum of what was last saved, to tell when it was changed outside the game.
+/// Older games have none, so they are never warned about.
+const MIGRATE_FROM_32_TO_33: &str = "
+    UPDATE Version SET version = 33;
+    ALTER TABLE SaveGame ADD COLUMN checksum TEXT DEFAULT NULL;
+";
+
+/// Daily runs are recorded apart from their saves, so they can still be compared once deleted.
+const MIGRATE_FROM_33_TO_34: &str = "
+    UPDATE Version SET version = 34;
+    CREATE TABLE DailyRun(
+        game_id        INTEGER PRIMARY KEY,
+        date           TEXT    NOT NULL,
+        world_seed     INTEGER NOT NULL,
+        outcome        TEXT    NOT NULL,
+        rooms_explored INTEGER NOT NULL DEFAULT 0,
+        enemies_killed INTEGER NOT NULL DEFAULT 0,
+        damage_taken   INTEGER NOT NULL DEFAULT 0,
+        turns_taken    INTEGER NOT NULL DEFAULT 0
+    ) STRICT;
+";
+
+/// Runs of challenge maps are recorded like daily runs, kept once their saves are deleted.
+const MIGRATE_FROM_34_TO_35: &str = "
+    UPDATE Version SET version = 35;
+    CREATE TABLE ChallengeRun(
+        game_id        INTEGER PRIMARY KEY,
+        challenge      TEXT    NOT NULL,
+        outcome        TEXT    NOT NULL,
+        rooms_explored INTEGER NOT NULL DEFAULT 0,
+        turns_taken    INTEGER NOT NULL DEFAULT 0
+    ) STRICT;
+";
+
+/// Rooms remember whether their chest was opened. There were no chests before, so none were.
+const MIGRATE_FROM_35_TO_36: &str = "
+    UPDATE Version SET version = 36;
+    ALTER TABLE RoomInfo ADD COLUMN chest_opened INTEGER NOT NULL DEFAULT 0;
+";
+
+/// Items remember the party slot of the hero wearing them. Nothing could be worn before.
+const MIGRATE_FROM_36_TO_37: &str = "
+    UPDATE Version SET version = 37;
+    ALTER TABLE Item ADD COLUMN equipped_by INTEGER DEFAULT NULL;
+";
+
+/// Games keep the hex shards the party carries. There were no shards before, so none are.
+const MIGRATE_FROM_37_TO_38: &str = "
+    UPDATE Version SET version = 38;
+    ALTER TABLE SaveGame ADD COLUMN hex_shards INTEGER NOT NULL DEFAULT 0;
+";
+
+/// Journal entries are kept by the floor their room is on, as every floor has its own rooms.
+/// Older entries are all on the first floor.
+///
+/// SQLite can't change the keys of a table, so it is rebuilt with the floor in them.
+const MIGRATE_FROM_38_TO_39: &str = "
+    UPDATE Version SET version = 39;
+
+    CREATE TABLE JournalEntryWithFloor(
+        game_id    INTEGER NOT NULL REFERENCES SaveGame(game_id) DEFERRABLE INITIALLY DEFERRED,
+        position_x INTEGER NOT NULL,
+        position_y INTEGER NOT NULL,
+        r_type     TEXT    NOT NULL,
+        loot       TEXT    NOT NULL DEFAULT '[]',
+        cleared_at TEXT    NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        floor      INTEGER NOT NULL DEFAULT 0,
+        PRIMARY KEY(game_id, floor, position_x, position_y)
+    ) STRICT;
+    INSERT INTO JournalEntryWithFloor(game_id, position_x, position_y, r_type, loot, cleared_at)
+        SELECT game_id, position_x, position_y, r_type, loot, cleared_at FROM JournalEntry;
+    DROP TABLE JournalEntry;
+    ALTER TABLE JournalEntryWithFloor RENAME TO JournalEntry;
+";
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::controls::{Input, InputList};
 
     const VERSION_11_SCHEMA: &str = "
     BEGIN TRANSACTION;
@@ -525,6 +1171,52 @@ mod test {
         validate_schema(&db).unwrap();
     }
 
+    #[test]
+    pub fn collect_garbage() {
+        let db = Database::open_in_memory().unwrap();
+
+        // The bundled SQLite enforces foreign keys, so turn them off to make some orphans.
+        db.connection
+            .execute_batch(
+                "PRAGMA foreign_keys = OFF;
+                 INSERT INTO SaveGame(game_id, last_saved, world_seed, current_room_x, current_room_y)
+                    VALUES (1, datetime('now'), 1, 0, 0), (2, datetime('now'), 2, NULL, NULL);
+                 INSERT INTO RoomInfo(game_id, position_x, position_y, cleared, r_type, rng_seed)
+                    VALUES
+                    (1, 0, 0, 0, 'Entrance', 0),
+                    (2, 0, 0, 0, 'Entrance', 0),
+                    (3, 0, 0, 0, 'Entrance', 0);
+                 INSERT INTO PlayerActor VALUES (3, 'Warrior', 10, 10, 1, 2, 3, 0.5, 'Warrior', 1, 0);
+                 INSERT INTO Item(game_id, type) VALUES (1, 'HealingPotion'), (3, 'HealingPotion');
+                 INSERT INTO JournalEntry(game_id, position_x, position_y, r_type)
+                    VALUES (1, 0, 1, 'EmptyRoom'), (3, 0, 1, 'EmptyRoom');
+                 INSERT INTO RunStats(game_id) VALUES (1), (3);
+                 INSERT INTO ComponentBlob VALUES (3, 0, 'Health', '()');
+                 PRAGMA foreign_keys = ON;",
+            )
+            .unwrap();
+
+        assert_eq!(
+            db.collect_garbage().unwrap(),
+            GarbageReport {
+                unfinished_saves: 1,
+                party_members: 1,
+                rooms: 2,
+                items: 1,
+                journal_entries: 1,
+                run_stats: 1,
+                component_blobs: 1,
+            }
+        );
+        assert_eq!(db.collect_garbage().unwrap().total(), 0);
+
+        let saves: i64 = db
+            .connection
+            .query_one("SELECT count(*) FROM SaveGame", (), |row| row.get(0))
+            .unwrap();
+        assert_eq!(saves, 1);
+    }
+
     #[test]
     pub fn migrate_from_10() {
         let db = Database {
@@ -537,4 +1229,88 @@ mod test {
 
         validate_schema(&db).unwrap();
     }
+
+    #[test]
+    pub fn migrate_rooms_onto_the_first_floor() {
+        let db = Database {
+            connection: Connection::open_in_memory().unwrap(),
+        };
+
+        db.connection.execute_batch(VERSION_11_SCHEMA).unwrap();
+        db.connection
+            .execute_batch(
+                "BEGIN TRANSACTION;
+                 INSERT INTO SaveGame(game_id, last_saved, world_seed, current_room_x, current_room_y)
+                    VALUES (1, datetime('now'), 7, 0, 0);
+                 INSERT INTO RoomInfo VALUES (1, 0, 0, 0, 'Entrance', 3), (1, 0, 1, 1, 'EmptyRoom', 4);
+                 COMMIT;",
+            )
+            .unwrap();
+
+        migrate_database(&db, 11).unwrap();
+        validate_schema(&db).unwrap();
+
+        let rooms: i64 = db
+            .connection
+            .query_one("SELECT count(*) FROM RoomInfo WHERE floor = 0", (), |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(rooms, 2);
+
+        // The current room is still found through the rebuilt tables.
+        db.connection
+            .execute(
+                "UPDATE SaveGame SET current_room_y = 1 WHERE game_id = 1",
+                (),
+            )
+            .unwrap();
+        let broken_keys = db
+            .connection
+            .prepare("PRAGMA foreign_key_check")
+            .unwrap()
+            .query_map((), |_| Ok(()))
+            .unwrap()
+            .count();
+        assert_eq!(broken_keys, 0);
+    }
+
+    #[test]
+    pub fn migrate_keybinds_to_3_inputs() {
+        let db = Database {
+            connection: Connection::open_in_memory().unwrap(),
+        };
+
+        db.connection.execute_batch(VERSION_11_SCHEMA).unwrap();
+        db.connection
+            .execute_batch(
+                "INSERT INTO Keybinds VALUES
+                    ('move_up', '(Some(Keyboard(ArrowUp)),Some(Keyboard(KeyW)))'),
+                    ('zoom_in', '(Some(Keyboard(Comma)),None)');",
+            )
+            .unwrap();
+
+        migrate_database(&db, 11).unwrap();
+
+        let default = [None; 3];
+        let move_up: InputList = db.get_kv("Keybinds", "move_up", default);
+        let zoom_in: InputList = db.get_kv("Keybinds", "zoom_in", default);
+
+        assert_eq!(
+            move_up,
+            [
+                Some(Input::Keyboard(KeyCode::ArrowUp)),
+                Some(Input::Keyboard(KeyCode::KeyW)),
+                Some(Input::Gamepad(GamepadButton::DPadUp)),
+            ]
+        );
+        assert_eq!(
+            zoom_in,
+            [
+                Some(Input::Keyboard(KeyCode::Comma)),
+                None,
+                Some(Input::Gamepad(GamepadButton::RightTrigger2)),
+            ]
+        );
+    }
 }
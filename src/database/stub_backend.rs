@@ -1,3 +1,4 @@
+use super::GarbageReport;
 use bevy::prelude::*;
 use thiserror::Error;
 
@@ -15,6 +16,10 @@ impl Database {
         Ok(Self)
     }
 
+    pub fn open_in_memory() -> Result<Self, Error> {
+        Ok(Self)
+    }
+
     pub fn get_kv<T>(&self, _: &str, _: &str, default: T) -> T
     where
         T: Serialize + DeserializeOwned + Clone,
@@ -25,4 +30,8 @@ impl Database {
     pub fn set_kv<T: Serialize>(&self, _: &str, _: &str, _: T) -> Result<(), SetKvError> {
         Ok(())
     }
+
+    pub fn collect_garbage(&self) -> Result<GarbageReport, Error> {
+        Ok(GarbageReport::default())
+    }
 }
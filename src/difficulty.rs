@@ -0,0 +1,204 @@
+//! How hard a game is, chosen when starting it and kept with the save.
+
+use crate::generate_map::{GenerationMode, MapShape};
+use crate::room::RoomMix;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+use strum::{Display, EnumIter, IntoEnumIterator};
+
+#[derive(
+    Resource,
+    Debug,
+    Default,
+    Hash,
+    PartialEq,
+    Eq,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    EnumIter,
+    Display,
+)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    /// The difficulty after this one, wrapping back to the easiest.
+    pub fn next(self) -> Self {
+        Self::iter()
+            .cycle()
+            .skip_while(|difficulty| *difficulty != self)
+            .nth(1)
+            .unwrap()
+    }
+
+    /// How much the health and damage of enemies is multiplied by.
+    pub fn enemy_scale(self) -> f32 {
+        match self {
+            Self::Easy => 0.75,
+            Self::Normal => 1.0,
+            Self::Hard => 1.35,
+        }
+    }
+
+    /// Scales an enemy stat, never going below 1.
+    pub fn scale_enemy_stat(self, stat: u32) -> u32 {
+        ((stat as f32 * self.enemy_scale()).round() as u32).max(1)
+    }
+
+    /// How much damage a pit can do.
+    pub fn pit_damage(self) -> Range<u32> {
+        match self {
+            Self::Easy => 0..11,
+            Self::Normal => 0..21,
+            Self::Hard => 5..31,
+        }
+    }
+
+    /// How much the budget for buying the enemies of a room is multiplied by.
+    pub fn encounter_scale(self) -> f32 {
+        match self {
+            Self::Easy => 0.75,
+            Self::Normal => 1.0,
+            Self::Hard => 1.5,
+        }
+    }
+}
+
+/// What has to be done to win a game, see [`WinCondition`](crate::game::WinCondition).
+#[derive(
+    Resource,
+    Debug,
+    Default,
+    Hash,
+    PartialEq,
+    Eq,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    EnumIter,
+    Display,
+)]
+pub enum GameMode {
+    /// Bring every Pillar back to the entrance.
+    #[default]
+    Pillars,
+    /// Beat the guardian of every Pillar.
+    Guardians,
+    /// Make it through enough rooms.
+    Survival,
+}
+
+impl GameMode {
+    /// The mode after this one, wrapping back to the first.
+    pub fn next(self) -> Self {
+        Self::iter()
+            .cycle()
+            .skip_while(|mode| *mode != self)
+            .nth(1)
+            .unwrap()
+    }
+}
+
+/// Extra ways to make combat harder, kept with the save so runs with them can be told apart.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Challenges {
+    /// The Priestess's special move only shields.
+    /// Fallen heroes can still be revived, at the entrance, a shrine or by the Priestess,
+    /// as otherwise one unlucky fight would leave the party short for the rest of the run.
+    pub no_healing: bool,
+    /// Every enemy takes their turn before the party.
+    pub enemies_first: bool,
+    /// The party has a few seconds to choose each action, or loses the turn.
+    pub timed_turns: bool,
+}
+
+/// The optional rules picked for a new game, which are kept with the save.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct GameRules {
+    /// Quitting and loading can't take back a fight, and fallen heroes stay down.
+    pub hardcore: bool,
+    /// Pillars left too long are corrupted, making the enemies around them stronger.
+    pub corruption: bool,
+    pub mode: GameMode,
+    pub challenges: Challenges,
+    pub generation: GenerationMode,
+    pub shape: MapShape,
+    pub rooms: RoomMix,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumIter)]
+pub enum Rule {
+    Hardcore,
+    Corruption,
+    #[strum(to_string = "No Healing")]
+    NoHealing,
+    #[strum(to_string = "Enemies Act First")]
+    EnemiesFirst,
+    #[strum(to_string = "Timed Turns")]
+    TimedTurns,
+}
+
+impl GameRules {
+    pub fn get(&self, rule: Rule) -> bool {
+        match rule {
+            Rule::Hardcore => self.hardcore,
+            Rule::Corruption => self.corruption,
+            Rule::NoHealing => self.challenges.no_healing,
+            Rule::EnemiesFirst => self.challenges.enemies_first,
+            Rule::TimedTurns => self.challenges.timed_turns,
+        }
+    }
+
+    pub fn toggle(&mut self, rule: Rule) {
+        let on = match rule {
+            Rule::Hardcore => &mut self.hardcore,
+            Rule::Corruption => &mut self.corruption,
+            Rule::NoHealing => &mut self.challenges.no_healing,
+            Rule::EnemiesFirst => &mut self.challenges.enemies_first,
+            Rule::TimedTurns => &mut self.challenges.timed_turns,
+        };
+        *on = !*on;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn next_wraps_around() {
+        assert_eq!(Difficulty::Easy.next(), Difficulty::Normal);
+        assert_eq!(Difficulty::Normal.next(), Difficulty::Hard);
+        assert_eq!(Difficulty::Hard.next(), Difficulty::Easy);
+    }
+
+    #[test]
+    fn rules_toggle_on_and_off() {
+        let mut rules = GameRules::default();
+        for rule in Rule::iter() {
+            rules.toggle(rule);
+            assert!(rules.get(rule), "{rule} should be on");
+        }
+        assert!(rules.challenges.no_healing && rules.challenges.timed_turns);
+
+        rules.toggle(Rule::EnemiesFirst);
+        assert!(!rules.get(Rule::EnemiesFirst));
+    }
+
+    #[test]
+    fn normal_leaves_enemies_alone() {
+        assert_eq!(Difficulty::Normal.scale_enemy_stat(61), 61);
+        assert!(Difficulty::Easy.scale_enemy_stat(61) < 61);
+        assert!(Difficulty::Hard.scale_enemy_stat(61) > 61);
+        assert_eq!(Difficulty::Easy.scale_enemy_stat(1), 1);
+    }
+}
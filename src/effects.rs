@@ -0,0 +1,210 @@
+//! How strong flashes, shakes and particles are, for players who are sensitive to them.
+//!
+//! Every visual effect asks [`EffectsBudget`] before it plays, so the one
+//! [`EffectsIntensity`] setting turns all of them down at once.
+//! Effects are components implementing [`VisualEffect`] and registered with
+//! [`RegisterEffect::register_effect`], and debug builds report any that
+//! were spawned without going through the budget.
+
+use crate::prelude::*;
+use crate::settings::SETTINGS_DB_TABLE;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use strum::Display;
+
+pub struct EffectsPlugin;
+
+impl Plugin for EffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PreStartup, setup_effects_intensity)
+            .add_systems(
+                Update,
+                effects_intensity_sync.run_if(
+                    resource_changed::<EffectsIntensity>
+                        .and(not(resource_added::<EffectsIntensity>)),
+                ),
+            );
+    }
+}
+
+/// How strongly visual effects play, set in the display settings.
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Display)]
+pub enum EffectsIntensity {
+    Off,
+    Reduced,
+    #[default]
+    Full,
+}
+
+impl EffectsIntensity {
+    const DB_KEY: &str = "effects_intensity";
+
+    /// The setting after this one, for cycling through them with a button.
+    pub fn next(self) -> Self {
+        match self {
+            EffectsIntensity::Full => EffectsIntensity::Reduced,
+            EffectsIntensity::Reduced => EffectsIntensity::Off,
+            EffectsIntensity::Off => EffectsIntensity::Full,
+        }
+    }
+
+    /// How strongly an effect of the kind plays, from 0 (not at all) to 1.
+    pub fn scale(self, kind: EffectKind) -> f32 {
+        use EffectKind as K;
+        use EffectsIntensity as I;
+        match (self, kind) {
+            (I::Off, _) => 0.0,
+            // Flashing is the most likely to cause harm, so it is the first to go.
+            (I::Reduced, K::Flash) => 0.0,
+            (I::Reduced, K::Shake) => 0.35,
+            (I::Reduced, K::Particles) => 0.5,
+            (I::Reduced, K::HitStop) => 0.5,
+            (I::Full, _) => 1.0,
+        }
+    }
+}
+
+/// The kinds of effect the intensity setting limits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Display)]
+pub enum EffectKind {
+    Flash,
+    Shake,
+    Particles,
+    /// Holding the game still for a moment, which isn't an entity of its own.
+    HitStop,
+}
+
+/// Put on an effect's entity to show the budget allowed it, and how strongly to play it.
+/// Only made by [`EffectsBudget::allow`].
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Budgeted {
+    kind: EffectKind,
+    scale: f32,
+}
+
+impl Budgeted {
+    pub fn kind(&self) -> EffectKind {
+        self.kind
+    }
+
+    /// How strongly to play the effect, from 0 to 1.
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+}
+
+/// The one way effects should check how strongly they may play.
+#[derive(SystemParam)]
+pub struct EffectsBudget<'w> {
+    intensity: Res<'w, EffectsIntensity>,
+}
+
+impl EffectsBudget<'_> {
+    /// Asks to play an effect of the kind, returning `None` when it should be skipped.
+    /// Otherwise the returned [`Budgeted`] goes on the entity along with the effect.
+    pub fn allow(&self, kind: EffectKind) -> Option<Budgeted> {
+        let scale = self.scale(kind);
+        (scale > 0.0).then_some(Budgeted { kind, scale })
+    }
+
+    /// How strongly an effect that isn't its own entity,
+    /// like moving the camera, may play.
+    pub fn scale(&self, kind: EffectKind) -> f32 {
+        self.intensity.scale(kind)
+    }
+}
+
+/// A component that plays a visual effect, which has to go through the [`EffectsBudget`].
+pub trait VisualEffect: Component {
+    const KIND: EffectKind;
+}
+
+pub trait RegisterEffect {
+    /// Lets debug builds check every effect of this type asked the budget first.
+    fn register_effect<T: VisualEffect>(&mut self) -> &mut Self;
+}
+
+impl RegisterEffect for App {
+    fn register_effect<T: VisualEffect>(&mut self) -> &mut Self {
+        #[cfg(feature = "debug")]
+        self.add_systems(PostUpdate, validate_effect::<T>);
+        self
+    }
+}
+
+/// What is wrong with how an effect of the kind was spawned, if anything.
+#[cfg(any(feature = "debug", test))]
+fn effect_problem(kind: EffectKind, budgeted: Option<&Budgeted>) -> Option<String> {
+    match budgeted {
+        None => Some(format!("{kind} effect bypassed the EffectsBudget")),
+        Some(budgeted) if budgeted.kind != kind => Some(format!(
+            "{kind} effect was budgeted as a {} effect",
+            budgeted.kind
+        )),
+        Some(_) => None,
+    }
+}
+
+#[cfg(feature = "debug")]
+fn validate_effect<T: VisualEffect>(effects: Query<(Entity, Option<&Budgeted>), Added<T>>) {
+    for (entity, budgeted) in effects.iter() {
+        if let Some(problem) = effect_problem(T::KIND, budgeted) {
+            error!("{problem}: {} on {entity}", std::any::type_name::<T>());
+        }
+    }
+}
+
+fn setup_effects_intensity(mut commands: Commands, database: NonSend<Database>) {
+    let intensity = database.get_kv(
+        SETTINGS_DB_TABLE,
+        EffectsIntensity::DB_KEY,
+        EffectsIntensity::default(),
+    );
+    commands.insert_resource(intensity);
+}
+
+fn effects_intensity_sync(database: NonSend<Database>, intensity: Res<EffectsIntensity>) -> Result {
+    database
+        .set_kv(SETTINGS_DB_TABLE, EffectsIntensity::DB_KEY, *intensity)
+        .map_err(GameError::from)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const KINDS: [EffectKind; 4] = [
+        EffectKind::Flash,
+        EffectKind::Shake,
+        EffectKind::Particles,
+        EffectKind::HitStop,
+    ];
+
+    #[test]
+    fn lower_settings_never_play_stronger() {
+        for kind in KINDS {
+            let off = EffectsIntensity::Off.scale(kind);
+            let reduced = EffectsIntensity::Reduced.scale(kind);
+            let full = EffectsIntensity::Full.scale(kind);
+
+            assert_eq!(off, 0.0);
+            assert!(off <= reduced && reduced <= full && full == 1.0);
+        }
+
+        assert_eq!(EffectsIntensity::Reduced.scale(EffectKind::Flash), 0.0);
+    }
+
+    #[test]
+    fn effects_must_be_budgeted_as_their_kind() {
+        let shake = Budgeted {
+            kind: EffectKind::Shake,
+            scale: 0.5,
+        };
+
+        assert_eq!(effect_problem(EffectKind::Shake, Some(&shake)), None);
+        assert!(effect_problem(EffectKind::Shake, None).is_some());
+        assert!(effect_problem(EffectKind::Flash, Some(&shake)).is_some());
+    }
+}
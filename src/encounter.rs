@@ -0,0 +1,269 @@
+//! How many and which enemies a combat room is given.
+//!
+//! Each enemy costs points, and every encounter is bought with a budget
+//! that grows the further the room is from the entrance.
+//! Rooms laid out in a [`Biome`] buy from the enemies that live there.
+
+use crate::generate_map::Biome;
+use crate::prelude::*;
+use rand::Rng;
+
+/// What an enemy costs to add to an encounter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EnemyCost {
+    pub name: ActorName,
+    pub cost: u32,
+}
+
+/// The enemies that live in a biome, bought in its rooms instead of the usual ones.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BiomeEnemies {
+    pub biome: Biome,
+    pub enemies: &'static [EnemyCost],
+}
+
+/// The content data encounters are built from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EncounterRules {
+    /// The most enemies a single room can hold.
+    pub max_enemies: usize,
+    /// The budget of a room right next to the entrance.
+    pub base_budget: u32,
+    /// How much the budget grows for each room away from the entrance.
+    pub budget_per_depth: f32,
+    /// The enemies that can be bought, and what they cost.
+    pub enemies: &'static [EnemyCost],
+    /// The enemies bought in the biomes that have their own, in place of [`Self::enemies`].
+    pub biomes: &'static [BiomeEnemies],
+}
+
+impl Default for EncounterRules {
+    fn default() -> Self {
+        Self {
+            max_enemies: crate::room::ENEMY_POSITIONS.len(),
+            base_budget: 3,
+            budget_per_depth: 0.5,
+            enemies: &[
+                EnemyCost {
+                    name: ActorName::Goblin,
+                    cost: 2,
+                },
+                EnemyCost {
+                    name: ActorName::Skeleton,
+                    cost: 3,
+                },
+                EnemyCost {
+                    name: ActorName::Archer,
+                    cost: 3,
+                },
+                EnemyCost {
+                    name: ActorName::Ogre,
+                    cost: 4,
+                },
+                EnemyCost {
+                    name: ActorName::Shaman,
+                    cost: 4,
+                },
+            ],
+            biomes: &[
+                BiomeEnemies {
+                    biome: Biome::Lair,
+                    enemies: &[
+                        EnemyCost {
+                            name: ActorName::Goblin,
+                            cost: 2,
+                        },
+                        EnemyCost {
+                            name: ActorName::Skeleton,
+                            cost: 3,
+                        },
+                        EnemyCost {
+                            name: ActorName::Shaman,
+                            cost: 4,
+                        },
+                    ],
+                },
+                BiomeEnemies {
+                    biome: Biome::Ruins,
+                    enemies: &[
+                        EnemyCost {
+                            name: ActorName::Construct,
+                            cost: 3,
+                        },
+                        EnemyCost {
+                            name: ActorName::Archer,
+                            cost: 3,
+                        },
+                        EnemyCost {
+                            name: ActorName::Ogre,
+                            cost: 4,
+                        },
+                    ],
+                },
+            ],
+        }
+    }
+}
+
+impl EncounterRules {
+    /// The points to spend on a room the given number of rooms away from the entrance.
+    pub fn budget(&self, depth: u32, difficulty: Difficulty) -> u32 {
+        let budget = self.base_budget as f32 + depth as f32 * self.budget_per_depth;
+        (budget * difficulty.encounter_scale()).floor() as u32
+    }
+
+    /// The enemies that can be bought in a room of the biome,
+    /// or the usual ones if it has none of its own or the room isn't in one.
+    pub fn pool(&self, biome: Option<Biome>) -> &'static [EnemyCost] {
+        self.biomes
+            .iter()
+            .find(|pool| Some(pool.biome) == biome)
+            .map_or(self.enemies, |pool| pool.enemies)
+    }
+
+    /// Buys random enemies from the biome's pool until the budget or the room runs out.
+    /// A room always gets at least the cheapest enemy.
+    pub fn roll_enemies(
+        &self,
+        rng: &mut impl Rng,
+        budget: u32,
+        biome: Option<Biome>,
+    ) -> Box<[ActorName]> {
+        let pool = self.pool(biome);
+        let mut remaining = budget;
+        let mut enemies = Vec::new();
+
+        while enemies.len() < self.max_enemies {
+            let affordable: Vec<&EnemyCost> = pool
+                .iter()
+                .filter(|enemy| enemy.cost <= remaining)
+                .collect();
+            if affordable.is_empty() {
+                break;
+            }
+
+            let enemy = affordable[rng.random_range(0..affordable.len())];
+            remaining -= enemy.cost;
+            enemies.push(enemy.name);
+        }
+
+        if enemies.is_empty()
+            && let Some(cheapest) = pool.iter().min_by_key(|enemy| enemy.cost)
+        {
+            enemies.push(cheapest.name);
+        }
+
+        enemies.into()
+    }
+
+    /// What the enemies in an encounter cost together.
+    pub fn cost(&self, enemies: &[ActorName]) -> u32 {
+        let costs = self
+            .enemies
+            .iter()
+            .chain(self.biomes.iter().flat_map(|pool| pool.enemies));
+        enemies
+            .iter()
+            .filter_map(|name| costs.clone().find(|enemy| enemy.name == *name))
+            .map(|enemy| enemy.cost)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::SeedableRng;
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn budget_grows_with_depth_and_difficulty() {
+        let rules = EncounterRules::default();
+
+        assert!(rules.budget(8, Difficulty::Normal) > rules.budget(0, Difficulty::Normal));
+        assert!(rules.budget(4, Difficulty::Hard) > rules.budget(4, Difficulty::Normal));
+        assert!(rules.budget(4, Difficulty::Easy) < rules.budget(4, Difficulty::Normal));
+    }
+
+    #[test]
+    fn encounters_stay_in_budget_and_cap() {
+        let rules = EncounterRules {
+            max_enemies: 2,
+            ..default()
+        };
+        let mut rng = RandomSource::seed_from_u64(0xe4c0);
+
+        for budget in 0..20 {
+            for _ in 0..20 {
+                let enemies = rules.roll_enemies(&mut rng, budget, None);
+
+                assert!(!enemies.is_empty());
+                assert!(enemies.len() <= rules.max_enemies);
+                assert!(enemies.len() == 1 || rules.cost(&enemies) <= budget);
+            }
+        }
+    }
+
+    #[test]
+    fn tiny_budgets_get_the_cheapest_enemy() {
+        let rules = EncounterRules::default();
+        let mut rng = RandomSource::seed_from_u64(1);
+
+        assert_eq!(*rules.roll_enemies(&mut rng, 0, None), [ActorName::Goblin]);
+        assert_eq!(
+            *rules.roll_enemies(&mut rng, 0, Some(Biome::Ruins)),
+            [ActorName::Construct]
+        );
+    }
+
+    #[test]
+    fn biomes_spawn_from_their_own_pools() {
+        let rules = EncounterRules::default();
+        let mut rng = RandomSource::seed_from_u64(0xb10e);
+
+        for biome in Biome::iter() {
+            let pool = rules.pool(Some(biome));
+            for _ in 0..200 {
+                let enemies = rules.roll_enemies(&mut rng, 12, Some(biome));
+                assert!(
+                    enemies
+                        .iter()
+                        .all(|name| pool.iter().any(|e| e.name == *name))
+                );
+            }
+        }
+
+        assert_eq!(rules.pool(None), rules.enemies);
+        assert_eq!(rules.pool(Some(Biome::Halls)), rules.enemies);
+        let ruins = rules.pool(Some(Biome::Ruins));
+        assert!(ruins.iter().all(|enemy| enemy.name != ActorName::Goblin));
+        assert!(ruins.iter().any(|enemy| enemy.name == ActorName::Construct));
+    }
+
+    #[test]
+    fn biome_enemies_are_spread_evenly() {
+        let rules = EncounterRules {
+            max_enemies: 1,
+            ..default()
+        };
+        let mut rng = RandomSource::seed_from_u64(0x5a7e);
+
+        for biome in [Biome::Lair, Biome::Ruins] {
+            let pool = rules.pool(Some(biome));
+            let mut counts = vec![0_usize; pool.len()];
+            for _ in 0..3000 {
+                let enemies = rules.roll_enemies(&mut rng, 12, Some(biome));
+                let index = pool.iter().position(|e| e.name == enemies[0]).unwrap();
+                counts[index] += 1;
+            }
+
+            let expected = 3000 / pool.len();
+            for count in counts {
+                assert!(
+                    count.abs_diff(expected) < expected / 10,
+                    "{biome:?}: {count}"
+                );
+            }
+        }
+    }
+}
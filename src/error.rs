@@ -0,0 +1,112 @@
+//! The error type shared by the whole game, and where errors from systems end up.
+//!
+//! Systems that can fail return [`Result`](bevy::ecs::error::Result) and use `?` on a [`GameError`].
+//! Those are shown to the player as a notification instead of crashing the game.
+//! Any other error still panics, as it is a bug.
+
+#[cfg(feature = "sqlite")]
+use crate::database::{OpenError, SetKvError};
+use crate::prelude::*;
+use bevy::ecs::error::{BevyError, ErrorContext, GLOBAL_ERROR_HANDLER};
+use bevy::ecs::query::{QueryEntityError, QuerySingleError};
+use bevy::ecs::system::{RegisteredSystemError, SystemInput};
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::TilePos;
+use std::sync::Mutex;
+use thiserror::Error;
+
+pub struct ErrorPlugin;
+
+impl Plugin for ErrorPlugin {
+    fn build(&self, app: &mut App) {
+        // Already set if another app in this process got here first, like in the tests.
+        let _ = GLOBAL_ERROR_HANDLER.set(route_error);
+
+        app.add_systems(Last, show_errors);
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum GameError {
+    #[error("Database error: {0}")]
+    Database(#[from] DatabaseError),
+    #[cfg(feature = "sqlite")]
+    #[error("Failed to open the database: {0}")]
+    OpenDatabase(#[from] OpenError),
+    #[cfg(feature = "sqlite")]
+    #[error("Failed to save a setting: {0}")]
+    SetKv(#[from] SetKvError),
+    #[error("Expected exactly one match: {0}")]
+    QuerySingle(#[from] QuerySingleError),
+    #[error("Entity not found: {0}")]
+    QueryEntity(#[from] QueryEntityError),
+    #[error("Failed to run a system: {0}")]
+    System(String),
+    #[error("The saved room at ({}, {}) is not on the map", .0.x, .0.y)]
+    MissingRoom(TilePos),
+}
+
+impl<I: SystemInput + 'static, O: 'static> From<RegisteredSystemError<I, O>> for GameError {
+    fn from(err: RegisteredSystemError<I, O>) -> Self {
+        GameError::System(err.to_string())
+    }
+}
+
+/// Errors waiting to be shown, since the error handler can't reach the world.
+static PENDING_ERRORS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Logs the error and shows it to the player.
+/// For code that can't return its error to a system.
+pub fn report_error(error: GameError) {
+    warn!("{error}");
+    PENDING_ERRORS.lock().unwrap().push(error.to_string());
+}
+
+fn route_error(error: BevyError, ctx: ErrorContext) {
+    match error.downcast_ref::<GameError>() {
+        Some(game_error) => {
+            warn!("{} failed with: {game_error}", ctx.name());
+            PENDING_ERRORS.lock().unwrap().push(game_error.to_string());
+        }
+        None => bevy::ecs::error::panic(error, ctx),
+    }
+}
+
+fn show_errors(mut notifications: ResMut<Notifications>) {
+    for error in PENDING_ERRORS.lock().unwrap().drain(..) {
+        notifications.error(error);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bevy::ecs::component::Tick;
+
+    #[test]
+    fn game_errors_become_notifications() {
+        // The global handler may already be set by another test, so route the error directly.
+        let error = GameError::System("Cached system was not found".into());
+        let ctx = ErrorContext::System {
+            name: "test_system".into(),
+            last_run: Tick::new(0),
+        };
+        route_error(error.into(), ctx);
+
+        let mut world = World::new();
+        world.init_resource::<Notifications>();
+        world.run_system_cached(show_errors).unwrap();
+
+        assert!(!world.resource::<Notifications>().is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn other_errors_still_panic() {
+        let ctx = ErrorContext::System {
+            name: "test_system".into(),
+            last_run: Tick::new(0),
+        };
+        route_error("not a game error".into(), ctx);
+    }
+}
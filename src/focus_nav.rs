@@ -0,0 +1,391 @@
+//! Moving between buttons with the keyboard or a gamepad, for when there's no mouse to hand.
+//!
+//! The move controls walk the [`NavFocus`] to the nearest button in that direction,
+//! and [`Control::Select`] clicks it. While navigating a room its doors can be focused too,
+//! aimed at from the middle of the room so holding up and right picks the north east door.
+
+use crate::game::{CombatState, GameState, PauseState};
+use crate::prelude::*;
+use crate::room::{EntranceDirection, ROOM_CENTER};
+use bevy::input_focus::InputFocus;
+use bevy::picking::backend::HitData;
+use bevy::picking::pointer::{Location, PointerId};
+use bevy::render::camera::NormalizedRenderTarget;
+use bevy::window::{PrimaryWindow, WindowRef};
+use bevy_ecs_tilemap::prelude::*;
+use std::time::Duration;
+
+const FOCUS_OUTLINE_WIDTH: f32 = 3.0;
+const FOCUS_OUTLINE_OFFSET: f32 = 2.0;
+/// How much more being off to the side counts against a target than being further along.
+const ACROSS_WEIGHT: f32 = 2.0;
+
+pub struct FocusNavPlugin;
+
+impl Plugin for FocusNavPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NavFocus>().add_systems(
+            Update,
+            (
+                move_focus,
+                select_focus,
+                show_focus.run_if(resource_changed::<NavFocus>),
+            )
+                .chain()
+                .run_if(
+                    in_state(AppState::Menu)
+                        .or(in_state(PauseState::Paused))
+                        .or(in_state(CombatState::SpawnMenu))
+                        .or(in_state(GameState::Navigation)),
+                ),
+        );
+    }
+}
+
+/// The button or door the move controls have landed on, if any.
+#[derive(Resource, Default, Debug, Deref, DerefMut)]
+pub struct NavFocus(pub Option<Entity>);
+
+/// Marks what [`show_focus`] has outlined or tinted, to undo it once focus moves on.
+#[derive(Component)]
+struct FocusShown;
+
+/// The target nearest to `from` in `direction`, preferring ones straight along it.
+/// Positions are on screen, so up is negative y.
+pub fn nearest_in_direction(
+    from: Vec2,
+    direction: Vec2,
+    targets: impl IntoIterator<Item = (Entity, Vec2)>,
+) -> Option<Entity> {
+    targets
+        .into_iter()
+        .filter_map(|(entity, position)| {
+            let offset = position - from;
+            let along = offset.dot(direction);
+            (along > f32::EPSILON).then(|| {
+                (
+                    entity,
+                    along + offset.perp_dot(direction).abs() * ACROSS_WEIGHT,
+                )
+            })
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(entity, _)| entity)
+}
+
+/// The move controls held down, added up into the direction they point on screen.
+fn held_direction(key: &ControlState) -> Option<Vec2> {
+    [
+        (Control::MoveUp, Vec2::NEG_Y),
+        (Control::MoveDown, Vec2::Y),
+        (Control::MoveLeft, Vec2::NEG_X),
+        (Control::MoveRight, Vec2::X),
+    ]
+    .into_iter()
+    .filter(|(control, _)| key.pressed(*control))
+    .map(|(_, direction)| direction)
+    .sum::<Vec2>()
+    .try_normalize()
+}
+
+type RoomMapParams<'a> = (
+    &'a TilemapSize,
+    &'a TilemapGridSize,
+    &'a TilemapTileSize,
+    &'a TilemapType,
+    &'a TilemapAnchor,
+    &'a GlobalTransform,
+);
+
+/// Where a tile of the room is on screen.
+fn room_tile_on_screen(
+    pos: &TilePos,
+    room_map: &RoomMapParams,
+    (camera, camera_transform): &(&Camera, &GlobalTransform),
+) -> Option<Vec2> {
+    let (map_size, grid_size, tile_size, map_type, anchor, map_transform) = *room_map;
+    let world = map_transform.transform_point(
+        pos.center_in_world(map_size, grid_size, tile_size, map_type, anchor)
+            .extend(0.0),
+    );
+    camera.world_to_viewport(camera_transform, world).ok()
+}
+
+/// Everything that can be focused right now, with where it is on screen.
+fn focus_targets(
+    buttons: &Query<
+        (
+            Entity,
+            &GlobalTransform,
+            &ComputedNode,
+            &InheritedVisibility,
+        ),
+        With<Button>,
+    >,
+    doors: &Query<(Entity, &TilePos, &TileVisible), With<EntranceDirection>>,
+    room_map: Option<&RoomMapParams>,
+    camera: Option<&(&Camera, &GlobalTransform)>,
+    in_room: bool,
+) -> Vec<(Entity, Vec2)> {
+    let mut targets: Vec<(Entity, Vec2)> = buttons
+        .iter()
+        .filter(|(_, _, node, visibility)| visibility.get() && node.size() != Vec2::ZERO)
+        .map(|(entity, transform, node, _)| {
+            (
+                entity,
+                transform.translation().truncate() * node.inverse_scale_factor(),
+            )
+        })
+        .collect();
+
+    if let (true, Some(room_map), Some(camera)) = (in_room, room_map, camera) {
+        targets.extend(doors.iter().filter(|(_, _, visible)| visible.0).filter_map(
+            |(entity, pos, _)| {
+                room_tile_on_screen(pos, room_map, camera).map(|position| (entity, position))
+            },
+        ));
+    }
+
+    targets
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn move_focus(
+    key: Res<ControlState>,
+    input_focus: Option<Res<InputFocus>>,
+    mut focus: ResMut<NavFocus>,
+    buttons: Query<
+        (
+            Entity,
+            &GlobalTransform,
+            &ComputedNode,
+            &InheritedVisibility,
+        ),
+        With<Button>,
+    >,
+    doors: Query<(Entity, &TilePos, &TileVisible), With<EntranceDirection>>,
+    room_map: Option<
+        Single<
+            (
+                &TilemapSize,
+                &TilemapGridSize,
+                &TilemapTileSize,
+                &TilemapType,
+                &TilemapAnchor,
+                &GlobalTransform,
+            ),
+            With<RoomTilemap>,
+        >,
+    >,
+    camera: Option<Single<(&Camera, &GlobalTransform), With<MainCameraMarker>>>,
+    game_state: Option<Res<State<GameState>>>,
+    pause: Option<Res<State<PauseState>>>,
+) {
+    // Typing into a text box shouldn't move the focus.
+    if input_focus.is_some_and(|input_focus| input_focus.0.is_some()) {
+        return;
+    }
+
+    let in_room = game_state.is_some_and(|state| *state.get() == GameState::Navigation)
+        && pause.is_some_and(|pause| *pause.get() == PauseState::Running);
+    let targets = focus_targets(
+        &buttons,
+        &doors,
+        room_map.as_deref(),
+        camera.as_deref(),
+        in_room,
+    );
+
+    let current = focus.and_then(|focused| {
+        targets
+            .iter()
+            .find(|(entity, _)| *entity == focused)
+            .copied()
+    });
+    if current.is_none() && focus.is_some() {
+        focus.0 = None;
+    }
+
+    let direction = [
+        (Control::MoveUp, Vec2::NEG_Y),
+        (Control::MoveDown, Vec2::Y),
+        (Control::MoveLeft, Vec2::NEG_X),
+        (Control::MoveRight, Vec2::X),
+    ]
+    .into_iter()
+    .find(|(control, _)| key.just_pressed(*control))
+    .map(|(_, direction)| direction);
+    let Some(direction) = direction else {
+        return;
+    };
+
+    // In a room the doors are aimed at from its middle, unless a button has the focus.
+    let room_center = match (in_room, room_map.as_deref(), camera.as_deref()) {
+        (true, Some(room_map), Some(camera)) => room_tile_on_screen(&ROOM_CENTER, room_map, camera),
+        _ => None,
+    };
+    if let Some(center) =
+        room_center.filter(|_| current.is_none_or(|(entity, _)| doors.contains(entity)))
+    {
+        let doors = targets
+            .into_iter()
+            .filter(|(entity, _)| doors.contains(*entity));
+        let aim = held_direction(&key).unwrap_or(direction);
+        if let Some(door) = nearest_in_direction(center, aim, doors) {
+            focus.0 = Some(door);
+        }
+        return;
+    }
+
+    let next = match current {
+        Some((_, from)) => nearest_in_direction(from, direction, targets),
+        // Start from the top left, where menus begin.
+        None => targets
+            .into_iter()
+            .min_by(|(_, a), (_, b)| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)))
+            .map(|(entity, _)| entity),
+    };
+    if next.is_some() {
+        focus.0 = next;
+    }
+}
+
+/// Clicks whatever is focused, so it does what the mouse would.
+fn select_focus(
+    mut commands: Commands,
+    key: Res<ControlState>,
+    input_focus: Option<Res<InputFocus>>,
+    focus: Res<NavFocus>,
+    window: Option<Single<Entity, With<PrimaryWindow>>>,
+    camera: Option<Single<Entity, With<MainCameraMarker>>>,
+) {
+    if !key.just_pressed(Control::Select)
+        || input_focus.is_some_and(|input_focus| input_focus.0.is_some())
+    {
+        return;
+    }
+    let (Some(target), Some(window), Some(camera)) = (focus.0, window, camera) else {
+        return;
+    };
+    let Some(window) = WindowRef::Primary.normalize(Some(*window)) else {
+        return;
+    };
+
+    commands.trigger_targets(
+        Pointer::new(
+            PointerId::Mouse,
+            Location {
+                target: NormalizedRenderTarget::Window(window),
+                position: Vec2::ZERO,
+            },
+            target,
+            Click {
+                button: PointerButton::Primary,
+                hit: HitData::new(*camera, 0.0, None, None),
+                duration: Duration::ZERO,
+            },
+        ),
+        target,
+    );
+}
+
+/// Outlines the focused button, or tints the focused door.
+fn show_focus(
+    mut commands: Commands,
+    style: Res<Style>,
+    focus: Res<NavFocus>,
+    shown: Query<Entity, With<FocusShown>>,
+    nodes: Query<(), With<Node>>,
+    mut doors: Query<&mut TileColor, With<EntranceDirection>>,
+) {
+    for entity in shown.iter() {
+        if let Ok(mut color) = doors.get_mut(entity) {
+            *color = TileColor::default();
+        }
+        commands.entity(entity).remove::<(FocusShown, Outline)>();
+    }
+
+    let Some(focused) = focus.0 else {
+        return;
+    };
+
+    if let Ok(mut color) = doors.get_mut(focused) {
+        color.0 = style.focus_color;
+    } else if nodes.contains(focused) {
+        commands.entity(focused).insert(Outline::new(
+            Val::Px(FOCUS_OUTLINE_WIDTH),
+            Val::Px(FOCUS_OUTLINE_OFFSET),
+            style.focus_color,
+        ));
+    } else {
+        return;
+    }
+    commands.entity(focused).insert(FocusShown);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn focus_moves_to_the_nearest_in_line() {
+        let mut world = World::new();
+        let [above, right, far_right, off_to_the_side] = [(); 4].map(|_| world.spawn_empty().id());
+        let targets = [
+            (above, Vec2::new(0.0, -50.0)),
+            (right, Vec2::new(100.0, 0.0)),
+            (far_right, Vec2::new(300.0, 0.0)),
+            (off_to_the_side, Vec2::new(60.0, 80.0)),
+        ];
+
+        assert_eq!(
+            nearest_in_direction(Vec2::ZERO, Vec2::X, targets),
+            Some(right)
+        );
+        assert_eq!(
+            nearest_in_direction(Vec2::ZERO, Vec2::NEG_Y, targets),
+            Some(above)
+        );
+        assert_eq!(
+            nearest_in_direction(Vec2::ZERO, Vec2::Y, targets),
+            Some(off_to_the_side)
+        );
+        assert_eq!(nearest_in_direction(Vec2::ZERO, Vec2::NEG_X, targets), None);
+    }
+
+    #[test]
+    fn held_directions_aim_at_the_doors_between_them() {
+        let mut world = World::new();
+        // The doors of a room around its middle, on screen.
+        let doors: Vec<(Entity, Vec2)> = [30.0_f32, 90.0, 150.0, 210.0, 270.0, 330.0]
+            .map(|degrees| {
+                let angle = degrees.to_radians();
+                (
+                    world.spawn_empty().id(),
+                    Vec2::new(angle.cos(), -angle.sin()) * 100.0,
+                )
+            })
+            .into();
+        let [north_east, north, _, south_west, south, _] = [0, 1, 2, 3, 4, 5].map(|i| doors[i].0);
+
+        let aim = |controls: &[Control]| {
+            let mut key = ControlState::default();
+            for control in controls {
+                key.press(*control, 1.0);
+            }
+            nearest_in_direction(Vec2::ZERO, held_direction(&key)?, doors.clone())
+        };
+
+        assert_eq!(aim(&[Control::MoveUp]), Some(north));
+        assert_eq!(aim(&[Control::MoveDown]), Some(south));
+        assert_eq!(
+            aim(&[Control::MoveUp, Control::MoveRight]),
+            Some(north_east)
+        );
+        assert_eq!(
+            aim(&[Control::MoveDown, Control::MoveLeft]),
+            Some(south_west)
+        );
+        assert_eq!(aim(&[Control::MoveUp, Control::MoveDown]), None);
+    }
+}
@@ -2,6 +2,7 @@ use super::*;
 use crate::embed_asset;
 use crate::menu::*;
 use crate::prelude::*;
+use crate::rules;
 use bevy::prelude::*;
 use bevy_ecs_tilemap::prelude::*;
 use rand::Rng;
@@ -14,6 +15,12 @@ pub const BUTTON_IMAGE_PATH: &str = "embedded://assets/sprites/buttons.png";
 pub const GAMEOVER_IMAGE_PATH: &str = "embedded://assets/sprites/Game Over.png";
 pub const VICTORY_IMAGE_PATH: &str = "embedded://assets/sprites/Victory.png";
 
+/// How far above its target the selection marker floats.
+const TARGET_MARKER_OFFSET: f32 = 60.0;
+const TARGET_MARKER_SIZE: f32 = 16.0;
+/// Tint on the actors that can be chosen as the target.
+const TARGET_HIGHLIGHT: Color = Color::srgb(1.0, 0.85, 0.55);
+
 pub struct AttackOptionsPlugin;
 
 impl Plugin for AttackOptionsPlugin {
@@ -24,33 +31,127 @@ impl Plugin for AttackOptionsPlugin {
         embed_asset!(app, "assets/sprites/buttons.png");
         embed_asset!(app, "assets/sprites/Game Over.png");
         embed_asset!(app, "assets/sprites/Victory.png");
+
+        app.add_systems(
+            OnEnter(CombatState::ChooseTarget),
+            (spawn_target_ui, highlight_candidates),
+        )
+        .add_systems(
+            Update,
+            (
+                cycle_target.run_if(in_state(PauseState::Running)),
+                move_target_marker,
+            )
+                .chain()
+                .run_if(in_state(CombatState::ChooseTarget)),
+        )
+        .add_systems(
+            OnExit(CombatState::ChooseTarget),
+            (
+                remove_resource::<TargetChoice>,
+                clear_target,
+                clear_candidates,
+            ),
+        )
+        .add_observer(hover_target)
+        .add_observer(click_target);
     }
 }
 
 #[derive(Component)]
 pub struct AttackMenu;
 
+/// The actor currently chosen as the target.
 #[derive(Component)]
 pub struct TargetActor;
 
+/// An actor that can be clicked to choose them as the target,
+/// highlighted until the choice is made.
+#[derive(Component)]
+pub struct TargetCandidate {
+    /// The sprite's color before it was highlighted.
+    base: Color,
+}
+
+/// Which attack is waiting on a target.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PendingAttack {
+    Basic,
+    Special,
+}
+
+/// The targets the player is choosing between, in turn order.
+#[derive(Resource, Debug)]
+pub struct TargetChoice {
+    pub attack: PendingAttack,
+    pub candidates: Vec<Entity>,
+    pub selected: usize,
+}
+
+impl TargetChoice {
+    pub fn selected(&self) -> Entity {
+        self.candidates[self.selected]
+    }
+
+    /// Selects the actor, if they are one of the candidates.
+    pub fn select(&mut self, actor: Entity) -> bool {
+        match self
+            .candidates
+            .iter()
+            .position(|candidate| *candidate == actor)
+        {
+            Some(index) => {
+                self.selected = index;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Moves the selection by `step`, wrapping around at either end.
+    pub fn cycle(&mut self, step: isize) {
+        let len = self.candidates.len() as isize;
+        self.selected = (self.selected as isize + step).rem_euclid(len) as usize;
+    }
+
+    pub fn action(&self) -> Action {
+        let target = self.selected();
+        match self.attack {
+            PendingAttack::Basic => Action::Attack { target },
+            PendingAttack::Special => Action::SpecialAction { target },
+        }
+    }
+}
+
+#[derive(Component)]
+struct TargetMarker;
+
+/// Asks the player to confirm their special move before it is used.
+#[derive(Component)]
+struct SpecialConfirm;
+
 pub fn create_attack_menu(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
-    mut next_state: ResMut<NextState<CombatState>>,
+    style: Res<Style>,
+    layout: Res<ScreenLayout>,
+    actor: Single<(&ActorName, Option<&Energy>), With<ActingActor>>,
 ) {
+    let (actor_name, energy) = *actor;
+    let special = SpecialAction::from_name(*actor_name);
+    let special_name = special.map_or_else(|| "Special Move".to_string(), |s| s.to_string());
+    let special_tooltip = Tooltip::new(special_name.clone()).with_body(format!(
+        "{}\nCosts {} energy",
+        special.map_or("", |special| special.description()),
+        rules::SPECIAL_COST
+    ));
+    let special_tint = match Energy::can_afford_special(energy) {
+        true => Color::WHITE,
+        false => Color::WHITE.with_alpha(0.4),
+    };
+
     commands
-        .spawn((
-            Node {
-                width: Val::Percent(37.5),
-                height: Val::Percent(100.0),
-                align_items: AlignItems::Center,
-                flex_direction: FlexDirection::Column,
-                justify_content: JustifyContent::Center,
-                align_content: AlignContent::Center,
-                ..default()
-            },
-            AttackMenu,
-        ))
+        .spawn((layout.side_panel(), SidePanel, AttackMenu))
         .with_children(|builder| {
             builder.spawn((
                 ImageNode {
@@ -85,6 +186,7 @@ pub fn create_attack_menu(
                 .spawn((
                     ImageNode {
                         image: asset_server.load(SPECIAL_MOVE_IMAGE_PATH),
+                        color: special_tint,
                         ..default()
                     },
                     Node {
@@ -94,8 +196,75 @@ pub fn create_attack_menu(
                         ..default()
                     },
                     Button,
+                    special_tooltip,
                 ))
                 .observe(special_move);
+
+            builder.spawn((
+                Text::new(special_name),
+                style.font(24.0),
+                TextColor(style.text_color),
+                Pickable::IGNORE,
+            ));
+
+            builder.spawn((
+                Text::new(format!(
+                    "Energy {}/{}",
+                    energy.map_or(0, |energy| energy.0),
+                    rules::MAX_ENERGY
+                )),
+                style.font(20.0),
+                TextColor(style.text_color),
+                Pickable::IGNORE,
+            ));
+
+            builder.spawn(Node::default()).with_children(|builder| {
+                for (label, tooltip, action) in [
+                    (
+                        "Defend",
+                        "Doubles block chance until your next turn",
+                        Action::Defend,
+                    ),
+                    (
+                        "Flee",
+                        "Try to get away, faster parties get away more often",
+                        Action::Flee,
+                    ),
+                ] {
+                    builder
+                        .spawn((
+                            Button,
+                            Node {
+                                width: Val::Px(110.0),
+                                height: Val::Px(40.0),
+                                margin: UiRect::all(Val::Px(5.0)),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            BackgroundColor(style.button_color),
+                            Tooltip::new(label).with_body(tooltip),
+                            children![(
+                                Text::new(label),
+                                style.font(22.0),
+                                TextColor(style.text_color),
+                                Pickable::IGNORE,
+                            )],
+                        ))
+                        .observe(
+                            move |mut click: Trigger<Pointer<Click>>,
+                                  mut commands: Commands,
+                                  mut next_state: ResMut<NextState<CombatState>>| {
+                                click.propagate(false);
+
+                                if click.button == PointerButton::Primary {
+                                    commands.insert_resource(ActingActorAction(action));
+                                    next_state.set(CombatState::PerformAction);
+                                }
+                            },
+                        );
+                }
+            });
         });
 }
 
@@ -103,11 +272,25 @@ pub fn despawn_attack_menu(mut commands: Commands, menu_entity: Single<Entity, W
     commands.entity(*menu_entity).despawn();
 }
 
+/// How the run went, under the game over and victory banners.
+fn run_stats_text(style: &Style, stats: &RunStats) -> impl Bundle {
+    (
+        Text::new(stats.summary()),
+        style.font(28.0),
+        TextColor(style.text_color),
+        TextLayout::new_with_justify(JustifyText::Center),
+        Node {
+            margin: UiRect::all(Val::Px(15.0)),
+            ..default()
+        },
+    )
+}
+
 pub fn spawn_gameover_screen(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     style: Res<Style>,
-    keybinds: Res<Controls>,
+    stats: Option<Res<RunStats>>,
 ) {
     commands
         .spawn((
@@ -135,11 +318,12 @@ pub fn spawn_gameover_screen(
                 },
             ));
 
+            if let Some(stats) = &stats {
+                builder.spawn(run_stats_text(&style, stats));
+            }
+
             builder
-                .spawn((Node::default(),))
-                .with_children(|builder| {
-                    style.display_keybind(builder, &Keybind(Control::Pause, keybinds.pause))
-                })
+                .spawn((Node::default(), KeybindHint(Control::Pause)))
                 .observe(exit_gameover);
         });
 }
@@ -147,9 +331,9 @@ pub fn spawn_gameover_screen(
 pub fn spawn_victory_screen(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
-
     style: Res<Style>,
-    keybinds: Res<Controls>,
+    skirmish: Option<Res<Skirmish>>,
+    stats: Option<Res<RunStats>>,
 ) {
     commands
         .spawn((
@@ -178,81 +362,382 @@ pub fn spawn_victory_screen(
                 },
             ));
 
+            if let Some(stats) = &stats {
+                builder.spawn(run_stats_text(&style, stats));
+            }
+
             builder
-                .spawn((Node {
-                    align_content: AlignContent::Center,
-                    ..default()
-                },))
-                .with_children(|builder| {
-                    style.display_keybind(builder, &Keybind(Control::Pause, keybinds.pause))
-                })
+                .spawn((
+                    Node {
+                        align_content: AlignContent::Center,
+                        ..default()
+                    },
+                    KeybindHint(Control::Pause),
+                ))
                 .observe(exit_victory);
+
+            // A skirmish has no map to keep going on.
+            if skirmish.is_some() {
+                return;
+            }
+
+            builder
+                .spawn((
+                    Button,
+                    Node {
+                        width: Val::Px(300.0),
+                        height: Val::Px(65.0),
+                        margin: UiRect::all(Val::Px(15.0)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    BackgroundColor(style.button_color),
+                    children![(
+                        Text::new("Keep Going"),
+                        style.font(33.0),
+                        TextColor(style.text_color),
+                        Pickable::IGNORE
+                    )],
+                ))
+                .observe(start_endless_mode);
         });
 }
 
 fn basic_attack(
     mut click: Trigger<Pointer<Click>>,
     mut commands: Commands,
-    rng: ResMut<EventRng>,
-    queue: ResMut<TurnOrder>,
-    active_actor: Single<(Entity, &Team), With<ActingActor>>,
+    queue: Res<TurnOrder>,
+    active_actor: Single<&Team, With<ActingActor>>,
     actor_q: Query<(&Health, &Team)>,
+    state: Res<State<CombatState>>,
     mut next_state: ResMut<NextState<CombatState>>,
 ) {
     click.propagate(false);
 
     if click.button == PointerButton::Primary {
-        commands.insert_resource(ActingActorAction(Action::Attack {
-            target: choose_target(rng, queue, active_actor, actor_q),
-        }));
-        next_state.set(CombatState::PerformAction);
+        let candidates = attack_targets(&queue, *active_actor, &actor_q);
+        start_choosing_target(
+            &mut commands,
+            PendingAttack::Basic,
+            candidates,
+            &state,
+            &mut next_state,
+        );
     }
 }
 
 fn special_move(
     mut click: Trigger<Pointer<Click>>,
     mut commands: Commands,
+    style: Res<Style>,
+    menu: Single<Entity, With<AttackMenu>>,
+    open: Query<(), With<SpecialConfirm>>,
+    actor: Single<(&ActorName, Option<&Energy>), With<ActingActor>>,
+) {
+    click.propagate(false);
+
+    let (actor_name, energy) = *actor;
+    if click.button != PointerButton::Primary
+        || !open.is_empty()
+        || !Energy::can_afford_special(energy)
+    {
+        return;
+    }
+
+    let Some(special) = SpecialAction::from_name(*actor_name) else {
+        return;
+    };
+
+    let button_node = Node {
+        width: Val::Px(120.0),
+        height: Val::Px(50.0),
+        margin: UiRect::all(Val::Px(5.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let text_style = (
+        style.font(24.0),
+        TextColor(style.text_color),
+        TextLayout::new_with_justify(JustifyText::Center),
+    );
+
+    // A child of the menu, so it goes away with it once the turn is taken.
+    commands.entity(*menu).with_children(|builder| {
+        builder
+            .spawn((
+                Node {
+                    max_width: Val::Px(300.0),
+                    padding: UiRect::all(Val::Px(10.0)),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                BackgroundColor(style.background_color),
+                SpecialConfirm,
+            ))
+            .with_children(|builder| {
+                builder.spawn((
+                    Text::new(format!("{special}\n{}", special.description())),
+                    text_style.clone(),
+                ));
+
+                builder.spawn(Node::default()).with_children(|builder| {
+                    builder
+                        .spawn((
+                            Button,
+                            button_node.clone(),
+                            BackgroundColor(style.button_color),
+                            children![(Text::new("Use"), text_style.clone(), Pickable::IGNORE)],
+                        ))
+                        .observe(confirm_special);
+
+                    builder
+                        .spawn((
+                            Button,
+                            button_node.clone(),
+                            BackgroundColor(style.button_color),
+                            children![(Text::new("Cancel"), text_style.clone(), Pickable::IGNORE)],
+                        ))
+                        .observe(cancel_special);
+                });
+            });
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn confirm_special(
+    mut click: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    state: Res<State<CombatState>>,
     mut next_state: ResMut<NextState<CombatState>>,
     rng: ResMut<EventRng>,
     queue: ResMut<TurnOrder>,
     active_actor: Single<(Entity, &Team, &ActorName), With<ActingActor>>,
     actor_q: Query<(&Health, &Team)>,
+    confirm: Query<Entity, With<SpecialConfirm>>,
 ) {
     click.propagate(false);
 
-    if click.button == PointerButton::Primary {
+    if click.button != PointerButton::Primary {
+        return;
+    }
+
+    for entity in confirm.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let (_, team, name) = *active_actor;
+    // The Priestess heals her own party, so there's no enemy to pick.
+    if *name == ActorName::Priestess {
         commands.insert_resource(ActingActorAction(Action::SpecialAction {
             target: choose_special_target(rng, queue, active_actor, actor_q),
         }));
         next_state.set(CombatState::PerformAction);
+    } else {
+        let candidates = attack_targets(&queue, team, &actor_q);
+        start_choosing_target(
+            &mut commands,
+            PendingAttack::Special,
+            candidates,
+            &state,
+            &mut next_state,
+        );
     }
 }
 
-pub fn choose_target(
-    mut rng: ResMut<EventRng>,
-    queue: ResMut<TurnOrder>,
-    active_actor: Single<(Entity, &Team), With<ActingActor>>,
-    actor_q: Query<(&Health, &Team)>,
-) -> Entity {
-    //remove any current action
-    let (_, team) = *active_actor;
-    let targets: Vec<Entity> = queue
+fn cancel_special(
+    mut click: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    confirm: Query<Entity, With<SpecialConfirm>>,
+) {
+    click.propagate(false);
+
+    if click.button != PointerButton::Primary {
+        return;
+    }
+
+    for entity in confirm.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn start_choosing_target(
+    commands: &mut Commands,
+    attack: PendingAttack,
+    candidates: Vec<Entity>,
+    state: &State<CombatState>,
+    next_state: &mut NextState<CombatState>,
+) {
+    if candidates.is_empty() {
+        warn!("No targets to choose from");
+        return;
+    }
+
+    commands.insert_resource(TargetChoice {
+        attack,
+        candidates,
+        selected: 0,
+    });
+
+    // Re-entering would remove the new choice on the way out.
+    if *state.get() != CombatState::ChooseTarget {
+        next_state.set(CombatState::ChooseTarget);
+    }
+}
+
+/// The living actors that `team` can attack, in turn order.
+pub fn attack_targets(
+    queue: &TurnOrder,
+    team: &Team,
+    actor_q: &Query<(&Health, &Team)>,
+) -> Vec<Entity> {
+    queue
         .queue()
         .iter()
-        .filter_map(|&entity| {
-            if let Ok((health, target_team)) = actor_q.get(entity) {
-                if health.is_alive() && *target_team != *team {
-                    Some(entity)
-                } else {
-                    None
-                }
-            } else {
-                None
-            }
+        .copied()
+        .filter(|&entity| {
+            actor_q
+                .get(entity)
+                .is_ok_and(|(health, target_team)| health.is_alive() && target_team != team)
         })
-        .collect();
+        .collect()
+}
+
+fn spawn_target_ui(mut commands: Commands, style: Res<Style>) {
+    commands.spawn((
+        Sprite::from_color(style.accent_color, Vec2::splat(TARGET_MARKER_SIZE)),
+        Transform::from_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_4)),
+        StateScoped(CombatState::ChooseTarget),
+        TargetMarker,
+    ));
 
-    targets[rng.random_range(0..targets.len())]
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(20.0),
+            justify_self: JustifySelf::Center,
+            ..default()
+        },
+        Text::new("Click a target, or Left/Right to choose and Select to attack"),
+        StateScoped(CombatState::ChooseTarget),
+        style.font(33.0),
+        TextColor(style.text_color),
+    ));
+}
+
+/// Cycles through the targets with left and right, or the shoulder buttons,
+/// and attacks the selected one on select.
+fn cycle_target(
+    mut commands: Commands,
+    key: Res<ControlState>,
+    gamepads: Query<&Gamepad>,
+    choice: Option<ResMut<TargetChoice>>,
+    targeted: Query<Entity, With<TargetActor>>,
+    mut next_state: ResMut<NextState<CombatState>>,
+) {
+    let Some(mut choice) = choice else {
+        return;
+    };
+
+    let shoulder = |button| gamepads.iter().any(|gamepad| gamepad.just_pressed(button));
+    if key.just_pressed(Control::MoveLeft) || shoulder(GamepadButton::LeftTrigger) {
+        choice.cycle(-1);
+    }
+    if key.just_pressed(Control::MoveRight) || shoulder(GamepadButton::RightTrigger) {
+        choice.cycle(1);
+    }
+
+    let selected = choice.selected();
+    if !targeted.contains(selected) {
+        for entity in targeted.iter() {
+            commands.entity(entity).remove::<TargetActor>();
+        }
+        commands.entity(selected).insert(TargetActor);
+    }
+
+    if key.just_pressed(Control::Select) {
+        commands.insert_resource(ActingActorAction(choice.action()));
+        next_state.set(CombatState::PerformAction);
+    }
+}
+
+fn move_target_marker(
+    choice: Option<Res<TargetChoice>>,
+    actors: Query<&Transform, (With<Actor>, Without<TargetMarker>)>,
+    mut marker: Single<&mut Transform, With<TargetMarker>>,
+) {
+    let Some(target) = choice.and_then(|choice| actors.get(choice.selected()).ok()) else {
+        return;
+    };
+
+    marker.translation = target.translation + Vec3::new(0.0, TARGET_MARKER_OFFSET, 1.0);
+}
+
+fn highlight_candidates(
+    mut commands: Commands,
+    choice: Option<Res<TargetChoice>>,
+    mut sprites: Query<&mut Sprite, With<Actor>>,
+) {
+    let Some(choice) = choice else {
+        return;
+    };
+
+    for &candidate in choice.candidates.iter() {
+        if let Ok(mut sprite) = sprites.get_mut(candidate) {
+            commands
+                .entity(candidate)
+                .insert(TargetCandidate { base: sprite.color });
+            sprite.color = TARGET_HIGHLIGHT;
+        }
+    }
+}
+
+fn clear_candidates(
+    mut commands: Commands,
+    mut candidates: Query<(Entity, &TargetCandidate, &mut Sprite)>,
+) {
+    for (entity, candidate, mut sprite) in candidates.iter_mut() {
+        sprite.color = candidate.base;
+        commands.entity(entity).remove::<TargetCandidate>();
+    }
+}
+
+/// Moves the selection to the candidate under the pointer.
+fn hover_target(over: Trigger<Pointer<Over>>, choice: Option<ResMut<TargetChoice>>) {
+    if let Some(mut choice) = choice {
+        choice.select(over.target());
+    }
+}
+
+/// Attacks the candidate that was clicked.
+fn click_target(
+    mut click: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    choice: Option<ResMut<TargetChoice>>,
+    pause: Option<Res<State<PauseState>>>,
+    mut next_state: ResMut<NextState<CombatState>>,
+) {
+    let Some(mut choice) = choice else {
+        return;
+    };
+    if click.button != PointerButton::Primary
+        || pause.is_none_or(|pause| *pause.get() != PauseState::Running)
+        || !choice.select(click.target())
+    {
+        return;
+    }
+
+    click.propagate(false);
+    commands.insert_resource(ActingActorAction(choice.action()));
+    next_state.set(CombatState::PerformAction);
+}
+
+fn clear_target(mut commands: Commands, targeted: Query<Entity, With<TargetActor>>) {
+    for entity in targeted.iter() {
+        commands.entity(entity).remove::<TargetActor>();
+    }
 }
 
 pub fn choose_special_target(
@@ -328,3 +813,52 @@ fn exit_victory(
         update_appstate.set(AppState::Menu);
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn cycling_targets_wraps_around() {
+        let mut world = World::new();
+        let candidates = vec![world.spawn_empty().id(), world.spawn_empty().id()];
+        let mut choice = TargetChoice {
+            attack: PendingAttack::Basic,
+            candidates: candidates.clone(),
+            selected: 0,
+        };
+
+        choice.cycle(-1);
+        assert_eq!(choice.selected(), candidates[1]);
+        choice.cycle(1);
+        assert_eq!(
+            choice.action(),
+            Action::Attack {
+                target: candidates[0]
+            }
+        );
+    }
+
+    #[test]
+    fn only_candidates_can_be_clicked() {
+        let mut world = World::new();
+        let candidates = vec![world.spawn_empty().id(), world.spawn_empty().id()];
+        let party_member = world.spawn_empty().id();
+        let mut choice = TargetChoice {
+            attack: PendingAttack::Special,
+            candidates: candidates.clone(),
+            selected: 0,
+        };
+
+        assert!(!choice.select(party_member));
+        assert_eq!(choice.selected(), candidates[0]);
+
+        assert!(choice.select(candidates[1]));
+        assert_eq!(
+            choice.action(),
+            Action::SpecialAction {
+                target: candidates[1]
+            }
+        );
+    }
+}
@@ -0,0 +1,196 @@
+//! When the game saves itself, besides on reaching [`GameState::Navigation`].
+//!
+//! The [`SavePolicy`] is set in the settings, and can autosave every few minutes of play,
+//! save whenever the game is paused, and save when quitting. Games can also be saved
+//! by hand from the pause menu.
+
+use super::*;
+#[cfg(feature = "sqlite")]
+use crate::saving::{autosave_game, save_game};
+use crate::settings::SETTINGS_DB_TABLE;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use strum::Display;
+
+pub struct AutosavePlugin;
+
+impl Plugin for AutosavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PreStartup, setup_save_policy).add_systems(
+            Update,
+            save_policy_sync
+                .run_if(resource_changed::<SavePolicy>.and(not(resource_added::<SavePolicy>))),
+        );
+
+        #[cfg(feature = "sqlite")]
+        app.add_systems(OnEnter(AppState::Game), start_autosave_timer)
+            .add_systems(
+                Update,
+                tick_autosave
+                    .run_if(in_state(GameState::Navigation).or(in_state(GameState::Combat))),
+            )
+            .add_systems(
+                OnTransition {
+                    exited: PauseState::Running,
+                    entered: PauseState::Paused,
+                },
+                save_on_pause,
+            )
+            .add_systems(Last, save_on_exit.run_if(in_state(AppState::Game)))
+            .add_systems(OnExit(AppState::Game), remove_resource::<AutosaveTimer>);
+    }
+}
+
+/// How often the game saves itself while it is being played.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Display)]
+pub enum AutosaveInterval {
+    Off,
+    #[strum(to_string = "Every Minute")]
+    OneMinute,
+    #[default]
+    #[strum(to_string = "Every 5 Minutes")]
+    FiveMinutes,
+    #[strum(to_string = "Every 10 Minutes")]
+    TenMinutes,
+}
+
+impl AutosaveInterval {
+    /// The setting after this one, for cycling through them with a button.
+    pub fn next(self) -> Self {
+        match self {
+            AutosaveInterval::Off => AutosaveInterval::OneMinute,
+            AutosaveInterval::OneMinute => AutosaveInterval::FiveMinutes,
+            AutosaveInterval::FiveMinutes => AutosaveInterval::TenMinutes,
+            AutosaveInterval::TenMinutes => AutosaveInterval::Off,
+        }
+    }
+
+    /// How much play goes between autosaves, or `None` to never autosave.
+    pub fn duration(self) -> Option<Duration> {
+        let minutes = match self {
+            AutosaveInterval::Off => return None,
+            AutosaveInterval::OneMinute => 1,
+            AutosaveInterval::FiveMinutes => 5,
+            AutosaveInterval::TenMinutes => 10,
+        };
+        Some(Duration::from_secs(minutes * 60))
+    }
+}
+
+/// When the game saves itself, set in the settings.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SavePolicy {
+    pub autosave: AutosaveInterval,
+    pub on_pause: bool,
+    pub on_quit: bool,
+}
+
+impl Default for SavePolicy {
+    fn default() -> Self {
+        Self {
+            autosave: AutosaveInterval::default(),
+            on_pause: false,
+            on_quit: true,
+        }
+    }
+}
+
+impl SavePolicy {
+    const DB_KEY: &str = "save_policy";
+}
+
+/// Counts down the play time to the next autosave. Paused time doesn't count.
+#[cfg(feature = "sqlite")]
+#[derive(Resource, Deref, DerefMut)]
+struct AutosaveTimer(Timer);
+
+fn setup_save_policy(mut commands: Commands, database: NonSend<Database>) {
+    let policy = database.get_kv(SETTINGS_DB_TABLE, SavePolicy::DB_KEY, SavePolicy::default());
+    commands.insert_resource(policy);
+}
+
+fn save_policy_sync(database: NonSend<Database>, policy: Res<SavePolicy>) -> Result {
+    database
+        .set_kv(SETTINGS_DB_TABLE, SavePolicy::DB_KEY, *policy)
+        .map_err(GameError::from)?;
+    Ok(())
+}
+
+#[cfg(feature = "sqlite")]
+fn start_autosave_timer(mut commands: Commands, policy: Res<SavePolicy>) {
+    if let Some(duration) = policy.autosave.duration() {
+        commands.insert_resource(AutosaveTimer(Timer::new(duration, TimerMode::Repeating)));
+    }
+}
+
+/// Autosaves once the interval has gone by, only between the steps of the game
+/// where a save can be picked back up from.
+#[cfg(feature = "sqlite")]
+fn tick_autosave(
+    mut commands: Commands,
+    time: Res<Time>,
+    policy: Res<SavePolicy>,
+    timer: Option<ResMut<AutosaveTimer>>,
+) {
+    let Some(duration) = policy.autosave.duration() else {
+        commands.remove_resource::<AutosaveTimer>();
+        return;
+    };
+    let Some(mut timer) = timer else {
+        commands.insert_resource(AutosaveTimer(Timer::new(duration, TimerMode::Repeating)));
+        return;
+    };
+
+    if timer.duration() != duration {
+        timer.set_duration(duration);
+    }
+    if timer.tick(time.delta()).just_finished() {
+        commands.run_system_cached(autosave_game);
+    }
+}
+
+#[cfg(feature = "sqlite")]
+fn save_on_pause(mut commands: Commands, policy: Res<SavePolicy>) {
+    if policy.on_pause {
+        commands.run_system_cached(save_game);
+    }
+}
+
+/// Saves when the window is closed mid game, as the pause menu's quit button would.
+#[cfg(feature = "sqlite")]
+fn save_on_exit(mut commands: Commands, mut exits: EventReader<AppExit>, policy: Res<SavePolicy>) {
+    if exits.read().last().is_some() && policy.on_quit {
+        commands.run_system_cached(save_game);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn every_interval_can_be_cycled_to_and_kept() {
+        let mut interval = AutosaveInterval::Off;
+        let mut seen = vec![];
+        loop {
+            seen.push(interval.duration());
+            interval = interval.next();
+            if interval == AutosaveInterval::Off {
+                break;
+            }
+        }
+        assert_eq!(seen.len(), 4);
+        assert!(seen[1..].windows(2).all(|pair| pair[0] < pair[1]));
+
+        let policy = SavePolicy {
+            autosave: AutosaveInterval::TenMinutes,
+            on_pause: true,
+            on_quit: false,
+        };
+        let kept: SavePolicy = ron::from_str(&ron::to_string(&policy).unwrap()).unwrap();
+        assert_eq!(kept, policy);
+        let old: SavePolicy = ron::from_str("(on_pause: true)").unwrap();
+        assert_eq!(old.autosave, SavePolicy::default().autosave);
+    }
+}
@@ -0,0 +1,204 @@
+//! A faint trail on the minimap through the last rooms the party walked through,
+//! to help keep track of where they came from on maps that look the same every way.
+
+use super::*;
+use crate::generate_map::{MapTile, MapTilemap};
+use crate::settings::SETTINGS_DB_TABLE;
+use bevy::prelude::*;
+use std::collections::VecDeque;
+
+/// How many rooms the trail goes back.
+pub const BREADCRUMB_LENGTH: usize = 8;
+/// Above the map tiles, so the trail is drawn over them.
+const BREADCRUMB_LAYER: f32 = 1.0;
+const BREADCRUMB_WIDTH: f32 = 4.0;
+const BREADCRUMB_ALPHA: f32 = 0.45;
+
+pub struct BreadcrumbsPlugin;
+
+impl Plugin for BreadcrumbsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PreStartup, setup_show_breadcrumbs)
+            .add_systems(OnEnter(AppState::Game), init_resource::<Breadcrumbs>)
+            .add_systems(OnEnter(GameState::EnterRoom), drop_breadcrumb)
+            .add_systems(
+                Update,
+                (
+                    show_breadcrumbs_sync.run_if(
+                        resource_changed::<ShowBreadcrumbs>
+                            .and(not(resource_added::<ShowBreadcrumbs>)),
+                    ),
+                    draw_breadcrumbs.run_if(in_state(AppState::Game).and(
+                        resource_changed::<Breadcrumbs>.or(resource_changed::<ShowBreadcrumbs>),
+                    )),
+                ),
+            )
+            .add_systems(OnExit(AppState::Game), remove_resource::<Breadcrumbs>);
+    }
+}
+
+/// Whether the trail is drawn on the minimap, set in the display settings.
+#[derive(Resource, Deref, DerefMut, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ShowBreadcrumbs(pub bool);
+
+impl ShowBreadcrumbs {
+    const DB_KEY: &str = "show_breadcrumbs";
+}
+
+fn setup_show_breadcrumbs(mut commands: Commands, database: NonSend<Database>) {
+    let show = database.get_kv(SETTINGS_DB_TABLE, ShowBreadcrumbs::DB_KEY, true);
+    commands.insert_resource(ShowBreadcrumbs(show));
+}
+
+fn show_breadcrumbs_sync(database: NonSend<Database>, show: Res<ShowBreadcrumbs>) -> Result {
+    database
+        .set_kv(SETTINGS_DB_TABLE, ShowBreadcrumbs::DB_KEY, show.0)
+        .map_err(GameError::from)?;
+    Ok(())
+}
+
+/// The last rooms visited this session, oldest first.
+#[derive(Resource, Default, Debug, Clone, PartialEq)]
+pub struct Breadcrumbs(VecDeque<TilePos>);
+
+impl Breadcrumbs {
+    /// Adds a room to the end of the trail, dropping the oldest once it is full.
+    pub fn visit(&mut self, pos: TilePos) {
+        if self.0.back() == Some(&pos) {
+            return;
+        }
+
+        if self.0.len() == BREADCRUMB_LENGTH {
+            self.0.pop_front();
+        }
+        self.0.push_back(pos);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &TilePos> {
+        self.0.iter()
+    }
+
+    /// Moves the trail along with the map, when it grows in endless mode.
+    pub fn shift(&mut self, by: u32) {
+        for pos in self.0.iter_mut() {
+            pos.x += by;
+            pos.y += by;
+        }
+    }
+}
+
+/// One line of the trail between two rooms.
+#[derive(Component)]
+struct BreadcrumbSegment;
+
+fn drop_breadcrumb(
+    current_room: Query<&TilePos, (With<CurrentRoom>, With<MapTile>)>,
+    breadcrumbs: Option<ResMut<Breadcrumbs>>,
+) {
+    let (Ok(pos), Some(mut breadcrumbs)) = (current_room.single(), breadcrumbs) else {
+        return;
+    };
+
+    breadcrumbs.visit(*pos);
+}
+
+/// Where a line from `from` to `to` goes, and how long it is.
+fn segment_transform(from: Vec2, to: Vec2) -> (Transform, f32) {
+    let delta = to - from;
+    let center = from + delta / 2.0;
+
+    let transform = Transform::from_xyz(center.x, center.y, BREADCRUMB_LAYER)
+        .with_rotation(Quat::from_rotation_z(delta.to_angle()));
+    (transform, delta.length())
+}
+
+#[allow(clippy::type_complexity)]
+fn draw_breadcrumbs(
+    mut commands: Commands,
+    breadcrumbs: Res<Breadcrumbs>,
+    show: Res<ShowBreadcrumbs>,
+    style: Res<Style>,
+    map: Single<
+        (
+            &Transform,
+            &TilemapSize,
+            &TilemapGridSize,
+            &TilemapTileSize,
+            &TilemapType,
+            &TilemapAnchor,
+        ),
+        With<MapTilemap>,
+    >,
+    segments: Query<Entity, With<BreadcrumbSegment>>,
+) {
+    for segment in segments.iter() {
+        commands.entity(segment).despawn();
+    }
+
+    if !show.0 {
+        return;
+    }
+
+    let (map_transform, map_size, grid_size, tile_size, map_type, anchor) = *map;
+    let points: Vec<Vec2> = breadcrumbs
+        .iter()
+        .map(|pos| {
+            map_transform.translation.truncate()
+                + pos.center_in_world(map_size, grid_size, tile_size, map_type, anchor)
+        })
+        .collect();
+
+    // Older parts of the trail fade out.
+    let count = points.len().saturating_sub(1);
+    for (index, pair) in points.windows(2).enumerate() {
+        let (mut transform, length) = segment_transform(pair[0], pair[1]);
+        transform.translation.z += map_transform.translation.z;
+        let alpha = BREADCRUMB_ALPHA * (index + 1) as f32 / count as f32;
+
+        commands.spawn((
+            BreadcrumbSegment,
+            Sprite {
+                color: style.text_color.with_alpha(alpha),
+                custom_size: Some(Vec2::new(length, BREADCRUMB_WIDTH)),
+                ..default()
+            },
+            transform,
+            Pickable::IGNORE,
+            StateScoped(AppState::Game),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn trail_keeps_only_the_latest_rooms() {
+        let mut breadcrumbs = Breadcrumbs::default();
+        breadcrumbs.visit(TilePos::new(5, 5));
+        breadcrumbs.visit(TilePos::new(5, 5));
+        assert_eq!(breadcrumbs.iter().count(), 1);
+
+        for x in 0..BREADCRUMB_LENGTH as u32 + 3 {
+            breadcrumbs.visit(TilePos::new(x, 0));
+        }
+
+        assert_eq!(breadcrumbs.iter().count(), BREADCRUMB_LENGTH);
+        assert_eq!(breadcrumbs.iter().next(), Some(&TilePos::new(3, 0)));
+        assert_eq!(
+            breadcrumbs.iter().last(),
+            Some(&TilePos::new(BREADCRUMB_LENGTH as u32 + 2, 0))
+        );
+    }
+
+    #[test]
+    fn segments_join_their_rooms() {
+        let (transform, length) = segment_transform(Vec2::new(0.0, 0.0), Vec2::new(0.0, 10.0));
+
+        assert_eq!(length, 10.0);
+        assert_eq!(transform.translation.truncate(), Vec2::new(0.0, 5.0));
+        let end = transform.transform_point(Vec3::new(length / 2.0, 0.0, 0.0));
+        assert!(end.truncate().distance(Vec2::new(0.0, 10.0)) < 1e-4);
+    }
+}
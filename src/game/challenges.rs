@@ -0,0 +1,185 @@
+//! The parts of the [`Challenges`] a game was started with that need more than a check in combat.
+//!
+//! With timed turns on, the party has [`TIMED_TURN_SECONDS`] to choose what to do,
+//! counted down by a ring around the acting hero, or they defend instead.
+
+use super::*;
+
+pub const TIMED_TURN_SECONDS: f32 = 10.0;
+/// How many dots make up the countdown ring.
+const RING_SEGMENTS: usize = 12;
+const RING_RADIUS: f32 = 48.0;
+const RING_DOT_SIZE: f32 = 6.0;
+
+pub struct ChallengesPlugin;
+
+impl Plugin for ChallengesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(CombatState::SpawnMenu),
+            start_turn_timer.run_if(timed_turns.and(not(resource_exists::<TurnTimer>))),
+        )
+        .add_systems(
+            Update,
+            tick_turn_timer.run_if(
+                resource_exists::<TurnTimer>
+                    .and(in_state(CombatState::SpawnMenu).or(in_state(CombatState::ChooseTarget))),
+            ),
+        )
+        .add_systems(
+            OnEnter(CombatState::PerformAction),
+            (
+                remove_resource::<TurnTimer>,
+                despawn_filtered::<With<TurnTimerText>>,
+                despawn_filtered::<With<TurnTimerRing>>,
+            ),
+        )
+        .add_systems(
+            OnExit(GameState::Combat),
+            (
+                remove_resource::<TurnTimer>,
+                despawn_filtered::<With<TurnTimerRing>>,
+            ),
+        );
+    }
+}
+
+/// How long the party has left to choose their action.
+#[derive(Resource, Deref, DerefMut)]
+pub struct TurnTimer(Timer);
+
+impl TurnTimer {
+    /// The whole seconds left, as counted down on screen.
+    pub fn seconds_left(&self) -> u32 {
+        self.remaining_secs().ceil() as u32
+    }
+
+    /// How many dots of the ring are still lit.
+    pub fn lit_segments(&self) -> usize {
+        (self.fraction_remaining() * RING_SEGMENTS as f32).ceil() as usize
+    }
+}
+
+#[derive(Component)]
+struct TurnTimerText;
+
+/// The countdown ring around the acting hero.
+#[derive(Component)]
+struct TurnTimerRing;
+
+#[derive(Component)]
+struct RingSegment(usize);
+
+pub fn timed_turns(challenges: Option<Res<Challenges>>) -> bool {
+    challenges.is_some_and(|challenges| challenges.timed_turns)
+}
+
+fn start_turn_timer(
+    mut commands: Commands,
+    style: Res<Style>,
+    acting: Single<Entity, With<ActingActor>>,
+) {
+    let timer = TurnTimer(Timer::from_seconds(TIMED_TURN_SECONDS, TimerMode::Once));
+
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(80.0),
+            justify_self: JustifySelf::Center,
+            ..default()
+        },
+        Text::new(timer.seconds_left().to_string()),
+        style.font(40.0),
+        TextColor(style.accent_color),
+        Pickable::IGNORE,
+        StateScoped(GameState::Combat),
+        TurnTimerText,
+        HudPart(HudElement::Timer),
+    ));
+
+    commands.entity(*acting).with_children(|builder| {
+        builder
+            .spawn((
+                Name::new("Turn Timer Ring"),
+                TurnTimerRing,
+                HudPart(HudElement::Timer),
+                Transform::from_xyz(0.0, 0.0, 0.1),
+                Visibility::Inherited,
+            ))
+            .with_children(|builder| {
+                for segment in 0..RING_SEGMENTS {
+                    // Clockwise from the top, so the dots go out the way a clock hand goes.
+                    let angle = std::f32::consts::FRAC_PI_2
+                        - std::f32::consts::TAU * segment as f32 / RING_SEGMENTS as f32;
+                    builder.spawn((
+                        RingSegment(segment),
+                        Sprite::from_color(style.accent_color, Vec2::splat(RING_DOT_SIZE)),
+                        Transform::from_translation(
+                            (Vec2::from_angle(angle) * RING_RADIUS).extend(0.0),
+                        ),
+                        Pickable::IGNORE,
+                    ));
+                }
+            });
+    });
+    commands.insert_resource(timer);
+}
+
+/// Counts down the turn, defending once the time runs out.
+#[allow(clippy::too_many_arguments)]
+fn tick_turn_timer(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut timer: ResMut<TurnTimer>,
+    mut text: Query<&mut Text, With<TurnTimerText>>,
+    mut segments: Query<(&RingSegment, &mut Visibility)>,
+    mut next_state: ResMut<NextState<CombatState>>,
+    acting: Single<Entity, With<ActingActor>>,
+    names: Query<(&ActorName, Option<&HeroName>)>,
+    mut log: EventWriter<CombatLogEvent>,
+) {
+    timer.tick(time.delta());
+    for mut text in text.iter_mut() {
+        text.0 = timer.seconds_left().to_string();
+    }
+    // The ring empties from its last dot back to the top.
+    let lit = timer.lit_segments();
+    for (segment, mut visibility) in segments.iter_mut() {
+        *visibility = match segment.0 < lit {
+            true => Visibility::Inherited,
+            false => Visibility::Hidden,
+        };
+    }
+
+    if timer.just_finished() {
+        let name = log_name(&names, *acting);
+        log.write(CombatLogEvent(format!("{name} ran out of time")));
+        commands.insert_resource(ActingActorAction(Action::Defend));
+        next_state.set(CombatState::PerformAction);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn countdown_rounds_up() {
+        let mut timer = TurnTimer(Timer::from_seconds(TIMED_TURN_SECONDS, TimerMode::Once));
+        assert_eq!(timer.seconds_left(), TIMED_TURN_SECONDS as u32);
+        assert_eq!(timer.lit_segments(), RING_SEGMENTS);
+
+        timer.tick(Duration::from_secs_f32(0.5));
+        assert_eq!(timer.seconds_left(), TIMED_TURN_SECONDS as u32);
+        assert_eq!(timer.lit_segments(), RING_SEGMENTS);
+
+        timer.tick(Duration::from_secs_f32(TIMED_TURN_SECONDS / 2.0));
+        assert_eq!(timer.lit_segments(), RING_SEGMENTS / 2);
+
+        timer.tick(Duration::from_secs_f32(TIMED_TURN_SECONDS));
+        assert_eq!(timer.seconds_left(), 0);
+        assert_eq!(timer.lit_segments(), 0);
+        assert!(timer.finished());
+    }
+}
@@ -0,0 +1,278 @@
+//! Chests left in empty and item rooms, opened by clicking them for gold and items.
+//! Epic chests also hold a piece of gear to wear, see [`Equipment`].
+//!
+//! Whether a room has a chest, how rare it is and what is inside are all rolled
+//! from the room's seed, so a chest holds the same loot however often the game is loaded.
+//! Opened chests stay in their room, emptied, see [`RoomInfo::chest_opened`].
+
+use super::*;
+use crate::damage_popup::DamagePopup;
+use crate::room::{PROP_LAYER, RoomLayout};
+use rand::{Rng, SeedableRng};
+use std::ops::Range;
+use strum::{Display, EnumIter, IntoEnumIterator};
+
+/// Mixed into a room's seed for its chest, so having a chest doesn't change its events.
+const CHEST_RNG_STREAM: u64 = 0xc4e5_7000_ba11_0071;
+/// One in this many rooms that can have a chest does.
+const CHEST_CHANCE: u32 = 2;
+/// Chests sit in the middle of the room, where nothing else is put.
+const CHEST_POSITION: IVec2 = IVec2::ZERO;
+const CHEST_SIZE: Vec2 = Vec2::new(22.0, 16.0);
+const OPENED_CHEST_COLOR: Color = Color::srgb_u8(0x52, 0x4f, 0x67);
+
+pub struct ChestsPlugin;
+
+impl Plugin for ChestsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_observer(click_chest)
+            .add_systems(OnEnter(GameState::Navigation), spawn_chest);
+    }
+}
+
+/// How rare a chest is, and so how much is in it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumIter)]
+pub enum ChestTier {
+    Common,
+    Rare,
+    Epic,
+}
+
+impl ChestTier {
+    /// How likely each tier is, out of every tier's weight added up.
+    pub fn weight(self) -> u32 {
+        match self {
+            ChestTier::Common => 70,
+            ChestTier::Rare => 25,
+            ChestTier::Epic => 5,
+        }
+    }
+
+    pub fn roll(rng: &mut impl Rng) -> Self {
+        let total = ChestTier::iter().map(ChestTier::weight).sum::<u32>();
+        let mut roll = rng.random_range(0..total);
+        for tier in ChestTier::iter() {
+            if roll < tier.weight() {
+                return tier;
+            }
+            roll -= tier.weight();
+        }
+        unreachable!()
+    }
+
+    pub fn gold(self) -> Range<u32> {
+        match self {
+            ChestTier::Common => 10..26,
+            ChestTier::Rare => 25..51,
+            ChestTier::Epic => 60..101,
+        }
+    }
+
+    pub fn item_count(self) -> usize {
+        match self {
+            ChestTier::Common => 0,
+            ChestTier::Rare => 1,
+            ChestTier::Epic => 2,
+        }
+    }
+
+    /// How many of the chest's items are gear to wear, on top of [`Self::item_count`].
+    pub fn gear_count(self) -> usize {
+        match self {
+            ChestTier::Common | ChestTier::Rare => 0,
+            ChestTier::Epic => 1,
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            ChestTier::Common => Color::srgb_u8(0x9c, 0x6b, 0x3c),
+            ChestTier::Rare => Color::srgb_u8(0x3e, 0x8f, 0xb0),
+            ChestTier::Epic => Color::srgb_u8(0xc4, 0xa7, 0xe7),
+        }
+    }
+}
+
+/// A chest in the current room, holding what it was rolled with until it is opened.
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub struct Chest {
+    pub tier: ChestTier,
+    pub gold: u32,
+    pub items: Vec<Item>,
+}
+
+impl Chest {
+    /// The room's chest, if it has one, opened or not.
+    pub fn roll(info: &RoomInfo) -> Option<Self> {
+        if !matches!(info.r_type, RoomType::EmptyRoom | RoomType::Item(_)) {
+            return None;
+        }
+
+        let mut rng = RandomSource::seed_from_u64(info.rng_seed ^ CHEST_RNG_STREAM);
+        if !rng.random_ratio(1, CHEST_CHANCE) {
+            return None;
+        }
+
+        let tier = ChestTier::roll(&mut rng);
+        let gold = rng.random_range(tier.gold());
+        let mut items: Vec<Item> = (0..tier.item_count())
+            .map(|_| Item::get_rand_item(&mut rng))
+            .collect();
+        items.extend((0..tier.gear_count()).map(|_| Item::get_rand_gear(&mut rng)));
+        Some(Self { tier, gold, items })
+    }
+}
+
+fn chest_tooltip(tier: ChestTier, opened: bool) -> Tooltip {
+    let body = match opened {
+        true => "Already opened",
+        false => "Click to open it",
+    };
+    Tooltip::new(format!("{tier} Chest")).with_body(body)
+}
+
+fn spawn_chest(
+    mut commands: Commands,
+    info: Single<&RoomInfo, With<CurrentRoom>>,
+    chests: Query<(), With<Chest>>,
+    tilemap: Single<
+        (
+            &TilemapSize,
+            &TilemapGridSize,
+            &TilemapTileSize,
+            &TilemapType,
+            &TilemapAnchor,
+        ),
+        With<RoomTilemap>,
+    >,
+) {
+    if !chests.is_empty() {
+        return;
+    }
+    let Some(chest) = Chest::roll(&info) else {
+        return;
+    };
+
+    let color = match info.chest_opened {
+        true => OPENED_CHEST_COLOR,
+        false => chest.tier.color(),
+    };
+    let tooltip = chest_tooltip(chest.tier, info.chest_opened);
+    let world_pos = RoomLayout::new(*tilemap).world_pos(CHEST_POSITION);
+    commands.spawn((
+        InRoom,
+        chest,
+        Sprite::from_color(color, CHEST_SIZE),
+        Transform::from_xyz(world_pos.x, world_pos.y, PROP_LAYER),
+        Pickable::default(),
+        tooltip,
+    ));
+}
+
+fn click_chest(
+    mut click: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    chests: Query<(), With<Chest>>,
+    game_state: Option<Res<State<GameState>>>,
+) {
+    if !chests.contains(click.target()) {
+        return;
+    }
+    click.propagate(false);
+
+    let exploring = game_state.is_some_and(|state| *state.get() == GameState::Navigation);
+    if click.button == PointerButton::Primary && exploring {
+        commands.run_system_cached_with(open_chest, click.target());
+    }
+}
+
+/// Gives the party what is in the chest, floating it up out of the chest as it goes.
+fn open_chest(
+    In(entity): In<Entity>,
+    mut commands: Commands,
+    mut room: Single<&mut RoomInfo, With<CurrentRoom>>,
+    mut chests: Query<(&Chest, &mut Sprite, &mut Tooltip)>,
+    mut gold: ResMut<Gold>,
+    mut items: ResMut<Items>,
+    mut notifications: ResMut<Notifications>,
+) {
+    let Ok((chest, mut sprite, mut tooltip)) = chests.get_mut(entity) else {
+        return;
+    };
+    if room.chest_opened {
+        return;
+    }
+    room.chest_opened = true;
+
+    **gold += chest.gold as u64;
+    commands.trigger_targets(DamagePopup::Gold(chest.gold), entity);
+    for item in chest.items.iter() {
+        items.push(*item);
+        commands.trigger_targets(DamagePopup::Loot(*item), entity);
+    }
+    notifications.success(format!("Opened a {} Chest", chest.tier));
+
+    sprite.color = OPENED_CHEST_COLOR;
+    *tooltip = chest_tooltip(chest.tier, true);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn chests_are_rolled_from_the_room_seed() {
+        let mut tiers = Vec::new();
+        for seed in 0..500 {
+            let info = RoomInfo::from_type(RoomType::EmptyRoom, seed);
+            let chest = Chest::roll(&info);
+            assert_eq!(chest, Chest::roll(&info));
+
+            if let Some(chest) = chest {
+                assert!(chest.tier.gold().contains(&chest.gold));
+                assert_eq!(
+                    chest.items.len(),
+                    chest.tier.item_count() + chest.tier.gear_count()
+                );
+                tiers.push(chest.tier);
+            }
+
+            let combat = RoomInfo::from_type(RoomType::Combat([ActorName::Goblin].into()), seed);
+            assert_eq!(Chest::roll(&combat), None);
+        }
+
+        let count = |tier| tiers.iter().filter(|t| **t == tier).count();
+        assert!(tiers.len() > 150 && tiers.len() < 350);
+        assert!(count(ChestTier::Common) > count(ChestTier::Rare));
+        assert!(count(ChestTier::Rare) > count(ChestTier::Epic));
+    }
+
+    #[test]
+    fn chests_are_only_opened_once() {
+        let mut world = World::new();
+        world.init_resource::<Notifications>();
+        world.init_resource::<Gold>();
+        world.init_resource::<Items>();
+        let room = world
+            .spawn((RoomInfo::from_type(RoomType::EmptyRoom, 0), CurrentRoom))
+            .id();
+        let chest = world
+            .spawn((
+                Chest {
+                    tier: ChestTier::Rare,
+                    gold: 30,
+                    items: vec![Item::HealingPotion],
+                },
+                Sprite::default(),
+                Tooltip::new("Rare Chest"),
+            ))
+            .id();
+
+        world.run_system_cached_with(open_chest, chest).unwrap();
+        world.run_system_cached_with(open_chest, chest).unwrap();
+
+        assert!(world.get::<RoomInfo>(room).unwrap().chest_opened);
+        assert_eq!(*world.resource::<Gold>(), Gold(30));
+        assert_eq!(world.resource::<Items>().0, vec![Item::HealingPotion]);
+    }
+}
@@ -1,6 +1,9 @@
 use super::*;
+use crate::animation::{ActiveAnimation, AnimationEvent, start_clip};
+use crate::damage_popup::DamagePopup;
 use crate::prelude::*;
-use crate::update_player_hp_bar;
+use crate::rules::{self, Outcome};
+use crate::{update_player_hp_bar, update_player_hp_bar_pit};
 use bevy::prelude::*;
 use bevy_ecs_tilemap::prelude::*;
 use rand::Rng;
@@ -8,7 +11,9 @@ use std::fmt;
 
 pub struct CombatPlugin;
 const ACTOR_SPEED: f32 = 300.0;
-const DAMAGE_MULTIPLIER: f32 = 1.2;
+const INTRO_SECONDS: f32 = 1.2;
+/// How far to the right enemies start their intro slide from.
+const INTRO_SLIDE_DISTANCE: f32 = 250.0;
 
 impl Plugin for CombatPlugin {
     fn build(&self, app: &mut App) {
@@ -20,6 +25,12 @@ impl Plugin for CombatPlugin {
             OnEnter(GameState::Combat),
             (setup_turn_order, store_actor_positions),
         )
+        .add_systems(OnEnter(CombatState::Intro), intro_enter)
+        .add_systems(Update, intro_slide.run_if(in_state(CombatState::Intro)))
+        .add_systems(
+            OnExit(CombatState::Intro),
+            (intro_finish, remove_resource::<IntroTimer>),
+        )
         .add_systems(OnEnter(CombatState::TurnSetup), prep_turn_order)
         .add_systems(OnEnter(CombatState::MoveToCenter), move_to_center)
         .add_systems(OnEnter(CombatState::MoveBack), move_back)
@@ -35,14 +46,30 @@ impl Plugin for CombatPlugin {
         )
         .add_systems(
             OnEnter(CombatState::PerformAction),
-            (despawn_attack_menu, perform_action).chain(),
+            (despawn_attack_menu, start_attack_clip).chain(),
         )
         .add_systems(
             Update,
-            (move_to_target, move_back_check).run_if(in_state(CombatState::MoveBack)),
+            (
+                move_to_target,
+                move_back_check.run_if(not(resource_exists::<ResultPause>)),
+            )
+                .run_if(in_state(CombatState::MoveBack)),
+        )
+        .add_systems(
+            OnEnter(CombatState::EndOfTurn),
+            (tick_status_effects, land_scheduled_effects, end_turn).chain(),
         )
-        .add_systems(OnEnter(CombatState::EndOfTurn), end_turn)
-        .add_systems(OnExit(GameState::Combat), cleanup_positions);
+        .add_systems(
+            OnExit(GameState::Combat),
+            (
+                cleanup_positions,
+                clear_status_effects,
+                remove_component::<Defending>,
+                remove_component::<Energy>,
+            ),
+        )
+        .add_observer(land_attack);
     }
 }
 
@@ -56,13 +83,18 @@ impl Plugin for CombatPlugin {
 #[source(GameState = GameState::Combat)]
 #[states(scoped_entities)]
 pub enum CombatState {
+    /// The enemies slide and fade in, under a banner naming them.
+    ///
+    /// OnEnter: Spawns the banner
+    /// Update:  Moves the enemies in, then moves to [`TurnSetup`]
+    #[default]
+    Intro,
     /// Everything to set up the turn that is about to come
     ///
     /// [`ActingActor`] is front of queue
     /// Asserts it is not empty
     ///
     /// Set [`ActingActor`]
-    #[default]
     TurnSetup,
     /// Move the choosen actor to the next state.
     ///
@@ -70,6 +102,11 @@ pub enum CombatState {
     MoveToCenter,
     /// Spawns Menu
     SpawnMenu,
+    /// The player picks which enemy to attack, see [`TargetChoice`]
+    ///
+    /// Update: Cycle through targets and confirm one
+    /// OnExit: Removes [`TargetChoice`]
+    ChooseTarget,
     /// Checks which Team is Attacking
     CheckTeam,
     /// Monster Attack
@@ -77,10 +114,9 @@ pub enum CombatState {
     /// The attacking actor does the attack
     /// and the attackee gets hurt
     ///
-    /// Update: Update animation timer
-    ///         When timer done, move on
-    /// OnExit: Deal Damage
-    ///         Removes [`ActingActorAction`]
+    /// OnEnter: Plays the attack clip
+    /// The damage is dealt when the clip's [`AnimationEvent::Impact`] fires,
+    /// then moves to [`MoveBack`]
     ///
     /// If an actor gets an additional turn,
     /// go back to `ChooseAction`
@@ -134,6 +170,10 @@ pub enum Action {
         target: Entity,
     },
     SkipTurn,
+    /// The actor guards, blocking more often until their next turn.
+    Defend,
+    /// The party tries to get away from the fight.
+    Flee,
 }
 
 ////////////COMPONENTS//////////////////
@@ -142,6 +182,21 @@ pub enum Action {
 #[derive(Component)]
 pub struct ActingActor;
 
+/// Doubles the actor's [`BlockChance`] until their next turn.
+#[derive(Component)]
+pub struct Defending;
+
+/// What the actor has left to spend on special moves this fight,
+/// see [`rules::SPECIAL_COST`].
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Energy(pub u32);
+
+impl Energy {
+    pub fn can_afford_special(energy: Option<&Self>) -> bool {
+        energy.is_some_and(|energy| rules::spend_energy(energy.0, rules::SPECIAL_COST).is_some())
+    }
+}
+
 //Stoes the original positions of all actors
 #[derive(Component, Deref, DerefMut)]
 pub struct ActorOriginalPosition(pub Vec2);
@@ -151,6 +206,14 @@ pub struct ActorOriginalPosition(pub Vec2);
 pub struct ActorTargetPosition(pub Vec2);
 
 ////////////RESOURCES//////////////////
+/// How far through the combat intro we are.
+#[derive(Resource, Deref, DerefMut)]
+pub struct IntroTimer(pub Timer);
+
+/// The party got away, so the fight ends without the room being cleared.
+#[derive(Resource)]
+pub struct Fled;
+
 /// The action being taken by the acting actor
 #[derive(Resource, Deref, DerefMut)]
 pub struct ActingActorAction(pub Action);
@@ -173,6 +236,18 @@ impl TurnOrder {
         Self { queue }
     }
 
+    /// A queue already in turn order, with the active actor last.
+    pub fn from_queue(queue: VecDeque<Entity>) -> Self {
+        Self { queue }
+    }
+
+    /// Moves every enemy ahead of the party, keeping the order within each team.
+    pub fn enemies_first(&mut self, team_q: &Query<&Team>) {
+        self.queue
+            .make_contiguous()
+            .sort_by_key(|entity| team_q.get(*entity).is_ok_and(|team| *team == Team::Enemy));
+    }
+
     /// Gets the active actor.
     /// asserts that the queue isn't empty
     pub fn active(&self) -> Entity {
@@ -237,12 +312,22 @@ pub struct ActionEvent {
 }
 
 //sets up the turn queue
-fn setup_turn_order(
+pub fn setup_turn_order(
     mut commands: Commands,
     actor_q: Query<Entity, With<Actor>>,
     speed_q: Query<&AttackSpeed>,
+    team_q: Query<&Team>,
+    challenges: Option<Res<Challenges>>,
 ) {
-    commands.insert_resource(TurnOrder::new(actor_q, speed_q));
+    for actor in actor_q.iter() {
+        commands.entity(actor).insert(Energy(rules::MAX_ENERGY));
+    }
+
+    let mut queue = TurnOrder::new(actor_q, speed_q);
+    if challenges.is_some_and(|challenges| challenges.enemies_first) {
+        queue.enemies_first(&team_q);
+    }
+    commands.insert_resource(queue);
 }
 
 //stores the actors original positions
@@ -257,6 +342,93 @@ fn store_actor_positions(
     }
 }
 
+/// Names the enemies by kind, like "2 Goblins, 1 Ogre".
+fn enemy_banner(names: impl IntoIterator<Item = ActorName>) -> String {
+    let mut counts: Vec<(ActorName, usize)> = Vec::new();
+    for name in names {
+        match counts.iter_mut().find(|(counted, _)| *counted == name) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((name, 1)),
+        }
+    }
+
+    counts
+        .iter()
+        .map(|(name, count)| match count {
+            1 => format!("1 {name}"),
+            _ => format!("{count} {name}s"),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn intro_enter(
+    mut commands: Commands,
+    style: Res<Style>,
+    enemies: Query<(&ActorName, &Team, &Health)>,
+) {
+    let names = enemies
+        .iter()
+        .filter(|(_, team, health)| **team == Team::Enemy && health.is_alive())
+        .map(|(name, ..)| *name);
+
+    commands.insert_resource(IntroTimer(Timer::from_seconds(
+        INTRO_SECONDS,
+        TimerMode::Once,
+    )));
+
+    commands.spawn((
+        Node {
+            align_self: AlignSelf::Start,
+            justify_self: JustifySelf::Center,
+            margin: UiRect::top(Val::Px(40.0)),
+            ..default()
+        },
+        Text::new(enemy_banner(names)),
+        StateScoped(CombatState::Intro),
+        style.font(66.0),
+        TextColor(style.text_color),
+    ));
+}
+
+/// Where an enemy is and how visible it is, `progress` through the intro.
+fn intro_offset(progress: f32) -> (f32, f32) {
+    // Ease out so they slow down as they arrive.
+    let eased = 1.0 - (1.0 - progress).powi(2);
+    (INTRO_SLIDE_DISTANCE * (1.0 - eased), eased)
+}
+
+fn intro_slide(
+    time: Res<Time>,
+    mut timer: ResMut<IntroTimer>,
+    mut enemies: Query<(&ActorOriginalPosition, &Team, &mut Transform, &mut Sprite)>,
+    mut next_state: ResMut<NextState<CombatState>>,
+) {
+    timer.tick(time.delta());
+    let (offset, alpha) = intro_offset(timer.fraction());
+
+    for (original, team, mut transform, mut sprite) in enemies.iter_mut() {
+        if *team == Team::Enemy {
+            transform.translation.x = original.x + offset;
+            sprite.color.set_alpha(alpha);
+        }
+    }
+
+    if timer.finished() {
+        next_state.set(CombatState::TurnSetup);
+    }
+}
+
+/// Puts the enemies where they belong, however the intro ended.
+fn intro_finish(mut enemies: Query<(&ActorOriginalPosition, &Team, &mut Transform, &mut Sprite)>) {
+    for (original, team, mut transform, mut sprite) in enemies.iter_mut() {
+        if *team == Team::Enemy {
+            transform.translation.x = original.x;
+            sprite.color.set_alpha(1.0);
+        }
+    }
+}
+
 //removes the actors original positions
 fn cleanup_positions(mut commands: Commands, queue: ResMut<TurnOrder>) {
     commands
@@ -266,26 +438,56 @@ fn cleanup_positions(mut commands: Commands, queue: ResMut<TurnOrder>) {
 }
 
 //sets the active actor and insert the composnent
-fn prep_turn_order(
+#[allow(clippy::too_many_arguments)]
+pub fn prep_turn_order(
     mut commands: Commands,
     mut queue: ResMut<TurnOrder>,
     mut next_state: ResMut<NextState<CombatState>>,
     actor_q: Query<(&Health, &Team)>,
     name_q: Query<&ActorName>,
+    names: Query<(&ActorName, Option<&HeroName>)>,
+    effects_q: Query<&StatusEffects>,
+    mut energy_q: Query<&mut Energy>,
+    teams: Query<&Team>,
+    detail: Res<EnemyTurnDetail>,
+    mut log: EventWriter<CombatLogEvent>,
 ) {
-    println!("Turn order: {}", queue.display_with_names(&name_q));
+    debug!("Turn order: {}", queue.display_with_names(&name_q));
     match queue.teams_alive(actor_q) {
         TeamAlive::Both => {
             //commands.entity(queue.active()).remove::<ActingActor>();
-            commands.entity(queue.active()).insert(ActingActor);
-            next_state.set(CombatState::MoveToCenter);
+            commands
+                .entity(queue.active())
+                .insert(ActingActor)
+                .remove::<Defending>();
+            if let Ok(mut energy) = energy_q.get_mut(queue.active()) {
+                energy.0 = rules::regain_energy(energy.0);
+            }
+
+            // Stunned actors lose their turn, but their effects still count down.
+            if effects_q
+                .get(queue.active())
+                .is_ok_and(StatusEffects::is_stunned)
+            {
+                let stunned = log_name(&names, queue.active());
+                log.write(CombatLogEvent(format!("{stunned} is stunned")));
+                commands.insert_resource(ActingActorAction(Action::SkipTurn));
+                next_state.set(CombatState::EndOfTurn);
+            } else {
+                let acting = log_name(&names, queue.active());
+                log.write(CombatLogEvent(format!("{acting}'s turn")));
+                next_state.set(
+                    teams
+                        .get(queue.active())
+                        .map_or(CombatState::MoveToCenter, |team| detail.turn_start(*team)),
+                );
+            }
         }
         // End the turn in this case (likely another function)
         TeamAlive::Player | TeamAlive::Enemy | TeamAlive::Neither => {
             commands.entity(queue.active()).remove::<ActingActor>();
         }
     }
-    println!("Turn order: {}", queue.display_with_names(&name_q));
 }
 
 //////////FROM HERE ARE MOVEMENT SYSTEMS//////////////////
@@ -293,7 +495,7 @@ fn prep_turn_order(
 //sets target postion to be center
 fn move_to_center(
     mut commands: Commands,
-    active_actor: Single<Entity, With<ActingActor>>,
+    active_actor: Single<(Entity, &Transform, &AttackPattern), With<ActingActor>>,
     tilemap: Single<
         (
             &TilemapSize,
@@ -305,6 +507,15 @@ fn move_to_center(
         With<RoomTilemap>,
     >,
 ) {
+    let (entity, transform, pattern) = *active_actor;
+    // Ranged and area attackers act from where they stand.
+    if !pattern.moves_to_center() {
+        commands
+            .entity(entity)
+            .insert(ActorTargetPosition(transform.translation.xy()));
+        return;
+    }
+
     //set the center_world_pos
     let (map_size, grid_size, tile_size, map_type, map_anchor) = *tilemap;
 
@@ -317,7 +528,7 @@ fn move_to_center(
         center_tile_pos.center_in_world(&map_size, &grid_size, &tile_size, &map_type, &map_anchor);
     //Set a component with the target position
     commands
-        .entity(*active_actor)
+        .entity(entity)
         .insert(ActorTargetPosition(center_world_pos));
 }
 
@@ -393,11 +604,11 @@ pub fn choose_action(
     mut next_state: ResMut<NextState<CombatState>>,
     mut rng: ResMut<EventRng>,
     queue: ResMut<TurnOrder>,
-    active_actor: Single<(Entity, &Team), With<ActingActor>>,
+    active_actor: Single<(Entity, &Team, &ActorName, Option<&Energy>), With<ActingActor>>,
     actor_q: Query<(&Health, &Team)>,
 ) {
     //remove any current action
-    let (_, team) = *active_actor;
+    let (_, team, name, energy) = *active_actor;
     let targets: Vec<Entity> = queue
         .queue()
         .iter()
@@ -415,8 +626,22 @@ pub fn choose_action(
         .collect();
 
     let chosen_target = targets[rng.random_range(0..targets.len())];
-    let combat_action = Action::Attack {
-        target: chosen_target,
+    let special_chance = match name {
+        name if name.is_guardian() => rules::GUARDIAN_SPECIAL_CHANCE,
+        ActorName::Construct => rules::CHARGE_UP_CHANCE,
+        _ => 0.0,
+    };
+    let combat_action = if special_chance > 0.0
+        && Energy::can_afford_special(energy)
+        && rng.random_bool(special_chance)
+    {
+        Action::SpecialAction {
+            target: chosen_target,
+        }
+    } else {
+        Action::Attack {
+            target: chosen_target,
+        }
     };
     debug!("CHOSEN TARGET {:?}", chosen_target);
 
@@ -428,100 +653,444 @@ pub fn choose_action(
 
 ///////////////Perform Action///////////////////
 
+/// Plays the acting actor's attack clip, so the damage can wait for the blow to land.
+fn start_attack_clip(
+    mut commands: Commands,
+    actor_action: Res<ActingActorAction>,
+    detail: Res<EnemyTurnDetail>,
+    active_actor: Single<(Entity, &Team, &mut AnimationConfigs, &mut Sprite), With<ActingActor>>,
+) {
+    let (entity, team, mut config, mut sprite) = active_actor.into_inner();
+    if !detail.animates(*team) {
+        commands.run_system_cached(perform_action);
+        return;
+    }
+
+    match **actor_action {
+        Action::Attack { .. } | Action::SpecialAction { .. } => {
+            start_clip(
+                &mut commands,
+                entity,
+                ActiveAnimation::Attack,
+                &mut config,
+                &mut sprite,
+            );
+        }
+        Action::UseItem { .. } | Action::SkipTurn | Action::Defend | Action::Flee => {
+            commands.run_system_cached(perform_action);
+        }
+    }
+}
+
+/// Deals the damage once the acting actor's attack lands.
+fn land_attack(
+    event: Trigger<AnimationEvent>,
+    mut commands: Commands,
+    acting: Query<(), With<ActingActor>>,
+    state: Option<Res<State<CombatState>>>,
+) {
+    if *event.event() == AnimationEvent::Impact
+        && acting.contains(event.target())
+        && state.is_some_and(|state| *state.get() == CombatState::PerformAction)
+    {
+        commands.run_system_cached(perform_action);
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn perform_action(
     mut commands: Commands,
     mut next_state: ResMut<NextState<CombatState>>,
     mut rng: ResMut<EventRng>,
-    active_actor: Single<(Entity, &Attack), With<ActingActor>>,
+    active_actor: Single<
+        (Entity, &Attack, &Team, &AttackPattern, Option<&mut Energy>),
+        With<ActingActor>,
+    >,
     actor_action: Res<ActingActorAction>,
-    mut actor_q: Query<(&mut Health, &BlockChance), With<Actor>>,
+    mut actor_q: Query<
+        (
+            &mut Health,
+            &BlockChance,
+            &mut StatusEffects,
+            Has<Defending>,
+        ),
+        With<Actor>,
+    >,
     actor_name: Single<&ActorName, With<ActingActor>>,
+    name_q: Query<&ActorName>,
+    speed_q: Query<&AttackSpeed>,
+    names: Query<(&ActorName, Option<&HeroName>)>,
+    team_q: Query<(Entity, &Team), With<Actor>>,
+    rewards: Option<ResMut<RoomRewards>>,
+    challenges: Option<Res<Challenges>>,
+    mut stats: ResMut<RunStats>,
+    mut sfx: EventWriter<Sfx>,
+    mut log: EventWriter<CombatLogEvent>,
 ) {
-    let (_, a_attack) = *active_actor;
+    let (actor, a_attack, team, pattern, energy) = active_actor.into_inner();
+    let no_healing = challenges.is_some_and(|challenges| challenges.no_healing);
+    let log_as = |entity| log_name(&names, entity);
+    let alive_before: Vec<Entity> = team_q
+        .iter()
+        .map(|(entity, _)| entity)
+        .filter(|entity| {
+            actor_q
+                .get(*entity)
+                .is_ok_and(|(health, ..)| health.is_alive())
+        })
+        .collect();
+    let health_before: Vec<(Entity, Team, u32)> = team_q
+        .iter()
+        .filter_map(|(entity, team)| {
+            let (health, ..) = actor_q.get(entity).ok()?;
+            Some((entity, *team, health_points(health)))
+        })
+        .collect();
+    let target = match **actor_action {
+        Action::Attack { target }
+        | Action::SpecialAction { target }
+        | Action::UseItem { target, .. } => Some(target),
+        Action::SkipTurn | Action::Defend | Action::Flee => None,
+    };
+    let target_was_alive = target
+        .and_then(|target| actor_q.get(target).ok())
+        .is_some_and(|(health, ..)| health.is_alive());
+
+    if let (Action::SpecialAction { .. }, Some(mut energy)) = (**actor_action, energy) {
+        energy.0 = rules::spend_energy(energy.0, rules::SPECIAL_COST).unwrap_or_default();
+    }
+
+    let living_foes = || -> (Vec<Entity>, Vec<rules::Guard>) {
+        team_q
+            .iter()
+            .filter(|(_, foe_team)| *foe_team != team)
+            .filter_map(|(foe, _)| {
+                let (health, block_chance, _, defending) = actor_q.get(foe).ok()?;
+                health
+                    .is_alive()
+                    .then(|| (foe, block_chance.guard(defending)))
+            })
+            .unzip()
+    };
+
     match **actor_action {
-        Action::Attack { target } => {
-            let attack = a_attack.clone();
-
-            let attack_result = attack.conduct(&mut *rng);
-            debug!("ATTACK RESULT {:?}", attack_result);
-
-            match attack_result {
-                AttackDamage::Hit(damage) => {
-                    if let Ok((mut target_health, block_chance)) = actor_q.get_mut(target) {
-                        debug!("TARGETS BLOCK CHANCE: {}\n", block_chance.0);
-                        let blocked = rng.random_bool(block_chance.0.into());
-                        debug!("Block chance: {:?}, Blocked: {}\n", block_chance.0, blocked);
-                        if !blocked {
-                            target_health.damage(damage.get());
-                            let current_health =
-                                target_health.current().map(|h| h.get()).unwrap_or(0);
-                            debug!(
-                                "DAMAGE DEALT: {}, TARGET HEALTH: {}\n",
-                                damage.get(),
-                                current_health
-                            );
-
-                            if !target_health.is_alive() {
-                                debug!("{:?} IS DEAD!!!!!!!!!!!!!!\n", target);
+        Action::Attack { target } if *pattern == AttackPattern::Area => {
+            let (foes, guards) = living_foes();
+            let strike = a_attack.strike(rules::AREA_MULTIPLIER);
+
+            match rules::resolve_area_strike(&mut *rng, &strike, &guards) {
+                Some(outcomes) => {
+                    sfx.write(Sfx::Hit);
+                    log.write(CombatLogEvent(format!(
+                        "{} struck every foe",
+                        log_as(actor)
+                    )));
+
+                    for (foe, outcome) in foes.into_iter().zip(outcomes) {
+                        let Ok((mut foe_health, _, mut effects, _)) = actor_q.get_mut(foe) else {
+                            continue;
+                        };
+                        match outcome {
+                            Outcome::Hit(damage) | Outcome::Crit(damage) => {
+                                let dealt = effects.shielded(damage);
+                                foe_health.damage(dealt);
+                                log.write(CombatLogEvent(format!("{} took {dealt}", log_as(foe))));
+                                inflict_status(&mut *rng, &mut effects, **actor_name, false);
+                            }
+                            Outcome::Blocked | Outcome::Missed => {
+                                commands.trigger_targets(DamagePopup::Blocked, foe);
+                                log.write(CombatLogEvent(format!("{} blocked it", log_as(foe))));
                             }
                         }
                     }
                 }
-                AttackDamage::Miss => {
-                    debug!("MISSED!!!!!!!!!!!!!!\n");
+                None => {
+                    sfx.write(Sfx::Miss);
+                    commands.trigger_targets(DamagePopup::Miss, target);
+                    log.write(CombatLogEvent(format!(
+                        "{}'s attack missed everyone",
+                        log_as(actor)
+                    )));
+                }
+            }
+        }
+        Action::Attack { target } => {
+            if let Ok((mut target_health, block_chance, mut effects, defending)) =
+                actor_q.get_mut(target)
+            {
+                let outcome = rules::resolve_strike(
+                    &mut *rng,
+                    &a_attack.strike(1.0),
+                    Some(block_chance.guard(defending)),
+                );
+                debug!("ATTACK RESULT {:?}", outcome);
+
+                match outcome {
+                    Outcome::Hit(damage) | Outcome::Crit(damage) => {
+                        sfx.write(Sfx::Hit);
+                        let dealt = effects.shielded(damage);
+                        target_health.damage(dealt);
+                        log.write(CombatLogEvent(format!(
+                            "{} {} {} for {dealt}",
+                            log_as(actor),
+                            hit_verb(outcome),
+                            log_as(target)
+                        )));
+                        inflict_status(&mut *rng, &mut effects, **actor_name, false);
+
+                        if !target_health.is_alive() {
+                            debug!("{:?} IS DEAD!!!!!!!!!!!!!!\n", target);
+                        }
+                    }
+                    Outcome::Blocked => {
+                        sfx.write(Sfx::Miss);
+                        commands.trigger_targets(DamagePopup::Blocked, target);
+                        log.write(CombatLogEvent(format!(
+                            "{} blocked {}'s attack",
+                            log_as(target),
+                            log_as(actor)
+                        )));
+                    }
+                    Outcome::Missed => {
+                        sfx.write(Sfx::Miss);
+                        commands.trigger_targets(DamagePopup::Miss, target);
+                        log.write(CombatLogEvent(format!(
+                            "{} missed {}",
+                            log_as(actor),
+                            log_as(target)
+                        )));
+                    }
                 }
             }
         }
         Action::SpecialAction { target } => match **actor_name {
             ActorName::Warrior => {
-                if let Ok((mut target_health, _)) = actor_q.get_mut(target) {
-                    let attack_result = a_attack.conduct(&mut *rng);
-                    match attack_result {
-                        AttackDamage::Hit(damage) => {
-                            let extra_damage = (damage.get() as f32 * DAMAGE_MULTIPLIER) as u32;
-                            target_health.damage(extra_damage);
+                if let Ok((mut target_health, _, mut effects, _)) = actor_q.get_mut(target) {
+                    let strike = a_attack.strike(rules::CRUSHING_BLOW_MULTIPLIER);
+                    match rules::resolve_strike(&mut *rng, &strike, None) {
+                        outcome @ (Outcome::Hit(damage) | Outcome::Crit(damage)) => {
+                            sfx.write(Sfx::Hit);
+                            let dealt = effects.shielded(damage);
+                            target_health.damage(dealt);
+                            log.write(CombatLogEvent(format!(
+                                "{} {} {} with a heavy blow for {dealt}",
+                                log_as(actor),
+                                match outcome {
+                                    Outcome::Crit(_) => "critically struck",
+                                    _ => "struck",
+                                },
+                                log_as(target)
+                            )));
+                            inflict_status(&mut *rng, &mut effects, **actor_name, true);
+                        }
+                        Outcome::Missed | Outcome::Blocked => {
+                            sfx.write(Sfx::Miss);
+                            commands.trigger_targets(DamagePopup::Miss, target);
+                            log.write(CombatLogEvent(format!(
+                                "{} missed {}",
+                                log_as(actor),
+                                log_as(target)
+                            )));
                         }
-                        AttackDamage::Miss => {}
                     }
                 }
             }
             ActorName::Priestess => {
-                if let Ok((mut target_health, _)) = actor_q.get_mut(target) {
-                    let health_before = target_health.current().map(|h| h.get()).unwrap_or(0);
-                    debug!("target {} health is {}", target, health_before);
-                    let heal_num = rng.random_range(15..30);
-                    target_health.heal_or_revive(heal_num);
-                    let health_after = target_health.current().map(|h| h.get()).unwrap_or(0);
-                    debug!(
-                        "{} has healed {} points, health is now {}",
-                        target, heal_num, health_after
-                    );
+                if let Ok((mut target_health, _, mut effects, _)) = actor_q.get_mut(target) {
+                    let heal_num = rules::roll_heal(&mut *rng);
+                    if no_healing {
+                        log.write(CombatLogEvent(format!(
+                            "{} shielded {}",
+                            log_as(actor),
+                            log_as(target)
+                        )));
+                    } else {
+                        target_health.heal_or_revive(heal_num);
+                        log.write(CombatLogEvent(format!(
+                            "{} healed {} for {heal_num}",
+                            log_as(actor),
+                            log_as(target)
+                        )));
+                    }
+                    inflict_status(&mut *rng, &mut effects, **actor_name, true);
                 }
             }
             ActorName::Theif => {
-                let theif_attack = a_attack.clone();
+                if let Ok((mut target_health, block_chance, effects, defending)) =
+                    actor_q.get_mut(target)
+                {
+                    let outcome = rules::resolve_strike(
+                        &mut *rng,
+                        &a_attack.strike(1.0),
+                        Some(block_chance.guard(defending)),
+                    );
+
+                    match outcome {
+                        Outcome::Hit(damage) | Outcome::Crit(damage) => {
+                            sfx.write(Sfx::Hit);
+                            let dealt = effects.shielded(damage);
+                            target_health.damage(dealt);
+                            log.write(CombatLogEvent(format!(
+                                "{} {} {} for {dealt}",
+                                log_as(actor),
+                                hit_verb(outcome),
+                                log_as(target)
+                            )));
+                        }
+                        Outcome::Blocked => {
+                            sfx.write(Sfx::Miss);
+                            commands.trigger_targets(DamagePopup::Blocked, target);
+                            log.write(CombatLogEvent(format!(
+                                "{} blocked {}'s attack",
+                                log_as(target),
+                                log_as(actor)
+                            )));
+                        }
+                        Outcome::Missed => {
+                            sfx.write(Sfx::Miss);
+                            commands.trigger_targets(DamagePopup::Miss, target);
+                            log.write(CombatLogEvent(format!(
+                                "{} missed {}",
+                                log_as(actor),
+                                log_as(target)
+                            )));
+                        }
+                    }
+                }
+            }
+            name if name.is_guardian() => {
+                let (foes, guards) = living_foes();
+                let strike = a_attack.strike(rules::SWEEP_MULTIPLIER);
 
-                let attack_result = theif_attack.conduct(&mut *rng);
+                match rules::resolve_area_strike(&mut *rng, &strike, &guards) {
+                    Some(outcomes) => {
+                        sfx.write(Sfx::Hit);
+                        log.write(CombatLogEvent(format!(
+                            "{} used Sweeping Strike",
+                            log_as(actor)
+                        )));
 
-                match attack_result {
-                    AttackDamage::Hit(damage) => {
-                        if let Ok((mut target_health, block_chance)) = actor_q.get_mut(target) {
-                            let blocked = rng.random_bool(block_chance.0.into());
-                            if !blocked {
-                                target_health.damage(damage.get());
+                        for (foe, outcome) in foes.into_iter().zip(outcomes) {
+                            let Ok((mut foe_health, _, mut effects, _)) = actor_q.get_mut(foe)
+                            else {
+                                continue;
+                            };
+                            match outcome {
+                                Outcome::Hit(damage) | Outcome::Crit(damage) => {
+                                    let dealt = effects.shielded(damage);
+                                    foe_health.damage(dealt);
+                                    log.write(CombatLogEvent(format!(
+                                        "{} took {dealt}",
+                                        log_as(foe)
+                                    )));
+                                    inflict_status(&mut *rng, &mut effects, name, true);
+                                }
+                                Outcome::Blocked | Outcome::Missed => {
+                                    commands.trigger_targets(DamagePopup::Blocked, foe);
+                                    log.write(CombatLogEvent(format!(
+                                        "{} blocked the sweep",
+                                        log_as(foe)
+                                    )));
+                                }
                             }
                         }
                     }
-                    AttackDamage::Miss => {
-                        debug!("MISSED!!!!!!!!!!!!!!\n");
+                    None => {
+                        sfx.write(Sfx::Miss);
+                        commands.trigger_targets(DamagePopup::Miss, target);
+                        log.write(CombatLogEvent(format!(
+                            "{}'s Sweeping Strike missed",
+                            log_as(actor)
+                        )));
                     }
                 }
             }
+            ActorName::Construct => {
+                commands.run_system_cached_with(charge_up, (actor, target));
+            }
             _ => {}
         },
 
         Action::UseItem { target, item } => {}
         Action::SkipTurn => {}
+        Action::Defend => {
+            commands.entity(actor).insert(Defending);
+            log.write(CombatLogEvent(format!("{} is defending", log_as(actor))));
+        }
+        Action::Flee => {
+            let average_speed = |fleeing: bool| {
+                let speeds: Vec<u32> = team_q
+                    .iter()
+                    .filter(|(_, other)| (*other == team) == fleeing)
+                    .filter(|(entity, _)| {
+                        actor_q
+                            .get(*entity)
+                            .is_ok_and(|(health, ..)| health.is_alive())
+                    })
+                    .filter_map(|(entity, _)| speed_q.get(entity).ok())
+                    .map(|speed| speed.0)
+                    .collect();
+                speeds.iter().sum::<u32>() as f32 / speeds.len().max(1) as f32
+            };
+
+            if rng.random_bool(rules::flee_chance(
+                average_speed(true),
+                average_speed(false),
+            )) {
+                commands.insert_resource(Fled);
+            } else {
+                log.write(CombatLogEvent(format!(
+                    "{} couldn't get away",
+                    log_as(actor)
+                )));
+            }
+        }
+    }
+
+    for fallen in alive_before {
+        if actor_q
+            .get(fallen)
+            .is_ok_and(|(health, ..)| !health.is_alive())
+        {
+            log.write(CombatLogEvent(format!("{} fell", log_as(fallen))));
+            if team_q
+                .get(fallen)
+                .is_ok_and(|(_, team)| *team == Team::Enemy)
+            {
+                stats.enemies_killed += 1;
+            }
+        }
+    }
+
+    for (entity, hurt, before) in health_before {
+        if let Ok((health, ..)) = actor_q.get(entity) {
+            stats.count_damage(hurt, before.saturating_sub(health_points(health)));
+        }
+    }
+    if *team == Team::Player {
+        stats.turns_taken += 1;
+        if let Action::UseItem { .. } = **actor_action {
+            stats.items_used += 1;
+        }
+    }
+
+    // Heroes earn experience for the enemies they finish off.
+    if let Some(target) = target
+        && target_was_alive
+        && *team == Team::Player
+        && actor_q
+            .get(target)
+            .is_ok_and(|(health, ..)| !health.is_alive())
+    {
+        let xp = name_q
+            .get(target)
+            .map_or(0, |name| Experience::for_kill(*name));
+        if xp > 0 {
+            commands.trigger_targets(GainExperience(xp), actor);
+            if let Some(mut rewards) = rewards {
+                rewards.xp += xp;
+            }
+        }
     }
 
     commands.run_system_cached(update_player_hp_bar);
@@ -529,6 +1098,62 @@ fn perform_action(
     next_state.set(CombatState::MoveBack);
 }
 
+/// How the combat log tells a hit apart from a critical one.
+fn hit_verb(outcome: Outcome) -> &'static str {
+    match outcome {
+        Outcome::Crit(_) => "critically hit",
+        _ => "hit",
+    }
+}
+
+/// Leaves the effect `attacker`'s attack can cause, if it lands.
+fn inflict_status(
+    rng: &mut impl Rng,
+    effects: &mut StatusEffects,
+    attacker: ActorName,
+    special: bool,
+) {
+    if let Some((kind, turns, chance)) = StatusKind::from_attack(attacker, special)
+        && rng.random_bool(chance)
+    {
+        debug!("{attacker} inflicted {kind}");
+        effects.apply(kind, turns);
+    }
+}
+
+/// Hurts the acting actor with their poison, and counts down their effects.
+fn tick_status_effects(
+    mut commands: Commands,
+    active_actor: Single<(Entity, &mut StatusEffects, &mut Health, &Team), With<ActingActor>>,
+    names: Query<(&ActorName, Option<&HeroName>)>,
+    mut stats: ResMut<RunStats>,
+    mut log: EventWriter<CombatLogEvent>,
+) {
+    let (actor, mut effects, mut health, team) = active_actor.into_inner();
+    let poison = effects.end_turn();
+
+    if poison > 0 && health.is_alive() {
+        let health_before = health_points(&health);
+        health.damage(poison);
+        stats.count_damage(*team, health_before - health_points(&health));
+        if !health.is_alive() && *team == Team::Enemy {
+            stats.enemies_killed += 1;
+        }
+        let poisoned = log_name(&names, actor);
+        log.write(CombatLogEvent(format!(
+            "{poisoned} took {poison} poison damage"
+        )));
+        commands.run_system_cached(update_player_hp_bar_pit);
+    }
+}
+
+/// Effects only last for the fight they were caused in.
+fn clear_status_effects(mut effects_q: Query<&mut StatusEffects>) {
+    for mut effects in effects_q.iter_mut() {
+        effects.clear();
+    }
+}
+
 pub fn end_turn(
     mut commands: Commands,
     mut queue: ResMut<TurnOrder>,
@@ -538,9 +1163,15 @@ pub fn end_turn(
     health_q: Query<&Health>,
     actor_name: Single<&ActorName, With<ActingActor>>,
     actor_action: Res<ActingActorAction>,
+    fled: Option<Res<Fled>>,
+    skirmish: Option<Res<Skirmish>>,
+    mut notifications: ResMut<Notifications>,
+    mut log: EventWriter<CombatLogEvent>,
 ) {
+    // A Theif killed by poison doesn't get their extra turn.
     if matches!(**actor_name, ActorName::Theif)
         && matches!(actor_action.0, Action::SpecialAction { .. })
+        && health_q.get(queue.active()).is_ok_and(Health::is_alive)
     {
     } else {
         commands.entity(queue.active()).remove::<ActingActor>();
@@ -548,6 +1179,19 @@ pub fn end_turn(
     }
     commands.remove_resource::<ActingActorAction>();
 
+    // A skirmish is the one fight, so it is over with the fight, and getting away gives it up.
+    let (after_win, after_fleeing) = match skirmish {
+        Some(_) => (GameState::Victory, GameState::GameOver),
+        None => (GameState::Navigation, GameState::Navigation),
+    };
+
+    if fled.is_some() {
+        log.write(CombatLogEvent("The party got away".to_string()));
+        notifications.info("The party got away");
+        update_gamestate.set(after_fleeing);
+        return;
+    }
+
     match queue.teams_alive(actor_q) {
         TeamAlive::Both => {
             next_state.set(CombatState::TurnSetup);
@@ -556,15 +1200,71 @@ pub fn end_turn(
         //TODO: If you have time, despawn enemies
         TeamAlive::Player => {
             debug!("Players won");
-            update_gamestate.set(GameState::Navigation);
+            log.write(CombatLogEvent("Victory!".to_string()));
+            update_gamestate.set(after_win);
         }
         TeamAlive::Enemy => {
             debug!("ENEMY WON");
+            log.write(CombatLogEvent("The party has fallen".to_string()));
             update_gamestate.set(GameState::GameOver);
         }
         TeamAlive::Neither => {
             debug!("Everyone is dead!!!!!");
+            log.write(CombatLogEvent("No one is left standing".to_string()));
             update_gamestate.set(GameState::GameOver);
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+
+    #[test]
+    fn banner_counts_enemies_by_kind() {
+        use ActorName as A;
+
+        assert_eq!(
+            enemy_banner([A::Goblin, A::Ogre, A::Goblin]),
+            "2 Goblins, 1 Ogre"
+        );
+        assert_eq!(enemy_banner([A::Skeleton]), "1 Skeleton");
+    }
+
+    #[test]
+    fn enemies_first_keeps_each_team_in_order() {
+        let mut world = World::new();
+        let hero = world.spawn(Team::Player).id();
+        let slow = world.spawn(Team::Enemy).id();
+        let fast_hero = world.spawn(Team::Player).id();
+        let fast = world.spawn(Team::Enemy).id();
+
+        let queue = world
+            .run_system_once(move |team_q: Query<&Team>| {
+                let mut queue = TurnOrder::from_queue([hero, slow, fast_hero, fast].into());
+                queue.enemies_first(&team_q);
+                queue.queue().clone()
+            })
+            .unwrap();
+
+        assert_eq!(queue, VecDeque::from([hero, fast_hero, slow, fast]));
+    }
+
+    #[test]
+    fn ranged_and_area_attackers_stay_back() {
+        assert!(AttackPattern::from_name(ActorName::Ogre).moves_to_center());
+        assert!(!AttackPattern::from_name(ActorName::Archer).moves_to_center());
+        assert!(!AttackPattern::from_name(ActorName::Shaman).moves_to_center());
+
+        assert_eq!(AttackPattern::Ranged.spread_damage(30), 30);
+        assert_eq!(AttackPattern::Area.spread_damage(30), 15);
+        assert_eq!(AttackPattern::Area.spread_damage(1), 1);
+    }
+
+    #[test]
+    fn intro_ends_in_place() {
+        assert_eq!(intro_offset(0.0), (INTRO_SLIDE_DISTANCE, 0.0));
+        assert_eq!(intro_offset(1.0), (0.0, 1.0));
+    }
+}
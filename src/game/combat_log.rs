@@ -0,0 +1,139 @@
+//! A scrolling record of what happened in the fight, shown in the corner during combat.
+
+use super::*;
+use crate::menu::update_scroll_position_event;
+
+/// How many lines the log keeps before dropping the oldest.
+pub const COMBAT_LOG_LINES: usize = 50;
+const COMBAT_LOG_FONT_SIZE: f32 = 18.0;
+
+pub struct CombatLogPlugin;
+
+impl Plugin for CombatLogPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<CombatLogEvent>()
+            .add_systems(
+                OnEnter(GameState::Combat),
+                (init_resource::<CombatLog>, spawn_combat_log),
+            )
+            .add_systems(
+                Update,
+                (append_combat_log, show_combat_log)
+                    .chain()
+                    .run_if(in_state(GameState::Combat)),
+            )
+            .add_systems(OnExit(GameState::Combat), remove_resource::<CombatLog>);
+    }
+}
+
+/// A line to add to the combat log.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct CombatLogEvent(pub String);
+
+/// The lines of the fight going on, oldest first.
+#[derive(Resource, Debug, Default)]
+pub struct CombatLog {
+    lines: VecDeque<String>,
+}
+
+impl CombatLog {
+    pub fn push(&mut self, line: String) {
+        self.lines.push_back(line);
+        while self.lines.len() > COMBAT_LOG_LINES {
+            self.lines.pop_front();
+        }
+    }
+
+    pub fn lines(&self) -> impl Iterator<Item = &str> {
+        self.lines.iter().map(String::as_str)
+    }
+}
+
+#[derive(Component)]
+struct CombatLogPanel;
+
+/// What an actor is called in the log, a hero by their name.
+pub fn log_name(names: &Query<(&ActorName, Option<&HeroName>)>, actor: Entity) -> String {
+    match names.get(actor) {
+        Ok((_, Some(hero_name))) => hero_name.0.clone(),
+        Ok((name, None)) => name.to_string(),
+        Err(_) => "Someone".to_string(),
+    }
+}
+
+fn spawn_combat_log(mut commands: Commands, style: Res<Style>) {
+    commands
+        .spawn((
+            Name::new("Combat Log"),
+            CombatLogPanel,
+            HudPart(HudElement::EventLog),
+            Node {
+                position_type: PositionType::Absolute,
+                right: Val::Px(10.0),
+                bottom: Val::Px(10.0),
+                width: Val::Px(320.0),
+                height: Val::Px(180.0),
+                padding: UiRect::all(Val::Px(8.0)),
+                row_gap: Val::Px(2.0),
+                overflow: Overflow::scroll_y(),
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            BackgroundColor(style.background_color.with_alpha(0.8)),
+            StateScoped(GameState::Combat),
+        ))
+        .observe(update_scroll_position_event);
+}
+
+fn append_combat_log(mut events: EventReader<CombatLogEvent>, mut log: ResMut<CombatLog>) {
+    for CombatLogEvent(line) in events.read() {
+        log.push(line.clone());
+    }
+}
+
+fn show_combat_log(
+    mut commands: Commands,
+    log: Res<CombatLog>,
+    style: Res<Style>,
+    panel: Single<(Entity, &mut ScrollPosition), With<CombatLogPanel>>,
+) {
+    if !log.is_changed() {
+        return;
+    }
+
+    let (panel, mut scroll) = panel.into_inner();
+    commands
+        .entity(panel)
+        .despawn_related::<Children>()
+        .with_children(|builder| {
+            for line in log.lines() {
+                builder.spawn((
+                    Text::new(line),
+                    style.font(COMBAT_LOG_FONT_SIZE),
+                    TextColor(style.text_color),
+                ));
+            }
+        });
+    // Stick to the newest line, layout clamps it to the bottom.
+    scroll.offset_y = f32::MAX;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn log_drops_the_oldest_lines() {
+        let mut log = CombatLog::default();
+        for turn in 0..COMBAT_LOG_LINES + 10 {
+            log.push(format!("Turn {turn}"));
+        }
+
+        assert_eq!(log.lines().count(), COMBAT_LOG_LINES);
+        assert_eq!(log.lines().next(), Some("Turn 10"));
+        assert_eq!(
+            log.lines().last(),
+            Some(format!("Turn {}", COMBAT_LOG_LINES + 9).as_str())
+        );
+    }
+}
@@ -0,0 +1,214 @@
+//! Optional pressure to keep moving. Every so many turns a Pillar that hasn't been collected
+//! is corrupted, and the enemies in its quarter of the map are stronger until it is collected.
+
+use super::*;
+use crate::generate_map::MapTile;
+use serde::{Deserialize, Serialize};
+use std::num::NonZero;
+
+/// How many rooms the party walks into between each corruption.
+pub const CORRUPTION_TURNS: u32 = 50;
+/// How much the health and damage of enemies near a corrupted pillar is multiplied by.
+pub const CORRUPTION_SCALE: f32 = 1.25;
+const CORRUPTED_TILE_COLOR: Color = Color::srgb(0.65, 0.3, 0.8);
+
+pub struct CorruptionPlugin;
+
+impl Plugin for CorruptionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnExit(GameState::Navigation),
+            advance_corruption.run_if(resource_exists::<PillarCorruption>),
+        )
+        .add_systems(
+            OnEnter(GameState::EnterRoom),
+            corrupt_room_enemies
+                .after(spawn_room_entities)
+                .before(suspend::restore_combatants)
+                .run_if(resource_exists::<PillarCorruption>),
+        )
+        .add_systems(
+            Update,
+            (lift_corruption, show_corrupted_pillars)
+                .chain()
+                .run_if(in_state(AppState::Game).and(resource_exists::<PillarCorruption>)),
+        )
+        .add_systems(OnExit(AppState::Game), remove_resource::<PillarCorruption>);
+    }
+}
+
+/// Only present in games started with corruption on.
+#[derive(Resource, Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PillarCorruption {
+    /// Rooms walked into since the game started.
+    turns: u32,
+    /// The map positions of the corrupted pillars that haven't been collected.
+    corrupted: Vec<(u32, u32)>,
+}
+
+/// Which quarter of a map of the size a room is in.
+fn quadrant(pos: &TilePos, size: &TilemapSize) -> (bool, bool) {
+    (pos.x < size.x / 2, pos.y < size.y / 2)
+}
+
+impl PillarCorruption {
+    /// Counts a turn, returning whether a pillar is corrupted this turn.
+    pub fn advance(&mut self) -> bool {
+        self.turns += 1;
+        self.turns.is_multiple_of(CORRUPTION_TURNS)
+    }
+
+    pub fn corrupt(&mut self, pos: &TilePos) {
+        if !self.is_corrupted(pos) {
+            self.corrupted.push((pos.x, pos.y));
+        }
+    }
+
+    /// Lifts the corruption, once the pillar is collected.
+    pub fn lift(&mut self, pos: &TilePos) {
+        self.corrupted
+            .retain(|corrupted| *corrupted != (pos.x, pos.y));
+    }
+
+    /// Moves the corrupted pillars along with the map, when it grows in endless mode.
+    pub fn shift(&mut self, by: u32) {
+        for (x, y) in self.corrupted.iter_mut() {
+            *x += by;
+            *y += by;
+        }
+    }
+
+    /// Forgets the pillars of the floor the party left, they can't be collected anymore.
+    pub fn leave_floor(&mut self) {
+        self.corrupted.clear();
+    }
+
+    pub fn is_corrupted(&self, pos: &TilePos) -> bool {
+        self.corrupted.contains(&(pos.x, pos.y))
+    }
+
+    /// Whether the enemies of the room are made stronger by a corrupted pillar.
+    pub fn strengthens(&self, room: &TilePos, size: &TilemapSize) -> bool {
+        self.corrupted
+            .iter()
+            .any(|(x, y)| quadrant(&TilePos::new(*x, *y), size) == quadrant(room, size))
+    }
+}
+
+/// Corrupts the nearest pillar left once enough turns have gone by.
+fn advance_corruption(
+    mut corruption: ResMut<PillarCorruption>,
+    current_room: Query<&TilePos, (With<CurrentRoom>, With<MapTile>)>,
+    rooms: Query<(&TilePos, &RoomInfo), With<MapTile>>,
+    mut notifications: ResMut<Notifications>,
+) {
+    if !corruption.advance() {
+        return;
+    }
+    let Ok(party) = current_room.single() else {
+        return;
+    };
+
+    let nearest = rooms
+        .iter()
+        .filter(|(pos, info)| info.has_pillar() && !corruption.is_corrupted(pos))
+        .min_by_key(|(pos, _)| (hex_distance(party, pos), pos.x, pos.y));
+
+    if let Some((pos, _)) = nearest {
+        corruption.corrupt(pos);
+        notifications.warn("A Pillar has been corrupted! The monsters around it grow stronger.");
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn corrupt_room_enemies(
+    corruption: Res<PillarCorruption>,
+    current_room: Single<&TilePos, (With<CurrentRoom>, With<MapTile>)>,
+    map: Single<&TilemapSize, With<MapTilemap>>,
+    mut enemies: Query<(&Team, &mut Health, &mut Attack), (With<InRoom>, With<Actor>)>,
+) {
+    if !corruption.strengthens(*current_room, *map) {
+        return;
+    }
+
+    let scale = |stat: u32| ((stat as f32 * CORRUPTION_SCALE).round() as u32).max(1);
+    for (team, mut health, mut attack) in enemies.iter_mut() {
+        if *team != Team::Enemy {
+            continue;
+        }
+
+        let current = health.current().map(|current| scale(current.get()));
+        let max = NonZero::new(scale(health.max().get())).unwrap();
+        *health = Health::with_current(current.and_then(NonZero::new), max);
+        *attack = Attack::new(
+            scale(attack.damage().start)..scale(attack.damage().end),
+            attack.hit_chance(),
+        );
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn lift_corruption(
+    mut corruption: ResMut<PillarCorruption>,
+    rooms: Query<(&TilePos, &RoomInfo), (With<MapTile>, Changed<RoomInfo>)>,
+) {
+    for (pos, info) in rooms.iter() {
+        if !info.has_pillar() && corruption.is_corrupted(pos) {
+            corruption.lift(pos);
+        }
+    }
+}
+
+fn show_corrupted_pillars(
+    corruption: Res<PillarCorruption>,
+    mut rooms: Query<(&TilePos, &RoomInfo, &mut TileColor), With<MapTile>>,
+) {
+    if !corruption.is_changed() {
+        return;
+    }
+
+    for (pos, info, mut color) in rooms.iter_mut() {
+        if info.r_type == RoomType::Pillar {
+            color.0 = match corruption.is_corrupted(pos) {
+                true => CORRUPTED_TILE_COLOR,
+                false => Color::WHITE,
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::generate_map::MapShape;
+
+    #[test]
+    fn pillars_are_corrupted_every_few_turns() {
+        let mut corruption = PillarCorruption::default();
+        let corrupting: Vec<u32> = (1..=CORRUPTION_TURNS * 2 + 1)
+            .filter(|_| corruption.advance())
+            .collect();
+
+        assert_eq!(corrupting, vec![CORRUPTION_TURNS, CORRUPTION_TURNS * 2]);
+    }
+
+    #[test]
+    fn corruption_strengthens_its_quarter_until_collected() {
+        let mut corruption = PillarCorruption::default();
+        let pillar = TilePos::new(1, 1);
+        let near = TilePos::new(2, 3);
+        let size = MapShape::default().size();
+        let far = TilePos::new(size.x - 1, 2);
+
+        assert!(!corruption.strengthens(&near, &size));
+
+        corruption.corrupt(&pillar);
+        corruption.corrupt(&pillar);
+        assert!(corruption.strengthens(&near, &size));
+        assert!(corruption.strengthens(&pillar, &size));
+        assert!(!corruption.strengthens(&far, &size));
+
+        corruption.lift(&pillar);
+        assert!(!corruption.strengthens(&near, &size));
+    }
+}
@@ -0,0 +1,401 @@
+//! Hex shards, found now and then in pits and after fights, and the Hex Forge at the Entrance
+//! that turns them into items or boosts for the whole party.
+//!
+//! Whether a room drops shards, and how many, is rolled from the room's seed like its chest.
+//! What the forge makes is kept in `assets/crafting/recipes.ron`.
+//! The shards the party carries are saved with the game, see [`SaveGame::save_hex_shards`].
+
+use super::*;
+use crate::room::{PROP_LAYER, RoomLayout};
+use rand::{Rng, SeedableRng};
+use serde::Deserialize;
+
+const RECIPES_RON: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/assets/crafting/recipes.ron"
+));
+
+/// Mixed into a room's seed for its shards, so dropping them doesn't change its events.
+const SHARD_RNG_STREAM: u64 = 0x4e85_0000_5ba2_d005;
+/// One in this many pit and combat rooms drops shards.
+const SHARD_CHANCE: u32 = 3;
+/// How many shards a room can drop.
+const SHARD_DROP: std::ops::RangeInclusive<u32> = 1..=2;
+/// The forge sits in the middle of the Entrance, where nothing else is put.
+const FORGE_POSITION: IVec2 = IVec2::ZERO;
+const FORGE_SIZE: Vec2 = Vec2::new(24.0, 24.0);
+const FORGE_COLOR: Color = Color::srgb_u8(0xc4, 0xa7, 0xe7);
+
+pub struct CraftingPlugin;
+
+impl Plugin for CraftingPlugin {
+    fn build(&self, app: &mut App) {
+        let recipes: Recipes = ron::from_str(RECIPES_RON).expect("recipes.ron should be valid");
+
+        app.insert_resource(recipes)
+            .add_observer(click_hex_forge)
+            .add_systems(OnEnter(AppState::Game), init_resource::<HexShards>)
+            .add_systems(
+                OnEnter(GameState::Navigation),
+                (
+                    drop_hex_shards
+                        .before(mark_room_cleared)
+                        .run_if(not(resource_exists::<Fled>)),
+                    spawn_hex_forge.run_if(in_entrance),
+                ),
+            )
+            .add_systems(OnEnter(GameState::Crafting), spawn_crafting_menu)
+            .add_systems(OnExit(AppState::Game), remove_resource::<HexShards>);
+    }
+}
+
+/// The hex shards the party carries.
+#[derive(Resource, Deref, DerefMut, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HexShards(pub u32);
+
+impl HexShards {
+    /// The shards a room drops the first time the party is done with it, if it drops any.
+    pub fn roll(info: &RoomInfo) -> u32 {
+        if info.cleared || !matches!(info.r_type, RoomType::Pit(_) | RoomType::Combat(_)) {
+            return 0;
+        }
+
+        let mut rng = RandomSource::seed_from_u64(info.rng_seed ^ SHARD_RNG_STREAM);
+        match rng.random_ratio(1, SHARD_CHANCE) {
+            true => rng.random_range(SHARD_DROP),
+            false => 0,
+        }
+    }
+
+    /// Takes `cost` shards, or leaves them be and returns false if there aren't enough.
+    pub fn spend(&mut self, cost: u32) -> bool {
+        if self.0 < cost {
+            return false;
+        }
+        self.0 -= cost;
+        true
+    }
+}
+
+/// A boost the forge gives every hero for the rest of the game.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum StatBoost {
+    /// Added to both ends of the damage range.
+    Damage(u32),
+    Health(u32),
+    BlockChance(f32),
+}
+
+impl StatBoost {
+    pub fn apply(&self, attack: &mut Attack, block_chance: &mut BlockChance, health: &mut Health) {
+        match *self {
+            StatBoost::Damage(damage) => {
+                *attack = Attack::new(
+                    attack.damage().start + damage..attack.damage().end + damage,
+                    attack.hit_chance(),
+                )
+            }
+            StatBoost::Health(amount) => health.raise_max(amount),
+            StatBoost::BlockChance(chance) => block_chance.0 += chance,
+        }
+    }
+}
+
+/// What a recipe makes.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum CraftOutput {
+    Item(Item),
+    Boost(StatBoost),
+}
+
+impl CraftOutput {
+    pub fn describe(&self) -> String {
+        match self {
+            CraftOutput::Item(item) => match item.slot() {
+                Some(_) => format!("{item} ({})", item.bonus().describe()),
+                None => item.to_string(),
+            },
+            CraftOutput::Boost(StatBoost::Damage(damage)) => {
+                format!("+{damage} damage for the party")
+            }
+            CraftOutput::Boost(StatBoost::Health(health)) => {
+                format!("+{health} max health for the party")
+            }
+            CraftOutput::Boost(StatBoost::BlockChance(chance)) => {
+                format!("+{:.0}% block for the party", chance * 100.0)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Recipe {
+    pub name: String,
+    pub cost: u32,
+    pub output: CraftOutput,
+}
+
+/// Everything the forge can make, loaded from `assets/crafting/recipes.ron`.
+#[derive(Resource, Debug, Clone, PartialEq, Deref, Deserialize)]
+#[serde(transparent)]
+pub struct Recipes(pub Vec<Recipe>);
+
+/// The forge in the Entrance, clicked to open the crafting menu.
+#[derive(Component)]
+struct HexForge;
+
+/// The root of the crafting menu, so it can be rebuilt after something is made.
+#[derive(Component)]
+struct CraftingMenu;
+
+fn drop_hex_shards(
+    info: Single<&RoomInfo, With<CurrentRoom>>,
+    mut shards: ResMut<HexShards>,
+    mut notifications: ResMut<Notifications>,
+) {
+    let dropped = HexShards::roll(&info);
+    if dropped == 0 {
+        return;
+    }
+
+    let first = shards.0 == 0;
+    **shards += dropped;
+    let plural = if dropped == 1 { "" } else { "s" };
+    notifications.success(format!("Found {dropped} hex shard{plural}"));
+    if first {
+        notifications.info("Hex shards can be forged into something useful at the Entrance");
+    }
+}
+
+fn spawn_hex_forge(
+    mut commands: Commands,
+    forges: Query<(), With<HexForge>>,
+    tilemap: Single<
+        (
+            &TilemapSize,
+            &TilemapGridSize,
+            &TilemapTileSize,
+            &TilemapType,
+            &TilemapAnchor,
+        ),
+        With<RoomTilemap>,
+    >,
+) {
+    if !forges.is_empty() {
+        return;
+    }
+
+    let world_pos = RoomLayout::new(*tilemap).world_pos(FORGE_POSITION);
+    commands.spawn((
+        InRoom,
+        HexForge,
+        Sprite::from_color(FORGE_COLOR, FORGE_SIZE),
+        Transform::from_xyz(world_pos.x, world_pos.y, PROP_LAYER)
+            .with_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_6)),
+        Pickable::default(),
+        Tooltip::new("Hex Forge").with_body("Click to forge hex shards into something useful"),
+    ));
+}
+
+fn click_hex_forge(
+    mut click: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    forges: Query<(), With<HexForge>>,
+    game_state: Option<Res<State<GameState>>>,
+) {
+    if !forges.contains(click.target()) {
+        return;
+    }
+    click.propagate(false);
+
+    let exploring = game_state.is_some_and(|state| *state.get() == GameState::Navigation);
+    if click.button == PointerButton::Primary && exploring {
+        commands.set_state(GameState::Crafting);
+    }
+}
+
+fn spawn_crafting_menu(
+    mut commands: Commands,
+    style: Res<Style>,
+    recipes: Res<Recipes>,
+    shards: Res<HexShards>,
+    menus: Query<Entity, With<CraftingMenu>>,
+) {
+    for menu in menus.iter() {
+        commands.entity(menu).despawn();
+    }
+
+    let button_node = Node {
+        width: Val::Px(560.0),
+        height: Val::Px(50.0),
+        margin: UiRect::all(Val::Px(5.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let text_style = (style.font(24.0), TextColor(style.text_color));
+
+    commands
+        .spawn((
+            Node {
+                align_self: AlignSelf::Center,
+                justify_self: JustifySelf::Center,
+                padding: UiRect::all(Val::Px(20.0)),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(style.background_color),
+            StateScoped(GameState::Crafting),
+            CraftingMenu,
+        ))
+        .with_children(|builder| {
+            builder.spawn((
+                Text::new("Hex Forge"),
+                style.font(50.0),
+                TextColor(style.text_color),
+            ));
+            builder.spawn((
+                Text::new(format!("Hex Shards: {}", shards.0)),
+                text_style.clone(),
+            ));
+
+            for (index, recipe) in recipes.iter().enumerate() {
+                let color = match shards.0 >= recipe.cost {
+                    true => style.button_color,
+                    false => style.button_color.with_alpha(0.4),
+                };
+                builder
+                    .spawn((
+                        Button,
+                        button_node.clone(),
+                        BackgroundColor(color),
+                        children![(
+                            Text::new(format!(
+                                "{} ({} shards): {}",
+                                recipe.name,
+                                recipe.cost,
+                                recipe.output.describe()
+                            )),
+                            text_style.clone(),
+                            Pickable::IGNORE
+                        )],
+                    ))
+                    .observe(craft_on_click(index));
+            }
+
+            builder
+                .spawn((
+                    Button,
+                    Node {
+                        width: Val::Px(200.0),
+                        ..button_node.clone()
+                    },
+                    BackgroundColor(style.button_color),
+                    children![(Text::new("Leave"), text_style.clone(), Pickable::IGNORE)],
+                ))
+                .observe(change_state_on_click(
+                    PointerButton::Primary,
+                    GameState::Navigation,
+                ));
+        });
+}
+
+fn craft_on_click(recipe: usize) -> impl Fn(Trigger<Pointer<Click>>, Commands) {
+    move |mut click, mut commands| {
+        click.propagate(false);
+
+        if click.button == PointerButton::Primary {
+            commands.run_system_cached_with(craft, recipe);
+        }
+    }
+}
+
+/// Makes the recipe, if the party has the shards for it.
+fn craft(
+    In(recipe): In<usize>,
+    mut commands: Commands,
+    recipes: Res<Recipes>,
+    mut shards: ResMut<HexShards>,
+    mut items: ResMut<Items>,
+    mut party: Query<(&mut Attack, &mut BlockChance, &mut Health), With<PartySlot>>,
+    mut notifications: ResMut<Notifications>,
+) {
+    let Some(recipe) = recipes.get(recipe) else {
+        return;
+    };
+    if !shards.spend(recipe.cost) {
+        notifications.warn(format!("{} needs {} hex shards", recipe.name, recipe.cost));
+        return;
+    }
+
+    match recipe.output {
+        CraftOutput::Item(item) => items.push(item),
+        CraftOutput::Boost(boost) => {
+            for (mut attack, mut block_chance, mut health) in party.iter_mut() {
+                boost.apply(&mut attack, &mut block_chance, &mut health);
+            }
+            commands.run_system_cached(update_player_hp_bar_pit);
+        }
+    }
+    notifications.success(format!("Forged {}", recipe.name));
+
+    commands.run_system_cached(spawn_crafting_menu);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::num::NonZero;
+
+    #[test]
+    fn recipes_parse() {
+        let recipes: Recipes = ron::from_str(RECIPES_RON).unwrap();
+        assert!(!recipes.is_empty());
+        for recipe in recipes.iter() {
+            assert!(recipe.cost > 0, "{} is free", recipe.name);
+        }
+    }
+
+    #[test]
+    fn shards_are_rolled_from_the_room_seed() {
+        let mut dropped = 0;
+        for seed in 0..300 {
+            let pit = RoomInfo::from_type(RoomType::Pit(10), seed);
+            let shards = HexShards::roll(&pit);
+            assert_eq!(shards, HexShards::roll(&pit));
+            assert!(shards == 0 || SHARD_DROP.contains(&shards));
+            if shards > 0 {
+                dropped += 1;
+            }
+
+            let mut cleared = pit.clone();
+            cleared.cleared = true;
+            assert_eq!(HexShards::roll(&cleared), 0);
+            assert_eq!(
+                HexShards::roll(&RoomInfo::from_type(RoomType::EmptyRoom, seed)),
+                0
+            );
+        }
+        assert!(dropped > 50 && dropped < 150);
+    }
+
+    #[test]
+    fn boosts_are_only_paid_for_when_affordable() {
+        let mut shards = HexShards(5);
+        assert!(shards.spend(4));
+        assert!(!shards.spend(4));
+        assert_eq!(shards, HexShards(1));
+
+        let mut attack = Attack::new(20..31, 0.7);
+        let mut block_chance = BlockChance(0.2);
+        let mut health = Health::new(NonZero::new(50).unwrap());
+        StatBoost::Damage(2).apply(&mut attack, &mut block_chance, &mut health);
+        StatBoost::Health(10).apply(&mut attack, &mut block_chance, &mut health);
+        StatBoost::BlockChance(0.9).apply(&mut attack, &mut block_chance, &mut health);
+        assert_eq!(*attack.damage(), 22..33);
+        assert_eq!(health.max().get(), 60);
+        assert!((block_chance.0 - 1.1).abs() < 1e-6);
+        assert_eq!(block_chance.while_defending(false), 1.0);
+    }
+}
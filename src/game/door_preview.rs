@@ -0,0 +1,172 @@
+//! A ghost arrow from the party to the door being hovered or focused in [`GameState::Navigation`],
+//! so it's clear which way the party will go before they do.
+
+use super::*;
+use crate::focus_nav::NavFocus;
+use std::f32::consts::FRAC_PI_4;
+
+/// Under the actors, over the room's floor and props.
+const ARROW_LAYER: f32 = 0.8;
+const ARROW_WIDTH: f32 = 10.0;
+const ARROW_HEAD_SIZE: f32 = 24.0;
+/// How far short of the door the arrow stops.
+const ARROW_GAP: f32 = 20.0;
+const ARROW_ALPHA: f32 = 0.5;
+
+pub struct DoorPreviewPlugin;
+
+impl Plugin for DoorPreviewPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HoveredDoor>()
+            .add_systems(OnEnter(GameState::Navigation), spawn_door_arrow)
+            .add_systems(
+                Update,
+                point_door_arrow.run_if(in_state(GameState::Navigation)),
+            )
+            .add_systems(OnExit(GameState::Navigation), remove_hovered_door);
+    }
+}
+
+/// The door the pointer is over, if any.
+#[derive(Resource, Default, Debug, Deref, DerefMut)]
+pub struct HoveredDoor(pub Option<Entity>);
+
+/// Both parts of the arrow, the shaft and its head.
+#[derive(Component)]
+struct DoorArrow;
+
+#[derive(Component)]
+struct DoorArrowHead;
+
+pub fn hover_door(over: Trigger<Pointer<Over>>, mut hovered: ResMut<HoveredDoor>) {
+    hovered.0 = Some(over.target());
+}
+
+pub fn unhover_door(out: Trigger<Pointer<Out>>, mut hovered: ResMut<HoveredDoor>) {
+    if hovered.0 == Some(out.target()) {
+        hovered.0 = None;
+    }
+}
+
+fn remove_hovered_door(mut hovered: ResMut<HoveredDoor>) {
+    hovered.0 = None;
+}
+
+/// Where the arrow's shaft goes and how it is turned, with where its tip is.
+/// `None` if the party is already on top of the door.
+pub fn arrow_between(from: Vec2, to: Vec2) -> Option<(Transform, f32, Vec2)> {
+    let offset = to - from;
+    let length = offset.length() - ARROW_GAP - ARROW_HEAD_SIZE / 2.0;
+    if length <= 0.0 {
+        return None;
+    }
+
+    let direction = offset.normalize();
+    let shaft = Transform::from_translation((from + direction * length / 2.0).extend(ARROW_LAYER))
+        .with_rotation(Quat::from_rotation_z(direction.to_angle()));
+    Some((shaft, length, from + direction * length))
+}
+
+fn spawn_door_arrow(mut commands: Commands, style: Res<Style>) {
+    let color = style.focus_color.with_alpha(ARROW_ALPHA);
+    for size in [ARROW_WIDTH, ARROW_HEAD_SIZE] {
+        let mut arrow = commands.spawn((
+            DoorArrow,
+            Sprite::from_color(color, Vec2::splat(size)),
+            Transform::default(),
+            Visibility::Hidden,
+            Pickable::IGNORE,
+            StateScoped(GameState::Navigation),
+        ));
+        if size == ARROW_HEAD_SIZE {
+            arrow.insert(DoorArrowHead);
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn point_door_arrow(
+    hovered: Res<HoveredDoor>,
+    focus: Res<NavFocus>,
+    doors: Query<(&TilePos, &TileVisible), With<EntranceDirection>>,
+    room_map: Single<
+        (
+            &TilemapSize,
+            &TilemapGridSize,
+            &TilemapTileSize,
+            &TilemapType,
+            &TilemapAnchor,
+            &GlobalTransform,
+        ),
+        With<RoomTilemap>,
+    >,
+    party: Query<(&GlobalTransform, &Health), With<PartySlot>>,
+    mut arrow: Query<
+        (
+            &mut Transform,
+            &mut Sprite,
+            &mut Visibility,
+            Has<DoorArrowHead>,
+        ),
+        With<DoorArrow>,
+    >,
+) {
+    let door = hovered
+        .or(focus.0)
+        .and_then(|door| doors.get(door).ok())
+        .filter(|(_, visible)| visible.0);
+
+    let (map_size, grid_size, tile_size, map_type, anchor, map_transform) = *room_map;
+    let living: Vec<Vec2> = party
+        .iter()
+        .filter(|(_, health)| health.is_alive())
+        .map(|(transform, _)| transform.translation().truncate())
+        .collect();
+    let placed = door.filter(|_| !living.is_empty()).and_then(|(pos, _)| {
+        let door = map_transform
+            .transform_point(
+                pos.center_in_world(map_size, grid_size, tile_size, map_type, anchor)
+                    .extend(0.0),
+            )
+            .truncate();
+        let party = living.iter().sum::<Vec2>() / living.len() as f32;
+        arrow_between(party, door)
+    });
+
+    for (mut transform, mut sprite, mut visibility, head) in arrow.iter_mut() {
+        let Some((shaft, length, tip)) = placed else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+
+        *visibility = Visibility::Inherited;
+        if head {
+            // A square turned on its corner, pointing the way the shaft goes.
+            *transform = Transform::from_translation(tip.extend(ARROW_LAYER))
+                .with_rotation(shaft.rotation * Quat::from_rotation_z(FRAC_PI_4));
+        } else {
+            *transform = shaft;
+            sprite.custom_size = Some(Vec2::new(length, ARROW_WIDTH));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::f32::consts::FRAC_PI_2;
+
+    #[test]
+    fn arrows_point_at_the_door_and_stop_short() {
+        let from = Vec2::new(10.0, 20.0);
+        let to = Vec2::new(10.0, 220.0);
+        let (shaft, length, tip) = arrow_between(from, to).unwrap();
+
+        assert!(tip.distance(to) > ARROW_GAP);
+        assert!((tip.distance(from) - length).abs() < 0.001);
+        assert!((shaft.translation.y - (from.y + length / 2.0)).abs() < 0.001);
+        assert!((shaft.rotation.to_euler(EulerRot::XYZ).2 - FRAC_PI_2).abs() < 0.001);
+
+        assert!(arrow_between(from, from + Vec2::X).is_none());
+    }
+}
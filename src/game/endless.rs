@@ -0,0 +1,279 @@
+//! Endless mode, offered on the victory screen.
+//!
+//! The map keeps growing outward a few rings at a time as the party nears its edge,
+//! with each new ring rolled from its own stream of the world's seed and harder
+//! encounters the further out it is.
+
+use super::*;
+use crate::camera::CAMERA_MAP_SCALE;
+use crate::generate_map::{GenerationSettings, MapShape, MapTile};
+#[cfg(feature = "sqlite")]
+use crate::saving::LastSavedGame;
+use bevy_ecs_tilemap::helpers::filling::generate_hex_ring;
+use bevy_ecs_tilemap::helpers::hex_grid::axial::AxialPos;
+
+/// How many rings the map grows by at a time.
+pub const ENDLESS_CHUNK_RINGS: u32 = 2;
+/// How close to the edge of the map the party gets before it grows.
+const FRONTIER_DISTANCE: u32 = 1;
+/// How much deeper a room counts for its encounter budget with each ring out from the entrance.
+const DEPTH_PER_RING: u32 = 2;
+/// Mixed into the world seed, so the rooms of new rings don't repeat those of the first map.
+const ENDLESS_RNG_STREAM: u64 = 0x3e6d_1e55_0c4a_2b17;
+
+pub struct EndlessPlugin;
+
+impl Plugin for EndlessPlugin {
+    fn build(&self, app: &mut App) {
+        let extend = extend_map
+            .run_if(in_endless_mode.and(party_at_frontier))
+            .before(navigation_enter);
+        #[cfg(feature = "sqlite")]
+        let extend = extend.before(save_game);
+
+        app.add_systems(OnEnter(GameState::Navigation), extend)
+            .add_systems(Update, fit_map_camera.run_if(in_state(AppState::Game)));
+    }
+}
+
+/// Whether the map has grown past its starting size, which only happens in endless mode.
+pub fn in_endless_mode(
+    map: Query<&TilemapSize, With<MapTilemap>>,
+    shape: Option<Res<MapShape>>,
+) -> bool {
+    let start = shape.map_or(MapShape::default(), |shape| *shape).size();
+    map.single().is_ok_and(|size| size.x > start.x)
+}
+
+fn map_center(size: &TilemapSize) -> TilePos {
+    TilePos {
+        x: size.x / 2,
+        y: size.y / 2,
+    }
+}
+
+fn party_at_frontier(
+    current_room: Query<&TilePos, With<CurrentRoom>>,
+    map: Query<&TilemapSize, With<MapTilemap>>,
+) -> bool {
+    let (Ok(pos), Ok(size)) = (current_room.single(), map.single()) else {
+        return false;
+    };
+    hex_distance(pos, &map_center(size)) + FRONTIER_DISTANCE >= size.x / 2
+}
+
+/// Keeps playing past the victory screen, on a map that keeps growing.
+pub fn start_endless_mode(
+    mut click: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    click.propagate(false);
+
+    if click.button == PointerButton::Primary {
+        commands.run_system_cached(extend_map);
+        next_state.set(GameState::Navigation);
+    }
+}
+
+/// The rooms added when a map of `radius` grows by `rings`, placed on the grown map.
+///
+/// Every new ring is filled, and a corridor is carved out to them from `from`,
+/// so they can be reached wherever the old edge of the map was.
+pub fn chunk_rooms(
+    settings: &GenerationSettings,
+    radius: u32,
+    rings: u32,
+    from: TilePos,
+    is_room: impl Fn(&TilePos) -> bool,
+) -> Vec<(TilePos, RoomInfo)> {
+    let center = TilePos::new(radius + rings, radius + rings);
+    let mut rng = RandomSource::seed_from_u64(settings.seed ^ ENDLESS_RNG_STREAM ^ radius as u64);
+    let mut roll = |pos: TilePos| {
+        let depth = hex_distance(&pos, &center) * DEPTH_PER_RING;
+        let r_type = RoomType::from_rng(&mut rng, settings, depth);
+        (pos, RoomInfo::from_type(r_type, rng.random()))
+    };
+
+    let mut rooms = Vec::new();
+    let mut pos = from;
+    while hex_distance(&pos, &center) < radius {
+        pos = EntranceDirection::ALL
+            .iter()
+            .map(|dir| (AxialPos::from(pos) + dir.axial_offset()).as_tile_pos_unchecked())
+            .max_by_key(|next| hex_distance(next, &center))
+            .unwrap();
+        if !is_room(&pos) {
+            rooms.push(roll(pos));
+        }
+    }
+
+    for ring in radius + 1..=radius + rings {
+        for pos in generate_hex_ring(AxialPos::from(center), ring) {
+            rooms.push(roll(pos.as_tile_pos_unchecked()));
+        }
+    }
+    rooms
+}
+
+/// Grows the map outward by [`ENDLESS_CHUNK_RINGS`], moving everything on it
+/// so the old map stays in the middle.
+#[allow(clippy::too_many_arguments)]
+fn extend_map(
+    mut commands: Commands,
+    #[cfg(not(feature = "sqlite"))] difficulty: Option<Res<Difficulty>>,
+    map: Single<(Entity, &mut TilemapSize, &mut TileStorage), With<MapTilemap>>,
+    mut rooms: Query<(Entity, &mut TilePos, Has<CurrentRoom>), With<MapTile>>,
+    breadcrumbs: Option<ResMut<Breadcrumbs>>,
+    corruption: Option<ResMut<PillarCorruption>>,
+    mut notifications: ResMut<Notifications>,
+    #[cfg(feature = "sqlite")] save: Res<SaveGame>,
+    #[cfg(feature = "sqlite")] db: NonSend<Database>,
+) {
+    let (map_entity, mut size, mut storage) = map.into_inner();
+    let rings = ENDLESS_CHUNK_RINGS;
+    let radius = size.x / 2;
+    let grown = TilemapSize {
+        x: size.x + rings * 2,
+        y: size.y + rings * 2,
+    };
+
+    let mut grown_storage = TileStorage::empty(grown);
+    let mut from = map_center(&grown);
+    for (entity, mut pos, current) in rooms.iter_mut() {
+        pos.x += rings;
+        pos.y += rings;
+        grown_storage.set(&pos, entity);
+
+        if current {
+            from = *pos;
+        }
+    }
+
+    #[cfg(feature = "sqlite")]
+    let settings = save.generation_settings();
+    #[cfg(not(feature = "sqlite"))]
+    let settings = GenerationSettings {
+        difficulty: difficulty.map_or_else(Difficulty::default, |difficulty| *difficulty),
+        ..default()
+    };
+    let new_rooms = chunk_rooms(&settings, radius, rings, from, |pos| {
+        grown_storage.checked_get(pos).is_some()
+    });
+    for (pos, info) in new_rooms {
+        let id = commands
+            .spawn((
+                info,
+                TileBundle {
+                    position: pos,
+                    tilemap_id: TilemapId(map_entity),
+                    texture_index: TileTextureIndex(FLOOR_TILE_VARIENTS.start),
+                    visible: TileVisible(false),
+                    ..default()
+                },
+                MapTile,
+            ))
+            .id();
+        commands.entity(map_entity).add_child(id);
+        grown_storage.set(&pos, id);
+    }
+
+    *size = grown;
+    *storage = grown_storage;
+
+    if let Some(mut breadcrumbs) = breadcrumbs {
+        breadcrumbs.shift(rings);
+    }
+    if let Some(mut corruption) = corruption {
+        corruption.shift(rings);
+    }
+
+    #[cfg(feature = "sqlite")]
+    {
+        // Every saved room has moved, so the next save can't just add to the last.
+        commands.remove_resource::<LastSavedGame>();
+        if let Err(err) = JournalEntry::shift_all(&db, save.game_id, save.floor, rings) {
+            report_error(err.into());
+        }
+    }
+
+    notifications.info(format!(
+        "The dungeon stretches on, {} rooms from the entrance",
+        radius + rings
+    ));
+}
+
+/// Zooms the map camera out as the map grows, so all of it still fits.
+fn fit_map_camera(
+    map: Query<&TilemapSize, (With<MapTilemap>, Changed<TilemapSize>)>,
+    mut camera: Query<&mut Projection, With<MapCameraMarker>>,
+    shape: Option<Res<MapShape>>,
+) {
+    let Ok(size) = map.single() else {
+        return;
+    };
+    let start = shape.map_or(MapShape::default(), |shape| *shape).size();
+
+    for mut projection in camera.iter_mut() {
+        if let Projection::Orthographic(ortho) = &mut *projection {
+            ortho.scale = CAMERA_MAP_SCALE * size.x as f32 / start.x as f32;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::generate_map::MAP_RADIUS;
+
+    fn settings() -> GenerationSettings {
+        GenerationSettings {
+            seed: 0xe2d1e55,
+            ..default()
+        }
+    }
+
+    #[test]
+    fn chunks_fill_their_rings() {
+        let origin = MapShape::default().origin();
+        let from = TilePos::new(
+            origin.x + ENDLESS_CHUNK_RINGS,
+            origin.y + ENDLESS_CHUNK_RINGS,
+        );
+        let rooms = chunk_rooms(&settings(), MAP_RADIUS, ENDLESS_CHUNK_RINGS, from, |_| {
+            false
+        });
+        let center = TilePos::new(
+            MAP_RADIUS + ENDLESS_CHUNK_RINGS,
+            MAP_RADIUS + ENDLESS_CHUNK_RINGS,
+        );
+
+        for ring in MAP_RADIUS + 1..=MAP_RADIUS + ENDLESS_CHUNK_RINGS {
+            let count = rooms
+                .iter()
+                .filter(|(pos, _)| hex_distance(pos, &center) == ring)
+                .count();
+            assert_eq!(count, ring as usize * 6);
+        }
+
+        // The corridor out from the entrance is the only thing added inside the old map.
+        let corridor = rooms
+            .iter()
+            .filter(|(pos, _)| hex_distance(pos, &center) <= MAP_RADIUS)
+            .count();
+        assert_eq!(corridor, MAP_RADIUS as usize);
+    }
+
+    #[test]
+    fn chunks_are_the_same_each_time() {
+        let from = TilePos::new(MAP_RADIUS * 2, MAP_RADIUS);
+        let roll = || {
+            chunk_rooms(&settings(), MAP_RADIUS, ENDLESS_CHUNK_RINGS, from, |_| true)
+                .into_iter()
+                .map(|(pos, info)| (pos, info.rng_seed))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(roll(), roll());
+    }
+}
@@ -0,0 +1,133 @@
+//! Fog of war on the map. Rooms only show up once they have been discovered,
+//! by walking into them or drinking a Vision Potion.
+
+use super::*;
+use crate::generate_map::MapTile;
+use bevy::prelude::*;
+
+/// How far around the current room a Vision Potion reveals.
+pub const VISION_RADIUS: u32 = 2;
+
+pub struct FogPlugin;
+
+impl Plugin for FogPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::EnterRoom), discover_current_room)
+            .add_systems(Update, sync_fog.run_if(in_state(AppState::Game)));
+    }
+}
+
+/// The distance in rooms between two map positions.
+/// Map positions are axial, as the map uses [`HexCoordSystem::Column`].
+pub fn hex_distance(a: &TilePos, b: &TilePos) -> u32 {
+    let dq = a.x as i32 - b.x as i32;
+    let dr = a.y as i32 - b.y as i32;
+    (dq.unsigned_abs() + dr.unsigned_abs() + (dq + dr).unsigned_abs()) / 2
+}
+
+/// Discovers every room within `radius` of `center`, returning how many were new.
+pub fn reveal_rooms<'a>(
+    center: &TilePos,
+    radius: u32,
+    rooms: impl IntoIterator<Item = (&'a TilePos, Mut<'a, RoomInfo>)>,
+) -> usize {
+    let mut revealed = 0;
+    for (pos, mut info) in rooms {
+        // Checked first so untouched rooms aren't marked as changed for saving.
+        if !info.discovered && hex_distance(center, pos) <= radius {
+            info.discovered = true;
+            revealed += 1;
+        }
+    }
+    revealed
+}
+
+/// Walking into a room nobody has seen yet gets the party talking, unless it is the way in.
+fn discover_current_room(
+    mut commands: Commands,
+    mut info: Query<&mut RoomInfo, With<CurrentRoom>>,
+) {
+    if let Ok(mut info) = info.single_mut()
+        && !info.discovered
+    {
+        info.discovered = true;
+        if info.r_type != RoomType::Entrance {
+            commands.trigger(Bark::new(BarkContext::DarkRoom));
+        }
+    }
+}
+
+/// Reveals the rooms around the party.
+pub fn drink_vision_potion(
+    current_room: Query<&TilePos, With<CurrentRoom>>,
+    mut rooms: Query<(&TilePos, &mut RoomInfo), With<MapTile>>,
+    mut notifications: ResMut<Notifications>,
+) {
+    let Ok(center) = current_room.single() else {
+        warn!("No current room to drink the Vision Potion in");
+        return;
+    };
+
+    let revealed = reveal_rooms(center, VISION_RADIUS, rooms.iter_mut());
+    notifications.success(format!("The Vision Potion reveals {revealed} nearby rooms"));
+}
+
+#[allow(clippy::type_complexity)]
+fn sync_fog(mut tiles: Query<(&RoomInfo, &mut TileVisible), (With<MapTile>, Changed<RoomInfo>)>) {
+    for (info, mut visible) in tiles.iter_mut() {
+        visible.0 = info.discovered;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn hex_distance_is_in_rooms() {
+        let center = TilePos::new(5, 5);
+
+        assert_eq!(hex_distance(&center, &center), 0);
+        for dir in EntranceDirection::ALL {
+            let offset = dir.axial_offset();
+            let neighbor = TilePos::new((5 + offset.q) as u32, (5 + offset.r) as u32);
+            assert_eq!(hex_distance(&center, &neighbor), 1);
+        }
+        assert_eq!(hex_distance(&center, &TilePos::new(7, 3)), 2);
+        assert_eq!(hex_distance(&center, &TilePos::new(7, 5)), 2);
+        assert_eq!(hex_distance(&center, &TilePos::new(8, 5)), 3);
+    }
+
+    #[test]
+    fn vision_potion_reveals_nearby_rooms() {
+        let mut world = World::new();
+        world.init_resource::<Notifications>();
+
+        for x in 0..10 {
+            for y in 0..10 {
+                let mut room = world.spawn((
+                    MapTile,
+                    TilePos::new(x, y),
+                    RoomInfo::from_type(RoomType::EmptyRoom, 0),
+                ));
+                if (x, y) == (5, 5) {
+                    room.insert(CurrentRoom);
+                }
+            }
+        }
+
+        world.run_system_cached(drink_vision_potion).unwrap();
+
+        let mut rooms = world.query::<(&TilePos, &RoomInfo)>();
+        let discovered: Vec<TilePos> = rooms
+            .iter(&world)
+            .filter(|(_, info)| info.discovered)
+            .map(|(pos, _)| *pos)
+            .collect();
+
+        // A radius 2 hexagon has 1 + 6 + 12 rooms.
+        assert_eq!(discovered.len(), 19);
+        assert!(discovered.contains(&TilePos::new(7, 3)));
+        assert!(!discovered.contains(&TilePos::new(7, 6)));
+    }
+}
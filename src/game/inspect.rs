@@ -0,0 +1,145 @@
+use super::PauseState;
+use crate::prelude::*;
+use bevy::prelude::*;
+
+/// The right click menu for the player's party members.
+pub fn party_member_context_menu() -> ContextMenu {
+    ContextMenu::new()
+        .item("inspect", "Inspect")
+        .item("equip", "Equip")
+}
+
+#[derive(Component)]
+pub struct InspectPanel;
+
+/// The name shown over an actor's stats.
+pub fn actor_title(name: ActorName, hero_name: Option<&HeroName>) -> String {
+    match hero_name {
+        Some(hero_name) if **hero_name != name.to_string() => {
+            format!("{} the {name}", hero_name.0)
+        }
+        _ => name.to_string(),
+    }
+}
+
+/// Each of the actor's stats, one per line.
+pub fn actor_stats(
+    health: &Health,
+    attack: &Attack,
+    speed: &AttackSpeed,
+    block: &BlockChance,
+) -> [String; 5] {
+    let current = health.current().map(|h| h.get()).unwrap_or(0);
+    [
+        format!("Health: {current}/{}", health.max()),
+        format!(
+            "Damage: {}-{}",
+            attack.damage().start,
+            attack.damage().end.saturating_sub(1)
+        ),
+        format!("Hit Chance: {:.0}%", attack.hit_chance() * 100.0),
+        format!("Block Chance: {:.0}%", block.0 * 100.0),
+        format!("Speed: {}", speed.0),
+    ]
+}
+
+#[allow(clippy::type_complexity)]
+pub fn party_member_menu_select(
+    select: Trigger<ContextMenuSelect>,
+    mut commands: Commands,
+    actors: Query<(
+        &ActorName,
+        Option<&HeroName>,
+        &Health,
+        &Attack,
+        &AttackSpeed,
+        &BlockChance,
+    )>,
+    open: Query<Entity, With<InspectPanel>>,
+    style: Res<Style>,
+    mut pause_state: ResMut<NextState<PauseState>>,
+) {
+    match select.id {
+        // Gear is worn from the party screen.
+        "equip" => pause_state.set(PauseState::Party),
+        "inspect" => {
+            let Ok((name, hero_name, health, attack, speed, block)) = actors.get(select.target())
+            else {
+                return;
+            };
+
+            for entity in open.iter() {
+                commands.entity(entity).despawn();
+            }
+
+            let stats = actor_stats(health, attack, speed, block);
+
+            commands
+                .spawn((
+                    Node {
+                        position_type: PositionType::Absolute,
+                        align_self: AlignSelf::Center,
+                        justify_self: JustifySelf::Center,
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        padding: UiRect::all(Val::Px(20.0)),
+                        row_gap: Val::Px(5.0),
+                        ..default()
+                    },
+                    BackgroundColor(style.background_color),
+                    StateScoped(AppState::Game),
+                    InspectPanel,
+                ))
+                .with_children(|builder| {
+                    builder.spawn((
+                        Text::new(actor_title(*name, hero_name)),
+                        style.font(48.0),
+                        TextColor(style.text_color),
+                        Label,
+                    ));
+
+                    for stat in stats {
+                        builder.spawn((
+                            Text::new(stat),
+                            style.font(28.0),
+                            TextColor(style.text_color),
+                            Label,
+                        ));
+                    }
+
+                    builder
+                        .spawn((
+                            Button,
+                            Node {
+                                width: Val::Px(150.0),
+                                height: Val::Px(50.0),
+                                margin: UiRect::top(Val::Px(10.0)),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            BackgroundColor(style.button_color),
+                            children![(
+                                Text::new("Close"),
+                                style.font(33.0),
+                                TextColor(style.text_color),
+                                Pickable::IGNORE,
+                            )],
+                        ))
+                        .observe(close_inspect_panel);
+                });
+        }
+        _ => {}
+    }
+}
+
+fn close_inspect_panel(
+    mut click: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    panel: Query<Entity, With<InspectPanel>>,
+) {
+    click.propagate(false);
+    for entity in panel.iter() {
+        commands.entity(entity).despawn();
+    }
+}
@@ -0,0 +1,507 @@
+//! A travelogue of the run, with an entry for each room as it is first cleared.
+//!
+//! Entries keep the room type, when it was cleared and what was found there,
+//! and when [`JournalScreenshots`] is on, a thumbnail of the room saved under the data directory.
+
+use super::{Fled, GameState, PauseState, RewardsSummary, RoomRewards, pause_overlay};
+use crate::database::get_default_db_directory;
+use crate::menu::update_scroll_position_event;
+use crate::prelude::*;
+use crate::room::{CurrentRoom, mark_room_cleared};
+use crate::settings::SETTINGS_DB_TABLE;
+use bevy::asset::RenderAssetUsages;
+use bevy::image::{CompressedImageFormats, ImageSampler, ImageType};
+use bevy::render::view::screenshot::{Screenshot, ScreenshotCaptured};
+use bevy_ecs_tilemap::prelude::*;
+use std::path::{Path, PathBuf};
+
+/// The largest a thumbnail is saved at, it keeps the window's aspect ratio.
+const THUMBNAIL_SIZE: UVec2 = UVec2::new(320, 180);
+/// The size thumbnails are shown at in the journal.
+const THUMBNAIL_DISPLAY_SIZE: Vec2 = Vec2::new(160.0, 90.0);
+
+pub struct JournalPlugin;
+
+impl Plugin for JournalPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PreStartup, setup_journal_screenshots)
+            .add_systems(
+                Update,
+                journal_screenshots_sync.run_if(
+                    resource_changed::<JournalScreenshots>
+                        .and(not(resource_added::<JournalScreenshots>)),
+                ),
+            );
+
+        #[cfg(feature = "sqlite")]
+        app.add_systems(
+            OnEnter(GameState::Navigation),
+            queue_journal_entry
+                .before(mark_room_cleared)
+                .run_if(not(resource_exists::<Fled>)),
+        )
+        .add_systems(
+            Update,
+            write_journal_entry.run_if(
+                resource_exists::<PendingJournalEntry>
+                    .and(in_state(GameState::Navigation))
+                    .and(in_state(PauseState::Running)),
+            ),
+        )
+        .add_systems(
+            OnExit(GameState::Navigation),
+            remove_resource::<PendingJournalEntry>,
+        )
+        .add_systems(OnEnter(PauseState::Journal), pause_journal_enter);
+    }
+}
+
+/// Whether a thumbnail of each cleared room is saved to the journal, set in the display settings.
+#[derive(Resource, Deref, DerefMut, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct JournalScreenshots(pub bool);
+
+impl JournalScreenshots {
+    const DB_KEY: &str = "journal_screenshots";
+}
+
+fn setup_journal_screenshots(mut commands: Commands, database: NonSend<Database>) {
+    let screenshots = database.get_kv(SETTINGS_DB_TABLE, JournalScreenshots::DB_KEY, true);
+    commands.insert_resource(JournalScreenshots(screenshots));
+}
+
+fn journal_screenshots_sync(
+    database: NonSend<Database>,
+    screenshots: Res<JournalScreenshots>,
+) -> Result {
+    database
+        .set_kv(SETTINGS_DB_TABLE, JournalScreenshots::DB_KEY, screenshots.0)
+        .map_err(GameError::from)?;
+    Ok(())
+}
+
+/// One room in the journal.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JournalEntry {
+    /// The floor the room is on, see [`SaveGame::floor`].
+    pub floor: u32,
+    pub position: TilePos,
+    pub r_type: RoomType,
+    pub loot: Vec<Item>,
+}
+
+impl JournalEntry {
+    /// What kind of room it was, for the heading of the entry.
+    pub fn title(&self) -> String {
+        match &self.r_type {
+            RoomType::EmptyRoom => "Empty Room".to_string(),
+            RoomType::Combat(enemies) => {
+                let enemies: Vec<String> = enemies.iter().map(ActorName::to_string).collect();
+                format!("Fight: {}", enemies.join(", "))
+            }
+            RoomType::Pit(_) => "Spike Pit".to_string(),
+            RoomType::Item(_) => "Treasure Room".to_string(),
+            RoomType::Puzzle => "Puzzle Room".to_string(),
+            RoomType::Shrine => "Shrine".to_string(),
+            RoomType::Stairs => "Stairs".to_string(),
+            RoomType::Entrance => "Entrance".to_string(),
+            RoomType::Pillar => "Pillar Room".to_string(),
+        }
+    }
+
+    pub fn loot_text(&self) -> String {
+        if self.loot.is_empty() {
+            return "No loot".to_string();
+        }
+        let loot: Vec<String> = self.loot.iter().map(Item::to_string).collect();
+        format!("Loot: {}", loot.join(", "))
+    }
+}
+
+/// Where the thumbnails for a save are kept.
+#[cfg(feature = "sqlite")]
+pub fn journal_directory(game_id: GameID) -> PathBuf {
+    get_default_db_directory()
+        .join("journal")
+        .join(game_id.0.to_string())
+}
+
+/// Where the thumbnail of the room at `position` on `floor` is kept.
+#[cfg(feature = "sqlite")]
+pub fn thumbnail_path(game_id: GameID, floor: u32, position: TilePos) -> PathBuf {
+    journal_directory(game_id)
+        .join(floor.to_string())
+        .join(format!("{}_{}.png", position.x, position.y))
+}
+
+#[cfg(feature = "sqlite")]
+impl JournalEntry {
+    /// Adds the entry to the save's journal, replacing any earlier entry for the same room.
+    pub fn save(&self, db: &Database, game_id: GameID) -> Result<(), DatabaseError> {
+        db.connection.execute(
+            "INSERT OR REPLACE INTO JournalEntry(game_id,floor,position_x,position_y,r_type,loot)
+                VALUES(?1, ?2, ?3, ?4, ?5, ?6)",
+            (
+                game_id.0,
+                self.floor,
+                self.position.x,
+                self.position.y,
+                ron::to_string(&self.r_type).unwrap(),
+                ron::to_string(&self.loot).unwrap(),
+            ),
+        )?;
+        Ok(())
+    }
+
+    /// Moves the journal of the save's `floor`, thumbnails included, along with the map
+    /// when it grows outward by `by` rings.
+    pub fn shift_all(
+        db: &Database,
+        game_id: GameID,
+        floor: u32,
+        by: u32,
+    ) -> Result<(), DatabaseError> {
+        // Flipped negative on the way, so no entry lands on one that hasn't moved yet.
+        db.connection.execute(
+            "UPDATE JournalEntry
+                SET position_x = -1 - (position_x + ?3), position_y = -1 - (position_y + ?3)
+                WHERE game_id = ?1 AND floor = ?2",
+            (game_id.0, floor, by),
+        )?;
+        db.connection.execute(
+            "UPDATE JournalEntry
+                SET position_x = -1 - position_x, position_y = -1 - position_y
+                WHERE game_id = ?1 AND floor = ?2",
+            (game_id.0, floor),
+        )?;
+
+        // Furthest out first, for the same reason.
+        let mut entries = Self::get_all(db, game_id)?;
+        entries.retain(|(entry, _)| entry.floor == floor);
+        entries.sort_by_key(|(entry, _)| std::cmp::Reverse(entry.position.x + entry.position.y));
+        for (entry, _) in entries {
+            let from = thumbnail_path(
+                game_id,
+                floor,
+                TilePos::new(entry.position.x - by, entry.position.y - by),
+            );
+            if from.exists() {
+                let to = thumbnail_path(game_id, floor, entry.position);
+                if let Err(err) = std::fs::rename(&from, to) {
+                    warn!("Couldn't move the thumbnail {}: {err}", from.display());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The save's journal in the order the rooms were cleared, with when each was cleared.
+    pub fn get_all(
+        db: &Database,
+        game_id: GameID,
+    ) -> Result<Vec<(Self, chrono::DateTime<chrono::Local>)>, DatabaseError> {
+        db.connection
+            .prepare(
+                "SELECT position_x,position_y,r_type,loot,cleared_at,floor FROM JournalEntry
+                    WHERE game_id = :game_id ORDER BY cleared_at, rowid",
+            )?
+            .query_map((game_id.0,), |row| {
+                let r_type: String = row.get(2)?;
+                let loot: String = row.get(3)?;
+                let cleared_at: chrono::DateTime<chrono::Utc> = row.get(4)?;
+                Ok((
+                    Self {
+                        floor: row.get(5)?,
+                        position: TilePos::new(row.get(0)?, row.get(1)?),
+                        r_type: ron::from_str(&r_type).unwrap_or(RoomType::EmptyRoom),
+                        loot: ron::from_str(&loot).unwrap_or_default(),
+                    },
+                    cleared_at.into(),
+                ))
+            })?
+            .collect()
+    }
+}
+
+/// A room that was just cleared, written to the journal once the rewards summary is dismissed
+/// so it isn't in the thumbnail.
+#[cfg(feature = "sqlite")]
+#[derive(Resource, Debug)]
+struct PendingJournalEntry(JournalEntry);
+
+#[cfg(feature = "sqlite")]
+fn queue_journal_entry(
+    mut commands: Commands,
+    room: Single<(&RoomInfo, &TilePos), With<CurrentRoom>>,
+    rewards: Option<Res<RoomRewards>>,
+    save: Res<SaveGame>,
+) {
+    let (info, position) = *room;
+    if info.cleared || info.r_type == RoomType::Entrance {
+        return;
+    }
+
+    let mut loot = rewards
+        .map(|rewards| rewards.loot.clone())
+        .unwrap_or_default();
+    if let RoomType::Item(item) = info.r_type {
+        loot.push(item);
+    }
+
+    commands.insert_resource(PendingJournalEntry(JournalEntry {
+        floor: save.floor,
+        position: *position,
+        r_type: info.r_type.clone(),
+        loot,
+    }));
+}
+
+#[cfg(feature = "sqlite")]
+fn write_journal_entry(
+    mut commands: Commands,
+    database: NonSend<Database>,
+    pending: Res<PendingJournalEntry>,
+    save: Res<SaveGame>,
+    screenshots: Res<JournalScreenshots>,
+    summaries: Query<(), With<RewardsSummary>>,
+) -> Result {
+    if !summaries.is_empty() {
+        return Ok(());
+    }
+    commands.remove_resource::<PendingJournalEntry>();
+
+    let entry = &pending.0;
+    entry
+        .save(&database, save.game_id)
+        .map_err(GameError::from)?;
+
+    if screenshots.0 {
+        let path = thumbnail_path(save.game_id, entry.floor, entry.position);
+        commands.spawn(Screenshot::primary_window()).observe(
+            move |trigger: Trigger<ScreenshotCaptured>| {
+                if let Err(err) = save_thumbnail(&trigger.0, &path) {
+                    warn!(
+                        "Failed to save journal thumbnail to '{}' with: {err}",
+                        path.display()
+                    );
+                }
+            },
+        );
+    }
+
+    Ok(())
+}
+
+/// Shrinks the screenshot down and saves it as a png.
+#[cfg(feature = "sqlite")]
+fn save_thumbnail(screenshot: &Image, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(directory) = path.parent() {
+        std::fs::create_dir_all(directory)?;
+    }
+    screenshot
+        .clone()
+        .try_into_dynamic()?
+        .thumbnail(THUMBNAIL_SIZE.x, THUMBNAIL_SIZE.y)
+        .save(path)?;
+    Ok(())
+}
+
+/// The saved thumbnail of a room, if there is one.
+#[cfg(feature = "sqlite")]
+fn load_thumbnail(path: &Path) -> Option<Image> {
+    let bytes = std::fs::read(path).ok()?;
+    Image::from_buffer(
+        &bytes,
+        ImageType::Extension("png"),
+        CompressedImageFormats::NONE,
+        true,
+        ImageSampler::Default,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+    .inspect_err(|err| warn!("Failed to load '{}' with: {err}", path.display()))
+    .ok()
+}
+
+#[cfg(feature = "sqlite")]
+fn pause_journal_enter(
+    mut commands: Commands,
+    style: Res<Style>,
+    database: NonSend<Database>,
+    save: Res<SaveGame>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let entries = JournalEntry::get_all(&database, save.game_id)
+        .inspect_err(|err| warn!("Failed to load the journal with: {err}"))
+        .unwrap_or_default();
+
+    let text_style = (style.font(24.0), TextColor(style.text_color));
+
+    commands
+        .spawn(pause_overlay(&style, PauseState::Journal))
+        .with_children(|builder| {
+            builder.spawn((
+                Text::new("Journal"),
+                style.font(66.0),
+                TextColor(style.text_color),
+            ));
+
+            builder
+                .spawn(Node {
+                    width: Val::Percent(80.0),
+                    height: Val::Percent(70.0),
+                    margin: UiRect::all(Val::Px(10.0)),
+                    row_gap: Val::Px(10.0),
+                    overflow: Overflow::scroll_y(),
+                    flex_direction: FlexDirection::Column,
+                    ..default()
+                })
+                .observe(update_scroll_position_event)
+                .with_children(|builder| {
+                    if entries.is_empty() {
+                        builder.spawn((
+                            Text::new("No rooms cleared yet"),
+                            text_style.clone(),
+                            Pickable::IGNORE,
+                        ));
+                    }
+
+                    for (entry, cleared_at) in entries {
+                        let thumbnail = load_thumbnail(&thumbnail_path(
+                            save.game_id,
+                            entry.floor,
+                            entry.position,
+                        ));
+
+                        builder
+                            .spawn((
+                                Node {
+                                    padding: UiRect::all(Val::Px(10.0)),
+                                    column_gap: Val::Px(15.0),
+                                    align_items: AlignItems::Center,
+                                    ..default()
+                                },
+                                BackgroundColor(style.background_color),
+                                Pickable::IGNORE,
+                            ))
+                            .with_children(|builder| {
+                                let mut picture = builder.spawn((
+                                    Node {
+                                        width: Val::Px(THUMBNAIL_DISPLAY_SIZE.x),
+                                        height: Val::Px(THUMBNAIL_DISPLAY_SIZE.y),
+                                        flex_shrink: 0.0,
+                                        ..default()
+                                    },
+                                    BackgroundColor(style.button_color),
+                                    Pickable::IGNORE,
+                                ));
+                                if let Some(thumbnail) = thumbnail {
+                                    picture.insert(ImageNode::new(images.add(thumbnail)));
+                                }
+
+                                builder.spawn((
+                                    Node {
+                                        flex_direction: FlexDirection::Column,
+                                        ..default()
+                                    },
+                                    Pickable::IGNORE,
+                                    children![
+                                        (
+                                            Text::new(entry.title()),
+                                            style.font(33.0),
+                                            TextColor(style.title_color),
+                                        ),
+                                        (
+                                            Text::new(format!(
+                                                "Cleared {}\n{}",
+                                                cleared_at.format("%Y/%m/%d %H:%M"),
+                                                entry.loot_text()
+                                            )),
+                                            text_style.clone(),
+                                        ),
+                                    ],
+                                ));
+                            });
+                    }
+                });
+
+            builder
+                .spawn((
+                    Button,
+                    Node {
+                        width: Val::Px(200.0),
+                        height: Val::Px(65.0),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    BackgroundColor(style.button_color),
+                    children![(
+                        Text::new("Back"),
+                        style.font(33.0),
+                        TextColor(style.text_color),
+                        Pickable::IGNORE
+                    )],
+                ))
+                .observe(change_state_on_click(
+                    PointerButton::Primary,
+                    PauseState::Paused,
+                ));
+        });
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn thumbnails_are_kept_per_save_and_room() {
+        let path = thumbnail_path(GameID(3), 1, TilePos::new(4, 7));
+        assert!(path.ends_with("journal/3/1/4_7.png"));
+        assert_ne!(path, thumbnail_path(GameID(4), 1, TilePos::new(4, 7)));
+        assert_ne!(path, thumbnail_path(GameID(3), 0, TilePos::new(4, 7)));
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn journal_entries_are_kept_with_their_save() {
+        let db = Database::open_in_memory().unwrap();
+        db.connection
+            .execute(
+                "INSERT INTO SaveGame(game_id, last_saved, world_seed) VALUES (1, datetime('now'), 1)",
+                (),
+            )
+            .unwrap();
+
+        let fight = JournalEntry {
+            floor: 0,
+            position: TilePos::new(1, 2),
+            r_type: RoomType::Combat(Box::new([ActorName::Ogre])),
+            loot: vec![Item::HealingPotion],
+        };
+        let treasure = JournalEntry {
+            floor: 0,
+            position: TilePos::new(2, 2),
+            r_type: RoomType::Item(Item::VisionPotion),
+            loot: vec![Item::VisionPotion],
+        };
+        let downstairs = JournalEntry {
+            floor: 1,
+            ..fight.clone()
+        };
+        fight.save(&db, GameID(1)).unwrap();
+        treasure.save(&db, GameID(1)).unwrap();
+        fight.save(&db, GameID(1)).unwrap();
+        downstairs.save(&db, GameID(1)).unwrap();
+
+        let entries: Vec<JournalEntry> = JournalEntry::get_all(&db, GameID(1))
+            .unwrap()
+            .into_iter()
+            .map(|(entry, _)| entry)
+            .collect();
+        assert_eq!(entries.len(), 3);
+        assert!(entries.contains(&fight));
+        assert!(entries.contains(&treasure));
+        assert!(entries.contains(&downstairs));
+        assert_eq!(fight.title(), "Fight: Ogre");
+    }
+}
@@ -0,0 +1,165 @@
+//! What defeated enemies drop, and the gold the party carries.
+
+use super::*;
+use bevy::prelude::*;
+use rand::Rng;
+use std::ops::Range;
+
+pub struct LootPlugin;
+
+impl Plugin for LootPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::Game), spawn_gold_counter)
+            .add_systems(OnExit(GameState::Combat), roll_loot)
+            .add_systems(
+                Update,
+                update_gold_counter.run_if(in_state(AppState::Game).and(resource_changed::<Gold>)),
+            )
+            .add_systems(OnExit(AppState::Game), remove_resource::<Gold>);
+    }
+}
+
+/// The gold the party has collected.
+#[derive(Resource, Deref, DerefMut, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Gold(pub u64);
+
+/// What an enemy can drop when it is defeated.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LootTable {
+    pub gold: Range<u32>,
+    /// The chance of also dropping a random item.
+    pub item_chance: f64,
+    /// The chance a dropped item is a piece of gear rather than a potion.
+    pub gear_chance: f64,
+}
+
+impl LootTable {
+    pub fn from_name(name: ActorName) -> Self {
+        use ActorName as A;
+        let (gold, item_chance, gear_chance) = match name {
+            A::Goblin => (5..16, 0.1, 0.0),
+            A::Skeleton => (10..21, 0.15, 0.0),
+            A::Ogre => (20..41, 0.3, 0.0),
+            A::Construct => (15..31, 0.2, 0.0),
+            A::Archer => (10..21, 0.15, 0.0),
+            A::Shaman => (15..26, 0.25, 0.0),
+            A::GoblinWarlord | A::BoneKnight | A::OgreTyrant => (50..81, 1.0, 0.5),
+            A::Warrior | A::Priestess | A::Theif | A::UnknownJim => (0..1, 0.0, 0.0),
+        };
+
+        Self {
+            gold,
+            item_chance,
+            gear_chance,
+        }
+    }
+
+    /// Rolls the gold and maybe an item that are dropped.
+    pub fn roll(&self, rng: &mut impl Rng) -> (u32, Option<Item>) {
+        let gold = rng.random_range(self.gold.clone());
+        let item =
+            rng.random_bool(self.item_chance)
+                .then(|| match rng.random_bool(self.gear_chance) {
+                    true => Item::get_rand_gear(rng),
+                    false => Item::get_rand_item(rng),
+                });
+
+        (gold, item)
+    }
+}
+
+#[derive(Component)]
+struct GoldText;
+
+fn gold_text(gold: u64) -> String {
+    format!("Gold: {gold}")
+}
+
+fn spawn_gold_counter(mut commands: Commands, style: Res<Style>, gold: Option<Res<Gold>>) {
+    commands.spawn((
+        Text::new(gold_text(gold.map_or(0, |gold| **gold))),
+        style.font(24.0),
+        TextColor(style.text_color),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            right: Val::Px(20.0),
+            ..default()
+        },
+        StateScoped(AppState::Game),
+        GoldText,
+    ));
+}
+
+fn update_gold_counter(gold: Res<Gold>, mut text: Query<&mut Text, With<GoldText>>) {
+    for mut text in text.iter_mut() {
+        text.0 = gold_text(**gold);
+    }
+}
+
+/// Gives the party what the defeated enemies dropped, if they won.
+fn roll_loot(
+    mut rng: ResMut<EventRng>,
+    rewards: Option<ResMut<RoomRewards>>,
+    mut gold: ResMut<Gold>,
+    mut items: ResMut<Items>,
+    actors: Query<(&ActorName, &Team, &Health)>,
+) {
+    let Some(mut rewards) = rewards else {
+        return;
+    };
+
+    let won = actors
+        .iter()
+        .any(|(_, team, health)| *team == Team::Player && health.is_alive())
+        && !actors
+            .iter()
+            .any(|(_, team, health)| *team == Team::Enemy && health.is_alive());
+    if !won {
+        return;
+    }
+
+    for (name, team, _) in actors.iter() {
+        if *team != Team::Enemy {
+            continue;
+        }
+
+        let (dropped_gold, item) = LootTable::from_name(*name).roll(&mut **rng);
+        rewards.gold += dropped_gold;
+        **gold += dropped_gold as u64;
+
+        if let Some(item) = item {
+            rewards.loot.push(item);
+            items.push(item);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn drops_stay_in_the_loot_table() {
+        let mut rng = RandomSource::seed_from_u64(0x601d);
+
+        for name in [
+            ActorName::Goblin,
+            ActorName::Skeleton,
+            ActorName::Ogre,
+            ActorName::Construct,
+            ActorName::Archer,
+            ActorName::Shaman,
+        ] {
+            let table = LootTable::from_name(name);
+            for _ in 0..50 {
+                let (gold, _) = table.roll(&mut rng);
+                assert!(table.gold.contains(&gold));
+            }
+        }
+
+        let (gold, item) = LootTable::from_name(ActorName::Warrior).roll(&mut rng);
+        assert_eq!((gold, item), (0, None));
+    }
+}
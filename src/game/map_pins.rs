@@ -0,0 +1,301 @@
+//! Pins the player puts on rooms of the minimap, with a note to remember them by.
+//!
+//! A room is pinned from its right click menu, which then offers to edit the note or take the
+//! pin out. Pins are kept on the map tiles, so they move with the map when it grows in endless
+//! mode and are left behind with the floor.
+
+use super::*;
+use crate::generate_map::{MapTile, MapTilemap};
+use bevy::prelude::*;
+use bevy_ui_text_input::actions::{TextInputAction, TextInputEdit};
+use bevy_ui_text_input::{TextInputContents, TextInputMode, TextInputNode, TextInputQueue};
+
+/// Above the map tiles and the breadcrumbs.
+const PIN_LAYER: f32 = 2.0;
+const PIN_SIZE: f32 = 10.0;
+const PIN_NOTE_SIZE: f32 = 14.0;
+const MAX_NOTE_CHARS: usize = 32;
+
+pub struct MapPinsPlugin;
+
+impl Plugin for MapPinsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_observer(add_pin_menu)
+            .add_observer(map_tile_menu_select)
+            .add_systems(Update, draw_map_pins.run_if(in_state(AppState::Game)))
+            .add_systems(OnEnter(PauseState::PinNote), pin_note_enter)
+            .add_systems(OnExit(PauseState::PinNote), remove_resource::<EditingPin>);
+    }
+}
+
+/// A pin on a room of the map, see [`MapPinsPlugin`].
+#[derive(Component, Default, Debug, Clone, PartialEq, Eq)]
+pub struct MapPin {
+    pub note: String,
+}
+
+/// The right click menu for a room on the map, depending on whether it is pinned.
+pub fn map_tile_context_menu(pinned: bool) -> ContextMenu {
+    match pinned {
+        true => ContextMenu::new()
+            .item("edit", "Edit")
+            .item("delete", "Delete"),
+        false => ContextMenu::new().item("pin", "Pin"),
+    }
+}
+
+/// The room whose pin is having its note written.
+#[derive(Resource)]
+struct EditingPin(Entity);
+
+/// One pin drawn on the map.
+#[derive(Component)]
+struct MapPinMarker;
+
+#[derive(Component)]
+struct PinNoteTextBox;
+
+fn add_pin_menu(add: Trigger<OnAdd, MapTile>, mut commands: Commands) {
+    commands
+        .entity(add.target())
+        .insert(map_tile_context_menu(false));
+}
+
+fn map_tile_menu_select(
+    select: Trigger<ContextMenuSelect>,
+    mut commands: Commands,
+    tiles: Query<(), With<MapTile>>,
+    mut next_state: ResMut<NextState<PauseState>>,
+) {
+    let tile = select.target();
+    if !tiles.contains(tile) {
+        return;
+    }
+
+    match select.id {
+        "pin" => {
+            commands
+                .entity(tile)
+                .insert((MapPin::default(), map_tile_context_menu(true)));
+        }
+        "edit" => {
+            commands.insert_resource(EditingPin(tile));
+            next_state.set(PauseState::PinNote);
+        }
+        "delete" => {
+            commands
+                .entity(tile)
+                .remove::<MapPin>()
+                .insert(map_tile_context_menu(false));
+        }
+        _ => {}
+    }
+}
+
+/// Redraws every pin when one of them is put in, changed, moved or taken out.
+#[allow(clippy::type_complexity)]
+fn draw_map_pins(
+    mut commands: Commands,
+    style: Res<Style>,
+    pins: Query<(&TilePos, &MapPin), With<MapTile>>,
+    changed: Query<(), (With<MapPin>, Or<(Changed<MapPin>, Changed<TilePos>)>)>,
+    mut removed: RemovedComponents<MapPin>,
+    map: Query<
+        (
+            &Transform,
+            &TilemapSize,
+            &TilemapGridSize,
+            &TilemapTileSize,
+            &TilemapType,
+            &TilemapAnchor,
+        ),
+        With<MapTilemap>,
+    >,
+    markers: Query<Entity, With<MapPinMarker>>,
+) {
+    if changed.is_empty() && removed.read().count() == 0 {
+        return;
+    }
+
+    for marker in markers.iter() {
+        commands.entity(marker).despawn();
+    }
+
+    let Ok((map_transform, map_size, grid_size, tile_size, map_type, anchor)) = map.single() else {
+        return;
+    };
+
+    for (pos, pin) in pins.iter() {
+        let center = map_transform.translation.truncate()
+            + pos.center_in_world(map_size, grid_size, tile_size, map_type, anchor);
+
+        commands
+            .spawn((
+                MapPinMarker,
+                Sprite {
+                    color: style.text_color,
+                    custom_size: Some(Vec2::splat(PIN_SIZE)),
+                    ..default()
+                },
+                Transform::from_translation(center.extend(map_transform.translation.z + PIN_LAYER))
+                    .with_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_4)),
+                Pickable::IGNORE,
+                StateScoped(AppState::Game),
+            ))
+            .with_children(|builder| {
+                if pin.note.is_empty() {
+                    return;
+                }
+                // Undoes the marker's turn, so the note reads level under it.
+                builder.spawn((
+                    Text2d::new(pin.note.clone()),
+                    style.font(PIN_NOTE_SIZE),
+                    TextColor(style.text_color),
+                    Transform::from_xyz(0.0, -PIN_SIZE, 0.0)
+                        .with_rotation(Quat::from_rotation_z(-std::f32::consts::FRAC_PI_4)),
+                    Pickable::IGNORE,
+                ));
+            });
+    }
+}
+
+fn pin_note_enter(
+    mut commands: Commands,
+    style: Res<Style>,
+    editing: Res<EditingPin>,
+    pins: Query<&MapPin>,
+) {
+    let button_node = Node {
+        width: Val::Px(200.0),
+        height: Val::Px(65.0),
+        margin: UiRect::all(Val::Px(5.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let text_style = (style.font(33.0), TextColor(style.text_color));
+
+    // Starts from the note the pin has, so it can be touched up.
+    let mut note = TextInputQueue::default();
+    let current = pins.get(editing.0).map_or("", |pin| pin.note.as_str());
+    for c in current.chars() {
+        note.add(TextInputAction::Edit(TextInputEdit::Insert(c, false)));
+    }
+
+    commands
+        .spawn(pause_overlay(&style, PauseState::PinNote))
+        .with_children(|builder| {
+            builder.spawn((
+                Text::new("Pin Note"),
+                style.font(66.0),
+                TextColor(style.text_color),
+            ));
+
+            builder
+                .spawn((
+                    Node {
+                        width: Val::Px(400.0),
+                        height: Val::Px(60.0),
+                        padding: UiRect::all(Val::Px(10.0)),
+                        margin: UiRect::all(Val::Px(10.0)),
+                        ..default()
+                    },
+                    BackgroundColor(style.button_color),
+                ))
+                .with_children(|builder| {
+                    builder.spawn((
+                        Node {
+                            width: Val::Percent(100.0),
+                            height: Val::Percent(100.0),
+                            ..default()
+                        },
+                        PinNoteTextBox,
+                        TextInputContents::default(),
+                        note,
+                        TextInputNode {
+                            clear_on_submit: false,
+                            mode: TextInputMode::SingleLine,
+                            focus_on_pointer_down: true,
+                            unfocus_on_submit: true,
+                            max_chars: Some(MAX_NOTE_CHARS),
+                            ..default()
+                        },
+                        text_style.clone(),
+                    ));
+                })
+                .observe(stop_event_propagate::<Pointer<Click>>);
+
+            builder.spawn(Node::default()).with_children(|builder| {
+                builder
+                    .spawn((
+                        Button,
+                        button_node.clone(),
+                        BackgroundColor(style.button_color),
+                        children![(Text::new("Save"), text_style.clone(), Pickable::IGNORE)],
+                    ))
+                    .observe(save_pin_note);
+
+                builder
+                    .spawn((
+                        Button,
+                        button_node.clone(),
+                        BackgroundColor(style.button_color),
+                        children![(Text::new("Cancel"), text_style.clone(), Pickable::IGNORE)],
+                    ))
+                    .observe(change_state_on_click(
+                        PointerButton::Primary,
+                        PauseState::Running,
+                    ));
+            });
+        });
+}
+
+fn save_pin_note(
+    mut click: Trigger<Pointer<Click>>,
+    editing: Res<EditingPin>,
+    note_box: Query<&TextInputContents, With<PinNoteTextBox>>,
+    mut pins: Query<&mut MapPin>,
+    mut next_state: ResMut<NextState<PauseState>>,
+) {
+    click.propagate(false);
+    if click.button != PointerButton::Primary {
+        return;
+    }
+
+    if let (Ok(mut pin), Ok(note)) = (pins.get_mut(editing.0), note_box.single()) {
+        pin.note = note.get().trim().to_string();
+    }
+    next_state.set(PauseState::Running);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn menu_ids(world: &World, tile: Entity) -> Vec<&'static str> {
+        let menu = world.get::<ContextMenu>(tile).unwrap();
+        menu.0.iter().map(|item| item.id).collect()
+    }
+
+    #[test]
+    fn rooms_are_pinned_and_unpinned_from_their_menu() {
+        let mut world = World::new();
+        world.init_resource::<NextState<PauseState>>();
+        world.add_observer(add_pin_menu);
+        world.add_observer(map_tile_menu_select);
+
+        let tile = world.spawn(MapTile).id();
+        world.flush();
+        assert_eq!(menu_ids(&world, tile), ["pin"]);
+
+        world.trigger_targets(ContextMenuSelect { id: "pin" }, tile);
+        world.flush();
+        assert_eq!(world.get::<MapPin>(tile), Some(&MapPin::default()));
+        assert_eq!(menu_ids(&world, tile), ["edit", "delete"]);
+
+        world.trigger_targets(ContextMenuSelect { id: "delete" }, tile);
+        world.flush();
+        assert_eq!(world.get::<MapPin>(tile), None);
+        assert_eq!(menu_ids(&world, tile), ["pin"]);
+    }
+}
@@ -1,15 +1,77 @@
 mod attack_options;
+mod autosave;
+mod breadcrumbs;
+mod challenges;
+mod chests;
 pub mod combat;
+mod combat_log;
+mod corruption;
+mod crafting;
+mod door_preview;
+mod endless;
+mod fog;
+mod inspect;
+mod journal;
+mod loot;
+mod map_pins;
+mod party;
+mod pause;
+mod pillar_pickup;
 mod pouch;
+mod prefetch;
+mod puzzle;
+mod rewards;
+mod run_stats;
+mod scheduler;
+mod shrine;
+mod skirmish;
+mod spectate;
+mod stairs;
+mod suspend;
+mod traps;
+mod turn_order_bar;
+mod win_condition;
 
 pub use attack_options::*;
+pub use autosave::*;
+pub use breadcrumbs::*;
+pub use challenges::*;
+pub use chests::*;
 pub use combat::*;
+pub use combat_log::*;
+pub use corruption::*;
+pub use crafting::*;
+pub use door_preview::*;
+pub use endless::*;
+pub use fog::*;
+pub use inspect::*;
+pub use journal::*;
+pub use loot::*;
+pub use map_pins::*;
+pub use party::*;
+pub use pause::*;
+pub use pillar_pickup::*;
 pub use pouch::*;
-
+pub use prefetch::*;
+pub use puzzle::*;
+pub use rewards::*;
+pub use run_stats::*;
+pub use scheduler::*;
+pub use shrine::*;
+pub use skirmish::*;
+pub use spectate::*;
+pub use stairs::*;
+pub use suspend::*;
+pub use traps::*;
+pub use turn_order_bar::*;
+pub use win_condition::*;
+
+use crate::barks::{Bark, BarkContext};
+use crate::camera::RoomApproach;
 use crate::prelude::*;
 use crate::room::{
-    CurrentRoom, EntranceDirection, InRoom, ROOM_CENTER, ROOM_RADIUS, mark_room_cleared,
-    spawn_room, spawn_room_entities,
+    CurrentRoom, EntranceDirection, InRoom, ROOM_CENTER, ROOM_RADIUS, RoomItem, mark_room_cleared,
+    paint_room_floor, spawn_room, spawn_room_entities,
 };
 #[cfg(feature = "sqlite")]
 use crate::saving::save_game;
@@ -30,7 +92,13 @@ impl Plugin for GamePlugin {
 
         app.add_systems(
             OnEnter(AppState::Game),
-            (init_room_rng, spawn_room, place_player_actors).chain(),
+            (
+                init_room_rng,
+                spawn_room,
+                spawn_room_doors,
+                place_player_actors,
+            )
+                .chain(),
         )
         .add_systems(
             OnEnter(GameState::EnterRoom),
@@ -40,6 +108,7 @@ impl Plugin for GamePlugin {
                     spawn_room_entities,
                 )
                     .chain(),
+                paint_room_floor,
                 change_state(GameState::TriggerEvent),
             ),
         )
@@ -59,7 +128,8 @@ impl Plugin for GamePlugin {
             OnEnter(GameState::Navigation),
             (
                 (
-                    mark_room_cleared,
+                    (claim_pillar, mark_room_cleared).run_if(not(resource_exists::<Fled>)),
+                    revive_at_entrance.run_if(in_entrance.and(not(in_hardcore))),
                     #[cfg(feature = "sqlite")]
                     save_game,
                 )
@@ -69,12 +139,47 @@ impl Plugin for GamePlugin {
         )
         .add_systems(
             OnExit(GameState::Navigation),
-            despawn_filtered::<With<EntranceDirection>>,
+            (hide_room_doors, remove_resource::<Fled>),
         )
+        .add_systems(
+            Update,
+            (kill_heal_revive, sync_actor_tooltips).run_if(in_state(AppState::Game)),
+        )
+        .add_observer(gain_experience)
         .add_systems(OnEnter(GameState::GameOver), spawn_gameover_screen)
         .add_systems(OnEnter(GameState::Victory), spawn_victory_screen)
         .add_plugins(CombatPlugin)
-        .add_plugins(AttackOptionsPlugin);
+        .add_plugins(CombatLogPlugin)
+        .add_plugins(ChallengesPlugin)
+        .add_plugins(TurnOrderBarPlugin)
+        .add_plugins(AttackOptionsPlugin)
+        .add_plugins(PausePlugin)
+        .add_plugins(PartyPlugin)
+        .add_plugins(FogPlugin)
+        .add_plugins(BreadcrumbsPlugin)
+        .add_plugins(MapPinsPlugin)
+        .add_plugins(JournalPlugin)
+        .add_plugins(PouchPlugin)
+        .add_plugins(RewardsPlugin)
+        .add_plugins(RunStatsPlugin)
+        .add_plugins(SuspendPlugin)
+        .add_plugins(SchedulerPlugin)
+        .add_plugins(SpectatePlugin)
+        .add_plugins(CorruptionPlugin)
+        .add_plugins(EndlessPlugin)
+        .add_plugins(SkirmishPlugin)
+        .add_plugins(WinConditionPlugin)
+        .add_plugins(LootPlugin)
+        .add_plugins(PrefetchPlugin)
+        .add_plugins(PuzzlePlugin)
+        .add_plugins(ShrinePlugin)
+        .add_plugins(StairsPlugin)
+        .add_plugins(PillarPickupPlugin)
+        .add_plugins(TrapsPlugin)
+        .add_plugins(ChestsPlugin)
+        .add_plugins(CraftingPlugin)
+        .add_plugins(DoorPreviewPlugin)
+        .add_plugins(AutosavePlugin);
     }
 }
 
@@ -98,6 +203,18 @@ pub enum GameState {
     TriggerEvent,
     /// The combat state. See [`CombatState`]
     Combat,
+    /// Playing the puzzle in a puzzle room, see [`PuzzlePlugin`].
+    /// Goes to `Navigation` once it is solved or skipped.
+    Puzzle,
+    /// Making an offering at a shrine, see [`ShrinePlugin`].
+    /// Goes to `Navigation` once an offering is made or the party leaves.
+    Shrine,
+    /// Choosing whether to take the stairs down, see [`StairsPlugin`].
+    /// Goes to `EnterRoom` on the next floor, or `Navigation` to stay.
+    Stairs,
+    /// Forging hex shards at the Entrance, see [`CraftingPlugin`].
+    /// Goes to `Navigation` once the party leaves the forge.
+    Crafting,
     /// The UI for navigation pops up,
     /// and any things in the room are there.
     /// i.e. Item chests and spike traps
@@ -129,6 +246,9 @@ pub struct EventRng(pub RandomSource);
 // Whenever we change rooms,
 // despawn all that are in the old room.
 
+/// Revived heroes come back with a quarter of their max health.
+const REVIVE_HEALTH_DIVISOR: u32 = 4;
+
 /// The default player positons in Axial coordinate space
 const PLAYER_POSITIONS: [IVec2; 3] = [IVec2::new(-1, -1), IVec2::new(1, -2), IVec2::new(2, -1)];
 
@@ -146,7 +266,11 @@ fn place_player_actors(
     >,
     mut actors: Query<(Entity, &mut Transform), With<Actor>>,
 ) {
-    let (map_size, grid_size, tile_size, map_type, map_anchor) = tilemap.single().unwrap();
+    // The room map is gone if world generation was cancelled on the way into the game.
+    let Ok((map_size, grid_size, tile_size, map_type, map_anchor)) = tilemap.single() else {
+        warn!("No room tilemap to place the party in");
+        return;
+    };
 
     let center_tile_pos = UVec2 {
         x: map_size.x / 2,
@@ -163,21 +287,31 @@ fn place_player_actors(
 
         commands
             .entity(entity)
-            .insert((Pickable::default(), Visibility::Visible));
+            .insert((
+                Pickable::default(),
+                Visibility::Visible,
+                party_member_context_menu(),
+            ))
+            .observe(party_member_menu_select);
     }
 }
 
-fn init_room_rng(mut commands: Commands, info: Query<&RoomInfo, With<CurrentRoom>>) {
+fn init_room_rng(
+    mut commands: Commands,
+    info: Single<&RoomInfo, With<CurrentRoom>>,
+    config: Res<Config>,
+) {
     commands.insert_resource(EventRng(RandomSource::seed_from_u64(
-        info.single().unwrap().rng_seed,
+        info.rng_seed ^ config.event_rng_stream,
     )));
 }
 
 fn set_room_rng(
     info: Single<&RoomInfo, (With<CurrentRoom>, Added<CurrentRoom>)>,
     mut rng: ResMut<EventRng>,
+    config: Res<Config>,
 ) {
-    rng.0 = RandomSource::seed_from_u64(info.rng_seed);
+    rng.0 = RandomSource::seed_from_u64(info.rng_seed ^ config.event_rng_stream);
 }
 
 /// Shows a text box with the event happening,
@@ -185,15 +319,16 @@ fn set_room_rng(
 /// Skip to the navigation state.
 fn display_trigger_or_skip(
     mut commands: Commands,
-    info: Query<&RoomInfo, With<CurrentRoom>>,
+    info: Single<&RoomInfo, With<CurrentRoom>>,
     mut game_state: ResMut<NextState<GameState>>,
     style: Res<Style>,
 ) {
+    let info = *info;
     let RoomInfo {
-        cleared, r_type, ..
-    } = info.single().unwrap();
+        r_type, rng_seed, ..
+    } = info;
 
-    if *cleared || *r_type == RoomType::EmptyRoom {
+    if skips_trigger(info) {
         game_state.set(GameState::Navigation);
     } else {
         use RoomType as R;
@@ -203,7 +338,10 @@ fn display_trigger_or_skip(
             R::Combat(_) => format!("Monsters attack!"),
             R::Pit(damage) => format!("You fell in a Pit O' Doom!\n\t    -{} Health", damage),
             R::Item(item) => format!("Found item: {}", item),
-            R::Pillar => format!("You have a Pillar of OO!"),
+            R::Puzzle => "A puzzle blocks the way!".to_string(),
+            R::Shrine => "A shrine asks for an offering".to_string(),
+            R::Stairs => "Stairs lead deeper down".to_string(),
+            R::Pillar => format!("The {} guards a Pillar!", ActorName::guardian(*rng_seed)),
         };
 
         commands.spawn((
@@ -221,6 +359,11 @@ fn display_trigger_or_skip(
     }
 }
 
+/// Whether nothing happens in the room, so [`display_trigger_or_skip`] goes straight on.
+fn skips_trigger(info: &RoomInfo) -> bool {
+    info.cleared || info.r_type == RoomType::EmptyRoom
+}
+
 /// Waits for a time so the player can see the event, then do the event.
 /// TODO: Let users skip over this by pressing space or something.
 fn wait_for_trigger(
@@ -230,6 +373,10 @@ fn wait_for_trigger(
     mut game_state: ResMut<NextState<GameState>>,
     info: Single<&RoomInfo, With<CurrentRoom>>,
 ) {
+    // Sped up enough, the timer can finish before the skip takes effect.
+    if skips_trigger(*info) {
+        return;
+    }
     let RoomInfo { r_type, .. } = *info;
 
     let trigger = &mut timer.trigger_timer;
@@ -242,20 +389,63 @@ fn wait_for_trigger(
         let pause = &mut timer.pause_timer;
         pause.tick(time.delta());
         if pause.just_finished() {
-            if let RoomType::Combat(_) = &r_type {
-                game_state.set(GameState::Combat);
-            } else {
-                game_state.set(GameState::Navigation)
-            }
+            game_state.set(match r_type {
+                _ if r_type.has_enemies() => GameState::Combat,
+                RoomType::Puzzle => GameState::Puzzle,
+                RoomType::Shrine => GameState::Shrine,
+                RoomType::Stairs => GameState::Stairs,
+                _ => GameState::Navigation,
+            });
         }
     }
 }
 
+/// Brings a fallen hero back with a quarter of their max health.
+/// Revives aren't healing as far as [`Challenges::no_healing`] goes.
+pub fn revive_hero(health: &mut Health) {
+    let amount = health.max().get().div_ceil(REVIVE_HEALTH_DIVISOR);
+    health.heal_or_revive(amount);
+}
+
+/// Whether the run is hardcore, where fallen heroes don't get back up at the entrance.
+#[cfg(feature = "sqlite")]
+pub fn in_hardcore(save: Option<Res<SaveGame>>) -> bool {
+    save.is_some_and(|save| save.hardcore)
+}
+
+/// Hardcore is kept with the save, so without saves no run is hardcore.
+#[cfg(not(feature = "sqlite"))]
+pub fn in_hardcore() -> bool {
+    false
+}
+
+/// Fallen heroes get back up when the party returns to the entrance, unless the run is hardcore.
+fn revive_at_entrance(
+    mut party: Query<(&mut Health, &ActorName, Option<&HeroName>), With<PartySlot>>,
+    mut notifications: ResMut<Notifications>,
+) {
+    for (mut health, name, hero_name) in party.iter_mut() {
+        if health.is_alive() {
+            continue;
+        }
+
+        revive_hero(&mut health);
+
+        let name = hero_name.map_or_else(|| name.to_string(), |hero| hero.0.clone());
+        notifications.info(format!("{name} revived at the Entrance"));
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 fn trigger_event(
     mut commands: Commands,
     info: Single<&RoomInfo, With<CurrentRoom>>,
     mut actor_q: Query<&mut Health>,
     mut event_rng: ResMut<EventRng>,
+    mut items: ResMut<Items>,
+    room_item: Query<Entity, With<RoomItem>>,
+    mut notifications: ResMut<Notifications>,
+    mut stats: ResMut<RunStats>,
 ) {
     let RoomInfo {
         cleared, r_type, ..
@@ -265,103 +455,278 @@ fn trigger_event(
     use RoomType as R;
     match r_type {
         R::EmptyRoom => unreachable!(),
-        R::Entrance => {
-            commands.run_system_cached(pouch::pillar_count);
-        }
-        R::Combat(_) => {}
+        // Pillars are claimed once their guardian is beaten.
+        R::Entrance | R::Combat(_) | R::Pillar | R::Puzzle | R::Shrine | R::Stairs => {}
         R::Pit(damage) => {
             let actor_count = actor_q.iter().filter(|h| h.is_alive()).count();
             assert!(actor_count > 0);
 
             let actor_damaged = event_rng.random_range(0..actor_count);
 
-            actor_q
+            let mut health = actor_q
                 .iter_mut()
                 .filter(|h| h.is_alive())
                 .skip(actor_damaged)
                 .next()
-                .unwrap()
-                .damage_no_one_shot(*damage);
+                .unwrap();
+            let health_before = health_points(&health);
+            health.damage_no_one_shot(*damage);
+
+            stats.pits_triggered += 1;
+            stats.count_damage(Team::Player, health_before - health_points(&health));
 
             commands.run_system_cached(update_player_hp_bar_pit);
         }
-        R::Item(item) => {}
-        R::Pillar => {
-            commands.run_system_cached(pouch::add_pillar);
+        R::Item(item) => {
+            // Vision Potions are drunk as soon as they are picked up.
+            if *item == Item::VisionPotion {
+                stats.items_used += 1;
+                commands.run_system_cached(drink_vision_potion);
+            } else {
+                items.push(*item);
+                notifications.success(format!("Picked up a {item}"));
+            }
+
+            for entity in room_item.iter() {
+                commands.entity(entity).despawn();
+            }
         }
     }
 }
 
-fn navigation_enter(
+/// Collects the pillar once the party has beaten its guardian,
+/// or leaves it to be taken by hand, see [`PillarPickupPlugin`].
+fn claim_pillar(
     mut commands: Commands,
-    current_room: Single<&TilePos, With<CurrentRoom>>,
-    map_map: Single<(&TilemapSize, &TileStorage), (With<MapTilemap>, Without<RoomTilemap>)>,
-    mut room_map: Single<(Entity, &mut TileStorage), (With<RoomTilemap>, Without<MapTilemap>)>,
-    maptile_q: Query<&TileTextureIndex>,
+    mut info: Single<&mut RoomInfo, With<CurrentRoom>>,
+    config: Res<Config>,
+    mut notifications: ResMut<Notifications>,
 ) {
-    let (map_size, map_storage) = *map_map;
-
-    let (room_entity, ref mut room_storage) = *room_map;
+    if info.r_type != RoomType::Pillar || info.cleared {
+        return;
+    }
 
-    let room_center = ROOM_CENTER;
+    if config.manual_pillar_pickup {
+        info.guardian_respawn = Some(GUARDIAN_RESPAWN_ROOMS);
+        return;
+    }
+    collect_pillar(&mut commands, &mut notifications);
+}
 
-    let neighbors =
-        HexNeighbors::<TilePos>::get_neighboring_positions_standard(&current_room, map_size);
+fn collect_pillar(commands: &mut Commands, notifications: &mut Notifications) {
+    commands.run_system_cached(pouch::add_pillar);
+    commands.trigger(Bark::new(BarkContext::PillarFound));
+    notifications.success("You have a Pillar of OO!");
+}
 
-    let door_directions = neighbors
-        .iter()
-        .zip(EntranceDirection::ALL)
-        .filter_map(|(neighbor, dir)| map_storage.checked_get(neighbor).map(|n| (n, dir)))
-        .filter_map(|(entity, dir)| {
-            maptile_q
-                .get(entity)
-                .is_ok_and(|t| *t != TileTextureIndex(OUTLINE_TILE))
-                .then_some(dir)
-        });
+/// Spawns every door once, hidden until [`navigation_enter`] shows the ones that lead somewhere.
+/// Reusing them avoids spawning and despawning tiles every room.
+fn spawn_room_doors(
+    mut commands: Commands,
+    mut room_map: Single<(Entity, &mut TileStorage), With<RoomTilemap>>,
+) {
+    let (room_entity, ref mut room_storage) = *room_map;
 
-    commands.entity(room_entity).with_children(move |parent| {
-        for dir in door_directions {
-            let tile_pos = dir.door_offset(&room_center, ROOM_RADIUS, HEX_COORD_SYSTEM);
+    commands.entity(room_entity).with_children(|parent| {
+        for dir in EntranceDirection::ALL {
+            let tile_pos = dir.door_offset(&ROOM_CENTER, ROOM_RADIUS, HEX_COORD_SYSTEM);
 
             let id = parent
                 .spawn((
-                    StateScoped(GameState::Navigation),
                     dir,
+                    Tooltip::new(format!("{dir} Door")).with_body("Click to enter the next room"),
                     TileBundle {
                         position: tile_pos,
                         tilemap_id: TilemapId(room_entity),
                         texture_index: TileTextureIndex(DOOR_TILE_VARIENT),
+                        visible: TileVisible(false),
                         ..Default::default()
                     },
                 ))
                 .observe(click_door)
+                .observe(hover_door)
+                .observe(unhover_door)
                 .id();
             room_storage.set(&tile_pos, id);
         }
     });
 }
 
+#[allow(clippy::type_complexity)]
+fn navigation_enter(
+    current_room: Query<&TilePos, With<CurrentRoom>>,
+    map_map: Query<(&TilemapSize, &TileStorage), (With<MapTilemap>, Without<RoomTilemap>)>,
+    maptile_q: Query<&TileTextureIndex>,
+    mut door_q: Query<(&EntranceDirection, &mut TileVisible)>,
+) {
+    let Ok(current_room) = current_room.single() else {
+        warn!("No current room to show the doors of");
+        return;
+    };
+    let Ok((map_size, map_storage)) = map_map.single() else {
+        warn!("No map to show the doors with");
+        return;
+    };
+
+    let neighbors =
+        HexNeighbors::<TilePos>::get_neighboring_positions_standard(current_room, map_size);
+
+    let door_directions: Vec<EntranceDirection> = neighbors
+        .iter()
+        .zip(EntranceDirection::ALL)
+        .filter_map(|(neighbor, dir)| map_storage.checked_get(neighbor).map(|n| (n, dir)))
+        .filter_map(|(entity, dir)| {
+            maptile_q
+                .get(entity)
+                .is_ok_and(|t| *t != TileTextureIndex(OUTLINE_TILE))
+                .then_some(dir)
+        })
+        .collect();
+
+    for (dir, mut visible) in door_q.iter_mut() {
+        visible.0 = door_directions.contains(dir);
+    }
+}
+
+fn hide_room_doors(mut door_q: Query<&mut TileVisible, With<EntranceDirection>>) {
+    for mut visible in door_q.iter_mut() {
+        visible.0 = false;
+    }
+}
+
 fn click_door(
     event: Trigger<Pointer<Click>>,
     mut commands: Commands,
-    current_room: Single<(Entity, &TilePos), With<CurrentRoom>>,
-    map_map: Single<&TileStorage, (With<MapTilemap>, Without<RoomTilemap>)>,
+    current_room: Query<(Entity, &TilePos), With<CurrentRoom>>,
+    map_map: Query<&TileStorage, (With<MapTilemap>, Without<RoomTilemap>)>,
     direction_q: Query<&EntranceDirection>,
     mut next_state: ResMut<NextState<GameState>>,
 ) {
-    let (current_room_entity, current_room_pos) = *current_room;
-    let map_storage = *map_map;
-    let move_dir = direction_q.get(event.target).unwrap();
+    let Ok(move_dir) = direction_q.get(event.target) else {
+        warn!("Clicked door {} has no direction", event.target);
+        return;
+    };
+    let (Ok(current_room), Ok(map_storage)) = (current_room.single(), map_map.single()) else {
+        warn!("Clicked a door with no current room or map");
+        return;
+    };
 
+    go_through_door(
+        &mut commands,
+        current_room,
+        map_storage,
+        *move_dir,
+        &mut next_state,
+    );
+}
+
+/// Moves the party from the current room to the neighboring room in `move_dir`.
+pub fn go_through_door(
+    commands: &mut Commands,
+    (current_room_entity, current_room_pos): (Entity, &TilePos),
+    map_storage: &TileStorage,
+    move_dir: EntranceDirection,
+    next_state: &mut NextState<GameState>,
+) {
     let new_room_pos = TilePos {
         x: (current_room_pos.x as i32 + move_dir.axial_offset().q) as u32,
         y: (current_room_pos.y as i32 + move_dir.axial_offset().r) as u32,
     };
 
-    let new_room_entity = map_storage.get(&new_room_pos).unwrap();
+    let Some(new_room_entity) = map_storage.get(&new_room_pos) else {
+        warn!("No room at {new_room_pos:?} to go through the door to");
+        return;
+    };
     commands.entity(new_room_entity).insert(CurrentRoom);
 
+    commands.run_system_cached_with(cross_spike_traps, move_dir);
     commands.entity(current_room_entity).remove::<CurrentRoom>();
+    commands.insert_resource(RoomApproach(move_dir));
 
     next_state.set(GameState::EnterRoom);
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::menu::new_game::{NewGameState, cancel_generation};
+    use std::num::NonZero;
+
+    /// A world whose generation was cancelled just as the game was being entered.
+    fn cancelled_world() -> World {
+        let mut world = World::new();
+        world.insert_non_send_resource(Database::open_in_memory().unwrap());
+        world.init_resource::<NextState<NewGameState>>();
+
+        world.spawn((
+            RoomTilemap,
+            TilemapSize { x: 9, y: 9 },
+            TilemapGridSize { x: 16.0, y: 16.0 },
+            TilemapTileSize { x: 16.0, y: 16.0 },
+            TilemapType::default(),
+            TilemapAnchor::default(),
+        ));
+        world.spawn((Actor, Transform::default()));
+        world.spawn((CurrentRoom, TilePos { x: 4, y: 4 }));
+
+        world.run_system_cached(cancel_generation).unwrap();
+        world.flush();
+        world
+    }
+
+    #[test]
+    fn placing_the_party_after_cancel() {
+        let mut world = cancelled_world();
+
+        world.run_system_cached(place_player_actors).unwrap();
+        world.flush();
+
+        assert!(world.query::<&RoomTilemap>().iter(&world).next().is_none());
+    }
+
+    #[test]
+    fn showing_doors_after_cancel() {
+        let mut world = cancelled_world();
+        let door = world
+            .spawn((EntranceDirection::North, TileVisible(false)))
+            .id();
+
+        world.run_system_cached(navigation_enter).unwrap();
+
+        assert!(!world.get::<TileVisible>(door).unwrap().0);
+    }
+
+    #[test]
+    fn fallen_heroes_revive_at_the_entrance() {
+        let mut world = World::new();
+        world.init_resource::<Notifications>();
+
+        let max = NonZero::new(75).unwrap();
+        let fallen = world
+            .spawn((
+                Health::with_current(None, max),
+                ActorName::Theif,
+                PartySlot(2),
+            ))
+            .id();
+        let hurt = world
+            .spawn((
+                Health::with_current(NonZero::new(5), max),
+                ActorName::Warrior,
+                PartySlot(0),
+            ))
+            .id();
+
+        world.run_system_cached(revive_at_entrance).unwrap();
+
+        assert_eq!(
+            world.get::<Health>(fallen).unwrap().current(),
+            NonZero::new(19)
+        );
+        assert_eq!(
+            world.get::<Health>(hurt).unwrap().current(),
+            NonZero::new(5)
+        );
+        assert!(!world.resource::<Notifications>().is_empty());
+    }
+}
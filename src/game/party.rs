@@ -0,0 +1,462 @@
+//! The party screen in the pause menu, where fallen heroes can be brought back
+//! and gear is put on and taken off.
+//!
+//! Out of combat, a Priestess who is still standing can revive the others for gold
+//! or a Healing Potion. Revived heroes come back with a quarter of their max health.
+//! Gear can be swapped around out of combat too, see [`Equipment`].
+
+use super::*;
+
+/// The gold the Priestess asks to bring a hero back.
+pub const REVIVE_GOLD_COST: u64 = 60;
+/// The item the Priestess can use instead of gold.
+pub const REVIVE_ITEM: Item = Item::HealingPotion;
+
+pub struct PartyPlugin;
+
+impl Plugin for PartyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(PauseState::Party), pause_party_enter);
+    }
+}
+
+/// What the party pays the Priestess with for a revival.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RevivePayment {
+    Gold,
+    Item,
+}
+
+impl RevivePayment {
+    pub fn label(self) -> String {
+        match self {
+            RevivePayment::Gold => format!("Revive ({REVIVE_GOLD_COST} gold)"),
+            RevivePayment::Item => format!("Revive ({REVIVE_ITEM})"),
+        }
+    }
+
+    pub fn affordable(self, gold: &Gold, items: &Items) -> bool {
+        match self {
+            RevivePayment::Gold => gold.0 >= REVIVE_GOLD_COST,
+            RevivePayment::Item => items.contains(&REVIVE_ITEM),
+        }
+    }
+
+    /// Takes the payment, or leaves everything be and returns false if the party can't afford it.
+    pub fn pay(self, gold: &mut Gold, items: &mut Items) -> bool {
+        if !self.affordable(gold, items) {
+            return false;
+        }
+
+        match self {
+            RevivePayment::Gold => gold.0 -= REVIVE_GOLD_COST,
+            RevivePayment::Item => {
+                let index = items.iter().position(|item| *item == REVIVE_ITEM).unwrap();
+                items.remove(index);
+            }
+        }
+        true
+    }
+}
+
+/// Why the Priestess can't revive anyone right now, if she can't.
+pub fn revive_blocker(game_state: GameState, priestess_standing: bool) -> Option<&'static str> {
+    match (game_state, priestess_standing) {
+        (GameState::Combat, _) => Some("No reviving in the middle of a fight"),
+        (_, false) => Some("Only a standing Priestess can revive the fallen"),
+        _ => None,
+    }
+}
+
+/// Why gear can't be changed right now, if it can't.
+pub fn gear_blocker(game_state: GameState) -> Option<&'static str> {
+    match game_state {
+        GameState::Combat => Some("No changing gear in the middle of a fight"),
+        _ => None,
+    }
+}
+
+/// The gear the party is carrying that could go in `slot`, each kind once.
+fn carried_gear(items: &Items, slot: GearSlot) -> Vec<Item> {
+    let mut gear: Vec<Item> = items
+        .iter()
+        .filter(|item| item.slot() == Some(slot))
+        .copied()
+        .collect();
+    gear.sort_by_key(|item| item.to_string());
+    gear.dedup();
+    gear
+}
+
+/// The root of the party screen, so it can be rebuilt after a revival or a change of gear.
+#[derive(Component)]
+struct PartyScreen;
+
+#[allow(clippy::type_complexity)]
+fn pause_party_enter(
+    mut commands: Commands,
+    style: Res<Style>,
+    game_state: Res<State<GameState>>,
+    gold: Option<Res<Gold>>,
+    items: Res<Items>,
+    screens: Query<Entity, With<PartyScreen>>,
+    party: Query<(
+        Entity,
+        &PartySlot,
+        &ActorName,
+        Option<&HeroName>,
+        &Health,
+        Option<&Equipment>,
+    )>,
+) {
+    for screen in screens.iter() {
+        commands.entity(screen).despawn();
+    }
+
+    let gold = gold.map_or_else(Gold::default, |gold| *gold);
+    let mut party: Vec<_> = party.iter().collect();
+    party.sort_by_key(|(_, slot, ..)| **slot);
+
+    let priestess_standing = party
+        .iter()
+        .any(|(_, _, name, _, health, _)| **name == ActorName::Priestess && health.is_alive());
+    let blocker = revive_blocker(*game_state.get(), priestess_standing);
+    let gear_blocked = gear_blocker(*game_state.get());
+
+    let button_node = Node {
+        width: Val::Px(300.0),
+        height: Val::Px(50.0),
+        margin: UiRect::all(Val::Px(5.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let text_style = (style.font(24.0), TextColor(style.text_color));
+
+    commands
+        .spawn((pause_overlay(&style, PauseState::Party), PartyScreen))
+        .with_children(|builder| {
+            builder.spawn((
+                Text::new("Party"),
+                style.font(66.0),
+                TextColor(style.text_color),
+            ));
+
+            builder.spawn((Text::new(format!("Gold: {}", gold.0)), text_style.clone()));
+
+            for (entity, _, name, hero_name, health, equipment) in party {
+                let equipment = equipment.copied().unwrap_or_default();
+                builder
+                    .spawn((
+                        Node {
+                            margin: UiRect::all(Val::Px(10.0)),
+                            padding: UiRect::all(Val::Px(10.0)),
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        BackgroundColor(style.background_color),
+                    ))
+                    .with_children(|builder| {
+                        let current = health.current().map(|h| h.get()).unwrap_or(0);
+                        let status = match health.is_alive() {
+                            true => format!("Health: {current}/{}", health.max()),
+                            false => "Fallen".to_string(),
+                        };
+                        builder.spawn((
+                            Text::new(actor_title(*name, hero_name)),
+                            style.font(33.0),
+                            TextColor(style.text_color),
+                        ));
+                        builder.spawn((Text::new(status), text_style.clone()));
+
+                        for slot in [GearSlot::Weapon, GearSlot::Armor] {
+                            let worn = match equipment.get(slot) {
+                                Some(item) => {
+                                    format!("{slot}: {item} ({})", item.bonus().describe())
+                                }
+                                None => format!("{slot}: None"),
+                            };
+                            builder.spawn((Text::new(worn), text_style.clone()));
+                            if gear_blocked.is_some() {
+                                continue;
+                            }
+
+                            if equipment.get(slot).is_some() {
+                                builder
+                                    .spawn((
+                                        Button,
+                                        button_node.clone(),
+                                        BackgroundColor(style.button_color),
+                                        children![(
+                                            Text::new(format!("Take off {slot}")),
+                                            text_style.clone(),
+                                            Pickable::IGNORE
+                                        )],
+                                    ))
+                                    .observe(unequip_on_click(entity, slot));
+                            }
+                            for item in carried_gear(&items, slot) {
+                                builder
+                                    .spawn((
+                                        Button,
+                                        button_node.clone(),
+                                        BackgroundColor(style.button_color),
+                                        children![(
+                                            Text::new(format!("Wear {item}")),
+                                            text_style.clone(),
+                                            Pickable::IGNORE
+                                        )],
+                                    ))
+                                    .observe(equip_on_click(entity, item));
+                            }
+                        }
+                        if let Some(gear_blocked) = gear_blocked {
+                            builder.spawn((Text::new(gear_blocked), text_style.clone()));
+                        }
+
+                        if health.is_alive() {
+                            return;
+                        }
+                        if let Some(blocker) = blocker {
+                            builder.spawn((Text::new(blocker), text_style.clone()));
+                            return;
+                        }
+
+                        for payment in [RevivePayment::Gold, RevivePayment::Item] {
+                            let color = match payment.affordable(&gold, &items) {
+                                true => style.button_color,
+                                false => style.button_color.with_alpha(0.4),
+                            };
+                            builder
+                                .spawn((
+                                    Button,
+                                    button_node.clone(),
+                                    BackgroundColor(color),
+                                    children![(
+                                        Text::new(payment.label()),
+                                        text_style.clone(),
+                                        Pickable::IGNORE
+                                    )],
+                                ))
+                                .observe(revive_on_click(entity, payment));
+                        }
+                    });
+            }
+
+            builder
+                .spawn((
+                    Button,
+                    Node {
+                        width: Val::Px(200.0),
+                        height: Val::Px(65.0),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    BackgroundColor(style.button_color),
+                    children![(
+                        Text::new("Back"),
+                        style.font(33.0),
+                        TextColor(style.text_color),
+                        Pickable::IGNORE
+                    )],
+                ))
+                .observe(change_state_on_click(
+                    PointerButton::Primary,
+                    PauseState::Paused,
+                ));
+        });
+}
+
+fn revive_on_click(
+    hero: Entity,
+    payment: RevivePayment,
+) -> impl Fn(Trigger<Pointer<Click>>, Commands) {
+    move |mut click, mut commands| {
+        click.propagate(false);
+
+        if click.button == PointerButton::Primary {
+            commands.run_system_cached_with(priestess_revive, (hero, payment));
+        }
+    }
+}
+
+fn equip_on_click(hero: Entity, item: Item) -> impl Fn(Trigger<Pointer<Click>>, Commands) {
+    move |mut click, mut commands| {
+        click.propagate(false);
+
+        if click.button == PointerButton::Primary {
+            commands.run_system_cached_with(equip_hero, (hero, item));
+        }
+    }
+}
+
+fn unequip_on_click(hero: Entity, slot: GearSlot) -> impl Fn(Trigger<Pointer<Click>>, Commands) {
+    move |mut click, mut commands| {
+        click.propagate(false);
+
+        if click.button == PointerButton::Primary {
+            commands.run_system_cached_with(unequip_hero, (hero, slot));
+        }
+    }
+}
+
+/// Puts a carried piece of gear on `hero`, putting what they wore before back in the bag.
+pub fn equip_hero(
+    In((hero, item)): In<(Entity, Item)>,
+    mut commands: Commands,
+    mut party: Query<
+        (
+            &mut Attack,
+            &mut BlockChance,
+            &mut Health,
+            Option<&Equipment>,
+        ),
+        With<PartySlot>,
+    >,
+    mut items: ResMut<Items>,
+    game_state: Res<State<GameState>>,
+    mut notifications: ResMut<Notifications>,
+) {
+    if let Some(blocker) = gear_blocker(*game_state.get()) {
+        notifications.warn(blocker);
+        return;
+    }
+    let Ok((mut attack, mut block_chance, mut health, equipment)) = party.get_mut(hero) else {
+        return;
+    };
+    let Some(index) = items.iter().position(|carried| *carried == item) else {
+        return;
+    };
+    if item.slot().is_none() {
+        return;
+    }
+
+    items.remove(index);
+    let mut equipment = equipment.copied().unwrap_or_default();
+    if let Some(replaced) = equipment.equip(item, &mut attack, &mut block_chance, &mut health) {
+        items.push(replaced);
+    }
+    commands.entity(hero).insert(equipment);
+
+    commands.run_system_cached(update_player_hp_bar_pit);
+    commands.run_system_cached(pause_party_enter);
+}
+
+/// Takes off what `hero` wears in `slot`, putting it back in the bag.
+pub fn unequip_hero(
+    In((hero, slot)): In<(Entity, GearSlot)>,
+    mut commands: Commands,
+    mut party: Query<(&mut Attack, &mut BlockChance, &mut Health, &mut Equipment)>,
+    mut items: ResMut<Items>,
+    game_state: Res<State<GameState>>,
+    mut notifications: ResMut<Notifications>,
+) {
+    if let Some(blocker) = gear_blocker(*game_state.get()) {
+        notifications.warn(blocker);
+        return;
+    }
+    let Ok((mut attack, mut block_chance, mut health, mut equipment)) = party.get_mut(hero) else {
+        return;
+    };
+
+    if let Some(item) = equipment.unequip(slot, &mut attack, &mut block_chance, &mut health) {
+        items.push(item);
+    }
+
+    commands.run_system_cached(update_player_hp_bar_pit);
+    commands.run_system_cached(pause_party_enter);
+}
+
+/// Has the Priestess bring `hero` back, if she is standing and the party can pay.
+pub fn priestess_revive(
+    In((hero, payment)): In<(Entity, RevivePayment)>,
+    mut commands: Commands,
+    mut party: Query<(&mut Health, &ActorName, Option<&HeroName>), With<PartySlot>>,
+    mut gold: ResMut<Gold>,
+    mut items: ResMut<Items>,
+    game_state: Res<State<GameState>>,
+    mut notifications: ResMut<Notifications>,
+) {
+    let priestess_standing = party
+        .iter()
+        .any(|(health, name, _)| *name == ActorName::Priestess && health.is_alive());
+    if let Some(blocker) = revive_blocker(*game_state.get(), priestess_standing) {
+        notifications.warn(blocker);
+        return;
+    }
+
+    let Ok((mut health, name, hero_name)) = party.get_mut(hero) else {
+        return;
+    };
+    if health.is_alive() {
+        return;
+    }
+    if !payment.pay(&mut gold, &mut items) {
+        match payment {
+            RevivePayment::Gold => {
+                notifications.warn(format!("The Priestess needs {REVIVE_GOLD_COST} gold"))
+            }
+            RevivePayment::Item => notifications.warn(format!("The party has no {REVIVE_ITEM}")),
+        }
+        return;
+    }
+
+    revive_hero(&mut health);
+    let name = hero_name.map_or_else(|| name.to_string(), |hero| hero.0.clone());
+    notifications.success(format!("The Priestess revived {name}"));
+
+    commands.run_system_cached(update_player_hp_bar_pit);
+    commands.run_system_cached(pause_party_enter);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn revivals_are_only_paid_for_when_affordable() {
+        let mut gold = Gold(REVIVE_GOLD_COST + 5);
+        let mut items = Items(vec![Item::VisionPotion, REVIVE_ITEM, REVIVE_ITEM]);
+
+        assert!(RevivePayment::Gold.pay(&mut gold, &mut items));
+        assert_eq!(gold, Gold(5));
+        assert!(!RevivePayment::Gold.pay(&mut gold, &mut items));
+        assert_eq!(gold, Gold(5));
+
+        assert!(RevivePayment::Item.pay(&mut gold, &mut items));
+        assert!(RevivePayment::Item.pay(&mut gold, &mut items));
+        assert!(!RevivePayment::Item.pay(&mut gold, &mut items));
+        assert_eq!(items.0, vec![Item::VisionPotion]);
+    }
+
+    #[test]
+    fn only_carried_gear_can_be_worn() {
+        let items = Items(vec![
+            Item::WarAxe,
+            Item::HealingPotion,
+            Item::Chainmail,
+            Item::IronSword,
+            Item::WarAxe,
+        ]);
+
+        assert_eq!(
+            carried_gear(&items, GearSlot::Weapon),
+            vec![Item::IronSword, Item::WarAxe]
+        );
+        assert_eq!(carried_gear(&items, GearSlot::Armor), vec![Item::Chainmail]);
+        assert!(carried_gear(&Items::default(), GearSlot::Armor).is_empty());
+
+        assert_eq!(gear_blocker(GameState::Navigation), None);
+        assert!(gear_blocker(GameState::Combat).is_some());
+    }
+
+    #[test]
+    fn only_a_standing_priestess_revives_out_of_combat() {
+        assert_eq!(revive_blocker(GameState::Navigation, true), None);
+        assert_eq!(revive_blocker(GameState::Shrine, true), None);
+        assert!(revive_blocker(GameState::Combat, true).is_some());
+        assert!(revive_blocker(GameState::Navigation, false).is_some());
+    }
+}
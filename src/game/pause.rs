@@ -0,0 +1,447 @@
+//! The in-game pause menu, laid over the game without leaving the current state.
+
+use super::GameState;
+use super::autosave::SavePolicy;
+use super::inspect::{actor_stats, actor_title};
+use crate::controls::Input;
+use crate::menu::{button_highlight, spawn_volume_sliders};
+use crate::prelude::*;
+#[cfg(feature = "sqlite")]
+use crate::saving::save_game;
+use bevy::prelude::*;
+
+const PAUSE_Z_INDEX: i32 = 50;
+
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_sub_state::<PauseState>();
+
+        #[cfg(feature = "debug")]
+        app.add_systems(Update, log_transitions::<PauseState>);
+
+        app.add_systems(Update, toggle_pause.run_if(in_state(AppState::Game)))
+            .add_systems(
+                Update,
+                button_highlight.run_if(
+                    in_state(PauseState::Paused)
+                        .or(in_state(PauseState::Settings))
+                        .or(in_state(PauseState::Inspect))
+                        .or(in_state(PauseState::Journal))
+                        .or(in_state(PauseState::Party))
+                        .or(in_state(PauseState::PinNote)),
+                ),
+            )
+            .add_systems(OnEnter(PauseState::Paused), (freeze_time, pause_enter))
+            // The party screen can also be opened straight from a party member's menu.
+            .add_systems(OnEnter(PauseState::Party), freeze_time)
+            .add_systems(OnEnter(PauseState::PinNote), freeze_time)
+            .add_systems(OnEnter(PauseState::Settings), pause_settings_enter)
+            .add_systems(OnEnter(PauseState::Inspect), pause_inspect_enter)
+            .add_systems(OnEnter(PauseState::Running), unfreeze_time)
+            .add_systems(OnExit(AppState::Game), unfreeze_time);
+    }
+}
+
+/// Whether the game is paused. Pausing stops [`Time<Virtual>`],
+/// so every timer and movement in the game stops with it.
+#[derive(SubStates, Clone, Copy, Default, Eq, PartialEq, Debug, Hash)]
+#[source(AppState = AppState::Game)]
+#[states(scoped_entities)]
+pub enum PauseState {
+    #[default]
+    Running,
+    Paused,
+    Settings,
+    /// Everyone's stats and the keybinds, so they can be looked over mid fight.
+    Inspect,
+    /// The rooms cleared so far this run.
+    Journal,
+    /// The heroes and who among them has fallen, see [`PartyPlugin`](super::PartyPlugin).
+    Party,
+    /// Writing the note of a pin on the map, see [`MapPinsPlugin`](super::MapPinsPlugin).
+    /// The game is held, so typing doesn't press its keybinds.
+    PinNote,
+}
+
+fn toggle_pause(
+    key: Res<ControlState>,
+    pause_state: Res<State<PauseState>>,
+    mut next_state: ResMut<NextState<PauseState>>,
+) {
+    if key.just_pressed(Control::Pause) {
+        use PauseState as P;
+        next_state.set(match *pause_state.get() {
+            P::Running => P::Paused,
+            P::Paused => P::Running,
+            P::Settings | P::Inspect | P::Journal | P::Party => P::Paused,
+            P::PinNote => P::Running,
+        });
+    }
+}
+
+fn freeze_time(mut time: ResMut<Time<Virtual>>) {
+    time.pause();
+}
+
+fn unfreeze_time(mut time: ResMut<Time<Virtual>>) {
+    time.unpause();
+}
+
+/// The dimmed full screen node the pause menus are shown in.
+/// It also blocks clicks from reaching the game underneath.
+pub(super) fn pause_overlay(style: &Style, state: PauseState) -> impl Bundle {
+    (
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        BackgroundColor(style.background_color.with_alpha(0.8)),
+        GlobalZIndex(PAUSE_Z_INDEX),
+        StateScoped(state),
+    )
+}
+
+fn pause_enter(
+    mut commands: Commands,
+    style: Res<Style>,
+    game_state: Res<State<GameState>>,
+    policy: Res<SavePolicy>,
+    #[cfg(feature = "sqlite")] save: Option<Res<SaveGame>>,
+) {
+    // Skirmishes aren't saved, and have no journal.
+    #[cfg(feature = "sqlite")]
+    let saved = save.is_some();
+    #[cfg(not(feature = "sqlite"))]
+    let saved = false;
+
+    let button_node = Node {
+        width: Val::Px(300.0),
+        height: Val::Px(65.0),
+        margin: UiRect::all(Val::Px(15.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let button_text_style = (style.font(33.0), TextColor(style.text_color));
+
+    commands
+        .spawn(pause_overlay(&style, PauseState::Paused))
+        .with_children(|builder| {
+            builder.spawn((
+                Text::new("Paused"),
+                style.font(66.0),
+                TextColor(style.text_color),
+            ));
+
+            builder
+                .spawn((
+                    Button,
+                    button_node.clone(),
+                    BackgroundColor(style.button_color),
+                    children![(
+                        Text::new("Resume"),
+                        button_text_style.clone(),
+                        Pickable::IGNORE
+                    )],
+                ))
+                .observe(change_state_on_click(
+                    PointerButton::Primary,
+                    PauseState::Running,
+                ));
+
+            builder
+                .spawn((
+                    Button,
+                    button_node.clone(),
+                    BackgroundColor(style.button_color),
+                    children![(
+                        Text::new("Settings"),
+                        button_text_style.clone(),
+                        Pickable::IGNORE
+                    )],
+                ))
+                .observe(change_state_on_click(
+                    PointerButton::Primary,
+                    PauseState::Settings,
+                ));
+
+            builder
+                .spawn((
+                    Button,
+                    button_node.clone(),
+                    BackgroundColor(style.button_color),
+                    children![(
+                        Text::new("Inspect"),
+                        button_text_style.clone(),
+                        Pickable::IGNORE
+                    )],
+                ))
+                .observe(change_state_on_click(
+                    PointerButton::Primary,
+                    PauseState::Inspect,
+                ));
+
+            builder
+                .spawn((
+                    Button,
+                    button_node.clone(),
+                    BackgroundColor(style.button_color),
+                    children![(
+                        Text::new("Party"),
+                        button_text_style.clone(),
+                        Pickable::IGNORE
+                    )],
+                ))
+                .observe(change_state_on_click(
+                    PointerButton::Primary,
+                    PauseState::Party,
+                ));
+
+            if saved {
+                builder
+                    .spawn((
+                        Button,
+                        button_node.clone(),
+                        BackgroundColor(style.button_color),
+                        children![(
+                            Text::new("Save"),
+                            button_text_style.clone(),
+                            Pickable::IGNORE
+                        )],
+                    ))
+                    .observe(save_on_click);
+
+                builder
+                    .spawn((
+                        Button,
+                        button_node.clone(),
+                        BackgroundColor(style.button_color),
+                        children![(
+                            Text::new("Journal"),
+                            button_text_style.clone(),
+                            Pickable::IGNORE
+                        )],
+                    ))
+                    .observe(change_state_on_click(
+                        PointerButton::Primary,
+                        PauseState::Journal,
+                    ));
+            }
+
+            // Fights are saved where they are, and pick back up on the same turn.
+            let quit_text = match (saved && policy.on_quit, game_state.get()) {
+                (false, _) => "Quit",
+                (true, GameState::Combat) => "Save & Exit",
+                (true, _) => "Save & Quit",
+            };
+
+            builder
+                .spawn((
+                    Button,
+                    button_node.clone(),
+                    BackgroundColor(style.button_color),
+                    children![(
+                        Text::new(quit_text),
+                        button_text_style.clone(),
+                        Pickable::IGNORE
+                    )],
+                ))
+                .observe(save_and_quit_on_click);
+        });
+}
+
+fn save_on_click(mut click: Trigger<Pointer<Click>>, mut commands: Commands) {
+    click.propagate(false);
+
+    #[cfg(feature = "sqlite")]
+    if click.button == PointerButton::Primary {
+        commands.run_system_cached(save_game);
+    }
+}
+
+fn save_and_quit_on_click(
+    mut click: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    policy: Res<SavePolicy>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    click.propagate(false);
+
+    if click.button != PointerButton::Primary {
+        return;
+    }
+
+    #[cfg(feature = "sqlite")]
+    if policy.on_quit {
+        commands.run_system_cached(save_game);
+    }
+    next_state.set(AppState::Menu);
+}
+
+fn pause_settings_enter(mut commands: Commands, style: Res<Style>, volume: Res<Volume>) {
+    let button_node = Node {
+        width: Val::Px(200.0),
+        height: Val::Px(65.0),
+        margin: UiRect::all(Val::Px(20.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let button_text_style = (style.font(33.0), TextColor(style.text_color));
+
+    commands
+        .spawn(pause_overlay(&style, PauseState::Settings))
+        .with_children(|builder| {
+            spawn_volume_sliders(builder, &style, &volume);
+
+            builder
+                .spawn((
+                    Button,
+                    button_node,
+                    BackgroundColor(style.button_color),
+                    children![(Text::new("Back"), button_text_style, Pickable::IGNORE)],
+                ))
+                .observe(change_state_on_click(
+                    PointerButton::Primary,
+                    PauseState::Paused,
+                ));
+        });
+}
+
+/// The inputs bound to each control, one control per line.
+fn keybinds_text(controls: &Controls) -> String {
+    controls
+        .clone()
+        .into_iter()
+        .map(|Keybind(control, inputs)| {
+            let inputs: Vec<String> = inputs.iter().flatten().map(Input::to_string).collect();
+            format!("{}: {}", control.as_string(), inputs.join(", "))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[allow(clippy::type_complexity)]
+fn pause_inspect_enter(
+    mut commands: Commands,
+    style: Res<Style>,
+    controls: Res<Controls>,
+    actors: Query<
+        (
+            &ActorName,
+            Option<&HeroName>,
+            Option<&PartySlot>,
+            &Team,
+            &Health,
+            &Attack,
+            &AttackSpeed,
+            &BlockChance,
+        ),
+        With<Actor>,
+    >,
+) {
+    // The party in order, then whichever enemies are still standing.
+    let mut actors: Vec<_> = actors
+        .iter()
+        .filter(|(.., team, health, _, _, _)| **team == Team::Player || health.is_alive())
+        .collect();
+    actors.sort_by_key(|(_, _, slot, team, ..)| (**team == Team::Enemy, slot.copied()));
+
+    let text_style = (style.font(24.0), TextColor(style.text_color));
+
+    commands
+        .spawn(pause_overlay(&style, PauseState::Inspect))
+        .with_children(|builder| {
+            builder
+                .spawn(Node {
+                    flex_wrap: FlexWrap::Wrap,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                })
+                .with_children(|builder| {
+                    for (name, hero_name, _, team, health, attack, speed, block) in actors {
+                        let color = match team {
+                            Team::Player => style.text_color,
+                            Team::Enemy => style.accent_color,
+                        };
+
+                        builder
+                            .spawn((
+                                Node {
+                                    margin: UiRect::all(Val::Px(10.0)),
+                                    padding: UiRect::all(Val::Px(10.0)),
+                                    flex_direction: FlexDirection::Column,
+                                    ..default()
+                                },
+                                BackgroundColor(style.background_color),
+                            ))
+                            .with_children(|builder| {
+                                builder.spawn((
+                                    Text::new(actor_title(*name, hero_name)),
+                                    style.font(33.0),
+                                    TextColor(color),
+                                ));
+                                builder.spawn((
+                                    Text::new(actor_stats(health, attack, speed, block).join("\n")),
+                                    text_style.clone(),
+                                ));
+                            });
+                    }
+                });
+
+            builder.spawn((
+                Text::new(keybinds_text(&controls)),
+                text_style.clone(),
+                Node {
+                    margin: UiRect::all(Val::Px(10.0)),
+                    ..default()
+                },
+            ));
+
+            builder
+                .spawn((
+                    Button,
+                    Node {
+                        width: Val::Px(200.0),
+                        height: Val::Px(65.0),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    BackgroundColor(style.button_color),
+                    children![(
+                        Text::new("Back"),
+                        style.font(33.0),
+                        TextColor(style.text_color),
+                        Pickable::IGNORE
+                    )],
+                ))
+                .observe(change_state_on_click(
+                    PointerButton::Primary,
+                    PauseState::Paused,
+                ));
+        });
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn keybinds_list_every_control() {
+        let text = keybinds_text(&Controls::default());
+        let lines: Vec<&str> = text.lines().collect();
+
+        assert_eq!(lines.len(), 9);
+        assert!(lines[0].starts_with("Move Up: "));
+        assert!(lines[7].starts_with("Select: "));
+        assert!(lines[8].starts_with("Pan Camera: "));
+    }
+}
@@ -0,0 +1,157 @@
+//! Taking pillars by hand, when `manual_pillar_pickup` is on in the [`Config`].
+//!
+//! Beating a guardian leaves its pillar lying in the room, to be taken by clicking it.
+//! Each room the party walks into counts down the guardians of the pillars left behind,
+//! and one that runs out returns, so beaten guardians can't be left waiting forever.
+
+use super::*;
+use crate::room::{PROP_LAYER, RoomLayout, RoomTilemap};
+
+/// How many rooms the party can walk into before a beaten guardian returns to its pillar.
+pub const GUARDIAN_RESPAWN_ROOMS: u32 = 10;
+const PILLAR_POSITION: IVec2 = IVec2::new(-1, 2);
+const PILLAR_SIZE: Vec2 = Vec2::new(14.0, 30.0);
+const PILLAR_COLOR: Color = Color::srgb_u8(0xf2, 0x8c, 0x33);
+
+pub struct PillarPickupPlugin;
+
+impl Plugin for PillarPickupPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_observer(click_pillar)
+            .add_systems(
+                OnEnter(GameState::Navigation),
+                spawn_pillar.after(claim_pillar),
+            )
+            .add_systems(
+                OnEnter(GameState::EnterRoom),
+                count_down_guardians.before(spawn_room_entities),
+            );
+    }
+}
+
+/// The pillar lying in its room, until it is taken.
+#[derive(Component)]
+pub struct PillarPickup;
+
+/// Leaves the pillar out to be taken, in a room whose guardian was beaten.
+fn spawn_pillar(
+    mut commands: Commands,
+    info: Single<&RoomInfo, With<CurrentRoom>>,
+    pickups: Query<(), With<PillarPickup>>,
+    tilemap: Single<
+        (
+            &TilemapSize,
+            &TilemapGridSize,
+            &TilemapTileSize,
+            &TilemapType,
+            &TilemapAnchor,
+        ),
+        With<RoomTilemap>,
+    >,
+) {
+    let Some(rooms_left) = info.guardian_respawn else {
+        return;
+    };
+    if !pickups.is_empty() {
+        return;
+    }
+
+    let world_pos = RoomLayout::new(*tilemap).world_pos(PILLAR_POSITION);
+    commands.spawn((
+        InRoom,
+        PillarPickup,
+        Sprite::from_color(PILLAR_COLOR, PILLAR_SIZE),
+        Transform::from_xyz(world_pos.x, world_pos.y, PROP_LAYER),
+        Pickable::default(),
+        Tooltip::new("Pillar of OO").with_body(format!(
+            "Click to take it, its guardian returns in {rooms_left} rooms"
+        )),
+    ));
+}
+
+fn click_pillar(
+    mut click: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    pickups: Query<(), With<PillarPickup>>,
+    game_state: Option<Res<State<GameState>>>,
+) {
+    if !pickups.contains(click.target()) {
+        return;
+    }
+    click.propagate(false);
+
+    let exploring = game_state.is_some_and(|state| *state.get() == GameState::Navigation);
+    if click.button == PointerButton::Primary && exploring {
+        commands.run_system_cached_with(take_pillar, click.target());
+    }
+}
+
+/// Collects the pillar, so its guardian stays beaten.
+fn take_pillar(
+    In(entity): In<Entity>,
+    mut commands: Commands,
+    mut room: Single<&mut RoomInfo, With<CurrentRoom>>,
+    mut notifications: ResMut<Notifications>,
+) {
+    if room.guardian_respawn.take().is_none() {
+        return;
+    }
+
+    commands.entity(entity).despawn();
+    collect_pillar(&mut commands, &mut notifications);
+}
+
+/// Counts down the guardians of the pillars left lying in other rooms,
+/// bringing back the ones whose time is up.
+fn count_down_guardians(
+    mut rooms: Query<&mut RoomInfo, Without<CurrentRoom>>,
+    mut notifications: ResMut<Notifications>,
+) {
+    for mut info in rooms.iter_mut() {
+        let Some(rooms_left) = info.guardian_respawn else {
+            continue;
+        };
+
+        match rooms_left {
+            0 | 1 => {
+                info.guardian_respawn = None;
+                info.cleared = false;
+                notifications.warn("A guardian has returned to the pillar left behind");
+            }
+            _ => info.guardian_respawn = Some(rooms_left - 1),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn guardians_return_to_pillars_left_behind() {
+        let mut world = World::new();
+        world.init_resource::<Notifications>();
+        let mut left = RoomInfo::from_type(RoomType::Pillar, 1);
+        left.cleared = true;
+        left.guardian_respawn = Some(2);
+        let left = world.spawn(left).id();
+        let mut current = RoomInfo::from_type(RoomType::Pillar, 2);
+        current.cleared = true;
+        current.guardian_respawn = Some(1);
+        let current = world.spawn((current, CurrentRoom)).id();
+
+        world.run_system_cached(count_down_guardians).unwrap();
+        let info = world.get::<RoomInfo>(left).unwrap();
+        assert_eq!(info.guardian_respawn, Some(1));
+        assert!(info.cleared && info.has_pillar());
+
+        world.run_system_cached(count_down_guardians).unwrap();
+        let info = world.get::<RoomInfo>(left).unwrap();
+        assert_eq!(info.guardian_respawn, None);
+        assert!(!info.cleared && info.has_pillar());
+
+        // The room the party is in isn't counted down.
+        let info = world.get::<RoomInfo>(current).unwrap();
+        assert_eq!(info.guardian_respawn, Some(1));
+    }
+}
@@ -1,25 +1,43 @@
 use super::*;
+use crate::generate_map::MapShape;
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 
 pub use imp::*;
 
-#[cfg(feature = "sqlite")]
-mod imp {
-    use super::*;
+pub struct PouchPlugin;
 
-    pub struct PouchPlugin;
+impl Plugin for PouchPlugin {
+    fn build(&self, app: &mut App) {
+        #[cfg(not(feature = "sqlite"))]
+        app.init_resource::<PillarCount>();
 
-    impl Plugin for PouchPlugin {
-        fn build(&self, _app: &mut App) {}
+        app.add_systems(
+            OnEnter(GameState::Navigation),
+            spawn_exit_prompt
+                .after(claim_pillar)
+                .run_if(in_entrance.and(ready_to_leave).and(not(in_endless_mode))),
+        );
     }
+}
+
+#[cfg(feature = "sqlite")]
+mod imp {
+    use super::*;
 
     pub fn add_pillar(mut save_game: ResMut<SaveGame>) {
         save_game.pillar_count += 1;
     }
 
-    pub fn pillar_count(save_game: Res<SaveGame>, mut next_state: ResMut<NextState<GameState>>) {
-        if save_game.pillar_count == 4 {
-            next_state.set(GameState::Victory);
+    /// How many pillars the party has.
+    #[derive(SystemParam)]
+    pub struct Pillars<'w> {
+        save_game: Option<Res<'w, SaveGame>>,
+    }
+
+    impl Pillars<'_> {
+        pub fn count(&self) -> u64 {
+            self.save_game.as_ref().map_or(0, |save| save.pillar_count)
         }
     }
 }
@@ -28,24 +46,111 @@ mod imp {
 mod imp {
     use super::*;
 
-    pub struct PouchPlugin;
-
-    impl Plugin for PouchPlugin {
-        fn build(&self, app: &mut App) {
-            app.init_resource::<PillarCount>();
-        }
-    }
-
     #[derive(Resource, Deref, DerefMut, Default)]
-    pub struct PillarCount(pub usize);
+    pub struct PillarCount(pub u64);
 
     pub fn add_pillar(mut pillars: ResMut<PillarCount>) {
         **pillars += 1;
     }
 
-    pub fn pillar_count(pillars: Res<PillarCount>, mut next_state: ResMut<NextState<GameState>>) {
-        if **pillars == 4 {
-            next_state.set(GameState::Victory);
+    /// How many pillars the party has.
+    #[derive(SystemParam)]
+    pub struct Pillars<'w> {
+        pillars: Option<Res<'w, PillarCount>>,
+    }
+
+    impl Pillars<'_> {
+        pub fn count(&self) -> u64 {
+            self.pillars.as_ref().map_or(0, |pillars| ***pillars)
         }
     }
 }
+
+/// Asks the player whether to leave the dungeon and win,
+/// since they may want to keep exploring.
+#[derive(Component)]
+pub struct ExitPrompt;
+
+pub fn in_entrance(info: Query<&RoomInfo, With<CurrentRoom>>) -> bool {
+    info.single()
+        .is_ok_and(|info| info.r_type == RoomType::Entrance)
+}
+
+fn spawn_exit_prompt(mut commands: Commands, style: Res<Style>, shape: Option<Res<MapShape>>) {
+    let pillars = shape.map_or(MapShape::default(), |shape| *shape).pillars;
+    let button_node = Node {
+        width: Val::Px(200.0),
+        height: Val::Px(65.0),
+        margin: UiRect::all(Val::Px(15.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let text_style = (
+        style.font(33.0),
+        TextColor(style.text_color),
+        TextLayout::new_with_justify(JustifyText::Center),
+    );
+
+    commands
+        .spawn((
+            Node {
+                align_self: AlignSelf::Center,
+                justify_self: JustifySelf::Center,
+                padding: UiRect::all(Val::Px(20.0)),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(style.background_color),
+            StateScoped(GameState::Navigation),
+            ExitPrompt,
+        ))
+        .with_children(|builder| {
+            builder.spawn((
+                Text::new(format!(
+                    "You have all {pillars} Pillars of OO!\nLeave the dungeon?"
+                )),
+                text_style.clone(),
+            ));
+
+            builder.spawn(Node::default()).with_children(|builder| {
+                builder
+                    .spawn((
+                        Button,
+                        button_node.clone(),
+                        BackgroundColor(style.button_color),
+                        children![(Text::new("Leave"), text_style.clone(), Pickable::IGNORE)],
+                    ))
+                    .observe(change_state_on_click(
+                        PointerButton::Primary,
+                        GameState::Victory,
+                    ));
+
+                builder
+                    .spawn((
+                        Button,
+                        button_node.clone(),
+                        BackgroundColor(style.button_color),
+                        children![(Text::new("Stay"), text_style.clone(), Pickable::IGNORE)],
+                    ))
+                    .observe(close_exit_prompt);
+            });
+        });
+}
+
+fn close_exit_prompt(
+    mut click: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    prompt: Query<Entity, With<ExitPrompt>>,
+) {
+    click.propagate(false);
+
+    if click.button != PointerButton::Primary {
+        return;
+    }
+
+    for entity in prompt.iter() {
+        commands.entity(entity).despawn();
+    }
+}
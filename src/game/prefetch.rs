@@ -0,0 +1,134 @@
+//! Prepares the rooms behind each door while the player is choosing one,
+//! so entering the next room doesn't have to build it from scratch.
+
+use super::GameState;
+use crate::prelude::*;
+use crate::room::{CurrentRoom, PreparedRoom, RoomLayout};
+use bevy::prelude::*;
+use bevy::tasks::{AsyncComputeTaskPool, Task, block_on, futures_lite::future};
+use bevy_ecs_tilemap::helpers::hex_grid::neighbors::HexNeighbors;
+use bevy_ecs_tilemap::prelude::*;
+
+pub struct PrefetchPlugin;
+
+impl Plugin for PrefetchPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RoomPrefetch>()
+            .add_systems(
+                OnEnter(GameState::Navigation),
+                start_room_prefetch.run_if(prefetch_enabled),
+            )
+            .add_systems(
+                Update,
+                poll_room_prefetch.run_if(in_state(GameState::Navigation)),
+            )
+            .add_systems(OnExit(GameState::Navigation), keep_chosen_room_prefetch)
+            .add_systems(OnExit(AppState::Game), clear_room_prefetch);
+    }
+}
+
+/// The rooms being prepared for each door out of the current room.
+#[derive(Resource, Default)]
+pub struct RoomPrefetch {
+    pending: Vec<(Entity, Task<PreparedRoom>)>,
+    ready: Vec<(Entity, PreparedRoom)>,
+}
+
+impl RoomPrefetch {
+    /// Takes the prepared room, waiting for it if it is still being prepared.
+    pub fn take(&mut self, room: Entity) -> Option<PreparedRoom> {
+        if let Some(index) = self.pending.iter().position(|(e, _)| *e == room) {
+            let (_, task) = self.pending.swap_remove(index);
+            return Some(block_on(task));
+        }
+
+        let index = self.ready.iter().position(|(e, _)| *e == room)?;
+        Some(self.ready.swap_remove(index).1)
+    }
+
+    /// Drops every prefetch, cancelling the ones still being prepared.
+    pub fn clear(&mut self) {
+        self.pending.clear();
+        self.ready.clear();
+    }
+}
+
+fn prefetch_enabled(config: Res<Config>) -> bool {
+    config.prefetch_rooms
+}
+
+#[allow(clippy::type_complexity)]
+fn start_room_prefetch(
+    mut prefetch: ResMut<RoomPrefetch>,
+    current_room: Single<&TilePos, With<CurrentRoom>>,
+    map: Single<(&TilemapSize, &TileStorage), (With<MapTilemap>, Without<RoomTilemap>)>,
+    room_q: Query<&RoomInfo>,
+    asset_server: Res<AssetServer>,
+    difficulty: Res<Difficulty>,
+    tilemap: Single<
+        (
+            &TilemapSize,
+            &TilemapGridSize,
+            &TilemapTileSize,
+            &TilemapType,
+            &TilemapAnchor,
+        ),
+        With<RoomTilemap>,
+    >,
+) {
+    let (map_size, map_storage) = *map;
+    let layout = RoomLayout::new(*tilemap);
+    let difficulty = *difficulty;
+    let pool = AsyncComputeTaskPool::get();
+
+    prefetch.clear();
+
+    let neighbors =
+        HexNeighbors::<TilePos>::get_neighboring_positions_standard(&current_room, map_size);
+
+    for neighbor in neighbors.iter() {
+        let Some(room) = map_storage.checked_get(neighbor) else {
+            continue;
+        };
+        let Ok(info) = room_q.get(room) else {
+            continue;
+        };
+
+        let info = info.clone();
+        let asset_server = asset_server.clone();
+        let layout = layout.clone();
+        let task =
+            pool.spawn(async move { PreparedRoom::new(&info, &asset_server, &layout, difficulty) });
+
+        prefetch.pending.push((room, task));
+    }
+}
+
+fn poll_room_prefetch(mut prefetch: ResMut<RoomPrefetch>) {
+    let RoomPrefetch { pending, ready } = &mut *prefetch;
+
+    pending.retain_mut(|(room, task)| match block_on(future::poll_once(task)) {
+        Some(prepared) => {
+            ready.push((*room, prepared));
+            false
+        }
+        None => true,
+    });
+}
+
+/// Discards the rooms behind every door but the one the player went through.
+fn keep_chosen_room_prefetch(
+    mut prefetch: ResMut<RoomPrefetch>,
+    current_room: Single<Entity, With<CurrentRoom>>,
+) {
+    let chosen = prefetch.take(*current_room);
+    prefetch.clear();
+
+    if let Some(prepared) = chosen {
+        prefetch.ready.push((*current_room, prepared));
+    }
+}
+
+fn clear_room_prefetch(mut prefetch: ResMut<RoomPrefetch>) {
+    prefetch.clear();
+}
@@ -0,0 +1,356 @@
+//! Puzzle rooms, a game of lights out played on the middle of the room's floor.
+//!
+//! Pressing a tile flips it and the tiles around it. Lighting every tile wins an item,
+//! running out of presses starts the puzzle over, and after [`PUZZLE_FAILURES_TO_SKIP`]
+//! tries it can be skipped. The starting tiles are picked by the room's seed.
+
+use super::*;
+use crate::room::RoomTile;
+use bevy_ecs_tilemap::helpers::hex_grid::axial::AxialPos;
+
+/// How many times the puzzle has to be failed before it can be skipped.
+pub const PUZZLE_FAILURES_TO_SKIP: u32 = 3;
+/// Presses given on top of the ones it took to scramble the puzzle.
+const PUZZLE_SPARE_PRESSES: u32 = 2;
+/// Mixed into the room's seed, so the puzzle doesn't follow the room's events.
+const PUZZLE_RNG_STREAM: u64 = 0x9022_1e5e_ed11_6475;
+/// The middle tile and the six around it.
+const PUZZLE_TILES: usize = 7;
+
+const LIT_TILE: u32 = 2;
+const DARK_TILE: u32 = 5;
+const DARK_TILE_SHADE: f32 = 0.6;
+
+pub struct PuzzlePlugin;
+
+impl Plugin for PuzzlePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Puzzle), (start_puzzle, spawn_puzzle_hud))
+            .add_systems(
+                Update,
+                (update_puzzle_tiles, update_puzzle_hud)
+                    .run_if(in_state(GameState::Puzzle).and(resource_changed::<HexPuzzle>)),
+            )
+            .add_systems(
+                OnExit(GameState::Puzzle),
+                (
+                    reward_puzzle,
+                    remove_resource::<HexPuzzle>,
+                    remove_component::<PuzzleLight>,
+                )
+                    .chain(),
+            )
+            .add_observer(press_puzzle_tile);
+    }
+}
+
+/// A tile of the puzzle, by its index on the board.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct PuzzleLight(pub usize);
+
+#[derive(Component)]
+struct PuzzleHudText;
+
+#[derive(Component)]
+struct PuzzleSkipButton;
+
+/// The puzzle being played in the current room.
+///
+/// Tile 0 is the middle of the room, and tiles 1 to 6 go around it
+/// in the order of [`EntranceDirection::ALL`].
+#[derive(Resource, Debug, Clone, PartialEq, Eq)]
+pub struct HexPuzzle {
+    start: [bool; PUZZLE_TILES],
+    pub lit: [bool; PUZZLE_TILES],
+    pub presses_left: u32,
+    pub failures: u32,
+    item: Item,
+}
+
+impl HexPuzzle {
+    /// Scrambles a lit board with random presses, so it can always be solved.
+    /// Each try gets a couple more presses than the fewest it can be solved in.
+    pub fn from_seed(seed: u64) -> Self {
+        let mut rng = RandomSource::seed_from_u64(seed ^ PUZZLE_RNG_STREAM);
+        let item = Item::get_rand_item(&mut rng);
+
+        loop {
+            let mut lit = [true; PUZZLE_TILES];
+            for tile in 0..PUZZLE_TILES {
+                if rng.random_bool(0.5) {
+                    flip(&mut lit, tile);
+                }
+            }
+
+            if lit.contains(&false) {
+                let mut puzzle = Self {
+                    start: lit,
+                    lit,
+                    presses_left: 0,
+                    failures: 0,
+                    item,
+                };
+                puzzle.presses_left = puzzle.budget();
+                return puzzle;
+            }
+        }
+    }
+
+    /// Flips the tile and the ones around it, starting over once out of presses.
+    pub fn press(&mut self, tile: usize) {
+        flip(&mut self.lit, tile);
+        self.presses_left -= 1;
+
+        if self.presses_left == 0 && !self.is_solved() {
+            self.failures += 1;
+            self.presses_left = self.budget();
+            self.lit = self.start;
+        }
+    }
+
+    pub fn is_solved(&self) -> bool {
+        !self.lit.contains(&false)
+    }
+
+    pub fn can_skip(&self) -> bool {
+        self.failures >= PUZZLE_FAILURES_TO_SKIP
+    }
+
+    /// The fewest tiles that can be pressed to light every tile.
+    pub fn solution(&self) -> Vec<usize> {
+        (0..1u32 << PUZZLE_TILES)
+            .map(|presses| {
+                (0..PUZZLE_TILES)
+                    .filter(|tile| presses & (1 << tile) != 0)
+                    .collect::<Vec<usize>>()
+            })
+            .filter(|presses| {
+                let mut lit = self.lit;
+                for tile in presses {
+                    flip(&mut lit, *tile);
+                }
+                !lit.contains(&false)
+            })
+            .min_by_key(|presses| presses.len())
+            .unwrap_or_default()
+    }
+
+    /// The presses each try starts with.
+    fn budget(&self) -> u32 {
+        let mut puzzle = self.clone();
+        puzzle.lit = self.start;
+        puzzle.solution().len() as u32 + PUZZLE_SPARE_PRESSES
+    }
+}
+
+/// The tiles next to the tile on the board.
+fn puzzle_neighbors(tile: usize) -> Vec<usize> {
+    match tile {
+        0 => (1..PUZZLE_TILES).collect(),
+        _ => vec![0, tile % 6 + 1, (tile + 4) % 6 + 1],
+    }
+}
+
+fn flip(lit: &mut [bool; PUZZLE_TILES], tile: usize) {
+    lit[tile] = !lit[tile];
+    for neighbor in puzzle_neighbors(tile) {
+        lit[neighbor] = !lit[neighbor];
+    }
+}
+
+/// Where the tile is in the room.
+fn puzzle_tile_pos(tile: usize) -> TilePos {
+    let center = AxialPos::from_tile_pos_given_coord_system(&ROOM_CENTER, HEX_COORD_SYSTEM);
+    let pos = match tile {
+        0 => center,
+        _ => center + EntranceDirection::ALL[tile - 1].axial_offset(),
+    };
+    pos.as_tile_pos_given_coord_system(HEX_COORD_SYSTEM)
+}
+
+fn start_puzzle(
+    mut commands: Commands,
+    info: Single<&RoomInfo, With<CurrentRoom>>,
+    tiles: Query<(Entity, &TilePos), With<RoomTile>>,
+) {
+    commands.insert_resource(HexPuzzle::from_seed(info.rng_seed));
+
+    for (entity, pos) in tiles.iter() {
+        if let Some(tile) = (0..PUZZLE_TILES).find(|tile| puzzle_tile_pos(*tile) == *pos) {
+            commands.entity(entity).insert(PuzzleLight(tile));
+        }
+    }
+}
+
+fn update_puzzle_tiles(
+    puzzle: Res<HexPuzzle>,
+    mut tiles: Query<(&PuzzleLight, &mut TileTextureIndex, &mut TileColor)>,
+) {
+    for (PuzzleLight(tile), mut texture, mut color) in tiles.iter_mut() {
+        if puzzle.lit[*tile] {
+            *texture = TileTextureIndex(LIT_TILE);
+            *color = TileColor::default();
+        } else {
+            *texture = TileTextureIndex(DARK_TILE);
+            *color = TileColor(Color::srgb(
+                DARK_TILE_SHADE,
+                DARK_TILE_SHADE,
+                DARK_TILE_SHADE,
+            ));
+        }
+    }
+}
+
+fn press_puzzle_tile(
+    mut click: Trigger<Pointer<Click>>,
+    lights: Query<&PuzzleLight>,
+    puzzle: Option<ResMut<HexPuzzle>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut notifications: ResMut<Notifications>,
+) {
+    let (Ok(PuzzleLight(tile)), Some(mut puzzle)) = (lights.get(click.target()), puzzle) else {
+        return;
+    };
+    click.propagate(false);
+    if click.button != PointerButton::Primary || puzzle.is_solved() {
+        return;
+    }
+
+    let failures = puzzle.failures;
+    puzzle.press(*tile);
+
+    if puzzle.is_solved() {
+        notifications.success("Puzzle solved!");
+        next_state.set(GameState::Navigation);
+    } else if puzzle.failures > failures {
+        notifications.warn("Out of presses, the puzzle starts over");
+    }
+}
+
+fn spawn_puzzle_hud(mut commands: Commands, style: Res<Style>) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(20.0),
+                justify_self: JustifySelf::Center,
+                padding: UiRect::all(Val::Px(15.0)),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(style.background_color),
+            StateScoped(GameState::Puzzle),
+        ))
+        .with_children(|builder| {
+            builder.spawn((
+                Text::default(),
+                style.font(33.0),
+                TextColor(style.text_color),
+                TextLayout::new_with_justify(JustifyText::Center),
+                PuzzleHudText,
+            ));
+
+            builder
+                .spawn((
+                    Button,
+                    Node {
+                        width: Val::Px(200.0),
+                        height: Val::Px(50.0),
+                        margin: UiRect::top(Val::Px(10.0)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        display: Display::None,
+                        ..default()
+                    },
+                    BackgroundColor(style.button_color),
+                    PuzzleSkipButton,
+                    children![(
+                        Text::new("Skip"),
+                        style.font(33.0),
+                        TextColor(style.text_color),
+                        Pickable::IGNORE
+                    )],
+                ))
+                .observe(skip_puzzle);
+        });
+}
+
+fn update_puzzle_hud(
+    puzzle: Res<HexPuzzle>,
+    mut text: Single<&mut Text, With<PuzzleHudText>>,
+    mut skip: Single<&mut Node, With<PuzzleSkipButton>>,
+) {
+    text.0 = format!("Light every tile\n{} presses left", puzzle.presses_left);
+    skip.display = match puzzle.can_skip() {
+        true => Display::Flex,
+        false => Display::None,
+    };
+}
+
+fn skip_puzzle(
+    mut click: Trigger<Pointer<Click>>,
+    puzzle: Res<HexPuzzle>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    click.propagate(false);
+
+    if click.button == PointerButton::Primary && puzzle.can_skip() {
+        next_state.set(GameState::Navigation);
+    }
+}
+
+/// Hands out the puzzle's item once it is solved, skipping it gives nothing.
+pub fn reward_puzzle(
+    mut commands: Commands,
+    puzzle: Option<Res<HexPuzzle>>,
+    mut items: ResMut<Items>,
+    mut notifications: ResMut<Notifications>,
+) {
+    let Some(puzzle) = puzzle.filter(|puzzle| puzzle.is_solved()) else {
+        return;
+    };
+
+    if puzzle.item == Item::VisionPotion {
+        commands.run_system_cached(drink_vision_potion);
+    } else {
+        items.push(puzzle.item);
+        notifications.success(format!("Won a {}", puzzle.item));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn puzzles_can_be_solved_and_skipped() {
+        for seed in 0..50 {
+            let puzzle = HexPuzzle::from_seed(seed);
+            assert!(!puzzle.is_solved());
+            assert_eq!(puzzle, HexPuzzle::from_seed(seed));
+
+            let mut solved = puzzle.clone();
+            for tile in puzzle.solution() {
+                solved.press(tile);
+            }
+            assert!(solved.is_solved(), "seed {seed} couldn't be solved");
+            assert_eq!(solved.failures, 0);
+        }
+
+        let mut puzzle = HexPuzzle::from_seed(7);
+        // Pressing the same tile over and over never gets anywhere, unless it is the answer.
+        let tile = (0..PUZZLE_TILES)
+            .find(|tile| puzzle.solution() != [*tile])
+            .unwrap();
+        for _ in 0..PUZZLE_FAILURES_TO_SKIP {
+            assert!(!puzzle.can_skip());
+            let failures = puzzle.failures;
+            while puzzle.failures == failures {
+                puzzle.press(tile);
+            }
+            assert_eq!(puzzle.lit, puzzle.start);
+        }
+        assert!(puzzle.can_skip());
+    }
+}
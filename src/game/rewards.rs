@@ -0,0 +1,203 @@
+//! The summary shown after winning a fight, before going back to navigating.
+
+use super::*;
+use bevy::prelude::*;
+
+const REWARDS_Z_INDEX: i32 = 40;
+
+pub struct RewardsPlugin;
+
+impl Plugin for RewardsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Combat), init_resource::<RoomRewards>)
+            .add_systems(
+                OnEnter(GameState::Navigation),
+                (
+                    remove_resource::<RoomRewards>.run_if(resource_exists::<Fled>),
+                    spawn_rewards_summary.after(claim_pillar).run_if(fight_won),
+                ),
+            )
+            .add_systems(
+                Update,
+                dismiss_rewards_on_select
+                    .run_if(in_state(GameState::Navigation).and(in_state(PauseState::Running))),
+            )
+            .add_systems(OnExit(AppState::Game), remove_resource::<RoomRewards>);
+    }
+}
+
+/// What the party earned in the current fight.
+/// Add to this during combat, and it will be shown once the fight is won.
+#[derive(Resource, Default, Debug)]
+pub struct RoomRewards {
+    pub xp: u32,
+    pub gold: u32,
+    pub loot: Vec<Item>,
+}
+
+#[derive(Component)]
+pub struct RewardsSummary;
+
+/// Whether the fight that just ended was won, fleeing gives up its rewards.
+fn fight_won(rewards: Option<Res<RoomRewards>>, fled: Option<Res<Fled>>) -> bool {
+    rewards.is_some() && fled.is_none()
+}
+
+fn rewards_text(rewards: &RoomRewards, progress: Option<String>) -> String {
+    let mut lines = vec!["Room Cleared!".to_string()];
+
+    if rewards.xp > 0 {
+        lines.push(format!("+{} XP", rewards.xp));
+    }
+
+    if rewards.gold > 0 {
+        lines.push(format!("+{} Gold", rewards.gold));
+    }
+
+    if rewards.loot.is_empty() {
+        lines.push("No loot".to_string());
+    } else {
+        let loot: Vec<String> = rewards.loot.iter().map(Item::to_string).collect();
+        lines.push(format!("Loot: {}", loot.join(", ")));
+    }
+
+    lines.extend(progress);
+    lines.join("\n")
+}
+
+fn spawn_rewards_summary(
+    mut commands: Commands,
+    style: Res<Style>,
+    rewards: Res<RoomRewards>,
+    condition: Option<Res<ActiveWinCondition>>,
+    progress: Progress,
+) {
+    commands.remove_resource::<RoomRewards>();
+
+    let progress = condition.map(|condition| condition.progress_text(&progress.get()));
+
+    let text_style = (
+        style.font(33.0),
+        TextColor(style.text_color),
+        TextLayout::new_with_justify(JustifyText::Center),
+    );
+
+    // Covers the whole screen so the doors can't be clicked until this is dismissed.
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            GlobalZIndex(REWARDS_Z_INDEX),
+            StateScoped(GameState::Navigation),
+            RewardsSummary,
+        ))
+        .with_children(|builder| {
+            builder
+                .spawn((
+                    Node {
+                        padding: UiRect::all(Val::Px(20.0)),
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    BackgroundColor(style.background_color),
+                ))
+                .with_children(|builder| {
+                    builder.spawn((
+                        Text::new(rewards_text(&rewards, progress)),
+                        text_style.clone(),
+                    ));
+
+                    builder
+                        .spawn((
+                            Button,
+                            Node {
+                                width: Val::Px(200.0),
+                                height: Val::Px(65.0),
+                                margin: UiRect::top(Val::Px(20.0)),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            BackgroundColor(style.button_color),
+                            children![(Text::new("Continue"), text_style, Pickable::IGNORE)],
+                        ))
+                        .observe(dismiss_rewards_on_click);
+                });
+        });
+}
+
+fn dismiss_rewards_on_click(
+    mut click: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    summary: Query<Entity, With<RewardsSummary>>,
+) {
+    click.propagate(false);
+
+    if click.button == PointerButton::Primary {
+        for entity in summary.iter() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn dismiss_rewards_on_select(
+    mut commands: Commands,
+    key: Res<ControlState>,
+    summary: Query<Entity, With<RewardsSummary>>,
+) {
+    if key.just_pressed(Control::Select) {
+        for entity in summary.iter() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn summary_lists_rewards_and_pillars() {
+        let rewards = RoomRewards {
+            xp: 25,
+            gold: 12,
+            loot: vec![Item::HealingPotion, Item::VisionPotion],
+        };
+
+        let pillars = |pillars| {
+            Some(CollectPillars(4).progress_text(&RunProgress {
+                pillars,
+                ..default()
+            }))
+        };
+
+        assert_eq!(
+            rewards_text(&rewards, pillars(2)),
+            "Room Cleared!\n+25 XP\n+12 Gold\nLoot: Healing Potion, Vision Potion\nPillars: 2/4"
+        );
+        assert_eq!(
+            rewards_text(&RoomRewards::default(), pillars(0)),
+            "Room Cleared!\nNo loot\nPillars: 0/4"
+        );
+    }
+
+    #[test]
+    fn no_summary_after_fleeing() {
+        let mut world = World::new();
+        world.insert_resource(RoomRewards {
+            xp: 25,
+            ..default()
+        });
+        assert!(world.run_system_cached(fight_won).unwrap());
+
+        world.insert_resource(Fled);
+        assert!(!world.run_system_cached(fight_won).unwrap());
+    }
+}
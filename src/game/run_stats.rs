@@ -0,0 +1,91 @@
+//! Counts of how the run has gone, kept with the save and shown once it is over.
+
+use super::*;
+use crate::room::mark_room_cleared;
+
+pub struct RunStatsPlugin;
+
+impl Plugin for RunStatsPlugin {
+    fn build(&self, app: &mut App) {
+        // A loaded game brings its own stats, so this only starts new games from nothing.
+        app.add_systems(OnEnter(AppState::Game), init_resource::<RunStats>)
+            .add_systems(
+                OnEnter(GameState::Navigation),
+                count_room_explored
+                    .before(mark_room_cleared)
+                    .run_if(not(resource_exists::<Fled>)),
+            )
+            .add_systems(OnExit(AppState::Game), remove_resource::<RunStats>);
+    }
+}
+
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RunStats {
+    /// Rooms the party has made it through, not counting the entrance or stairs.
+    pub rooms_explored: u32,
+    pub enemies_killed: u32,
+    pub damage_dealt: u32,
+    pub damage_taken: u32,
+    pub pits_triggered: u32,
+    pub items_used: u32,
+    /// Turns taken by the party in fights.
+    pub turns_taken: u32,
+}
+
+impl RunStats {
+    /// Counts the health an actor on the team lost.
+    pub fn count_damage(&mut self, hurt: Team, amount: u32) {
+        match hurt {
+            Team::Enemy => self.damage_dealt += amount,
+            Team::Player => self.damage_taken += amount,
+        }
+    }
+
+    /// The stats as lines of text, for the end of game screens.
+    pub fn summary(&self) -> String {
+        format!(
+            "Rooms explored: {}\n\
+             Enemies killed: {}\n\
+             Damage dealt: {}\n\
+             Damage taken: {}\n\
+             Pits triggered: {}\n\
+             Items used: {}\n\
+             Turns taken: {}",
+            self.rooms_explored,
+            self.enemies_killed,
+            self.damage_dealt,
+            self.damage_taken,
+            self.pits_triggered,
+            self.items_used,
+            self.turns_taken,
+        )
+    }
+}
+
+/// The current health of an actor, with the dead at 0.
+pub fn health_points(health: &Health) -> u32 {
+    health.current().map_or(0, |health| health.get())
+}
+
+fn count_room_explored(room: Single<&RoomInfo, With<CurrentRoom>>, mut stats: ResMut<RunStats>) {
+    if !room.cleared && !matches!(room.r_type, RoomType::Entrance | RoomType::Stairs) {
+        stats.rooms_explored += 1;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn damage_is_counted_by_who_was_hurt() {
+        let mut stats = RunStats::default();
+        stats.count_damage(Team::Enemy, 12);
+        stats.count_damage(Team::Player, 5);
+        stats.count_damage(Team::Enemy, 3);
+
+        assert_eq!(stats.damage_dealt, 15);
+        assert_eq!(stats.damage_taken, 5);
+        assert!(stats.summary().contains("Damage dealt: 15"));
+    }
+}
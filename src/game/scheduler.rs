@@ -0,0 +1,322 @@
+//! Effects that land some turns after they are set up, like a Construct's charged blow.
+//!
+//! They wait in the [`EffectSchedule`] by who they are on, count down at the end of every
+//! actor's turn, and show a pip over their target for each turn left. Fights saved part way
+//! through keep them, see [`SuspendedCombat`].
+
+use super::*;
+use crate::damage_popup::DamagePopup;
+use crate::rules;
+use crate::update_player_hp_bar;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// How many turns a Construct's charged blow takes to land.
+pub const CHARGE_TURNS: u32 = 3;
+/// Where countdown pips sit, above the actor's center.
+const PIP_HEIGHT: f32 = 42.0;
+const PIP_SIZE: f32 = 7.0;
+const PIP_SPACING: f32 = 11.0;
+const PIP_LAYER: f32 = 9.0;
+const PIP_COLOR: Color = Color::srgb(1.0, 0.6, 0.15);
+
+pub struct SchedulerPlugin;
+
+impl Plugin for SchedulerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Combat), init_resource::<EffectSchedule>)
+            .add_systems(
+                Update,
+                sync_countdown_pips.run_if(resource_exists_and_changed::<EffectSchedule>),
+            )
+            .add_systems(
+                OnExit(GameState::Combat),
+                (
+                    remove_resource::<EffectSchedule>,
+                    despawn_filtered::<With<CountdownPip>>,
+                ),
+            );
+    }
+}
+
+/// What happens once a scheduled effect's turns run out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DelayedEffect {
+    /// A blow wound up ahead of time, for this much damage. It can still be blocked.
+    Strike(u32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScheduledEffect {
+    /// Who set it up. It comes to nothing if they fall before it lands.
+    pub source: Combatant,
+    pub target: Combatant,
+    pub effect: DelayedEffect,
+    /// Turns left until it lands.
+    pub turns: u32,
+    /// Set up during the turn that is still going, which doesn't count towards `turns`.
+    #[serde(default)]
+    pub fresh: bool,
+}
+
+/// The effects waiting to land in the current fight, in the order they were set up.
+#[derive(Resource, Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EffectSchedule(pub Vec<ScheduledEffect>);
+
+impl EffectSchedule {
+    pub fn schedule(&mut self, effect: ScheduledEffect) {
+        self.0.push(ScheduledEffect {
+            fresh: true,
+            ..effect
+        });
+    }
+
+    /// Counts every effect down a turn, taking out the ones that land now.
+    /// Effects set up this turn only start counting from the next one.
+    pub fn tick(&mut self) -> Vec<ScheduledEffect> {
+        for scheduled in self.0.iter_mut() {
+            match scheduled.fresh {
+                true => scheduled.fresh = false,
+                false => scheduled.turns = scheduled.turns.saturating_sub(1),
+            }
+        }
+        let (landed, waiting) = self.0.drain(..).partition(|scheduled| scheduled.turns == 0);
+        self.0 = waiting;
+        landed
+    }
+
+    /// The turns until the next effect on `target` lands, if any are coming.
+    pub fn soonest(&self, target: Combatant) -> Option<u32> {
+        self.0
+            .iter()
+            .filter(|scheduled| scheduled.target == target)
+            .map(|scheduled| scheduled.turns)
+            .min()
+    }
+}
+
+/// One of the pips counting down the turns until an effect lands on an actor.
+#[derive(Component)]
+struct CountdownPip;
+
+/// The Construct's special move, winding up a blow on `target` that lands a few turns later.
+pub fn charge_up(
+    In((actor, target)): In<(Entity, Entity)>,
+    mut rng: ResMut<EventRng>,
+    mut schedule: ResMut<EffectSchedule>,
+    attacks: Query<&Attack>,
+    slots: Query<(Option<&PartySlot>, Option<&EnemySlot>)>,
+    names: Query<(&ActorName, Option<&HeroName>)>,
+    mut log: EventWriter<CombatLogEvent>,
+) {
+    let find = |entity| {
+        slots
+            .get(entity)
+            .ok()
+            .and_then(|(party, enemy)| Combatant::find(party, enemy))
+    };
+    let (Ok(attack), Some(source), Some(on)) = (attacks.get(actor), find(actor), find(target))
+    else {
+        return;
+    };
+
+    let damage = rules::scale_damage(
+        rng.random_range(attack.damage().clone()),
+        rules::CHARGE_MULTIPLIER,
+    );
+    schedule.schedule(ScheduledEffect {
+        source,
+        target: on,
+        effect: DelayedEffect::Strike(damage),
+        turns: CHARGE_TURNS,
+        fresh: true,
+    });
+    log.write(CombatLogEvent(format!(
+        "{} is winding up a blow on {}",
+        log_name(&names, actor),
+        log_name(&names, target)
+    )));
+}
+
+/// Counts the schedule down a turn and lands whatever is due.
+#[allow(clippy::type_complexity)]
+pub(super) fn land_scheduled_effects(
+    mut commands: Commands,
+    mut schedule: ResMut<EffectSchedule>,
+    mut rng: ResMut<EventRng>,
+    mut actors: Query<
+        (
+            Entity,
+            Option<&PartySlot>,
+            Option<&EnemySlot>,
+            &mut Health,
+            &BlockChance,
+            &StatusEffects,
+            Has<Defending>,
+            &Team,
+        ),
+        With<Actor>,
+    >,
+    names: Query<(&ActorName, Option<&HeroName>)>,
+    mut stats: ResMut<RunStats>,
+    mut log: EventWriter<CombatLogEvent>,
+) {
+    let landed = schedule.tick();
+    if landed.is_empty() {
+        return;
+    }
+
+    let combatants: Vec<(Combatant, Entity)> = actors
+        .iter()
+        .filter_map(|(entity, party, enemy, ..)| Some((Combatant::find(party, enemy)?, entity)))
+        .collect();
+    let entity_of = |who| {
+        combatants
+            .iter()
+            .find(|(combatant, _)| *combatant == who)
+            .map(|(_, entity)| *entity)
+    };
+
+    for scheduled in landed {
+        let (Some(source), Some(target)) =
+            (entity_of(scheduled.source), entity_of(scheduled.target))
+        else {
+            continue;
+        };
+        if !actors
+            .get(source)
+            .is_ok_and(|(_, _, _, health, ..)| health.is_alive())
+        {
+            log.write(CombatLogEvent(format!(
+                "{}'s charged blow came to nothing",
+                log_name(&names, source)
+            )));
+            continue;
+        }
+        let Ok((_, _, _, mut health, block_chance, effects, defending, team)) =
+            actors.get_mut(target)
+        else {
+            continue;
+        };
+        if !health.is_alive() {
+            continue;
+        }
+
+        match scheduled.effect {
+            DelayedEffect::Strike(damage) => {
+                if rules::roll_block(&mut *rng, block_chance.guard(defending)) {
+                    commands.trigger_targets(DamagePopup::Blocked, target);
+                    log.write(CombatLogEvent(format!(
+                        "{} blocked {}'s charged blow",
+                        log_name(&names, target),
+                        log_name(&names, source)
+                    )));
+                    continue;
+                }
+
+                let health_before = health_points(&health);
+                let dealt = effects.shielded(damage);
+                health.damage(dealt);
+                stats.count_damage(*team, health_before - health_points(&health));
+                log.write(CombatLogEvent(format!(
+                    "{}'s charged blow hit {} for {dealt}",
+                    log_name(&names, source),
+                    log_name(&names, target)
+                )));
+
+                if !health.is_alive() {
+                    log.write(CombatLogEvent(format!("{} fell", log_name(&names, target))));
+                    if *team == Team::Enemy {
+                        stats.enemies_killed += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    commands.run_system_cached(update_player_hp_bar);
+}
+
+/// Puts a pip over each actor for every turn until the next effect on them lands.
+#[allow(clippy::type_complexity)]
+fn sync_countdown_pips(
+    mut commands: Commands,
+    schedule: Res<EffectSchedule>,
+    pips: Query<Entity, With<CountdownPip>>,
+    actors: Query<(Entity, Option<&PartySlot>, Option<&EnemySlot>), With<Actor>>,
+) {
+    for pip in pips.iter() {
+        commands.entity(pip).despawn();
+    }
+
+    for (entity, party, enemy) in actors.iter() {
+        let Some(turns) = Combatant::find(party, enemy).and_then(|who| schedule.soonest(who))
+        else {
+            continue;
+        };
+
+        commands.entity(entity).with_children(|parent| {
+            for pip in 0..turns {
+                let x = (pip as f32 - (turns - 1) as f32 / 2.0) * PIP_SPACING;
+                parent.spawn((
+                    CountdownPip,
+                    Sprite::from_color(PIP_COLOR, Vec2::splat(PIP_SIZE)),
+                    Transform::from_xyz(x, PIP_HEIGHT, PIP_LAYER),
+                ));
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn effects_land_once_their_turns_run_out() {
+        let strike = |target, turns| ScheduledEffect {
+            source: Combatant::Enemy(0),
+            target,
+            effect: DelayedEffect::Strike(40),
+            turns,
+            fresh: false,
+        };
+        let mut schedule = EffectSchedule::default();
+        schedule.schedule(strike(Combatant::Hero(0), 2));
+        schedule.schedule(strike(Combatant::Hero(1), 1));
+        schedule.schedule(strike(Combatant::Hero(0), 3));
+        assert_eq!(schedule.soonest(Combatant::Hero(0)), Some(2));
+        assert!(schedule.tick().is_empty());
+
+        assert_eq!(schedule.tick(), vec![strike(Combatant::Hero(1), 0)]);
+        assert_eq!(schedule.soonest(Combatant::Hero(1)), None);
+        assert_eq!(schedule.tick(), vec![strike(Combatant::Hero(0), 0)]);
+        assert_eq!(schedule.soonest(Combatant::Hero(0)), Some(1));
+        assert_eq!(schedule.tick().len(), 1);
+        assert!(schedule.tick().is_empty());
+        assert_eq!(schedule, EffectSchedule::default());
+    }
+
+    #[test]
+    fn charged_blows_land_after_three_more_turns() {
+        let mut schedule = EffectSchedule::default();
+        schedule.schedule(ScheduledEffect {
+            source: Combatant::Enemy(0),
+            target: Combatant::Hero(0),
+            effect: DelayedEffect::Strike(40),
+            turns: CHARGE_TURNS,
+            fresh: true,
+        });
+
+        // The end of the Construct's own turn, then one for every actor after it.
+        let mut turns = 0;
+        while schedule.tick().is_empty() {
+            assert_eq!(
+                schedule.soonest(Combatant::Hero(0)),
+                Some(CHARGE_TURNS - turns)
+            );
+            turns += 1;
+        }
+        assert_eq!(turns, CHARGE_TURNS);
+    }
+}
@@ -0,0 +1,232 @@
+//! Shrine rooms, where the party can make an offering for a chance at a reward.
+//!
+//! Offering health takes some of every hero's max health for a chance at the blessing of a level,
+//! and offering gold a chance at a handful of items. Either way the offering is kept,
+//! and the shrine only takes one before it goes quiet.
+//!
+//! Walking in also brings back the first fallen hero, whatever is offered after.
+
+use super::*;
+
+/// The max health each hero offers.
+pub const SHRINE_HEALTH_WAGER: u32 = 10;
+/// The gold offered.
+pub const SHRINE_GOLD_WAGER: u64 = 50;
+/// The chance of the shrine rewarding an offering.
+pub const SHRINE_WIN_CHANCE: f64 = 0.5;
+/// How many items an accepted gold offering is rewarded with.
+const SHRINE_ITEM_REWARD: usize = 2;
+
+pub struct ShrinePlugin;
+
+impl Plugin for ShrinePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(GameState::Shrine),
+            (shrine_revive, spawn_shrine_prompt),
+        );
+    }
+}
+
+/// What the party can offer at a shrine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Wager {
+    Health,
+    Gold,
+}
+
+impl Wager {
+    /// Whether the shrine rewards the offering.
+    pub fn roll(rng: &mut impl Rng) -> bool {
+        rng.random_bool(SHRINE_WIN_CHANCE)
+    }
+}
+
+/// Revives the fallen hero in the lowest party slot, if anyone has fallen.
+fn shrine_revive(
+    mut commands: Commands,
+    mut party: Query<(&PartySlot, &mut Health, &ActorName, Option<&HeroName>)>,
+    mut notifications: ResMut<Notifications>,
+) {
+    let Some((_, mut health, name, hero_name)) = party
+        .iter_mut()
+        .filter(|(_, health, ..)| !health.is_alive())
+        .min_by_key(|(slot, ..)| **slot)
+    else {
+        return;
+    };
+
+    revive_hero(&mut health);
+    let name = hero_name.map_or_else(|| name.to_string(), |hero| hero.0.clone());
+    notifications.success(format!("The shrine's light brings {name} back"));
+    commands.run_system_cached(update_player_hp_bar_pit);
+}
+
+fn spawn_shrine_prompt(mut commands: Commands, style: Res<Style>, gold: Option<Res<Gold>>) {
+    let button_node = Node {
+        width: Val::Px(260.0),
+        height: Val::Px(65.0),
+        margin: UiRect::all(Val::Px(10.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let text_style = (
+        style.font(33.0),
+        TextColor(style.text_color),
+        TextLayout::new_with_justify(JustifyText::Center),
+    );
+    let can_offer_gold = gold.is_some_and(|gold| gold.0 >= SHRINE_GOLD_WAGER);
+
+    commands
+        .spawn((
+            Node {
+                align_self: AlignSelf::Center,
+                justify_self: JustifySelf::Center,
+                padding: UiRect::all(Val::Px(20.0)),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(style.background_color),
+            StateScoped(GameState::Shrine),
+        ))
+        .with_children(|builder| {
+            builder.spawn((
+                Text::new("The shrine asks for an offering.\nIt may answer with a gift."),
+                text_style.clone(),
+            ));
+
+            builder
+                .spawn((
+                    Button,
+                    button_node.clone(),
+                    BackgroundColor(style.button_color),
+                    Tooltip::new("Offer Health").with_body(format!(
+                        "Every hero gives {SHRINE_HEALTH_WAGER} max health, for a chance at a level"
+                    )),
+                    children![(
+                        Text::new("Offer Health"),
+                        text_style.clone(),
+                        Pickable::IGNORE
+                    )],
+                ))
+                .observe(offer_on_click(Wager::Health));
+
+            let gold_color = match can_offer_gold {
+                true => style.button_color,
+                false => style.button_color.with_alpha(0.4),
+            };
+            builder
+                .spawn((
+                    Button,
+                    button_node.clone(),
+                    BackgroundColor(gold_color),
+                    Tooltip::new("Offer Gold").with_body(format!(
+                        "Give {SHRINE_GOLD_WAGER} gold, for a chance at {SHRINE_ITEM_REWARD} items"
+                    )),
+                    children![(
+                        Text::new("Offer Gold"),
+                        text_style.clone(),
+                        Pickable::IGNORE
+                    )],
+                ))
+                .observe(offer_on_click(Wager::Gold));
+
+            builder
+                .spawn((
+                    Button,
+                    button_node.clone(),
+                    BackgroundColor(style.button_color),
+                    children![(Text::new("Leave"), text_style.clone(), Pickable::IGNORE)],
+                ))
+                .observe(change_state_on_click(
+                    PointerButton::Primary,
+                    GameState::Navigation,
+                ));
+        });
+}
+
+fn offer_on_click(wager: Wager) -> impl Fn(Trigger<Pointer<Click>>, Commands) {
+    move |mut click, mut commands| {
+        click.propagate(false);
+
+        if click.button == PointerButton::Primary {
+            commands.run_system_cached_with(offer_wager, wager);
+        }
+    }
+}
+
+/// Takes the offering and rolls for the reward, on the room's [`EventRng`].
+#[allow(clippy::too_many_arguments)]
+pub fn offer_wager(
+    In(wager): In<Wager>,
+    mut commands: Commands,
+    mut party: Query<(&mut Health, &mut Attack, &mut AttackSpeed), With<PartySlot>>,
+    mut gold: ResMut<Gold>,
+    mut items: ResMut<Items>,
+    mut rng: ResMut<EventRng>,
+    mut notifications: ResMut<Notifications>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    match wager {
+        Wager::Health => {
+            let blessed = Wager::roll(&mut *rng);
+            for (mut health, mut attack, mut speed) in party.iter_mut() {
+                if !health.is_alive() {
+                    continue;
+                }
+                health.lower_max(SHRINE_HEALTH_WAGER);
+                if blessed {
+                    level_up_stats(&mut health, &mut attack, &mut speed);
+                }
+            }
+            commands.run_system_cached(update_player_hp_bar_pit);
+
+            match blessed {
+                true => notifications.success("The shrine blesses the party with strength"),
+                false => notifications.warn("The shrine takes the offering in silence"),
+            }
+        }
+        Wager::Gold => {
+            if gold.0 < SHRINE_GOLD_WAGER {
+                notifications.warn(format!("The shrine wants {SHRINE_GOLD_WAGER} gold"));
+                return;
+            }
+            gold.0 -= SHRINE_GOLD_WAGER;
+
+            if Wager::roll(&mut *rng) {
+                let gifts: Vec<Item> = (0..SHRINE_ITEM_REWARD)
+                    .map(|_| Item::get_rand_item(&mut *rng))
+                    .collect();
+                let names: Vec<String> = gifts.iter().map(Item::to_string).collect();
+                items.extend(gifts);
+                notifications.success(format!("The shrine gives {}", names.join(" and ")));
+            } else {
+                notifications.warn("The shrine takes the offering in silence");
+            }
+        }
+    }
+
+    next_state.set(GameState::Navigation);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::num::NonZero;
+
+    #[test]
+    fn offerings_are_rewarded_about_half_the_time() {
+        let mut rng = RandomSource::seed_from_u64(0x0054_717e);
+        let wins = (0..1000).filter(|_| Wager::roll(&mut rng)).count();
+        assert!((400..600).contains(&wins), "{wins} offerings rewarded");
+
+        let mut health = Health::new(NonZero::new(15).unwrap());
+        health.lower_max(SHRINE_HEALTH_WAGER);
+        assert_eq!(health.max().get(), 5);
+        assert_eq!(health.current().unwrap().get(), 5);
+        health.lower_max(SHRINE_HEALTH_WAGER);
+        assert_eq!(health.max().get(), 1);
+    }
+}
@@ -0,0 +1,107 @@
+//! Skirmishes, a single fight picked from the main menu without generating a world.
+//!
+//! The fight takes place in a made up map of one room, and nothing about it is saved.
+//! Winning it shows the victory screen, losing or fleeing it the game over screen.
+
+use super::*;
+use crate::generate_map::{
+    GenerationSettings, MAP_TILE_ASSET_LOAD_PATH, MAP_TILE_SIZE, MapTile, WORLD_MAP_ORIGIN,
+};
+use crate::menu::party_select::{PartySelection, spawn_party};
+
+/// The enemies a skirmish can be set up with.
+pub const SKIRMISH_ENEMIES: [ActorName; 9] = [
+    ActorName::Goblin,
+    ActorName::Skeleton,
+    ActorName::Ogre,
+    ActorName::Construct,
+    ActorName::Archer,
+    ActorName::Shaman,
+    ActorName::GoblinWarlord,
+    ActorName::BoneKnight,
+    ActorName::OgreTyrant,
+];
+
+const SKIRMISH_MAP_SIZE: TilemapSize = TilemapSize { x: 1, y: 1 };
+
+pub struct SkirmishPlugin;
+
+impl Plugin for SkirmishPlugin {
+    fn build(&self, app: &mut App) {
+        // Unlike a world, there is nothing to load the party back from,
+        // so everything from the fight goes for the next skirmish to start fresh.
+        app.add_systems(
+            OnExit(AppState::Game),
+            (
+                despawn_filtered::<With<Actor>>,
+                despawn_filtered::<With<RoomTilemap>>,
+                remove_resource::<Skirmish>,
+            )
+                .run_if(resource_exists::<Skirmish>),
+        );
+    }
+}
+
+/// Only present while playing a skirmish.
+#[derive(Resource, Debug, Default)]
+pub struct Skirmish;
+
+/// Sets up a skirmish against the enemies, for [`AppState::Game`] to start.
+pub fn start_skirmish(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    party: &PartySelection,
+    enemies: Box<[ActorName]>,
+    seed: u64,
+    difficulty: Difficulty,
+) {
+    // A save left from an earlier game would otherwise be saved over.
+    #[cfg(feature = "sqlite")]
+    commands.remove_resource::<SaveGame>();
+    commands.remove_resource::<GenerationSettings>();
+    commands.remove_resource::<PillarCorruption>();
+    commands.insert_resource(Skirmish);
+    commands.insert_resource(difficulty);
+    commands.insert_resource(GameMode::default());
+    commands.insert_resource(Challenges::default());
+    commands.insert_resource(Items::default());
+    commands.insert_resource(Gold::default());
+
+    let tilemap_entity = commands.spawn_empty().id();
+    let mut tile_storage = TileStorage::empty(SKIRMISH_MAP_SIZE);
+    let position = TilePos::new(0, 0);
+
+    let room = commands
+        .spawn((
+            RoomInfo::from_type(RoomType::Combat(enemies), seed),
+            CurrentRoom,
+            TileBundle {
+                position,
+                tilemap_id: TilemapId(tilemap_entity),
+                texture_index: TileTextureIndex(FLOOR_TILE_VARIENTS.start),
+                ..default()
+            },
+            MapTile,
+        ))
+        .id();
+    commands.entity(tilemap_entity).add_child(room);
+    tile_storage.set(&position, room);
+
+    commands.entity(tilemap_entity).insert((
+        MapTilemap,
+        TilemapBundle {
+            grid_size: MAP_TILE_SIZE.into(),
+            map_type: TilemapType::Hexagon(HexCoordSystem::Column),
+            size: SKIRMISH_MAP_SIZE,
+            storage: tile_storage,
+            texture: TilemapTexture::Single(asset_server.load(MAP_TILE_ASSET_LOAD_PATH)),
+            tile_size: MAP_TILE_SIZE,
+            anchor: TilemapAnchor::Center,
+            transform: Transform::from_translation(WORLD_MAP_ORIGIN),
+            ..default()
+        },
+        StateScoped(AppState::Game),
+    ));
+
+    spawn_party(commands, asset_server, party);
+}
@@ -0,0 +1,226 @@
+//! How much of the enemies' turns is shown, for players who would rather not sit through them.
+//!
+//! [`EnemyTurnDetail::Full`] plays every enemy turn like the party's.
+//! Fast skips walking to the center and the attack clip, pausing on the result instead,
+//! and Instant goes through the enemies' turns back to back and sums them up after.
+
+use super::*;
+use crate::settings::SETTINGS_DB_TABLE;
+use serde::{Deserialize, Serialize};
+use std::num::NonZero;
+use strum::Display;
+
+/// How long a fast enemy turn stays on its result.
+const FAST_RESULT_SECONDS: f32 = 0.6;
+
+pub struct SpectatePlugin;
+
+impl Plugin for SpectatePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PreStartup, setup_enemy_turn_detail)
+            .add_systems(
+                Update,
+                enemy_turn_detail_sync.run_if(
+                    resource_changed::<EnemyTurnDetail>.and(not(resource_added::<EnemyTurnDetail>)),
+                ),
+            )
+            .add_systems(
+                OnEnter(CombatState::TurnSetup),
+                summarize_instant_turns.before(prep_turn_order),
+            )
+            .add_systems(OnEnter(CombatState::MoveBack), pause_on_result)
+            .add_systems(
+                Update,
+                wait_on_result.run_if(resource_exists::<ResultPause>),
+            )
+            .add_systems(
+                OnExit(GameState::Combat),
+                (
+                    remove_resource::<ResultPause>,
+                    remove_resource::<InstantTurns>,
+                ),
+            );
+    }
+}
+
+/// How much of each enemy turn is shown, set in the display settings.
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Display)]
+pub enum EnemyTurnDetail {
+    #[default]
+    Full,
+    Fast,
+    Instant,
+}
+
+impl EnemyTurnDetail {
+    const DB_KEY: &str = "enemy_turn_detail";
+
+    /// The setting after this one, for cycling through them with a button.
+    pub fn next(self) -> Self {
+        match self {
+            EnemyTurnDetail::Full => EnemyTurnDetail::Fast,
+            EnemyTurnDetail::Fast => EnemyTurnDetail::Instant,
+            EnemyTurnDetail::Instant => EnemyTurnDetail::Full,
+        }
+    }
+
+    /// Whether the turn is played out with walking and attack clips.
+    pub fn animates(self, team: Team) -> bool {
+        team == Team::Player || self == EnemyTurnDetail::Full
+    }
+
+    /// Where an actor of the team starts their turn once it is set up.
+    pub fn turn_start(self, team: Team) -> CombatState {
+        match self.animates(team) {
+            true => CombatState::MoveToCenter,
+            false => CombatState::CheckTeam,
+        }
+    }
+}
+
+/// Holds a fast enemy turn on its result for a moment, so it can be read.
+#[derive(Resource, Deref, DerefMut)]
+pub struct ResultPause(Timer);
+
+/// Enemy turns gone through instantly since the party's last turn.
+#[derive(Resource, Debug, Default, Clone, PartialEq)]
+struct InstantTurns {
+    turns: u32,
+    /// The party's health before the first of them.
+    party_health: u32,
+}
+
+impl InstantTurns {
+    fn summary(&self, party_health: u32) -> String {
+        let turns = match self.turns {
+            1 => "1 enemy turn".to_string(),
+            turns => format!("{turns} enemy turns"),
+        };
+        match self.party_health.saturating_sub(party_health) {
+            0 => format!("{turns}: the party took no damage"),
+            damage => format!("{turns}: the party took {damage} damage"),
+        }
+    }
+}
+
+fn setup_enemy_turn_detail(mut commands: Commands, database: NonSend<Database>) {
+    let detail = database.get_kv(
+        SETTINGS_DB_TABLE,
+        EnemyTurnDetail::DB_KEY,
+        EnemyTurnDetail::default(),
+    );
+    commands.insert_resource(detail);
+}
+
+fn enemy_turn_detail_sync(database: NonSend<Database>, detail: Res<EnemyTurnDetail>) -> Result {
+    database
+        .set_kv(SETTINGS_DB_TABLE, EnemyTurnDetail::DB_KEY, *detail)
+        .map_err(GameError::from)?;
+    Ok(())
+}
+
+/// Counts the enemy turns gone through instantly, and sums them up once the party's turn comes.
+#[allow(clippy::too_many_arguments)]
+fn summarize_instant_turns(
+    mut commands: Commands,
+    detail: Res<EnemyTurnDetail>,
+    queue: Res<TurnOrder>,
+    instant: Option<ResMut<InstantTurns>>,
+    teams: Query<&Team>,
+    party: Query<&Health, With<PartySlot>>,
+    mut notifications: ResMut<Notifications>,
+    mut log: EventWriter<CombatLogEvent>,
+) {
+    let party_health = party
+        .iter()
+        .map(|health| health.current().map_or(0, NonZero::get))
+        .sum();
+    let enemy_turn = teams
+        .get(queue.active())
+        .is_ok_and(|team| *team == Team::Enemy);
+
+    match instant {
+        Some(mut instant) if enemy_turn && *detail == EnemyTurnDetail::Instant => {
+            instant.turns += 1;
+        }
+        None if enemy_turn && *detail == EnemyTurnDetail::Instant => {
+            commands.insert_resource(InstantTurns {
+                turns: 1,
+                party_health,
+            });
+        }
+        Some(instant) => {
+            let summary = instant.summary(party_health);
+            log.write(CombatLogEvent(summary.clone()));
+            notifications.info(summary);
+            commands.remove_resource::<InstantTurns>();
+        }
+        None => {}
+    }
+}
+
+fn pause_on_result(
+    mut commands: Commands,
+    detail: Res<EnemyTurnDetail>,
+    acting: Single<&Team, With<ActingActor>>,
+) {
+    if *detail == EnemyTurnDetail::Fast && !detail.animates(**acting) {
+        commands.insert_resource(ResultPause(Timer::from_seconds(
+            FAST_RESULT_SECONDS,
+            TimerMode::Once,
+        )));
+    }
+}
+
+fn wait_on_result(mut commands: Commands, time: Res<Time>, mut pause: ResMut<ResultPause>) {
+    if pause.tick(time.delta()).finished() {
+        commands.remove_resource::<ResultPause>();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn only_enemy_turns_are_cut_short() {
+        for detail in [
+            EnemyTurnDetail::Full,
+            EnemyTurnDetail::Fast,
+            EnemyTurnDetail::Instant,
+        ] {
+            assert_eq!(detail.turn_start(Team::Player), CombatState::MoveToCenter);
+        }
+
+        assert_eq!(
+            EnemyTurnDetail::Full.turn_start(Team::Enemy),
+            CombatState::MoveToCenter
+        );
+        assert_eq!(
+            EnemyTurnDetail::Fast.turn_start(Team::Enemy),
+            CombatState::CheckTeam
+        );
+        assert!(!EnemyTurnDetail::Instant.animates(Team::Enemy));
+    }
+
+    #[test]
+    fn instant_turns_are_summed_up() {
+        let instant = InstantTurns {
+            turns: 3,
+            party_health: 90,
+        };
+
+        assert_eq!(
+            instant.summary(72),
+            "3 enemy turns: the party took 18 damage"
+        );
+        assert_eq!(
+            InstantTurns {
+                turns: 1,
+                party_health: 40
+            }
+            .summary(40),
+            "1 enemy turn: the party took no damage"
+        );
+    }
+}
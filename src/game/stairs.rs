@@ -0,0 +1,147 @@
+//! Stairs rooms, which take the party down to the next floor.
+//!
+//! Each floor is a new map generated from the world's seed and the floor,
+//! with harder encounters the deeper it is. The floors left behind stay saved,
+//! but the party can't go back up to them, so descending needs the `sqlite` feature.
+
+use super::*;
+#[cfg(feature = "sqlite")]
+use crate::generate_map::{MapLayout, MapTilemap};
+#[cfg(feature = "sqlite")]
+use crate::spawn_map::spawn_map_rooms;
+
+pub struct StairsPlugin;
+
+impl Plugin for StairsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Stairs), spawn_stairs_prompt);
+    }
+}
+
+fn spawn_stairs_prompt(
+    mut commands: Commands,
+    style: Res<Style>,
+    #[cfg(feature = "sqlite")] save: Option<Res<SaveGame>>,
+) {
+    let button_node = Node {
+        width: Val::Px(260.0),
+        height: Val::Px(65.0),
+        margin: UiRect::all(Val::Px(10.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let text_style = (
+        style.font(33.0),
+        TextColor(style.text_color),
+        TextLayout::new_with_justify(JustifyText::Center),
+    );
+    #[cfg(feature = "sqlite")]
+    let prompt = format!(
+        "The stairs lead down to floor {}.\nThere is no coming back up.",
+        save.map_or(0, |save| save.floor) + 2
+    );
+    #[cfg(not(feature = "sqlite"))]
+    let prompt =
+        "The stairs lead further down,\nbut only a saved game can follow them.".to_string();
+
+    commands
+        .spawn((
+            Node {
+                align_self: AlignSelf::Center,
+                justify_self: JustifySelf::Center,
+                padding: UiRect::all(Val::Px(20.0)),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(style.background_color),
+            StateScoped(GameState::Stairs),
+        ))
+        .with_children(|builder| {
+            builder.spawn((Text::new(prompt), text_style.clone()));
+
+            #[cfg(feature = "sqlite")]
+            builder
+                .spawn((
+                    Button,
+                    button_node.clone(),
+                    BackgroundColor(style.button_color),
+                    children![(Text::new("Descend"), text_style.clone(), Pickable::IGNORE)],
+                ))
+                .observe(descend_on_click);
+
+            builder
+                .spawn((
+                    Button,
+                    button_node.clone(),
+                    BackgroundColor(style.button_color),
+                    children![(Text::new("Stay"), text_style.clone(), Pickable::IGNORE)],
+                ))
+                .observe(change_state_on_click(
+                    PointerButton::Primary,
+                    GameState::Navigation,
+                ));
+        });
+}
+
+#[cfg(feature = "sqlite")]
+fn descend_on_click(mut click: Trigger<Pointer<Click>>, mut commands: Commands) {
+    click.propagate(false);
+
+    if click.button == PointerButton::Primary {
+        commands.run_system_cached(descend_floor);
+    }
+}
+
+/// Replaces the map with the next floor down, and puts the party at its entrance.
+#[cfg(feature = "sqlite")]
+pub fn descend_floor(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut save: ResMut<SaveGame>,
+    map: Single<Entity, With<MapTilemap>>,
+    corruption: Option<ResMut<PillarCorruption>>,
+    mut notifications: ResMut<Notifications>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    save.floor += 1;
+
+    let settings = save.generation_settings();
+    let layout = MapLayout::generate(&settings);
+    info!(
+        "Generated layout {:016x} for floor {}",
+        layout.hash(),
+        save.floor
+    );
+
+    commands.entity(*map).despawn();
+    let tilemap = spawn_map_rooms(
+        &mut commands,
+        &asset_server,
+        layout.room_infos(),
+        save.shape.radius,
+    );
+    commands.entity(tilemap).insert(StateScoped(AppState::Game));
+    // The rooms are only in the tilemap's storage once the commands have been applied.
+    let origin = save.shape.origin();
+    commands.queue(move |world: &mut World| {
+        let entrance = world
+            .get::<TileStorage>(tilemap)
+            .and_then(|storage| storage.get(&origin));
+        match entrance {
+            Some(entrance) => {
+                world.entity_mut(entrance).insert(CurrentRoom);
+            }
+            None => error!("Floor has no entrance to start from"),
+        }
+    });
+
+    commands.insert_resource(Breadcrumbs::default());
+    if let Some(mut corruption) = corruption {
+        corruption.leave_floor();
+    }
+
+    notifications.info(format!("The party reached floor {}", save.floor + 1));
+    next_state.set(GameState::EnterRoom);
+}
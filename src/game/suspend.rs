@@ -0,0 +1,286 @@
+//! Fights saved part way through, so loading the game picks them back up on the same turn.
+//!
+//! The room's enemies are spawned as usual, then given back the health and effects
+//! they had, and the [`TurnOrder`] carries on from whoever's turn it was.
+//! Effects still waiting to land go back in the [`EffectSchedule`].
+
+use super::*;
+use serde::{Deserialize, Serialize};
+use std::num::NonZero;
+
+pub struct SuspendPlugin;
+
+impl Plugin for SuspendPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(GameState::EnterRoom),
+            restore_combatants
+                .after(spawn_room_entities)
+                .run_if(resource_exists::<SuspendedCombat>),
+        )
+        .add_systems(
+            OnEnter(GameState::Combat),
+            resume_turn_order
+                .after(setup_turn_order)
+                .run_if(resource_exists::<SuspendedCombat>),
+        )
+        .add_systems(OnExit(AppState::Game), remove_resource::<SuspendedCombat>);
+    }
+}
+
+/// Someone in a suspended fight, found again by their place in the party or the room.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Combatant {
+    Hero(usize),
+    Enemy(usize),
+}
+
+impl Combatant {
+    pub(super) fn find(party: Option<&PartySlot>, enemy: Option<&EnemySlot>) -> Option<Self> {
+        match (party, enemy) {
+            (Some(slot), _) => Some(Self::Hero(slot.0)),
+            (None, Some(slot)) => Some(Self::Enemy(slot.0)),
+            (None, None) => None,
+        }
+    }
+}
+
+/// A fight saved part way through. The party's health is saved with them,
+/// so only the enemies' health is kept here.
+#[derive(Resource, Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SuspendedCombat {
+    /// Everyone in the fight in turn order, with whoever's turn it is last.
+    pub turn_order: Vec<Combatant>,
+    /// The health each enemy had left, 0 once defeated.
+    pub enemy_health: Vec<(usize, u32)>,
+    pub effects: Vec<(Combatant, StatusEffects)>,
+    /// Missing from fights saved before effects could be scheduled.
+    #[serde(default)]
+    pub scheduled: Vec<ScheduledEffect>,
+    /// Missing from fights saved before special moves cost energy,
+    /// where everyone picks back up with full energy.
+    #[serde(default)]
+    pub energy: Vec<(Combatant, u32)>,
+}
+
+/// Moves the turn on to the next actor still standing.
+fn next_living_turn(order: &mut VecDeque<(Combatant, bool)>) {
+    for _ in 0..order.len() {
+        order.rotate_right(1);
+        if order.back().is_some_and(|(_, alive)| *alive) {
+            break;
+        }
+    }
+}
+
+/// What to save of the fight going on, if there is one.
+#[allow(clippy::type_complexity)]
+pub fn suspend_combat(
+    queue: Option<Res<TurnOrder>>,
+    combat_state: Option<Res<State<CombatState>>>,
+    schedule: Option<Res<EffectSchedule>>,
+    actors: Query<(
+        Option<&PartySlot>,
+        Option<&EnemySlot>,
+        &Health,
+        &StatusEffects,
+        Option<&Energy>,
+    )>,
+) -> Option<SuspendedCombat> {
+    let (queue, combat_state) = (queue?, combat_state?);
+
+    let mut order: VecDeque<(Combatant, bool)> = queue
+        .queue()
+        .iter()
+        .filter_map(|entity| {
+            let (party, enemy, health, ..) = actors.get(*entity).ok()?;
+            Some((Combatant::find(party, enemy)?, health.is_alive()))
+        })
+        .collect();
+
+    // Their attack already landed, so loading shouldn't let them take it again.
+    if matches!(
+        combat_state.get(),
+        CombatState::MoveBack | CombatState::EndOfTurn
+    ) {
+        next_living_turn(&mut order);
+    }
+
+    let mut combat = SuspendedCombat {
+        turn_order: order.into_iter().map(|(who, _)| who).collect(),
+        scheduled: schedule.map_or_else(Vec::new, |schedule| schedule.0.clone()),
+        ..default()
+    };
+    for (party, enemy, health, effects, energy) in actors.iter() {
+        let Some(who) = Combatant::find(party, enemy) else {
+            continue;
+        };
+
+        if let Combatant::Enemy(slot) = who {
+            let current = health.current().map_or(0, NonZero::get);
+            combat.enemy_health.push((slot, current));
+        }
+        if *effects != StatusEffects::default() {
+            combat.effects.push((who, effects.clone()));
+        }
+        if let Some(energy) = energy {
+            combat.energy.push((who, energy.0));
+        }
+    }
+    combat.enemy_health.sort();
+    combat.effects.sort_by_key(|(who, _)| *who);
+    combat.energy.sort();
+
+    Some(combat)
+}
+
+pub(super) fn restore_combatants(
+    mut commands: Commands,
+    combat: Res<SuspendedCombat>,
+    mut actors: Query<(
+        Option<&PartySlot>,
+        Option<&EnemySlot>,
+        &mut Health,
+        &mut StatusEffects,
+    )>,
+) {
+    for (party, enemy, mut health, mut effects) in actors.iter_mut() {
+        let Some(who) = Combatant::find(party, enemy) else {
+            continue;
+        };
+
+        if let Combatant::Enemy(slot) = who
+            && let Some((_, current)) = combat.enemy_health.iter().find(|(s, _)| *s == slot)
+        {
+            *health = Health::with_current(NonZero::new(*current), health.max());
+        }
+        if let Some((_, saved)) = combat.effects.iter().find(|(w, _)| *w == who) {
+            *effects = saved.clone();
+        }
+    }
+    commands.insert_resource(EffectSchedule(combat.scheduled.clone()));
+}
+
+#[allow(clippy::type_complexity)]
+fn resume_turn_order(
+    mut commands: Commands,
+    combat: Res<SuspendedCombat>,
+    actors: Query<(Entity, Option<&PartySlot>, Option<&EnemySlot>), With<Actor>>,
+) {
+    commands.remove_resource::<SuspendedCombat>();
+
+    let queue: VecDeque<Entity> = combat
+        .turn_order
+        .iter()
+        .filter_map(|who| {
+            actors
+                .iter()
+                .find(|(_, party, enemy)| Combatant::find(*party, *enemy) == Some(*who))
+                .map(|(entity, ..)| entity)
+        })
+        .collect();
+
+    // Keep the new turn order if the room no longer matches the save.
+    if queue.len() != actors.iter().count() {
+        warn!("Suspended fight doesn't match the room, starting a new turn order");
+        return;
+    }
+
+    for (entity, party, enemy) in actors.iter() {
+        let saved = Combatant::find(party, enemy)
+            .and_then(|who| combat.energy.iter().find(|(w, _)| *w == who));
+        if let Some((_, energy)) = saved {
+            commands.entity(entity).insert(Energy(*energy));
+        }
+    }
+
+    commands.insert_resource(TurnOrder::from_queue(queue));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn spawn_fight(world: &mut World) -> Vec<Entity> {
+        let hero = |slot| {
+            (
+                Actor,
+                PartySlot(slot),
+                Health::new(NonZero::new(40).unwrap()),
+                StatusEffects::default(),
+                Energy(crate::rules::MAX_ENERGY),
+            )
+        };
+        let enemy = |slot| {
+            (
+                Actor,
+                EnemySlot(slot),
+                Health::new(NonZero::new(30).unwrap()),
+                StatusEffects::default(),
+                Energy(crate::rules::MAX_ENERGY),
+            )
+        };
+
+        vec![
+            world.spawn(enemy(1)).id(),
+            world.spawn(hero(0)).id(),
+            world.spawn(enemy(0)).id(),
+            world.spawn(hero(1)).id(),
+        ]
+    }
+
+    fn suspend(world: &mut World, state: CombatState) -> SuspendedCombat {
+        world.insert_resource(State::new(state));
+        world.run_system_cached(suspend_combat).unwrap().unwrap()
+    }
+
+    #[test]
+    fn fights_resume_on_the_same_turn() {
+        let mut world = World::new();
+        let actors = spawn_fight(&mut world);
+        world.insert_resource(TurnOrder::from_queue(actors.clone().into()));
+
+        let mut enemy = world.get_mut::<Health>(actors[2]).unwrap();
+        enemy.damage(12);
+        let mut effects = world.get_mut::<StatusEffects>(actors[3]).unwrap();
+        effects.apply(StatusKind::Poison, 2);
+        world.get_mut::<Energy>(actors[3]).unwrap().0 = 0;
+        world.insert_resource(EffectSchedule(vec![ScheduledEffect {
+            source: Combatant::Enemy(1),
+            target: Combatant::Hero(0),
+            effect: DelayedEffect::Strike(50),
+            turns: 2,
+            fresh: false,
+        }]));
+
+        let combat = suspend(&mut world, CombatState::SpawnMenu);
+        assert_eq!(*combat.turn_order.last().unwrap(), Combatant::Hero(1));
+        assert_eq!(combat.enemy_health, vec![(0, 18), (1, 30)]);
+        assert!(combat.energy.contains(&(Combatant::Hero(1), 0)));
+
+        // Load it into a freshly spawned room, with everyone spawned in a different order.
+        let mut loaded = World::new();
+        let mut actors = spawn_fight(&mut loaded);
+        actors.reverse();
+        loaded.insert_resource(TurnOrder::from_queue(actors.into()));
+        loaded.insert_resource(combat.clone());
+        loaded.run_system_cached(restore_combatants).unwrap();
+        loaded.run_system_cached(resume_turn_order).unwrap();
+
+        assert!(!loaded.contains_resource::<SuspendedCombat>());
+        assert_eq!(suspend(&mut loaded, CombatState::SpawnMenu), combat);
+    }
+
+    #[test]
+    fn finished_turns_resume_on_the_next_one() {
+        let mut world = World::new();
+        let actors = spawn_fight(&mut world);
+        world.insert_resource(TurnOrder::from_queue(actors.clone().into()));
+        world.get_mut::<Health>(actors[2]).unwrap().kill();
+
+        // Hero 1 already attacked, and the enemy after them is down.
+        let combat = suspend(&mut world, CombatState::MoveBack);
+        assert_eq!(*combat.turn_order.last().unwrap(), Combatant::Hero(0));
+        assert_eq!(combat.enemy_health[0], (0, 0));
+    }
+}
@@ -0,0 +1,320 @@
+//! Spike traps hidden in the floor of some rooms, found once the party has the room to itself.
+//!
+//! A trap springs on a hero when the party leaves through a door it lies on the way to.
+//! Clicking one tries to disarm it first, which a standing Thief always manages,
+//! and anyone else only some of the time. Either way a trap only ever goes off once,
+//! and rooms have none left after their first visit.
+
+use super::*;
+use crate::room::{ENEMY_POSITIONS, ITEM_POSITION, PROP_LAYER, RoomLayout};
+use bevy_ecs_tilemap::helpers::hex_grid::axial::AxialPos;
+use rand::{Rng, SeedableRng};
+
+/// Mixed into a room's seed for its traps, so having traps doesn't change its events.
+const TRAP_RNG_STREAM: u64 = 0x5b1c_e7a9_0000_7a95;
+/// One in this many rooms that can have traps does.
+const TRAP_CHANCE: u32 = 3;
+/// The most traps a room can have.
+const MAX_TRAPS: u32 = 2;
+/// The chance of disarming a trap without a Thief.
+pub const DISARM_CHANCE: f64 = 0.5;
+const TRAP_SIZE: Vec2 = Vec2::new(14.0, 14.0);
+const TRAP_COLOR: Color = Color::srgb_u8(0xeb, 0x6f, 0x92);
+
+pub struct TrapsPlugin;
+
+impl Plugin for TrapsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_observer(click_spike_trap).add_systems(
+            OnEnter(GameState::Navigation),
+            spawn_spike_traps
+                .before(mark_room_cleared)
+                .run_if(not(resource_exists::<Fled>)),
+        );
+    }
+}
+
+/// A trap lying on a room tile, doing `damage` to whoever it springs on.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpikeTrap {
+    pub damage: u32,
+    pub tile: TilePos,
+}
+
+/// The traps in a room on its first visit, the same every time it is rolled.
+///
+/// They are kept off the edge with its props and off everywhere actors or items stand.
+pub fn roll_spike_traps(info: &RoomInfo, difficulty: Difficulty) -> Vec<SpikeTrap> {
+    let can_have_traps = matches!(
+        info.r_type,
+        RoomType::EmptyRoom | RoomType::Combat(_) | RoomType::Item(_)
+    );
+    if info.cleared || !can_have_traps {
+        return Vec::new();
+    }
+
+    let mut rng = RandomSource::seed_from_u64(info.rng_seed ^ TRAP_RNG_STREAM);
+    if !rng.random_ratio(1, TRAP_CHANCE) {
+        return Vec::new();
+    }
+
+    let taken: Vec<IVec2> = ENEMY_POSITIONS
+        .into_iter()
+        .chain(PLAYER_POSITIONS)
+        .chain([ITEM_POSITION])
+        .collect();
+    let radius = ROOM_RADIUS as i32 - 1;
+    let mut free: Vec<IVec2> = (-radius..=radius)
+        .flat_map(|q| (-radius..=radius).map(move |r| AxialPos::new(q, r)))
+        .filter(|pos| (1..=radius).contains(&pos.magnitude()))
+        .map(|pos| IVec2::new(pos.q, pos.r))
+        .filter(|offset| !taken.contains(offset))
+        .collect();
+
+    (0..rng.random_range(1..=MAX_TRAPS))
+        .map(|_| {
+            let offset = free.swap_remove(rng.random_range(0..free.len()));
+            let tile: TilePos = (IVec2::new(ROOM_CENTER.x as i32, ROOM_CENTER.y as i32) + offset)
+                .as_uvec2()
+                .into();
+            SpikeTrap {
+                damage: rng.random_range(difficulty.pit_damage()).max(1),
+                tile,
+            }
+        })
+        .collect()
+}
+
+/// Whether the party walks over `tile` on its way from the middle of the room to the door.
+pub fn in_the_way(tile: &TilePos, door: EntranceDirection) -> bool {
+    let door = door.door_offset(&ROOM_CENTER, ROOM_RADIUS, HEX_COORD_SYSTEM);
+    hex_distance(&ROOM_CENTER, tile) + hex_distance(tile, &door)
+        == hex_distance(&ROOM_CENTER, &door)
+}
+
+fn spawn_spike_traps(
+    mut commands: Commands,
+    info: Single<&RoomInfo, With<CurrentRoom>>,
+    difficulty: Res<Difficulty>,
+    tilemap: Single<
+        (
+            &TilemapSize,
+            &TilemapGridSize,
+            &TilemapTileSize,
+            &TilemapType,
+            &TilemapAnchor,
+        ),
+        With<RoomTilemap>,
+    >,
+) {
+    let layout = RoomLayout::new(*tilemap);
+    for trap in roll_spike_traps(&info, *difficulty) {
+        let offset = IVec2::new(trap.tile.x as i32, trap.tile.y as i32)
+            - IVec2::new(ROOM_CENTER.x as i32, ROOM_CENTER.y as i32);
+        let world_pos = layout.world_pos(offset);
+
+        commands.spawn((
+            InRoom,
+            trap,
+            Sprite::from_color(TRAP_COLOR, TRAP_SIZE),
+            Transform::from_xyz(world_pos.x, world_pos.y, PROP_LAYER)
+                .with_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_4)),
+            Pickable::default(),
+            Tooltip::new("Spike Trap").with_body(format!(
+                "Up to {} damage to whoever walks past it to a door. Click to try disarming it",
+                trap.damage
+            )),
+        ));
+    }
+}
+
+fn click_spike_trap(
+    mut click: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    traps: Query<(), With<SpikeTrap>>,
+    game_state: Option<Res<State<GameState>>>,
+) {
+    if !traps.contains(click.target()) {
+        return;
+    }
+    click.propagate(false);
+
+    let exploring = game_state.is_some_and(|state| *state.get() == GameState::Navigation);
+    if click.button == PointerButton::Primary && exploring {
+        commands.run_system_cached_with(disarm_spike_trap, click.target());
+    }
+}
+
+/// Tries to take the trap apart, springing it on someone if that goes wrong.
+fn disarm_spike_trap(
+    In(entity): In<Entity>,
+    mut commands: Commands,
+    traps: Query<&SpikeTrap>,
+    mut party: Query<(&mut Health, &ActorName, Option<&HeroName>), With<PartySlot>>,
+    mut rng: ResMut<EventRng>,
+    mut stats: ResMut<RunStats>,
+    mut notifications: ResMut<Notifications>,
+) {
+    let Ok(trap) = traps.get(entity) else {
+        return;
+    };
+    commands.entity(entity).despawn();
+
+    let thief_standing = party
+        .iter()
+        .any(|(health, name, _)| *name == ActorName::Theif && health.is_alive());
+    if thief_standing || rng.random_bool(DISARM_CHANCE) {
+        notifications.success("Disarmed the spike trap");
+        return;
+    }
+
+    spring(trap, &mut party, &mut rng, &mut stats, &mut notifications);
+    commands.run_system_cached(update_player_hp_bar_pit);
+}
+
+/// Springs every trap on the way to the door the party is leaving through.
+pub fn cross_spike_traps(
+    In(door): In<EntranceDirection>,
+    mut commands: Commands,
+    traps: Query<(Entity, &SpikeTrap)>,
+    mut party: Query<(&mut Health, &ActorName, Option<&HeroName>), With<PartySlot>>,
+    mut rng: ResMut<EventRng>,
+    mut stats: ResMut<RunStats>,
+    mut notifications: ResMut<Notifications>,
+) {
+    let mut sprung = false;
+    for (entity, trap) in traps
+        .iter()
+        .filter(|(_, trap)| in_the_way(&trap.tile, door))
+    {
+        commands.entity(entity).despawn();
+        spring(trap, &mut party, &mut rng, &mut stats, &mut notifications);
+        sprung = true;
+    }
+
+    if sprung {
+        commands.run_system_cached(update_player_hp_bar_pit);
+    }
+}
+
+/// Hurts a random standing hero, like a pit would.
+fn spring(
+    trap: &SpikeTrap,
+    party: &mut Query<(&mut Health, &ActorName, Option<&HeroName>), With<PartySlot>>,
+    rng: &mut EventRng,
+    stats: &mut RunStats,
+    notifications: &mut Notifications,
+) {
+    let standing = party
+        .iter()
+        .filter(|(health, ..)| health.is_alive())
+        .count();
+    if standing == 0 {
+        return;
+    }
+
+    let hit = rng.random_range(0..standing);
+    let Some((mut health, name, hero_name)) = party
+        .iter_mut()
+        .filter(|(health, ..)| health.is_alive())
+        .nth(hit)
+    else {
+        return;
+    };
+
+    let health_before = health_points(&health);
+    health.damage_no_one_shot(trap.damage);
+    let dealt = health_before - health_points(&health);
+    stats.count_damage(Team::Player, dealt);
+
+    let name = hero_name.map_or_else(|| name.to_string(), |hero| hero.0.clone());
+    notifications.warn(format!("A spike trap hit {name} for {dealt}"));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::num::NonZero;
+
+    #[test]
+    fn spike_traps_stay_clear_of_everyone() {
+        let taken: Vec<IVec2> = ENEMY_POSITIONS
+            .into_iter()
+            .chain(PLAYER_POSITIONS)
+            .collect();
+        let mut trapped = 0;
+
+        for seed in 0..300 {
+            let info = RoomInfo::from_type(RoomType::EmptyRoom, seed);
+            let traps = roll_spike_traps(&info, Difficulty::Normal);
+            assert_eq!(traps, roll_spike_traps(&info, Difficulty::Normal));
+            assert!(traps.len() <= MAX_TRAPS as usize);
+            if !traps.is_empty() {
+                trapped += 1;
+            }
+
+            for trap in traps {
+                let distance = hex_distance(&ROOM_CENTER, &trap.tile);
+                assert!((1..ROOM_RADIUS).contains(&distance));
+                let offset = IVec2::new(trap.tile.x as i32, trap.tile.y as i32)
+                    - IVec2::new(ROOM_CENTER.x as i32, ROOM_CENTER.y as i32);
+                assert!(!taken.contains(&offset));
+                assert!(trap.damage > 0);
+            }
+
+            let mut cleared = info.clone();
+            cleared.cleared = true;
+            assert!(roll_spike_traps(&cleared, Difficulty::Normal).is_empty());
+            let stairs = RoomInfo::from_type(RoomType::Stairs, seed);
+            assert!(roll_spike_traps(&stairs, Difficulty::Normal).is_empty());
+        }
+        assert!(trapped > 50 && trapped < 150);
+    }
+
+    #[test]
+    fn leaving_springs_only_the_traps_on_the_way() {
+        let mut world = World::new();
+        world.init_resource::<Notifications>();
+        world.init_resource::<RunStats>();
+        world.insert_resource(EventRng(RandomSource::seed_from_u64(0)));
+
+        let max = NonZero::new(50).unwrap();
+        let hero = world
+            .spawn((
+                Health::with_current(NonZero::new(40), max),
+                ActorName::Warrior,
+                PartySlot(0),
+            ))
+            .id();
+
+        let ahead = TilePos::new(ROOM_CENTER.x + 1, ROOM_CENTER.y + 1);
+        let behind = TilePos::new(ROOM_CENTER.x - 1, ROOM_CENTER.y - 1);
+        assert!(in_the_way(&ahead, EntranceDirection::NorthEast));
+        assert!(!in_the_way(&behind, EntranceDirection::NorthEast));
+        let ahead = world
+            .spawn(SpikeTrap {
+                damage: 15,
+                tile: ahead,
+            })
+            .id();
+        let behind = world
+            .spawn(SpikeTrap {
+                damage: 15,
+                tile: behind,
+            })
+            .id();
+
+        world
+            .run_system_cached_with(cross_spike_traps, EntranceDirection::NorthEast)
+            .unwrap();
+        world.flush();
+
+        assert!(world.get_entity(ahead).is_err());
+        assert!(world.get_entity(behind).is_ok());
+        assert_eq!(
+            world.get::<Health>(hero).unwrap().current(),
+            NonZero::new(25)
+        );
+        assert_eq!(world.resource::<RunStats>().damage_taken, 15);
+    }
+}
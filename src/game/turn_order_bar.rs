@@ -0,0 +1,111 @@
+//! A row of portraits along the top of the fight, in the order everyone takes their turn.
+
+use super::*;
+
+const PORTRAIT_SIZE: f32 = 48.0;
+const ACTIVE_PORTRAIT_SIZE: f32 = 60.0;
+const PORTRAIT_BORDER: f32 = 3.0;
+/// Tint for the portrait of someone who has fallen.
+const FALLEN_TINT: Color = Color::srgba(0.3, 0.3, 0.3, 0.6);
+
+pub struct TurnOrderBarPlugin;
+
+impl Plugin for TurnOrderBarPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Combat), spawn_turn_order_bar)
+            .add_systems(
+                OnEnter(CombatState::TurnSetup),
+                update_turn_order_bar.after(prep_turn_order),
+            );
+    }
+}
+
+#[derive(Component)]
+struct TurnOrderBar;
+
+/// Everyone in the fight, starting with whoever's turn it is.
+fn initiative(queue: &TurnOrder) -> Vec<Entity> {
+    queue.queue().iter().rev().copied().collect()
+}
+
+fn spawn_turn_order_bar(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Turn Order Bar"),
+        TurnOrderBar,
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            column_gap: Val::Px(6.0),
+            ..default()
+        },
+        Pickable::IGNORE,
+        StateScoped(GameState::Combat),
+    ));
+}
+
+fn update_turn_order_bar(
+    mut commands: Commands,
+    style: Res<Style>,
+    queue: Res<TurnOrder>,
+    bar: Single<Entity, With<TurnOrderBar>>,
+    actors: Query<(&Sprite, &Health, &ActorName, Option<&HeroName>)>,
+) {
+    commands
+        .entity(*bar)
+        .despawn_related::<Children>()
+        .with_children(|builder| {
+            for entity in initiative(&queue) {
+                let Ok((sprite, health, name, hero_name)) = actors.get(entity) else {
+                    continue;
+                };
+
+                let active = entity == queue.active();
+                let size = match active {
+                    true => ACTIVE_PORTRAIT_SIZE,
+                    false => PORTRAIT_SIZE,
+                };
+
+                builder.spawn((
+                    Node {
+                        width: Val::Px(size),
+                        height: Val::Px(size),
+                        border: UiRect::all(Val::Px(PORTRAIT_BORDER)),
+                        ..default()
+                    },
+                    ImageNode {
+                        image: sprite.image.clone(),
+                        texture_atlas: sprite.texture_atlas.clone(),
+                        color: match health.is_alive() {
+                            true => Color::WHITE,
+                            false => FALLEN_TINT,
+                        },
+                        ..default()
+                    },
+                    BackgroundColor(style.background_color.with_alpha(0.8)),
+                    BorderColor(match active {
+                        true => style.accent_color,
+                        false => style.background_color,
+                    }),
+                    Tooltip::new(actor_title(*name, hero_name)),
+                ));
+            }
+        });
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn bar_starts_with_the_acting_actor() {
+        let mut world = World::new();
+        let actors: Vec<Entity> = (0..3).map(|_| world.spawn_empty().id()).collect();
+        let queue = TurnOrder::from_queue(actors.clone().into());
+
+        assert_eq!(initiative(&queue)[0], queue.active());
+        assert_eq!(initiative(&queue), vec![actors[2], actors[1], actors[0]]);
+    }
+}
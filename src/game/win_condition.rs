@@ -0,0 +1,221 @@
+//! What has to be done to win, picked with the [`GameMode`] of a new game.
+//!
+//! The [`ActiveWinCondition`] is checked each time the party comes out of a room.
+//! New modes plug in by implementing [`WinCondition`] and returning it from
+//! [`GameMode::win_condition`].
+
+use super::*;
+use crate::generate_map::{MapShape, MapTile};
+use bevy::ecs::system::SystemParam;
+
+/// The share of the map's rooms that have to be made it through in [`GameMode::Survival`].
+pub const SURVIVAL_SHARE: f32 = 0.75;
+
+pub struct WinConditionPlugin;
+
+impl Plugin for WinConditionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::Game), insert_win_condition)
+            .add_systems(
+                OnEnter(GameState::Navigation),
+                check_win_condition
+                    .run_if(not(in_endless_mode))
+                    .after(claim_pillar)
+                    .after(mark_room_cleared),
+            )
+            .add_systems(
+                OnExit(AppState::Game),
+                remove_resource::<ActiveWinCondition>,
+            );
+    }
+}
+
+/// How far along the game is, for a [`WinCondition`] to check.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RunProgress {
+    pub pillars: u64,
+    /// Pillar rooms whose guardian hasn't been beaten.
+    pub guardians_left: usize,
+    /// Rooms the party has made it through, not counting the entrance.
+    pub rooms_cleared: usize,
+    /// Rooms on the map, not counting the entrance.
+    pub rooms: usize,
+}
+
+pub trait WinCondition: Send + Sync + 'static {
+    /// Whether everything needed to win has been done.
+    fn is_met(&self, progress: &RunProgress) -> bool;
+
+    /// How far along the party is, shown after each fight.
+    fn progress_text(&self, progress: &RunProgress) -> String;
+
+    /// Whether the party still has to make it back to the entrance once it is met,
+    /// rather than winning straight away.
+    fn leave_from_entrance(&self) -> bool {
+        false
+    }
+}
+
+/// Bring back as many pillars as the map has.
+pub struct CollectPillars(pub u64);
+
+impl WinCondition for CollectPillars {
+    fn is_met(&self, progress: &RunProgress) -> bool {
+        progress.pillars >= self.0
+    }
+
+    fn progress_text(&self, progress: &RunProgress) -> String {
+        format!("Pillars: {}/{}", progress.pillars, self.0)
+    }
+
+    fn leave_from_entrance(&self) -> bool {
+        true
+    }
+}
+
+pub struct DefeatGuardians;
+
+impl WinCondition for DefeatGuardians {
+    fn is_met(&self, progress: &RunProgress) -> bool {
+        progress.guardians_left == 0
+    }
+
+    fn progress_text(&self, progress: &RunProgress) -> String {
+        format!("Guardians left: {}", progress.guardians_left)
+    }
+}
+
+/// Make it through [`SURVIVAL_SHARE`] of the map's rooms.
+pub struct SurviveRooms;
+
+impl SurviveRooms {
+    /// How many rooms have to be made it through, at least one.
+    pub fn target(progress: &RunProgress) -> usize {
+        ((progress.rooms as f32 * SURVIVAL_SHARE).ceil() as usize).max(1)
+    }
+}
+
+impl WinCondition for SurviveRooms {
+    fn is_met(&self, progress: &RunProgress) -> bool {
+        progress.rooms_cleared >= Self::target(progress)
+    }
+
+    fn progress_text(&self, progress: &RunProgress) -> String {
+        let target = Self::target(progress);
+        format!("Rooms: {}/{target}", progress.rooms_cleared.min(target))
+    }
+}
+
+impl GameMode {
+    /// The condition for the mode, on a map with `pillars` pillars.
+    pub fn win_condition(self, pillars: u64) -> Box<dyn WinCondition> {
+        match self {
+            GameMode::Pillars => Box::new(CollectPillars(pillars)),
+            GameMode::Guardians => Box::new(DefeatGuardians),
+            GameMode::Survival => Box::new(SurviveRooms),
+        }
+    }
+}
+
+/// The condition the current game is won by.
+#[derive(Resource, Deref)]
+pub struct ActiveWinCondition(pub Box<dyn WinCondition>);
+
+/// Gathers the [`RunProgress`] of the current game.
+#[derive(SystemParam)]
+pub struct Progress<'w, 's> {
+    pillars: Pillars<'w>,
+    rooms: Query<'w, 's, &'static RoomInfo, With<MapTile>>,
+}
+
+impl Progress<'_, '_> {
+    pub fn get(&self) -> RunProgress {
+        RunProgress {
+            pillars: self.pillars.count(),
+            guardians_left: self
+                .rooms
+                .iter()
+                .filter(|info| info.r_type == RoomType::Pillar && !info.cleared)
+                .count(),
+            rooms_cleared: self
+                .rooms
+                .iter()
+                .filter(|info| info.r_type != RoomType::Entrance && info.cleared)
+                .count(),
+            rooms: self
+                .rooms
+                .iter()
+                .filter(|info| info.r_type != RoomType::Entrance)
+                .count(),
+        }
+    }
+}
+
+fn insert_win_condition(
+    mut commands: Commands,
+    mode: Option<Res<GameMode>>,
+    shape: Option<Res<MapShape>>,
+) {
+    let mode = mode.map_or(GameMode::default(), |mode| *mode);
+    let pillars = shape.map_or(MapShape::default(), |shape| *shape).pillars;
+    commands.insert_resource(ActiveWinCondition(mode.win_condition(pillars as u64)));
+}
+
+fn check_win_condition(
+    condition: Res<ActiveWinCondition>,
+    progress: Progress,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if !condition.leave_from_entrance() && condition.is_met(&progress.get()) {
+        next_state.set(GameState::Victory);
+    }
+}
+
+/// Whether the game is won once the party leaves the dungeon.
+pub fn ready_to_leave(condition: Option<Res<ActiveWinCondition>>, progress: Progress) -> bool {
+    condition.is_some_and(|condition| {
+        condition.leave_from_entrance() && condition.is_met(&progress.get())
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn each_mode_is_won_its_own_way() {
+        let progress = RunProgress {
+            pillars: 4,
+            guardians_left: 1,
+            rooms_cleared: 14,
+            rooms: 20,
+        };
+
+        assert!(GameMode::Pillars.win_condition(4).is_met(&progress));
+        assert!(!GameMode::Guardians.win_condition(4).is_met(&progress));
+        assert!(!GameMode::Survival.win_condition(4).is_met(&progress));
+
+        let progress = RunProgress {
+            guardians_left: 0,
+            rooms_cleared: 15,
+            ..progress
+        };
+        assert!(GameMode::Guardians.win_condition(4).is_met(&progress));
+        assert!(GameMode::Survival.win_condition(4).is_met(&progress));
+    }
+
+    #[test]
+    fn only_pillars_have_to_be_brought_back() {
+        assert!(GameMode::Pillars.win_condition(4).leave_from_entrance());
+        assert!(!GameMode::Guardians.win_condition(4).leave_from_entrance());
+        assert_eq!(
+            SurviveRooms.progress_text(&RunProgress {
+                rooms_cleared: 5,
+                rooms: 4,
+                ..default()
+            }),
+            "Rooms: 3/3"
+        );
+        assert_eq!(SurviveRooms::target(&RunProgress::default()), 1);
+    }
+}
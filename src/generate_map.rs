@@ -2,32 +2,44 @@ use crate::embed_asset;
 use crate::menu::new_game::GenerationProgress;
 use crate::menu::new_game::NewGameState;
 use crate::prelude::*;
+use crate::room::EntranceDirection;
 use bevy::prelude::*;
+use bevy::tasks::futures_lite::future;
+use bevy::tasks::{AsyncComputeTaskPool, Task, block_on};
 use bevy_ecs_tilemap::helpers::hex_grid::axial::AxialPos;
-use bevy_ecs_tilemap::helpers::hex_grid::neighbors::HexNeighbors;
 use bevy_ecs_tilemap::prelude::*;
 use rand::{Rng, SeedableRng};
 use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use strum::{Display, EnumIter, IntoEnumIterator};
+use thiserror::Error;
 
 pub struct GenerateMapPlugin;
 
 pub const WORLD_MAP_ORIGIN: Vec3 = Vec3::new(10000.0, 0.0, MAP_TILE_LAYER);
+/// How many rooms out from the entrance the map goes, unless picked otherwise in the New Game menu.
 pub const MAP_RADIUS: u32 = 5;
-pub const PILLAR_OFFSET_VERT: u32 = 3;
-pub const PILLAR_OFFSET_HORZ_X: u32 = 4;
-pub const PILLAR_OFFSET_HORZ_Y: u32 = 1;
-pub const MAP_SIZE: TilemapSize = TilemapSize {
-    x: MAP_RADIUS * 2 + 1,
-    y: MAP_RADIUS * 2 + 1,
-};
-pub const MAP_ORIGIN: TilePos = TilePos {
-    x: MAP_RADIUS,
-    y: MAP_RADIUS,
-};
+/// How many pillars are on each floor, unless picked otherwise in the New Game menu.
+pub const MAP_PILLARS: u32 = 4;
+/// What the pillars are called in [`MapLayout::hash`], after the sides of the map
+/// the first four were always on, so the layouts of older seeds hash the same.
+const PILLAR_NAMES: [&str; 4] = ["North", "East", "South", "West"];
+/// How many rooms from each pillar a Healing Potion is guaranteed.
+pub const HEALING_RADIUS: u32 = 2;
+/// How much deeper every room counts for its encounter budget on each floor down.
+pub const DEPTH_PER_FLOOR: u32 = 4;
+/// Mixed into the world seed for each floor down, so no two floors are laid out alike.
+const FLOOR_RNG_STREAM: u64 = 0x57a1_25d0_3e4f_10a7;
+/// How many rooms of open ground a room carved out of rock is worth,
+/// when finding a way to the pillars through a map laid out by [`GenerationMode::Constraints`].
+const CARVE_COST: u32 = 4;
 pub const MAP_TILE_LAYER: f32 = 0.0;
 pub const MAP_COORD_SYSTEM: HexCoordSystem = HexCoordSystem::Column;
 
-const GENERATION_SCHEDULE_FREQUENCY: f64 = 10000.0;
 const GENERATING_STATE: NewGameState = NewGameState::GeneratingWorld;
 
 pub const MAP_TILE_SIZE: TilemapTileSize = TilemapTileSize { x: 52.0, y: 48.0 };
@@ -38,42 +50,196 @@ impl Plugin for GenerateMapPlugin {
     fn build(&self, app: &mut App) {
         embed_asset!(app, "assets/sprites/map_tiles.png");
 
-        app.add_systems(
-            OnEnter(GENERATING_STATE),
-            set_fixed_update_time(GENERATION_SCHEDULE_FREQUENCY),
-        )
-        .add_systems(
-            OnEnter(GENERATING_STATE),
-            (
-                setup,
-                spawn_map,
-                #[cfg(feature = "debug")]
-                spawn_tile_labels::<With<MapTilemap>, With<MapTile>>,
-                (create_origin_and_pillars, build_paths).chain(),
-            )
-                .chain(),
-        )
-        .add_systems(
-            OnExit(GENERATING_STATE),
-            (
-                restore_fixed_update_time,
-                despawn_outline_tiles,
-                remove_component::<Collapsed>,
-            ),
-        );
+        app.add_systems(OnEnter(GENERATING_STATE), start_generation)
+            .add_systems(
+                OnExit(GENERATING_STATE),
+                (
+                    despawn_outline_tiles,
+                    remove_component::<Collapsed>,
+                    remove_resource::<MapLayout>,
+                    remove_resource::<GenerationTask>,
+                    remove_resource::<PresetLayout>,
+                ),
+            );
     }
 }
 
 /// Settings set by the UI before world generation to
 /// give generation parameters.
-#[derive(Resource)]
+#[derive(Resource, Debug, Default, Clone)]
 pub struct GenerationSettings {
     pub seed: u64,
+    pub difficulty: Difficulty,
+    pub encounters: EncounterRules,
+    pub rooms: RoomWeights,
+    /// How many floors down the map is, with the first floor at 0.
+    pub floor: u32,
+    pub mode: GenerationMode,
+    pub shape: MapShape,
+}
+
+/// How big the map is and how many pillars are on it, picked in the New Game menu.
+/// Kept with the save, and put in as a resource for the game it was picked for.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MapShape {
+    pub radius: u32,
+    pub pillars: u32,
+}
+
+impl Default for MapShape {
+    fn default() -> Self {
+        Self {
+            radius: MAP_RADIUS,
+            pillars: MAP_PILLARS,
+        }
+    }
+}
+
+impl MapShape {
+    /// The radii that can be picked. Any smaller and the pillars would crowd the entrance.
+    pub const RADII: RangeInclusive<u32> = 4..=7;
+    /// The pillar counts that can be picked, at most one for each side of the map.
+    pub const PILLARS: RangeInclusive<u32> = 1..=6;
+
+    /// The size of the tilemap the map fits in.
+    pub fn size(&self) -> TilemapSize {
+        TilemapSize {
+            x: self.radius * 2 + 1,
+            y: self.radius * 2 + 1,
+        }
+    }
+
+    /// Where the entrance is, in the middle of the map.
+    pub fn origin(&self) -> TilePos {
+        TilePos {
+            x: self.radius,
+            y: self.radius,
+        }
+    }
+
+    /// Whether the position is on the map.
+    pub fn contains(&self, pos: &TilePos) -> bool {
+        let size = self.size();
+        pos.x < size.x && pos.y < size.y
+    }
+
+    /// The shape with the next radius, wrapping back to the smallest.
+    pub fn next_radius(self) -> Self {
+        Self {
+            radius: next_in(Self::RADII, self.radius),
+            ..self
+        }
+    }
+
+    /// The shape with one more pillar, wrapping back to the fewest.
+    pub fn next_pillars(self) -> Self {
+        Self {
+            pillars: next_in(Self::PILLARS, self.pillars),
+            ..self
+        }
+    }
+}
+
+fn next_in(range: RangeInclusive<u32>, value: u32) -> u32 {
+    match value < *range.end() && value >= *range.start() {
+        true => value + 1,
+        false => *range.start(),
+    }
+}
+
+/// How the rooms between the entrance and the pillars are laid out, picked in the New Game menu
+/// and kept with the save so every floor is laid out the same way.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, EnumIter, Display,
+)]
+pub enum GenerationMode {
+    /// Rooms follow the shortest paths from the entrance to each pillar.
+    #[default]
+    Paths,
+    /// The whole map is filled with [`Biome`]s that have to fit with their neighbors,
+    /// then paths are carved through the rock to any pillar that can't be reached.
+    Constraints,
+}
+
+impl GenerationMode {
+    /// The mode after this one, wrapping back to the first.
+    pub fn next(self) -> Self {
+        Self::iter()
+            .cycle()
+            .skip_while(|mode| *mode != self)
+            .nth(1)
+            .unwrap()
+    }
+}
+
+/// What a part of the map is like, when laid out by [`GenerationMode::Constraints`].
+/// Each biome leans toward its own kinds of rooms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter)]
+pub enum Biome {
+    /// Rock, with no room in it.
+    Solid,
+    /// Quiet halls, where the party can rest and find supplies.
+    Halls,
+    /// Where the monsters live. The pillars are always in one.
+    Lair,
+    /// Old ruins, full of puzzles and shrines.
+    Ruins,
 }
 
-/// Seedable Rand Resource
-#[derive(Resource, Deref, DerefMut)]
-struct GenerationRand(pub RandomSource);
+impl Biome {
+    fn bit(self) -> u8 {
+        1 << self as u8
+    }
+
+    /// How likely the biome is to be picked, out of the ones a position can still be.
+    fn weight(self) -> u32 {
+        match self {
+            Biome::Solid => 14,
+            Biome::Halls => 3,
+            Biome::Lair => 2,
+            Biome::Ruins => 2,
+        }
+    }
+
+    /// Whether the biomes can be next to each other.
+    /// Lairs and ruins have to be kept apart by halls or rock.
+    pub fn can_border(self, other: Biome) -> bool {
+        !matches!(
+            (self, other),
+            (Biome::Lair, Biome::Ruins) | (Biome::Ruins, Biome::Lair)
+        )
+    }
+
+    /// The room weights of the settings, scaled toward the biome's kinds of rooms.
+    pub fn room_weights(self, weights: &RoomWeights) -> RoomWeights {
+        // Rock only ever has a room in it once a path is carved through, which makes it halls.
+        let [empty, combat, pit, item, puzzle, shrine] = match self {
+            Biome::Solid | Biome::Halls => [3, 1, 0, 2, 1, 1],
+            Biome::Lair => [0, 3, 2, 1, 0, 0],
+            Biome::Ruins => [1, 1, 1, 1, 3, 2],
+        };
+        RoomWeights {
+            empty: weights.empty * empty,
+            combat: weights.combat * combat,
+            pit: weights.pit * pit,
+            item: weights.item * item,
+            puzzle: weights.puzzle * puzzle,
+            shrine: weights.shrine * shrine,
+            ..*weights
+        }
+    }
+
+    /// A random room for the biome, falling back on the settings' weights
+    /// if they leave the biome with no kinds of rooms at all.
+    fn roll_room(self, rng: &mut impl Rng, settings: &GenerationSettings, depth: u32) -> RoomType {
+        let weights = self.room_weights(&settings.rooms);
+        let kind = match weights.total() {
+            0 => settings.rooms.roll(rng),
+            _ => weights.roll(rng),
+        };
+        RoomType::from_kind(kind, rng, settings, depth, Some(self))
+    }
+}
 
 #[derive(Component)]
 pub struct MapTile;
@@ -106,34 +272,575 @@ impl Collapsed {
     }
 }
 
-#[derive(Component, Clone, Copy, Serialize, Deserialize)]
-pub enum Pillars {
-    North,
-    East,
-    South,
-    West,
+/// Everything about a map that is decided by its seed,
+/// worked out before any of it is put on the tiles.
+///
+/// The same settings must always give the same layout, or shared seeds
+/// and old saves would no longer match the world they came from.
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MapLayout {
+    pub shape: MapShape,
+    pub entrance_seed: u64,
+    /// The pillars with their rooms' seeds.
+    pub pillars: Vec<(TilePos, u64)>,
+    /// The rooms between the entrance and the pillars, in the order they were laid out.
+    pub rooms: Vec<(TilePos, RoomType, u64)>,
+}
+
+/// A layout made by hand, in the map editor or as a challenge map,
+/// played in place of generating one from the seed.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct PresetLayout(pub MapLayout);
+
+/// What is wrong with a layout that wasn't generated, like one made in the map editor.
+#[derive(Error, Debug, PartialEq)]
+pub enum LayoutError {
+    #[error("Couldn't read the layout: {0}")]
+    Parse(String),
+    #[error("The room at {0:?} is off the map")]
+    OffMap(TilePos),
+    #[error("There is more than one room at {0:?}")]
+    Overlapping(TilePos),
+    #[error("The layout has no pillars")]
+    NoPillars,
+    #[error("The room at {0:?} can't be reached from the entrance")]
+    Unreachable(TilePos),
+}
+
+/// How far along a layout being generated is, shared with the task generating it.
+#[derive(Debug, Default, Clone)]
+pub struct LayoutProgress(Arc<AtomicU32>);
+
+impl LayoutProgress {
+    fn step(&self) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// How many steps of [`MapLayout::steps`] are done.
+    pub fn steps(&self) -> u32 {
+        self.0.load(Ordering::Relaxed)
+    }
 }
 
-/// Setup for Generation settings so generation is seedable
-fn setup(mut commands: Commands, settings: Res<GenerationSettings>) {
-    let rng = RandomSource::seed_from_u64(settings.seed);
-    commands.insert_resource(GenerationRand(rng));
+/// The task generating the layout of a new world, off the main thread.
+#[derive(Resource)]
+pub struct GenerationTask(Task<MapLayout>);
+
+impl GenerationTask {
+    /// The layout, once the task is done with it.
+    pub fn poll(&mut self) -> Option<MapLayout> {
+        block_on(future::poll_once(&mut self.0))
+    }
+}
+
+impl MapLayout {
+    /// How many steps generating a layout from the settings takes:
+    /// placing the pillars, reaching each of them, then finishing the rooms off.
+    pub fn steps(settings: &GenerationSettings) -> u32 {
+        settings.shape.pillars + 2
+    }
+
+    pub fn generate(settings: &GenerationSettings) -> Self {
+        Self::generate_with_progress(settings, &LayoutProgress::default())
+    }
+
+    /// Generates the layout, counting each of its [`steps`](Self::steps) in `progress` as it goes.
+    pub fn generate_with_progress(
+        settings: &GenerationSettings,
+        progress: &LayoutProgress,
+    ) -> Self {
+        let seed = settings.seed ^ FLOOR_RNG_STREAM.wrapping_mul(settings.floor as u64);
+        let mut rng = RandomSource::seed_from_u64(seed);
+        let shape = settings.shape;
+
+        let pillar_positions = place_pillars(&mut rng, &shape);
+        let entrance_seed = rng.random_range(..u64::MAX);
+        let pillars = pillar_positions
+            .iter()
+            .map(|pos| (*pos, rng.random_range(..u64::MAX)))
+            .collect();
+        progress.step();
+
+        let (positions, mut r_types, seeds) = match settings.mode {
+            GenerationMode::Paths => path_rooms(&mut rng, settings, &pillar_positions, progress),
+            GenerationMode::Constraints => {
+                constraint_rooms(&mut rng, settings, &pillar_positions, progress)
+            }
+        };
+
+        settings.rooms.ensure_minimums(&mut rng, &mut r_types);
+        ensure_healing_near_pillars(&mut rng, &positions, &mut r_types, &pillar_positions);
+        place_stairs(&positions, &mut r_types, &shape.origin());
+
+        let rooms = positions
+            .into_iter()
+            .zip(r_types)
+            .zip(seeds)
+            .map(|((pos, r_type), seed)| (pos, r_type, seed))
+            .collect();
+        progress.step();
+
+        Self {
+            shape,
+            entrance_seed,
+            pillars,
+            rooms,
+        }
+    }
+
+    /// Reads a layout saved as RON, making sure it can be played.
+    pub fn from_ron(ron: &str) -> Result<Self, LayoutError> {
+        let layout: Self = ron::from_str(ron).map_err(|err| LayoutError::Parse(err.to_string()))?;
+        layout.validate()?;
+        Ok(layout)
+    }
+
+    /// Checks that every room is on the map in a place of its own,
+    /// and that the party can walk to all of them from the entrance.
+    pub fn validate(&self) -> Result<(), LayoutError> {
+        if self.pillars.is_empty() {
+            return Err(LayoutError::NoPillars);
+        }
+
+        let origin = self.shape.origin();
+        // Every room as written, so a room placed on a pillar is caught
+        // rather than skipped like in `placed_rooms`.
+        let positions: Vec<TilePos> = std::iter::once(origin)
+            .chain(self.pillars.iter().map(|(pos, _)| *pos))
+            .chain(self.rooms.iter().map(|(pos, ..)| *pos))
+            .collect();
+        for (index, pos) in positions.iter().enumerate() {
+            if !self.shape.contains(pos)
+                || crate::game::hex_distance(pos, &origin) > self.shape.radius
+            {
+                return Err(LayoutError::OffMap(*pos));
+            }
+            if positions[..index].contains(pos) {
+                return Err(LayoutError::Overlapping(*pos));
+            }
+        }
+
+        let mut reached = vec![origin];
+        let mut frontier = VecDeque::from([origin]);
+        while let Some(pos) = frontier.pop_front() {
+            for next in map_neighbors(&pos, &self.shape) {
+                if positions.contains(&next) && !reached.contains(&next) {
+                    reached.push(next);
+                    frontier.push_back(next);
+                }
+            }
+        }
+        match positions.iter().find(|pos| !reached.contains(pos)) {
+            Some(pos) => Err(LayoutError::Unreachable(*pos)),
+            None => Ok(()),
+        }
+    }
+
+    /// The rooms between the entrance and the pillars that end up on the map.
+    /// A path to one pillar can run through another, and the pillar keeps its spot.
+    pub fn placed_rooms(&self) -> impl Iterator<Item = &(TilePos, RoomType, u64)> {
+        self.rooms
+            .iter()
+            .filter(|(pos, ..)| self.pillars.iter().all(|(pillar, _)| pillar != pos))
+    }
+
+    /// Every room of the layout with where it goes, the entrance first.
+    pub fn room_infos(&self) -> Vec<(TilePos, RoomInfo)> {
+        let entrance = RoomInfo::from_type(RoomType::Entrance, self.entrance_seed);
+        let pillars = self
+            .pillars
+            .iter()
+            .map(|(pos, seed)| (*pos, RoomInfo::from_type(RoomType::Pillar, *seed)));
+        let rooms = self
+            .placed_rooms()
+            .map(|(pos, r_type, seed)| (*pos, RoomInfo::from_type(r_type.clone(), *seed)));
+
+        std::iter::once((self.shape.origin(), entrance))
+            .chain(pillars)
+            .chain(rooms)
+            .collect()
+    }
+
+    /// A fingerprint of the layout that stays the same between builds and platforms.
+    pub fn hash(&self) -> u64 {
+        let mut bytes: Vec<u8> = Vec::new();
+        let mut write_room = |pos: &TilePos, kind: String, seed: u64| {
+            bytes.extend(pos.x.to_le_bytes());
+            bytes.extend(pos.y.to_le_bytes());
+            bytes.extend(kind.as_bytes());
+            bytes.extend(seed.to_le_bytes());
+        };
+
+        write_room(
+            &self.shape.origin(),
+            format!("{:?}", RoomType::Entrance),
+            self.entrance_seed,
+        );
+        for (index, (pos, seed)) in self.pillars.iter().enumerate() {
+            let name = PILLAR_NAMES.get(index).copied().unwrap_or("Pillar");
+            write_room(pos, name.to_string(), *seed);
+        }
+        for (pos, r_type, seed) in &self.rooms {
+            write_room(pos, format!("{r_type:?}"), *seed);
+        }
+
+        // FNV-1a, as the std hashers are free to change between releases.
+        bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ *byte as u64).wrapping_mul(0x0100_0000_01b3)
+        })
+    }
+}
+
+/// Where the pillars go. Four pillars get a side of the map each, as maps have always had,
+/// any other number are spread evenly around the rings near the edge of the map.
+fn place_pillars(rng: &mut impl Rng, shape: &MapShape) -> Vec<TilePos> {
+    let radius = shape.radius;
+    if shape.pillars == 4 {
+        // How far the pillars are from the middle, along and across their side of the map.
+        let far = radius * 3 / 5;
+        let farther = radius * 4 / 5;
+        let across = radius / 5;
+
+        let north = TilePos {
+            x: rng.random_range(radius - far..=radius),
+            y: rng.random_range(radius + far..=radius + radius),
+        };
+        let east = TilePos {
+            x: rng.random_range(radius - farther..=radius - far),
+            y: rng.random_range(radius - across..=radius + across),
+        };
+        let south = TilePos {
+            x: rng.random_range(radius..=radius + far),
+            y: rng.random_range(0..=radius - far),
+        };
+        let west = TilePos {
+            x: rng.random_range(radius + far..=radius + farther),
+            y: rng.random_range(radius - across..=radius + across),
+        };
+        return vec![north, east, south, west];
+    }
+
+    let origin = AxialPos::from(shape.origin());
+    let count = shape.pillars as usize;
+    (0..count)
+        .map(|index| {
+            let ring = generate_hex_ring(origin, rng.random_range(radius * 3 / 5..=radius));
+            let sector = ring.len() / count;
+            ring[index * sector + rng.random_range(0..sector)].as_tile_pos_unchecked()
+        })
+        .collect()
+}
+
+/// The rooms, their types and their seeds, in the order they were laid out.
+type LaidOutRooms = (Vec<TilePos>, Vec<RoomType>, Vec<u64>);
+
+/// How deep a room counts for its encounter budget.
+fn room_depth(pos: &TilePos, settings: &GenerationSettings) -> u32 {
+    crate::game::hex_distance(pos, &settings.shape.origin()) + settings.floor * DEPTH_PER_FLOOR
+}
+
+/// Lays out rooms along the shortest path from the entrance to each pillar.
+fn path_rooms(
+    rng: &mut impl Rng,
+    settings: &GenerationSettings,
+    pillars: &[TilePos],
+    progress: &LayoutProgress,
+) -> LaidOutRooms {
+    let mut positions: Vec<TilePos> = Vec::new();
+    let mut r_types: Vec<RoomType> = Vec::new();
+    let mut seeds: Vec<u64> = Vec::new();
+    let shape = &settings.shape;
+    for pillar in pillars {
+        let mut current_pos: TilePos = shape.origin();
+
+        while current_pos != *pillar {
+            let mut least: u32 = 20;
+            let mut next = current_pos;
+
+            for neighbor in map_neighbors(&current_pos, shape) {
+                let x_diff = pillar.x.abs_diff(neighbor.x);
+                let y_diff = pillar.y.abs_diff(neighbor.y);
+
+                if least > x_diff + y_diff {
+                    least = x_diff + y_diff;
+                    next = neighbor;
+                }
+            }
+
+            current_pos = next;
+
+            if current_pos == *pillar || positions.contains(&current_pos) {
+                continue;
+            }
+
+            positions.push(current_pos);
+            r_types.push(RoomType::from_rng(
+                rng,
+                settings,
+                room_depth(&current_pos, settings),
+            ));
+            seeds.push(rng.random_range(..u64::MAX));
+        }
+        progress.step();
+    }
+
+    (positions, r_types, seeds)
+}
+
+/// Fills the map with biomes, carves a way through the rock to each pillar,
+/// and lays out a room in every open position that can be reached from the entrance.
+fn constraint_rooms(
+    rng: &mut impl Rng,
+    settings: &GenerationSettings,
+    pillars: &[TilePos],
+    progress: &LayoutProgress,
+) -> LaidOutRooms {
+    let shape = &settings.shape;
+    let origin = shape.origin();
+    let mut fixed = vec![(origin, Biome::Halls)];
+    fixed.extend(pillars.iter().map(|pos| (*pos, Biome::Lair)));
+    let mut biomes = collapse_biomes(rng, shape, &fixed);
+
+    for pillar in pillars {
+        for pos in cheapest_path(&biomes, shape, origin, *pillar) {
+            for (cell, biome) in biomes.iter_mut() {
+                // Halls can border anything, so carving never breaks the rules.
+                if *cell == pos && *biome == Biome::Solid {
+                    *biome = Biome::Halls;
+                }
+            }
+        }
+        progress.step();
+    }
+
+    let open = |pos: &TilePos| {
+        biomes
+            .iter()
+            .find(|(cell, biome)| cell == pos && *biome != Biome::Solid)
+            .map(|(_, biome)| *biome)
+    };
+    let mut positions: Vec<TilePos> = Vec::new();
+    let mut r_types: Vec<RoomType> = Vec::new();
+    let mut seeds: Vec<u64> = Vec::new();
+    let mut seen = vec![origin];
+    let mut queue = VecDeque::from([origin]);
+    while let Some(pos) = queue.pop_front() {
+        for next in map_neighbors(&pos, shape) {
+            let Some(biome) = open(&next).filter(|_| !seen.contains(&next)) else {
+                continue;
+            };
+            seen.push(next);
+            queue.push_back(next);
+            if pillars.contains(&next) {
+                continue;
+            }
+
+            positions.push(next);
+            r_types.push(biome.roll_room(rng, settings, room_depth(&next, settings)));
+            seeds.push(rng.random_range(..u64::MAX));
+        }
+    }
+
+    (positions, r_types, seeds)
+}
+
+/// Fills every position on the map with a biome, so that every two neighbors can border each other.
+///
+/// Starting from the `fixed` biomes, the position with the fewest biomes left is picked at random
+/// from those, and the biomes that can't border it are ruled out of its neighbors, until every
+/// position is down to one. Halls and rock can border anything, so there is always one left.
+pub fn collapse_biomes(
+    rng: &mut impl Rng,
+    shape: &MapShape,
+    fixed: &[(TilePos, Biome)],
+) -> Vec<(TilePos, Biome)> {
+    let size = shape.size();
+    let cells: Vec<TilePos> = (0..size.x)
+        .flat_map(|x| (0..size.y).map(move |y| TilePos { x, y }))
+        .filter(|pos| crate::game::hex_distance(pos, &shape.origin()) <= shape.radius)
+        .collect();
+    let all = Biome::iter().fold(0, |options, biome| options | biome.bit());
+    let mut options = vec![all; cells.len()];
+
+    for (pos, biome) in fixed {
+        if let Some(cell) = cells.iter().position(|cell| cell == pos) {
+            options[cell] = biome.bit();
+            propagate_biomes(&cells, shape, &mut options, cell);
+        }
+    }
+
+    while let Some(cell) = (0..cells.len())
+        .filter(|cell| options[*cell].count_ones() > 1)
+        .min_by_key(|cell| options[*cell].count_ones())
+    {
+        let choices: Vec<Biome> = Biome::iter()
+            .filter(|biome| options[cell] & biome.bit() != 0)
+            .collect();
+        let mut roll = rng.random_range(0..choices.iter().map(|biome| biome.weight()).sum::<u32>());
+        let biome = choices
+            .into_iter()
+            .find(|biome| match roll.checked_sub(biome.weight()) {
+                Some(rest) => {
+                    roll = rest;
+                    false
+                }
+                None => true,
+            })
+            .unwrap();
+
+        options[cell] = biome.bit();
+        propagate_biomes(&cells, shape, &mut options, cell);
+    }
+
+    cells
+        .into_iter()
+        .zip(options)
+        .map(|(pos, options)| {
+            let biome = Biome::iter().find(|biome| options & biome.bit() != 0);
+            (pos, biome.unwrap_or(Biome::Halls))
+        })
+        .collect()
+}
+
+/// Rules out the biomes that can't border what is left of `from`, spreading out from there.
+fn propagate_biomes(cells: &[TilePos], shape: &MapShape, options: &mut [u8], from: usize) {
+    let mut queue = VecDeque::from([from]);
+    while let Some(cell) = queue.pop_front() {
+        let allowed = Biome::iter()
+            .filter(|next| {
+                Biome::iter()
+                    .any(|biome| options[cell] & biome.bit() != 0 && biome.can_border(*next))
+            })
+            .fold(0, |allowed, next| allowed | next.bit());
+
+        for neighbor in map_neighbors(&cells[cell], shape) {
+            let Some(neighbor) = cells.iter().position(|pos| *pos == neighbor) else {
+                continue;
+            };
+            let narrowed = options[neighbor] & allowed;
+            if narrowed != options[neighbor] {
+                options[neighbor] = narrowed;
+                queue.push_back(neighbor);
+            }
+        }
+    }
+}
+
+/// The positions on the cheapest way from `from` to `to`, not counting `from`,
+/// where going through rock costs [`CARVE_COST`] rooms.
+fn cheapest_path(
+    biomes: &[(TilePos, Biome)],
+    shape: &MapShape,
+    from: TilePos,
+    to: TilePos,
+) -> Vec<TilePos> {
+    let index = |pos: &TilePos| biomes.iter().position(|(cell, _)| cell == pos);
+    let (Some(start), Some(end)) = (index(&from), index(&to)) else {
+        return Vec::new();
+    };
+
+    let mut cost = vec![u32::MAX; biomes.len()];
+    let mut came_from: Vec<Option<usize>> = vec![None; biomes.len()];
+    let mut queue = BinaryHeap::from([Reverse((0, start))]);
+    cost[start] = 0;
+    while let Some(Reverse((so_far, cell))) = queue.pop() {
+        if cell == end {
+            break;
+        }
+        if so_far > cost[cell] {
+            continue;
+        }
+
+        for next in map_neighbors(&biomes[cell].0, shape).filter_map(|pos| index(&pos)) {
+            let step = match biomes[next].1 {
+                Biome::Solid => CARVE_COST,
+                _ => 1,
+            };
+            if so_far + step < cost[next] {
+                cost[next] = so_far + step;
+                came_from[next] = Some(cell);
+                queue.push(Reverse((cost[next], next)));
+            }
+        }
+    }
+
+    let mut path = Vec::new();
+    let mut cell = end;
+    while let Some(previous) = came_from[cell] {
+        path.push(biomes[cell].0);
+        cell = previous;
+    }
+    path
+}
+
+/// The positions next to `pos` that are on the map,
+/// in the same order as [`HexNeighbors::get_neighboring_positions_standard`].
+fn map_neighbors(pos: &TilePos, shape: &MapShape) -> impl Iterator<Item = TilePos> {
+    let pos = *pos;
+    let shape = *shape;
+    EntranceDirection::ALL.into_iter().filter_map(move |dir| {
+        let offset = dir.axial_offset();
+        let x = pos.x.checked_add_signed(offset.q)?;
+        let y = pos.y.checked_add_signed(offset.r)?;
+        let next = TilePos { x, y };
+        shape.contains(&next).then_some(next)
+    })
+}
+
+/// Starts working out the layout of the map from the settings, off the main thread.
+fn start_generation(
+    mut commands: Commands,
+    settings: Res<GenerationSettings>,
+    preset: Option<Res<PresetLayout>>,
+    mut generation_progress: ResMut<GenerationProgress>,
+) {
+    if let Some(preset) = preset {
+        let layout = preset.0.clone();
+        info!("Playing preset layout {:016x}", layout.hash());
+        generation_progress.layout_steps = 0;
+        let task = AsyncComputeTaskPool::get().spawn(async move { layout });
+        commands.insert_resource(GenerationTask(task));
+        return;
+    }
+
+    let settings = settings.clone();
+    let progress = LayoutProgress::default();
+    generation_progress.layout = progress.clone();
+    generation_progress.layout_steps = MapLayout::steps(&settings);
+
+    let task = AsyncComputeTaskPool::get().spawn(async move {
+        let layout = MapLayout::generate_with_progress(&settings, &progress);
+        info!(
+            "Generated layout {:016x} from seed {}",
+            layout.hash(),
+            settings.seed
+        );
+        layout
+    });
+    commands.insert_resource(GenerationTask(task));
+}
+
+/// Puts the layout on the tiles of a new map, once the task generating it is done.
+pub fn place_layout(commands: &mut Commands, layout: MapLayout) {
+    commands.insert_resource(layout);
+    commands.run_system_cached(spawn_map);
+    #[cfg(feature = "debug")]
+    commands.run_system_cached(spawn_tile_labels::<With<MapTilemap>, With<MapTile>>);
+    commands.run_system_cached(create_origin_and_pillars);
+    commands.run_system_cached(build_paths);
 }
 
 /// Spawns tilemap
-fn spawn_map(mut commands: Commands, asset_server: Res<AssetServer>) {
+fn spawn_map(mut commands: Commands, asset_server: Res<AssetServer>, layout: Res<MapLayout>) {
     let tile_sprite = asset_server.load(MAP_TILE_ASSET_LOAD_PATH);
     let tilemap_entity = commands.spawn_empty().id();
+    let shape = layout.shape;
 
-    let mut tile_storage = TileStorage::empty(MAP_SIZE);
-    let origin = TilePos {
-        x: MAP_SIZE.x / 2,
-        y: MAP_SIZE.y / 2,
-    };
+    let mut tile_storage = TileStorage::empty(shape.size());
 
     let tile_positions = generate_hexagon(
-        AxialPos::from_tile_pos_given_coord_system(&origin, MAP_COORD_SYSTEM),
-        MAP_RADIUS,
+        AxialPos::from_tile_pos_given_coord_system(&shape.origin(), MAP_COORD_SYSTEM),
+        shape.radius,
     )
     .into_iter()
     .map(|axial_pos| axial_pos.as_tile_pos_given_coord_system(MAP_COORD_SYSTEM));
@@ -157,10 +864,12 @@ fn spawn_map(mut commands: Commands, asset_server: Res<AssetServer>) {
 
     commands.entity(tilemap_entity).insert((
         MapTilemap,
+        // So rooms can be pinned from their right click menu.
+        Pickable::default(),
         TilemapBundle {
             grid_size: MAP_TILE_SIZE.into(),
             map_type: TilemapType::Hexagon(MAP_COORD_SYSTEM),
-            size: MAP_SIZE,
+            size: shape.size(),
             storage: tile_storage,
             texture: TilemapTexture::Single(tile_sprite),
             tile_size: MAP_TILE_SIZE,
@@ -172,177 +881,129 @@ fn spawn_map(mut commands: Commands, asset_server: Res<AssetServer>) {
     ));
 }
 
-/// finds the origin of the Map
+/// Marks the entrance and the pillars on the map
 fn create_origin_and_pillars(
     mut commands: Commands,
     tilestorage_q: Query<&mut TileStorage, With<MapTilemap>>,
-    mut tile_rand: ResMut<GenerationRand>,
+    layout: Res<MapLayout>,
     mut tile_text_q: Query<&mut TileTextureIndex>,
 ) {
-    let north_tile_pos: TilePos = TilePos {
-        x: tile_rand
-            .0
-            .random_range(MAP_RADIUS - PILLAR_OFFSET_VERT..=MAP_RADIUS),
-        y: tile_rand
-            .0
-            .random_range(MAP_RADIUS + PILLAR_OFFSET_VERT..=MAP_RADIUS + MAP_RADIUS),
-    };
-    let east_tile_pos: TilePos = TilePos {
-        x: tile_rand
-            .0
-            .random_range(MAP_RADIUS - PILLAR_OFFSET_HORZ_X..=MAP_RADIUS - PILLAR_OFFSET_VERT),
-        y: tile_rand
-            .0
-            .random_range(MAP_RADIUS - PILLAR_OFFSET_HORZ_Y..=MAP_RADIUS + PILLAR_OFFSET_HORZ_Y),
-    };
-    let south_tile_pos: TilePos = TilePos {
-        x: tile_rand
-            .0
-            .random_range(MAP_RADIUS..=MAP_RADIUS + PILLAR_OFFSET_VERT),
-        y: tile_rand
-            .0
-            .random_range(MAP_RADIUS - MAP_RADIUS..=MAP_RADIUS - PILLAR_OFFSET_VERT),
-    };
-    let west_tile_pos: TilePos = TilePos {
-        x: tile_rand
-            .0
-            .random_range(MAP_RADIUS + PILLAR_OFFSET_VERT..=MAP_RADIUS + PILLAR_OFFSET_HORZ_X),
-        y: tile_rand
-            .0
-            .random_range(MAP_RADIUS - PILLAR_OFFSET_HORZ_Y..=MAP_RADIUS + PILLAR_OFFSET_HORZ_Y),
-    };
     for tile_storage in &tilestorage_q {
         let start = tile_storage
-            .get(&MAP_ORIGIN)
+            .get(&layout.shape.origin())
             .expect("The origin should exist, as we just made it...");
-        let north = tile_storage
-            .get(&north_tile_pos)
-            .expect("The north should exist");
-        let east = tile_storage
-            .get(&east_tile_pos)
-            .expect("The east should exist");
-        let south = tile_storage
-            .get(&south_tile_pos)
-            .expect("The south should exist");
-        let west = tile_storage
-            .get(&west_tile_pos)
-            .expect("The west should exist");
 
         let collapsed = Collapsed::Red;
 
         let mut tile_texture = tile_text_q.get_mut(start).unwrap();
         *tile_texture = collapsed.to_texture();
 
-        let mut tile_text_north = tile_text_q.get_mut(north).unwrap();
-        *tile_text_north = collapsed.to_texture();
-
-        let mut tile_text_east = tile_text_q.get_mut(east).unwrap();
-        *tile_text_east = collapsed.to_texture();
-
-        let mut tile_text_south = tile_text_q.get_mut(south).unwrap();
-        *tile_text_south = collapsed.to_texture();
-
-        let mut tile_text_west = tile_text_q.get_mut(west).unwrap();
-        *tile_text_west = collapsed.to_texture();
-
         commands.entity(start).insert((
             collapsed,
-            RoomInfo::from_type(RoomType::Entrance, tile_rand.random_range(..u64::MAX)),
-        ));
-        commands.entity(north).insert((
-            Pillars::North,
-            collapsed,
-            RoomInfo::from_type(RoomType::Pillar, tile_rand.random_range(..u64::MAX)),
-        ));
-        commands.entity(east).insert((
-            Pillars::East,
-            collapsed,
-            RoomInfo::from_type(RoomType::Pillar, tile_rand.random_range(..u64::MAX)),
-        ));
-        commands.entity(south).insert((
-            Pillars::South,
-            collapsed,
-            RoomInfo::from_type(RoomType::Pillar, tile_rand.random_range(..u64::MAX)),
-        ));
-        commands.entity(west).insert((
-            Pillars::West,
-            collapsed,
-            RoomInfo::from_type(RoomType::Pillar, tile_rand.random_range(..u64::MAX)),
+            RoomInfo::from_type(RoomType::Entrance, layout.entrance_seed),
         ));
+
+        for (pos, seed) in &layout.pillars {
+            let tile = tile_storage
+                .get(pos)
+                .unwrap_or_else(|| panic!("The pillar at {pos:?} should exist"));
+
+            let mut tile_texture = tile_text_q.get_mut(tile).unwrap();
+            *tile_texture = collapsed.to_texture();
+
+            commands
+                .entity(tile)
+                .insert((collapsed, RoomInfo::from_type(RoomType::Pillar, *seed)));
+        }
     }
 }
 
+/// Lays out the rooms on the paths from the entrance to each pillar
 fn build_paths(
     mut commands: Commands,
-    pillars_q: Query<&TilePos, With<Pillars>>,
     tilestorage_q: Query<&mut TileStorage, With<MapTilemap>>,
     mut tile_text_q: Query<&mut TileTextureIndex>,
-    mut rng: ResMut<GenerationRand>,
+    layout: Res<MapLayout>,
     mut generation_progress: ResMut<GenerationProgress>,
 ) {
-    let mut seen: Vec<TilePos> = Vec::new();
     for tile_storage in tilestorage_q {
-        for pillar in pillars_q {
-            let mut current_pos: TilePos = TilePos { x: 5, y: 5 };
+        for (pos, r_type, rng_seed) in layout.placed_rooms() {
+            let tile = tile_storage.get(pos).unwrap();
 
-            while current_pos.x != pillar.x || current_pos.y != pillar.y {
-                let neighbors = HexNeighbors::<TilePos>::get_neighboring_positions_standard(
-                    &current_pos,
-                    &MAP_SIZE,
-                );
+            let mut texture = tile_text_q.get_mut(tile).unwrap();
+            *texture = Collapsed::Gray.to_texture();
 
-                let mut least: u32 = 20;
-                let mut store_x: u32 = current_pos.x;
-                let mut store_y: u32 = current_pos.y;
-
-                for neighbor in neighbors.iter() {
-                    let x_diff = ((pillar.x as i32) - (neighbor.x as i32)).abs() as u32;
-                    let y_diff = ((pillar.y as i32) - (neighbor.y as i32)).abs() as u32;
-
-                    if least > x_diff + y_diff {
-                        least = x_diff + y_diff;
-                        store_x = neighbor.x;
-                        store_y = neighbor.y;
-                    }
-                }
-
-                current_pos.x = store_x;
-                current_pos.y = store_y;
-
-                if current_pos.x == pillar.x && current_pos.y == pillar.y {
-                    break;
-                }
+            commands.entity(tile).insert((
+                Collapsed::Gray,
+                RoomInfo::from_type(r_type.clone(), *rng_seed),
+            ));
+        }
+    }
+    generation_progress.world_done = true;
+}
 
-                let mut check = true;
+/// Makes sure every pillar has a Healing Potion within [`HEALING_RADIUS`] rooms,
+/// so a run can't be lost to the seed alone.
+/// `positions` are where each of the `rooms` are on the map.
+pub fn ensure_healing_near_pillars(
+    rng: &mut impl Rng,
+    positions: &[TilePos],
+    rooms: &mut [RoomType],
+    pillars: &[TilePos],
+) {
+    for pillar in pillars {
+        let nearby: Vec<usize> = positions
+            .iter()
+            .enumerate()
+            .filter(|(_, pos)| crate::game::hex_distance(pillar, pos) <= HEALING_RADIUS)
+            .map(|(index, _)| index)
+            .collect();
 
-                for seen_idx in 0..seen.len() {
-                    let tile_pos: &TilePos = seen.get(seen_idx).unwrap();
-                    if tile_pos.x == current_pos.x && tile_pos.y == current_pos.y {
-                        check = false;
-                        break;
-                    }
-                }
+        if nearby
+            .iter()
+            .any(|index| rooms[*index] == RoomType::Item(Item::HealingPotion))
+        {
+            continue;
+        }
 
-                if check {
-                    seen.push(current_pos);
+        // Empty rooms are left alone, as every map needs some.
+        let replaceable: Vec<usize> = nearby
+            .iter()
+            .copied()
+            .filter(|index| rooms[*index] != RoomType::EmptyRoom)
+            .collect();
+        let candidates = if replaceable.is_empty() {
+            &nearby
+        } else {
+            &replaceable
+        };
+        if candidates.is_empty() {
+            warn!("No rooms near the pillar at {pillar:?} to leave a Healing Potion in");
+            continue;
+        }
 
-                    let selected_tile = tile_storage.get(&current_pos).unwrap();
+        let index = candidates[rng.random_range(0..candidates.len())];
+        rooms[index] = RoomType::Item(Item::HealingPotion);
+    }
+}
 
-                    let mut selected_texture = tile_text_q.get_mut(selected_tile).unwrap();
-                    *selected_texture = Collapsed::Gray.to_texture();
+/// Turns the room furthest from the entrance into the stairs down to the next floor.
+/// Healing Potions and empty rooms are left alone, as they are guaranteed.
+pub fn place_stairs(positions: &[TilePos], rooms: &mut [RoomType], origin: &TilePos) {
+    let furthest = positions
+        .iter()
+        .zip(rooms.iter())
+        .enumerate()
+        .filter(|(_, (_, room))| {
+            **room != RoomType::Item(Item::HealingPotion) && **room != RoomType::EmptyRoom
+        })
+        .max_by_key(|(index, (pos, _))| (crate::game::hex_distance(pos, origin), Reverse(*index)))
+        .map(|(index, _)| index);
 
-                    commands.entity(selected_tile).insert((
-                        Collapsed::Gray,
-                        RoomInfo::from_type(
-                            RoomType::from_rng(&mut *rng),
-                            rng.random_range(..u64::MAX),
-                        ),
-                    ));
-                }
-            }
-        }
+    match furthest {
+        Some(index) => rooms[index] = RoomType::Stairs,
+        None => warn!("No room on the map to put the stairs in"),
     }
-    generation_progress.world_done = true;
 }
 
 fn despawn_outline_tiles(
@@ -361,3 +1022,315 @@ fn despawn_outline_tiles(
         })
         .for_each(|entity| commands.entity(entity).despawn());
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game::hex_distance;
+
+    /// The rooms on the way from `from` to `to`, not counting either end.
+    fn path_between(from: TilePos, to: TilePos) -> Vec<TilePos> {
+        let mut path = Vec::new();
+        let mut pos = from;
+        while pos != to {
+            pos = EntranceDirection::ALL
+                .iter()
+                .map(|dir| {
+                    let offset = dir.axial_offset();
+                    TilePos::new(
+                        (pos.x as i32 + offset.q) as u32,
+                        (pos.y as i32 + offset.r) as u32,
+                    )
+                })
+                .min_by_key(|next| hex_distance(next, &to))
+                .unwrap();
+            if pos != to {
+                path.push(pos);
+            }
+        }
+        path
+    }
+
+    /// Layout hashes for a handful of seeds, so any change to what a seed generates
+    /// is caught. Shared seeds and saved games rely on these staying put, so only
+    /// change them on purpose, alongside a bump of the save version.
+    const GOLDEN_LAYOUTS: [(u64, Difficulty, u64); 6] = [
+        (0, Difficulty::Normal, 0xbd0e32121a38f33b),
+        (1, Difficulty::Normal, 0x75cbda4ce0071ba0),
+        (42, Difficulty::Normal, 0xf2972abee584270f),
+        (0x5eed, Difficulty::Easy, 0x8c9da54413ed8da3),
+        (0x5eed, Difficulty::Hard, 0x70db8630a1481fb0),
+        (u64::MAX, Difficulty::Normal, 0x8d93d2a07fcde294),
+    ];
+
+    #[test]
+    fn seeds_keep_their_layouts() {
+        for (seed, difficulty, hash) in GOLDEN_LAYOUTS {
+            let settings = GenerationSettings {
+                seed,
+                difficulty,
+                ..default()
+            };
+            let layout = MapLayout::generate(&settings);
+
+            assert_eq!(layout, MapLayout::generate(&settings));
+            assert_eq!(
+                layout.hash(),
+                hash,
+                "seed {seed} on {difficulty:?} generated a different map: {layout:#?}"
+            );
+        }
+    }
+
+    #[test]
+    fn layouts_reach_every_pillar() {
+        let shapes = MapShape::RADII
+            .flat_map(|radius| MapShape::PILLARS.map(move |pillars| MapShape { radius, pillars }));
+        for shape in shapes {
+            for seed in 0..30 {
+                let layout = MapLayout::generate(&GenerationSettings {
+                    seed,
+                    shape,
+                    ..default()
+                });
+                assert_eq!(layout.pillars.len(), shape.pillars as usize);
+
+                let infos = layout.room_infos();
+                for (pos, _) in &infos {
+                    assert!(hex_distance(pos, &shape.origin()) <= shape.radius);
+                    assert_eq!(infos.iter().filter(|(other, _)| other == pos).count(), 1);
+                }
+
+                // Paths to one pillar may run through another, but every pillar can be walked to.
+                let mut reached = vec![shape.origin()];
+                let mut index = 0;
+                while index < reached.len() {
+                    let pos = reached[index];
+                    for (next, _) in &infos {
+                        if hex_distance(&pos, next) == 1 && !reached.contains(next) {
+                            reached.push(*next);
+                        }
+                    }
+                    index += 1;
+                }
+                for (pos, _) in &layout.pillars {
+                    assert!(
+                        reached.contains(pos),
+                        "seed {seed} on {shape:?} has no path to the pillar at {pos:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn generation_counts_its_steps_and_layouts_round_trip() {
+        for mode in GenerationMode::iter() {
+            let settings = GenerationSettings {
+                seed: 0x7a5c,
+                mode,
+                ..default()
+            };
+            let progress = LayoutProgress::default();
+            let layout = MapLayout::generate_with_progress(&settings, &progress);
+            assert_eq!(progress.steps(), MapLayout::steps(&settings));
+            assert_eq!(layout, MapLayout::generate(&settings));
+
+            let ron = ron::to_string(&layout).unwrap();
+            assert_eq!(ron::from_str::<MapLayout>(&ron).unwrap(), layout);
+        }
+    }
+
+    #[test]
+    fn every_floor_has_its_own_layout_and_stairs() {
+        for seed in 0..50 {
+            let floors: Vec<MapLayout> = (0..3)
+                .map(|floor| {
+                    MapLayout::generate(&GenerationSettings {
+                        seed,
+                        floor,
+                        ..default()
+                    })
+                })
+                .collect();
+
+            for layout in &floors {
+                let stairs = layout
+                    .rooms
+                    .iter()
+                    .filter(|(_, r_type, _)| *r_type == RoomType::Stairs)
+                    .count();
+                assert_eq!(stairs, 1, "seed {seed} has {stairs} stairs");
+            }
+            assert_ne!(floors[0], floors[1]);
+            assert_ne!(floors[1], floors[2]);
+        }
+    }
+
+    #[test]
+    fn constraint_layouts_keep_to_their_rules() {
+        for seed in 0..50 {
+            let settings = GenerationSettings {
+                seed,
+                mode: GenerationMode::Constraints,
+                ..default()
+            };
+            let layout = MapLayout::generate(&settings);
+            assert_eq!(layout, MapLayout::generate(&settings));
+            assert_ne!(
+                layout,
+                MapLayout::generate(&GenerationSettings { seed, ..default() })
+            );
+
+            // Every room and pillar can be walked to from the entrance.
+            let infos = layout.room_infos();
+            let mut reached = vec![layout.shape.origin()];
+            let mut index = 0;
+            while index < reached.len() {
+                let pos = reached[index];
+                for (next, _) in &infos {
+                    if hex_distance(&pos, next) == 1 && !reached.contains(next) {
+                        reached.push(*next);
+                    }
+                }
+                index += 1;
+            }
+            assert_eq!(reached.len(), infos.len(), "seed {seed} has rooms cut off");
+
+            let stairs = layout
+                .rooms
+                .iter()
+                .filter(|(_, r_type, _)| *r_type == RoomType::Stairs)
+                .count();
+            assert_eq!(stairs, 1, "seed {seed} has {stairs} stairs");
+        }
+
+        let mut rng = RandomSource::seed_from_u64(7);
+        let shape = MapShape::default();
+        let biomes = collapse_biomes(&mut rng, &shape, &[(shape.origin(), Biome::Ruins)]);
+        assert!(biomes.contains(&(shape.origin(), Biome::Ruins)));
+        for (pos, biome) in &biomes {
+            for (next, other) in &biomes {
+                assert!(
+                    hex_distance(pos, next) != 1 || biome.can_border(*other),
+                    "{biome:?} at {pos:?} borders {other:?} at {next:?}"
+                );
+            }
+        }
+        for biome in Biome::iter() {
+            assert!(biomes.iter().any(|(_, placed)| *placed == biome));
+        }
+    }
+
+    #[test]
+    fn every_pillar_has_healing_nearby() {
+        // No random items at all, so every potion has to come from the adjustment.
+        let settings = GenerationSettings {
+            rooms: RoomWeights {
+                item: 0,
+                min_item: 0,
+                ..default()
+            },
+            ..default()
+        };
+
+        let origin = MapShape::default().origin();
+        for seed in 0..200 {
+            let mut rng = RandomSource::seed_from_u64(seed);
+            // Spread out like on a real map, so no pillar's path runs through another.
+            let mut pillars: Vec<TilePos> = Vec::new();
+            while pillars.len() < 4 {
+                let pos = TilePos::new(rng.random_range(0..11), rng.random_range(0..11));
+                if (3..=MAP_RADIUS).contains(&hex_distance(&pos, &origin))
+                    && pillars
+                        .iter()
+                        .all(|pillar| hex_distance(pillar, &pos) > HEALING_RADIUS)
+                {
+                    pillars.push(pos);
+                }
+            }
+
+            let mut positions: Vec<TilePos> = Vec::new();
+            for pillar in &pillars {
+                for pos in path_between(origin, *pillar) {
+                    if !positions.contains(&pos) && !pillars.contains(&pos) {
+                        positions.push(pos);
+                    }
+                }
+            }
+            let mut rooms: Vec<RoomType> = positions
+                .iter()
+                .map(|_| RoomType::from_rng(&mut rng, &settings, 0))
+                .collect();
+
+            ensure_healing_near_pillars(&mut rng, &positions, &mut rooms, &pillars);
+
+            for pillar in &pillars {
+                assert!(
+                    positions.iter().zip(&rooms).any(|(pos, room)| {
+                        hex_distance(pillar, pos) <= HEALING_RADIUS
+                            && *room == RoomType::Item(Item::HealingPotion)
+                    }),
+                    "seed {seed} has no healing near the pillar at {pillar:?}"
+                );
+            }
+            let potions = rooms
+                .iter()
+                .filter(|room| **room == RoomType::Item(Item::HealingPotion))
+                .count();
+            assert!(potions <= pillars.len());
+        }
+    }
+
+    #[test]
+    fn hand_made_layouts_are_checked() {
+        let layout = MapLayout::generate(&GenerationSettings::default());
+        let ron = ron::to_string(&layout).unwrap();
+        assert_eq!(MapLayout::from_ron(&ron), Ok(layout));
+        assert!(matches!(
+            MapLayout::from_ron("not a layout"),
+            Err(LayoutError::Parse(_))
+        ));
+
+        let shape = MapShape::default();
+        let origin = shape.origin();
+        let east = |steps| TilePos::new(origin.x + steps, origin.y);
+        let small = MapLayout {
+            shape,
+            entrance_seed: 1,
+            pillars: vec![(east(2), 2)],
+            rooms: vec![(east(1), RoomType::EmptyRoom, 3)],
+        };
+        assert_eq!(small.validate(), Ok(()));
+
+        let broken = [
+            (vec![], small.rooms.clone(), LayoutError::NoPillars),
+            (
+                vec![(east(shape.radius + 1), 2)],
+                small.rooms.clone(),
+                LayoutError::OffMap(east(shape.radius + 1)),
+            ),
+            (
+                small.pillars.clone(),
+                vec![
+                    (east(1), RoomType::EmptyRoom, 3),
+                    (east(2), RoomType::Shrine, 4),
+                ],
+                LayoutError::Overlapping(east(2)),
+            ),
+            (
+                small.pillars.clone(),
+                vec![],
+                LayoutError::Unreachable(east(2)),
+            ),
+        ];
+        for (pillars, rooms, err) in broken {
+            let layout = MapLayout {
+                pillars,
+                rooms,
+                ..small.clone()
+            };
+            assert_eq!(layout.validate(), Err(err));
+        }
+    }
+}
@@ -13,9 +13,14 @@ pub const HP_BAR_IMAGE_PATH: &str = "embedded://assets/sprites/HP-Bar.png";
 pub const PRIESTESS_IMAGE_PATH: &str = "embedded://assets/sprites/Priestess_name.png";
 pub const THIEF_IMAGE_PATH: &str = "embedded://assets/sprites/Thief_name.png";
 pub const WARRIOR_IMAGE_PATH: &str = "embedded://assets/sprites/Warrior_name.png";
+pub const SKULL_IMAGE_PATH: &str = "embedded://assets/sprites/skull.png";
 
 pub const FONT_SIZE: f32 = 18.0;
 pub const STANDARD_FLEX_GROW: f32 = 1.75;
+/// Tint for the health of a party member that has died.
+const GHOST_COLOR: Color = Color::srgba(0.4, 0.4, 0.4, 0.6);
+/// Where each party member's health is along the top of the screen.
+const HP_TEXT_LEFT: [f32; 3] = [56.5, 177.5, 297.5];
 
 impl Plugin for HpPlugin {
     fn build(&self, app: &mut App) {
@@ -24,21 +29,69 @@ impl Plugin for HpPlugin {
         embed_asset!(app, "assets/sprites/Priestess_name.png");
         embed_asset!(app, "assets/sprites/Thief_name.png");
         embed_asset!(app, "assets/sprites/Warrior_name.png");
-        app.add_systems(OnEnter(AppState::Game), (create_hp_bars, spawn_hp).chain());
+        embed_asset!(app, "assets/sprites/skull.png");
+        app.add_systems(
+            OnEnter(AppState::Game),
+            (
+                create_hp_bars,
+                spawn_hp,
+                spawn_death_markers,
+                spawn_status_icons,
+                ghost_dead_heroes,
+            )
+                .chain(),
+        )
+        .add_systems(Update, update_status_icons.run_if(in_state(AppState::Game)))
+        .add_observer(ghost_on_health_change);
     }
 }
 
 #[derive(Component)]
 pub struct HPBar;
 
-fn create_hp_bars(mut commands: Commands, asset_server: Res<AssetServer>) {
+/// The bar behind a party member's health, grayed out once they die.
+#[derive(Component)]
+struct HpSegment;
+
+/// The skull and hint shown over a party member that has died.
+#[derive(Component)]
+struct DeathMarker;
+
+/// The effects on a party member, shown under their health.
+#[derive(Component)]
+struct StatusIcons;
+
+/// The image with the class name shown above a party member's health.
+fn name_image_path(class: ActorName) -> &'static str {
+    match class {
+        ActorName::Priestess => PRIESTESS_IMAGE_PATH,
+        ActorName::Theif => THIEF_IMAGE_PATH,
+        _ => WARRIOR_IMAGE_PATH,
+    }
+}
+
+fn create_hp_bars(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    party_q: Query<(&ActorName, &PartySlot)>,
+) {
+    let mut classes = ActorName::HEROES;
+    for (class, PartySlot(slot)) in party_q.iter() {
+        if let Some(slot_class) = classes.get_mut(*slot) {
+            *slot_class = *class;
+        }
+    }
+
     // Left HP
     commands
-        .spawn((Node {
-            align_items: AlignItems::Start,
-            flex_direction: FlexDirection::Column,
-            ..default()
-        },))
+        .spawn((
+            Node {
+                align_items: AlignItems::Start,
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            HudPart(HudElement::HpBars),
+        ))
         .with_children(|builder| {
             builder
                 .spawn(Node {
@@ -49,7 +102,7 @@ fn create_hp_bars(mut commands: Commands, asset_server: Res<AssetServer>) {
                 .with_children(|builder| {
                     builder.spawn((
                         ImageNode {
-                            image: asset_server.load(WARRIOR_IMAGE_PATH),
+                            image: asset_server.load(name_image_path(classes[0])),
                             ..default()
                         },
                         Node {
@@ -63,7 +116,7 @@ fn create_hp_bars(mut commands: Commands, asset_server: Res<AssetServer>) {
 
                     builder.spawn((
                         ImageNode {
-                            image: asset_server.load(PRIESTESS_IMAGE_PATH),
+                            image: asset_server.load(name_image_path(classes[1])),
                             ..default()
                         },
                         Node {
@@ -76,7 +129,7 @@ fn create_hp_bars(mut commands: Commands, asset_server: Res<AssetServer>) {
                     ));
                     builder.spawn((
                         ImageNode {
-                            image: asset_server.load(THIEF_IMAGE_PATH),
+                            image: asset_server.load(name_image_path(classes[2])),
                             ..default()
                         },
                         Node {
@@ -99,6 +152,8 @@ fn create_hp_bars(mut commands: Commands, asset_server: Res<AssetServer>) {
                 ))
                 .with_children(|builder| {
                     builder.spawn((
+                        HpSegment,
+                        PartySlot(0),
                         ImageNode {
                             image: asset_server.load(HP_SPRITE_IMAGE_PATH),
                             ..default()
@@ -111,6 +166,8 @@ fn create_hp_bars(mut commands: Commands, asset_server: Res<AssetServer>) {
                         },
                     ));
                     builder.spawn((
+                        HpSegment,
+                        PartySlot(1),
                         ImageNode {
                             image: asset_server.load(HP_SPRITE_IMAGE_PATH),
                             ..default()
@@ -123,6 +180,8 @@ fn create_hp_bars(mut commands: Commands, asset_server: Res<AssetServer>) {
                         },
                     ));
                     builder.spawn((
+                        HpSegment,
+                        PartySlot(2),
                         ImageNode {
                             image: asset_server.load(HP_SPRITE_IMAGE_PATH),
                             ..default()
@@ -140,26 +199,25 @@ fn create_hp_bars(mut commands: Commands, asset_server: Res<AssetServer>) {
 
 fn spawn_hp(
     mut commands: Commands,
-    mut actors_health_q: Query<&Health, With<Actor>>,
+    actors_health_q: Query<(&PartySlot, &Health), With<Actor>>,
     asset_server: Res<AssetServer>,
 ) {
-    let mut actors_health: Vec<&Health> = Vec::new();
-
-    for health in actors_health_q {
-        actors_health.push(health);
-    }
+    let mut party: Vec<(&PartySlot, &Health)> = actors_health_q.iter().collect();
+    party.sort_by_key(|(slot, _)| **slot);
+    let actors_health: Vec<&Health> = party.into_iter().map(|(_, health)| health).collect();
 
     commands.spawn((
         Node {
             top: Val::Px(67.5),
-            left: Val::Px(56.5),
+            left: Val::Px(HP_TEXT_LEFT[0]),
             position_type: PositionType::Absolute,
             justify_content: JustifyContent::Start,
             align_items: AlignItems::Center,
             ..default()
         },
         HPBar,
-        ActorName::Warrior,
+        HudPart(HudElement::HpBars),
+        PartySlot(0),
         Text::new(format!(
             "{}/{}",
             actors_health.get(0).unwrap().current().unwrap(),
@@ -174,14 +232,15 @@ fn spawn_hp(
     commands.spawn((
         Node {
             top: Val::Px(67.5),
-            left: Val::Px(177.5),
+            left: Val::Px(HP_TEXT_LEFT[1]),
             position_type: PositionType::Absolute,
             justify_content: JustifyContent::Start,
             align_items: AlignItems::Center,
             ..default()
         },
         HPBar,
-        ActorName::Priestess,
+        HudPart(HudElement::HpBars),
+        PartySlot(1),
         Text::new(format!(
             "{}/{}",
             actors_health.get(1).unwrap().current().unwrap(),
@@ -197,14 +256,15 @@ fn spawn_hp(
     commands.spawn((
         Node {
             top: Val::Px(67.5),
-            left: Val::Px(297.5),
+            left: Val::Px(HP_TEXT_LEFT[2]),
             position_type: PositionType::Absolute,
             justify_content: JustifyContent::Start,
             align_items: AlignItems::Center,
             ..default()
         },
         HPBar,
-        ActorName::Theif,
+        HudPart(HudElement::HpBars),
+        PartySlot(2),
         Text::new(format!(
             "{}/{}",
             actors_health.get(2).unwrap().current().unwrap(),
@@ -218,18 +278,164 @@ fn spawn_hp(
     ));
 }
 
+fn spawn_death_markers(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    style: Res<Style>,
+    #[cfg(feature = "sqlite")] save: Option<Res<SaveGame>>,
+) {
+    #[cfg(feature = "sqlite")]
+    let hardcore = in_hardcore(save);
+    #[cfg(not(feature = "sqlite"))]
+    let hardcore = in_hardcore();
+    for (slot, left) in HP_TEXT_LEFT.into_iter().enumerate() {
+        let mut marker = commands.spawn((
+            Node {
+                top: Val::Px(85.0),
+                left: Val::Px(left - 20.0),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            Visibility::Hidden,
+            DeathMarker,
+            HudPart(HudElement::HpBars),
+            PartySlot(slot),
+            children![ImageNode {
+                image: asset_server.load(SKULL_IMAGE_PATH),
+                ..default()
+            }],
+        ));
+        if !hardcore {
+            marker.with_child((
+                Text::new("Revives at Entrance"),
+                style.font(12.0),
+                TextColor(style.text_color),
+            ));
+        }
+    }
+}
+
+fn spawn_status_icons(mut commands: Commands) {
+    for (slot, left) in HP_TEXT_LEFT.into_iter().enumerate() {
+        commands.spawn((
+            Node {
+                top: Val::Px(82.0),
+                left: Val::Px(left),
+                position_type: PositionType::Absolute,
+                column_gap: Val::Px(3.0),
+                ..default()
+            },
+            StatusIcons,
+            HudPart(HudElement::HpBars),
+            PartySlot(slot),
+        ));
+    }
+}
+
+fn status_label(effect: &StatusEffect) -> String {
+    match effect.stacks {
+        1 => effect.kind.label().to_string(),
+        stacks => format!("{}x{stacks}", effect.kind.label()),
+    }
+}
+
+fn update_status_icons(
+    mut commands: Commands,
+    style: Res<Style>,
+    party: Query<(&PartySlot, &StatusEffects, &Health), Changed<StatusEffects>>,
+    icons: Query<(Entity, &PartySlot), With<StatusIcons>>,
+) {
+    for (slot, effects, health) in party.iter() {
+        for (entity, _) in icons.iter().filter(|(_, s)| *s == slot) {
+            commands.entity(entity).despawn_related::<Children>();
+
+            // The skull says enough once they are dead.
+            if !health.is_alive() {
+                continue;
+            }
+
+            commands.entity(entity).with_children(|builder| {
+                for effect in effects.iter() {
+                    builder.spawn((
+                        Node {
+                            padding: UiRect::horizontal(Val::Px(2.0)),
+                            ..default()
+                        },
+                        BackgroundColor(effect.kind.color()),
+                        children![(
+                            Text::new(status_label(effect)),
+                            style.font(10.0),
+                            TextColor(Color::BLACK),
+                        )],
+                    ));
+                }
+            });
+        }
+    }
+}
+
+/// Grays out or restores the health of the party member in `slot`.
+fn set_ghosted(
+    slot: PartySlot,
+    dead: bool,
+    segments: &mut Query<(&PartySlot, &mut ImageNode), With<HpSegment>>,
+    markers: &mut Query<(&PartySlot, &mut Visibility), With<DeathMarker>>,
+) {
+    for (_, mut image) in segments.iter_mut().filter(|(s, _)| **s == slot) {
+        image.color = if dead { GHOST_COLOR } else { Color::WHITE };
+    }
+
+    for (_, mut visibility) in markers.iter_mut().filter(|(s, _)| **s == slot) {
+        *visibility = if dead {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+/// Ghosts the party members that were already dead in the loaded save.
+fn ghost_dead_heroes(
+    party: Query<(&PartySlot, &Health)>,
+    mut segments: Query<(&PartySlot, &mut ImageNode), With<HpSegment>>,
+    mut markers: Query<(&PartySlot, &mut Visibility), With<DeathMarker>>,
+) {
+    for (slot, health) in party.iter() {
+        set_ghosted(*slot, !health.is_alive(), &mut segments, &mut markers);
+    }
+}
+
+fn ghost_on_health_change(
+    change: Trigger<HealthChange>,
+    party: Query<&PartySlot>,
+    mut segments: Query<(&PartySlot, &mut ImageNode), With<HpSegment>>,
+    mut markers: Query<(&PartySlot, &mut Visibility), With<DeathMarker>>,
+) {
+    let Ok(slot) = party.get(change.target()) else {
+        return;
+    };
+
+    match change.event() {
+        HealthChange::Killed => set_ghosted(*slot, true, &mut segments, &mut markers),
+        HealthChange::Revived => set_ghosted(*slot, false, &mut segments, &mut markers),
+        HealthChange::Damaged | HealthChange::Healed => {}
+    }
+}
+
 pub fn update_player_hp_bar(
     mut commands: Commands,
     active_actor_team: Single<&Team, With<ActingActor>>,
     active_actor_name: Single<&ActorName, With<ActingActor>>,
-    mut actor_q: Query<(&ActorName, &Health), With<Actor>>,
-    mut text_q: Query<(Entity, &ActorName), With<HPBar>>,
+    actor_q: Query<(&PartySlot, &Health), With<Actor>>,
+    text_q: Query<(Entity, &PartySlot), With<HPBar>>,
     actor_action: Res<ActingActorAction>,
 ) {
     match *active_actor_team {
         Team::Enemy => match **actor_action {
             Action::Attack { target } => {
-                if let Ok((actor_name, target_health)) = actor_q.get(target) {
+                if let Ok((actor_slot, target_health)) = actor_q.get(target) {
                     let mut health_str: String = format!("");
                     if let Some(current_health) = target_health.current() {
                         health_str = format!("{}/{}", current_health, target_health.max());
@@ -237,8 +443,8 @@ pub fn update_player_hp_bar(
                         health_str = format!("0/{}", target_health.max());
                     }
 
-                    for (text_entity, text_actorname) in text_q {
-                        if text_actorname == actor_name {
+                    for (text_entity, text_slot) in text_q {
+                        if text_slot == actor_slot {
                             commands
                                 .entity(text_entity)
                                 .remove::<(Text, TextFont, TextLayout)>()
@@ -260,7 +466,7 @@ pub fn update_player_hp_bar(
         Team::Player => match **actor_action {
             Action::SpecialAction { target } => match *active_actor_name {
                 ActorName::Priestess => {
-                    if let Ok((actor_name, target_health)) = actor_q.get(target) {
+                    if let Ok((actor_slot, target_health)) = actor_q.get(target) {
                         let mut health_str: String = format!("");
                         if let Some(current_health) = target_health.current() {
                             health_str = format!("{}/{}", current_health, target_health.max());
@@ -268,8 +474,8 @@ pub fn update_player_hp_bar(
                             health_str = format!("0/{}", target_health.max());
                         }
 
-                        for (text_entity, text_actorname) in text_q {
-                            if text_actorname == actor_name {
+                        for (text_entity, text_slot) in text_q {
+                            if text_slot == actor_slot {
                                 commands
                                     .entity(text_entity)
                                     .remove::<(Text, TextFont, TextLayout)>()
@@ -295,10 +501,10 @@ pub fn update_player_hp_bar(
 
 pub fn update_player_hp_bar_pit(
     mut commands: Commands,
-    mut actor_q: Query<(&ActorName, &Health), With<Actor>>,
-    mut text_q: Query<(Entity, &ActorName), With<HPBar>>,
+    actor_q: Query<(&PartySlot, &Health), With<Actor>>,
+    text_q: Query<(Entity, &PartySlot), With<HPBar>>,
 ) {
-    for (actor_name, health) in actor_q {
+    for (actor_slot, health) in actor_q {
         let mut health_str: String = format!("");
         if let Some(current_health) = health.current() {
             health_str = format!("{}/{}", current_health, health.max());
@@ -306,8 +512,8 @@ pub fn update_player_hp_bar_pit(
             health_str = format!("0/{}", health.max());
         }
 
-        for (entity, text_actor_name) in text_q {
-            if actor_name == text_actor_name {
+        for (entity, text_slot) in text_q {
+            if actor_slot == text_slot {
                 commands
                     .entity(entity)
                     .remove::<(Text, TextFont, TextLayout)>()
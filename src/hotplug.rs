@@ -0,0 +1,187 @@
+//! Reacts to gamepads being plugged in and out.
+
+use crate::game::{GameState, PauseState};
+use crate::prelude::*;
+use bevy::input::gamepad::{GamepadConnection, GamepadConnectionEvent};
+use bevy::prelude::*;
+
+const PROMPT_Z_INDEX: i32 = 90;
+
+pub struct HotplugPlugin;
+
+impl Plugin for HotplugPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (gamepad_connections, answer_bindings_prompt).chain(),
+        );
+    }
+}
+
+/// Asks whether to use the default gamepad bindings, since the player has none.
+#[derive(Component)]
+struct BindingsPrompt;
+
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+enum BindingsPromptButton {
+    Apply,
+    Dismiss,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn gamepad_connections(
+    mut commands: Commands,
+    mut events: EventReader<GamepadConnectionEvent>,
+    mut notifications: ResMut<Notifications>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+    last_device: Res<LastInputDevice>,
+    controls: Res<Controls>,
+    style: Res<Style>,
+    gamepads: Query<Entity, With<Gamepad>>,
+    game_state: Option<Res<State<GameState>>>,
+    pause_state: Option<Res<State<PauseState>>>,
+    prompt: Query<(), With<BindingsPrompt>>,
+) {
+    for event in events.read() {
+        match &event.connection {
+            GamepadConnection::Connected { name, .. } => {
+                info!("Gamepad connected: {name}");
+                notifications.info(format!("Controller connected ({name})"));
+
+                if !controls.has_gamepad_bindings() && prompt.is_empty() {
+                    spawn_bindings_prompt(&mut commands, &style);
+                }
+            }
+            GamepadConnection::Disconnected => {
+                info!("Gamepad disconnected");
+                notifications.warn("Controller disconnected");
+
+                let in_combat = game_state
+                    .as_ref()
+                    .is_some_and(|s| *s.get() == GameState::Combat);
+                let running = pause_state
+                    .as_ref()
+                    .is_some_and(|s| *s.get() == PauseState::Running);
+                let others_left = gamepads.iter().any(|gamepad| gamepad != event.gamepad);
+
+                if in_combat && running && *last_device == LastInputDevice::Gamepad && !others_left
+                {
+                    next_pause_state.set(PauseState::Paused);
+                }
+            }
+        }
+    }
+}
+
+fn spawn_bindings_prompt(commands: &mut Commands, style: &Style) {
+    let button_node = Node {
+        width: Val::Px(160.0),
+        height: Val::Px(50.0),
+        margin: UiRect::all(Val::Px(5.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+
+    let text_style = (
+        style.font(24.0),
+        TextColor(style.text_color),
+        TextLayout::new_with_justify(JustifyText::Center),
+    );
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(10.0),
+                left: Val::Px(10.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(style.background_color),
+            GlobalZIndex(PROMPT_Z_INDEX),
+            BindingsPrompt,
+        ))
+        .with_children(|builder| {
+            builder.spawn((
+                Text::new("No controller controls are set.\nUse the defaults?"),
+                text_style.clone(),
+            ));
+
+            builder.spawn(Node::default()).with_children(|builder| {
+                for (button, label) in [
+                    (BindingsPromptButton::Apply, "Apply (South)"),
+                    (BindingsPromptButton::Dismiss, "Dismiss (East)"),
+                ] {
+                    builder
+                        .spawn((
+                            Button,
+                            button_node.clone(),
+                            BackgroundColor(style.button_color),
+                            button,
+                            children![(Text::new(label), text_style.clone())],
+                        ))
+                        .observe(
+                            |click: Trigger<Pointer<Click>>,
+                             mut commands: Commands,
+                             buttons: Query<&BindingsPromptButton>,
+                             prompt: Single<Entity, With<BindingsPrompt>>,
+                             mut controls: ResMut<Controls>| {
+                                if click.button != PointerButton::Primary {
+                                    return;
+                                }
+                                if let Ok(button) = buttons.get(click.target()) {
+                                    close_bindings_prompt(
+                                        &mut commands,
+                                        *prompt,
+                                        *button,
+                                        &mut controls,
+                                    );
+                                }
+                            },
+                        );
+                }
+            });
+        });
+}
+
+/// The bindings prompt is answered with the raw gamepad buttons,
+/// since none of the controls are bound to the gamepad yet.
+fn answer_bindings_prompt(
+    mut commands: Commands,
+    prompt: Option<Single<Entity, With<BindingsPrompt>>>,
+    gamepads: Query<&Gamepad>,
+    mut controls: ResMut<Controls>,
+) {
+    let Some(prompt) = prompt else {
+        return;
+    };
+
+    for gamepad in gamepads.iter() {
+        let answer = if gamepad.just_pressed(GamepadButton::South) {
+            BindingsPromptButton::Apply
+        } else if gamepad.just_pressed(GamepadButton::East) {
+            BindingsPromptButton::Dismiss
+        } else {
+            continue;
+        };
+
+        close_bindings_prompt(&mut commands, *prompt, answer, &mut controls);
+        return;
+    }
+}
+
+fn close_bindings_prompt(
+    commands: &mut Commands,
+    prompt: Entity,
+    answer: BindingsPromptButton,
+    controls: &mut ResMut<Controls>,
+) {
+    if answer == BindingsPromptButton::Apply {
+        controls.apply_default_gamepad_bindings();
+    }
+
+    commands.entity(prompt).despawn();
+}
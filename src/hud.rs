@@ -0,0 +1,269 @@
+//! Lets the player hide or fade parts of the HUD, set in the HUD settings.
+//!
+//! Each part of the HUD is tagged with a [`HudPart`] when it is spawned. Every frame,
+//! the parts are shown or hidden and their colors faded to match the [`HudConfig`],
+//! including anything spawned under them since, like new lines in the combat log.
+
+use crate::prelude::*;
+use crate::settings::{SETTINGS_DB_TABLE, setting_settled};
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter};
+
+/// The least a shown part of the HUD can be faded to, so it can't be lost by accident.
+pub const MIN_HUD_OPACITY: f32 = 0.1;
+
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PreStartup, setup_hud_config)
+            .add_systems(Update, hud_config_sync.run_if(setting_settled::<HudConfig>))
+            .add_systems(PostUpdate, apply_hud_config);
+    }
+}
+
+/// A part of the HUD that can be hidden or faded on its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Display, EnumIter)]
+pub enum HudElement {
+    #[strum(to_string = "HP Bars")]
+    HpBars,
+    Minimap,
+    #[strum(to_string = "Event Log")]
+    EventLog,
+    #[strum(to_string = "Turn Timer")]
+    Timer,
+}
+
+/// How one part of the HUD is shown.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HudSetting {
+    pub visible: bool,
+    /// From [`MIN_HUD_OPACITY`] to 1, multiplied into the alpha of everything in the part.
+    pub opacity: f32,
+}
+
+impl Default for HudSetting {
+    fn default() -> Self {
+        Self {
+            visible: true,
+            opacity: 1.0,
+        }
+    }
+}
+
+/// How each part of the HUD is shown, set in the HUD settings.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HudConfig {
+    pub hp_bars: HudSetting,
+    pub minimap: HudSetting,
+    pub event_log: HudSetting,
+    pub timer: HudSetting,
+}
+
+impl HudConfig {
+    const DB_KEY: &str = "hud";
+
+    pub fn get(&self, element: HudElement) -> HudSetting {
+        match element {
+            HudElement::HpBars => self.hp_bars,
+            HudElement::Minimap => self.minimap,
+            HudElement::EventLog => self.event_log,
+            HudElement::Timer => self.timer,
+        }
+    }
+
+    pub fn get_mut(&mut self, element: HudElement) -> &mut HudSetting {
+        match element {
+            HudElement::HpBars => &mut self.hp_bars,
+            HudElement::Minimap => &mut self.minimap,
+            HudElement::EventLog => &mut self.event_log,
+            HudElement::Timer => &mut self.timer,
+        }
+    }
+
+    /// Sets how faded `element` is, kept from going below [`MIN_HUD_OPACITY`].
+    pub fn set_opacity(&mut self, element: HudElement, opacity: f32) {
+        self.get_mut(element).opacity = opacity.clamp(MIN_HUD_OPACITY, 1.0);
+    }
+}
+
+/// Marks the root of a part of the HUD, shown and faded along with everything under it.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HudPart(pub HudElement);
+
+/// The alpha a color in the HUD had before it was faded, and what it was faded to.
+/// When the color no longer has the faded alpha, something else changed it,
+/// and its new alpha is taken as the one to fade.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct FadedAlpha(Option<(f32, f32)>);
+
+impl FadedAlpha {
+    /// The color faded to `opacity`, or None if it already is.
+    fn fade(&mut self, color: Color, opacity: f32) -> Option<Color> {
+        let alpha = color.alpha();
+        let base = match self.0 {
+            Some((base, faded)) if faded == alpha => base,
+            _ => alpha,
+        };
+        let faded = base * opacity;
+        self.0 = Some((base, faded));
+
+        (faded != alpha).then(|| color.with_alpha(faded))
+    }
+}
+
+/// The faded alphas of each color an entity in the HUD can have.
+#[derive(Component, Debug, Default, Clone, Copy)]
+struct HudFade {
+    text: FadedAlpha,
+    background: FadedAlpha,
+    image: FadedAlpha,
+    sprite: FadedAlpha,
+}
+
+fn setup_hud_config(mut commands: Commands, database: NonSend<Database>) {
+    let config = database.get_kv(SETTINGS_DB_TABLE, HudConfig::DB_KEY, HudConfig::default());
+    commands.insert_resource(config);
+}
+
+fn hud_config_sync(database: NonSend<Database>, config: Res<HudConfig>) -> Result {
+    database
+        .set_kv(SETTINGS_DB_TABLE, HudConfig::DB_KEY, *config)
+        .map_err(GameError::from)?;
+    Ok(())
+}
+
+#[allow(clippy::type_complexity)]
+fn apply_hud_config(
+    mut commands: Commands,
+    config: Res<HudConfig>,
+    mut parts: Query<(Entity, &HudPart, Option<&mut Node>, Option<&mut Visibility>)>,
+    children: Query<&Children>,
+    mut colors: Query<(
+        Option<&mut HudFade>,
+        Option<&mut TextColor>,
+        Option<&mut BackgroundColor>,
+        Option<&mut ImageNode>,
+        Option<&mut Sprite>,
+    )>,
+) {
+    for (entity, HudPart(element), node, visibility) in parts.iter_mut() {
+        let setting = config.get(*element);
+
+        // UI is taken out of the layout, so it doesn't fight anything else over its visibility.
+        if let Some(mut node) = node {
+            let display = match setting.visible {
+                true => Display::Flex,
+                false => Display::None,
+            };
+            if node.display != display {
+                node.display = display;
+            }
+        } else if let Some(mut visibility) = visibility {
+            let shown = match setting.visible {
+                true => Visibility::Inherited,
+                false => Visibility::Hidden,
+            };
+            if *visibility != shown {
+                *visibility = shown;
+            }
+        }
+
+        for entity in [entity]
+            .into_iter()
+            .chain(children.iter_descendants(entity))
+        {
+            let Ok((fade, text, background, image, sprite)) = colors.get_mut(entity) else {
+                continue;
+            };
+            let mut faded = fade.as_deref().copied().unwrap_or_default();
+
+            if let Some(mut text) = text
+                && let Some(color) = faded.text.fade(text.0, setting.opacity)
+            {
+                text.0 = color;
+            }
+            if let Some(mut background) = background
+                && let Some(color) = faded.background.fade(background.0, setting.opacity)
+            {
+                background.0 = color;
+            }
+            if let Some(mut image) = image
+                && let Some(color) = faded.image.fade(image.color, setting.opacity)
+            {
+                image.color = color;
+            }
+            if let Some(mut sprite) = sprite
+                && let Some(color) = faded.sprite.fade(sprite.color, setting.opacity)
+            {
+                sprite.color = color;
+            }
+
+            match fade {
+                Some(mut fade) => *fade = faded,
+                None => {
+                    commands.entity(entity).insert(faded);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fading_keeps_the_alpha_it_started_with() {
+        let mut faded = FadedAlpha::default();
+        let color = Color::srgba(1.0, 1.0, 1.0, 0.8);
+
+        let half = faded.fade(color, 0.5).unwrap();
+        assert!((half.alpha() - 0.4).abs() < 1e-6);
+        assert_eq!(faded.fade(half, 0.5), None);
+
+        // Fading again starts from the original alpha, not the faded one.
+        let quarter = faded.fade(half, 0.25).unwrap();
+        assert!((quarter.alpha() - 0.2).abs() < 1e-6);
+        let full = faded.fade(quarter, 1.0).unwrap();
+        assert!((full.alpha() - 0.8).abs() < 1e-6);
+
+        // Something else set the color, so its alpha is the one faded from now on.
+        let ghost = Color::srgba(0.4, 0.4, 0.4, 0.6);
+        let ghost = faded.fade(ghost, 0.5).unwrap();
+        assert!((ghost.alpha() - 0.3).abs() < 1e-6);
+    }
+
+    #[test]
+    fn hud_parts_are_hidden_and_faded() {
+        let mut world = World::new();
+        world.insert_resource(HudConfig::default());
+        let log = world
+            .spawn((
+                HudPart(HudElement::EventLog),
+                Node::default(),
+                BackgroundColor(Color::BLACK),
+            ))
+            .id();
+        let line = world.spawn((Text::default(), TextColor(Color::WHITE))).id();
+        world.entity_mut(log).add_child(line);
+
+        let mut config = HudConfig::default();
+        config.set_opacity(HudElement::EventLog, 0.0);
+        assert_eq!(config.event_log.opacity, MIN_HUD_OPACITY);
+        config.set_opacity(HudElement::EventLog, 0.5);
+        world.insert_resource(config);
+        world.run_system_cached(apply_hud_config).unwrap();
+
+        let text = world.get::<TextColor>(line).unwrap().0;
+        assert!((text.alpha() - 0.5).abs() < 1e-6);
+        let background = world.get::<BackgroundColor>(log).unwrap().0;
+        assert!((background.alpha() - 0.5).abs() < 1e-6);
+
+        world.resource_mut::<HudConfig>().event_log.visible = false;
+        world.run_system_cached(apply_hud_config).unwrap();
+        assert_eq!(world.get::<Node>(log).unwrap().display, Display::None);
+    }
+}
@@ -0,0 +1,364 @@
+//! An opt-in log of the last presses and releases of each [`Control`], for working out
+//! why an input didn't do what the player expected.
+//!
+//! Each entry keeps when it happened and which states were active at the time.
+//! The log can be saved to a file to go along with a bug report,
+//! and with the `debug` feature it is shown in a panel toggled with F7.
+
+use crate::controls::update_control_state;
+use crate::database::get_default_db_directory;
+use crate::game::{GameState, PauseState};
+use crate::menu::MenuState;
+use crate::prelude::*;
+use crate::settings::SETTINGS_DB_TABLE;
+#[cfg(feature = "debug")]
+use bevy::input::common_conditions::input_just_pressed;
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// How many changes are kept, the oldest are dropped first.
+pub const INPUT_LOG_CAPACITY: usize = 200;
+const INPUT_LOG_FILE: &str = "input_log.txt";
+
+#[cfg(feature = "debug")]
+const INPUT_LOG_TOGGLE_KEY: KeyCode = KeyCode::F7;
+/// How many of the latest changes the panel shows.
+#[cfg(feature = "debug")]
+const INPUT_LOG_PANEL_LINES: usize = 20;
+#[cfg(feature = "debug")]
+const INPUT_LOG_Z_INDEX: i32 = 200;
+
+pub struct InputLogPlugin;
+
+impl Plugin for InputLogPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InputLog>()
+            .add_systems(PreStartup, setup_input_logging)
+            .add_systems(
+                PreUpdate,
+                record_control_changes
+                    .after(update_control_state)
+                    .run_if(resource_equals(InputLogging(true))),
+            )
+            .add_systems(
+                Update,
+                input_logging_sync.run_if(
+                    resource_changed::<InputLogging>.and(not(resource_added::<InputLogging>)),
+                ),
+            );
+
+        #[cfg(feature = "debug")]
+        app.add_systems(Startup, spawn_input_log_panel).add_systems(
+            Update,
+            (
+                toggle_input_log_panel.run_if(input_just_pressed(INPUT_LOG_TOGGLE_KEY)),
+                update_input_log_panel,
+            )
+                .chain(),
+        );
+    }
+}
+
+/// Whether changes to the [`ControlState`] are logged, set in the controls settings.
+/// Off unless the player turns it on.
+#[derive(Resource, Deref, DerefMut, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InputLogging(pub bool);
+
+impl InputLogging {
+    const DB_KEY: &str = "input_logging";
+}
+
+/// How a control changed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ControlChange {
+    /// Pressed this far, see [`ControlState::value`].
+    Pressed(f32),
+    Released,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputLogEntry {
+    /// Since the game started, so it keeps counting while the game is paused.
+    pub time: Duration,
+    pub control: Control,
+    pub change: ControlChange,
+    /// The states that were active, like `Game > Combat > Running`.
+    pub state: String,
+}
+
+impl std::fmt::Display for InputLogEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let change = match self.change {
+            ControlChange::Pressed(value) => format!("pressed ({value:.2})"),
+            ControlChange::Released => "released".to_string(),
+        };
+        write!(
+            f,
+            "[{:>9.3}s] {} {change} in {}",
+            self.time.as_secs_f64(),
+            self.control.as_string(),
+            self.state
+        )
+    }
+}
+
+/// The last [`INPUT_LOG_CAPACITY`] changes to the [`ControlState`], oldest first.
+#[derive(Resource, Default, Debug, Clone)]
+pub struct InputLog(VecDeque<InputLogEntry>);
+
+impl InputLog {
+    pub fn push(&mut self, entry: InputLogEntry) {
+        if self.0.len() == INPUT_LOG_CAPACITY {
+            self.0.pop_front();
+        }
+        self.0.push_back(entry);
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &InputLogEntry> {
+        self.0.iter()
+    }
+
+    /// The whole log as text, one change a line, with the game's version at the top.
+    pub fn report(&self) -> String {
+        let mut report = format!("A Hex Befalls the Hexagons {}\n", env!("CARGO_PKG_VERSION"));
+        if self.is_empty() {
+            report.push_str("No input changes were logged\n");
+            return report;
+        }
+
+        report.push_str(&format!("Last {} input changes:\n", self.len()));
+        for entry in self.iter() {
+            report.push_str(&entry.to_string());
+            report.push('\n');
+        }
+        report
+    }
+}
+
+/// Where the log is saved, see [`save_input_log`].
+pub fn input_log_path() -> PathBuf {
+    get_default_db_directory().join(INPUT_LOG_FILE)
+}
+
+/// Writes the log out to [`input_log_path`], so it can be attached to a bug report.
+pub fn save_input_log(log: Res<InputLog>, mut notifications: ResMut<Notifications>) {
+    let path = input_log_path();
+    match std::fs::write(&path, log.report()) {
+        Ok(()) => notifications.success(format!("Saved the input log to {}", path.display())),
+        Err(err) => notifications.error(format!("Failed to save the input log: {err}")),
+    }
+}
+
+fn setup_input_logging(mut commands: Commands, database: NonSend<Database>) {
+    let logging = database.get_kv(SETTINGS_DB_TABLE, InputLogging::DB_KEY, false);
+    commands.insert_resource(InputLogging(logging));
+}
+
+/// Saves the setting, and forgets what was logged once it is turned off.
+fn input_logging_sync(
+    database: NonSend<Database>,
+    logging: Res<InputLogging>,
+    mut log: ResMut<InputLog>,
+) -> Result {
+    if !logging.0 {
+        log.clear();
+    }
+    database
+        .set_kv(SETTINGS_DB_TABLE, InputLogging::DB_KEY, logging.0)
+        .map_err(GameError::from)?;
+    Ok(())
+}
+
+/// The active states, outermost first, joined with `>`.
+fn active_state(
+    app: Option<&State<AppState>>,
+    menu: Option<&State<MenuState>>,
+    game: Option<&State<GameState>>,
+    pause: Option<&State<PauseState>>,
+) -> String {
+    let states: Vec<String> = [
+        app.map(|s| format!("{:?}", s.get())),
+        menu.map(|s| format!("{:?}", s.get())),
+        game.map(|s| format!("{:?}", s.get())),
+        pause.map(|s| format!("{:?}", s.get())),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    states.join(" > ")
+}
+
+fn record_control_changes(
+    control_state: Res<ControlState>,
+    time: Res<Time<Real>>,
+    app: Option<Res<State<AppState>>>,
+    menu: Option<Res<State<MenuState>>>,
+    game: Option<Res<State<GameState>>>,
+    pause: Option<Res<State<PauseState>>>,
+    mut log: ResMut<InputLog>,
+) {
+    let pressed = control_state.get_just_pressed().map(|control| {
+        (
+            *control,
+            ControlChange::Pressed(control_state.value(*control)),
+        )
+    });
+    let released = control_state
+        .get_just_released()
+        .map(|control| (*control, ControlChange::Released));
+    let changes: Vec<_> = pressed.chain(released).collect();
+    if changes.is_empty() {
+        return;
+    }
+
+    let state = active_state(
+        app.as_deref(),
+        menu.as_deref(),
+        game.as_deref(),
+        pause.as_deref(),
+    );
+    for (control, change) in changes {
+        log.push(InputLogEntry {
+            time: time.elapsed(),
+            control,
+            change,
+            state: state.clone(),
+        });
+    }
+}
+
+#[cfg(feature = "debug")]
+#[derive(Component)]
+struct InputLogPanel;
+
+#[cfg(feature = "debug")]
+fn spawn_input_log_panel(mut commands: Commands, style: Res<Style>) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(4.0),
+            right: Val::Px(4.0),
+            padding: UiRect::all(Val::Px(4.0)),
+            flex_direction: FlexDirection::Column,
+            ..default()
+        },
+        BackgroundColor(style.background_color),
+        GlobalZIndex(INPUT_LOG_Z_INDEX),
+        Visibility::Hidden,
+        Pickable::IGNORE,
+        InputLogPanel,
+    ));
+}
+
+#[cfg(feature = "debug")]
+fn toggle_input_log_panel(mut panel: Query<&mut Visibility, With<InputLogPanel>>) {
+    for mut visibility in panel.iter_mut() {
+        visibility.toggle_visible_hidden();
+    }
+}
+
+#[cfg(feature = "debug")]
+fn update_input_log_panel(
+    mut commands: Commands,
+    panel: Query<(Entity, Ref<Visibility>), With<InputLogPanel>>,
+    log: Res<InputLog>,
+    logging: Res<InputLogging>,
+    style: Res<Style>,
+) {
+    for (entity, visibility) in panel.iter() {
+        let shown = *visibility != Visibility::Hidden;
+        if !shown || !(visibility.is_changed() || log.is_changed() || logging.is_changed()) {
+            continue;
+        }
+
+        let mut lines = vec![match logging.0 {
+            true => format!("Input log: {} / {INPUT_LOG_CAPACITY}", log.len()),
+            false => "Input log is off, turn it on in the controls settings".to_string(),
+        }];
+        let skip = log.len().saturating_sub(INPUT_LOG_PANEL_LINES);
+        lines.extend(log.iter().skip(skip).map(InputLogEntry::to_string));
+
+        commands
+            .entity(entity)
+            .despawn_related::<Children>()
+            .with_children(|builder| {
+                for line in lines {
+                    builder.spawn((
+                        Text::new(line),
+                        TextFont {
+                            font_size: 16.0,
+                            ..default()
+                        },
+                        TextColor(style.text_color),
+                        Pickable::IGNORE,
+                    ));
+                }
+            });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn entry(control: Control) -> InputLogEntry {
+        InputLogEntry {
+            time: Duration::ZERO,
+            control,
+            change: ControlChange::Released,
+            state: "Menu > Main".to_string(),
+        }
+    }
+
+    #[test]
+    fn only_the_latest_changes_are_kept() {
+        let mut log = InputLog::default();
+        log.push(entry(Control::Pause));
+        for _ in 0..INPUT_LOG_CAPACITY {
+            log.push(entry(Control::Select));
+        }
+
+        assert_eq!(log.len(), INPUT_LOG_CAPACITY);
+        assert!(log.iter().all(|entry| entry.control == Control::Select));
+        assert_eq!(log.report().lines().count(), INPUT_LOG_CAPACITY + 2);
+    }
+
+    #[test]
+    fn presses_and_releases_are_logged_with_the_state() {
+        let mut world = World::new();
+        world.init_resource::<InputLog>();
+        world.init_resource::<Time<Real>>();
+        world.insert_resource(State::new(AppState::Menu));
+
+        let mut control_state = ControlState::default();
+        control_state.press(Control::Select, 1.0);
+        world.insert_resource(control_state);
+        world.run_system_cached(record_control_changes).unwrap();
+
+        let mut control_state = world.resource_mut::<ControlState>();
+        control_state.clear();
+        control_state.release(Control::Select);
+        world.run_system_cached(record_control_changes).unwrap();
+
+        let log = world.resource::<InputLog>();
+        let changes: Vec<_> = log.iter().map(|entry| entry.change).collect();
+        assert_eq!(
+            changes,
+            [ControlChange::Pressed(1.0), ControlChange::Released]
+        );
+        assert!(log.iter().all(|entry| entry.state == "Menu"));
+    }
+}
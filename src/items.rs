@@ -3,6 +3,9 @@ use rand::Rng;
 use serde::{Deserialize, Serialize};
 use strum::Display;
 
+#[cfg(feature = "sqlite")]
+use std::collections::HashMap;
+
 #[derive(Resource, Deref, DerefMut, Default)]
 pub struct Items(pub Vec<Item>);
 
@@ -12,9 +15,91 @@ pub enum Item {
     HealingPotion,
     #[strum(to_string = "Vision Potion")]
     VisionPotion,
+    #[strum(to_string = "Iron Sword")]
+    IronSword,
+    #[strum(to_string = "War Axe")]
+    WarAxe,
+    #[strum(to_string = "Leather Armor")]
+    LeatherArmor,
+    #[strum(to_string = "Chainmail")]
+    Chainmail,
+}
+
+/// Where a piece of gear is worn, see [`Equipment`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy, Display)]
+pub enum GearSlot {
+    Weapon,
+    Armor,
+}
+
+/// What wearing a piece of gear adds to a hero's stats.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct GearBonus {
+    /// Added to both ends of the damage range.
+    pub damage: u32,
+    pub hit_chance: f32,
+    pub block_chance: f32,
+    pub health: u32,
+}
+
+impl GearBonus {
+    /// A short summary for the party screen, like "+5 damage, +10 health".
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if self.damage > 0 {
+            parts.push(format!("+{} damage", self.damage));
+        }
+        if self.hit_chance > 0.0 {
+            parts.push(format!("+{:.0}% hit", self.hit_chance * 100.0));
+        }
+        if self.block_chance > 0.0 {
+            parts.push(format!("+{:.0}% block", self.block_chance * 100.0));
+        }
+        if self.health > 0 {
+            parts.push(format!("+{} health", self.health));
+        }
+        parts.join(", ")
+    }
 }
 
 impl Item {
+    /// The color of the item's sprite on the room floor.
+    pub fn color(&self) -> Color {
+        match self {
+            Item::HealingPotion => Color::srgb_u8(0xeb, 0x6f, 0x92),
+            Item::VisionPotion => Color::srgb_u8(0x9c, 0xcf, 0xd8),
+            Item::IronSword | Item::WarAxe => Color::srgb_u8(0xe0, 0xde, 0xf4),
+            Item::LeatherArmor | Item::Chainmail => Color::srgb_u8(0xf6, 0xc1, 0x77),
+        }
+    }
+
+    /// Where the item is worn, or None if it can't be.
+    pub fn slot(&self) -> Option<GearSlot> {
+        match self {
+            Item::HealingPotion | Item::VisionPotion => None,
+            Item::IronSword | Item::WarAxe => Some(GearSlot::Weapon),
+            Item::LeatherArmor | Item::Chainmail => Some(GearSlot::Armor),
+        }
+    }
+
+    /// What wearing the item adds, nothing for items that can't be worn.
+    pub fn bonus(&self) -> GearBonus {
+        let (damage, hit_chance, block_chance, health) = match self {
+            Item::HealingPotion | Item::VisionPotion => (0, 0.0, 0.0, 0),
+            Item::IronSword => (5, 0.05, 0.0, 0),
+            Item::WarAxe => (10, 0.0, 0.0, 0),
+            Item::LeatherArmor => (0, 0.0, 0.05, 10),
+            Item::Chainmail => (0, 0.0, 0.1, 20),
+        };
+
+        GearBonus {
+            damage,
+            hit_chance,
+            block_chance,
+            health,
+        }
+    }
+
     pub fn get_rand_item(rng: &mut impl Rng) -> Item {
         let item = rng.random_range(0..2);
 
@@ -24,6 +109,99 @@ impl Item {
             _ => unreachable!(),
         }
     }
+
+    /// A random piece of gear, for the rarer drops.
+    pub fn get_rand_gear(rng: &mut impl Rng) -> Item {
+        let item = rng.random_range(0..4);
+
+        match item {
+            0 => Item::IronSword,
+            1 => Item::WarAxe,
+            2 => Item::LeatherArmor,
+            3 => Item::Chainmail,
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// The gear a party member is wearing, one piece in each [`GearSlot`].
+///
+/// Worn gear is added straight onto the hero's [`Attack`], [`BlockChance`] and [`Health`],
+/// which are saved with it, so it is only taken back off when the gear is.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Equipment {
+    pub weapon: Option<Item>,
+    pub armor: Option<Item>,
+}
+
+impl Equipment {
+    pub fn get(&self, slot: GearSlot) -> Option<Item> {
+        match slot {
+            GearSlot::Weapon => self.weapon,
+            GearSlot::Armor => self.armor,
+        }
+    }
+
+    fn get_mut(&mut self, slot: GearSlot) -> &mut Option<Item> {
+        match slot {
+            GearSlot::Weapon => &mut self.weapon,
+            GearSlot::Armor => &mut self.armor,
+        }
+    }
+
+    /// Puts `item` on, adding it to the hero's stats, and returns what it replaced.
+    /// Items that can't be worn are handed straight back.
+    pub fn equip(
+        &mut self,
+        item: Item,
+        attack: &mut Attack,
+        block_chance: &mut BlockChance,
+        health: &mut Health,
+    ) -> Option<Item> {
+        let Some(slot) = item.slot() else {
+            return Some(item);
+        };
+
+        let replaced = self.unequip(slot, attack, block_chance, health);
+        let bonus = item.bonus();
+        *attack = Attack::new(
+            attack.damage().start + bonus.damage..attack.damage().end + bonus.damage,
+            attack.hit_chance() + bonus.hit_chance,
+        );
+        block_chance.0 += bonus.block_chance;
+        health.raise_max(bonus.health);
+        *self.get_mut(slot) = Some(item);
+
+        replaced
+    }
+
+    /// Takes off whatever is worn in `slot`, taking it back off the hero's stats.
+    pub fn unequip(
+        &mut self,
+        slot: GearSlot,
+        attack: &mut Attack,
+        block_chance: &mut BlockChance,
+        health: &mut Health,
+    ) -> Option<Item> {
+        let item = self.get_mut(slot).take()?;
+
+        let bonus = item.bonus();
+        *attack = Attack::new(
+            attack.damage().start.saturating_sub(bonus.damage)
+                ..attack.damage().end.saturating_sub(bonus.damage),
+            attack.hit_chance() - bonus.hit_chance,
+        );
+        block_chance.0 -= bonus.block_chance;
+        health.lower_max(bonus.health);
+
+        Some(item)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (GearSlot, Item)> {
+        [GearSlot::Weapon, GearSlot::Armor]
+            .into_iter()
+            .filter_map(|slot| self.get(slot).map(|item| (slot, item)))
+    }
 }
 
 // #[cfg(feature = "sqlite")]
@@ -53,44 +231,139 @@ impl Item {
 
 #[cfg(feature = "sqlite")]
 pub fn save_items(
+    In(kind): In<SaveKind>,
     items: Res<Items>,
+    party: Query<(&PartySlot, Ref<Equipment>)>,
     save_info: Res<SaveGame>,
     db: NonSend<Database>,
 ) -> Result<(), DatabaseError> {
+    let equipment_changed = party.iter().any(|(_, equipment)| equipment.is_changed());
+    if kind == SaveKind::Incremental && !items.is_changed() && !equipment_changed {
+        return Ok(());
+    }
+
     let game_id = save_info.game_id.0;
     db.connection
         .execute("DELETE FROM Item WHERE game_id = :game_id", (game_id,))?;
 
-    let query = "INSERT INTO Item(game_id, type) VALUES(:game_id, :type)";
+    let query =
+        "INSERT INTO Item(game_id, type, equipped_by) VALUES(:game_id, :type, :equipped_by)";
     let mut statement = db.connection.prepare(query)?;
     for item in items.0.iter() {
         let item = ron::to_string(&item).unwrap();
-        statement.execute((game_id, item))?;
+        statement.execute((game_id, item, None::<usize>))?;
+    }
+    for (slot, equipment) in party.iter() {
+        for (_, item) in equipment.iter() {
+            let item = ron::to_string(&item).unwrap();
+            statement.execute((game_id, item, Some(slot.0)))?;
+        }
     }
 
     Ok(())
 }
 
+/// Loads the party's items, putting worn gear back on whoever was wearing it.
+/// The gear's bonuses were saved with the heroes' stats, so they aren't added again.
 #[cfg(feature = "sqlite")]
 pub fn load_items(
     mut commands: Commands,
+    party: Query<(Entity, &PartySlot)>,
     save_info: Res<SaveGame>,
     db: NonSend<Database>,
 ) -> Result<(), DatabaseError> {
     let game_id = save_info.game_id.0;
 
-    let items = db
+    let rows = db
         .connection
-        .prepare("SELECT type FROM Item WHERE game_id = :game_id")?
+        .prepare("SELECT type, equipped_by FROM Item WHERE game_id = :game_id")?
         .query_map((game_id,), |row| {
             let i_type = row.get::<_, String>(0)?;
             let i_type = ron::from_str(&i_type).unwrap();
 
-            Ok(i_type)
+            Ok((i_type, row.get::<_, Option<usize>>(1)?))
         })?
-        .collect::<Result<Vec<Item>, _>>()?;
+        .collect::<Result<Vec<(Item, Option<usize>)>, _>>()?;
 
+    let mut items = Vec::new();
+    let mut equipment = HashMap::<usize, Equipment>::new();
+    for (item, equipped_by) in rows {
+        match (equipped_by, item.slot()) {
+            (Some(slot), Some(GearSlot::Weapon)) => {
+                equipment.entry(slot).or_default().weapon = Some(item)
+            }
+            (Some(slot), Some(GearSlot::Armor)) => {
+                equipment.entry(slot).or_default().armor = Some(item)
+            }
+            _ => items.push(item),
+        }
+    }
+
+    for (entity, slot) in party.iter() {
+        if let Some(equipment) = equipment.remove(&slot.0) {
+            commands.entity(entity).insert(equipment);
+        }
+    }
     commands.insert_resource(Items(items));
 
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::SeedableRng;
+    use std::num::NonZero;
+
+    #[test]
+    fn gear_comes_off_as_it_went_on() {
+        let mut attack = Attack::new(20..31, 0.7);
+        let mut block_chance = BlockChance(0.25);
+        let mut health = Health::new(NonZero::new(50).unwrap());
+        let mut equipment = Equipment::default();
+
+        assert_eq!(
+            equipment.equip(
+                Item::HealingPotion,
+                &mut attack,
+                &mut block_chance,
+                &mut health
+            ),
+            Some(Item::HealingPotion)
+        );
+        assert_eq!(equipment, Equipment::default());
+
+        let replaced =
+            equipment.equip(Item::IronSword, &mut attack, &mut block_chance, &mut health);
+        assert_eq!(replaced, None);
+        equipment.equip(Item::Chainmail, &mut attack, &mut block_chance, &mut health);
+        assert_eq!(*attack.damage(), 25..36);
+        assert_eq!(health.max().get(), 70);
+
+        let replaced = equipment.equip(Item::WarAxe, &mut attack, &mut block_chance, &mut health);
+        assert_eq!(replaced, Some(Item::IronSword));
+        assert_eq!(*attack.damage(), 30..41);
+
+        equipment.unequip(
+            GearSlot::Weapon,
+            &mut attack,
+            &mut block_chance,
+            &mut health,
+        );
+        equipment.unequip(GearSlot::Armor, &mut attack, &mut block_chance, &mut health);
+        assert_eq!(equipment, Equipment::default());
+        assert_eq!(*attack.damage(), 20..31);
+        assert!((attack.hit_chance() - 0.7).abs() < 1e-6);
+        assert!((block_chance.0 - 0.25).abs() < 1e-6);
+        assert_eq!(health.max().get(), 50);
+    }
+
+    #[test]
+    fn random_gear_can_always_be_worn() {
+        let mut rng = RandomSource::seed_from_u64(0);
+        for _ in 0..50 {
+            assert!(Item::get_rand_gear(&mut rng).slot().is_some());
+            assert!(Item::get_rand_item(&mut rng).slot().is_none());
+        }
+    }
+}
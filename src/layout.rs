@@ -0,0 +1,159 @@
+//! Keeps the game playable at aspect ratios other than the 16:9 it was laid out for,
+//! from ultrawide monitors down to a phone held upright in a browser.
+//!
+//! The world camera zooms out just enough to keep the room in view, but never zooms in
+//! past one pixel per unit. The UI shrinks with the window once it is too small for the HUD,
+//! and side panels like the combat menu move to the bottom of the screen when it is taller than wide.
+
+use crate::prelude::*;
+use bevy::window::PrimaryWindow;
+use std::ops::RangeInclusive;
+
+/// The part of the world that always has to be in view: the room, the actors around it
+/// and a margin for their health and names.
+const PLAYFIELD_SIZE: Vec2 = Vec2::new(720.0, 560.0);
+const CAMERA_SCALE_RANGE: RangeInclusive<f32> = 1.0..=3.0;
+/// The smallest window the HUD fits in at full size.
+const UI_MIN_SIZE: Vec2 = Vec2::new(720.0, 540.0);
+const UI_SCALE_RANGE: RangeInclusive<f32> = 0.5..=1.0;
+
+/// How much of the screen a side panel takes up along its side.
+const SIDE_PANEL_PERCENT: f32 = 37.5;
+const SIDE_PANEL_MIN: f32 = 240.0;
+const SIDE_PANEL_MAX: f32 = 480.0;
+
+pub struct LayoutPlugin;
+
+impl Plugin for LayoutPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ScreenLayout>().add_systems(
+            PreUpdate,
+            (
+                fit_to_window,
+                place_side_panels.run_if(resource_changed::<ScreenLayout>),
+            )
+                .chain(),
+        );
+    }
+}
+
+/// Which way the screen is held, deciding where the side panels go.
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScreenLayout {
+    #[default]
+    Wide,
+    Tall,
+}
+
+impl ScreenLayout {
+    pub fn from_size(size: Vec2) -> Self {
+        if size.y > size.x {
+            ScreenLayout::Tall
+        } else {
+            ScreenLayout::Wide
+        }
+    }
+
+    /// The node for a panel along the side of the screen, on the left when it's wide
+    /// and along the bottom when it's tall.
+    pub fn side_panel(self) -> Node {
+        let node = Node {
+            position_type: PositionType::Absolute,
+            align_items: AlignItems::Center,
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::Center,
+            align_content: AlignContent::Center,
+            ..default()
+        };
+
+        match self {
+            ScreenLayout::Wide => Node {
+                left: Val::Px(0.0),
+                width: Val::Percent(SIDE_PANEL_PERCENT),
+                min_width: Val::Px(SIDE_PANEL_MIN),
+                max_width: Val::Px(SIDE_PANEL_MAX),
+                height: Val::Percent(100.0),
+                ..node
+            },
+            ScreenLayout::Tall => Node {
+                bottom: Val::Px(0.0),
+                width: Val::Percent(100.0),
+                height: Val::Percent(SIDE_PANEL_PERCENT),
+                min_height: Val::Px(SIDE_PANEL_MIN),
+                max_height: Val::Px(SIDE_PANEL_MAX),
+                ..node
+            },
+        }
+    }
+}
+
+/// A UI root that is laid out by [`ScreenLayout::side_panel`], and moved when the screen turns.
+#[derive(Component, Default)]
+pub struct SidePanel;
+
+/// How far the world camera has to zoom out for the playfield to fit in the window.
+pub fn camera_scale(window: Vec2) -> f32 {
+    let fit = PLAYFIELD_SIZE / window.max(Vec2::ONE);
+    fit.max_element()
+        .clamp(*CAMERA_SCALE_RANGE.start(), *CAMERA_SCALE_RANGE.end())
+}
+
+/// How much the UI has to shrink for the HUD to fit in the window.
+pub fn ui_scale(window: Vec2) -> f32 {
+    let fit = window / UI_MIN_SIZE;
+    fit.min_element()
+        .clamp(*UI_SCALE_RANGE.start(), *UI_SCALE_RANGE.end())
+}
+
+fn fit_to_window(
+    window: Query<&Window, (With<PrimaryWindow>, Changed<Window>)>,
+    mut camera: Query<&mut Projection, With<MainCameraMarker>>,
+    mut ui: ResMut<UiScale>,
+    mut layout: ResMut<ScreenLayout>,
+) {
+    let Ok(window) = window.single() else {
+        return;
+    };
+    let size = window.size();
+
+    for mut projection in camera.iter_mut() {
+        if let Projection::Orthographic(ortho) = &mut *projection {
+            ortho.scale = camera_scale(size);
+        }
+    }
+    let scale = ui_scale(size);
+    if ui.0 != scale {
+        ui.0 = scale;
+    }
+    layout.set_if_neq(ScreenLayout::from_size(size));
+}
+
+fn place_side_panels(layout: Res<ScreenLayout>, mut panels: Query<&mut Node, With<SidePanel>>) {
+    for mut node in panels.iter_mut() {
+        *node = layout.side_panel();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn the_playfield_fits_without_zooming_in() {
+        let wide = Vec2::new(1280.0, 720.0);
+        let ultrawide = Vec2::new(3440.0, 1440.0);
+        let phone = Vec2::new(390.0, 844.0);
+
+        assert_eq!(camera_scale(wide), 1.0);
+        assert_eq!(camera_scale(ultrawide), 1.0);
+        assert!(camera_scale(phone) * phone.x >= PLAYFIELD_SIZE.x - 0.01);
+        assert_eq!(camera_scale(Vec2::ZERO), *CAMERA_SCALE_RANGE.end());
+
+        assert_eq!(ui_scale(wide), 1.0);
+        assert!(ui_scale(phone) < 1.0);
+        assert!(ui_scale(phone) * UI_MIN_SIZE.x <= phone.x + 0.01);
+
+        assert_eq!(ScreenLayout::from_size(ultrawide), ScreenLayout::Wide);
+        assert_eq!(ScreenLayout::from_size(phone), ScreenLayout::Tall);
+    }
+}
@@ -1,20 +1,48 @@
 mod actor;
 mod animation;
+#[cfg(feature = "debug")]
+mod asset_budget;
+mod audio;
+mod barks;
+mod bot;
 mod camera;
+mod cli;
+#[cfg(feature = "sqlite")]
+mod component_blob;
+mod config;
+mod context_menu;
 mod controls;
+mod damage_popup;
 mod database;
+mod difficulty;
+mod effects;
+mod encounter;
+mod error;
+mod focus_nav;
 mod game;
 mod generate_map;
 mod health_bar;
+mod hotplug;
+mod hud;
+mod input_log;
 mod items;
+mod layout;
 mod menu;
+mod notifications;
+#[cfg(feature = "debug")]
+mod profiler;
 mod room;
+mod rules;
 #[cfg(feature = "sqlite")]
 mod saving;
+mod scrollbar;
+mod settings;
 mod sky;
 mod spawn_map;
 mod style;
 mod tile;
+mod time_scale;
+mod tooltip;
 mod util;
 
 pub mod prelude {
@@ -37,32 +65,72 @@ pub mod prelude {
     pub use crate::animation::{
         AnimationBundle, AnimationConfig, AnimationConfigs, AnimationFrameTimer,
     };
+    pub use crate::audio::{Sfx, Volume};
     pub use crate::camera::{MainCameraMarker, MapCameraMarker};
-    pub use crate::controls::{Control, ControlState, Controls, Keybind};
+    pub use crate::config::Config;
+    pub use crate::context_menu::{ContextMenu, ContextMenuSelect};
+    pub use crate::controls::{
+        Control, ControlState, Controls, Keybind, KeybindHint, LastInputDevice,
+    };
     pub use crate::database::{Database, Error as DatabaseError, FromDatabase, ToDatabase};
+    pub use crate::difficulty::{Challenges, Difficulty, GameMode, GameRules, Rule};
+    pub use crate::effects::{
+        Budgeted, EffectKind, EffectsBudget, EffectsIntensity, RegisterEffect, VisualEffect,
+    };
+    pub use crate::encounter::{BiomeEnemies, EncounterRules, EnemyCost};
+    pub use crate::error::{GameError, report_error};
     pub use crate::generate_map::MapTilemap;
     pub use crate::health_bar::*;
-    pub use crate::items::{Item, Items};
-    pub use crate::room::{RoomInfo, RoomTile, RoomTilemap, RoomType};
+    pub use crate::hud::{HudConfig, HudElement, HudPart};
+    pub use crate::items::{Equipment, GearBonus, GearSlot, Item, Items};
+    pub use crate::layout::{ScreenLayout, SidePanel};
+    pub use crate::notifications::{Notifications, Severity};
+    pub use crate::room::{RoomInfo, RoomKind, RoomTile, RoomTilemap, RoomType, RoomWeights};
     #[cfg(feature = "sqlite")]
-    pub use crate::saving::{GameID, SaveGame, SaveGameInfo};
+    pub use crate::saving::{GameID, SaveGame, SaveGameInfo, SaveKind};
     pub use crate::style::{Icons, Style};
     pub use crate::tile::*;
+    pub use crate::time_scale::TimeScale;
+    pub use crate::tooltip::Tooltip;
     pub use crate::util::*;
 }
 
 use animation::AnimationPlugin;
+#[cfg(feature = "debug")]
+use asset_budget::AssetBudgetPlugin;
+use audio::AudioPlugin;
+use barks::BarksPlugin;
+use bot::BotPlugin;
 use camera::CameraPlugin;
+use cli::{CliArgs, CliPlugin, USAGE};
+#[cfg(feature = "sqlite")]
+use component_blob::ComponentBlobPlugin;
+use config::ConfigPlugin;
+use context_menu::ContextMenuPlugin;
 use controls::ControlsPlugin;
+use damage_popup::DamagePopupPlugin;
 use database::DatabasePlugin;
+use effects::EffectsPlugin;
+use error::ErrorPlugin;
+use focus_nav::FocusNavPlugin;
 use game::GamePlugin;
 use generate_map::GenerateMapPlugin;
 use health_bar::HpPlugin;
+use hotplug::HotplugPlugin;
+use hud::HudPlugin;
+use input_log::InputLogPlugin;
+use layout::LayoutPlugin;
 use menu::MenuPlugin;
+use notifications::NotificationsPlugin;
 use prelude::*;
+#[cfg(feature = "debug")]
+use profiler::ProfilerPlugin;
+use scrollbar::ScrollbarPlugin;
 use sky::SkyPlugin;
 use style::StylePlugin;
 use tile::TilePlugin;
+use time_scale::TimeScalePlugin;
+use tooltip::TooltipPlugin;
 //use attack_options::AttackOptionsPlugin;
 
 #[cfg(feature = "debug")]
@@ -71,10 +139,28 @@ use bevy::{
     text::FontSmoothing,
 };
 
+use bevy::window::WindowResolution;
 use bevy_ecs_tilemap::prelude::*;
 use bevy_ui_text_input::TextInputPlugin;
 
 fn main() {
+    let args = match CliArgs::parse(std::env::args().skip(1)) {
+        Ok(args) if args.help => {
+            println!("{USAGE}");
+            return;
+        }
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("{err}\n\n{USAGE}");
+            std::process::exit(2);
+        }
+    };
+
+    let mut resolution = WindowResolution::default();
+    if let Some((width, height)) = args.windowed {
+        resolution = WindowResolution::new(width as f32, height as f32);
+    }
+
     let mut app = App::new();
 
     app.add_plugins(
@@ -84,6 +170,7 @@ fn main() {
                 primary_window: Some(Window {
                     title: "A Hex Befalls the Hexagons".into(),
                     fit_canvas_to_parent: true,
+                    resolution,
                     ..default()
                 }),
                 ..default()
@@ -103,8 +190,31 @@ fn main() {
             refresh_interval: core::time::Duration::from_millis(100),
             enabled: true,
         },
-    });
+    })
+    .add_plugins(ProfilerPlugin)
+    .add_plugins(AssetBudgetPlugin);
+
+    if args.bot {
+        // Keep the bot's saves out of the player's database.
+        app.insert_non_send_resource(Database::open_in_memory().unwrap());
+    }
+
+    add_game_plugins(&mut app);
+
+    if args.bot {
+        app.add_plugins(BotPlugin {
+            seed: args.seed.unwrap_or(bot::BOT_SEED),
+            ..default()
+        });
+    } else {
+        app.add_plugins(CliPlugin(args));
+    }
+
+    app.run();
+}
 
+/// Everything that makes up the game, without the windowing and rendering setup.
+fn add_game_plugins(app: &mut App) {
     // third party plugins
     app.add_plugins(TilemapPlugin).add_plugins(TextInputPlugin);
 
@@ -114,23 +224,41 @@ fn main() {
 
     app.init_state::<AppState>();
     // Local Plugins
-    app.add_plugins(DatabasePlugin)
+    app.add_plugins(ErrorPlugin)
+        .add_plugins(ConfigPlugin)
+        .add_plugins(DatabasePlugin)
         .add_plugins(AnimationPlugin)
+        .add_plugins(AudioPlugin)
+        .add_plugins(EffectsPlugin)
+        .add_plugins(DamagePopupPlugin)
+        .add_plugins(BarksPlugin)
         .add_plugins(TilePlugin)
         .add_plugins(GamePlugin)
         .add_plugins(StylePlugin)
         .add_plugins(ControlsPlugin)
+        .add_plugins(InputLogPlugin)
+        .add_plugins(HotplugPlugin)
         .add_plugins(MenuPlugin)
+        .add_plugins(NotificationsPlugin)
+        .add_plugins(TooltipPlugin)
+        .add_plugins(FocusNavPlugin)
+        .add_plugins(ContextMenuPlugin)
+        .add_plugins(ScrollbarPlugin)
+        .add_plugins(TimeScalePlugin)
         .add_plugins(SkyPlugin)
         .add_plugins(CameraPlugin)
+        .add_plugins(LayoutPlugin)
         .add_plugins(GenerateMapPlugin)
-        .add_plugins(HpPlugin);
+        .add_plugins(HpPlugin)
+        .add_plugins(HudPlugin);
+
+    #[cfg(feature = "sqlite")]
+    app.add_plugins(ComponentBlobPlugin);
 
     app.add_systems(
         Update,
         check_textures.run_if(in_state(AppState::InitialLoading)),
-    )
-    .run();
+    );
 }
 
 /// Wait for all of the `StartUp` commands to run for first iteration
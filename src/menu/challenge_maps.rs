@@ -0,0 +1,429 @@
+//! Challenge maps, handcrafted layouts shipped with the game and played in place of a generated world.
+//!
+//! They are laid out like exports from the map editor and embedded in the binary.
+//! Like daily runs, they are played on fixed settings, and every run of one is recorded
+//! to show how it has gone so far.
+
+#[cfg(feature = "sqlite")]
+use super::daily_run::ChosenDaily;
+use super::new_game::{ChosenWorld, NewGameState};
+#[cfg(feature = "sqlite")]
+use super::seed_history::SeedOutcome;
+#[cfg(feature = "sqlite")]
+use crate::game::{GameState, RunStats};
+use crate::generate_map::{LayoutError, MapLayout};
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter, IntoEnumIterator};
+
+pub struct ChallengeMapsPlugin;
+
+impl Plugin for ChallengeMapsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(NewGameState::ChallengeMaps), challenge_maps_enter);
+
+        #[cfg(feature = "sqlite")]
+        app.add_systems(
+            OnEnter(NewGameState::GeneratingWorld),
+            record_challenge_start,
+        )
+        .add_systems(
+            OnEnter(GameState::Victory),
+            record_challenge_finish(SeedOutcome::Won),
+        )
+        .add_systems(
+            OnEnter(GameState::GameOver),
+            record_challenge_finish(SeedOutcome::Lost),
+        );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, EnumIter, Display)]
+pub enum ChallengeMap {
+    #[strum(to_string = "Tutorial Dungeon")]
+    Tutorial,
+    #[strum(to_string = "Boss Rush")]
+    BossRush,
+    #[strum(to_string = "Puzzle Gauntlet")]
+    PuzzleGauntlet,
+}
+
+impl ChallengeMap {
+    pub fn description(&self) -> &'static str {
+        match self {
+            ChallengeMap::Tutorial => "A short dungeon with one of every room, and a single pillar",
+            ChallengeMap::BossRush => {
+                "Three guardians, with hard fights on the way to each of them"
+            }
+            ChallengeMap::PuzzleGauntlet => "A winding path of puzzles to the pillar at its end",
+        }
+    }
+
+    fn ron(&self) -> &'static str {
+        match self {
+            ChallengeMap::Tutorial => include_str!("../../assets/challenges/tutorial.ron"),
+            ChallengeMap::BossRush => include_str!("../../assets/challenges/boss_rush.ron"),
+            ChallengeMap::PuzzleGauntlet => {
+                include_str!("../../assets/challenges/puzzle_gauntlet.ron")
+            }
+        }
+    }
+
+    pub fn layout(&self) -> Result<MapLayout, LayoutError> {
+        MapLayout::from_ron(self.ron())
+    }
+}
+
+/// The challenge map picked on the Challenge Maps screen, until its world is generated.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChosenChallengeMap(pub ChallengeMap);
+
+/// Challenge maps are all played on these, whatever was picked for other games.
+/// The map is the size of the layout, so the layout fits on it.
+pub fn challenge_settings(layout: &MapLayout) -> (Difficulty, GameRules) {
+    let rules = GameRules::default();
+    (
+        Difficulty::Normal,
+        GameRules {
+            shape: layout.shape,
+            ..rules
+        },
+    )
+}
+
+/// A run of a challenge map, kept after its save is deleted.
+#[cfg(feature = "sqlite")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChallengeRun {
+    pub game_id: GameID,
+    pub map: ChallengeMap,
+    pub outcome: SeedOutcome,
+    pub rooms_explored: u32,
+    pub turns_taken: u32,
+}
+
+#[cfg(feature = "sqlite")]
+impl ChallengeRun {
+    pub fn start(db: &Database, game_id: GameID, map: ChallengeMap) -> Result<(), DatabaseError> {
+        db.connection.execute(
+            "INSERT OR IGNORE INTO ChallengeRun(game_id,challenge,outcome) VALUES(?1, ?2, ?3)",
+            (
+                game_id.0,
+                ron::to_string(&map).unwrap(),
+                ron::to_string(&SeedOutcome::Unfinished).unwrap(),
+            ),
+        )?;
+        Ok(())
+    }
+
+    /// Records how the game ended, if it is a run of a challenge map.
+    /// Only the first ending counts, so a loss can't be taken back by loading.
+    pub fn finish(
+        db: &Database,
+        game_id: GameID,
+        outcome: SeedOutcome,
+        stats: &RunStats,
+    ) -> Result<(), DatabaseError> {
+        db.connection.execute(
+            "UPDATE ChallengeRun SET outcome = ?2, rooms_explored = ?3, turns_taken = ?4
+                WHERE game_id = ?1 AND outcome = ?5",
+            (
+                game_id.0,
+                ron::to_string(&outcome).unwrap(),
+                stats.rooms_explored,
+                stats.turns_taken,
+                ron::to_string(&SeedOutcome::Unfinished).unwrap(),
+            ),
+        )?;
+        Ok(())
+    }
+
+    /// Drops a run that never got going, as its world was not finished generating.
+    pub fn forget(db: &Database, game_id: GameID) -> Result<(), DatabaseError> {
+        db.connection.execute(
+            "DELETE FROM ChallengeRun WHERE game_id = :game_id",
+            (game_id.0,),
+        )?;
+        Ok(())
+    }
+
+    /// Every run of the map, oldest first.
+    pub fn of_map(db: &Database, map: ChallengeMap) -> Result<Vec<ChallengeRun>, DatabaseError> {
+        let runs = db
+            .connection
+            .prepare(
+                "SELECT game_id,outcome,rooms_explored,turns_taken
+                    FROM ChallengeRun WHERE challenge = :challenge ORDER BY game_id",
+            )?
+            .query_map((ron::to_string(&map).unwrap(),), |row| {
+                let outcome: String = row.get(1)?;
+                Ok(ChallengeRun {
+                    game_id: GameID(row.get(0)?),
+                    map,
+                    outcome: ron::from_str(&outcome).unwrap_or(SeedOutcome::Unfinished),
+                    rooms_explored: row.get(2)?,
+                    turns_taken: row.get(3)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(runs)
+    }
+}
+
+/// What the Challenge Maps screen shows about the runs of a map.
+#[cfg(feature = "sqlite")]
+pub fn challenge_record(runs: &[ChallengeRun]) -> String {
+    let cleared: Vec<&ChallengeRun> = runs
+        .iter()
+        .filter(|run| run.outcome == SeedOutcome::Won)
+        .collect();
+    match cleared.iter().map(|run| run.turns_taken).min() {
+        _ if runs.is_empty() => "Not played yet".to_string(),
+        Some(turns) => format!(
+            "Cleared {} of {} runs, best in {turns} turns",
+            cleared.len(),
+            runs.len()
+        ),
+        None => format!("Played {} times, not cleared yet", runs.len()),
+    }
+}
+
+fn challenge_maps_enter(
+    mut commands: Commands,
+    style: Res<Style>,
+    #[cfg(feature = "sqlite")] db: NonSend<Database>,
+) {
+    let button_node = Node {
+        width: Val::Px(200.0),
+        height: Val::Px(65.0),
+        margin: UiRect::all(Val::Px(5.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let button_text_style = (style.font(33.0), TextColor(style.text_color));
+    let text_style = (style.font(24.0), TextColor(style.text_color));
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            StateScoped(NewGameState::ChallengeMaps),
+        ))
+        .with_children(|builder| {
+            builder.spawn((
+                Text::new("Challenge Maps"),
+                style.font(66.0),
+                TextColor(style.text_color),
+            ));
+
+            for map in ChallengeMap::iter() {
+                builder
+                    .spawn((
+                        Node {
+                            width: Val::Px(700.0),
+                            margin: UiRect::all(Val::Px(10.0)),
+                            padding: UiRect::all(Val::Px(10.0)),
+                            align_items: AlignItems::Center,
+                            justify_content: JustifyContent::SpaceBetween,
+                            ..default()
+                        },
+                        BackgroundColor(style.background_color),
+                    ))
+                    .with_children(|builder| {
+                        builder
+                            .spawn(Node {
+                                flex_direction: FlexDirection::Column,
+                                ..default()
+                            })
+                            .with_children(|builder| {
+                                builder
+                                    .spawn((Text::new(map.to_string()), button_text_style.clone()));
+                                builder.spawn((Text::new(map.description()), text_style.clone()));
+
+                                #[cfg(feature = "sqlite")]
+                                builder.spawn((
+                                    Text::new(challenge_record(
+                                        &ChallengeRun::of_map(&db, map).unwrap_or_default(),
+                                    )),
+                                    text_style.clone(),
+                                ));
+                            });
+
+                        builder
+                            .spawn((
+                                Button,
+                                button_node.clone(),
+                                BackgroundColor(style.button_color),
+                                children![(
+                                    Text::new("Play"),
+                                    button_text_style.clone(),
+                                    Pickable::IGNORE
+                                )],
+                            ))
+                            .observe(play_challenge_on_click(map));
+                    });
+            }
+
+            builder
+                .spawn((
+                    Button,
+                    button_node.clone(),
+                    BackgroundColor(style.button_color),
+                    children![(
+                        Text::new("Back"),
+                        button_text_style.clone(),
+                        Pickable::IGNORE
+                    )],
+                ))
+                .observe(change_state_on_click(
+                    PointerButton::Primary,
+                    NewGameState::Main,
+                ));
+        });
+}
+
+#[allow(clippy::type_complexity)]
+fn play_challenge_on_click(
+    map: ChallengeMap,
+) -> impl Fn(Trigger<Pointer<Click>>, Commands, ResMut<NextState<NewGameState>>, ResMut<Notifications>)
+{
+    move |mut click, mut commands, mut next_new_game_state, mut notifications| {
+        let PointerButton::Primary = click.button else {
+            return;
+        };
+        click.propagate(false);
+
+        let layout = match map.layout() {
+            Ok(layout) => layout,
+            Err(err) => {
+                notifications.error(format!("Couldn't load {map}: {err}"));
+                return;
+            }
+        };
+        // Every run of a challenge map shares the seed of its layout.
+        commands.insert_resource(ChosenWorld {
+            seed: layout.hash(),
+            name: map.to_string(),
+        });
+        commands.insert_resource(ChosenChallengeMap(map));
+        #[cfg(feature = "sqlite")]
+        commands.remove_resource::<ChosenDaily>();
+        next_new_game_state.set(NewGameState::PartySelect);
+    }
+}
+
+#[cfg(feature = "sqlite")]
+fn record_challenge_start(
+    mut commands: Commands,
+    db: NonSend<Database>,
+    challenge: Option<Res<ChosenChallengeMap>>,
+    save: Option<Res<SaveGame>>,
+) -> Result {
+    let (Some(challenge), Some(save)) = (challenge, save) else {
+        return Ok(());
+    };
+    commands.remove_resource::<ChosenChallengeMap>();
+
+    ChallengeRun::start(&db, save.game_id, challenge.0).map_err(GameError::from)?;
+    Ok(())
+}
+
+#[cfg(feature = "sqlite")]
+fn record_challenge_finish(
+    outcome: SeedOutcome,
+) -> impl FnMut(NonSend<Database>, Option<Res<SaveGame>>, Option<Res<RunStats>>) -> Result {
+    move |db, save, stats| {
+        let (Some(save), Some(stats)) = (save, stats) else {
+            return Ok(());
+        };
+
+        ChallengeRun::finish(&db, save.game_id, outcome, &stats).map_err(GameError::from)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::generate_map::MapShape;
+
+    #[test]
+    fn challenge_maps_are_playable() {
+        for map in ChallengeMap::iter() {
+            let layout = map.layout().unwrap_or_else(|err| panic!("{map}: {err}"));
+            assert_eq!(layout.shape.pillars as usize, layout.pillars.len(), "{map}");
+            assert!(MapShape::RADII.contains(&layout.shape.radius), "{map}");
+            assert!(
+                layout
+                    .rooms
+                    .iter()
+                    .all(|(_, r_type, _)| *r_type != RoomType::Stairs),
+                "{map} has no floor below it"
+            );
+        }
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn challenge_runs_are_recorded_by_map() {
+        let db = Database::open_in_memory().unwrap();
+        let layout = ChallengeMap::Tutorial.layout().unwrap();
+        let (difficulty, rules) = challenge_settings(&layout);
+        let new_run = |map, name| {
+            let save = SaveGame::new(&db, layout.hash(), difficulty, rules, name);
+            ChallengeRun::start(&db, save.game_id, map).unwrap();
+            save.game_id
+        };
+        let stats = |turns_taken| RunStats {
+            turns_taken,
+            ..default()
+        };
+
+        let lost = new_run(ChallengeMap::Tutorial, "lost");
+        let slow = new_run(ChallengeMap::Tutorial, "slow");
+        let fast = new_run(ChallengeMap::Tutorial, "fast");
+        let other = new_run(ChallengeMap::BossRush, "other");
+        ChallengeRun::finish(&db, lost, SeedOutcome::Lost, &stats(10)).unwrap();
+        ChallengeRun::finish(&db, lost, SeedOutcome::Won, &stats(5)).unwrap();
+        ChallengeRun::finish(&db, slow, SeedOutcome::Won, &stats(40)).unwrap();
+        ChallengeRun::finish(&db, fast, SeedOutcome::Won, &stats(25)).unwrap();
+        SaveGame::delete(&db, fast).unwrap();
+
+        let tutorial = ChallengeRun::of_map(&db, ChallengeMap::Tutorial).unwrap();
+        let outcomes: Vec<_> = tutorial
+            .iter()
+            .map(|run| (run.game_id, run.outcome))
+            .collect();
+        assert_eq!(
+            outcomes,
+            [
+                (lost, SeedOutcome::Lost),
+                (slow, SeedOutcome::Won),
+                (fast, SeedOutcome::Won)
+            ]
+        );
+        assert_eq!(
+            challenge_record(&tutorial),
+            "Cleared 2 of 3 runs, best in 25 turns"
+        );
+
+        let boss_rush = ChallengeRun::of_map(&db, ChallengeMap::BossRush).unwrap();
+        assert_eq!(
+            challenge_record(&boss_rush),
+            "Played 1 times, not cleared yet"
+        );
+        ChallengeRun::forget(&db, other).unwrap();
+        assert_eq!(
+            challenge_record(&ChallengeRun::of_map(&db, ChallengeMap::BossRush).unwrap()),
+            "Not played yet"
+        );
+    }
+}
@@ -8,15 +8,22 @@ use bevy::{
     a11y::AccessibilityNode,
     ecs::hierarchy::ChildSpawnerCommands,
     input::{
-        ButtonState, gamepad::GamepadButtonChangedEvent, keyboard::KeyboardInput,
-        mouse::MouseButtonInput,
+        ButtonState,
+        gamepad::{GamepadAxisChangedEvent, GamepadButtonChangedEvent},
+        keyboard::KeyboardInput,
+        mouse::{MouseButtonInput, MouseWheel},
     },
     picking::hover::HoverMap,
     prelude::*,
 };
 
 use crate::controls::Control;
-use crate::controls::{Input, Keybind, input_to_screen};
+use crate::controls::{AxisDirection, Input, Keybind, MouseWheelAxis, input_to_screen};
+use crate::input_log::{InputLogging, save_input_log};
+
+/// How far a stick has to be pushed to be bound,
+/// further than it takes to press so a resting stick isn't bound by accident.
+const AXIS_BIND_THRESHOLD: f32 = 0.8;
 
 pub struct MenuControlsPlugin;
 
@@ -34,6 +41,11 @@ impl Plugin for MenuControlsPlugin {
             Update,
             (
                 controls_changed.run_if(resource_exists_and_changed::<ControlsWIP>),
+                show_unsaved_changes.run_if(
+                    resource_exists_and_changed::<ControlsWIP>.or(resource_changed::<Controls>),
+                ),
+                show_active_device,
+                update_input_log_toggle.run_if(resource_changed::<InputLogging>),
                 escape_out,
             )
                 .run_if(in_state(MenuState::Controls)),
@@ -89,9 +101,29 @@ impl FromWorld for ControlsWIP {
 #[derive(Component)]
 pub struct CancelPromptButton;
 
+/// The name of the control at the start of each row.
+#[derive(Component)]
+pub struct ControlLabel(pub Control);
+
+/// Shows which kind of device was last used.
+#[derive(Component)]
+pub struct ActiveDeviceText;
+
+/// Shown while there are changes that haven't been saved.
+#[derive(Component)]
+pub struct UnsavedChangesBanner;
+
 #[derive(Component)]
 pub struct PromptButton(pub Control, pub usize);
 
+/// The text of the button that turns the input log on and off.
+#[derive(Component)]
+struct InputLogToggleText;
+
+fn input_log_toggle_text(logging: InputLogging) -> String {
+    format!("Input Log: {}", if logging.0 { "On" } else { "Off" })
+}
+
 fn prompt_on_click(
     mut click: Trigger<Pointer<Click>>,
     prompt: Query<&PromptButton>,
@@ -186,6 +218,32 @@ fn back_button_click(
     }
 }
 
+fn toggle_input_log_on_click(
+    mut click: Trigger<Pointer<Click>>,
+    mut logging: ResMut<InputLogging>,
+) {
+    click.propagate(false);
+    if click.button == PointerButton::Primary {
+        logging.0 = !logging.0;
+    }
+}
+
+fn save_input_log_on_click(mut click: Trigger<Pointer<Click>>, mut commands: Commands) {
+    click.propagate(false);
+    if click.button == PointerButton::Primary {
+        commands.run_system_cached(save_input_log);
+    }
+}
+
+fn update_input_log_toggle(
+    logging: Res<InputLogging>,
+    mut texts: Query<&mut Text, With<InputLogToggleText>>,
+) {
+    for mut text in texts.iter_mut() {
+        text.0 = input_log_toggle_text(*logging);
+    }
+}
+
 fn escape_out(
     controls_state: Res<State<ControlsState>>,
     mut input_focus: ResMut<InputFocus>,
@@ -220,7 +278,12 @@ fn escape_out(
     }
 }
 
-fn controls_enter(mut commands: Commands, style: Res<Style>, controls: Res<Controls>) {
+fn controls_enter(
+    mut commands: Commands,
+    style: Res<Style>,
+    controls: Res<Controls>,
+    logging: Res<InputLogging>,
+) {
     let button_node = Node {
         width: Val::Px(200.0),
         height: Val::Px(65.0),
@@ -270,7 +333,48 @@ fn controls_enter(mut commands: Commands, style: Res<Style>, controls: Res<Contr
                     controls
                         .clone()
                         .into_iter()
-                        .for_each(|keybind| controls_row(builder, &style, keybind))
+                        .for_each(|keybind| controls_row(builder, &style, keybind));
+
+                    // For bug reports about inputs that didn't do what was expected.
+                    builder
+                        .spawn(Node {
+                            align_items: AlignItems::Center,
+                            ..default()
+                        })
+                        .with_children(|builder| {
+                            builder
+                                .spawn((
+                                    Button,
+                                    Node {
+                                        width: Val::Px(300.0),
+                                        ..button_node.clone()
+                                    },
+                                    BackgroundColor(style.button_color),
+                                    children![(
+                                        Text::new(input_log_toggle_text(*logging)),
+                                        button_text_style.clone(),
+                                        InputLogToggleText,
+                                        Pickable::IGNORE,
+                                    )],
+                                ))
+                                .observe(toggle_input_log_on_click);
+
+                            builder
+                                .spawn((
+                                    Button,
+                                    Node {
+                                        width: Val::Px(300.0),
+                                        ..button_node.clone()
+                                    },
+                                    BackgroundColor(style.button_color),
+                                    children![(
+                                        Text::new("Save Input Log"),
+                                        button_text_style.clone(),
+                                        Pickable::IGNORE,
+                                    )],
+                                ))
+                                .observe(save_input_log_on_click);
+                        });
                 });
 
             builder
@@ -323,6 +427,23 @@ fn controls_enter(mut commands: Commands, style: Res<Style>, controls: Res<Contr
                         ))
                         .observe(reset_controls_on_click);
 
+                    builder.spawn((
+                        Text::new(""),
+                        style.font(28.0),
+                        TextColor(style.text_color),
+                        ActiveDeviceText,
+                        Pickable::IGNORE,
+                    ));
+
+                    builder.spawn((
+                        Text::new("Unsaved changes"),
+                        style.font(28.0),
+                        TextColor(style.accent_color),
+                        Visibility::Hidden,
+                        UnsavedChangesBanner,
+                        Pickable::IGNORE,
+                    ));
+
                     builder.spawn((
                         Text::new(
                             "Note: The keys show are based on the physical key and may not reflect the keyboard input in a text box.",
@@ -359,6 +480,7 @@ fn controls_row(builder: &mut ChildSpawnerCommands<'_>, style: &Style, keybind:
                         Text::new(control.to_string()),
                         TextColor(style.title_color),
                         style.font(33.0),
+                        ControlLabel(control),
                         Pickable::IGNORE,
                     ));
                 });
@@ -379,6 +501,9 @@ fn controls_row(builder: &mut ChildSpawnerCommands<'_>, style: &Style, keybind:
                         BackgroundColor(style.button_color),
                         AccessibilityNode(Accessible::new(Role::ListItem)),
                         PromptButton(control, i),
+                        Tooltip::new(control.to_string()).with_body(
+                            "Left click to rebind\nRight click to clear\nMiddle click to reset",
+                        ),
                         Pickable {
                             should_block_lower: false,
                             is_hoverable: true,
@@ -402,12 +527,14 @@ fn controls_row(builder: &mut ChildSpawnerCommands<'_>, style: &Style, keybind:
                     },
                     BackgroundColor(style.button_color),
                     AccessibilityNode(Accessible::new(Role::ListItem)),
+                    Tooltip::new(format!("Reset {control}"))
+                        .with_body("Resets all bindings to the defaults"),
                     Pickable {
                         should_block_lower: false,
                         is_hoverable: true,
                     },
                     children![(
-                        Text("Reset Both".into()),
+                        Text("Reset All".into()),
                         style.font(33.0),
                         TextColor(style.text_color)
                     )],
@@ -434,6 +561,49 @@ fn controls_changed(
     }
 }
 
+fn show_active_device(
+    device: Res<LastInputDevice>,
+    mut text: Query<&mut Text, With<ActiveDeviceText>>,
+    added: Query<(), Added<ActiveDeviceText>>,
+) {
+    if !device.is_changed() && added.is_empty() {
+        return;
+    }
+
+    for mut text in text.iter_mut() {
+        text.0 = format!("Using: {}", *device);
+    }
+}
+
+/// Marks the rows that differ from the saved controls,
+/// and shows the banner if any of them do.
+fn show_unsaved_changes(
+    style: Res<Style>,
+    controls_master: Res<Controls>,
+    controls_wip: Res<ControlsWIP>,
+    mut labels: Query<(&ControlLabel, &mut TextColor)>,
+    mut banner: Query<&mut Visibility, With<UnsavedChangesBanner>>,
+) {
+    for (ControlLabel(control), mut color) in labels.iter_mut() {
+        let modified =
+            controls_wip.0.get_control(*control) != controls_master.get_control(*control);
+        color.0 = if modified {
+            style.accent_color
+        } else {
+            style.title_color
+        };
+    }
+
+    let visibility = if controls_wip.0 == *controls_master {
+        Visibility::Hidden
+    } else {
+        Visibility::Inherited
+    };
+    for mut banner in banner.iter_mut() {
+        *banner = visibility;
+    }
+}
+
 fn control_prompt_enter(mut commands: Commands, style: Res<Style>) {
     let button_text_style = (
         style.font(33.0),
@@ -466,7 +636,7 @@ fn control_prompt_enter(mut commands: Commands, style: Res<Style>) {
             },
             children![
                 (
-                    Text::new("Press any key to bind,"),
+                    Text::new("Press any key, or move a stick or the scroll wheel to bind,"),
                     style.font(33.0),
                     TextColor(style.text_color),
                     Node {
@@ -512,6 +682,8 @@ fn assign_key_input(
     mut keyboard: EventReader<KeyboardInput>,
     mut mouse: EventReader<MouseButtonInput>,
     mut gamepad: EventReader<GamepadButtonChangedEvent>,
+    mut gamepad_axis: EventReader<GamepadAxisChangedEvent>,
+    mut wheel: EventReader<MouseWheel>,
     mut controls: ResMut<ControlsWIP>,
     cancel_button_query: Query<Has<CancelPromptButton>>,
     target: Res<PromptTarget>,
@@ -566,6 +738,32 @@ fn assign_key_input(
             ButtonState::Released => {}
         }
     }
+
+    for ev in gamepad_axis.read() {
+        if ev.value.abs() < AXIS_BIND_THRESHOLD {
+            continue;
+        }
+
+        let input = Input::GamepadAxis(ev.axis, AxisDirection::of(ev.value));
+        controls.0.set_control(target.0, target.1, Some(input));
+        commands.set_state(ControlsState::Main);
+        return;
+    }
+
+    for ev in wheel.read() {
+        let (axis, value) = if ev.y != 0.0 {
+            (MouseWheelAxis::Y, ev.y)
+        } else if ev.x != 0.0 {
+            (MouseWheelAxis::X, ev.x)
+        } else {
+            continue;
+        };
+
+        let input = Input::MouseWheelAxis(axis, AxisDirection::of(value));
+        controls.0.set_control(target.0, target.1, Some(input));
+        commands.set_state(ControlsState::Main);
+        return;
+    }
 }
 
 fn control_save_warning_enter(mut commands: Commands, style: Res<Style>) {
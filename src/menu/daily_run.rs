@@ -0,0 +1,270 @@
+//! The daily run, a world shared by everyone for the day. Its seed comes from the UTC date and
+//! its settings are fixed, so each run of it is recorded to compare against the others.
+
+use super::challenge_maps::ChosenChallengeMap;
+use super::new_game::{ChosenWorld, NewGameState};
+use super::seed_history::SeedOutcome;
+use crate::game::{GameState, RunStats};
+use crate::prelude::*;
+use chrono::{NaiveDate, Utc};
+
+pub struct DailyRunPlugin;
+
+impl Plugin for DailyRunPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(NewGameState::GeneratingWorld), record_daily_start)
+            .add_systems(
+                OnEnter(GameState::Victory),
+                record_daily_finish(SeedOutcome::Won),
+            )
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                record_daily_finish(SeedOutcome::Lost),
+            );
+    }
+}
+
+/// The date of the daily run picked on the New Game screen, until its world is generated.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChosenDaily(pub NaiveDate);
+
+/// The date of today's daily run, which starts at midnight UTC for everyone.
+pub fn daily_date() -> NaiveDate {
+    Utc::now().date_naive()
+}
+
+/// The seed of the daily run on the date.
+pub fn daily_seed(date: NaiveDate) -> u64 {
+    let hash = blake3::hash(format!("daily run {date}").as_bytes());
+    u64::from_le_bytes(hash.as_bytes()[..8].try_into().unwrap())
+}
+
+/// Daily runs are all played on these, whatever was picked for other games.
+pub fn daily_settings() -> (Difficulty, GameRules) {
+    (Difficulty::Normal, GameRules::default())
+}
+
+/// A run of a daily world, kept after its save is deleted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DailyRun {
+    pub game_id: GameID,
+    pub date: NaiveDate,
+    pub seed: u64,
+    pub outcome: SeedOutcome,
+    pub rooms_explored: u32,
+    pub enemies_killed: u32,
+    pub damage_taken: u32,
+    pub turns_taken: u32,
+}
+
+impl DailyRun {
+    pub fn start(db: &Database, game_id: GameID, date: NaiveDate) -> Result<(), DatabaseError> {
+        db.connection.execute(
+            "INSERT OR IGNORE INTO DailyRun(game_id,date,world_seed,outcome) VALUES(?1, ?2, ?3, ?4)",
+            (
+                game_id.0,
+                date,
+                daily_seed(date) as i64,
+                ron::to_string(&SeedOutcome::Unfinished).unwrap(),
+            ),
+        )?;
+        Ok(())
+    }
+
+    /// Records how the game ended, if it is a daily run.
+    /// Only the first ending counts, so a loss can't be taken back by loading.
+    pub fn finish(
+        db: &Database,
+        game_id: GameID,
+        outcome: SeedOutcome,
+        stats: &RunStats,
+    ) -> Result<(), DatabaseError> {
+        db.connection.execute(
+            "UPDATE DailyRun SET outcome = ?2, rooms_explored = ?3, enemies_killed = ?4, damage_taken = ?5, turns_taken = ?6
+                WHERE game_id = ?1 AND outcome = ?7",
+            (
+                game_id.0,
+                ron::to_string(&outcome).unwrap(),
+                stats.rooms_explored,
+                stats.enemies_killed,
+                stats.damage_taken,
+                stats.turns_taken,
+                ron::to_string(&SeedOutcome::Unfinished).unwrap(),
+            ),
+        )?;
+        Ok(())
+    }
+
+    /// Drops a run that never got going, as its world was not finished generating.
+    pub fn forget(db: &Database, game_id: GameID) -> Result<(), DatabaseError> {
+        db.connection.execute(
+            "DELETE FROM DailyRun WHERE game_id = :game_id",
+            (game_id.0,),
+        )?;
+        Ok(())
+    }
+
+    /// The runs of the date's world, best first.
+    pub fn on_date(db: &Database, date: NaiveDate) -> Result<Vec<DailyRun>, DatabaseError> {
+        let mut runs = db
+            .connection
+            .prepare(
+                "SELECT game_id,date,world_seed,outcome,rooms_explored,enemies_killed,damage_taken,turns_taken
+                    FROM DailyRun WHERE date = :date",
+            )?
+            .query_map((date,), |row| {
+                let outcome: String = row.get(3)?;
+                Ok(DailyRun {
+                    game_id: GameID(row.get(0)?),
+                    date: row.get(1)?,
+                    seed: row.get::<_, i64>(2)? as u64,
+                    outcome: ron::from_str(&outcome).unwrap_or(SeedOutcome::Unfinished),
+                    rooms_explored: row.get(4)?,
+                    enemies_killed: row.get(5)?,
+                    damage_taken: row.get(6)?,
+                    turns_taken: row.get(7)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        runs.sort_by_key(|run| run.rank());
+        Ok(runs)
+    }
+
+    /// Wins beat everything else, then going further, then taking fewer turns to do it.
+    fn rank(&self) -> (bool, std::cmp::Reverse<u32>, u32) {
+        (
+            self.outcome != SeedOutcome::Won,
+            std::cmp::Reverse(self.rooms_explored),
+            self.turns_taken,
+        )
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "{}: {} rooms, {} kills, {} turns",
+            self.outcome, self.rooms_explored, self.enemies_killed, self.turns_taken
+        )
+    }
+}
+
+/// What the Daily Run button shows about the runs of today's world.
+pub fn daily_tooltip_body(runs: &[DailyRun]) -> String {
+    let mut body =
+        "The same world for everyone today, played on Normal with the default rules.".to_string();
+    match runs.first() {
+        Some(best) => body.push_str(&format!(
+            "\nPlayed {} times, best run {}",
+            runs.len(),
+            best.summary()
+        )),
+        None => body.push_str("\nNot played yet today"),
+    }
+    body
+}
+
+pub fn daily_run_click(
+    mut click: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    mut next_new_game_state: ResMut<NextState<NewGameState>>,
+) {
+    let PointerButton::Primary = click.button else {
+        return;
+    };
+    click.propagate(false);
+
+    let date = daily_date();
+    commands.insert_resource(ChosenWorld {
+        seed: daily_seed(date),
+        name: format!("Daily {date}"),
+    });
+    commands.insert_resource(ChosenDaily(date));
+    commands.remove_resource::<ChosenChallengeMap>();
+    next_new_game_state.set(NewGameState::PartySelect);
+}
+
+fn record_daily_start(
+    mut commands: Commands,
+    db: NonSend<Database>,
+    daily: Option<Res<ChosenDaily>>,
+    save: Option<Res<SaveGame>>,
+) -> Result {
+    let (Some(daily), Some(save)) = (daily, save) else {
+        return Ok(());
+    };
+    commands.remove_resource::<ChosenDaily>();
+
+    DailyRun::start(&db, save.game_id, daily.0).map_err(GameError::from)?;
+    Ok(())
+}
+
+fn record_daily_finish(
+    outcome: SeedOutcome,
+) -> impl FnMut(NonSend<Database>, Option<Res<SaveGame>>, Option<Res<RunStats>>) -> Result {
+    move |db, save, stats| {
+        let (Some(save), Some(stats)) = (save, stats) else {
+            return Ok(());
+        };
+
+        DailyRun::finish(&db, save.game_id, outcome, &stats).map_err(GameError::from)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn daily_seeds_change_each_day() {
+        let date = NaiveDate::from_ymd_opt(2025, 6, 1).unwrap();
+
+        assert_eq!(daily_seed(date), daily_seed(date));
+        assert_ne!(daily_seed(date), daily_seed(date.succ_opt().unwrap()));
+    }
+
+    #[test]
+    fn only_the_first_ending_of_a_daily_run_counts() {
+        let db = Database::open_in_memory().unwrap();
+        let date = NaiveDate::from_ymd_opt(2025, 6, 1).unwrap();
+        let (difficulty, rules) = daily_settings();
+        let new_run = |name| {
+            let save = SaveGame::new(&db, daily_seed(date), difficulty, rules, name);
+            DailyRun::start(&db, save.game_id, date).unwrap();
+            save.game_id
+        };
+
+        let lost = new_run("lost");
+        let won = new_run("won");
+        let quit = new_run("quit");
+        let stats = RunStats {
+            rooms_explored: 4,
+            turns_taken: 30,
+            ..default()
+        };
+        DailyRun::finish(&db, lost, SeedOutcome::Lost, &stats).unwrap();
+        DailyRun::finish(&db, won, SeedOutcome::Won, &stats).unwrap();
+        DailyRun::finish(&db, lost, SeedOutcome::Won, &stats).unwrap();
+        SaveGame::delete(&db, won).unwrap();
+
+        let runs = DailyRun::on_date(&db, date).unwrap();
+        let outcomes: Vec<_> = runs.iter().map(|run| (run.game_id, run.outcome)).collect();
+        assert_eq!(
+            outcomes,
+            [
+                (won, SeedOutcome::Won),
+                (lost, SeedOutcome::Lost),
+                (quit, SeedOutcome::Unfinished)
+            ]
+        );
+        assert_eq!(runs[0].seed, daily_seed(date));
+
+        DailyRun::forget(&db, quit).unwrap();
+        assert_eq!(DailyRun::on_date(&db, date).unwrap().len(), 2);
+        assert!(
+            DailyRun::on_date(&db, date.succ_opt().unwrap())
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,211 @@
+//! The HUD settings, where each part of the HUD can be hidden or faded.
+
+use super::{MenuState, slider_value};
+use crate::hud::MIN_HUD_OPACITY;
+use crate::prelude::*;
+use strum::IntoEnumIterator;
+
+pub struct MenuHudPlugin;
+
+impl Plugin for MenuHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(MenuState::Hud), hud_enter)
+            .add_systems(
+                Update,
+                update_hud_settings
+                    .run_if(in_state(MenuState::Hud).and(resource_changed::<HudConfig>)),
+            );
+    }
+}
+
+/// The text of the button that shows or hides a part of the HUD.
+#[derive(Component)]
+struct HudToggleText(HudElement);
+
+fn hud_toggle_text(element: HudElement, config: &HudConfig) -> String {
+    let shown = match config.get(element).visible {
+        true => "Shown",
+        false => "Hidden",
+    };
+    format!("{element}: {shown}")
+}
+
+/// The slider for how faded a part of the HUD is.
+#[derive(Component, Clone, Copy)]
+struct HudSlider(HudElement);
+
+/// The filled part of a [`HudSlider`].
+#[derive(Component)]
+struct HudSliderFill(HudElement);
+
+#[derive(Component)]
+struct HudSliderText(HudElement);
+
+/// How much of a slider is filled for `opacity`, so the least opacity is an empty slider.
+fn opacity_fill(opacity: f32) -> f32 {
+    (opacity - MIN_HUD_OPACITY) / (1.0 - MIN_HUD_OPACITY)
+}
+
+fn toggle_hud_on(element: HudElement) -> impl Fn(Trigger<Pointer<Click>>, ResMut<HudConfig>) {
+    move |mut click, mut config| {
+        click.propagate(false);
+
+        if click.button == PointerButton::Primary {
+            let setting = config.get_mut(element);
+            setting.visible = !setting.visible;
+        }
+    }
+}
+
+/// Sets the opacity to wherever its slider was pressed or dragged to.
+fn slide_hud_on<E: std::fmt::Debug + Clone + Reflect>(
+    mut trigger: Trigger<Pointer<E>>,
+    sliders: Query<(&HudSlider, &ComputedNode, &GlobalTransform)>,
+    mut config: ResMut<HudConfig>,
+) {
+    trigger.propagate(false);
+
+    let Ok((HudSlider(element), computed, transform)) = sliders.get(trigger.target()) else {
+        return;
+    };
+
+    let value = slider_value(trigger.pointer_location.position.x, computed, transform);
+    let opacity = MIN_HUD_OPACITY + value * (1.0 - MIN_HUD_OPACITY);
+    config.set_opacity(*element, (opacity * 100.0).round() / 100.0);
+}
+
+fn update_hud_settings(
+    config: Res<HudConfig>,
+    mut fills: Query<(&mut Node, &HudSliderFill)>,
+    mut toggles: Query<(&mut Text, &HudToggleText), Without<HudSliderText>>,
+    mut percents: Query<(&mut Text, &HudSliderText), Without<HudToggleText>>,
+) {
+    for (mut node, HudSliderFill(element)) in fills.iter_mut() {
+        node.width = Val::Percent(opacity_fill(config.get(*element).opacity) * 100.0);
+    }
+    for (mut text, HudToggleText(element)) in toggles.iter_mut() {
+        text.0 = hud_toggle_text(*element, &config);
+    }
+    for (mut text, HudSliderText(element)) in percents.iter_mut() {
+        text.0 = format!("{:.0}%", config.get(*element).opacity * 100.0);
+    }
+}
+
+fn hud_enter(mut commands: Commands, style: Res<Style>, config: Res<HudConfig>) {
+    let button_node = Node {
+        width: Val::Px(200.0),
+        height: Val::Px(65.0),
+        margin: UiRect::all(Val::Px(20.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let button_text_style = (style.font(33.0), TextColor(style.text_color));
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            StateScoped(MenuState::Hud),
+        ))
+        .with_children(|builder| {
+            builder
+                .spawn(Node {
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    ..default()
+                })
+                .with_children(|builder| {
+                    for element in HudElement::iter() {
+                        let opacity = config.get(element).opacity;
+                        builder
+                            .spawn(Node {
+                                align_items: AlignItems::Center,
+                                ..default()
+                            })
+                            .with_children(|builder| {
+                                builder
+                                    .spawn((
+                                        Button,
+                                        Node {
+                                            width: Val::Px(350.0),
+                                            ..button_node.clone()
+                                        },
+                                        BackgroundColor(style.button_color),
+                                        children![(
+                                            Text::new(hud_toggle_text(element, &config)),
+                                            button_text_style.clone(),
+                                            HudToggleText(element),
+                                            Pickable::IGNORE,
+                                        )],
+                                    ))
+                                    .observe(toggle_hud_on(element));
+
+                                builder
+                                    .spawn((
+                                        Node {
+                                            width: Val::Px(200.0),
+                                            height: Val::Px(24.0),
+                                            ..default()
+                                        },
+                                        BackgroundColor(style.button_color),
+                                        HudSlider(element),
+                                    ))
+                                    .observe(slide_hud_on::<Pressed>)
+                                    .observe(slide_hud_on::<Drag>)
+                                    .with_child((
+                                        Node {
+                                            width: Val::Percent(opacity_fill(opacity) * 100.0),
+                                            height: Val::Percent(100.0),
+                                            ..default()
+                                        },
+                                        BackgroundColor(style.accent_color),
+                                        Pickable::IGNORE,
+                                        HudSliderFill(element),
+                                    ));
+
+                                builder.spawn((
+                                    Node {
+                                        width: Val::Px(100.0),
+                                        margin: UiRect::left(Val::Px(10.0)),
+                                        ..default()
+                                    },
+                                    Text::new(format!("{:.0}%", opacity * 100.0)),
+                                    style.font(24.0),
+                                    TextColor(style.text_color),
+                                    HudSliderText(element),
+                                ));
+                            });
+                    }
+
+                    builder
+                        .spawn((
+                            Button,
+                            button_node.clone(),
+                            BackgroundColor(style.button_color),
+                            children![(Text::new("Back"), button_text_style.clone())],
+                        ))
+                        .observe(change_state_on_click(
+                            PointerButton::Primary,
+                            MenuState::Settings,
+                        ));
+                });
+        });
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn the_least_opacity_is_an_empty_slider() {
+        assert_eq!(opacity_fill(MIN_HUD_OPACITY), 0.0);
+        assert_eq!(opacity_fill(1.0), 1.0);
+        assert!((opacity_fill(0.55) - 0.5).abs() < 1e-6);
+    }
+}
@@ -1,10 +1,13 @@
 use super::{MenuState, update_scroll_position_event};
 use crate::prelude::*;
+use crate::saving::{SaveFileError, export_save, import_saves, imports_directory};
 
 use accesskit::{Node as Accessible, Role};
 
 use bevy::input_focus::InputFocus;
 use bevy::{a11y::AccessibilityNode, ecs::hierarchy::ChildSpawnerCommands, prelude::*};
+use bevy_ui_text_input::actions::{TextInputAction, TextInputEdit};
+use bevy_ui_text_input::{TextInputContents, TextInputMode, TextInputNode, TextInputQueue};
 
 pub struct MenuLoadGamePlugin;
 
@@ -19,6 +22,8 @@ impl Plugin for MenuLoadGamePlugin {
         )
         .add_systems(OnExit(MenuState::LoadGame), remove_resource::<SaveGames>)
         .add_systems(OnEnter(LoadGameState::Prompt), prompt_enter)
+        .add_systems(OnEnter(LoadGameState::ConfirmDelete), confirm_delete_enter)
+        .add_systems(OnEnter(LoadGameState::Rename), rename_enter)
         .add_systems(
             OnEnter(LoadGameState::Main),
             remove_resource::<PromptTarget>,
@@ -38,6 +43,8 @@ pub enum LoadGameState {
     #[default]
     Main,
     Prompt,
+    ConfirmDelete,
+    Rename,
     Loading,
 }
 
@@ -53,9 +60,19 @@ fn get_save_games(mut commands: Commands, db: NonSend<Database>) {
     commands.insert_resource(SaveGames(games));
 }
 
+/// The root of the list of saves, rebuilt when saves are imported.
+#[derive(Component)]
+struct LoadGameMenu;
+
 #[derive(Component)]
 pub struct LoadGameButton(pub GameID);
 
+#[derive(Component)]
+struct DeleteGameButton(GameID);
+
+#[derive(Component)]
+struct RenameTextBox;
+
 fn escape_out(
     controls_state: Res<State<LoadGameState>>,
     mut input_focus: ResMut<InputFocus>,
@@ -72,7 +89,9 @@ fn escape_out(
         use LoadGameState as L;
         match *controls_state.get() {
             L::Main => next_menu_state.set(MenuState::Main),
-            L::Prompt | L::Loading => next_load_game_state.set(LoadGameState::Main),
+            L::Prompt | L::ConfirmDelete | L::Rename | L::Loading => {
+                next_load_game_state.set(LoadGameState::Main)
+            }
         }
     }
 }
@@ -98,6 +117,134 @@ fn prompt_on_click(
     }
 }
 
+fn delete_prompt_on_click(
+    mut click: Trigger<Pointer<Click>>,
+    buttons: Query<&DeleteGameButton>,
+    mut commands: Commands,
+    mut next_state: ResMut<NextState<LoadGameState>>,
+) {
+    click.propagate(false);
+
+    let Ok(DeleteGameButton(game_id)) = buttons.get(click.target()) else {
+        return;
+    };
+
+    if click.button == PointerButton::Primary {
+        commands.insert_resource(PromptTarget(*game_id));
+        next_state.set(LoadGameState::ConfirmDelete);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn save_entry_menu_select(
+    select: Trigger<ContextMenuSelect>,
+    mut commands: Commands,
+    db: NonSend<Database>,
+    saves: Res<SaveGames>,
+    prompt: Query<&LoadGameButton>,
+    menu: Query<Entity, With<LoadGameMenu>>,
+    mut notifications: ResMut<Notifications>,
+    mut next_state: ResMut<NextState<LoadGameState>>,
+) {
+    let Ok(LoadGameButton(game_id)) = prompt.get(select.target()) else {
+        return;
+    };
+
+    match select.id {
+        "load" => {
+            commands.insert_resource(PromptTarget(*game_id));
+            next_state.set(LoadGameState::Loading);
+        }
+        "rename" => {
+            commands.insert_resource(PromptTarget(*game_id));
+            next_state.set(LoadGameState::Rename);
+        }
+        "delete" => {
+            commands.insert_resource(PromptTarget(*game_id));
+            next_state.set(LoadGameState::ConfirmDelete);
+        }
+        "duplicate" => {
+            let title = save_title(&saves, *game_id);
+            match SaveGame::duplicate(&db, *game_id) {
+                Ok(_) => {
+                    notifications.success(format!("Duplicated {title}"));
+
+                    menu.iter()
+                        .for_each(|entity| commands.entity(entity).despawn());
+                    commands.run_system_cached(get_save_games);
+                    commands.run_system_cached(load_game_enter);
+                }
+                Err(err @ SaveFileError::Hardcore) => notifications.warn(err.to_string()),
+                Err(err) => {
+                    warn!("Failed to duplicate game {} with: {err}", game_id.0);
+                    notifications.error(format!("Failed to duplicate {title}"));
+                }
+            }
+        }
+        "export" => {
+            let Some(info) = saves.0.iter().find(|save| save.id == *game_id) else {
+                return;
+            };
+            match export_save(&db, info) {
+                Ok(path) => notifications.success(format!(
+                    "Exported {} to {}",
+                    info.title(),
+                    path.display()
+                )),
+                Err(err) => {
+                    warn!("Failed to export game {} with: {err}", game_id.0);
+                    notifications.error(format!("Failed to export {}", info.title()));
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Imports the saves waiting in the imports folder, and lists them with the rest.
+fn import_on_click(
+    mut click: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    db: NonSend<Database>,
+    menu: Query<Entity, With<LoadGameMenu>>,
+    mut notifications: ResMut<Notifications>,
+) {
+    click.propagate(false);
+    if click.button != PointerButton::Primary {
+        return;
+    }
+
+    let directory = imports_directory();
+    match import_saves(&db) {
+        Ok((0, errors)) if errors.is_empty() => notifications.info(format!(
+            "No saves to import, put them in {}",
+            directory.display()
+        )),
+        Ok((count, errors)) => {
+            match count {
+                0 => {}
+                1 => notifications.success("Imported 1 save"),
+                count => notifications.success(format!("Imported {count} saves")),
+            }
+            for err in errors {
+                notifications.error(format!("Failed to import a save: {err}"));
+            }
+
+            menu.iter()
+                .for_each(|entity| commands.entity(entity).despawn());
+            commands.run_system_cached(get_save_games);
+            commands.run_system_cached(load_game_enter);
+        }
+        Err(err) => {
+            warn!(
+                "Failed to import saves from {} with: {err}",
+                directory.display()
+            );
+            notifications.error("Failed to import saves");
+        }
+    }
+}
+
 fn load_game_enter(mut commands: Commands, style: Res<Style>, saves: Res<SaveGames>) {
     let button_node = Node {
         width: Val::Px(200.0),
@@ -125,6 +272,7 @@ fn load_game_enter(mut commands: Commands, style: Res<Style>, saves: Res<SaveGam
                 justify_content: JustifyContent::Center,
                 ..default()
             },
+            LoadGameMenu,
             StateScoped(MenuState::LoadGame),
         ))
         .with_children(|builder| {
@@ -198,6 +346,22 @@ fn load_game_enter(mut commands: Commands, style: Res<Style>, saves: Res<SaveGam
                             PointerButton::Primary,
                             MenuState::Main,
                         ));
+                    builder
+                        .spawn((
+                            Button,
+                            button_node.clone(),
+                            BackgroundColor(style.button_color),
+                            Tooltip::new("Import").with_body(format!(
+                                "Adds the saves in {}",
+                                imports_directory().display()
+                            )),
+                            children![(
+                                Text::new("Import"),
+                                button_text_style.clone(),
+                                Pickable::IGNORE
+                            )],
+                        ))
+                        .observe(import_on_click);
                 });
         });
 }
@@ -219,12 +383,19 @@ fn game_entry(builder: &mut ChildSpawnerCommands<'_>, style: &Style, game: SaveG
                     BackgroundColor(style.button_color),
                     Button,
                     LoadGameButton(game.id),
+                    ContextMenu::new()
+                        .item("load", "Load")
+                        .item("rename", "Rename")
+                        .item("delete", "Delete")
+                        .item("duplicate", "Duplicate")
+                        .item("export", "Export"),
                     Pickable {
                         should_block_lower: false,
                         is_hoverable: true,
                     },
                 ))
                 .observe(prompt_on_click)
+                .observe(save_entry_menu_select)
                 .with_children(|builder| {
                     builder
                         .spawn((
@@ -241,7 +412,7 @@ fn game_entry(builder: &mut ChildSpawnerCommands<'_>, style: &Style, game: SaveG
                         ))
                         .with_children(|builder| {
                             builder.spawn((
-                                Text::new(format!("game: {}", game.id.to_string())),
+                                Text::new(game.title()),
                                 style.font(33.0),
                                 Pickable::IGNORE,
                             ));
@@ -264,6 +435,17 @@ fn game_entry(builder: &mut ChildSpawnerCommands<'_>, style: &Style, game: SaveG
                                 Pickable::IGNORE,
                             ));
 
+                            if let Some(last_autosaved) = game.last_autosaved {
+                                builder.spawn((
+                                    Text::new(format!(
+                                        "last autosave: {}",
+                                        last_autosaved.format("%Y/%m/%d %H:%M")
+                                    )),
+                                    style.font(24.0),
+                                    Pickable::IGNORE,
+                                ));
+                            }
+
                             builder.spawn((
                                 Text::new(format!("seed: {:X}", game.world_seed)),
                                 style.font(24.0),
@@ -271,9 +453,39 @@ fn game_entry(builder: &mut ChildSpawnerCommands<'_>, style: &Style, game: SaveG
                             ));
                         });
                 });
+
+            builder
+                .spawn((
+                    Button,
+                    Node {
+                        width: Val::Px(120.0),
+                        margin: UiRect::left(Val::Px(10.0)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    BackgroundColor(style.button_color),
+                    DeleteGameButton(game.id),
+                    children![(
+                        Text::new("Delete"),
+                        style.font(24.0),
+                        TextColor(style.text_color),
+                        Pickable::IGNORE
+                    )],
+                ))
+                .observe(delete_prompt_on_click);
         });
 }
 
+/// The title of a save, falling back to its id if it is no longer listed.
+fn save_title(saves: &SaveGames, game_id: GameID) -> String {
+    saves
+        .0
+        .iter()
+        .find(|save| save.id == game_id)
+        .map_or_else(|| format!("game {}", game_id.0), SaveGameInfo::title)
+}
+
 fn prompt_enter(mut commands: Commands, style: Res<Style>) {
     let button_text_style = (
         style.font(33.0),
@@ -350,6 +562,270 @@ fn prompt_enter(mut commands: Commands, style: Res<Style>) {
         });
 }
 
+fn confirm_delete_enter(
+    mut commands: Commands,
+    style: Res<Style>,
+    target: Res<PromptTarget>,
+    saves: Res<SaveGames>,
+) {
+    let button_node = Node {
+        width: Val::Px(200.0),
+        height: Val::Px(65.0),
+        margin: UiRect::all(Val::Px(5.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        align_self: AlignSelf::Center,
+        ..default()
+    };
+
+    let button_text_style = (
+        style.font(33.0),
+        TextColor(style.text_color),
+        TextLayout::new_with_justify(JustifyText::Center),
+    );
+
+    commands
+        .spawn((
+            Node {
+                display: Display::Flex,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            StateScoped(LoadGameState::ConfirmDelete),
+            BackgroundColor(style.background_color),
+            ZIndex(2),
+        ))
+        .with_children(|builder| {
+            builder.spawn((
+                Text::new(format!(
+                    "Delete {}?\nThis can't be undone.",
+                    save_title(&saves, target.0)
+                )),
+                TextLayout::new_with_justify(JustifyText::Center),
+                style.font(48.0),
+                TextColor(style.text_color),
+            ));
+
+            builder
+                .spawn((
+                    Button,
+                    button_node.clone(),
+                    BackgroundColor(style.button_color),
+                    children![(Text::new("Delete"), button_text_style.clone())],
+                ))
+                .observe(delete_game_on_click);
+            builder
+                .spawn((
+                    Button,
+                    button_node.clone(),
+                    BackgroundColor(style.button_color),
+                    children![(Text::new("Cancel"), button_text_style.clone())],
+                ))
+                .observe(change_state_on_click(
+                    PointerButton::Primary,
+                    LoadGameState::Main,
+                ));
+        });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn delete_game_on_click(
+    mut click: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    db: NonSend<Database>,
+    target: Res<PromptTarget>,
+    mut saves: ResMut<SaveGames>,
+    entries: Query<(&LoadGameButton, &ChildOf)>,
+    mut notifications: ResMut<Notifications>,
+    mut next_state: ResMut<NextState<LoadGameState>>,
+) {
+    click.propagate(false);
+    if click.button != PointerButton::Primary {
+        return;
+    }
+
+    let game_id = target.0;
+    let title = save_title(&saves, game_id);
+    match SaveGame::delete(&db, game_id) {
+        Ok(()) => {
+            saves.0 = saves
+                .0
+                .iter()
+                .filter(|s| s.id.0 != game_id.0)
+                .cloned()
+                .collect();
+
+            entries
+                .iter()
+                .filter(|(LoadGameButton(id), _)| id.0 == game_id.0)
+                .for_each(|(_, child_of)| commands.entity(child_of.parent()).despawn());
+
+            notifications.info(format!("Deleted {title}"));
+        }
+        Err(err) => {
+            warn!("Failed to delete game {} with: {err}", game_id.0);
+            notifications.error("Failed to delete game");
+        }
+    }
+
+    next_state.set(LoadGameState::Main);
+}
+
+fn rename_enter(
+    mut commands: Commands,
+    style: Res<Style>,
+    target: Res<PromptTarget>,
+    saves: Res<SaveGames>,
+) {
+    let button_node = Node {
+        width: Val::Px(200.0),
+        height: Val::Px(65.0),
+        margin: UiRect::all(Val::Px(5.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        align_self: AlignSelf::Center,
+        ..default()
+    };
+
+    let button_text_style = (
+        style.font(33.0),
+        TextColor(style.text_color),
+        TextLayout::new_with_justify(JustifyText::Center),
+    );
+
+    // Starts from the current name, so it can be touched up.
+    let mut name = TextInputQueue::default();
+    let current = saves.0.iter().find(|save| save.id == target.0);
+    for c in current.map_or("", |save| save.name.as_str()).chars() {
+        name.add(TextInputAction::Edit(TextInputEdit::Insert(c, false)));
+    }
+
+    commands
+        .spawn((
+            Node {
+                display: Display::Flex,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            StateScoped(LoadGameState::Rename),
+            BackgroundColor(style.background_color),
+            ZIndex(2),
+        ))
+        .with_children(|builder| {
+            builder.spawn((
+                Text::new(format!("Rename {}", save_title(&saves, target.0))),
+                TextLayout::new_with_justify(JustifyText::Center),
+                style.font(48.0),
+                TextColor(style.text_color),
+            ));
+
+            builder
+                .spawn((
+                    Node {
+                        width: Val::Px(400.0),
+                        height: Val::Px(60.0),
+                        padding: UiRect::all(Val::Px(10.0)),
+                        margin: UiRect::all(Val::Px(10.0)),
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    BackgroundColor(style.button_color),
+                ))
+                .with_children(|builder| {
+                    builder.spawn((
+                        Node {
+                            width: Val::Percent(100.0),
+                            height: Val::Percent(100.0),
+                            ..default()
+                        },
+                        RenameTextBox,
+                        TextInputContents::default(),
+                        name,
+                        TextInputNode {
+                            clear_on_submit: false,
+                            mode: TextInputMode::SingleLine,
+                            focus_on_pointer_down: true,
+                            unfocus_on_submit: true,
+                            max_chars: Some(24),
+                            ..default()
+                        },
+                        button_text_style.clone(),
+                    ));
+                })
+                .observe(stop_event_propagate::<Pointer<Click>>);
+
+            builder
+                .spawn((
+                    Button,
+                    button_node.clone(),
+                    BackgroundColor(style.button_color),
+                    children![(Text::new("Rename"), button_text_style.clone())],
+                ))
+                .observe(rename_game_on_click);
+            builder
+                .spawn((
+                    Button,
+                    button_node.clone(),
+                    BackgroundColor(style.button_color),
+                    children![(Text::new("Cancel"), button_text_style.clone())],
+                ))
+                .observe(change_state_on_click(
+                    PointerButton::Primary,
+                    LoadGameState::Main,
+                ));
+        });
+}
+
+/// Names the save after what was typed, or leaves it unnamed if nothing was.
+#[allow(clippy::too_many_arguments)]
+fn rename_game_on_click(
+    mut click: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    db: NonSend<Database>,
+    target: Res<PromptTarget>,
+    saves: Res<SaveGames>,
+    name_box: Query<&TextInputContents, With<RenameTextBox>>,
+    menu: Query<Entity, With<LoadGameMenu>>,
+    mut notifications: ResMut<Notifications>,
+    mut next_state: ResMut<NextState<LoadGameState>>,
+) {
+    click.propagate(false);
+    if click.button != PointerButton::Primary {
+        return;
+    }
+
+    let game_id = target.0;
+    let title = save_title(&saves, game_id);
+    let name = name_box.single().map_or("", |name| name.get().trim());
+    match SaveGame::rename(&db, game_id, name) {
+        Ok(()) => {
+            notifications.success(format!("Renamed {title}"));
+
+            menu.iter()
+                .for_each(|entity| commands.entity(entity).despawn());
+            commands.run_system_cached(get_save_games);
+            commands.run_system_cached(load_game_enter);
+        }
+        Err(err) => {
+            warn!("Failed to rename game {} with: {err}", game_id.0);
+            notifications.error(format!("Failed to rename {title}"));
+        }
+    }
+
+    next_state.set(LoadGameState::Main);
+}
+
 fn prep_loading(mut commands: Commands, db: NonSend<Database>, target: Res<PromptTarget>) {
-    commands.insert_resource(SaveGame::load(&db, target.0));
+    let save = SaveGame::load(&db, target.0);
+    commands.insert_resource(save.difficulty);
+    commands.insert_resource(save);
 }
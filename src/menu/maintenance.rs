@@ -0,0 +1,126 @@
+use super::MenuState;
+use crate::database::{GarbageReport, collect_garbage};
+use crate::prelude::*;
+use bevy::prelude::*;
+
+pub struct MenuMaintenancePlugin;
+
+impl Plugin for MenuMaintenancePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(MenuState::Maintenance), maintenance_enter)
+            .add_systems(
+                Update,
+                update_garbage_report.run_if(
+                    in_state(MenuState::Maintenance).and(resource_changed::<GarbageReport>),
+                ),
+            );
+    }
+}
+
+/// The text showing what the last garbage collection removed.
+#[derive(Component)]
+struct GarbageReportText;
+
+fn garbage_report_text(report: &GarbageReport) -> String {
+    format!(
+        "Last cleanup removed:\n\
+         {} unfinished saves\n\
+         {} party members\n\
+         {} rooms\n\
+         {} items\n\
+         {} journal entries\n\
+         {} run stats\n\
+         {} saved components",
+        report.unfinished_saves,
+        report.party_members,
+        report.rooms,
+        report.items,
+        report.journal_entries,
+        report.run_stats,
+        report.component_blobs
+    )
+}
+
+fn maintenance_enter(mut commands: Commands, style: Res<Style>, report: Res<GarbageReport>) {
+    let button_node = Node {
+        width: Val::Px(300.0),
+        height: Val::Px(65.0),
+        margin: UiRect::all(Val::Px(20.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+
+    let text_style = (
+        style.font(33.0),
+        TextLayout::new_with_justify(JustifyText::Center),
+        TextColor(style.text_color),
+    );
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            StateScoped(MenuState::Maintenance),
+        ))
+        .with_children(|builder| {
+            builder
+                .spawn(Node {
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    ..default()
+                })
+                .with_children(|builder| {
+                    builder.spawn((
+                        Text::new(garbage_report_text(&report)),
+                        text_style.clone(),
+                        GarbageReportText,
+                    ));
+
+                    builder
+                        .spawn((
+                            Button,
+                            button_node.clone(),
+                            BackgroundColor(style.button_color),
+                            children![(
+                                Text::new("Clean Up Now"),
+                                text_style.clone(),
+                                Pickable::IGNORE
+                            )],
+                        ))
+                        .observe(clean_up_on_click);
+
+                    builder
+                        .spawn((
+                            Button,
+                            button_node.clone(),
+                            BackgroundColor(style.button_color),
+                            children![(Text::new("Back"), text_style.clone(), Pickable::IGNORE)],
+                        ))
+                        .observe(change_state_on_click(
+                            PointerButton::Primary,
+                            MenuState::Settings,
+                        ));
+                });
+        });
+}
+
+fn clean_up_on_click(mut click: Trigger<Pointer<Click>>, mut commands: Commands) {
+    click.propagate(false);
+
+    if click.button == PointerButton::Primary {
+        commands.run_system_cached(collect_garbage);
+    }
+}
+
+fn update_garbage_report(
+    report: Res<GarbageReport>,
+    mut text: Single<&mut Text, With<GarbageReportText>>,
+) {
+    text.0 = garbage_report_text(&report);
+}
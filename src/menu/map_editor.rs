@@ -0,0 +1,503 @@
+//! A map editor for making layouts by hand, only in debug builds.
+//!
+//! Rooms are painted onto the map with the picked brush, and right clicking a room rolls it a
+//! new seed. The layout is exported as RON to `assets/layouts`, and can be played with
+//! `--layout <PATH>`.
+
+use super::MenuState;
+use super::new_game::random_seed;
+use crate::game::hex_distance;
+use crate::generate_map::{GenerationSettings, MAP_COORD_SYSTEM, MapLayout};
+use crate::prelude::*;
+use crate::room::RoomKind;
+use bevy::ecs::hierarchy::ChildSpawnerCommands;
+use bevy::prelude::*;
+use bevy_ecs_tilemap::helpers::hex_grid::axial::AxialPos;
+use bevy_ecs_tilemap::prelude::*;
+use rand::SeedableRng;
+use std::path::{Path, PathBuf};
+use strum::{Display, EnumIter, IntoEnumIterator};
+
+/// Where exported layouts are written, next to the other assets.
+const LAYOUT_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/assets/layouts");
+/// The distance from the center of a tile on the editor's map to its corners.
+const TILE_RADIUS: f32 = 24.0;
+const TILE_SIZE: Vec2 = Vec2::new(40.0, 36.0);
+
+pub struct MapEditorPlugin;
+
+impl Plugin for MapEditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EditorBrush>()
+            .add_systems(OnEnter(MenuState::MapEditor), map_editor_enter)
+            .add_systems(
+                Update,
+                (
+                    update_editor_tiles.run_if(resource_changed::<EditedLayout>),
+                    update_brush_text.run_if(resource_changed::<EditorBrush>),
+                )
+                    .run_if(in_state(MenuState::MapEditor)),
+            );
+    }
+}
+
+/// The layout being edited, kept between visits to the editor.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct EditedLayout(pub MapLayout);
+
+impl Default for EditedLayout {
+    fn default() -> Self {
+        Self(random_layout())
+    }
+}
+
+/// What clicking a room on the editor's map paints there.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Display, EnumIter)]
+pub enum EditorBrush {
+    Erase,
+    #[default]
+    Empty,
+    Combat,
+    Pit,
+    Item,
+    Puzzle,
+    Shrine,
+    Stairs,
+    Pillar,
+}
+
+impl EditorBrush {
+    /// The brush that paints rooms of the type, if any can.
+    pub fn of(r_type: &RoomType) -> Option<Self> {
+        match r_type {
+            RoomType::EmptyRoom => Some(Self::Empty),
+            RoomType::Combat(_) => Some(Self::Combat),
+            RoomType::Pit(_) => Some(Self::Pit),
+            RoomType::Item(_) => Some(Self::Item),
+            RoomType::Puzzle => Some(Self::Puzzle),
+            RoomType::Shrine => Some(Self::Shrine),
+            RoomType::Stairs => Some(Self::Stairs),
+            RoomType::Pillar => Some(Self::Pillar),
+            RoomType::Entrance => None,
+        }
+    }
+
+    /// The room painted `depth` rooms from the entrance, with what is in it rolled from the seed
+    /// like a generated room would be.
+    pub fn room(self, seed: u64, depth: u32) -> Option<RoomType> {
+        let kind = match self {
+            Self::Erase => return None,
+            Self::Stairs => return Some(RoomType::Stairs),
+            Self::Pillar => return Some(RoomType::Pillar),
+            Self::Empty => RoomKind::Empty,
+            Self::Combat => RoomKind::Combat,
+            Self::Pit => RoomKind::Pit,
+            Self::Item => RoomKind::Item,
+            Self::Puzzle => RoomKind::Puzzle,
+            Self::Shrine => RoomKind::Shrine,
+        };
+        let mut rng = RandomSource::seed_from_u64(seed);
+        let settings = GenerationSettings::default();
+        Some(RoomType::from_kind(kind, &mut rng, &settings, depth, None))
+    }
+
+    fn color(self) -> Color {
+        match self {
+            Self::Erase => Color::srgb(0.15, 0.15, 0.18),
+            Self::Empty => Color::srgb(0.4, 0.4, 0.45),
+            Self::Combat => Color::srgb(0.7, 0.25, 0.25),
+            Self::Pit => Color::srgb(0.45, 0.3, 0.2),
+            Self::Item => Color::srgb(0.3, 0.6, 0.3),
+            Self::Puzzle => Color::srgb(0.3, 0.4, 0.7),
+            Self::Shrine => Color::srgb(0.6, 0.4, 0.7),
+            Self::Stairs => Color::srgb(0.25, 0.55, 0.6),
+            Self::Pillar => Color::srgb(0.8, 0.65, 0.2),
+        }
+    }
+}
+
+/// A room on the editor's map.
+#[derive(Component)]
+struct EditorTile(TilePos);
+
+#[derive(Component)]
+struct BrushText;
+
+fn random_layout() -> MapLayout {
+    MapLayout::generate(&GenerationSettings {
+        seed: random_seed(),
+        ..default()
+    })
+}
+
+/// The room at the position and its seed, if the layout has one there.
+pub fn room_at(layout: &MapLayout, pos: &TilePos) -> Option<(RoomType, u64)> {
+    if *pos == layout.shape.origin() {
+        return Some((RoomType::Entrance, layout.entrance_seed));
+    }
+
+    layout
+        .pillars
+        .iter()
+        .find(|(p, _)| p == pos)
+        .map(|(_, seed)| (RoomType::Pillar, *seed))
+        .or_else(|| {
+            layout
+                .rooms
+                .iter()
+                .find(|(p, ..)| p == pos)
+                .map(|(_, r_type, seed)| (r_type.clone(), *seed))
+        })
+}
+
+/// Paints the brush's room over whatever was at the position.
+/// The entrance is always in the middle of the map, so it is never painted over.
+pub fn paint(layout: &mut MapLayout, pos: TilePos, brush: EditorBrush, seed: u64) {
+    let origin = layout.shape.origin();
+    if pos == origin {
+        return;
+    }
+
+    layout.pillars.retain(|(p, _)| *p != pos);
+    layout.rooms.retain(|(p, ..)| *p != pos);
+    match brush.room(seed, hex_distance(&origin, &pos)) {
+        Some(RoomType::Pillar) => layout.pillars.push((pos, seed)),
+        Some(r_type) => layout.rooms.push((pos, r_type, seed)),
+        None => {}
+    }
+}
+
+/// Gives the room at the position a new seed, rolling what is in it again.
+pub fn reroll(layout: &mut MapLayout, pos: TilePos, seed: u64) {
+    match room_at(layout, &pos) {
+        Some((RoomType::Entrance, _)) => layout.entrance_seed = seed,
+        Some((r_type, _)) => {
+            if let Some(brush) = EditorBrush::of(&r_type) {
+                paint(layout, pos, brush, seed);
+            }
+        }
+        None => {}
+    }
+}
+
+/// Writes the layout to [`LAYOUT_DIR`], named by its hash, once it is checked to be playable.
+fn export_layout(layout: &MapLayout) -> Result<PathBuf, String> {
+    layout.validate().map_err(|err| err.to_string())?;
+
+    let dir = Path::new(LAYOUT_DIR);
+    std::fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+    let path = dir.join(format!("layout-{:016x}.ron", layout.hash()));
+    let ron = ron::ser::to_string_pretty(layout, ron::ser::PrettyConfig::default())
+        .map_err(|err| err.to_string())?;
+    std::fs::write(&path, ron).map_err(|err| err.to_string())?;
+
+    Ok(path)
+}
+
+/// What a room is shown as on the editor's map.
+fn tile_look(room: Option<&(RoomType, u64)>) -> (&'static str, Color) {
+    match room {
+        None => ("", EditorBrush::Erase.color()),
+        Some((RoomType::Entrance, _)) => ("En", Color::srgb(0.55, 0.55, 0.6)),
+        Some((r_type, _)) => {
+            let brush = EditorBrush::of(r_type).unwrap_or_default();
+            let label = match brush {
+                EditorBrush::Erase => "",
+                EditorBrush::Empty => "Em",
+                EditorBrush::Combat => "Co",
+                EditorBrush::Pit => "Pt",
+                EditorBrush::Item => "It",
+                EditorBrush::Puzzle => "Pz",
+                EditorBrush::Shrine => "Sh",
+                EditorBrush::Stairs => "St",
+                EditorBrush::Pillar => "Pi",
+            };
+            (label, brush.color())
+        }
+    }
+}
+
+/// Where the tile goes on the editor's map, from the middle of it.
+fn tile_offset(pos: &TilePos, origin: &TilePos) -> Vec2 {
+    let axial = AxialPos::from_tile_pos_given_coord_system(pos, MAP_COORD_SYSTEM);
+    let center = AxialPos::from_tile_pos_given_coord_system(origin, MAP_COORD_SYSTEM);
+    let (q, r) = ((axial.q - center.q) as f32, (axial.r - center.r) as f32);
+
+    // Up the screen is down the page.
+    Vec2::new(
+        1.5 * TILE_RADIUS * q,
+        -SQRT_3_2 * 2.0 * TILE_RADIUS * (r + q / 2.0),
+    )
+}
+
+fn edit_tile(
+    mut click: Trigger<Pointer<Click>>,
+    tiles: Query<&EditorTile>,
+    brush: Res<EditorBrush>,
+    mut layout: ResMut<EditedLayout>,
+) {
+    let Ok(EditorTile(pos)) = tiles.get(click.target()) else {
+        return;
+    };
+    click.propagate(false);
+
+    match click.button {
+        PointerButton::Primary => {
+            let seed = room_at(&layout.0, pos).map_or_else(random_seed, |(_, seed)| seed);
+            paint(&mut layout.0, *pos, *brush, seed);
+        }
+        PointerButton::Secondary => reroll(&mut layout.0, *pos, random_seed()),
+        PointerButton::Middle => {}
+    }
+}
+
+fn choose_brush(brush: EditorBrush) -> impl Fn(Trigger<Pointer<Click>>, ResMut<EditorBrush>) {
+    move |mut click, mut chosen| {
+        if click.button != PointerButton::Primary {
+            return;
+        }
+
+        *chosen = brush;
+        click.propagate(false);
+    }
+}
+
+fn new_layout_click(mut click: Trigger<Pointer<Click>>, mut layout: ResMut<EditedLayout>) {
+    if click.button != PointerButton::Primary {
+        return;
+    }
+    click.propagate(false);
+
+    layout.0 = random_layout();
+}
+
+fn clear_layout_click(mut click: Trigger<Pointer<Click>>, mut layout: ResMut<EditedLayout>) {
+    if click.button != PointerButton::Primary {
+        return;
+    }
+    click.propagate(false);
+
+    layout.0.pillars.clear();
+    layout.0.rooms.clear();
+}
+
+fn export_layout_click(
+    mut click: Trigger<Pointer<Click>>,
+    layout: Res<EditedLayout>,
+    mut notifications: ResMut<Notifications>,
+) {
+    if click.button != PointerButton::Primary {
+        return;
+    }
+    click.propagate(false);
+
+    match export_layout(&layout.0) {
+        Ok(path) => {
+            info!("Exported layout to {}", path.display());
+            notifications.success(format!("Exported to {}", path.display()));
+        }
+        Err(err) => notifications.error(format!("Couldn't export the layout: {err}")),
+    }
+}
+
+fn update_editor_tiles(
+    layout: Res<EditedLayout>,
+    mut tiles: Query<(&EditorTile, &mut Text, &mut BackgroundColor, &mut Tooltip)>,
+) {
+    for (EditorTile(pos), mut text, mut background, mut tooltip) in tiles.iter_mut() {
+        let room = room_at(&layout.0, pos);
+        let (label, color) = tile_look(room.as_ref());
+
+        text.0 = label.to_string();
+        background.0 = color;
+        *tooltip = match room {
+            Some((r_type, seed)) => Tooltip::new(format!("{r_type:?}")).with_body(format!(
+                "Seed {seed:016x}\nLeft click to paint, right click for a new seed"
+            )),
+            None => Tooltip::new("No room").with_body("Left click to paint"),
+        };
+    }
+}
+
+fn update_brush_text(brush: Res<EditorBrush>, mut text: Query<&mut Text, With<BrushText>>) {
+    for mut text in text.iter_mut() {
+        text.0 = format!("Brush: {}", *brush);
+    }
+}
+
+fn map_editor_enter(
+    mut commands: Commands,
+    style: Res<Style>,
+    layout: Option<Res<EditedLayout>>,
+    brush: Res<EditorBrush>,
+) {
+    let layout = match layout {
+        Some(layout) => layout.0.clone(),
+        None => {
+            let layout = EditedLayout::default();
+            commands.insert_resource(layout.clone());
+            layout.0
+        }
+    };
+    let shape = layout.shape;
+    let origin = shape.origin();
+
+    let text_style = (style.font(28.0), TextColor(style.text_color));
+    let map_size = Vec2::new(
+        TILE_RADIUS * (3.0 * shape.radius as f32 + 2.0),
+        TILE_RADIUS * SQRT_3_2 * 2.0 * (2.0 * shape.radius as f32 + 1.0),
+    );
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            StateScoped(MenuState::MapEditor),
+        ))
+        .with_children(|builder| {
+            builder.spawn((
+                Text::new("Map Editor"),
+                style.font(50.0),
+                TextColor(style.text_color),
+            ));
+            builder.spawn((
+                Text::new(format!("Brush: {}", *brush)),
+                text_style.clone(),
+                BrushText,
+            ));
+
+            builder.spawn(Node::default()).with_children(|builder| {
+                builder
+                    .spawn(Node {
+                        width: Val::Px(map_size.x),
+                        height: Val::Px(map_size.y),
+                        margin: UiRect::all(Val::Px(20.0)),
+                        ..default()
+                    })
+                    .with_children(|builder| {
+                        let positions = generate_hexagon(
+                            AxialPos::from_tile_pos_given_coord_system(&origin, MAP_COORD_SYSTEM),
+                            shape.radius,
+                        )
+                        .into_iter()
+                        .map(|axial| axial.as_tile_pos_given_coord_system(MAP_COORD_SYSTEM));
+
+                        for pos in positions {
+                            let center = map_size / 2.0 + tile_offset(&pos, &origin);
+
+                            builder
+                                .spawn((
+                                    EditorTile(pos),
+                                    Node {
+                                        position_type: PositionType::Absolute,
+                                        left: Val::Px(center.x - TILE_SIZE.x / 2.0),
+                                        top: Val::Px(center.y - TILE_SIZE.y / 2.0),
+                                        width: Val::Px(TILE_SIZE.x),
+                                        height: Val::Px(TILE_SIZE.y),
+                                        ..default()
+                                    },
+                                    Text::default(),
+                                    style.font(20.0),
+                                    TextColor(style.text_color),
+                                    TextLayout::new_with_justify(JustifyText::Center),
+                                    BackgroundColor::default(),
+                                    Tooltip::default(),
+                                ))
+                                .observe(edit_tile);
+                        }
+                    });
+
+                builder
+                    .spawn(Node {
+                        flex_direction: FlexDirection::Column,
+                        ..default()
+                    })
+                    .with_children(|builder| {
+                        for brush in EditorBrush::iter() {
+                            editor_button(builder, &style, &brush.to_string())
+                                .observe(choose_brush(brush));
+                        }
+                    });
+            });
+
+            builder.spawn(Node::default()).with_children(|builder| {
+                editor_button(builder, &style, "New").observe(new_layout_click);
+                editor_button(builder, &style, "Clear").observe(clear_layout_click);
+                editor_button(builder, &style, "Export").observe(export_layout_click);
+                editor_button(builder, &style, "Back").observe(change_state_on_click(
+                    PointerButton::Primary,
+                    MenuState::Main,
+                ));
+            });
+        });
+
+    // The layout may not have changed since the editor was last open, so fill the tiles in now.
+    commands.run_system_cached(update_editor_tiles);
+}
+
+fn editor_button<'a>(
+    builder: &'a mut ChildSpawnerCommands,
+    style: &Style,
+    text: &str,
+) -> EntityCommands<'a> {
+    builder.spawn((
+        Button,
+        Node {
+            width: Val::Px(160.0),
+            height: Val::Px(50.0),
+            margin: UiRect::all(Val::Px(5.0)),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        BackgroundColor(style.button_color),
+        children![(
+            Text::new(text),
+            style.font(28.0),
+            TextColor(style.text_color),
+            Pickable::IGNORE
+        )],
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn painting_replaces_rooms_but_not_the_entrance() {
+        let mut layout = MapLayout::generate(&GenerationSettings::default());
+        let origin = layout.shape.origin();
+        let pos = layout.rooms[0].0;
+
+        paint(&mut layout, pos, EditorBrush::Pillar, 7);
+        assert_eq!(room_at(&layout, &pos), Some((RoomType::Pillar, 7)));
+        assert!(layout.rooms.iter().all(|(p, ..)| *p != pos));
+
+        paint(&mut layout, pos, EditorBrush::Combat, 8);
+        let (r_type, seed) = room_at(&layout, &pos).unwrap();
+        assert!(matches!(r_type, RoomType::Combat(_)));
+        assert_eq!(seed, 8);
+        assert!(layout.pillars.iter().all(|(p, _)| *p != pos));
+
+        // Rerolling keeps the kind of room, and painting with the same seed rolls the same room.
+        reroll(&mut layout, pos, 9);
+        let rerolled = room_at(&layout, &pos).unwrap();
+        assert!(matches!(rerolled.0, RoomType::Combat(_)));
+        paint(&mut layout, pos, EditorBrush::Combat, 9);
+        assert_eq!(room_at(&layout, &pos), Some(rerolled));
+
+        paint(&mut layout, pos, EditorBrush::Erase, 10);
+        assert_eq!(room_at(&layout, &pos), None);
+
+        paint(&mut layout, origin, EditorBrush::Shrine, 11);
+        reroll(&mut layout, origin, 12);
+        assert_eq!(room_at(&layout, &origin), Some((RoomType::Entrance, 12)));
+    }
+}
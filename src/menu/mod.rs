@@ -1,19 +1,44 @@
 //! TODO: Make the UI hexagon based.
-//! TODO: Implement title screen and pausing separately.
 
+pub mod challenge_maps;
 pub mod controls;
 #[cfg(feature = "sqlite")]
+pub mod daily_run;
+pub mod hud;
+#[cfg(feature = "sqlite")]
 pub mod load_game;
+#[cfg(feature = "sqlite")]
+pub mod maintenance;
+#[cfg(feature = "debug")]
+pub mod map_editor;
 pub mod new_game;
+pub mod party_select;
+pub mod seed_history;
+pub mod skirmish;
 
+use crate::barks::BarkFrequency;
+use crate::camera::{CameraPanning, CameraSmoothing};
 use crate::embed_asset;
+use crate::game::{EnemyTurnDetail, JournalScreenshots, SavePolicy, ShowBreadcrumbs};
 use crate::prelude::*;
+use bevy::ecs::hierarchy::ChildSpawnerCommands;
 use bevy::input_focus::InputFocus;
 use bevy::{input::mouse::MouseScrollUnit, prelude::*};
+use challenge_maps::*;
 use controls::*;
 #[cfg(feature = "sqlite")]
+use daily_run::*;
+use hud::*;
+#[cfg(feature = "sqlite")]
 use load_game::*;
+#[cfg(feature = "sqlite")]
+use maintenance::*;
+#[cfg(feature = "debug")]
+use map_editor::*;
 use new_game::*;
+use party_select::*;
+use seed_history::*;
+use skirmish::*;
 
 const TITLE_IMAGE_PATH: &str = "embedded://assets/sprites/title.png";
 
@@ -28,10 +53,25 @@ impl Plugin for MenuPlugin {
         app.add_systems(Update, log_transitions::<MenuState>);
 
         app.add_plugins(MenuControlsPlugin)
-            .add_plugins(MenuNewGamePlugin);
+            .add_plugins(MenuNewGamePlugin)
+            .add_plugins(MenuPartySelectPlugin)
+            .add_plugins(SeedHistoryPlugin)
+            .add_plugins(ChallengeMapsPlugin)
+            .add_plugins(MenuHudPlugin)
+            .add_plugins(MenuSkirmishPlugin);
+
+        #[cfg(feature = "debug")]
+        app.add_plugins(MapEditorPlugin);
 
         #[cfg(feature = "sqlite")]
-        app.add_plugins(MenuLoadGamePlugin);
+        app.add_plugins(MenuLoadGamePlugin)
+            .add_plugins(MenuMaintenancePlugin)
+            .add_plugins(DailyRunPlugin)
+            .add_systems(OnEnter(MenuState::Saving), saving_enter)
+            .add_systems(
+                Update,
+                update_save_policy_buttons.run_if(resource_changed::<SavePolicy>),
+            );
 
         app.add_systems(
             Update,
@@ -40,7 +80,21 @@ impl Plugin for MenuPlugin {
         .add_systems(OnEnter(MenuState::Main), main_enter)
         .add_systems(OnEnter(MenuState::Settings), settings_enter)
         .add_systems(OnEnter(MenuState::Display), display_enter)
-        .add_systems(OnEnter(MenuState::Sound), sound_enter);
+        .add_systems(OnEnter(MenuState::Sound), sound_enter)
+        .add_systems(
+            Update,
+            (
+                update_volume_sliders.run_if(resource_changed::<Volume>),
+                update_breadcrumbs_toggle.run_if(resource_changed::<ShowBreadcrumbs>),
+                update_journal_toggle.run_if(resource_changed::<JournalScreenshots>),
+                update_effects_toggle.run_if(resource_changed::<EffectsIntensity>),
+                update_enemy_turns_toggle.run_if(resource_changed::<EnemyTurnDetail>),
+                update_barks_toggle.run_if(resource_changed::<BarkFrequency>),
+                update_camera_toggle.run_if(resource_changed::<CameraSmoothing>),
+                update_pan_settings.run_if(resource_changed::<CameraPanning>),
+                update_tile_animation_toggle.run_if(resource_changed::<AnimateTiles>),
+            ),
+        );
     }
 }
 
@@ -52,16 +106,24 @@ pub enum MenuState {
     Main,
     Settings,
     Display,
+    Hud,
     Sound,
     Controls,
     NewGame,
+    Skirmish,
     #[cfg(feature = "sqlite")]
     LoadGame,
+    #[cfg(feature = "sqlite")]
+    Maintenance,
+    #[cfg(feature = "sqlite")]
+    Saving,
+    #[cfg(feature = "debug")]
+    MapEditor,
 }
 
 /// Tag component used to mark which setting is currently selected
 #[derive(Component)]
-struct SelectedOption;
+pub struct SelectedOption;
 
 /// Whenever the player hits the pause button, it should
 /// put them out as if they hit the back button.
@@ -86,14 +148,18 @@ fn escape_out(
             #[cfg(feature = "sqlite")]
             M::LoadGame => {}
 
-            M::Settings => next_state.set(MenuState::Main),
-            M::Sound | M::Display => next_state.set(MenuState::Settings),
+            M::Settings | M::Skirmish => next_state.set(MenuState::Main),
+            #[cfg(feature = "debug")]
+            M::MapEditor => next_state.set(MenuState::Main),
+            M::Sound | M::Display | M::Hud => next_state.set(MenuState::Settings),
+            #[cfg(feature = "sqlite")]
+            M::Maintenance | M::Saving => next_state.set(MenuState::Settings),
         }
     }
 }
 
 /// Highlight the buttons on hover to make them look better.
-fn button_highlight(
+pub fn button_highlight(
     mut interaction_query: Query<
         (&Interaction, &mut BackgroundColor, Option<&SelectedOption>),
         (Changed<Interaction>, With<Button>),
@@ -171,11 +237,20 @@ fn main_enter(mut commands: Commands, style: Res<Style>, asset_server: Res<Asset
                             change_state_on_click(PointerButton::Primary, MenuState::NewGame),
                             "New Game",
                         ),
+                        (
+                            change_state_on_click(PointerButton::Primary, MenuState::Skirmish),
+                            "Skirmish",
+                        ),
                         #[cfg(feature = "sqlite")]
                         (
                             change_state_on_click(PointerButton::Primary, MenuState::LoadGame),
                             "Load Game",
                         ),
+                        #[cfg(feature = "debug")]
+                        (
+                            change_state_on_click(PointerButton::Primary, MenuState::MapEditor),
+                            "Map Editor",
+                        ),
                         (
                             change_state_on_click(PointerButton::Primary, MenuState::Settings),
                             "Settings",
@@ -255,10 +330,24 @@ fn settings_enter(mut commands: Commands, style: Res<Style>) {
                             change_state_on_click(PointerButton::Primary, MenuState::Display),
                             "Display",
                         ),
+                        (
+                            change_state_on_click(PointerButton::Primary, MenuState::Hud),
+                            "HUD",
+                        ),
                         (
                             change_state_on_click(PointerButton::Primary, MenuState::Sound),
                             "Sound",
                         ),
+                        #[cfg(feature = "sqlite")]
+                        (
+                            change_state_on_click(PointerButton::Primary, MenuState::Saving),
+                            "Saving",
+                        ),
+                        #[cfg(feature = "sqlite")]
+                        (
+                            change_state_on_click(PointerButton::Primary, MenuState::Maintenance),
+                            "Maintenance",
+                        ),
                         (
                             change_state_on_click(PointerButton::Primary, MenuState::Main),
                             "Back",
@@ -283,7 +372,299 @@ fn settings_enter(mut commands: Commands, style: Res<Style>) {
         });
 }
 
-fn display_enter(mut commands: Commands, style: Res<Style>) {
+/// The text of the button that turns the minimap trail on and off.
+#[derive(Component)]
+struct BreadcrumbsToggleText;
+
+fn breadcrumbs_toggle_text(show: ShowBreadcrumbs) -> String {
+    format!("Map Trail: {}", if show.0 { "On" } else { "Off" })
+}
+
+fn toggle_breadcrumbs_on_click(
+    mut click: Trigger<Pointer<Click>>,
+    mut show: ResMut<ShowBreadcrumbs>,
+) {
+    click.propagate(false);
+
+    if click.button == PointerButton::Primary {
+        show.0 = !show.0;
+    }
+}
+
+fn update_breadcrumbs_toggle(
+    show: Res<ShowBreadcrumbs>,
+    mut texts: Query<&mut Text, With<BreadcrumbsToggleText>>,
+) {
+    for mut text in texts.iter_mut() {
+        text.0 = breadcrumbs_toggle_text(*show);
+    }
+}
+
+/// The text of the button that turns the journal thumbnails on and off.
+#[derive(Component)]
+struct JournalToggleText;
+
+fn journal_toggle_text(screenshots: JournalScreenshots) -> String {
+    format!(
+        "Journal Pictures: {}",
+        if screenshots.0 { "On" } else { "Off" }
+    )
+}
+
+fn toggle_journal_on_click(
+    mut click: Trigger<Pointer<Click>>,
+    mut screenshots: ResMut<JournalScreenshots>,
+) {
+    click.propagate(false);
+
+    if click.button == PointerButton::Primary {
+        screenshots.0 = !screenshots.0;
+    }
+}
+
+fn update_journal_toggle(
+    screenshots: Res<JournalScreenshots>,
+    mut texts: Query<&mut Text, With<JournalToggleText>>,
+) {
+    for mut text in texts.iter_mut() {
+        text.0 = journal_toggle_text(*screenshots);
+    }
+}
+
+/// The text of the button that cycles the effects intensity.
+#[derive(Component)]
+struct EffectsToggleText;
+
+fn effects_toggle_text(intensity: EffectsIntensity) -> String {
+    format!("Effects: {intensity}")
+}
+
+fn cycle_effects_on_click(
+    mut click: Trigger<Pointer<Click>>,
+    mut intensity: ResMut<EffectsIntensity>,
+) {
+    click.propagate(false);
+
+    if click.button == PointerButton::Primary {
+        *intensity = intensity.next();
+    }
+}
+
+fn update_effects_toggle(
+    intensity: Res<EffectsIntensity>,
+    mut texts: Query<&mut Text, With<EffectsToggleText>>,
+) {
+    for mut text in texts.iter_mut() {
+        text.0 = effects_toggle_text(*intensity);
+    }
+}
+
+/// The text of the button that cycles how much of the enemies' turns is shown.
+#[derive(Component)]
+struct EnemyTurnsToggleText;
+
+fn enemy_turns_toggle_text(detail: EnemyTurnDetail) -> String {
+    format!("Enemy Turns: {detail}")
+}
+
+fn cycle_enemy_turns_on_click(
+    mut click: Trigger<Pointer<Click>>,
+    mut detail: ResMut<EnemyTurnDetail>,
+) {
+    click.propagate(false);
+
+    if click.button == PointerButton::Primary {
+        *detail = detail.next();
+    }
+}
+
+fn update_enemy_turns_toggle(
+    detail: Res<EnemyTurnDetail>,
+    mut texts: Query<&mut Text, With<EnemyTurnsToggleText>>,
+) {
+    for mut text in texts.iter_mut() {
+        text.0 = enemy_turns_toggle_text(*detail);
+    }
+}
+
+/// The text of the button that cycles how often the party speaks up.
+#[derive(Component)]
+struct BarksToggleText;
+
+fn barks_toggle_text(frequency: BarkFrequency) -> String {
+    format!("Party Barks: {frequency}")
+}
+
+fn cycle_barks_on_click(mut click: Trigger<Pointer<Click>>, mut frequency: ResMut<BarkFrequency>) {
+    click.propagate(false);
+
+    if click.button == PointerButton::Primary {
+        *frequency = frequency.next();
+    }
+}
+
+fn update_barks_toggle(
+    frequency: Res<BarkFrequency>,
+    mut texts: Query<&mut Text, With<BarksToggleText>>,
+) {
+    for mut text in texts.iter_mut() {
+        text.0 = barks_toggle_text(*frequency);
+    }
+}
+
+/// The text of the button that cycles how smoothly the camera follows the party.
+#[derive(Component)]
+struct CameraToggleText;
+
+fn camera_toggle_text(smoothing: CameraSmoothing) -> String {
+    format!("Camera: {smoothing}")
+}
+
+fn cycle_camera_on_click(
+    mut click: Trigger<Pointer<Click>>,
+    mut smoothing: ResMut<CameraSmoothing>,
+) {
+    click.propagate(false);
+
+    if click.button == PointerButton::Primary {
+        *smoothing = smoothing.next();
+    }
+}
+
+fn update_camera_toggle(
+    smoothing: Res<CameraSmoothing>,
+    mut texts: Query<&mut Text, With<CameraToggleText>>,
+) {
+    for mut text in texts.iter_mut() {
+        text.0 = camera_toggle_text(*smoothing);
+    }
+}
+
+/// The text of the button that turns panning at the edge of the window on and off.
+#[derive(Component)]
+struct EdgePanToggleText;
+
+fn edge_pan_toggle_text(panning: CameraPanning) -> String {
+    format!("Edge Pan: {}", if panning.edge_pan { "On" } else { "Off" })
+}
+
+fn toggle_edge_pan_on_click(
+    mut click: Trigger<Pointer<Click>>,
+    mut panning: ResMut<CameraPanning>,
+) {
+    click.propagate(false);
+
+    if click.button == PointerButton::Primary {
+        panning.edge_pan = !panning.edge_pan;
+    }
+}
+
+/// One of the sliders for edge panning in the display menu.
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+enum PanSlider {
+    Speed,
+    DeadZone,
+}
+
+impl PanSlider {
+    const ALL: [PanSlider; 2] = [PanSlider::Speed, PanSlider::DeadZone];
+
+    fn name(self) -> &'static str {
+        match self {
+            PanSlider::Speed => "Pan Speed",
+            PanSlider::DeadZone => "Pan Dead Zone",
+        }
+    }
+
+    fn get(self, panning: &CameraPanning) -> f32 {
+        match self {
+            PanSlider::Speed => panning.speed,
+            PanSlider::DeadZone => panning.dead_zone,
+        }
+    }
+
+    fn get_mut(self, panning: &mut CameraPanning) -> &mut f32 {
+        match self {
+            PanSlider::Speed => &mut panning.speed,
+            PanSlider::DeadZone => &mut panning.dead_zone,
+        }
+    }
+}
+
+/// The filled part of an edge panning slider.
+#[derive(Component)]
+struct PanSliderFill(PanSlider);
+
+/// Sets the edge panning setting to wherever its slider was pressed or dragged to.
+fn slide_pan_on<E: std::fmt::Debug + Clone + Reflect>(
+    mut trigger: Trigger<Pointer<E>>,
+    sliders: Query<(&PanSlider, &ComputedNode, &GlobalTransform)>,
+    mut panning: ResMut<CameraPanning>,
+) {
+    trigger.propagate(false);
+
+    let Ok((slider, computed, transform)) = sliders.get(trigger.target()) else {
+        return;
+    };
+
+    let value = slider_value(trigger.pointer_location.position.x, computed, transform);
+    *slider.get_mut(&mut panning) = (value * 100.0).round() / 100.0;
+}
+
+fn update_pan_settings(
+    panning: Res<CameraPanning>,
+    mut fills: Query<(&mut Node, &PanSliderFill)>,
+    mut texts: Query<&mut Text, With<EdgePanToggleText>>,
+) {
+    for (mut node, PanSliderFill(slider)) in fills.iter_mut() {
+        node.width = Val::Percent(slider.get(&panning) * 100.0);
+    }
+    for mut text in texts.iter_mut() {
+        text.0 = edge_pan_toggle_text(*panning);
+    }
+}
+
+/// The text of the button that turns the sky and torch animations on and off.
+#[derive(Component)]
+struct TileAnimationToggleText;
+
+fn tile_animation_toggle_text(animate: AnimateTiles) -> String {
+    format!("Animated Tiles: {}", if animate.0 { "On" } else { "Off" })
+}
+
+fn toggle_tile_animation_on_click(
+    mut click: Trigger<Pointer<Click>>,
+    mut animate: ResMut<AnimateTiles>,
+) {
+    click.propagate(false);
+
+    if click.button == PointerButton::Primary {
+        animate.0 = !animate.0;
+    }
+}
+
+fn update_tile_animation_toggle(
+    animate: Res<AnimateTiles>,
+    mut texts: Query<&mut Text, With<TileAnimationToggleText>>,
+) {
+    for mut text in texts.iter_mut() {
+        text.0 = tile_animation_toggle_text(*animate);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn display_enter(
+    mut commands: Commands,
+    style: Res<Style>,
+    show_breadcrumbs: Res<ShowBreadcrumbs>,
+    journal_screenshots: Res<JournalScreenshots>,
+    effects: Res<EffectsIntensity>,
+    enemy_turns: Res<EnemyTurnDetail>,
+    barks: Res<BarkFrequency>,
+    camera: Res<CameraSmoothing>,
+    panning: Res<CameraPanning>,
+    animate_tiles: Res<AnimateTiles>,
+) {
     let button_node = Node {
         width: Val::Px(200.0),
         height: Val::Px(65.0),
@@ -314,6 +695,189 @@ fn display_enter(mut commands: Commands, style: Res<Style>) {
                     ..default()
                 })
                 .with_children(|builder| {
+                    builder
+                        .spawn((
+                            Button,
+                            Node {
+                                width: Val::Px(300.0),
+                                ..button_node.clone()
+                            },
+                            BackgroundColor(style.button_color),
+                            children![(
+                                Text::new(breadcrumbs_toggle_text(*show_breadcrumbs)),
+                                button_text_style.clone(),
+                                BreadcrumbsToggleText,
+                                Pickable::IGNORE,
+                            )],
+                        ))
+                        .observe(toggle_breadcrumbs_on_click);
+
+                    builder
+                        .spawn((
+                            Button,
+                            Node {
+                                width: Val::Px(400.0),
+                                ..button_node.clone()
+                            },
+                            BackgroundColor(style.button_color),
+                            children![(
+                                Text::new(journal_toggle_text(*journal_screenshots)),
+                                button_text_style.clone(),
+                                JournalToggleText,
+                                Pickable::IGNORE,
+                            )],
+                        ))
+                        .observe(toggle_journal_on_click);
+
+                    builder
+                        .spawn((
+                            Button,
+                            Node {
+                                width: Val::Px(300.0),
+                                ..button_node.clone()
+                            },
+                            BackgroundColor(style.button_color),
+                            children![(
+                                Text::new(effects_toggle_text(*effects)),
+                                button_text_style.clone(),
+                                EffectsToggleText,
+                                Pickable::IGNORE,
+                            )],
+                        ))
+                        .observe(cycle_effects_on_click);
+
+                    builder
+                        .spawn((
+                            Button,
+                            Node {
+                                width: Val::Px(300.0),
+                                ..button_node.clone()
+                            },
+                            BackgroundColor(style.button_color),
+                            children![(
+                                Text::new(enemy_turns_toggle_text(*enemy_turns)),
+                                button_text_style.clone(),
+                                EnemyTurnsToggleText,
+                                Pickable::IGNORE,
+                            )],
+                        ))
+                        .observe(cycle_enemy_turns_on_click);
+
+                    builder
+                        .spawn((
+                            Button,
+                            Node {
+                                width: Val::Px(350.0),
+                                ..button_node.clone()
+                            },
+                            BackgroundColor(style.button_color),
+                            children![(
+                                Text::new(barks_toggle_text(*barks)),
+                                button_text_style.clone(),
+                                BarksToggleText,
+                                Pickable::IGNORE,
+                            )],
+                        ))
+                        .observe(cycle_barks_on_click);
+
+                    builder
+                        .spawn((
+                            Button,
+                            Node {
+                                width: Val::Px(300.0),
+                                ..button_node.clone()
+                            },
+                            BackgroundColor(style.button_color),
+                            children![(
+                                Text::new(camera_toggle_text(*camera)),
+                                button_text_style.clone(),
+                                CameraToggleText,
+                                Pickable::IGNORE,
+                            )],
+                        ))
+                        .observe(cycle_camera_on_click);
+
+                    builder
+                        .spawn(Node {
+                            align_items: AlignItems::Center,
+                            ..default()
+                        })
+                        .with_children(|builder| {
+                            builder
+                                .spawn((
+                                    Button,
+                                    Node {
+                                        width: Val::Px(300.0),
+                                        ..button_node.clone()
+                                    },
+                                    BackgroundColor(style.button_color),
+                                    children![(
+                                        Text::new(edge_pan_toggle_text(*panning)),
+                                        button_text_style.clone(),
+                                        EdgePanToggleText,
+                                        Pickable::IGNORE,
+                                    )],
+                                ))
+                                .observe(toggle_edge_pan_on_click);
+
+                            for slider in PanSlider::ALL {
+                                builder
+                                    .spawn(Node {
+                                        flex_direction: FlexDirection::Column,
+                                        margin: UiRect::horizontal(Val::Px(10.0)),
+                                        ..default()
+                                    })
+                                    .with_children(|builder| {
+                                        builder.spawn((
+                                            Text::new(slider.name()),
+                                            style.font(24.0),
+                                            TextColor(style.text_color),
+                                        ));
+                                        builder
+                                            .spawn((
+                                                Node {
+                                                    width: Val::Px(200.0),
+                                                    height: Val::Px(24.0),
+                                                    ..default()
+                                                },
+                                                BackgroundColor(style.button_color),
+                                                slider,
+                                            ))
+                                            .observe(slide_pan_on::<Pressed>)
+                                            .observe(slide_pan_on::<Drag>)
+                                            .with_child((
+                                                Node {
+                                                    width: Val::Percent(
+                                                        slider.get(&panning) * 100.0,
+                                                    ),
+                                                    height: Val::Percent(100.0),
+                                                    ..default()
+                                                },
+                                                BackgroundColor(style.accent_color),
+                                                Pickable::IGNORE,
+                                                PanSliderFill(slider),
+                                            ));
+                                    });
+                            }
+                        });
+
+                    builder
+                        .spawn((
+                            Button,
+                            Node {
+                                width: Val::Px(400.0),
+                                ..button_node.clone()
+                            },
+                            BackgroundColor(style.button_color),
+                            children![(
+                                Text::new(tile_animation_toggle_text(*animate_tiles)),
+                                button_text_style.clone(),
+                                TileAnimationToggleText,
+                                Pickable::IGNORE,
+                            )],
+                        ))
+                        .observe(toggle_tile_animation_on_click);
+
                     builder
                         .spawn((
                             Button,
@@ -329,7 +893,168 @@ fn display_enter(mut commands: Commands, style: Res<Style>) {
         });
 }
 
-fn sound_enter(mut commands: Commands, style: Res<Style> /*volume: Res<Volume>*/) {
+/// One of the buttons in the saving menu, each changing part of the [`SavePolicy`].
+#[cfg(feature = "sqlite")]
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+enum SavePolicyButton {
+    Autosave,
+    OnPause,
+    OnQuit,
+}
+
+#[cfg(feature = "sqlite")]
+impl SavePolicyButton {
+    const ALL: [SavePolicyButton; 3] = [
+        SavePolicyButton::Autosave,
+        SavePolicyButton::OnPause,
+        SavePolicyButton::OnQuit,
+    ];
+
+    fn text(self, policy: &SavePolicy) -> String {
+        let on_off = |on: bool| if on { "On" } else { "Off" };
+        match self {
+            SavePolicyButton::Autosave => format!("Autosave: {}", policy.autosave),
+            SavePolicyButton::OnPause => format!("Save on Pause: {}", on_off(policy.on_pause)),
+            SavePolicyButton::OnQuit => format!("Save on Quit: {}", on_off(policy.on_quit)),
+        }
+    }
+
+    fn change(self, policy: &mut SavePolicy) {
+        match self {
+            SavePolicyButton::Autosave => policy.autosave = policy.autosave.next(),
+            SavePolicyButton::OnPause => policy.on_pause = !policy.on_pause,
+            SavePolicyButton::OnQuit => policy.on_quit = !policy.on_quit,
+        }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+fn change_save_policy_on_click(
+    button: SavePolicyButton,
+) -> impl Fn(Trigger<Pointer<Click>>, ResMut<SavePolicy>) {
+    move |mut click, mut policy| {
+        click.propagate(false);
+
+        if click.button == PointerButton::Primary {
+            button.change(&mut policy);
+        }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+fn update_save_policy_buttons(
+    policy: Res<SavePolicy>,
+    buttons: Query<(&SavePolicyButton, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    for (button, children) in buttons.iter() {
+        let mut texts = texts.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            text.0 = button.text(&policy);
+        }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+fn saving_enter(mut commands: Commands, style: Res<Style>, policy: Res<SavePolicy>) {
+    let button_node = Node {
+        width: Val::Px(450.0),
+        height: Val::Px(65.0),
+        margin: UiRect::all(Val::Px(20.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+
+    let button_text_style = (style.font(33.0), TextColor(style.text_color));
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            StateScoped(MenuState::Saving),
+        ))
+        .with_children(|builder| {
+            for button in SavePolicyButton::ALL {
+                builder
+                    .spawn((
+                        Button,
+                        button,
+                        button_node.clone(),
+                        BackgroundColor(style.button_color),
+                        children![(
+                            Text::new(button.text(&policy)),
+                            button_text_style.clone(),
+                            Pickable::IGNORE,
+                        )],
+                    ))
+                    .observe(change_save_policy_on_click(button));
+            }
+
+            builder
+                .spawn((
+                    Button,
+                    Node {
+                        width: Val::Px(200.0),
+                        ..button_node
+                    },
+                    BackgroundColor(style.button_color),
+                    children![(Text::new("Back"), button_text_style.clone())],
+                ))
+                .observe(change_state_on_click(
+                    PointerButton::Primary,
+                    MenuState::Settings,
+                ));
+        });
+}
+
+/// One of the volume sliders in the sound menu.
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+enum VolumeSlider {
+    Master,
+    Sfx,
+}
+
+impl VolumeSlider {
+    const ALL: [VolumeSlider; 2] = [VolumeSlider::Master, VolumeSlider::Sfx];
+
+    fn name(self) -> &'static str {
+        match self {
+            VolumeSlider::Master => "Master",
+            VolumeSlider::Sfx => "Effects",
+        }
+    }
+
+    fn get(self, volume: &Volume) -> f32 {
+        match self {
+            VolumeSlider::Master => volume.master,
+            VolumeSlider::Sfx => volume.sfx,
+        }
+    }
+
+    fn get_mut(self, volume: &mut Volume) -> &mut f32 {
+        match self {
+            VolumeSlider::Master => &mut volume.master,
+            VolumeSlider::Sfx => &mut volume.sfx,
+        }
+    }
+}
+
+/// The filled part of a volume slider.
+#[derive(Component)]
+struct VolumeSliderFill(VolumeSlider);
+
+/// The percentage shown next to a volume slider.
+#[derive(Component)]
+struct VolumeSliderText(VolumeSlider);
+
+fn sound_enter(mut commands: Commands, style: Res<Style>, volume: Res<Volume>) {
     let button_node = Node {
         width: Val::Px(200.0),
         height: Val::Px(65.0),
@@ -344,7 +1069,6 @@ fn sound_enter(mut commands: Commands, style: Res<Style> /*volume: Res<Volume>*/
         TextColor(style.text_color),
     );
 
-    //let button_node_clone = button_node.clone();
     commands
         .spawn((
             Node {
@@ -364,6 +1088,8 @@ fn sound_enter(mut commands: Commands, style: Res<Style> /*volume: Res<Volume>*/
                     ..default()
                 })
                 .with_children(|builder| {
+                    spawn_volume_sliders(builder, &style, &volume);
+
                     builder
                         .spawn((
                             Button,
@@ -379,6 +1105,104 @@ fn sound_enter(mut commands: Commands, style: Res<Style> /*volume: Res<Volume>*/
         });
 }
 
+/// Spawns a labeled slider for each volume channel.
+pub fn spawn_volume_sliders(builder: &mut ChildSpawnerCommands, style: &Style, volume: &Volume) {
+    let text_style = (
+        style.font(33.0),
+        TextLayout::new_with_justify(JustifyText::Center),
+        TextColor(style.text_color),
+    );
+
+    for slider in VolumeSlider::ALL {
+        let value = slider.get(volume);
+        builder
+            .spawn(Node {
+                align_items: AlignItems::Center,
+                margin: UiRect::all(Val::Px(10.0)),
+                ..default()
+            })
+            .with_children(|builder| {
+                builder.spawn((
+                    Node {
+                        width: Val::Px(150.0),
+                        ..default()
+                    },
+                    Text::new(slider.name()),
+                    text_style.clone(),
+                ));
+                builder
+                    .spawn((
+                        Node {
+                            width: Val::Px(300.0),
+                            height: Val::Px(24.0),
+                            ..default()
+                        },
+                        BackgroundColor(style.button_color),
+                        slider,
+                    ))
+                    .observe(slide_volume_on::<Pressed>)
+                    .observe(slide_volume_on::<Drag>)
+                    .with_child((
+                        Node {
+                            width: Val::Percent(value * 100.0),
+                            height: Val::Percent(100.0),
+                            ..default()
+                        },
+                        BackgroundColor(style.accent_color),
+                        Pickable::IGNORE,
+                        VolumeSliderFill(slider),
+                    ));
+                builder.spawn((
+                    Node {
+                        width: Val::Px(100.0),
+                        ..default()
+                    },
+                    Text::new(format!("{:.0}%", value * 100.0)),
+                    text_style.clone(),
+                    VolumeSliderText(slider),
+                ));
+            });
+    }
+}
+
+/// Sets the volume to wherever the slider was pressed or dragged to.
+fn slide_volume_on<E: std::fmt::Debug + Clone + Reflect>(
+    mut trigger: Trigger<Pointer<E>>,
+    sliders: Query<(&VolumeSlider, &ComputedNode, &GlobalTransform)>,
+    mut volume: ResMut<Volume>,
+) {
+    trigger.propagate(false);
+
+    let Ok((slider, computed, transform)) = sliders.get(trigger.target()) else {
+        return;
+    };
+
+    let value = slider_value(trigger.pointer_location.position.x, computed, transform);
+    // Snap to whole percents so the saved value matches what is shown.
+    *slider.get_mut(&mut volume) = (value * 100.0).round() / 100.0;
+}
+
+/// How far along the slider the pointer at `pointer_x` is, from 0 to 1.
+fn slider_value(pointer_x: f32, computed: &ComputedNode, transform: &GlobalTransform) -> f32 {
+    let scale = computed.inverse_scale_factor();
+    let width = computed.size().x * scale;
+    let left = transform.translation().x * scale - width / 2.0;
+    ((pointer_x - left) / width.max(1.0)).clamp(0.0, 1.0)
+}
+
+fn update_volume_sliders(
+    volume: Res<Volume>,
+    mut fills: Query<(&mut Node, &VolumeSliderFill)>,
+    mut texts: Query<(&mut Text, &VolumeSliderText)>,
+) {
+    for (mut node, VolumeSliderFill(slider)) in fills.iter_mut() {
+        node.width = Val::Percent(slider.get(&volume) * 100.0);
+    }
+    for (mut text, VolumeSliderText(slider)) in texts.iter_mut() {
+        text.0 = format!("{:.0}%", slider.get(&volume) * 100.0);
+    }
+}
+
 const LINE_HEIGHT: f32 = 65.0;
 
 /// Update the scroll position of the hovered node
@@ -1,15 +1,30 @@
 use super::MenuState;
-use crate::generate_map::GenerationSettings;
+#[cfg(feature = "sqlite")]
+use super::challenge_maps::ChallengeRun;
+use super::challenge_maps::ChosenChallengeMap;
+#[cfg(feature = "sqlite")]
+use super::daily_run::{ChosenDaily, DailyRun, daily_date, daily_run_click, daily_tooltip_body};
+use super::party_select::{PartySelection, spawn_party};
+use super::seed_history::{SeedHistory, SeedOutcome};
+use crate::game::{Gold, PillarCorruption};
+use crate::generate_map::{
+    GenerationMode, GenerationSettings, GenerationTask, LayoutProgress, place_layout,
+};
 use crate::prelude::*;
 use crate::room::CurrentRoom;
 use bevy::input_focus::InputFocus;
 use bevy::prelude::*;
-use bevy_ui_text_input::{TextInputContents, TextInputFilter, TextInputMode, TextInputNode};
+use bevy::window::WindowCloseRequested;
+use bevy_ui_text_input::actions::{TextInputAction, TextInputEdit};
+use bevy_ui_text_input::{TextInputContents, TextInputMode, TextInputNode, TextInputQueue};
+use strum::{EnumIter, IntoEnumIterator};
 
 pub struct MenuNewGamePlugin;
 impl Plugin for MenuNewGamePlugin {
     fn build(&self, app: &mut App) {
-        app.add_sub_state::<NewGameState>();
+        app.add_sub_state::<NewGameState>()
+            .init_resource::<Difficulty>()
+            .init_resource::<GameRules>();
         #[cfg(feature = "debug")]
         app.add_systems(Update, log_transitions::<NewGameState>);
         app.add_systems(OnEnter(NewGameState::Main), new_game_enter)
@@ -20,7 +35,30 @@ impl Plugin for MenuNewGamePlugin {
             .add_systems(Update, escape_out.run_if(in_state(MenuState::NewGame)))
             .add_systems(
                 Update,
-                progress_check.run_if(in_state(NewGameState::GeneratingWorld)),
+                update_seed_feedback.run_if(in_state(NewGameState::Main)),
+            )
+            .add_systems(
+                Update,
+                (
+                    update_difficulty_text.run_if(resource_changed::<Difficulty>),
+                    (
+                        update_rule_text,
+                        update_game_mode_text,
+                        update_generation_text,
+                        update_map_option_text,
+                    )
+                        .run_if(resource_changed::<GameRules>),
+                )
+                    .run_if(in_state(NewGameState::Main)),
+            )
+            .add_systems(
+                Update,
+                (
+                    progress_check,
+                    update_generation_bar,
+                    cancel_generation_on_close,
+                )
+                    .run_if(in_state(NewGameState::GeneratingWorld)),
             );
     }
 }
@@ -31,11 +69,24 @@ impl Plugin for MenuNewGamePlugin {
 pub enum NewGameState {
     #[default]
     Main,
+    PartySelect,
+    /// Picking one of the [`ChallengeMap`](super::challenge_maps::ChallengeMap)s to play.
+    ChallengeMaps,
     GeneratingWorld,
 }
 
+/// The world picked on the first page, waiting for the party to be chosen.
+#[derive(Resource, Clone)]
+pub struct ChosenWorld {
+    pub seed: u64,
+    pub name: String,
+}
+
 #[derive(Resource, Default)]
 pub struct GenerationProgress {
+    /// How far the task laying out the world has got, out of `layout_steps`.
+    pub layout: LayoutProgress,
+    pub layout_steps: u32,
     pub world_done: bool,
     pub characters_done: bool,
 }
@@ -44,20 +95,310 @@ impl GenerationProgress {
     pub fn done(&self) -> bool {
         self.world_done & self.characters_done
     }
+
+    /// How much of the world is ready, from 0 to 1.
+    pub fn fraction(&self) -> f32 {
+        let steps = self.layout.steps().min(self.layout_steps)
+            + self.world_done as u32
+            + self.characters_done as u32;
+        steps as f32 / (self.layout_steps + 2) as f32
+    }
 }
 
+/// The filled part of the bar showing how much of the world is ready.
+#[derive(Component)]
+struct GenerationBar;
+
 #[derive(Component)]
 pub struct WorldNameTextBox;
 
 #[derive(Component)]
 pub struct WorldSeedTextBox;
 
+/// Says what seed the seed box will give, or what is wrong with it.
+#[derive(Component)]
+struct SeedFeedback;
+
+/// What was typed or pasted into the seed box.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeedInput {
+    /// Nothing was entered, so a new seed is rolled.
+    Random,
+    Seed(u64),
+    Invalid(&'static str),
+}
+
+impl SeedInput {
+    /// Reads a hex seed, allowing a `0x` prefix and spaces or underscores
+    /// between digits, as seeds are often pasted from elsewhere.
+    pub fn parse(text: &str) -> Self {
+        let text = text.trim();
+        let text = text
+            .strip_prefix("0x")
+            .or_else(|| text.strip_prefix("0X"))
+            .unwrap_or(text);
+        let digits: String = text
+            .chars()
+            .filter(|c| !c.is_whitespace() && *c != '_')
+            .collect();
+
+        if digits.is_empty() {
+            SeedInput::Random
+        } else if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            SeedInput::Invalid("Seeds are hex, only 0-9 and A-F")
+        } else if digits.len() > 16 {
+            SeedInput::Invalid("Seeds are at most 16 hex digits")
+        } else {
+            u64::from_str_radix(&digits, 16)
+                .map_or(SeedInput::Invalid("Not a valid seed"), SeedInput::Seed)
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            SeedInput::Random => "Leave empty for a random seed".to_string(),
+            SeedInput::Seed(seed) => format!("Seed {seed:X} ({seed})"),
+            SeedInput::Invalid(reason) => reason.to_string(),
+        }
+    }
+}
+
+fn update_seed_feedback(
+    style: Res<Style>,
+    seed_box: Query<&TextInputContents, (With<WorldSeedTextBox>, Changed<TextInputContents>)>,
+    mut feedback: Query<(&mut Text, &mut TextColor), With<SeedFeedback>>,
+) {
+    let Ok(contents) = seed_box.single() else {
+        return;
+    };
+
+    let input = SeedInput::parse(contents.get());
+    for (mut text, mut color) in feedback.iter_mut() {
+        text.0 = input.message();
+        color.0 = match input {
+            SeedInput::Invalid(_) => style.accent_color,
+            SeedInput::Random | SeedInput::Seed(_) => style.text_color,
+        };
+    }
+}
+
+/// The buttons next to the seed box.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+enum SeedButton {
+    /// Pastes over the seed box, the text input reads the clipboard.
+    Paste,
+    /// Fills the seed box with a freshly rolled seed.
+    Random,
+}
+
+fn seed_button_on_click(
+    mut click: Trigger<Pointer<Click>>,
+    buttons: Query<&SeedButton>,
+    mut seed_box: Query<&mut TextInputQueue, With<WorldSeedTextBox>>,
+) {
+    let Ok(button) = buttons.get(click.target()) else {
+        return;
+    };
+    if click.button != PointerButton::Primary {
+        return;
+    }
+
+    for mut queue in seed_box.iter_mut() {
+        match button {
+            SeedButton::Paste => {
+                queue.add(TextInputAction::Edit(TextInputEdit::SelectAll));
+                queue.add(TextInputAction::Paste);
+            }
+            SeedButton::Random => fill_seed_box(&mut queue, random_seed()),
+        }
+    }
+    click.propagate(false);
+}
+
+/// Replaces what is in the seed box with the seed.
+fn fill_seed_box(queue: &mut TextInputQueue, seed: u64) {
+    queue.add(TextInputAction::Edit(TextInputEdit::SelectAll));
+    queue.add(TextInputAction::Edit(TextInputEdit::Delete));
+    for digit in format!("{seed:X}").chars() {
+        queue.add(TextInputAction::Edit(TextInputEdit::Insert(digit, false)));
+    }
+}
+
+/// A recently played seed, filling the seed box when clicked.
+#[derive(Component)]
+struct SeedChip(u64);
+
+fn seed_chip_on_click(
+    mut click: Trigger<Pointer<Click>>,
+    chips: Query<&SeedChip>,
+    mut seed_box: Query<&mut TextInputQueue, With<WorldSeedTextBox>>,
+) {
+    let Ok(SeedChip(seed)) = chips.get(click.target()) else {
+        return;
+    };
+    if click.button != PointerButton::Primary {
+        return;
+    }
+
+    for mut queue in seed_box.iter_mut() {
+        fill_seed_box(&mut queue, *seed);
+    }
+    click.propagate(false);
+}
+
+#[derive(Component)]
+struct DifficultyText;
+
+fn difficulty_text(difficulty: Difficulty) -> String {
+    format!("Difficulty: {difficulty}")
+}
+
+fn cycle_difficulty_on_click(
+    mut click: Trigger<Pointer<Click>>,
+    mut difficulty: ResMut<Difficulty>,
+) {
+    if click.button != PointerButton::Primary {
+        return;
+    }
+
+    *difficulty = difficulty.next();
+    click.propagate(false);
+}
+
+fn update_difficulty_text(
+    difficulty: Res<Difficulty>,
+    mut text: Query<&mut Text, With<DifficultyText>>,
+) {
+    for mut text in text.iter_mut() {
+        text.0 = difficulty_text(*difficulty);
+    }
+}
+
+#[derive(Component)]
+struct RuleText(Rule);
+
+fn rule_text(rules: &GameRules, rule: Rule) -> String {
+    format!("{rule}: {}", if rules.get(rule) { "On" } else { "Off" })
+}
+
+fn toggle_rule_on_click(rule: Rule) -> impl FnMut(Trigger<Pointer<Click>>, ResMut<GameRules>) {
+    move |mut click, mut rules| {
+        if click.button != PointerButton::Primary {
+            return;
+        }
+
+        rules.toggle(rule);
+        click.propagate(false);
+    }
+}
+
+fn update_rule_text(rules: Res<GameRules>, mut text: Query<(&mut Text, &RuleText)>) {
+    for (mut text, RuleText(rule)) in text.iter_mut() {
+        text.0 = rule_text(&rules, *rule);
+    }
+}
+
+#[derive(Component)]
+struct GameModeText;
+
+fn game_mode_text(mode: GameMode) -> String {
+    format!("Mode: {mode}")
+}
+
+fn cycle_game_mode_on_click(mut click: Trigger<Pointer<Click>>, mut rules: ResMut<GameRules>) {
+    if click.button != PointerButton::Primary {
+        return;
+    }
+
+    rules.mode = rules.mode.next();
+    click.propagate(false);
+}
+
+fn update_game_mode_text(rules: Res<GameRules>, mut text: Query<&mut Text, With<GameModeText>>) {
+    for mut text in text.iter_mut() {
+        text.0 = game_mode_text(rules.mode);
+    }
+}
+
+#[derive(Component)]
+struct GenerationText;
+
+fn generation_text(generation: GenerationMode) -> String {
+    format!("Layout: {generation}")
+}
+
+fn cycle_generation_on_click(mut click: Trigger<Pointer<Click>>, mut rules: ResMut<GameRules>) {
+    if click.button != PointerButton::Primary {
+        return;
+    }
+
+    rules.generation = rules.generation.next();
+    click.propagate(false);
+}
+
+fn update_generation_text(rules: Res<GameRules>, mut text: Query<&mut Text, With<GenerationText>>) {
+    for mut text in text.iter_mut() {
+        text.0 = generation_text(rules.generation);
+    }
+}
+
+/// The options for how the map is generated, besides its layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter)]
+enum MapOption {
+    Radius,
+    Pillars,
+    Rooms,
+}
+
+#[derive(Component)]
+struct MapOptionText(MapOption);
+
+fn map_option_text(rules: &GameRules, option: MapOption) -> String {
+    match option {
+        MapOption::Radius => format!("Map Radius: {}", rules.shape.radius),
+        MapOption::Pillars => format!("Pillars: {}", rules.shape.pillars),
+        MapOption::Rooms => format!("Rooms: {}", rules.rooms),
+    }
+}
+
+fn cycle_map_option_on_click(
+    option: MapOption,
+) -> impl FnMut(Trigger<Pointer<Click>>, ResMut<GameRules>) {
+    move |mut click, mut rules| {
+        if click.button != PointerButton::Primary {
+            return;
+        }
+
+        match option {
+            MapOption::Radius => rules.shape = rules.shape.next_radius(),
+            MapOption::Pillars => rules.shape = rules.shape.next_pillars(),
+            MapOption::Rooms => rules.rooms = rules.rooms.next(),
+        }
+        click.propagate(false);
+    }
+}
+
+fn update_map_option_text(rules: Res<GameRules>, mut text: Query<(&mut Text, &MapOptionText)>) {
+    for (mut text, MapOptionText(option)) in text.iter_mut() {
+        text.0 = map_option_text(&rules, *option);
+    }
+}
+
 fn progress_check(
     mut commands: Commands,
     progress: Res<GenerationProgress>,
+    task: Option<ResMut<GenerationTask>>,
     mut next_state: ResMut<NextState<AppState>>,
     info_q: Query<(Entity, &RoomInfo)>,
 ) {
+    if let Some(mut task) = task {
+        if let Some(layout) = task.poll() {
+            commands.remove_resource::<GenerationTask>();
+            place_layout(&mut commands, layout);
+        }
+        return;
+    }
+
     if progress.done() {
         let current_room = info_q
             .iter()
@@ -74,16 +415,17 @@ fn progress_check(
         commands.entity(current_room).insert(CurrentRoom);
 
         #[cfg(feature = "sqlite")]
-        commands.run_system_cached(crate::saving::save_game);
+        commands.run_system_cached(crate::saving::save_game_full);
         next_state.set(AppState::Game);
     }
 }
 
 fn escape_out(
+    mut commands: Commands,
     new_game_state: Res<State<NewGameState>>,
     mut input_focus: ResMut<InputFocus>,
-    mut next_new_game_state: ResMut<NextState<NewGameState>>,
     mut next_menu_state: ResMut<NextState<MenuState>>,
+    mut next_new_game_state: ResMut<NextState<NewGameState>>,
     key: Res<ControlState>,
 ) {
     if key.just_pressed(Control::Pause) {
@@ -97,27 +439,64 @@ fn escape_out(
             S::Main => {
                 next_menu_state.set(MenuState::Main);
             }
-            S::GeneratingWorld => {
+            S::PartySelect | S::ChallengeMaps => {
                 next_new_game_state.set(NewGameState::Main);
             }
+            S::GeneratingWorld => {
+                commands.run_system_cached(cancel_generation);
+            }
         }
     }
 }
 
-fn cancel_generation(
-    mut click: Trigger<Pointer<Click>>,
+fn cancel_generation_on_click(mut click: Trigger<Pointer<Click>>, mut commands: Commands) {
+    let PointerButton::Primary = click.button else {
+        return;
+    };
+
+    commands.run_system_cached(cancel_generation);
+
+    click.propagate(false);
+}
+
+/// Closing the window mid generation would leave behind the save made for it,
+/// so it is cancelled before the window closes.
+fn cancel_generation_on_close(
+    mut commands: Commands,
+    mut close_requested: EventReader<WindowCloseRequested>,
+) {
+    if close_requested.read().count() > 0 {
+        info!("Window closed while generating, cancelling generation");
+        commands.run_system_cached(cancel_generation);
+    }
+}
+
+/// Throws away the world being generated, along with the save made for it.
+pub fn cancel_generation(
     mut commands: Commands,
     room_map_q: Query<Entity, With<RoomTilemap>>,
     party: Query<Entity, With<Actor>>,
     mut next_new_game_state: ResMut<NextState<NewGameState>>,
+    #[cfg(feature = "sqlite")] db: NonSend<Database>,
+    #[cfg(feature = "sqlite")] save: Option<Res<SaveGame>>,
 ) {
-    let PointerButton::Primary = click.button else {
-        return;
-    };
-
     next_new_game_state.set(NewGameState::Main);
 
+    #[cfg(feature = "sqlite")]
+    if let Some(save) = save {
+        if let Err(err) = SaveGame::delete(&db, save.game_id) {
+            warn!("Failed to delete the cancelled save game with {err}");
+        }
+        if let Err(err) = DailyRun::forget(&db, save.game_id) {
+            warn!("Failed to forget the cancelled daily run with {err}");
+        }
+        if let Err(err) = ChallengeRun::forget(&db, save.game_id) {
+            warn!("Failed to forget the cancelled challenge run with {err}");
+        }
+    }
+
     commands.remove_resource::<GenerationProgress>();
+    commands.remove_resource::<GenerationTask>();
     #[cfg(feature = "sqlite")]
     commands.remove_resource::<SaveGame>();
     commands.remove_resource::<GenerationSettings>();
@@ -129,43 +508,92 @@ fn cancel_generation(
     for entity in party.iter() {
         commands.entity(entity).despawn();
     }
-
-    click.propagate(false);
 }
 
-fn generate_world_click(
+fn choose_party_click(
     mut click: Trigger<Pointer<Click>>,
     mut commands: Commands,
     mut next_new_game_state: ResMut<NextState<NewGameState>>,
-    db: NonSend<Database>,
     contents_query: Query<&TextInputContents, With<WorldSeedTextBox>>,
+    name_query: Query<&TextInputContents, With<WorldNameTextBox>>,
+    mut notifications: ResMut<Notifications>,
 ) {
     let PointerButton::Primary = click.button else {
         return;
     };
+    click.propagate(false);
 
-    let seed = contents_query
+    let input = contents_query
         .single()
         .inspect_err(|e| warn!("Failed to get seed from textbox with {e}"))
-        .ok()
-        .and_then(|seed| {
-            u64::from_str_radix(seed.get(), 16)
-                .inspect_err(|e| warn!("Failed to parse seed from textbox with {e}"))
-                .ok()
-        })
-        .unwrap_or_else(|| getrandom::u64().unwrap_or(0x5eed_f0e_feee));
+        .map_or(SeedInput::Random, |seed| SeedInput::parse(seed.get()));
+    let seed = match input {
+        SeedInput::Random => random_seed(),
+        SeedInput::Seed(seed) => seed,
+        SeedInput::Invalid(reason) => {
+            notifications.warn(reason);
+            return;
+        }
+    };
 
-    commands.insert_resource(GenerationProgress::default());
+    let name = name_query
+        .single()
+        .map(|name| name.get().trim().to_string())
+        .unwrap_or_default();
+
+    commands.insert_resource(ChosenWorld { seed, name });
+    commands.remove_resource::<ChosenChallengeMap>();
     #[cfg(feature = "sqlite")]
-    commands.insert_resource(SaveGame::new(&db, seed));
-    commands.insert_resource(GenerationSettings { seed: seed });
+    commands.remove_resource::<ChosenDaily>();
+    next_new_game_state.set(NewGameState::PartySelect);
+}
 
-    next_new_game_state.set(NewGameState::GeneratingWorld);
+pub fn random_seed() -> u64 {
+    getrandom::u64().unwrap_or(0x5eed_0f0e_feee)
+}
 
-    click.propagate(false);
+/// Creates the save and starts generating a new world from the seed.
+pub fn start_generating_world(
+    commands: &mut Commands,
+    next_state: &mut NextState<NewGameState>,
+    #[cfg(feature = "sqlite")] db: &Database,
+    seed: u64,
+    difficulty: Difficulty,
+    rules: GameRules,
+    name: &str,
+) {
+    commands.insert_resource(GenerationProgress::default());
+    #[cfg(feature = "sqlite")]
+    commands.insert_resource(SaveGame::new(db, seed, difficulty, rules, name));
+    if rules.corruption {
+        commands.insert_resource(PillarCorruption::default());
+    } else {
+        commands.remove_resource::<PillarCorruption>();
+    }
+    commands.insert_resource(GenerationSettings {
+        seed,
+        difficulty,
+        rooms: rules.rooms.weights(),
+        mode: rules.generation,
+        shape: rules.shape,
+        ..default()
+    });
+    commands.insert_resource(difficulty);
+    commands.insert_resource(rules.mode);
+    commands.insert_resource(rules.challenges);
+    commands.insert_resource(rules.shape);
+
+    next_state.set(NewGameState::GeneratingWorld);
 }
 
-fn new_game_enter(mut commands: Commands, style: Res<Style>) {
+fn new_game_enter(
+    mut commands: Commands,
+    style: Res<Style>,
+    difficulty: Res<Difficulty>,
+    rules: Res<GameRules>,
+    history: Res<SeedHistory>,
+    #[cfg(feature = "sqlite")] db: NonSend<Database>,
+) {
     let button_node = Node {
         width: Val::Px(200.0),
         height: Val::Px(65.0),
@@ -204,7 +632,7 @@ fn new_game_enter(mut commands: Commands, style: Res<Style>) {
                     ..default()
                 })
                 .with_children(|builder| {
-                    builder.spawn((button_text_style.clone(), Text::new("Seed:")));
+                    builder.spawn((button_text_style.clone(), Text::new("World Name:")));
 
                     builder
                         .spawn((
@@ -226,15 +654,14 @@ fn new_game_enter(mut commands: Commands, style: Res<Style>) {
                                     height: Val::Percent(100.0),
                                     ..default()
                                 },
-                                WorldSeedTextBox,
+                                WorldNameTextBox,
                                 TextInputContents::default(),
                                 TextInputNode {
                                     clear_on_submit: false,
                                     mode: TextInputMode::SingleLine,
                                     focus_on_pointer_down: true,
                                     unfocus_on_submit: true,
-                                    max_chars: Some(16),
-                                    filter: Some(TextInputFilter::Hex),
+                                    max_chars: Some(24),
                                     ..default()
                                 },
                                 button_text_style.clone(),
@@ -242,18 +669,283 @@ fn new_game_enter(mut commands: Commands, style: Res<Style>) {
                         })
                         .observe(stop_event_propagate::<Pointer<Click>>);
 
+                    builder.spawn((button_text_style.clone(), Text::new("Seed:")));
+
+                    builder
+                        .spawn(Node {
+                            align_items: AlignItems::Center,
+                            ..default()
+                        })
+                        .with_children(|builder| {
+                            builder
+                                .spawn((
+                                    Node {
+                                        width: Val::Px(300.0),
+                                        height: Val::Px(60.0),
+                                        padding: UiRect::all(Val::Px(10.0)),
+                                        margin: UiRect::all(Val::Px(10.0)),
+                                        align_items: AlignItems::Center,
+                                        justify_content: JustifyContent::Center,
+                                        ..default()
+                                    },
+                                    BackgroundColor(style.background_color.with_alpha(1.0)),
+                                ))
+                                .with_children(|builder| {
+                                    builder.spawn((
+                                        Node {
+                                            width: Val::Percent(100.0),
+                                            height: Val::Percent(100.0),
+                                            ..default()
+                                        },
+                                        WorldSeedTextBox,
+                                        TextInputContents::default(),
+                                        TextInputQueue::default(),
+                                        TextInputNode {
+                                            clear_on_submit: false,
+                                            mode: TextInputMode::SingleLine,
+                                            focus_on_pointer_down: true,
+                                            unfocus_on_submit: true,
+                                            // Room for a pasted `0x` and digit separators.
+                                            max_chars: Some(24),
+                                            ..default()
+                                        },
+                                        button_text_style.clone(),
+                                    ));
+                                })
+                                .observe(stop_event_propagate::<Pointer<Click>>);
+
+                            for (label, button) in
+                                [("Paste", SeedButton::Paste), ("Random", SeedButton::Random)]
+                            {
+                                builder
+                                    .spawn((
+                                        Button,
+                                        Node {
+                                            width: Val::Px(130.0),
+                                            height: Val::Px(60.0),
+                                            ..button_node.clone()
+                                        },
+                                        BackgroundColor(style.button_color),
+                                        children![(
+                                            Text::new(label),
+                                            style.font(26.0),
+                                            TextColor(style.text_color),
+                                            Pickable::IGNORE,
+                                        )],
+                                        button,
+                                    ))
+                                    .observe(seed_button_on_click);
+                            }
+                        });
+
+                    builder.spawn((
+                        Text::new(SeedInput::Random.message()),
+                        style.font(24.0),
+                        TextColor(style.text_color),
+                        SeedFeedback,
+                    ));
+
+                    builder
+                        .spawn(Node {
+                            max_width: Val::Px(800.0),
+                            flex_wrap: FlexWrap::Wrap,
+                            justify_content: JustifyContent::Center,
+                            ..default()
+                        })
+                        .with_children(|builder| {
+                            for played in history.iter() {
+                                builder
+                                    .spawn((
+                                        Button,
+                                        Node {
+                                            padding: UiRect::axes(Val::Px(8.0), Val::Px(2.0)),
+                                            margin: UiRect::all(Val::Px(3.0)),
+                                            ..default()
+                                        },
+                                        BackgroundColor(style.button_color),
+                                        Tooltip::new(format!("Seed {:X}", played.seed)).with_body(
+                                            format!("{}, click to play it again", played.outcome),
+                                        ),
+                                        SeedChip(played.seed),
+                                        children![(
+                                            Text::new(format!(
+                                                "{:X} {}",
+                                                played.seed, played.outcome
+                                            )),
+                                            style.font(20.0),
+                                            TextColor(match played.outcome {
+                                                SeedOutcome::Won => style.accent_color,
+                                                SeedOutcome::Lost | SeedOutcome::Unfinished => {
+                                                    style.text_color
+                                                }
+                                            }),
+                                            Pickable::IGNORE,
+                                        )],
+                                    ))
+                                    .observe(seed_chip_on_click);
+                            }
+                        });
+
+                    builder
+                        .spawn((
+                            Button,
+                            Node {
+                                width: Val::Px(300.0),
+                                ..button_node.clone()
+                            },
+                            BackgroundColor(style.button_color),
+                            children![(
+                                Text::new(difficulty_text(*difficulty)),
+                                button_text_style.clone(),
+                                Pickable::IGNORE,
+                                DifficultyText,
+                            )],
+                        ))
+                        .observe(cycle_difficulty_on_click);
+
+                    builder
+                        .spawn((
+                            Button,
+                            Node {
+                                width: Val::Px(300.0),
+                                ..button_node.clone()
+                            },
+                            BackgroundColor(style.button_color),
+                            children![(
+                                Text::new(game_mode_text(rules.mode)),
+                                button_text_style.clone(),
+                                Pickable::IGNORE,
+                                GameModeText,
+                            )],
+                        ))
+                        .observe(cycle_game_mode_on_click);
+
+                    builder
+                        .spawn((
+                            Button,
+                            Node {
+                                width: Val::Px(300.0),
+                                ..button_node.clone()
+                            },
+                            BackgroundColor(style.button_color),
+                            Tooltip::new("Layout").with_body(
+                                "Paths lead straight to each Pillar, \
+                                 Constraints fill the map with lairs, ruins and halls",
+                            ),
+                            children![(
+                                Text::new(generation_text(rules.generation)),
+                                button_text_style.clone(),
+                                Pickable::IGNORE,
+                                GenerationText,
+                            )],
+                        ))
+                        .observe(cycle_generation_on_click);
+
+                    builder
+                        .spawn(Node {
+                            max_width: Val::Px(1000.0),
+                            flex_wrap: FlexWrap::Wrap,
+                            justify_content: JustifyContent::Center,
+                            ..default()
+                        })
+                        .with_children(|builder| {
+                            for option in MapOption::iter() {
+                                builder
+                                    .spawn((
+                                        Button,
+                                        Node {
+                                            width: Val::Px(300.0),
+                                            height: Val::Px(50.0),
+                                            ..button_node.clone()
+                                        },
+                                        BackgroundColor(style.button_color),
+                                        children![(
+                                            Text::new(map_option_text(&rules, option)),
+                                            style.font(26.0),
+                                            TextColor(style.text_color),
+                                            Pickable::IGNORE,
+                                            MapOptionText(option),
+                                        )],
+                                    ))
+                                    .observe(cycle_map_option_on_click(option));
+                            }
+                        });
+
+                    builder
+                        .spawn(Node {
+                            max_width: Val::Px(1000.0),
+                            flex_wrap: FlexWrap::Wrap,
+                            justify_content: JustifyContent::Center,
+                            ..default()
+                        })
+                        .with_children(|builder| {
+                            for rule in Rule::iter() {
+                                builder
+                                    .spawn((
+                                        Button,
+                                        Node {
+                                            width: Val::Px(320.0),
+                                            height: Val::Px(50.0),
+                                            ..button_node.clone()
+                                        },
+                                        BackgroundColor(style.button_color),
+                                        children![(
+                                            Text::new(rule_text(&rules, rule)),
+                                            style.font(26.0),
+                                            TextColor(style.text_color),
+                                            Pickable::IGNORE,
+                                            RuleText(rule),
+                                        )],
+                                    ))
+                                    .observe(toggle_rule_on_click(rule));
+                            }
+                        });
+
+                    builder
+                        .spawn((
+                            Button,
+                            button_node.clone(),
+                            BackgroundColor(style.button_color),
+                            children![(
+                                Text::new("Choose Party"),
+                                button_text_style.clone(),
+                                Pickable::IGNORE
+                            )],
+                        ))
+                        .observe(choose_party_click);
+
+                    #[cfg(feature = "sqlite")]
                     builder
                         .spawn((
                             Button,
                             button_node.clone(),
                             BackgroundColor(style.button_color),
+                            Tooltip::new("Daily Run").with_body(daily_tooltip_body(
+                                &DailyRun::on_date(&db, daily_date()).unwrap_or_default(),
+                            )),
                             children![(
-                                Text::new("Generate World"),
+                                Text::new("Daily Run"),
                                 button_text_style.clone(),
                                 Pickable::IGNORE
                             )],
                         ))
-                        .observe(generate_world_click);
+                        .observe(daily_run_click);
+
+                    builder
+                        .spawn((
+                            Button,
+                            button_node.clone(),
+                            BackgroundColor(style.button_color),
+                            children![(
+                                Text::new("Challenge Maps"),
+                                button_text_style.clone(),
+                                Pickable::IGNORE
+                            )],
+                        ))
+                        .observe(change_state_on_click(
+                            PointerButton::Primary,
+                            NewGameState::ChallengeMaps,
+                        ));
                 });
 
             builder
@@ -320,6 +1012,27 @@ fn generating_world_enter(mut commands: Commands, style: Res<Style>) {
             StateScoped(NewGameState::GeneratingWorld),
         ))
         .with_children(|builder| {
+            builder.spawn((
+                Node {
+                    width: Val::Px(600.0),
+                    height: Val::Px(40.0),
+                    align_self: AlignSelf::Center,
+                    border: UiRect::all(Val::Px(3.0)),
+                    ..default()
+                },
+                BorderColor(style.text_color),
+                BackgroundColor(style.background_color),
+                children![(
+                    Node {
+                        width: Val::Percent(0.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    BackgroundColor(style.button_color),
+                    GenerationBar,
+                )],
+            ));
+
             builder
                 .spawn((
                     Node {
@@ -346,24 +1059,77 @@ fn generating_world_enter(mut commands: Commands, style: Res<Style>) {
                                 Pickable::IGNORE
                             )],
                         ))
-                        .observe(cancel_generation);
+                        .observe(cancel_generation_on_click);
                 });
         });
 }
 
+fn update_generation_bar(
+    progress: Res<GenerationProgress>,
+    mut bar: Query<&mut Node, With<GenerationBar>>,
+) {
+    for mut node in bar.iter_mut() {
+        node.width = Val::Percent(progress.fraction() * 100.0);
+    }
+}
+
 fn setup_party(
     mut commands: Commands,
     mut progress: ResMut<GenerationProgress>,
     asset_server: Res<AssetServer>,
+    selection: Option<Res<PartySelection>>,
 ) {
-    use ActorName as A;
-    for name in [A::Warrior, A::Priestess, A::Theif] {
-        commands.spawn((
-            ActorBundle::from_name(&asset_server, name, Team::Player, Transform::IDENTITY, true),
-            Visibility::Hidden,
-        ));
-    }
+    let selection = selection.map(|s| s.clone()).unwrap_or_default();
+    spawn_party(&mut commands, &asset_server, &selection);
 
     commands.init_resource::<Items>();
+    commands.init_resource::<Gold>();
     progress.characters_done = true;
 }
+
+#[cfg(all(test, feature = "sqlite"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn cancelling_generation_deletes_the_save() {
+        let mut world = World::new();
+        let db = Database::open_in_memory().unwrap();
+        world.insert_resource(SaveGame::new(
+            &db,
+            0x5eed,
+            Difficulty::default(),
+            GameRules::default(),
+            "",
+        ));
+        world.insert_non_send_resource(db);
+        world.init_resource::<NextState<NewGameState>>();
+        world.insert_resource(GenerationProgress::default());
+
+        world.run_system_cached(cancel_generation).unwrap();
+        world.flush();
+
+        let db = world.non_send_resource::<Database>();
+        assert!(SaveGameInfo::get_all(db).unwrap().is_empty());
+        assert!(!world.contains_resource::<SaveGame>());
+        assert!(!world.contains_resource::<GenerationProgress>());
+    }
+
+    #[test]
+    fn pasted_seeds_are_read_leniently() {
+        assert_eq!(SeedInput::parse(""), SeedInput::Random);
+        assert_eq!(SeedInput::parse("  "), SeedInput::Random);
+        assert_eq!(SeedInput::parse("5eed"), SeedInput::Seed(0x5eed));
+        assert_eq!(
+            SeedInput::parse(" 0xDEAD_BEEF "),
+            SeedInput::Seed(0xdead_beef)
+        );
+        assert_eq!(SeedInput::parse("dead beef"), SeedInput::Seed(0xdead_beef));
+        assert!(matches!(SeedInput::parse("seed"), SeedInput::Invalid(_)));
+        assert!(matches!(
+            SeedInput::parse("1234567890abcdef0"),
+            SeedInput::Invalid(_)
+        ));
+        assert_eq!(SeedInput::Seed(0xff).message(), "Seed FF (255)");
+    }
+}
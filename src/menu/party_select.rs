@@ -0,0 +1,431 @@
+//! Picking and naming the heroes in the party before a new world is generated.
+
+use super::challenge_maps::{ChosenChallengeMap, challenge_settings};
+#[cfg(feature = "sqlite")]
+use super::daily_run::{ChosenDaily, daily_settings};
+use super::new_game::{ChosenWorld, NewGameState, start_generating_world};
+use crate::generate_map::PresetLayout;
+use crate::prelude::*;
+use bevy::prelude::*;
+use bevy_ui_text_input::{TextInputContents, TextInputMode, TextInputNode};
+
+pub struct MenuPartySelectPlugin;
+
+impl Plugin for MenuPartySelectPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PartySelection>()
+            .add_systems(OnEnter(NewGameState::PartySelect), party_select_enter)
+            .add_systems(
+                Update,
+                update_hero_cards.run_if(
+                    in_state(NewGameState::PartySelect).and(resource_changed::<PartySelection>),
+                ),
+            );
+    }
+}
+
+/// The heroes picked for the next new game, in party order.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct PartySelection {
+    pub classes: [ActorName; PARTY_SIZE],
+    /// The names given to each hero, blank to use their class.
+    pub names: [String; PARTY_SIZE],
+}
+
+impl Default for PartySelection {
+    fn default() -> Self {
+        Self {
+            classes: ActorName::HEROES,
+            names: Default::default(),
+        }
+    }
+}
+
+impl PartySelection {
+    /// The name of every hero, made unique so they can be told apart.
+    pub fn hero_names(&self) -> [String; PARTY_SIZE] {
+        let mut names: [String; PARTY_SIZE] = Default::default();
+
+        for slot in 0..PARTY_SIZE {
+            let base = match self.names[slot].trim() {
+                "" => self.classes[slot].to_string(),
+                name => name.to_string(),
+            };
+
+            let mut name = base.clone();
+            let mut count = 1;
+            while names[..slot].contains(&name) {
+                count += 1;
+                name = format!("{base} {count}");
+            }
+            names[slot] = name;
+        }
+
+        names
+    }
+
+    /// Moves the hero in the slot through the roster, wrapping around.
+    pub fn cycle_class(&mut self, slot: usize, step: isize) {
+        let heroes = &ActorName::HEROES;
+        let index = heroes
+            .iter()
+            .position(|hero| *hero == self.classes[slot])
+            .unwrap_or(0);
+        let index = (index as isize + step).rem_euclid(heroes.len() as isize) as usize;
+        self.classes[slot] = heroes[index];
+    }
+}
+
+#[derive(Component)]
+struct HeroClassText(usize);
+
+#[derive(Component)]
+struct HeroStatsText(usize);
+
+#[derive(Component)]
+struct HeroNameTextBox(usize);
+
+fn hero_stats_text(class: ActorName) -> String {
+    let health = HealthBundle::from_name(class).health;
+    let attack = Attack::from_name(class);
+
+    format!(
+        "Health: {}\nDamage: {}-{}\nHit Chance: {:.0}%\nBlock Chance: {:.0}%\nSpeed: {}",
+        health.max(),
+        attack.damage().start,
+        attack.damage().end.saturating_sub(1),
+        attack.hit_chance() * 100.0,
+        BlockChance::from_name(class).0 * 100.0,
+        AttackSpeed::from_name(class).0,
+    )
+}
+
+fn party_select_enter(mut commands: Commands, style: Res<Style>, selection: Res<PartySelection>) {
+    let button_node = Node {
+        width: Val::Px(200.0),
+        height: Val::Px(65.0),
+        margin: UiRect::all(Val::Px(5.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let arrow_node = Node {
+        width: Val::Px(50.0),
+        height: Val::Px(50.0),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+
+    let text_style = (
+        style.font(33.0),
+        TextColor(style.text_color),
+        TextLayout::new_with_justify(JustifyText::Center),
+    );
+    let stats_style = (
+        style.font(24.0),
+        TextColor(style.text_color),
+        TextLayout::new_with_justify(JustifyText::Left),
+    );
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            StateScoped(NewGameState::PartySelect),
+        ))
+        .observe(clear_focus_on_click)
+        .with_children(|builder| {
+            builder.spawn((Text::new("Choose your party"), text_style.clone()));
+
+            builder.spawn(Node::default()).with_children(|builder| {
+                for slot in 0..PARTY_SIZE {
+                    let class = selection.classes[slot];
+
+                    builder
+                        .spawn((
+                            Node {
+                                width: Val::Px(300.0),
+                                margin: UiRect::all(Val::Px(10.0)),
+                                padding: UiRect::all(Val::Px(10.0)),
+                                flex_direction: FlexDirection::Column,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            BackgroundColor(style.background_color),
+                        ))
+                        .with_children(|builder| {
+                            builder
+                                .spawn(Node {
+                                    width: Val::Percent(100.0),
+                                    align_items: AlignItems::Center,
+                                    justify_content: JustifyContent::SpaceBetween,
+                                    ..default()
+                                })
+                                .with_children(|builder| {
+                                    builder
+                                        .spawn((
+                                            Button,
+                                            arrow_node.clone(),
+                                            BackgroundColor(style.button_color),
+                                            children![(
+                                                Text::new("<"),
+                                                text_style.clone(),
+                                                Pickable::IGNORE
+                                            )],
+                                        ))
+                                        .observe(cycle_class_on_click(slot, -1));
+
+                                    builder.spawn((
+                                        Text::new(class.to_string()),
+                                        text_style.clone(),
+                                        HeroClassText(slot),
+                                    ));
+
+                                    builder
+                                        .spawn((
+                                            Button,
+                                            arrow_node.clone(),
+                                            BackgroundColor(style.button_color),
+                                            children![(
+                                                Text::new(">"),
+                                                text_style.clone(),
+                                                Pickable::IGNORE
+                                            )],
+                                        ))
+                                        .observe(cycle_class_on_click(slot, 1));
+                                });
+
+                            builder.spawn((
+                                Text::new(hero_stats_text(class)),
+                                stats_style.clone(),
+                                Node {
+                                    margin: UiRect::vertical(Val::Px(10.0)),
+                                    ..default()
+                                },
+                                HeroStatsText(slot),
+                            ));
+
+                            builder.spawn((Text::new("Name:"), stats_style.clone()));
+
+                            builder
+                                .spawn((
+                                    Node {
+                                        width: Val::Percent(100.0),
+                                        height: Val::Px(50.0),
+                                        padding: UiRect::all(Val::Px(5.0)),
+                                        ..default()
+                                    },
+                                    BackgroundColor(style.background_color.with_alpha(1.0)),
+                                ))
+                                .with_children(|builder| {
+                                    builder.spawn((
+                                        Node {
+                                            width: Val::Percent(100.0),
+                                            height: Val::Percent(100.0),
+                                            ..default()
+                                        },
+                                        HeroNameTextBox(slot),
+                                        TextInputContents::default(),
+                                        TextInputNode {
+                                            clear_on_submit: false,
+                                            mode: TextInputMode::SingleLine,
+                                            focus_on_pointer_down: true,
+                                            unfocus_on_submit: true,
+                                            max_chars: Some(16),
+                                            ..default()
+                                        },
+                                        stats_style.clone(),
+                                    ));
+                                })
+                                .observe(stop_event_propagate::<Pointer<Click>>);
+                        });
+                }
+            });
+
+            builder.spawn(Node::default()).with_children(|builder| {
+                builder
+                    .spawn((
+                        Button,
+                        button_node.clone(),
+                        BackgroundColor(style.button_color),
+                        children![(Text::new("Back"), text_style.clone(), Pickable::IGNORE)],
+                    ))
+                    .observe(change_state_on_click(
+                        PointerButton::Primary,
+                        NewGameState::Main,
+                    ));
+
+                builder
+                    .spawn((
+                        Button,
+                        Node {
+                            width: Val::Px(300.0),
+                            ..button_node.clone()
+                        },
+                        BackgroundColor(style.button_color),
+                        children![(
+                            Text::new("Generate World"),
+                            text_style.clone(),
+                            Pickable::IGNORE
+                        )],
+                    ))
+                    .observe(start_game_click);
+            });
+        });
+}
+
+/// Spawns the chosen heroes, hidden until they are placed in the first room.
+pub fn spawn_party(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    selection: &PartySelection,
+) {
+    let names = selection.hero_names();
+
+    for (slot, (class, name)) in selection.classes.into_iter().zip(names).enumerate() {
+        commands.spawn((
+            ActorBundle::from_name(
+                asset_server,
+                class,
+                Team::Player,
+                Transform::IDENTITY,
+                true,
+                Difficulty::default(),
+            ),
+            HeroName(name),
+            PartySlot(slot),
+            Visibility::Hidden,
+        ));
+    }
+}
+
+pub(super) fn cycle_class_on_click(
+    slot: usize,
+    step: isize,
+) -> impl Fn(Trigger<Pointer<Click>>, ResMut<PartySelection>) {
+    move |mut click, mut selection| {
+        if click.button != PointerButton::Primary {
+            return;
+        }
+
+        selection.cycle_class(slot, step);
+        click.propagate(false);
+    }
+}
+
+fn update_hero_cards(
+    selection: Res<PartySelection>,
+    mut class_text: Query<(&mut Text, &HeroClassText), Without<HeroStatsText>>,
+    mut stats_text: Query<(&mut Text, &HeroStatsText), Without<HeroClassText>>,
+) {
+    for (mut text, HeroClassText(slot)) in class_text.iter_mut() {
+        text.0 = selection.classes[*slot].to_string();
+    }
+
+    for (mut text, HeroStatsText(slot)) in stats_text.iter_mut() {
+        text.0 = hero_stats_text(selection.classes[*slot]);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn start_game_click(
+    mut click: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    mut next_new_game_state: ResMut<NextState<NewGameState>>,
+    mut selection: ResMut<PartySelection>,
+    world: Res<ChosenWorld>,
+    difficulty: Res<Difficulty>,
+    rules: Res<GameRules>,
+    db: NonSend<Database>,
+    #[cfg(feature = "sqlite")] daily: Option<Res<ChosenDaily>>,
+    challenge: Option<Res<ChosenChallengeMap>>,
+    name_boxes: Query<(&HeroNameTextBox, &TextInputContents)>,
+    mut notifications: ResMut<Notifications>,
+) {
+    let PointerButton::Primary = click.button else {
+        return;
+    };
+
+    for (HeroNameTextBox(slot), contents) in name_boxes.iter() {
+        selection.names[*slot] = contents.get().to_string();
+    }
+
+    // Daily runs are all played the same way, whatever was picked.
+    #[cfg(feature = "sqlite")]
+    let (difficulty, rules) = daily.map_or((*difficulty, *rules), |_| daily_settings());
+    #[cfg(not(feature = "sqlite"))]
+    let (difficulty, rules) = (*difficulty, *rules);
+
+    // So are challenge maps, which are played on their own layout instead of a generated one.
+    let (difficulty, rules) = match challenge.map(|challenge| challenge.0.layout()) {
+        Some(Ok(layout)) => {
+            let settings = challenge_settings(&layout);
+            commands.insert_resource(PresetLayout(layout));
+            settings
+        }
+        Some(Err(err)) => {
+            notifications.error(format!("Couldn't load the challenge map: {err}"));
+            return;
+        }
+        None => (difficulty, rules),
+    };
+
+    start_generating_world(
+        &mut commands,
+        &mut next_new_game_state,
+        #[cfg(feature = "sqlite")]
+        &db,
+        world.seed,
+        difficulty,
+        rules,
+        &world.name,
+    );
+
+    click.propagate(false);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn hero_names_are_unique() {
+        let selection = PartySelection {
+            classes: [ActorName::Warrior, ActorName::Warrior, ActorName::Theif],
+            names: ["".into(), "  ".into(), "Warrior".into()],
+        };
+
+        assert_eq!(
+            selection.hero_names(),
+            ["Warrior", "Warrior 2", "Warrior 3"].map(String::from)
+        );
+
+        let selection = PartySelection {
+            names: ["Bob".into(), "".into(), "Sneaky".into()],
+            ..default()
+        };
+
+        assert_eq!(
+            selection.hero_names(),
+            ["Bob", "Priestess", "Sneaky"].map(String::from)
+        );
+    }
+
+    #[test]
+    fn cycling_classes_wraps_around() {
+        let mut selection = PartySelection::default();
+
+        selection.cycle_class(0, -1);
+        assert_eq!(selection.classes[0], ActorName::Theif);
+
+        selection.cycle_class(2, 1);
+        assert_eq!(selection.classes[2], ActorName::Warrior);
+    }
+}
@@ -0,0 +1,140 @@
+//! The seeds of the last few games played, so a world worth another go can be picked again
+//! from the New Game screen.
+
+use super::new_game::NewGameState;
+use crate::game::GameState;
+use crate::generate_map::GenerationSettings;
+use crate::prelude::*;
+use crate::settings::SETTINGS_DB_TABLE;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use strum::Display;
+
+/// How many seeds are remembered.
+pub const SEED_HISTORY_LEN: usize = 10;
+
+pub struct SeedHistoryPlugin;
+
+impl Plugin for SeedHistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PreStartup, setup_seed_history)
+            .add_systems(
+                Update,
+                seed_history_sync.run_if(
+                    resource_changed::<SeedHistory>.and(not(resource_added::<SeedHistory>)),
+                ),
+            )
+            .add_systems(OnEnter(NewGameState::GeneratingWorld), record_played_seed);
+
+        #[cfg(feature = "sqlite")]
+        app.add_systems(
+            OnEnter(GameState::Victory),
+            record_outcome(SeedOutcome::Won),
+        )
+        .add_systems(
+            OnEnter(GameState::GameOver),
+            record_outcome(SeedOutcome::Lost),
+        );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Display)]
+pub enum SeedOutcome {
+    Unfinished,
+    Won,
+    Lost,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayedSeed {
+    pub seed: u64,
+    pub outcome: SeedOutcome,
+}
+
+/// The seeds played most recently, newest first.
+#[derive(Resource, Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SeedHistory(VecDeque<PlayedSeed>);
+
+impl SeedHistory {
+    const DB_KEY: &str = "seed_history";
+
+    /// Puts the seed at the front, as a new game started with it.
+    pub fn played(&mut self, seed: u64) {
+        self.0.retain(|played| played.seed != seed);
+        self.0.push_front(PlayedSeed {
+            seed,
+            outcome: SeedOutcome::Unfinished,
+        });
+        self.0.truncate(SEED_HISTORY_LEN);
+    }
+
+    /// Records how the latest game with the seed ended.
+    pub fn finished(&mut self, seed: u64, outcome: SeedOutcome) {
+        match self.0.iter_mut().find(|played| played.seed == seed) {
+            Some(played) => played.outcome = outcome,
+            None => {
+                self.played(seed);
+                self.0[0].outcome = outcome;
+            }
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &PlayedSeed> {
+        self.0.iter()
+    }
+}
+
+fn setup_seed_history(mut commands: Commands, database: NonSend<Database>) {
+    let history = database.get_kv(
+        SETTINGS_DB_TABLE,
+        SeedHistory::DB_KEY,
+        SeedHistory::default(),
+    );
+    commands.insert_resource(history);
+}
+
+fn seed_history_sync(database: NonSend<Database>, history: Res<SeedHistory>) -> Result {
+    database
+        .set_kv(SETTINGS_DB_TABLE, SeedHistory::DB_KEY, &*history)
+        .map_err(GameError::from)?;
+    Ok(())
+}
+
+fn record_played_seed(settings: Res<GenerationSettings>, mut history: ResMut<SeedHistory>) {
+    history.played(settings.seed);
+}
+
+#[cfg(feature = "sqlite")]
+fn record_outcome(outcome: SeedOutcome) -> impl FnMut(Option<Res<SaveGame>>, ResMut<SeedHistory>) {
+    move |save, mut history| {
+        if let Some(save) = save {
+            history.finished(save.seed, outcome);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn history_keeps_the_latest_of_each_seed() {
+        let mut history = SeedHistory::default();
+        for seed in 0..SEED_HISTORY_LEN as u64 + 2 {
+            history.played(seed);
+        }
+        history.finished(5, SeedOutcome::Won);
+        history.played(5);
+        history.finished(0xbad, SeedOutcome::Lost);
+
+        let seeds: Vec<u64> = history.iter().map(|played| played.seed).collect();
+        assert_eq!(seeds.len(), SEED_HISTORY_LEN);
+        assert_eq!(seeds[..3], [0xbad, 5, 11]);
+        assert!(!seeds.contains(&1));
+        assert_eq!(
+            history.iter().nth(1).unwrap().outcome,
+            SeedOutcome::Unfinished
+        );
+        assert_eq!(history.iter().next().unwrap().outcome, SeedOutcome::Lost);
+    }
+}
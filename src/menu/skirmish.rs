@@ -0,0 +1,371 @@
+//! Setting up a skirmish, a single fight to practice combat or test its balance.
+
+use super::MenuState;
+use super::new_game::{SeedInput, random_seed};
+use super::party_select::{PartySelection, cycle_class_on_click};
+use crate::game::{SKIRMISH_ENEMIES, start_skirmish};
+use crate::prelude::*;
+use crate::room::ENEMY_POSITIONS;
+use bevy::prelude::*;
+use bevy_ui_text_input::{TextInputContents, TextInputMode, TextInputNode};
+
+pub struct MenuSkirmishPlugin;
+
+impl Plugin for MenuSkirmishPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SkirmishSetup>()
+            .add_systems(OnEnter(MenuState::Skirmish), skirmish_enter)
+            .add_systems(
+                Update,
+                update_skirmish_slots.run_if(
+                    in_state(MenuState::Skirmish).and(
+                        resource_changed::<SkirmishSetup>.or(resource_changed::<PartySelection>),
+                    ),
+                ),
+            );
+    }
+}
+
+/// The enemies picked for the next skirmish, one for each place in the room.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct SkirmishSetup {
+    pub enemies: [Option<ActorName>; ENEMY_POSITIONS.len()],
+}
+
+impl Default for SkirmishSetup {
+    fn default() -> Self {
+        Self {
+            enemies: [Some(ActorName::Goblin), Some(ActorName::Skeleton), None],
+        }
+    }
+}
+
+impl SkirmishSetup {
+    /// Moves the enemy in the slot through [`SKIRMISH_ENEMIES`], with an empty slot between the
+    /// last and the first.
+    pub fn cycle_enemy(&mut self, slot: usize, step: isize) {
+        let choices = SKIRMISH_ENEMIES.len() as isize + 1;
+        let index = self.enemies[slot]
+            .and_then(|enemy| SKIRMISH_ENEMIES.iter().position(|e| *e == enemy))
+            .unwrap_or(SKIRMISH_ENEMIES.len()) as isize;
+        let index = (index + step).rem_euclid(choices) as usize;
+        self.enemies[slot] = SKIRMISH_ENEMIES.get(index).copied();
+    }
+
+    /// The enemies to fight, leaving out the empty slots.
+    pub fn chosen(&self) -> Box<[ActorName]> {
+        self.enemies.iter().flatten().copied().collect()
+    }
+}
+
+#[derive(Component)]
+struct SkirmishHeroText(usize);
+
+#[derive(Component)]
+struct SkirmishEnemyText(usize);
+
+#[derive(Component)]
+struct SkirmishSeedTextBox;
+
+fn enemy_slot_text(enemy: Option<ActorName>) -> String {
+    enemy.map_or_else(|| "(Empty)".to_string(), |enemy| enemy.to_string())
+}
+
+fn cycle_enemy_on_click(
+    slot: usize,
+    step: isize,
+) -> impl Fn(Trigger<Pointer<Click>>, ResMut<SkirmishSetup>) {
+    move |mut click, mut setup| {
+        if click.button != PointerButton::Primary {
+            return;
+        }
+
+        setup.cycle_enemy(slot, step);
+        click.propagate(false);
+    }
+}
+
+fn update_skirmish_slots(
+    setup: Res<SkirmishSetup>,
+    selection: Res<PartySelection>,
+    mut hero_text: Query<(&mut Text, &SkirmishHeroText), Without<SkirmishEnemyText>>,
+    mut enemy_text: Query<(&mut Text, &SkirmishEnemyText), Without<SkirmishHeroText>>,
+) {
+    for (mut text, SkirmishHeroText(slot)) in hero_text.iter_mut() {
+        text.0 = selection.classes[*slot].to_string();
+    }
+
+    for (mut text, SkirmishEnemyText(slot)) in enemy_text.iter_mut() {
+        text.0 = enemy_slot_text(setup.enemies[*slot]);
+    }
+}
+
+fn skirmish_enter(
+    mut commands: Commands,
+    style: Res<Style>,
+    setup: Res<SkirmishSetup>,
+    selection: Res<PartySelection>,
+) {
+    let button_node = Node {
+        width: Val::Px(200.0),
+        height: Val::Px(65.0),
+        margin: UiRect::all(Val::Px(5.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let arrow_node = Node {
+        width: Val::Px(50.0),
+        height: Val::Px(50.0),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let slot_node = Node {
+        width: Val::Px(300.0),
+        margin: UiRect::all(Val::Px(10.0)),
+        padding: UiRect::all(Val::Px(10.0)),
+        align_items: AlignItems::Center,
+        justify_content: JustifyContent::SpaceBetween,
+        ..default()
+    };
+
+    let text_style = (
+        style.font(33.0),
+        TextColor(style.text_color),
+        TextLayout::new_with_justify(JustifyText::Center),
+    );
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            StateScoped(MenuState::Skirmish),
+        ))
+        .observe(clear_focus_on_click)
+        .with_children(|builder| {
+            builder.spawn((Text::new("Party"), text_style.clone()));
+
+            builder.spawn(Node::default()).with_children(|builder| {
+                for slot in 0..PARTY_SIZE {
+                    builder
+                        .spawn((slot_node.clone(), BackgroundColor(style.background_color)))
+                        .with_children(|builder| {
+                            builder
+                                .spawn((
+                                    Button,
+                                    arrow_node.clone(),
+                                    BackgroundColor(style.button_color),
+                                    children![(
+                                        Text::new("<"),
+                                        text_style.clone(),
+                                        Pickable::IGNORE
+                                    )],
+                                ))
+                                .observe(cycle_class_on_click(slot, -1));
+
+                            builder.spawn((
+                                Text::new(selection.classes[slot].to_string()),
+                                text_style.clone(),
+                                SkirmishHeroText(slot),
+                            ));
+
+                            builder
+                                .spawn((
+                                    Button,
+                                    arrow_node.clone(),
+                                    BackgroundColor(style.button_color),
+                                    children![(
+                                        Text::new(">"),
+                                        text_style.clone(),
+                                        Pickable::IGNORE
+                                    )],
+                                ))
+                                .observe(cycle_class_on_click(slot, 1));
+                        });
+                }
+            });
+
+            builder.spawn((Text::new("Enemies"), text_style.clone()));
+
+            builder.spawn(Node::default()).with_children(|builder| {
+                for slot in 0..ENEMY_POSITIONS.len() {
+                    builder
+                        .spawn((slot_node.clone(), BackgroundColor(style.background_color)))
+                        .with_children(|builder| {
+                            builder
+                                .spawn((
+                                    Button,
+                                    arrow_node.clone(),
+                                    BackgroundColor(style.button_color),
+                                    children![(
+                                        Text::new("<"),
+                                        text_style.clone(),
+                                        Pickable::IGNORE
+                                    )],
+                                ))
+                                .observe(cycle_enemy_on_click(slot, -1));
+
+                            builder.spawn((
+                                Text::new(enemy_slot_text(setup.enemies[slot])),
+                                text_style.clone(),
+                                SkirmishEnemyText(slot),
+                            ));
+
+                            builder
+                                .spawn((
+                                    Button,
+                                    arrow_node.clone(),
+                                    BackgroundColor(style.button_color),
+                                    children![(
+                                        Text::new(">"),
+                                        text_style.clone(),
+                                        Pickable::IGNORE
+                                    )],
+                                ))
+                                .observe(cycle_enemy_on_click(slot, 1));
+                        });
+                }
+            });
+
+            builder.spawn((Text::new("Seed:"), text_style.clone()));
+
+            builder
+                .spawn((
+                    Node {
+                        width: Val::Px(300.0),
+                        height: Val::Px(60.0),
+                        padding: UiRect::all(Val::Px(10.0)),
+                        margin: UiRect::all(Val::Px(10.0)),
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    BackgroundColor(style.background_color.with_alpha(1.0)),
+                ))
+                .with_children(|builder| {
+                    builder.spawn((
+                        Node {
+                            width: Val::Percent(100.0),
+                            height: Val::Percent(100.0),
+                            ..default()
+                        },
+                        SkirmishSeedTextBox,
+                        TextInputContents::default(),
+                        TextInputNode {
+                            clear_on_submit: false,
+                            mode: TextInputMode::SingleLine,
+                            focus_on_pointer_down: true,
+                            unfocus_on_submit: true,
+                            max_chars: Some(24),
+                            ..default()
+                        },
+                        text_style.clone(),
+                    ));
+                })
+                .observe(stop_event_propagate::<Pointer<Click>>);
+
+            builder.spawn(Node::default()).with_children(|builder| {
+                builder
+                    .spawn((
+                        Button,
+                        button_node.clone(),
+                        BackgroundColor(style.button_color),
+                        children![(Text::new("Back"), text_style.clone(), Pickable::IGNORE)],
+                    ))
+                    .observe(change_state_on_click(
+                        PointerButton::Primary,
+                        MenuState::Main,
+                    ));
+
+                builder
+                    .spawn((
+                        Button,
+                        button_node.clone(),
+                        BackgroundColor(style.button_color),
+                        children![(Text::new("Fight"), text_style.clone(), Pickable::IGNORE)],
+                    ))
+                    .observe(start_skirmish_click);
+            });
+        });
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn start_skirmish_click(
+    mut click: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    mut next_state: ResMut<NextState<AppState>>,
+    asset_server: Res<AssetServer>,
+    setup: Res<SkirmishSetup>,
+    selection: Res<PartySelection>,
+    difficulty: Res<Difficulty>,
+    seed_box: Query<&TextInputContents, With<SkirmishSeedTextBox>>,
+    leftovers: Query<Entity, Or<(With<Actor>, With<RoomTilemap>)>>,
+    mut notifications: ResMut<Notifications>,
+) {
+    let PointerButton::Primary = click.button else {
+        return;
+    };
+    click.propagate(false);
+
+    let enemies = setup.chosen();
+    if enemies.is_empty() {
+        notifications.warn("Pick at least one enemy to fight");
+        return;
+    }
+
+    let input = seed_box
+        .single()
+        .map_or(SeedInput::Random, |seed| SeedInput::parse(seed.get()));
+    let seed = match input {
+        SeedInput::Random => random_seed(),
+        SeedInput::Seed(seed) => seed,
+        SeedInput::Invalid(reason) => {
+            notifications.warn(reason);
+            return;
+        }
+    };
+
+    // Whoever is left from an earlier game would otherwise join the fight.
+    for entity in leftovers.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    info!("Starting a skirmish against {enemies:?} with seed {seed:x}");
+    start_skirmish(
+        &mut commands,
+        &asset_server,
+        &selection,
+        enemies,
+        seed,
+        *difficulty,
+    );
+    next_state.set(AppState::Game);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn enemy_slots_cycle_through_empty() {
+        let mut setup = SkirmishSetup {
+            enemies: [None, Some(ActorName::Goblin), Some(ActorName::OgreTyrant)],
+        };
+
+        setup.cycle_enemy(0, 1);
+        assert_eq!(setup.enemies[0], Some(SKIRMISH_ENEMIES[0]));
+        setup.cycle_enemy(1, -1);
+        assert_eq!(setup.enemies[1], None);
+        setup.cycle_enemy(2, 1);
+        assert_eq!(setup.enemies[2], None);
+
+        assert_eq!(*setup.chosen(), [ActorName::Goblin]);
+    }
+}
@@ -0,0 +1,163 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// The most toasts that will be on screen at once.
+/// Any extra are queued until one is dismissed.
+pub const MAX_VISIBLE_TOASTS: usize = 4;
+const TOAST_DURATION: Duration = Duration::from_secs(4);
+const TOAST_WIDTH: f32 = 320.0;
+const TOAST_Z_INDEX: i32 = 100;
+
+const INFO_COLOR: Color = Color::srgb_u8(0x9c, 0xcf, 0xd8);
+const SUCCESS_COLOR: Color = Color::srgb_u8(0x31, 0x74, 0x8f);
+const WARNING_COLOR: Color = Color::srgb_u8(0xf6, 0xc1, 0x77);
+const ERROR_COLOR: Color = Color::srgb_u8(0xeb, 0x6f, 0x92);
+
+pub struct NotificationsPlugin;
+
+impl Plugin for NotificationsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Notifications>()
+            .add_systems(Startup, spawn_toast_container)
+            .add_systems(Update, (dismiss_toasts, show_toasts).chain());
+    }
+}
+
+#[derive(Clone, Copy, Default, Eq, PartialEq, Debug, Hash)]
+pub enum Severity {
+    #[default]
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn color(&self) -> Color {
+        match self {
+            Severity::Info => INFO_COLOR,
+            Severity::Success => SUCCESS_COLOR,
+            Severity::Warning => WARNING_COLOR,
+            Severity::Error => ERROR_COLOR,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Notification {
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// Queue of pending toasts.
+/// Push to this from anywhere and they will be shown in the corner.
+#[derive(Resource, Default)]
+pub struct Notifications {
+    queue: VecDeque<Notification>,
+}
+
+impl Notifications {
+    pub fn push(&mut self, severity: Severity, message: impl Into<String>) {
+        self.queue.push_back(Notification {
+            severity,
+            message: message.into(),
+        });
+    }
+
+    pub fn info(&mut self, message: impl Into<String>) {
+        self.push(Severity::Info, message);
+    }
+
+    pub fn success(&mut self, message: impl Into<String>) {
+        self.push(Severity::Success, message);
+    }
+
+    pub fn warn(&mut self, message: impl Into<String>) {
+        self.push(Severity::Warning, message);
+    }
+
+    pub fn error(&mut self, message: impl Into<String>) {
+        self.push(Severity::Error, message);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+}
+
+/// The corner node that all toasts are stacked in.
+#[derive(Component)]
+pub struct ToastContainer;
+
+#[derive(Component)]
+pub struct Toast(Timer);
+
+fn spawn_toast_container(mut commands: Commands) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            right: Val::Px(10.0),
+            width: Val::Px(TOAST_WIDTH),
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(5.0),
+            ..default()
+        },
+        GlobalZIndex(TOAST_Z_INDEX),
+        Pickable::IGNORE,
+        ToastContainer,
+    ));
+}
+
+fn dismiss_toasts(
+    mut commands: Commands,
+    mut toasts: Query<(Entity, &mut Toast)>,
+    time: Res<Time<Real>>,
+) {
+    for (entity, mut toast) in toasts.iter_mut() {
+        if toast.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn show_toasts(
+    mut commands: Commands,
+    mut notifications: ResMut<Notifications>,
+    container: Single<Entity, With<ToastContainer>>,
+    toasts: Query<&Toast>,
+    style: Res<Style>,
+) {
+    if notifications.is_empty() {
+        return;
+    }
+
+    let visible = toasts.iter().filter(|t| !t.0.finished()).count();
+    let available = MAX_VISIBLE_TOASTS
+        .saturating_sub(visible)
+        .min(notifications.queue.len());
+
+    for Notification { severity, message } in notifications.queue.drain(..available) {
+        commands.entity(*container).with_child((
+            Node {
+                width: Val::Percent(100.0),
+                padding: UiRect::all(Val::Px(8.0)),
+                border: UiRect::left(Val::Px(6.0)),
+                ..default()
+            },
+            BackgroundColor(style.button_color),
+            BorderColor(severity.color()),
+            Toast(Timer::new(TOAST_DURATION, TimerMode::Once)),
+            Pickable::IGNORE,
+            children![(
+                Text::new(message),
+                style.font(24.0),
+                TextColor(style.text_color),
+                Label,
+                Pickable::IGNORE,
+            )],
+        ));
+    }
+}
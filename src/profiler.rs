@@ -0,0 +1,177 @@
+//! A debug overlay with frame timings for each phase of the frame,
+//! and the entity and archetype counts. Toggled with F5.
+//!
+//! The phases are timed between the main schedules, so a phase includes
+//! everything run in its schedules. Render is the rest of the frame,
+//! which is extraction and waiting on the render thread.
+
+use crate::prelude::*;
+use bevy::app::MainScheduleOrder;
+use bevy::ecs::archetype::Archetypes;
+use bevy::ecs::entity::Entities;
+use bevy::ecs::schedule::ScheduleLabel;
+use bevy::input::common_conditions::input_just_pressed;
+use bevy::platform::time::Instant;
+use bevy::prelude::*;
+use std::time::Duration;
+
+const PROFILER_TOGGLE_KEY: KeyCode = KeyCode::F5;
+const PROFILER_REFRESH_INTERVAL: Duration = Duration::from_millis(250);
+/// How much each frame moves the averages.
+const PROFILER_SMOOTHING: f32 = 0.1;
+const PROFILER_Z_INDEX: i32 = 200;
+
+pub struct ProfilerPlugin;
+
+impl Plugin for ProfilerPlugin {
+    fn build(&self, app: &mut App) {
+        let mut order = app.world_mut().resource_mut::<MainScheduleOrder>();
+        order.insert_before(PreUpdate, ProfileMark(Phase::Input));
+        order.insert_after(PreUpdate, ProfileMark(Phase::Logic));
+        order.insert_after(Update, ProfileMark(Phase::Ui));
+        order.insert_after(Last, ProfileMark(Phase::Render));
+
+        for phase in Phase::ALL {
+            app.add_systems(ProfileMark(phase), mark(phase));
+        }
+
+        app.init_resource::<FrameTimings>()
+            .add_systems(Startup, spawn_profiler_overlay)
+            .add_systems(
+                Update,
+                (
+                    toggle_profiler_overlay.run_if(input_just_pressed(PROFILER_TOGGLE_KEY)),
+                    update_profiler_overlay,
+                ),
+            );
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Phase {
+    Input,
+    Logic,
+    Ui,
+    Render,
+}
+
+impl Phase {
+    const ALL: [Phase; 4] = [Phase::Input, Phase::Logic, Phase::Ui, Phase::Render];
+
+    fn name(self) -> &'static str {
+        match self {
+            Phase::Input => "Input",
+            Phase::Logic => "Logic",
+            Phase::Ui => "UI",
+            Phase::Render => "Render",
+        }
+    }
+}
+
+/// Runs at the start of the phase, and so the end of the previous one.
+#[derive(ScheduleLabel, Clone, Debug, PartialEq, Eq, Hash)]
+struct ProfileMark(Phase);
+
+#[derive(Resource, Default)]
+struct FrameTimings {
+    last_mark: Option<(Phase, Instant)>,
+    frame_start: Option<Instant>,
+    /// The smoothed milliseconds spent in each phase, in [`Phase::ALL`] order.
+    average_ms: [f32; 4],
+    /// The longest frame since the overlay last refreshed.
+    max_frame_ms: f32,
+    since_refresh: Duration,
+}
+
+fn mark(phase: Phase) -> impl FnMut(ResMut<FrameTimings>) {
+    move |mut timings| {
+        let now = Instant::now();
+
+        if let Some((last, at)) = timings.last_mark {
+            let ms = now.duration_since(at).as_secs_f32() * 1000.0;
+            let average = &mut timings.average_ms[last as usize];
+            *average += (ms - *average) * PROFILER_SMOOTHING;
+        }
+
+        // Every frame starts with input.
+        if phase == Phase::Input {
+            if let Some(start) = timings.frame_start {
+                let frame_ms = now.duration_since(start).as_secs_f32() * 1000.0;
+                timings.max_frame_ms = timings.max_frame_ms.max(frame_ms);
+            }
+            timings.frame_start = Some(now);
+        }
+
+        timings.last_mark = Some((phase, now));
+    }
+}
+
+#[derive(Component)]
+struct ProfilerOverlay;
+
+fn spawn_profiler_overlay(mut commands: Commands, style: Res<Style>) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(24.0),
+            left: Val::Px(4.0),
+            padding: UiRect::all(Val::Px(4.0)),
+            ..default()
+        },
+        Text::default(),
+        TextFont {
+            font_size: 16.0,
+            ..default()
+        },
+        TextColor(style.text_color),
+        BackgroundColor(style.background_color),
+        GlobalZIndex(PROFILER_Z_INDEX),
+        Visibility::Hidden,
+        Pickable::IGNORE,
+        ProfilerOverlay,
+    ));
+}
+
+fn toggle_profiler_overlay(mut overlay: Query<&mut Visibility, With<ProfilerOverlay>>) {
+    for mut visibility in overlay.iter_mut() {
+        visibility.toggle_visible_hidden();
+    }
+}
+
+fn update_profiler_overlay(
+    mut timings: ResMut<FrameTimings>,
+    mut overlay: Query<(&mut Text, &Visibility), With<ProfilerOverlay>>,
+    time: Res<Time<Real>>,
+    entities: &Entities,
+    archetypes: &Archetypes,
+) {
+    timings.since_refresh += time.delta();
+    if timings.since_refresh < PROFILER_REFRESH_INTERVAL {
+        return;
+    }
+    timings.since_refresh = Duration::ZERO;
+
+    for (mut text, visibility) in overlay.iter_mut() {
+        if *visibility == Visibility::Hidden {
+            continue;
+        }
+
+        let mut lines: Vec<String> = Phase::ALL
+            .iter()
+            .zip(timings.average_ms)
+            .map(|(phase, ms)| format!("{:<8}{ms:>6.2} ms", phase.name()))
+            .collect();
+        lines.push(format!(
+            "{:<8}{:>6.2} ms",
+            "Frame",
+            timings.average_ms.iter().sum::<f32>()
+        ));
+        lines.push(format!("{:<8}{:>6.2} ms", "Max", timings.max_frame_ms));
+        lines.push(format!("Entities:   {}", entities.len()));
+        lines.push(format!("Archetypes: {}", archetypes.len()));
+
+        text.0 = lines.join("\n");
+    }
+
+    timings.max_frame_ms = 0.0;
+}
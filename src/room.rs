@@ -1,10 +1,13 @@
+use crate::game::{RoomPrefetch, hex_distance};
+use crate::generate_map::{Biome, GenerationSettings};
 use crate::prelude::*;
 use bevy::prelude::*;
 use bevy_ecs_tilemap::helpers::hex_grid::axial::AxialPos;
 use bevy_ecs_tilemap::prelude::*;
-use rand::Rng;
+use rand::{Rng, SeedableRng};
 use serde::{Deserialize, Serialize};
 use std::ops::Range;
+use strum::{Display, EnumIter, IntoEnumIterator};
 
 pub const ROOM_RADIUS: u32 = 3;
 // + 1 for center and + 2 for doors
@@ -19,12 +22,24 @@ pub const ROOM_CENTER: TilePos = TilePos {
 };
 
 pub const ROOM_TILE_LAYER: f32 = -1.0;
+/// Above the floor, under everyone standing on it.
+pub const PROP_LAYER: f32 = 0.5;
 
 #[derive(Component, Debug, Clone, Serialize, Deserialize)]
 pub struct RoomInfo {
     pub cleared: bool,
     pub r_type: RoomType,
     pub rng_seed: u64,
+    /// Whether the room shows up on the map, see [`FogPlugin`](crate::game::FogPlugin).
+    pub discovered: bool,
+    /// How many more rooms the party can walk into before the beaten guardian returns.
+    /// Only set with `manual_pillar_pickup`, while the pillar is left lying in the room,
+    /// see [`PillarPickupPlugin`](crate::game::PillarPickupPlugin).
+    #[serde(default)]
+    pub guardian_respawn: Option<u32>,
+    /// Whether the room's chest has been opened, see [`ChestsPlugin`](crate::game::ChestsPlugin).
+    #[serde(default)]
+    pub chest_opened: bool,
 }
 
 impl RoomInfo {
@@ -33,8 +48,16 @@ impl RoomInfo {
             cleared: false,
             r_type,
             rng_seed,
+            discovered: false,
+            guardian_respawn: None,
+            chest_opened: false,
         }
     }
+
+    /// Whether the room has a pillar that hasn't been collected yet.
+    pub fn has_pillar(&self) -> bool {
+        self.r_type == RoomType::Pillar && (!self.cleared || self.guardian_respawn.is_some())
+    }
 }
 
 #[derive(Debug, Hash, PartialEq, Eq, Clone, Serialize, Deserialize)]
@@ -54,33 +77,273 @@ pub enum RoomType {
     /// will trigger on entrance
     Pit(u32),
     /// A room that grants an item upone entry.
-    /// Stores the item that is inside the room,
-    /// zero
+    /// Stores the item that is inside the room.
     ///
     /// When cleared, the item is automatically collected
     /// thus later visits will not grant the item again.
-    ///
-    /// TODO: Replace the `()` with the `Item` type when
-    /// that is created.
     Item(Item),
+    /// A room with a lights out puzzle on its floor, see [`PuzzlePlugin`](crate::game::PuzzlePlugin).
+    /// Solving it wins an item, picked by the room's seed.
+    ///
+    /// When cleared, the puzzle was solved or skipped and isn't played again.
+    Puzzle,
+    /// A room with a shrine taking offerings of health or gold, see
+    /// [`ShrinePlugin`](crate::game::ShrinePlugin).
+    /// The offering is rewarded on the room's event rng.
+    ///
+    /// When cleared, the shrine was left and takes no more offerings.
+    Shrine,
+    /// A room with stairs down to the next floor, see [`StairsPlugin`](crate::game::StairsPlugin).
+    ///
+    /// Like the entrance it is never cleared, so the party can always come back to take them.
+    Stairs,
     /// The entrance room, with nothing interesting
     ///
     /// Also acts as the exit once you have collected all
     /// nessesary parts
     Entrance,
+    /// A room holding one of the pillars, guarded by a boss.
+    /// The guardian is picked by the room's seed, see [`ActorName::guardian`].
+    ///
+    /// The pillar is only collected once the guardian is beaten,
+    /// and when cleared the guardian is spawned dead.
     Pillar,
 }
 
 impl RoomType {
-    pub fn from_rng(rng: &mut impl Rng) -> RoomType {
-        let val = rng.random_range(0..3);
-
-        match val {
-            0 => RoomType::EmptyRoom,
-            1 => RoomType::Combat(ActorName::get_enemies(rng)),
-            2 => RoomType::Pit(rng.random_range(0..21)),
-            //3 => RoomType::Item(Item::get_rand_item(rng)),
-            _ => unreachable!(),
+    /// A random room, `depth` rooms away from the entrance.
+    pub fn from_rng(rng: &mut impl Rng, settings: &GenerationSettings, depth: u32) -> RoomType {
+        let kind = settings.rooms.roll(rng);
+        RoomType::from_kind(kind, rng, settings, depth, None)
+    }
+
+    /// A random room of the kind, `depth` rooms away from the entrance,
+    /// with the enemies of the biome it is in.
+    pub fn from_kind(
+        kind: RoomKind,
+        rng: &mut impl Rng,
+        settings: &GenerationSettings,
+        depth: u32,
+        biome: Option<Biome>,
+    ) -> RoomType {
+        let difficulty = settings.difficulty;
+        let encounters = &settings.encounters;
+
+        match kind {
+            RoomKind::Empty => RoomType::EmptyRoom,
+            RoomKind::Combat => {
+                let budget = encounters.budget(depth, difficulty);
+                RoomType::Combat(encounters.roll_enemies(rng, budget, biome))
+            }
+            RoomKind::Pit => RoomType::Pit(rng.random_range(difficulty.pit_damage())),
+            RoomKind::Item => RoomType::Item(Item::get_rand_item(rng)),
+            RoomKind::Puzzle => RoomType::Puzzle,
+            RoomKind::Shrine => RoomType::Shrine,
+        }
+    }
+
+    /// Whether entering the room starts a fight.
+    pub fn has_enemies(&self) -> bool {
+        matches!(self, RoomType::Combat(_) | RoomType::Pillar)
+    }
+
+    /// The floor tiles the room is paved with, mostly the first and now and then any of them.
+    pub fn floor_tiles(&self) -> &'static [u32] {
+        match self {
+            RoomType::Entrance => &[5, 4],
+            RoomType::Pit(_) => &[1, 0],
+            RoomType::Pillar => &[2, 0],
+            RoomType::Puzzle => &[4, 0],
+            RoomType::Shrine => &[3, 0],
+            RoomType::Stairs => &[5, 0],
+            RoomType::EmptyRoom | RoomType::Combat(_) | RoomType::Item(_) => &[0, 3],
+        }
+    }
+
+    /// Whether the tile is under the pool of water in the middle of the room, if it has one.
+    pub fn is_water(&self, pos: &TilePos) -> bool {
+        matches!(self, RoomType::Shrine) && hex_distance(pos, &ROOM_CENTER) <= WATER_RADIUS
+    }
+
+    /// The props that can be scattered around the edge of the room.
+    pub fn props(&self) -> &'static [Prop] {
+        match self {
+            RoomType::Entrance => &[Prop::Torch],
+            RoomType::Pit(_) => &[Prop::Bones, Prop::Rock],
+            RoomType::Pillar => &[Prop::Torch, Prop::Bones],
+            RoomType::Puzzle => &[Prop::Rock, Prop::Torch],
+            RoomType::Shrine => &[Prop::Torch, Prop::Rock],
+            RoomType::Stairs => &[Prop::Torch],
+            RoomType::EmptyRoom | RoomType::Combat(_) | RoomType::Item(_) => {
+                &[Prop::Bones, Prop::Rock, Prop::Torch]
+            }
+        }
+    }
+
+    /// The kind of random room this is, if it is one.
+    pub fn kind(&self) -> Option<RoomKind> {
+        match self {
+            RoomType::EmptyRoom => Some(RoomKind::Empty),
+            RoomType::Combat(_) => Some(RoomKind::Combat),
+            RoomType::Pit(_) => Some(RoomKind::Pit),
+            RoomType::Item(_) => Some(RoomKind::Item),
+            RoomType::Puzzle => Some(RoomKind::Puzzle),
+            RoomType::Shrine => Some(RoomKind::Shrine),
+            RoomType::Entrance | RoomType::Pillar | RoomType::Stairs => None,
+        }
+    }
+}
+
+/// The kinds of room that are randomly placed between the pillars.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RoomKind {
+    Empty,
+    Combat,
+    Pit,
+    Item,
+    Puzzle,
+    Shrine,
+}
+
+/// How likely each kind of random room is,
+/// and how many of some kinds every map is guaranteed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RoomWeights {
+    pub empty: u32,
+    pub combat: u32,
+    pub pit: u32,
+    pub item: u32,
+    pub puzzle: u32,
+    pub shrine: u32,
+    pub min_empty: usize,
+    pub min_item: usize,
+}
+
+impl Default for RoomWeights {
+    fn default() -> Self {
+        Self {
+            empty: 1,
+            combat: 1,
+            pit: 1,
+            item: 1,
+            puzzle: 1,
+            shrine: 1,
+            min_empty: 2,
+            min_item: 3,
+        }
+    }
+}
+
+/// The mixes of rooms a new game can be started with, picked in the New Game menu.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Display, EnumIter)]
+pub enum RoomMix {
+    /// Every kind of room as likely as any other.
+    #[default]
+    Balanced,
+    /// More fights and pits, fewer places to rest.
+    Dangerous,
+    /// Fewer fights and no pits, with more items lying around.
+    Peaceful,
+    /// More puzzles and shrines.
+    Curious,
+}
+
+impl RoomMix {
+    /// The mix after this one, wrapping back to the first.
+    pub fn next(self) -> Self {
+        Self::iter()
+            .cycle()
+            .skip_while(|mix| *mix != self)
+            .nth(1)
+            .unwrap()
+    }
+
+    pub fn weights(self) -> RoomWeights {
+        let balanced = RoomWeights::default();
+        match self {
+            RoomMix::Balanced => balanced,
+            RoomMix::Dangerous => RoomWeights {
+                combat: 3,
+                pit: 2,
+                min_empty: 1,
+                ..balanced
+            },
+            RoomMix::Peaceful => RoomWeights {
+                empty: 2,
+                pit: 0,
+                item: 2,
+                ..balanced
+            },
+            RoomMix::Curious => RoomWeights {
+                puzzle: 3,
+                shrine: 3,
+                ..balanced
+            },
+        }
+    }
+}
+
+impl RoomWeights {
+    /// Picks a kind of room, each as likely as its weight.
+    pub fn roll(&self, rng: &mut impl Rng) -> RoomKind {
+        let weights = [
+            (RoomKind::Empty, self.empty),
+            (RoomKind::Combat, self.combat),
+            (RoomKind::Pit, self.pit),
+            (RoomKind::Item, self.item),
+            (RoomKind::Puzzle, self.puzzle),
+            (RoomKind::Shrine, self.shrine),
+        ];
+        let total = self.total();
+        assert!(total > 0, "At least one room kind needs a weight");
+
+        let mut roll = rng.random_range(0..total);
+        for (kind, weight) in weights {
+            if roll < weight {
+                return kind;
+            }
+            roll -= weight;
+        }
+        unreachable!()
+    }
+
+    /// The weights of every kind of room added up.
+    pub fn total(&self) -> u32 {
+        self.empty + self.combat + self.pit + self.item + self.puzzle + self.shrine
+    }
+
+    /// Turns random combat and pit rooms into item and empty rooms
+    /// until the map has at least the minimum of each.
+    pub fn ensure_minimums(&self, rng: &mut impl Rng, rooms: &mut [RoomType]) {
+        for (kind, min) in [
+            (RoomKind::Item, self.min_item),
+            (RoomKind::Empty, self.min_empty),
+        ] {
+            let mut count = rooms
+                .iter()
+                .filter(|room| room.kind() == Some(kind))
+                .count();
+            while count < min {
+                let replaceable: Vec<usize> = rooms
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, room)| {
+                        matches!(room.kind(), Some(RoomKind::Combat | RoomKind::Pit))
+                    })
+                    .map(|(index, _)| index)
+                    .collect();
+                if replaceable.is_empty() {
+                    break;
+                }
+
+                let index = replaceable[rng.random_range(0..replaceable.len())];
+                rooms[index] = match kind {
+                    RoomKind::Item => RoomType::Item(Item::get_rand_item(rng)),
+                    _ => RoomType::EmptyRoom,
+                };
+                count += 1;
+            }
         }
     }
 }
@@ -95,15 +358,129 @@ pub struct CurrentRoom;
 #[derive(Component)]
 pub struct InRoom;
 
+/// Marks the item lying in the room, until it is picked up.
+#[derive(Component)]
+pub struct RoomItem;
+
 /// Marker to indicate the room hex tiles
 #[derive(Component)]
 pub struct RoomTile;
 
+/// Marks the room tiles under water,
+/// which ripple while [`AnimateTiles`](crate::tile::AnimateTiles) is on.
+#[derive(Component)]
+pub struct WaterTile;
+
 /// Marker to indicate the room tile map
 #[derive(Component)]
 pub struct RoomTilemap;
 
-pub fn spawn_room(mut commands: Commands, tile_texture: Res<HexTileImage>) {
+/// Something lying around a room for show, which can't be interacted with.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Prop {
+    Bones,
+    Rock,
+    Torch,
+}
+
+impl Prop {
+    pub fn color(&self) -> Color {
+        match self {
+            Prop::Bones => Color::srgb_u8(0xe0, 0xde, 0xf4),
+            Prop::Rock => Color::srgb_u8(0x6e, 0x6a, 0x86),
+            Prop::Torch => Color::srgb_u8(0xf6, 0xc1, 0x77),
+        }
+    }
+
+    pub fn size(&self) -> Vec2 {
+        match self {
+            Prop::Bones => Vec2::new(16.0, 6.0),
+            Prop::Rock => Vec2::new(12.0, 10.0),
+            Prop::Torch => Vec2::new(6.0, 20.0),
+        }
+    }
+}
+
+/// Mixed into a room's seed for how it looks, so its looks don't change its events.
+const DECOR_RNG_STREAM: u64 = 0xdec0_7a7e_f100_5eed;
+/// One in this many floor tiles is picked from all of the room's floor tiles.
+const FLOOR_ACCENT_CHANCE: u32 = 6;
+/// How dark the shading of a floor tile can be.
+const MIN_FLOOR_SHADE: f32 = 0.85;
+/// The most props a room can have.
+const MAX_PROPS: u32 = 3;
+/// How far the pool of water reaches from the middle of the room.
+const WATER_RADIUS: u32 = 1;
+/// The tint of the floor under water.
+pub const WATER_COLOR: Color = Color::srgb(0.35, 0.55, 0.95);
+
+/// The texture and shade of a floor tile in the room, the same every time the room is entered.
+pub fn floor_tile(info: &RoomInfo, pos: &TilePos) -> (TileTextureIndex, TileColor) {
+    let seed = info.rng_seed ^ DECOR_RNG_STREAM ^ (((pos.x as u64) << 32) | pos.y as u64);
+    let mut rng = RandomSource::seed_from_u64(seed);
+
+    let tiles = info.r_type.floor_tiles();
+    let index = if rng.random_ratio(1, FLOOR_ACCENT_CHANCE) {
+        tiles[rng.random_range(0..tiles.len())]
+    } else {
+        tiles[0]
+    };
+    let shade = rng.random_range(MIN_FLOOR_SHADE..=1.0);
+
+    (
+        TileTextureIndex(FLOOR_TILE_VARIENTS.start + index),
+        TileColor(Color::srgb(shade, shade, shade)),
+    )
+}
+
+/// Paves the room tiles with the current room's floor, and floods its pool if it has one.
+pub fn paint_room_floor(
+    mut commands: Commands,
+    info: Single<&RoomInfo, With<CurrentRoom>>,
+    mut tiles: Query<(Entity, &TilePos, &mut TileTextureIndex, &mut TileColor), With<RoomTile>>,
+) {
+    for (entity, pos, mut texture, mut color) in tiles.iter_mut() {
+        (*texture, *color) = floor_tile(&info, pos);
+
+        match info.r_type.is_water(pos) {
+            true => {
+                *color = TileColor(WATER_COLOR);
+                commands.entity(entity).insert(WaterTile);
+            }
+            false => {
+                commands.entity(entity).remove::<WaterTile>();
+            }
+        }
+    }
+}
+
+/// The tiles around the edge of the room, out of the way of everyone in it.
+/// The ones in front of a door are left clear, so no prop stands in a doorway.
+fn edge_offsets() -> Vec<IVec2> {
+    let radius = ROOM_RADIUS as i32;
+    let doors: Vec<TilePos> = EntranceDirection::ALL
+        .iter()
+        .map(|dir| dir.door_offset(&ROOM_CENTER, ROOM_RADIUS, HEX_COORD_SYSTEM))
+        .collect();
+    (-radius..=radius)
+        .flat_map(|q| (-radius..=radius).map(move |r| AxialPos::new(q, r)))
+        .filter(|pos| pos.magnitude() == radius)
+        .map(|pos| IVec2::new(pos.q, pos.r))
+        .filter(|offset| {
+            let tile = room_tile(*offset);
+            doors.iter().all(|door| hex_distance(&tile, door) > 1)
+        })
+        .collect()
+}
+
+/// The tile `offset` away from [`ROOM_CENTER`].
+fn room_tile(offset: IVec2) -> TilePos {
+    (IVec2::new(ROOM_CENTER.x as i32, ROOM_CENTER.y as i32) + offset)
+        .as_uvec2()
+        .into()
+}
+
+pub fn spawn_room(mut commands: Commands, tile_texture: Res<HexTileImage>, config: Res<Config>) {
     let tilemap_entity = commands.spawn((Visibility::Visible,)).id();
 
     let mut tile_storage = TileStorage::empty(ROOM_SIZE);
@@ -137,7 +514,7 @@ pub fn spawn_room(mut commands: Commands, tile_texture: Res<HexTileImage>) {
         RoomTilemap,
         Pickable::default(),
         TilemapBundle {
-            grid_size: TILE_SIZE.into(),
+            grid_size: TilemapGridSize::new(config.tile_size.x, config.tile_size.y),
             map_type: TilemapType::Hexagon(HexCoordSystem::Row),
             size: ROOM_SIZE,
             storage: tile_storage,
@@ -153,80 +530,223 @@ pub fn spawn_room(mut commands: Commands, tile_texture: Res<HexTileImage>) {
 
 pub const ENEMY_POSITIONS: [IVec2; 3] = [IVec2::new(1, 1), IVec2::new(-1, 2), IVec2::new(-2, 1)];
 pub const ITEM_POSITION: IVec2 = IVec2::new(1, 1);
+pub const ITEM_SIZE: Vec2 = Vec2::new(16.0, 24.0);
 
-pub fn spawn_room_entities(
-    mut commands: Commands,
-    info: Single<&RoomInfo, With<CurrentRoom>>,
-    asset_server: Res<AssetServer>,
-    tilemap: Single<
-        (
+/// Where the room tilemap is, for placing things on its tiles.
+#[derive(Clone)]
+pub struct RoomLayout {
+    pub map_size: TilemapSize,
+    pub grid_size: TilemapGridSize,
+    pub tile_size: TilemapTileSize,
+    pub map_type: TilemapType,
+    pub anchor: TilemapAnchor,
+}
+
+impl RoomLayout {
+    pub fn new(
+        (map_size, grid_size, tile_size, map_type, anchor): (
             &TilemapSize,
             &TilemapGridSize,
             &TilemapTileSize,
             &TilemapType,
             &TilemapAnchor,
         ),
-        With<RoomTilemap>,
-    >,
-) {
-    let (map_size, grid_size, tile_size, map_type, map_anchor) = *tilemap;
+    ) -> Self {
+        Self {
+            map_size: *map_size,
+            grid_size: *grid_size,
+            tile_size: *tile_size,
+            map_type: *map_type,
+            anchor: *anchor,
+        }
+    }
 
-    let center_tile_pos = UVec2 {
-        x: map_size.x / 2,
-        y: map_size.y / 2,
-    };
+    /// The world position of the tile `offset` away from the center of the room.
+    pub fn world_pos(&self, offset: IVec2) -> Vec2 {
+        let center_tile_pos = UVec2 {
+            x: self.map_size.x / 2,
+            y: self.map_size.y / 2,
+        };
+        let pos: TilePos = (center_tile_pos.as_ivec2() + offset).as_uvec2().into();
 
-    let RoomInfo {
-        cleared, r_type, ..
-    } = *info;
+        pos.center_in_world(
+            &self.map_size,
+            &self.grid_size,
+            &self.tile_size,
+            &self.map_type,
+            &self.anchor,
+        )
+    }
+}
 
-    use RoomType as R;
-    match &r_type {
-        R::EmptyRoom => {}
-        R::Entrance => {}
-        R::Combat(enemies) => {
-            for (name, pos_offset) in enemies.iter().zip(ENEMY_POSITIONS.into_iter()) {
-                let actor_pos: TilePos =
-                    (center_tile_pos.as_ivec2() + pos_offset).as_uvec2().into();
+/// The entities that fill a room, made before they are spawned
+/// so rooms can be prepared ahead of time.
+pub struct PreparedRoom {
+    pub enemies: Vec<ActorBundle>,
+    pub item: Option<(Item, Transform)>,
+    pub props: Vec<(Prop, Transform)>,
+}
+
+impl PreparedRoom {
+    pub fn new(
+        info: &RoomInfo,
+        asset_server: &AssetServer,
+        layout: &RoomLayout,
+        difficulty: Difficulty,
+    ) -> Self {
+        let RoomInfo {
+            cleared,
+            r_type,
+            rng_seed,
+            ..
+        } = info;
+
+        let mut enemies = Vec::new();
+        let mut room_item = None;
 
-                let world_pos =
-                    actor_pos.center_in_world(map_size, grid_size, tile_size, map_type, map_anchor);
+        use RoomType as R;
+        match r_type {
+            R::EmptyRoom => {}
+            R::Entrance => {}
+            R::Combat(names) => {
+                for (name, pos_offset) in names.iter().zip(ENEMY_POSITIONS) {
+                    let world_pos = layout.world_pos(pos_offset);
+                    let transform = Transform::from_xyz(world_pos.x, world_pos.y, ACTOR_LAYER);
 
+                    enemies.push(ActorBundle::from_name(
+                        asset_server,
+                        *name,
+                        Team::Enemy,
+                        transform,
+                        !cleared,
+                        difficulty,
+                    ));
+                }
+            }
+            R::Item(item) => {
+                if !cleared {
+                    let world_pos = layout.world_pos(ITEM_POSITION);
+                    let transform = Transform::from_xyz(world_pos.x, world_pos.y, ACTOR_LAYER);
+                    room_item = Some((*item, transform));
+                }
+            }
+            R::Pit(_) => {}
+            R::Puzzle | R::Shrine | R::Stairs => {}
+            R::Pillar => {
+                let world_pos = layout.world_pos(ENEMY_POSITIONS[0]);
                 let transform = Transform::from_xyz(world_pos.x, world_pos.y, ACTOR_LAYER);
 
-                commands.spawn((
-                    InRoom,
-                    ActorBundle::from_name(&asset_server, *name, Team::Enemy, transform, !cleared),
-                    Pickable::default(),
-                    Visibility::Visible,
+                enemies.push(ActorBundle::from_name(
+                    asset_server,
+                    ActorName::guardian(*rng_seed),
+                    Team::Enemy,
+                    transform,
+                    !cleared,
+                    difficulty,
                 ));
             }
         }
-        R::Item(item) => match item {
-            Item::HealingPotion => {}
 
-            Item::VisionPotion => {}
-        },
-        R::Pit(damage) => {}
-        R::Pillar => {}
+        let mut rng = RandomSource::seed_from_u64(rng_seed ^ DECOR_RNG_STREAM);
+        let choices = r_type.props();
+        let mut edge = edge_offsets();
+        let props = (0..rng.random_range(1..=MAX_PROPS))
+            .map(|_| {
+                let prop = choices[rng.random_range(0..choices.len())];
+                let offset = edge.swap_remove(rng.random_range(0..edge.len()));
+                let world_pos = layout.world_pos(offset);
+                (
+                    prop,
+                    Transform::from_xyz(world_pos.x, world_pos.y, PROP_LAYER),
+                )
+            })
+            .collect();
+
+        Self {
+            enemies,
+            item: room_item,
+            props,
+        }
     }
+
+    pub fn spawn(self, commands: &mut Commands) {
+        for (slot, enemy) in self.enemies.into_iter().enumerate() {
+            commands.spawn((
+                InRoom,
+                enemy,
+                EnemySlot(slot),
+                Pickable::default(),
+                Visibility::Visible,
+            ));
+        }
+
+        for (prop, transform) in self.props {
+            commands.spawn((
+                InRoom,
+                prop,
+                Sprite::from_color(prop.color(), prop.size()),
+                transform,
+                Pickable::IGNORE,
+            ));
+        }
+
+        if let Some((item, transform)) = self.item {
+            commands.spawn((
+                InRoom,
+                RoomItem,
+                Sprite::from_color(item.color(), ITEM_SIZE),
+                transform,
+            ));
+        }
+    }
+}
+
+pub fn spawn_room_entities(
+    mut commands: Commands,
+    info: Single<(Entity, &RoomInfo), With<CurrentRoom>>,
+    asset_server: Res<AssetServer>,
+    difficulty: Res<Difficulty>,
+    mut prefetch: ResMut<RoomPrefetch>,
+    tilemap: Single<
+        (
+            &TilemapSize,
+            &TilemapGridSize,
+            &TilemapTileSize,
+            &TilemapType,
+            &TilemapAnchor,
+        ),
+        With<RoomTilemap>,
+    >,
+) {
+    let (room, info) = *info;
+
+    prefetch
+        .take(room)
+        .unwrap_or_else(|| {
+            PreparedRoom::new(info, &asset_server, &RoomLayout::new(*tilemap), *difficulty)
+        })
+        .spawn(&mut commands);
 }
 
 /// Should be run after the room
 pub fn mark_room_cleared(mut info: Single<&mut RoomInfo, With<CurrentRoom>>) {
     match info.r_type {
-        RoomType::Entrance => {}
+        RoomType::Entrance | RoomType::Stairs => {}
         _ => info.cleared = true,
     }
 }
 
-#[derive(Component, Debug, Hash, PartialEq, Eq, Clone, Copy)]
+#[derive(Component, Debug, Hash, PartialEq, Eq, Clone, Copy, Display)]
 pub enum EntranceDirection {
+    #[strum(to_string = "North East")]
     NorthEast,
     North,
+    #[strum(to_string = "North West")]
     NorthWest,
+    #[strum(to_string = "South West")]
     SouthWest,
     South,
+    #[strum(to_string = "South East")]
     SouthEast,
 }
 
@@ -251,6 +771,19 @@ impl EntranceDirection {
         }
     }
 
+    /// Which way the door is on screen, from the room's center.
+    pub fn screen_direction(&self) -> Vec2 {
+        let angle = match self {
+            EntranceDirection::NorthEast => 30.0_f32,
+            EntranceDirection::North => 90.0,
+            EntranceDirection::NorthWest => 150.0,
+            EntranceDirection::SouthWest => 210.0,
+            EntranceDirection::South => 270.0,
+            EntranceDirection::SouthEast => 330.0,
+        };
+        Vec2::from_angle(angle.to_radians())
+    }
+
     pub fn door_offset(
         &self,
         origin: &TilePos,
@@ -273,3 +806,133 @@ impl EntranceDirection {
         pos.as_tile_pos_given_coord_system(coord_sys)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn room_kinds_follow_their_weights() {
+        let weights = RoomWeights {
+            empty: 0,
+            combat: 3,
+            pit: 1,
+            item: 0,
+            puzzle: 0,
+            shrine: 0,
+            ..default()
+        };
+        let mut rng = RandomSource::seed_from_u64(0x3ea7);
+
+        let rolls: Vec<RoomKind> = (0..4000).map(|_| weights.roll(&mut rng)).collect();
+        let combat = rolls
+            .iter()
+            .filter(|kind| **kind == RoomKind::Combat)
+            .count();
+        let pit = rolls.iter().filter(|kind| **kind == RoomKind::Pit).count();
+
+        assert_eq!(combat + pit, rolls.len());
+        assert!((2800..3200).contains(&combat), "{combat} combat rooms");
+    }
+
+    #[test]
+    fn pillars_are_guarded_by_stronger_enemies() {
+        assert!(RoomType::Pillar.has_enemies());
+        assert!(!RoomType::Item(Item::HealingPotion).has_enemies());
+
+        for seed in 0..ActorName::GUARDIANS.len() as u64 * 2 {
+            let guardian = ActorName::guardian(seed);
+            let kin = guardian.sprite_name();
+            assert!(guardian.is_guardian() && !kin.is_guardian());
+
+            assert!(
+                HealthBundle::from_name(guardian).health.max()
+                    > HealthBundle::from_name(kin).health.max()
+            );
+            assert!(
+                Attack::from_name(guardian).damage().start > Attack::from_name(kin).damage().start
+            );
+            assert!(SpecialAction::from_name(guardian).is_some());
+        }
+    }
+
+    #[test]
+    fn every_map_gets_the_minimum_rooms() {
+        let weights = RoomWeights {
+            min_empty: 2,
+            min_item: 3,
+            ..default()
+        };
+        let mut rng = RandomSource::seed_from_u64(7);
+        let mut rooms = vec![RoomType::Pit(5); 10];
+        rooms.push(RoomType::Pillar);
+
+        weights.ensure_minimums(&mut rng, &mut rooms);
+
+        let count = |kind| {
+            rooms
+                .iter()
+                .filter(|room| room.kind() == Some(kind))
+                .count()
+        };
+        assert_eq!(count(RoomKind::Item), 3);
+        assert_eq!(count(RoomKind::Empty), 2);
+        assert_eq!(count(RoomKind::Pit), 5);
+        assert!(rooms.contains(&RoomType::Pillar));
+
+        // Maps too small for the minimums keep what they can.
+        let mut rooms = vec![RoomType::Combat([ActorName::Goblin].into())];
+        weights.ensure_minimums(&mut rng, &mut rooms);
+        assert_eq!(rooms.len(), 1);
+        assert_eq!(rooms[0].kind(), Some(RoomKind::Item));
+    }
+
+    #[test]
+    fn floors_are_themed_and_the_same_each_visit() {
+        let pit = RoomInfo::from_type(RoomType::Pit(5), 0xf100);
+        let positions: Vec<TilePos> = (0..ROOM_SIZE.x)
+            .flat_map(|x| (0..ROOM_SIZE.y).map(move |y| TilePos::new(x, y)))
+            .collect();
+
+        let paint = || -> Vec<(TileTextureIndex, Color)> {
+            positions
+                .iter()
+                .map(|pos| {
+                    let (texture, color) = floor_tile(&pit, pos);
+                    (texture, color.0)
+                })
+                .collect()
+        };
+        let floor = paint();
+        assert_eq!(floor, paint());
+
+        let themed = RoomType::Pit(5).floor_tiles();
+        assert!(
+            floor
+                .iter()
+                .all(|(texture, _)| themed.contains(&(texture.0 - FLOOR_TILE_VARIENTS.start)))
+        );
+        assert!(floor.iter().any(|(texture, _)| texture.0 != floor[0].0.0));
+    }
+
+    #[test]
+    fn only_shrines_have_a_pool() {
+        assert!(RoomType::Shrine.is_water(&ROOM_CENTER));
+        assert!(!RoomType::Shrine.is_water(&room_tile(IVec2::new(ROOM_RADIUS as i32, 0))));
+        assert!(!RoomType::EmptyRoom.is_water(&ROOM_CENTER));
+    }
+
+    #[test]
+    fn props_keep_out_of_the_doorways() {
+        let edge = edge_offsets();
+        assert!(edge.len() >= MAX_PROPS as usize);
+        for dir in EntranceDirection::ALL {
+            let door = dir.door_offset(&ROOM_CENTER, ROOM_RADIUS, HEX_COORD_SYSTEM);
+            assert!(
+                edge.iter()
+                    .all(|offset| hex_distance(&room_tile(*offset), &door) > 1),
+                "a prop can block the {dir} door"
+            );
+        }
+    }
+}
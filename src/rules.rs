@@ -0,0 +1,358 @@
+//! The numbers behind a fight: hitting, blocking, crits, special moves and their energy,
+//! healing and fleeing.
+//!
+//! Everything here works on plain values and a random source rather than components,
+//! so combat can be balanced and checked without a world.
+
+use rand::Rng;
+use std::num::NonZero;
+use std::ops::Range;
+
+/// How much more damage the Warrior's Crushing Blow does.
+pub const CRUSHING_BLOW_MULTIPLIER: f32 = 1.2;
+/// How much of a hit each foe takes from a guardian's Sweeping Strike.
+pub const SWEEP_MULTIPLIER: f32 = 0.6;
+/// How much harder a Construct's charged blow hits, for the turns it takes to land.
+pub const CHARGE_MULTIPLIER: f32 = 2.0;
+/// How much of an area attack's damage each foe takes.
+pub const AREA_MULTIPLIER: f32 = 0.5;
+/// How often a single target attack lands a critical hit.
+pub const CRIT_CHANCE: f32 = 0.1;
+/// How much more damage a critical hit does.
+pub const CRIT_MULTIPLIER: f32 = 1.5;
+/// The energy everyone starts a fight with, and the most they can hold.
+pub const MAX_ENERGY: u32 = 3;
+/// The energy regained at the start of each turn.
+pub const ENERGY_PER_TURN: u32 = 1;
+/// The energy a special move costs.
+pub const SPECIAL_COST: u32 = 2;
+/// How much the Priestess heals for.
+pub const HEAL_RANGE: Range<u32> = 15..30;
+/// How often a guardian uses their special move instead of attacking.
+pub const GUARDIAN_SPECIAL_CHANCE: f64 = 0.35;
+/// How often a Construct winds up a charged blow instead of attacking.
+pub const CHARGE_UP_CHANCE: f64 = 0.3;
+/// The chance of fleeing never drops below or rises above these.
+pub const MIN_FLEE_CHANCE: f64 = 0.2;
+pub const MAX_FLEE_CHANCE: f64 = 0.9;
+
+/// An attack about to be made.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Strike {
+    pub damage: Range<u32>,
+    pub hit_chance: f32,
+    /// What the rolled damage is scaled by, for special moves.
+    pub multiplier: f32,
+    pub crit_chance: f32,
+}
+
+/// How the one being attacked can stop it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Guard {
+    pub block_chance: f32,
+    pub defending: bool,
+}
+
+/// How an attack on one target turned out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Missed,
+    Blocked,
+    /// The damage before any shield takes its share.
+    Hit(u32),
+    /// A hit for [`CRIT_MULTIPLIER`] times the damage, already multiplied.
+    Crit(u32),
+}
+
+/// Rolls to hit, then for the damage. Hits that roll no damage count as misses.
+pub fn roll_damage(
+    rng: &mut impl Rng,
+    damage: &Range<u32>,
+    hit_chance: f32,
+) -> Option<NonZero<u32>> {
+    rng.random_bool(hit_chance as f64)
+        .then(|| rng.random_range(damage.clone()))
+        .and_then(NonZero::new)
+}
+
+/// The damage scaled by a special move's multiplier, rounded down.
+pub fn scale_damage(damage: u32, multiplier: f32) -> u32 {
+    (damage as f32 * multiplier) as u32
+}
+
+/// The chance to block, doubled while defending.
+/// Gear and boosts can add up past certain, so it is never more than 1.
+pub fn block_chance(block_chance: f32, defending: bool) -> f32 {
+    match defending {
+        true => block_chance * 2.0,
+        false => block_chance,
+    }
+    .clamp(0.0, 1.0)
+}
+
+pub fn roll_block(rng: &mut impl Rng, guard: Guard) -> bool {
+    rng.random_bool(block_chance(guard.block_chance, guard.defending).into())
+}
+
+pub fn roll_crit(rng: &mut impl Rng, crit_chance: f32) -> bool {
+    rng.random_bool(crit_chance.into())
+}
+
+/// Attacks one target, which gets to block if they have a guard up.
+pub fn resolve_strike(rng: &mut impl Rng, strike: &Strike, guard: Option<Guard>) -> Outcome {
+    let Some(damage) = roll_damage(rng, &strike.damage, strike.hit_chance) else {
+        return Outcome::Missed;
+    };
+    if guard.is_some_and(|guard| roll_block(rng, guard)) {
+        return Outcome::Blocked;
+    }
+
+    let damage = scale_damage(damage.get(), strike.multiplier);
+    match roll_crit(rng, strike.crit_chance) {
+        true => Outcome::Crit(scale_damage(damage, CRIT_MULTIPLIER)),
+        false => Outcome::Hit(damage),
+    }
+}
+
+/// The damage each foe takes from an area attack, which always does at least 1.
+pub fn area_damage(damage: u32) -> u32 {
+    scale_damage(damage, AREA_MULTIPLIER).max(1)
+}
+
+/// Attacks every foe at once, each taking the strike's share of the damage unless they block it.
+/// Every hit does at least 1, and none of them crit.
+/// Gives how it turned out for each of the `guards` in turn, or `None` if it missed them all.
+pub fn resolve_area_strike(
+    rng: &mut impl Rng,
+    strike: &Strike,
+    guards: &[Guard],
+) -> Option<Vec<Outcome>> {
+    let damage = roll_damage(rng, &strike.damage, strike.hit_chance)?;
+    let spread = scale_damage(damage.get(), strike.multiplier).max(1);
+
+    Some(
+        guards
+            .iter()
+            .map(|guard| match roll_block(rng, *guard) {
+                true => Outcome::Blocked,
+                false => Outcome::Hit(spread),
+            })
+            .collect(),
+    )
+}
+
+/// The energy after a turn starts, which never goes past [`MAX_ENERGY`].
+pub fn regain_energy(energy: u32) -> u32 {
+    (energy + ENERGY_PER_TURN).min(MAX_ENERGY)
+}
+
+/// The energy left after paying `cost`, or `None` if there isn't enough.
+pub fn spend_energy(energy: u32, cost: u32) -> Option<u32> {
+    energy.checked_sub(cost)
+}
+
+pub fn roll_heal(rng: &mut impl Rng) -> u32 {
+    rng.random_range(HEAL_RANGE)
+}
+
+/// The chance of getting away from a fight,
+/// from the average speed of the party and of the enemies.
+pub fn flee_chance(party_speed: f32, enemy_speed: f32) -> f64 {
+    let chance = party_speed / (party_speed + enemy_speed).max(1.0);
+    (chance as f64).clamp(MIN_FLEE_CHANCE, MAX_FLEE_CHANCE)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::RandomSource;
+    use rand::SeedableRng;
+
+    const TRIES: usize = 2000;
+
+    fn rng() -> RandomSource {
+        RandomSource::seed_from_u64(0x2a1e5)
+    }
+
+    #[test]
+    fn damage_stays_in_range() {
+        let mut rng = rng();
+        for (damage, hit_chance) in [(1..2, 1.0), (15..31, 0.8), (2000..4100, 0.5)] {
+            for _ in 0..TRIES {
+                if let Some(rolled) = roll_damage(&mut rng, &damage, hit_chance) {
+                    assert!(damage.contains(&rolled.get()));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn hit_chance_decides_how_often_attacks_land() {
+        let mut rng = rng();
+        let hits = |rng: &mut RandomSource, hit_chance| {
+            (0..TRIES)
+                .filter(|_| roll_damage(rng, &(10..20), hit_chance).is_some())
+                .count()
+        };
+
+        assert_eq!(hits(&mut rng, 0.0), 0);
+        assert_eq!(hits(&mut rng, 1.0), TRIES);
+        assert!(hits(&mut rng, 0.5).abs_diff(TRIES / 2) < TRIES / 10);
+        // Rolling no damage is as good as missing.
+        assert_eq!(roll_damage(&mut rng, &(0..1), 1.0), None);
+    }
+
+    #[test]
+    fn multipliers_round_down() {
+        assert_eq!(scale_damage(50, CRUSHING_BLOW_MULTIPLIER), 60);
+        assert_eq!(scale_damage(9, SWEEP_MULTIPLIER), 5);
+        assert_eq!(scale_damage(0, CRUSHING_BLOW_MULTIPLIER), 0);
+        assert_eq!(scale_damage(30, 1.0), 30);
+
+        assert_eq!(area_damage(30), 15);
+        assert_eq!(area_damage(1), 1);
+        assert_eq!(area_damage(0), 1);
+    }
+
+    #[test]
+    fn defending_doubles_block_chance() {
+        assert_eq!(block_chance(0.3, true), 0.6);
+        assert_eq!(block_chance(0.6, true), 1.0);
+        assert_eq!(block_chance(0.3, false), 0.3);
+        assert_eq!(block_chance(0.0, true), 0.0);
+        assert_eq!(block_chance(1.15, false), 1.0);
+    }
+
+    #[test]
+    fn strikes_miss_get_blocked_or_hit() {
+        let mut rng = rng();
+        let strike = Strike {
+            damage: 10..11,
+            hit_chance: 1.0,
+            multiplier: CRUSHING_BLOW_MULTIPLIER,
+            crit_chance: 0.0,
+        };
+        let wall = Guard {
+            block_chance: 0.5,
+            defending: true,
+        };
+        let open = Guard {
+            block_chance: 0.0,
+            defending: true,
+        };
+
+        for _ in 0..TRIES {
+            assert_eq!(
+                resolve_strike(&mut rng, &strike, Some(wall)),
+                Outcome::Blocked
+            );
+            assert_eq!(
+                resolve_strike(&mut rng, &strike, Some(open)),
+                Outcome::Hit(12)
+            );
+            assert_eq!(resolve_strike(&mut rng, &strike, None), Outcome::Hit(12));
+            assert_eq!(
+                resolve_strike(
+                    &mut rng,
+                    &Strike {
+                        hit_chance: 0.0,
+                        ..strike.clone()
+                    },
+                    Some(open)
+                ),
+                Outcome::Missed
+            );
+        }
+    }
+
+    #[test]
+    fn area_strikes_spread_over_every_guard() {
+        let mut rng = rng();
+        let strike = Strike {
+            damage: 30..31,
+            hit_chance: 1.0,
+            multiplier: AREA_MULTIPLIER,
+            crit_chance: 1.0,
+        };
+        let wall = Guard {
+            block_chance: 1.0,
+            defending: false,
+        };
+        let open = Guard {
+            block_chance: 0.0,
+            defending: false,
+        };
+
+        assert_eq!(
+            resolve_area_strike(&mut rng, &strike, &[open, wall, open]),
+            Some(vec![Outcome::Hit(15), Outcome::Blocked, Outcome::Hit(15)])
+        );
+        let miss = Strike {
+            hit_chance: 0.0,
+            ..strike
+        };
+        assert_eq!(resolve_area_strike(&mut rng, &miss, &[open]), None);
+    }
+
+    #[test]
+    fn crits_multiply_the_hit() {
+        let mut rng = rng();
+        let strike = Strike {
+            damage: 10..11,
+            hit_chance: 1.0,
+            multiplier: 1.0,
+            crit_chance: 1.0,
+        };
+
+        for _ in 0..TRIES {
+            assert_eq!(resolve_strike(&mut rng, &strike, None), Outcome::Crit(15));
+        }
+        let crits = (0..TRIES)
+            .filter(|_| {
+                let strike = Strike {
+                    crit_chance: CRIT_CHANCE,
+                    ..strike.clone()
+                };
+                matches!(resolve_strike(&mut rng, &strike, None), Outcome::Crit(_))
+            })
+            .count();
+        assert!(crits.abs_diff(TRIES / 10) < TRIES / 20);
+    }
+
+    #[test]
+    fn specials_cost_energy_regained_each_turn() {
+        assert_eq!(spend_energy(MAX_ENERGY, SPECIAL_COST), Some(1));
+        assert_eq!(spend_energy(1, SPECIAL_COST), None);
+        assert_eq!(regain_energy(1), 2);
+        assert_eq!(regain_energy(MAX_ENERGY), MAX_ENERGY);
+
+        // Starting full, two specials can be used in a row, then one every other turn.
+        let mut energy = MAX_ENERGY;
+        let mut specials = 0;
+        for _ in 0..10 {
+            energy = regain_energy(energy);
+            if let Some(left) = spend_energy(energy, SPECIAL_COST) {
+                energy = left;
+                specials += 1;
+            }
+        }
+        assert_eq!(specials, 6);
+    }
+
+    #[test]
+    fn heals_stay_in_range() {
+        let mut rng = rng();
+        for _ in 0..TRIES {
+            assert!(HEAL_RANGE.contains(&roll_heal(&mut rng)));
+        }
+    }
+
+    #[test]
+    fn faster_parties_get_away_more_often() {
+        assert_eq!(flee_chance(4.0, 4.0), 0.5);
+        assert!(flee_chance(6.0, 2.0) > flee_chance(2.0, 6.0));
+        assert_eq!(flee_chance(10.0, 0.0), MAX_FLEE_CHANCE);
+        assert_eq!(flee_chance(1.0, 9.0), MIN_FLEE_CHANCE);
+        assert_eq!(flee_chance(0.0, 0.0), MIN_FLEE_CHANCE);
+    }
+}
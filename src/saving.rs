@@ -1,11 +1,26 @@
-use crate::generate_map::MapTilemap;
+use crate::component_blob::{load_component_blobs, save_component_blobs};
+use crate::game::{
+    Gold, HexShards, PillarCorruption, RunStats, SuspendedCombat, journal_directory, suspend_combat,
+};
+use crate::generate_map::{GenerationMode, GenerationSettings, MapShape, MapTilemap};
+use crate::menu::load_game::LoadGameState;
 use crate::prelude::*;
 use crate::room::CurrentRoom;
 use bevy::prelude::*;
 use bevy_ecs_tilemap::prelude::*;
 
+#[cfg(feature = "sqlite")]
+use crate::database::{DB_VERSION, Version, get_default_db_directory};
 #[cfg(feature = "sqlite")]
 use chrono::{DateTime, Utc};
+#[cfg(feature = "sqlite")]
+use rusqlite::types::Value;
+#[cfg(feature = "sqlite")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "sqlite")]
+use std::path::{Path, PathBuf};
+#[cfg(feature = "sqlite")]
+use thiserror::Error;
 
 pub struct SavePlugin;
 
@@ -26,7 +41,7 @@ pub enum SaveState {
 }
 
 /// The rowid of the save game table.
-#[derive(Deref, DerefMut, Clone, Copy)]
+#[derive(Deref, DerefMut, Clone, Copy, PartialEq, Eq, Debug)]
 pub struct GameID(pub i64);
 
 /// The global resource for the currently loaded save game.
@@ -36,13 +51,68 @@ pub struct SaveGame {
     /// The seed used to generate the world
     pub seed: u64,
     pub pillar_count: u64,
+    pub difficulty: Difficulty,
+    /// What the player named the world, may be empty.
+    pub name: String,
+    /// Fights saved part way through can only be loaded once, and fallen heroes stay down.
+    pub hardcore: bool,
+    pub mode: GameMode,
+    pub challenges: Challenges,
+    /// How many floors down the party is, with the first floor at 0.
+    /// Only the rooms of this floor are loaded.
+    pub floor: u32,
+    /// How every floor of the world is laid out.
+    pub generation: GenerationMode,
+    /// How big every floor is and how many pillars are on it.
+    pub shape: MapShape,
+    /// How likely each kind of room is on every floor.
+    pub rooms: RoomWeights,
+}
+
+impl SaveGame {
+    /// What the map of the current floor is generated from.
+    pub fn generation_settings(&self) -> GenerationSettings {
+        GenerationSettings {
+            seed: self.seed,
+            difficulty: self.difficulty,
+            floor: self.floor,
+            rooms: self.rooms,
+            mode: self.generation,
+            shape: self.shape,
+            ..default()
+        }
+    }
 }
 
 #[cfg(feature = "sqlite")]
 impl SaveGame {
-    pub fn new(db: &Database, seed: u64) -> Self {
-        let query = "INSERT INTO SaveGame(last_saved,world_seed) VALUES(datetime('now'), ?1)";
-        db.connection.execute(query, (seed as i64,)).unwrap();
+    pub fn new(
+        db: &Database,
+        seed: u64,
+        difficulty: Difficulty,
+        rules: GameRules,
+        name: &str,
+    ) -> Self {
+        let query = "INSERT INTO SaveGame(last_saved,world_seed,difficulty,hardcore,game_mode,challenges,name,generation,map_radius,pillars,room_weights)
+            VALUES(datetime('now'), ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)";
+        let rooms = rules.rooms.weights();
+        db.connection
+            .execute(
+                query,
+                (
+                    seed as i64,
+                    ron::to_string(&difficulty).unwrap(),
+                    rules.hardcore,
+                    ron::to_string(&rules.mode).unwrap(),
+                    ron::to_string(&rules.challenges).unwrap(),
+                    name,
+                    ron::to_string(&rules.generation).unwrap(),
+                    rules.shape.radius,
+                    rules.shape.pillars,
+                    ron::to_string(&rooms).unwrap(),
+                ),
+            )
+            .unwrap();
 
         let game_id = db.connection.last_insert_rowid();
 
@@ -50,17 +120,38 @@ impl SaveGame {
             game_id: GameID(game_id),
             seed,
             pillar_count: 0,
+            difficulty,
+            name: name.to_string(),
+            hardcore: rules.hardcore,
+            mode: rules.mode,
+            challenges: rules.challenges,
+            floor: 0,
+            generation: rules.generation,
+            shape: rules.shape,
+            rooms,
         }
     }
 
     pub fn load(db: &Database, game_id: GameID) -> Self {
-        let query =
-            "SELECT world_seed,pillar_count FROM SaveGame WHERE SaveGame.game_id = :game_id";
+        let query = "SELECT world_seed,pillar_count,difficulty,name,hardcore,game_mode,challenges,floor,generation,map_radius,pillars,room_weights FROM SaveGame WHERE SaveGame.game_id = :game_id";
 
         let world_seed = db
             .connection
             .query_one(query, (game_id.0,), |row| {
-                Ok((row.get::<_, i64>(0)?, row.get(1)?))
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                    row.get::<_, String>(5)?,
+                    row.get::<_, String>(6)?,
+                    row.get(7)?,
+                    row.get::<_, String>(8)?,
+                    row.get(9)?,
+                    row.get(10)?,
+                    row.get::<_, String>(11)?,
+                ))
             })
             .unwrap();
 
@@ -68,17 +159,124 @@ impl SaveGame {
             game_id,
             seed: world_seed.0 as u64,
             pillar_count: world_seed.1,
+            difficulty: ron::from_str(&world_seed.2).unwrap_or_default(),
+            name: world_seed.3,
+            hardcore: world_seed.4,
+            mode: ron::from_str(&world_seed.5).unwrap_or_default(),
+            challenges: ron::from_str(&world_seed.6).unwrap_or_default(),
+            floor: world_seed.7,
+            generation: ron::from_str(&world_seed.8).unwrap_or_default(),
+            shape: MapShape {
+                radius: world_seed.9,
+                pillars: world_seed.10,
+            },
+            rooms: ron::from_str(&world_seed.11).unwrap_or_default(),
         }
     }
 
-    /// Updates the [`SaveGame`] database entry with the new save time and current room
-    pub fn save(&self, db: &Database, current_room: &TilePos) -> Result<(), DatabaseError> {
+    /// Removes the save game and everything saved with it.
+    pub fn delete(db: &Database, game_id: GameID) -> Result<(), DatabaseError> {
+        let tx = db.connection.unchecked_transaction()?;
+        for table in SAVE_TABLES.iter().rev() {
+            tx.execute(
+                &format!("DELETE FROM {table} WHERE game_id = :game_id"),
+                (game_id.0,),
+            )?;
+        }
+        tx.commit()?;
+
+        match std::fs::remove_dir_all(journal_directory(game_id)) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+                warn!("Failed to remove the journal thumbnails with: {err}")
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Copies the save game and everything saved with it into a new game, and gives its id,
+    /// so a run can be branched before a risky choice.
+    ///
+    /// Hardcore games can't be copied, as that would give them a life to spare.
+    /// Copies of daily runs and challenge maps are played like any other game, and not recorded.
+    pub fn duplicate(db: &Database, game_id: GameID) -> Result<GameID, SaveFileError> {
+        let trusted = !Self::is_tampered(db, game_id)?;
+
+        let mut save = ExportedSave::from_database(db, game_id)?;
+        if let Some(games) = save
+            .tables
+            .iter_mut()
+            .find(|table| table.name == "SaveGame")
+        {
+            let name = games.columns.iter().position(|column| column == "name");
+            if let (Some(name), Some(row)) = (name, games.rows.first_mut())
+                && let ExportedValue::Text(name) = &mut row[name]
+            {
+                name.push_str(" (copy)");
+            }
+        }
+        let copy = save.insert(db)?;
+
+        // The copy was only renamed, so it is as trustworthy as the original.
+        if trusted {
+            db.connection.execute(
+                "UPDATE SaveGame SET checksum = ?1 WHERE game_id = ?2 AND checksum IS NOT NULL",
+                (Self::compute_checksum(db, copy)?, copy.0),
+            )?;
+        }
+
+        let copy_thumbnails = || -> std::io::Result<()> {
+            let thumbnails = journal_directory(game_id);
+            if !thumbnails.exists() {
+                return Ok(());
+            }
+            let copied = journal_directory(copy);
+            std::fs::create_dir_all(&copied)?;
+            for entry in std::fs::read_dir(&thumbnails)? {
+                let path = entry?.path();
+                std::fs::copy(&path, copied.join(path.file_name().unwrap()))?;
+            }
+            Ok(())
+        };
+        if let Err(err) = copy_thumbnails() {
+            warn!("Failed to copy the journal thumbnails with: {err}");
+        }
+        Ok(copy)
+    }
+
+    /// Gives the save game a new name, keeping it trusted if it was.
+    pub fn rename(db: &Database, game_id: GameID, name: &str) -> Result<(), DatabaseError> {
+        let trusted = !Self::is_tampered(db, game_id)?;
+
+        db.connection.execute(
+            "UPDATE SaveGame SET name = ?1 WHERE game_id = ?2",
+            (name, game_id.0),
+        )?;
+
+        if trusted {
+            db.connection.execute(
+                "UPDATE SaveGame SET checksum = ?1 WHERE game_id = ?2 AND checksum IS NOT NULL",
+                (Self::compute_checksum(db, game_id)?, game_id.0),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Updates the [`SaveGame`] database entry with the new save time, current room and gold
+    pub fn save(
+        &self,
+        db: &Database,
+        current_room: &TilePos,
+        gold: Gold,
+    ) -> Result<(), DatabaseError> {
         let query = "
         UPDATE SaveGame
             SET last_saved = datetime('now'),
                 current_room_x = :current_room_x,
                 current_room_y = :current_room_y,
-                pillar_count = :pillar_count
+                pillar_count = :pillar_count,
+                gold = :gold,
+                floor = :floor
             WHERE game_id = :game_id";
         db.connection.execute(
             query,
@@ -86,23 +284,209 @@ impl SaveGame {
                 current_room.x,
                 current_room.y,
                 self.pillar_count,
+                gold.0 as i64,
+                self.floor,
                 self.game_id.0,
             ),
         )?;
         Ok(())
     }
+
+    /// Records that the last save was an autosave, to show in the list of saves.
+    pub fn mark_autosaved(&self, db: &Database) -> Result<(), DatabaseError> {
+        db.connection.execute(
+            "UPDATE SaveGame SET last_autosaved = last_saved WHERE game_id = :game_id",
+            (self.game_id.0,),
+        )?;
+        Ok(())
+    }
+
+    /// Saves the fight going on, or clears the last one when there is none.
+    pub fn save_suspended_combat(
+        &self,
+        db: &Database,
+        combat: Option<&SuspendedCombat>,
+    ) -> Result<(), DatabaseError> {
+        let combat = combat.map(|combat| ron::to_string(combat).unwrap());
+        db.connection.execute(
+            "UPDATE SaveGame SET suspended_combat = :combat WHERE game_id = :game_id",
+            (combat, self.game_id.0),
+        )?;
+        Ok(())
+    }
+
+    /// The fight the game was saved in the middle of, if any.
+    /// Hardcore games delete it as it is loaded, so it can't be loaded again to retry the fight.
+    pub fn load_suspended_combat(
+        &self,
+        db: &Database,
+    ) -> Result<Option<SuspendedCombat>, DatabaseError> {
+        let combat: Option<String> = db.connection.query_one(
+            "SELECT suspended_combat FROM SaveGame WHERE game_id = :game_id",
+            (self.game_id.0,),
+            |row| row.get(0),
+        )?;
+
+        if self.hardcore && combat.is_some() {
+            self.save_suspended_combat(db, None)?;
+            self.stamp_checksum(db)?;
+        }
+
+        Ok(combat.and_then(|combat| ron::from_str(&combat).ok()))
+    }
+
+    /// Saves how far along the pillar corruption is, if the game has it on.
+    pub fn save_corruption(
+        &self,
+        db: &Database,
+        corruption: Option<&PillarCorruption>,
+    ) -> Result<(), DatabaseError> {
+        let corruption = corruption.map(|corruption| ron::to_string(corruption).unwrap());
+        db.connection.execute(
+            "UPDATE SaveGame SET corruption = :corruption WHERE game_id = :game_id",
+            (corruption, self.game_id.0),
+        )?;
+        Ok(())
+    }
+
+    pub fn load_corruption(
+        &self,
+        db: &Database,
+    ) -> Result<Option<PillarCorruption>, DatabaseError> {
+        let corruption: Option<String> = db.connection.query_one(
+            "SELECT corruption FROM SaveGame WHERE game_id = :game_id",
+            (self.game_id.0,),
+            |row| row.get(0),
+        )?;
+        Ok(corruption.and_then(|corruption| ron::from_str(&corruption).ok()))
+    }
+
+    pub fn save_hex_shards(&self, db: &Database, shards: HexShards) -> Result<(), DatabaseError> {
+        db.connection.execute(
+            "UPDATE SaveGame SET hex_shards = :hex_shards WHERE game_id = :game_id",
+            (shards.0, self.game_id.0),
+        )?;
+        Ok(())
+    }
+
+    pub fn load_hex_shards(&self, db: &Database) -> Result<HexShards, DatabaseError> {
+        let shards = db.connection.query_one(
+            "SELECT hex_shards FROM SaveGame WHERE game_id = :game_id",
+            (self.game_id.0,),
+            |row| row.get(0),
+        )?;
+        Ok(HexShards(shards))
+    }
+
+    pub fn save_run_stats(&self, db: &Database, stats: &RunStats) -> Result<(), DatabaseError> {
+        db.connection.execute(
+            "INSERT OR REPLACE INTO RunStats(game_id,rooms_explored,enemies_killed,damage_dealt,damage_taken,pits_triggered,items_used,turns_taken)
+                VALUES(?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            (
+                self.game_id.0,
+                stats.rooms_explored,
+                stats.enemies_killed,
+                stats.damage_dealt,
+                stats.damage_taken,
+                stats.pits_triggered,
+                stats.items_used,
+                stats.turns_taken,
+            ),
+        )?;
+        Ok(())
+    }
+
+    /// Games from before stats were kept start counting from nothing.
+    pub fn load_run_stats(&self, db: &Database) -> Result<RunStats, DatabaseError> {
+        let stats = db.connection.query_one(
+            "SELECT rooms_explored,enemies_killed,damage_dealt,damage_taken,pits_triggered,items_used,turns_taken
+                FROM RunStats WHERE game_id = :game_id",
+            (self.game_id.0,),
+            |row| {
+                Ok(RunStats {
+                    rooms_explored: row.get(0)?,
+                    enemies_killed: row.get(1)?,
+                    damage_dealt: row.get(2)?,
+                    damage_taken: row.get(3)?,
+                    pits_triggered: row.get(4)?,
+                    items_used: row.get(5)?,
+                    turns_taken: row.get(6)?,
+                })
+            },
+        );
+        match stats {
+            Err(DatabaseError::QueryReturnedNoRows) => Ok(RunStats::default()),
+            stats => stats,
+        }
+    }
+
+    /// A checksum of everything saved with the game, which changes if any of it does.
+    /// Rows are hashed in order, so it is the same whichever order they were written in.
+    /// The journal is left out, as it is written as rooms are cleared rather than when saving.
+    pub fn compute_checksum(db: &Database, game_id: GameID) -> Result<String, DatabaseError> {
+        let save = ExportedSave::from_database(db, game_id)?;
+        let mut hasher = blake3::Hasher::new();
+
+        for table in save.tables.iter().filter(|t| t.name != "JournalEntry") {
+            let checked: Vec<usize> = (0..table.columns.len())
+                .filter(|i| !UNCHECKED_COLUMNS.contains(&table.columns[*i].as_str()))
+                .collect();
+            let mut rows: Vec<String> = table
+                .rows
+                .iter()
+                .map(|row| {
+                    let row: Vec<&ExportedValue> = checked.iter().map(|i| &row[*i]).collect();
+                    ron::to_string(&row).unwrap()
+                })
+                .collect();
+            rows.sort();
+
+            hasher.update(table.name.as_bytes());
+            for row in rows {
+                hasher.update(b"\n");
+                hasher.update(row.as_bytes());
+            }
+            hasher.update(b"\n\n");
+        }
+
+        Ok(hasher.finalize().to_hex().to_string())
+    }
+
+    /// Keeps the checksum of what is saved now, to check it against when loading.
+    pub fn stamp_checksum(&self, db: &Database) -> Result<(), DatabaseError> {
+        let checksum = Self::compute_checksum(db, self.game_id)?;
+        db.connection.execute(
+            "UPDATE SaveGame SET checksum = ?1 WHERE game_id = ?2",
+            (checksum, self.game_id.0),
+        )?;
+        Ok(())
+    }
+
+    /// Whether the save was changed outside the game since it was last saved.
+    /// Games saved before checksums were kept never are.
+    pub fn is_tampered(db: &Database, game_id: GameID) -> Result<bool, DatabaseError> {
+        let stored: Option<String> = db.connection.query_one(
+            "SELECT checksum FROM SaveGame WHERE game_id = :game_id",
+            (game_id.0,),
+            |row| row.get(0),
+        )?;
+        match stored {
+            Some(stored) => Ok(stored != Self::compute_checksum(db, game_id)?),
+            None => Ok(false),
+        }
+    }
 }
 
 #[cfg(not(feature = "sqlite"))]
 impl SaveGame {
-    pub fn new(_: &Database, seed: u64) -> Self {
+    pub fn new(_: &Database, seed: u64, _: Difficulty, _: &str) -> Self {
         Self {
             game_id: GameID(0),
             seed,
         }
     }
 
-    pub fn save(&self, _: &Database) -> Result<(), DatabaseError> {
+    pub fn save(&self, _: &Database, _: &TilePos, _: Gold) -> Result<(), DatabaseError> {
         Ok(())
     }
 }
@@ -113,97 +497,497 @@ pub struct SaveGameInfo {
     pub id: GameID,
     pub created: chrono::DateTime<chrono::Local>,
     pub last_saved: chrono::DateTime<chrono::Local>,
+    /// When the game was last saved without the player asking, if it ever was.
+    pub last_autosaved: Option<chrono::DateTime<chrono::Local>>,
     pub world_seed: u64,
+    pub name: String,
 }
 
 #[cfg(feature = "sqlite")]
 impl SaveGameInfo {
+    /// The name of the world, or its id when it was left unnamed.
+    pub fn title(&self) -> String {
+        match self.name.as_str() {
+            "" => format!("game: {}", self.id.0),
+            name => name.to_string(),
+        }
+    }
+
+    /// A name for the file of the save, from its name and id.
+    pub fn file_name(&self) -> String {
+        let title: String = self
+            .name
+            .chars()
+            .map(|c| match c.is_ascii_alphanumeric() || c == '-' {
+                true => c,
+                false => '_',
+            })
+            .collect();
+        match title.is_empty() {
+            true => format!("game-{}.ron", self.id.0),
+            false => format!("{title}-{}.ron", self.id.0),
+        }
+    }
+
     pub fn get_all(db: &Database) -> Result<Box<[Self]>, DatabaseError> {
         db.connection
             .prepare(
-                "SELECT game_id,created,last_saved,world_seed FROM SaveGame ORDER BY game_id DESC",
+                "SELECT game_id,created,last_saved,world_seed,name,last_autosaved FROM SaveGame ORDER BY game_id DESC",
             )?
             .query_map((), |row| {
                 let created: DateTime<Utc> = row.get(1)?;
                 let last_saved: DateTime<Utc> = row.get(2)?;
+                let last_autosaved: Option<DateTime<Utc>> = row.get(5)?;
                 Ok(Self {
                     id: GameID(row.get(0)?),
                     created: created.into(),
                     last_saved: last_saved.into(),
+                    last_autosaved: last_autosaved.map(Into::into),
                     world_seed: row.get::<_, i64>(3)? as u64,
+                    name: row.get(4)?,
                 })
             })?
             .collect()
     }
 }
 
-/// Takes the World as this should be the only thing running at the time.
+/// The tables everything saved with a game is kept in, by its `game_id`.
+/// The save game itself comes first, as the rest refer to it.
+#[cfg(feature = "sqlite")]
+const SAVE_TABLES: [&str; 7] = [
+    "SaveGame",
+    "PlayerActor",
+    "RoomInfo",
+    "Item",
+    "JournalEntry",
+    "RunStats",
+    "ComponentBlob",
+];
+
+/// The columns left out of the checksum, which change without the game being saved.
+#[cfg(feature = "sqlite")]
+const UNCHECKED_COLUMNS: [&str; 2] = ["checksum", "last_autosaved"];
+
+/// Where exported saves are written.
+#[cfg(feature = "sqlite")]
+pub fn exports_directory() -> PathBuf {
+    get_default_db_directory().join("exports")
+}
+
+/// Where saves to import are looked for.
+/// Once imported they are moved to the `imported` folder inside, so they are only imported once.
+#[cfg(feature = "sqlite")]
+pub fn imports_directory() -> PathBuf {
+    get_default_db_directory().join("imports")
+}
+
+#[cfg(feature = "sqlite")]
+#[derive(Error, Debug)]
+pub enum SaveFileError {
+    #[error("Database error: {0}")]
+    Database(#[from] DatabaseError),
+    #[error("Failed to access the save file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Failed to write the save file: {0}")]
+    Serialize(#[from] ron::Error),
+    #[error("Failed to read the save file: {0}")]
+    Deserialize(#[from] ron::error::SpannedError),
+    #[error("The save is from version {0} of the game, but this is version {DB_VERSION}")]
+    Version(Version),
+    #[error("The save has no `{0}` to import")]
+    Unknown(String),
+    #[error("Hardcore games can't be duplicated or imported")]
+    Hardcore,
+}
+
+/// A value in an exported row, as it was in the database.
+#[cfg(feature = "sqlite")]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum ExportedValue {
+    Null,
+    Integer(i64),
+    Real(f64),
+    Text(String),
+    Blob(Vec<u8>),
+}
+
+#[cfg(feature = "sqlite")]
+impl From<Value> for ExportedValue {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Null => Self::Null,
+            Value::Integer(value) => Self::Integer(value),
+            Value::Real(value) => Self::Real(value),
+            Value::Text(value) => Self::Text(value),
+            Value::Blob(value) => Self::Blob(value),
+        }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl From<ExportedValue> for Value {
+    fn from(value: ExportedValue) -> Self {
+        match value {
+            ExportedValue::Null => Value::Null,
+            ExportedValue::Integer(value) => Value::Integer(value),
+            ExportedValue::Real(value) => Value::Real(value),
+            ExportedValue::Text(value) => Value::Text(value),
+            ExportedValue::Blob(value) => Value::Blob(value),
+        }
+    }
+}
+
+/// The rows of one of the [`SAVE_TABLES`], without their `game_id`.
+#[cfg(feature = "sqlite")]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ExportedTable {
+    pub name: String,
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<ExportedValue>>,
+}
+
+/// A whole save game written out to a file, to back it up or share it.
+///
+/// Holds the seed, rooms of every floor, party, items, journal and stats,
+/// but not the journal's thumbnails, which are drawn again as rooms are cleared.
+#[cfg(feature = "sqlite")]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ExportedSave {
+    /// The database version it was exported from, as the columns change between them.
+    pub version: Version,
+    pub tables: Vec<ExportedTable>,
+}
+
+#[cfg(feature = "sqlite")]
+impl ExportedSave {
+    /// Reads everything saved with the game.
+    pub fn from_database(db: &Database, game_id: GameID) -> Result<Self, DatabaseError> {
+        let tables = SAVE_TABLES
+            .iter()
+            .map(|table| {
+                let mut statement = db
+                    .connection
+                    .prepare(&format!("SELECT * FROM {table} WHERE game_id = :game_id"))?;
+                let names: Vec<String> = statement
+                    .column_names()
+                    .into_iter()
+                    .map(String::from)
+                    .collect();
+                let kept: Vec<usize> = (0..names.len())
+                    .filter(|i| names[*i] != "game_id")
+                    .collect();
+                let rows = statement
+                    .query_map((game_id.0,), |row| {
+                        kept.iter()
+                            .map(|i| row.get::<_, Value>(*i).map(ExportedValue::from))
+                            .collect()
+                    })?
+                    .collect::<Result<_, _>>()?;
+
+                Ok(ExportedTable {
+                    name: table.to_string(),
+                    columns: kept.iter().map(|i| names[*i].clone()).collect(),
+                    rows,
+                })
+            })
+            .collect::<Result<_, DatabaseError>>()?;
+
+        Ok(Self {
+            version: DB_VERSION,
+            tables,
+        })
+    }
+
+    /// Whether the save is of a hardcore game.
+    pub fn is_hardcore(&self) -> bool {
+        let Some(games) = self.tables.iter().find(|table| table.name == "SaveGame") else {
+            return false;
+        };
+        let Some(hardcore) = games.columns.iter().position(|column| column == "hardcore") else {
+            return false;
+        };
+        games
+            .rows
+            .iter()
+            .any(|row| !matches!(row.get(hardcore), Some(ExportedValue::Integer(0)) | None))
+    }
+
+    /// Adds the save to the database as a new game, and gives its id.
+    /// Nothing is added if any part of it doesn't fit.
+    ///
+    /// Hardcore games are turned away, as a copy would give them a life to spare.
+    pub fn insert(&self, db: &Database) -> Result<GameID, SaveFileError> {
+        if self.version != DB_VERSION {
+            return Err(SaveFileError::Version(self.version));
+        }
+        let games = self.tables.iter().find(|table| table.name == "SaveGame");
+        if games.is_none_or(|games| games.rows.len() != 1) {
+            return Err(SaveFileError::Unknown("SaveGame".into()));
+        }
+        if self.is_hardcore() {
+            return Err(SaveFileError::Hardcore);
+        }
+
+        let tx = db.connection.unchecked_transaction()?;
+        // The save game is given a new id, which everything else is then saved under.
+        let mut game_id = Value::Null;
+        for name in SAVE_TABLES {
+            let Some(table) = self.tables.iter().find(|table| table.name == name) else {
+                continue;
+            };
+
+            // Only columns the table has are put back, so a file can't sneak anything else in.
+            let known: Vec<String> = tx
+                .prepare(&format!("SELECT * FROM {name} LIMIT 0"))?
+                .column_names()
+                .into_iter()
+                .map(String::from)
+                .collect();
+            if let Some(column) = table
+                .columns
+                .iter()
+                .find(|column| *column == "game_id" || !known.contains(column))
+            {
+                return Err(SaveFileError::Unknown(format!("{name}.{column}")));
+            }
+
+            let columns = table.columns.join(",");
+            let values: Vec<String> = (0..table.columns.len())
+                .map(|i| format!("?{}", i + 2))
+                .collect();
+            let query = format!(
+                "INSERT INTO {name}(game_id,{columns}) VALUES(?1,{})",
+                values.join(",")
+            );
+            for row in &table.rows {
+                let values = row.iter().cloned().map(Value::from);
+                tx.execute(
+                    &query,
+                    rusqlite::params_from_iter(std::iter::once(game_id.clone()).chain(values)),
+                )?;
+            }
+
+            if name == "SaveGame" {
+                game_id = Value::Integer(tx.last_insert_rowid());
+            }
+        }
+        tx.commit()?;
+
+        match game_id {
+            Value::Integer(game_id) => Ok(GameID(game_id)),
+            _ => unreachable!("the save game is always inserted"),
+        }
+    }
+}
+
+/// Writes the save game to a file in the [`exports_directory`], and gives its path.
+#[cfg(feature = "sqlite")]
+pub fn export_save(db: &Database, info: &SaveGameInfo) -> Result<PathBuf, SaveFileError> {
+    let save = ExportedSave::from_database(db, info.id)?;
+    let directory = exports_directory();
+    std::fs::create_dir_all(&directory)?;
+
+    let path = directory.join(info.file_name());
+    let pretty = ron::ser::PrettyConfig::default();
+    std::fs::write(&path, ron::ser::to_string_pretty(&save, pretty)?)?;
+    Ok(path)
+}
+
+/// Adds the save game in the file to the database as a new game, and gives its id.
+#[cfg(feature = "sqlite")]
+pub fn import_save(db: &Database, path: &Path) -> Result<GameID, SaveFileError> {
+    let save: ExportedSave = ron::from_str(&std::fs::read_to_string(path)?)?;
+    save.insert(db)
+}
+
+/// Imports every save in the [`imports_directory`], moving each one imported out of the way.
+/// Gives how many were imported, and the errors of those that couldn't be.
+#[cfg(feature = "sqlite")]
+pub fn import_saves(db: &Database) -> Result<(usize, Vec<SaveFileError>), SaveFileError> {
+    let directory = imports_directory();
+    let imported = directory.join("imported");
+    std::fs::create_dir_all(&imported)?;
+
+    let mut count = 0;
+    let mut errors = Vec::new();
+    for entry in std::fs::read_dir(&directory)? {
+        let path = entry?.path();
+        if !path.is_file() || path.extension().is_none_or(|ext| ext != "ron") {
+            continue;
+        }
+
+        match import_save(db, &path) {
+            Ok(game_id) => {
+                info!("Imported {} as game {}", path.display(), game_id.0);
+                std::fs::rename(&path, imported.join(path.file_name().unwrap()))?;
+                count += 1;
+            }
+            Err(err) => {
+                warn!("Failed to import {} with: {err}", path.display());
+                errors.push(err);
+            }
+        }
+    }
+
+    Ok((count, errors))
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SaveKind {
+    /// Only the rooms, actors and items that changed since the last save.
+    Incremental,
+    /// Everything, replacing whatever was saved before.
+    Full,
+}
+
+/// The game the last save was for, so switching games always saves in full.
+#[derive(Resource)]
+pub(crate) struct LastSavedGame(GameID);
+
+/// Saves only what changed since the last save.
+/// Does nothing in a skirmish, which has no save.
 pub fn save_game(world: &mut World) {
-    info!("Saving Game");
-    {
-        let db = world.get_non_send_resource::<Database>().unwrap();
-        db.connection.execute_batch("BEGIN TRANSACTION;").unwrap();
+    if !world.contains_resource::<SaveGame>() {
+        return;
     }
 
-    world.run_system_cached(save_game_inner).unwrap();
+    if let Err(err) = save_game_with(world, SaveKind::Incremental) {
+        report_error(err);
+    }
+}
 
-    world
-        .run_system_cached(crate::actor::save_actors)
-        .unwrap()
-        .unwrap();
+/// Saves what changed since the last save, and notes it was an autosave.
+pub fn autosave_game(world: &mut World) {
+    if !world.contains_resource::<SaveGame>() {
+        return;
+    }
 
-    world
-        .run_system_cached(crate::spawn_map::save_map)
-        .unwrap()
-        .unwrap();
+    let autosaved = save_game_with(world, SaveKind::Incremental).and_then(|()| {
+        let save = world.resource::<SaveGame>();
+        let db = world.non_send_resource::<Database>();
+        save.mark_autosaved(db).map_err(GameError::from)
+    });
+    if let Err(err) = autosaved {
+        report_error(err);
+    }
+}
 
-    world
-        .run_system_cached(crate::items::save_items)
-        .unwrap()
-        .unwrap();
+/// Saves everything, such as when the game is first created.
+pub fn save_game_full(world: &mut World) {
+    if let Err(err) = save_game_with(world, SaveKind::Full) {
+        report_error(err);
+    }
+}
 
+/// Takes the World as this should be the only thing running at the time.
+/// Nothing is written if any part of the save fails.
+pub fn save_game_with(world: &mut World, mut kind: SaveKind) -> Result<(), GameError> {
+    let game_id = world.resource::<SaveGame>().game_id;
+    let incremental_saves = world
+        .get_resource::<Config>()
+        .is_none_or(|config| config.incremental_saves);
+    if !incremental_saves
+        || world
+            .get_resource::<LastSavedGame>()
+            .is_none_or(|last| last.0 != game_id)
     {
-        let db = world.get_non_send_resource::<Database>().unwrap();
-        db.connection.execute_batch("COMMIT;").unwrap();
+        kind = SaveKind::Full;
+    }
+
+    info!("Saving Game ({kind:?})");
+    world
+        .non_send_resource::<Database>()
+        .connection
+        .execute_batch("BEGIN TRANSACTION;")?;
+
+    let saved = save_game_parts(world, kind);
+
+    let db = world.non_send_resource::<Database>();
+    if let Err(err) = saved {
+        db.connection.execute_batch("ROLLBACK;")?;
+        return Err(err);
     }
+    db.connection.execute_batch("COMMIT;")?;
 
+    world.insert_resource(LastSavedGame(game_id));
+    world.resource_mut::<Notifications>().success("Game saved");
     info!("Game Save Successful");
+
+    Ok(())
+}
+
+fn save_game_parts(world: &mut World, kind: SaveKind) -> Result<(), GameError> {
+    world.run_system_cached(save_game_inner)??;
+    world.run_system_cached_with(crate::actor::save_actors, kind)??;
+    save_component_blobs(world)?;
+    world.run_system_cached_with(crate::spawn_map::save_map, kind)??;
+    world.run_system_cached_with(crate::items::save_items, kind)??;
+
+    let combat = world.run_system_cached(suspend_combat)?;
+    let save = world.resource::<SaveGame>();
+    let db = world.non_send_resource::<Database>();
+    save.save_suspended_combat(db, combat.as_ref())?;
+    save.save_corruption(db, world.get_resource::<PillarCorruption>())?;
+    if let Some(shards) = world.get_resource::<HexShards>() {
+        save.save_hex_shards(db, *shards)?;
+    }
+    if let Some(stats) = world.get_resource::<RunStats>() {
+        save.save_run_stats(db, stats)?;
+    }
+    save.stamp_checksum(db)?;
+
+    Ok(())
 }
 
 fn save_game_inner(
     db: NonSend<Database>,
     save: Res<SaveGame>,
     pos: Single<&TilePos, With<CurrentRoom>>,
-) {
-    save.save(&db, *pos).unwrap();
+    gold: Option<Res<Gold>>,
+) -> Result<(), DatabaseError> {
+    save.save(&db, *pos, gold.map_or_else(Gold::default, |gold| *gold))
 }
 
 pub fn load_game(world: &mut World) {
     info!("Loading Game");
 
-    world
-        .run_system_cached(crate::actor::load_actors)
-        .unwrap()
-        .unwrap();
+    if let Err(err) = load_game_with(world) {
+        report_error(err);
+        // Back to the saves, rather than into a game that is only partly there.
+        if let Some(mut next_state) = world.get_resource_mut::<NextState<LoadGameState>>() {
+            next_state.set(LoadGameState::Main);
+        }
+        return;
+    }
 
     world
-        .run_system_cached(crate::spawn_map::load_map)
-        .unwrap()
-        .unwrap();
+        .resource_mut::<NextState<AppState>>()
+        .set(AppState::Game);
 
-    world
-        .run_system_cached(crate::items::load_items)
-        .unwrap()
-        .unwrap();
+    info!("Game Load Successful")
+}
 
-    world.run_system_cached(load_game_inner).unwrap();
+fn load_game_with(world: &mut World) -> Result<(), GameError> {
+    // Checked before anything is loaded, as loading a hardcore fight saves again.
+    let game_id = world.resource::<SaveGame>().game_id;
+    match SaveGame::is_tampered(world.non_send_resource::<Database>(), game_id) {
+        Ok(true) => {
+            warn!("Save game {} was changed outside of the game", game_id.0);
+            world
+                .resource_mut::<Notifications>()
+                .warn("This save was changed outside of the game since it was last saved");
+        }
+        Ok(false) => {}
+        Err(err) => report_error(err.into()),
+    }
 
-    world
-        .get_resource_mut::<NextState<AppState>>()
-        .unwrap()
-        .set(AppState::Game);
+    world.run_system_cached(crate::actor::load_actors)??;
+    load_component_blobs(world)?;
+    world.run_system_cached(crate::spawn_map::load_map)??;
+    world.run_system_cached(crate::items::load_items)??;
+    world.run_system_cached(load_game_inner)??;
 
-    info!("Game Load Successful")
+    Ok(())
 }
 
 fn load_game_inner(
@@ -211,21 +995,587 @@ fn load_game_inner(
     db: NonSend<Database>,
     save: Res<SaveGame>,
     storage: Single<&TileStorage, With<MapTilemap>>,
-) {
+) -> Result<(), GameError> {
     let query =
-        "SELECT current_room_x,current_room_y FROM SaveGame WHERE SaveGame.game_id = :game_id";
+        "SELECT current_room_x,current_room_y,gold FROM SaveGame WHERE SaveGame.game_id = :game_id";
 
-    let pos = db
-        .connection
-        .query_one(query, (save.game_id.0,), |row| {
-            Ok(TilePos {
-                x: row.get(0)?,
-                y: row.get(1)?,
-            })
+    let (pos, gold) = db.connection.query_one(query, (save.game_id.0,), |row| {
+        let pos = TilePos {
+            x: row.get(0)?,
+            y: row.get(1)?,
+        };
+        Ok((pos, Gold(row.get::<_, i64>(2)? as u64)))
+    })?;
+
+    let entity = storage.get(&pos).ok_or(GameError::MissingRoom(pos))?;
+
+    commands.insert_resource(gold);
+    commands.insert_resource(save.mode);
+    commands.insert_resource(save.challenges);
+    commands.insert_resource(save.shape);
+
+    if let Some(combat) = save.load_suspended_combat(&db)? {
+        commands.insert_resource(combat);
+    }
+    match save.load_corruption(&db)? {
+        Some(corruption) => commands.insert_resource(corruption),
+        None => commands.remove_resource::<PillarCorruption>(),
+    }
+    commands.insert_resource(save.load_hex_shards(&db)?);
+    commands.insert_resource(save.load_run_stats(&db)?);
+
+    commands.entity(entity).insert(CurrentRoom);
+
+    Ok(())
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod test {
+    use super::*;
+    use crate::game::Combatant;
+    use crate::generate_map::MapTile;
+    use std::num::NonZero;
+
+    type Rows = Vec<Vec<String>>;
+
+    fn test_world() -> World {
+        let mut world = World::new();
+        let db = Database::open_in_memory().unwrap();
+        world.insert_resource(SaveGame::new(
+            &db,
+            0x5a7e,
+            Difficulty::Hard,
+            GameRules {
+                mode: GameMode::Survival,
+                challenges: Challenges {
+                    timed_turns: true,
+                    ..default()
+                },
+                generation: GenerationMode::Constraints,
+                ..default()
+            },
+            "Test World",
+        ));
+        world.insert_non_send_resource(db);
+        world.init_resource::<Notifications>();
+        world.insert_resource(Items(vec![Item::HealingPotion]));
+
+        for x in 0..4 {
+            for y in 0..4 {
+                let r_type = match (x, y) {
+                    (0, 0) => RoomType::Entrance,
+                    (1, _) => RoomType::Combat([ActorName::Goblin, ActorName::Ogre].into()),
+                    (2, _) => RoomType::Pit(5),
+                    _ => RoomType::EmptyRoom,
+                };
+                let room = world
+                    .spawn((
+                        MapTile,
+                        TilePos::new(x, y),
+                        RoomInfo::from_type(r_type, (x * 4 + y) as u64),
+                    ))
+                    .id();
+                if (x, y) == (0, 0) {
+                    world.entity_mut(room).insert(CurrentRoom);
+                }
+            }
+        }
+
+        // Two of the same class, which are told apart by their names.
+        let party = [
+            (ActorName::Warrior, "Aria"),
+            (ActorName::Warrior, "Brom"),
+            (ActorName::Theif, "Theif"),
+        ];
+        for (slot, (name, hero_name)) in party.into_iter().enumerate() {
+            world.spawn((
+                name,
+                HeroName(hero_name.into()),
+                PartySlot(slot),
+                Team::Player,
+                Health::new(NonZero::new(50).unwrap()),
+                Attack::new(4..9, 0.8),
+                AttackSpeed::new(3),
+                Experience {
+                    level: slot as u32 + 1,
+                    xp: 5,
+                },
+            ));
+        }
+        world.spawn((
+            ActorName::Skeleton,
+            Team::Enemy,
+            Health::new(NonZero::new(30).unwrap()),
+            Attack::new(2..5, 0.6),
+            AttackSpeed::new(2),
+        ));
+
+        world
+    }
+
+    /// Plays a bit of the game, as the systems between saves would.
+    fn play(world: &mut World) {
+        world.increment_change_tick();
+
+        let mut rooms = world.query::<(&TilePos, &mut RoomInfo)>();
+        for (pos, mut info) in rooms.iter_mut(world) {
+            if pos.x == 1 && pos.y < 2 {
+                info.cleared = true;
+                info.discovered = true;
+            }
+        }
+
+        let mut actors = world.query::<(&HeroName, &mut Health, &mut Experience)>();
+        for (name, mut health, mut experience) in actors.iter_mut(world) {
+            match name.0.as_str() {
+                "Brom" => health.damage(12),
+                "Aria" => {
+                    experience.gain(10);
+                }
+                _ => {}
+            }
+        }
+
+        world.resource_mut::<Items>().push(Item::VisionPotion);
+        world.increment_change_tick();
+    }
+
+    fn saved_rows(world: &World) -> Rows {
+        let db = world.non_send_resource::<Database>();
+        let game_id = world.resource::<SaveGame>().game_id.0;
+
+        [
+            "SELECT position_x,position_y,cleared,r_type,rng_seed,discovered FROM RoomInfo
+                WHERE game_id = :game_id ORDER BY position_x,position_y",
+            "SELECT name,display_name,health_max,health_curr,attack_damage_min,attack_damage_max,
+                hit_chance,attack_speed,level,xp FROM PlayerActor WHERE game_id = :game_id ORDER BY rowid",
+            "SELECT type FROM Item WHERE game_id = :game_id ORDER BY type",
+            "SELECT current_room_x,current_room_y,pillar_count FROM SaveGame
+                WHERE game_id = :game_id",
+        ]
+        .iter()
+        .flat_map(|query| {
+            let mut statement = db.connection.prepare(query).unwrap();
+            let columns = statement.column_count();
+            statement
+                .query_map((game_id,), |row| {
+                    (0..columns)
+                        .map(|i| {
+                            row.get::<_, rusqlite::types::Value>(i)
+                                .map(|value| format!("{value:?}"))
+                        })
+                        .collect()
+                })
+                .unwrap()
+                .collect::<Result<Rows, _>>()
+                .unwrap()
         })
-        .unwrap();
+        .collect()
+    }
+
+    #[test]
+    fn incremental_save_matches_full_save() {
+        let mut world = test_world();
+        save_game(&mut world);
+
+        for _ in 0..3 {
+            play(&mut world);
+            save_game(&mut world);
+            let incremental = saved_rows(&world);
+
+            save_game_full(&mut world);
+            let full = saved_rows(&world);
+
+            assert_eq!(incremental, full);
+        }
+    }
+
+    fn room_seed(world: &World, x: u32) -> i64 {
+        let query = "SELECT rng_seed FROM RoomInfo WHERE position_x = ?1 AND position_y = 0";
+        world
+            .non_send_resource::<Database>()
+            .connection
+            .query_one(query, (x,), |row| row.get(0))
+            .unwrap()
+    }
 
-    let entity = storage.get(&pos).unwrap();
+    #[test]
+    fn incremental_save_skips_unchanged_rows() {
+        let mut world = test_world();
+        save_game(&mut world);
 
-    commands.get_entity(entity).unwrap().insert(CurrentRoom);
+        // Only changed rows get written, so these stay until a full save.
+        world
+            .non_send_resource::<Database>()
+            .connection
+            .execute(
+                "UPDATE RoomInfo SET rng_seed = 1234 WHERE position_y = 0",
+                (),
+            )
+            .unwrap();
+
+        play(&mut world);
+        save_game(&mut world);
+        assert_eq!(room_seed(&world, 1), 4);
+        assert_eq!(room_seed(&world, 3), 1234);
+
+        save_game_full(&mut world);
+        assert_eq!(room_seed(&world, 3), 12);
+    }
+
+    #[test]
+    fn first_save_of_a_game_is_full() {
+        let mut world = test_world();
+        save_game(&mut world);
+        let first = saved_rows(&world);
+
+        // A new game with the same entities still needs everything saved.
+        let db = world.non_send_resource::<Database>();
+        let save = SaveGame::new(db, 0x5a7e, Difficulty::default(), GameRules::default(), "");
+        world.insert_resource(save);
+        save_game(&mut world);
+
+        assert_eq!(saved_rows(&world), first);
+    }
+
+    #[test]
+    fn difficulty_is_kept_with_the_save() {
+        let world = test_world();
+        let db = world.non_send_resource::<Database>();
+        let game_id = world.resource::<SaveGame>().game_id;
+
+        let save = SaveGame::load(db, game_id);
+        assert_eq!(save.difficulty, Difficulty::Hard);
+        assert_eq!(save.mode, GameMode::Survival);
+        assert!(save.challenges.timed_turns && !save.challenges.no_healing);
+        assert_eq!(save.generation, GenerationMode::Constraints);
+    }
+
+    #[test]
+    fn hardcore_fights_only_load_once() {
+        let world = test_world();
+        let db = world.non_send_resource::<Database>();
+        let combat = SuspendedCombat {
+            turn_order: vec![Combatant::Enemy(0), Combatant::Hero(2)],
+            enemy_health: vec![(0, 7)],
+            ..default()
+        };
+
+        let save = world.resource::<SaveGame>();
+        save.save_suspended_combat(db, Some(&combat)).unwrap();
+        assert_eq!(
+            save.load_suspended_combat(db).unwrap(),
+            Some(combat.clone())
+        );
+        assert_eq!(
+            save.load_suspended_combat(db).unwrap(),
+            Some(combat.clone())
+        );
+
+        let rules = GameRules {
+            hardcore: true,
+            ..default()
+        };
+        let hardcore = SaveGame::new(db, 0x5a7e, Difficulty::default(), rules, "");
+        hardcore.save_suspended_combat(db, Some(&combat)).unwrap();
+        let hardcore = SaveGame::load(db, hardcore.game_id);
+        assert_eq!(hardcore.load_suspended_combat(db).unwrap(), Some(combat));
+        assert_eq!(hardcore.load_suspended_combat(db).unwrap(), None);
+    }
+
+    #[test]
+    fn corruption_is_kept_with_the_save() {
+        let mut world = test_world();
+        save_game(&mut world);
+        let save = world.resource::<SaveGame>();
+        let db = world.non_send_resource::<Database>();
+        assert_eq!(save.load_corruption(db).unwrap(), None);
+
+        let mut corruption = PillarCorruption::default();
+        corruption.advance();
+        corruption.corrupt(&TilePos::new(1, 2));
+        world.insert_resource(corruption.clone());
+        save_game(&mut world);
+
+        let save = world.resource::<SaveGame>();
+        let db = world.non_send_resource::<Database>();
+        assert_eq!(save.load_corruption(db).unwrap(), Some(corruption));
+    }
+
+    #[test]
+    fn run_stats_are_kept_with_the_save() {
+        let mut world = test_world();
+        save_game(&mut world);
+        let save = world.resource::<SaveGame>();
+        let db = world.non_send_resource::<Database>();
+        assert_eq!(save.load_run_stats(db).unwrap(), RunStats::default());
+
+        let stats = RunStats {
+            rooms_explored: 4,
+            enemies_killed: 7,
+            damage_dealt: 120,
+            damage_taken: 45,
+            pits_triggered: 1,
+            items_used: 2,
+            turns_taken: 19,
+        };
+        world.insert_resource(stats);
+        save_game(&mut world);
+
+        let save = world.resource::<SaveGame>();
+        let db = world.non_send_resource::<Database>();
+        assert_eq!(save.load_run_stats(db).unwrap(), stats);
+    }
+
+    #[test]
+    fn floors_keep_their_own_rooms() {
+        let mut world = test_world();
+        save_game(&mut world);
+        world.resource_mut::<SaveGame>().floor = 1;
+        save_game_full(&mut world);
+
+        let db = world.non_send_resource::<Database>();
+        let game_id = world.resource::<SaveGame>().game_id;
+        let floors: Vec<(u32, i64)> = db
+            .connection
+            .prepare("SELECT floor, count(*) FROM RoomInfo WHERE game_id = ?1 GROUP BY floor")
+            .unwrap()
+            .query_map((game_id.0,), |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert_eq!(floors, [(0, 16), (1, 16)]);
+        assert_eq!(SaveGame::load(db, game_id).floor, 1);
+    }
+
+    #[test]
+    fn world_name_is_listed_with_the_save() {
+        let world = test_world();
+        let db = world.non_send_resource::<Database>();
+        let game_id = world.resource::<SaveGame>().game_id;
+
+        assert_eq!(SaveGame::load(db, game_id).name, "Test World");
+
+        let saves = SaveGameInfo::get_all(db).unwrap();
+        assert!(
+            saves
+                .iter()
+                .any(|save| save.id == game_id && save.name == "Test World")
+        );
+    }
+
+    #[test]
+    fn autosaves_are_listed_with_the_save() {
+        let mut world = test_world();
+        save_game_full(&mut world);
+        let listed = |world: &World| {
+            let game_id = world.resource::<SaveGame>().game_id;
+            SaveGameInfo::get_all(world.non_send_resource::<Database>())
+                .unwrap()
+                .iter()
+                .find(|save| save.id == game_id)
+                .unwrap()
+                .clone()
+        };
+        assert_eq!(listed(&world).last_autosaved, None);
+
+        autosave_game(&mut world);
+        let save = listed(&world);
+        assert_eq!(save.last_autosaved, Some(save.last_saved));
+    }
+
+    #[test]
+    fn exported_saves_import_as_a_copy() {
+        let mut world = test_world();
+        world.insert_resource(Gold(42));
+        save_game_full(&mut world);
+
+        let db = world.non_send_resource::<Database>();
+        let game_id = world.resource::<SaveGame>().game_id;
+        let exported = ExportedSave::from_database(db, game_id).unwrap();
+
+        // Written out and read back, as it would be through a file.
+        let text = ron::to_string(&exported).unwrap();
+        let read: ExportedSave = ron::from_str(&text).unwrap();
+        assert_eq!(read, exported);
+
+        let copy = read.insert(db).unwrap();
+        assert_ne!(copy, game_id);
+        assert_eq!(ExportedSave::from_database(db, copy).unwrap(), exported);
+
+        let save = SaveGame::load(db, copy);
+        assert_eq!((save.seed, save.name.as_str()), (0x5a7e, "Test World"));
+        assert_eq!(save.generation, GenerationMode::Constraints);
+    }
+
+    #[test]
+    fn duplicated_saves_are_renamed_copies() {
+        let mut world = test_world();
+        world.insert_resource(Gold(42));
+        save_game_full(&mut world);
+
+        let db = world.non_send_resource::<Database>();
+        let game_id = world.resource::<SaveGame>().game_id;
+        let copy = SaveGame::duplicate(db, game_id).unwrap();
+        assert_ne!(copy, game_id);
+        assert!(!SaveGame::is_tampered(db, copy).unwrap());
+
+        let save = SaveGame::load(db, copy);
+        assert_eq!(
+            (save.seed, save.name.as_str()),
+            (0x5a7e, "Test World (copy)")
+        );
+        let rooms = |game_id: GameID| {
+            ExportedSave::from_database(db, game_id)
+                .unwrap()
+                .tables
+                .into_iter()
+                .find(|table| table.name == "RoomInfo")
+                .unwrap()
+        };
+        assert_eq!(rooms(copy), rooms(game_id));
+
+        db.connection
+            .execute(
+                "UPDATE SaveGame SET hardcore = 1 WHERE game_id = ?1",
+                (game_id.0,),
+            )
+            .unwrap();
+        let games = SaveGameInfo::get_all(db).unwrap().len();
+        assert!(matches!(
+            SaveGame::duplicate(db, game_id),
+            Err(SaveFileError::Hardcore)
+        ));
+        assert_eq!(SaveGameInfo::get_all(db).unwrap().len(), games);
+    }
+
+    #[test]
+    fn renamed_saves_stay_trusted() {
+        let mut world = test_world();
+        save_game_full(&mut world);
+
+        let db = world.non_send_resource::<Database>();
+        let game_id = world.resource::<SaveGame>().game_id;
+        SaveGame::rename(db, game_id, "Renamed World").unwrap();
+
+        assert_eq!(SaveGame::load(db, game_id).name, "Renamed World");
+        assert!(!SaveGame::is_tampered(db, game_id).unwrap());
+    }
+
+    #[test]
+    fn bad_imports_add_nothing() {
+        let mut world = test_world();
+        save_game_full(&mut world);
+
+        let db = world.non_send_resource::<Database>();
+        let game_id = world.resource::<SaveGame>().game_id;
+        let exported = ExportedSave::from_database(db, game_id).unwrap();
+        let games = || SaveGameInfo::get_all(db).unwrap().len();
+        let before = games();
+
+        let newer = ExportedSave {
+            version: DB_VERSION + 1,
+            ..exported.clone()
+        };
+        assert!(matches!(newer.insert(db), Err(SaveFileError::Version(_))));
+
+        let mut sneaky = exported.clone();
+        let items = sneaky.tables.iter_mut().find(|t| t.name == "Item").unwrap();
+        items.columns[0] = "type) VALUES(1); DROP TABLE Item; --".into();
+        assert!(matches!(sneaky.insert(db), Err(SaveFileError::Unknown(_))));
+
+        let mut empty = exported.clone();
+        empty.tables.retain(|table| table.name != "SaveGame");
+        assert!(matches!(empty.insert(db), Err(SaveFileError::Unknown(_))));
+
+        let mut hardcore = exported;
+        let games_table = hardcore
+            .tables
+            .iter_mut()
+            .find(|t| t.name == "SaveGame")
+            .unwrap();
+        let column = games_table
+            .columns
+            .iter()
+            .position(|c| c == "hardcore")
+            .unwrap();
+        games_table.rows[0][column] = ExportedValue::Integer(1);
+        assert!(matches!(hardcore.insert(db), Err(SaveFileError::Hardcore)));
+
+        assert_eq!(games(), before);
+    }
+
+    #[test]
+    fn changes_outside_the_game_are_caught() {
+        let mut world = test_world();
+        let game_id = world.resource::<SaveGame>().game_id;
+        save_game(&mut world);
+        let tampered = |world: &World| {
+            SaveGame::is_tampered(world.non_send_resource::<Database>(), game_id).unwrap()
+        };
+        assert!(!tampered(&world));
+
+        // Saving again writes the same rows in a different order.
+        save_game_full(&mut world);
+        autosave_game(&mut world);
+        assert!(!tampered(&world));
+
+        world
+            .non_send_resource::<Database>()
+            .connection
+            .execute(
+                "UPDATE SaveGame SET gold = 9999 WHERE game_id = ?1",
+                (game_id.0,),
+            )
+            .unwrap();
+        assert!(tampered(&world));
+
+        play(&mut world);
+        save_game(&mut world);
+        assert!(!tampered(&world));
+    }
+
+    #[test]
+    fn saves_without_a_checksum_are_trusted() {
+        let world = test_world();
+        let db = world.non_send_resource::<Database>();
+        let game_id = world.resource::<SaveGame>().game_id;
+
+        assert!(!SaveGame::is_tampered(db, game_id).unwrap());
+    }
+
+    #[test]
+    fn gold_is_kept_with_the_save() {
+        let mut world = test_world();
+        world.insert_resource(Gold(42));
+        save_game(&mut world);
+
+        let db = world.non_send_resource::<Database>();
+        let game_id = world.resource::<SaveGame>().game_id;
+        let gold: i64 = db
+            .connection
+            .query_one(
+                "SELECT gold FROM SaveGame WHERE game_id = :game_id",
+                (game_id.0,),
+                |row| row.get(0),
+            )
+            .unwrap();
+
+        assert_eq!(gold, 42);
+    }
+
+    #[test]
+    fn loading_a_room_off_the_map_fails() {
+        let mut world = test_world();
+        save_game(&mut world);
+
+        world.spawn((MapTilemap, TileStorage::empty(TilemapSize { x: 4, y: 4 })));
+        let loaded = world.run_system_cached(load_game_inner).unwrap();
+
+        assert!(matches!(
+            loaded,
+            Err(GameError::MissingRoom(pos)) if pos == TilePos::new(0, 0)
+        ));
+    }
 }
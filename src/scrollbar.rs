@@ -0,0 +1,167 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+
+const SCROLLBAR_WIDTH: f32 = 10.0;
+const SCROLLBAR_MIN_THUMB_HEIGHT: f32 = 20.0;
+
+pub struct ScrollbarPlugin;
+
+impl Plugin for ScrollbarPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, attach_scrollbars).add_systems(
+            PostUpdate,
+            sync_scrollbars.after(bevy::ui::UiSystem::Layout),
+        );
+    }
+}
+
+/// The scrollbar track.
+/// It is a child of the node it scrolls, held in place against the scroll offset.
+#[derive(Component)]
+pub struct ScrollbarTrack {
+    target: Entity,
+}
+
+#[derive(Component)]
+pub struct ScrollbarThumb {
+    target: Entity,
+}
+
+/// Every vertically scrolling node gets a scrollbar.
+fn attach_scrollbars(
+    mut commands: Commands,
+    nodes: Query<(Entity, &Node), Added<Node>>,
+    style: Res<Style>,
+) {
+    for (target, node) in nodes.iter() {
+        if node.overflow.y != OverflowAxis::Scroll {
+            continue;
+        }
+
+        commands.entity(target).with_children(|builder| {
+            builder
+                .spawn((
+                    Node {
+                        position_type: PositionType::Absolute,
+                        right: Val::Px(0.0),
+                        top: Val::Px(0.0),
+                        width: Val::Px(SCROLLBAR_WIDTH),
+                        display: Display::None,
+                        ..default()
+                    },
+                    BackgroundColor(style.background_color),
+                    ScrollbarTrack { target },
+                ))
+                .observe(jump_on_click)
+                .with_children(|builder| {
+                    builder
+                        .spawn((
+                            Node {
+                                position_type: PositionType::Absolute,
+                                width: Val::Percent(100.0),
+                                ..default()
+                            },
+                            BackgroundColor(style.button_color),
+                            ScrollbarThumb { target },
+                        ))
+                        .observe(drag_thumb)
+                        // Releasing a drag also counts as a click.
+                        .observe(stop_event_propagate::<Pointer<Click>>);
+                });
+        });
+    }
+}
+
+/// The visible height, content height and thumb height in logical pixels.
+fn scroll_metrics(computed: &ComputedNode) -> (f32, f32, f32) {
+    let scale = computed.inverse_scale_factor();
+    let visible = computed.size().y * scale;
+    let content = computed.content_size.y * scale;
+    let thumb = (visible * visible / content.max(1.0))
+        .max(SCROLLBAR_MIN_THUMB_HEIGHT)
+        .min(visible);
+
+    (visible, content, thumb)
+}
+
+fn sync_scrollbars(
+    targets: Query<(&ScrollPosition, &ComputedNode), Without<ScrollbarTrack>>,
+    mut tracks: Query<(&ScrollbarTrack, &mut Node, &Children), Without<ScrollbarThumb>>,
+    mut thumbs: Query<&mut Node, With<ScrollbarThumb>>,
+) {
+    for (ScrollbarTrack { target }, mut track_node, children) in tracks.iter_mut() {
+        let Ok((scroll, computed)) = targets.get(*target) else {
+            continue;
+        };
+
+        let (visible, content, thumb) = scroll_metrics(computed);
+        if content <= visible {
+            if track_node.display != Display::None {
+                track_node.display = Display::None;
+            }
+            continue;
+        }
+
+        // Only touch the nodes when they actually move, to avoid relayout every frame.
+        track_node.set_if_neq(Node {
+            display: Display::Flex,
+            top: Val::Px(scroll.offset_y),
+            height: Val::Px(visible),
+            ..track_node.clone()
+        });
+
+        let progress = (scroll.offset_y / (content - visible)).clamp(0.0, 1.0);
+        for child in children.iter() {
+            if let Ok(mut thumb_node) = thumbs.get_mut(child) {
+                thumb_node.set_if_neq(Node {
+                    height: Val::Px(thumb),
+                    top: Val::Px(progress * (visible - thumb)),
+                    ..thumb_node.clone()
+                });
+            }
+        }
+    }
+}
+
+/// Moves the thumb to be centered where the track was clicked.
+fn jump_on_click(
+    mut click: Trigger<Pointer<Click>>,
+    tracks: Query<(&ScrollbarTrack, &ComputedNode, &GlobalTransform)>,
+    mut targets: Query<(&mut ScrollPosition, &ComputedNode), Without<ScrollbarTrack>>,
+) {
+    click.propagate(false);
+
+    let Ok((ScrollbarTrack { target }, track, transform)) = tracks.get(click.target()) else {
+        return;
+    };
+    let Ok((mut scroll, computed)) = targets.get_mut(*target) else {
+        return;
+    };
+
+    let (visible, content, thumb) = scroll_metrics(computed);
+    let scale = track.inverse_scale_factor();
+    let track_top = transform.translation().y * scale - track.size().y * scale / 2.0;
+    let local_y = click.pointer_location.position.y - track_top;
+
+    let progress = ((local_y - thumb / 2.0) / (visible - thumb).max(1.0)).clamp(0.0, 1.0);
+    scroll.offset_y = progress * (content - visible).max(0.0);
+}
+
+fn drag_thumb(
+    mut drag: Trigger<Pointer<Drag>>,
+    thumbs: Query<&ScrollbarThumb>,
+    mut targets: Query<(&mut ScrollPosition, &ComputedNode)>,
+) {
+    drag.propagate(false);
+
+    let Ok(ScrollbarThumb { target }) = thumbs.get(drag.target()) else {
+        return;
+    };
+    let Ok((mut scroll, computed)) = targets.get_mut(*target) else {
+        return;
+    };
+
+    let (visible, content, thumb) = scroll_metrics(computed);
+    let ratio = (content - visible) / (visible - thumb).max(1.0);
+    scroll.offset_y = (scroll.offset_y + drag.delta.y * ratio).clamp(0.0, content - visible);
+}
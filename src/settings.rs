@@ -0,0 +1,72 @@
+//! Where the player's settings are kept.
+//!
+//! Each setting is a resource read from the [`SETTINGS_DB_TABLE`] at startup,
+//! and written back whenever it changes.
+
+use bevy::prelude::*;
+
+/// The key-value table in the database every setting is saved in.
+pub const SETTINGS_DB_TABLE: &str = "Settings";
+
+/// Run condition for saving a setting that is dragged, like a slider.
+/// True once the setting has changed and no mouse button is held anymore,
+/// so it is written once when the player lets go instead of every frame.
+pub fn setting_settled<T: Resource>(
+    mut pending: Local<bool>,
+    setting: Option<Res<T>>,
+    mouse: Option<Res<ButtonInput<MouseButton>>>,
+) -> bool {
+    if setting.is_some_and(|setting| setting.is_changed() && !setting.is_added()) {
+        *pending = true;
+    }
+
+    let held = mouse.is_some_and(|mouse| mouse.get_pressed().next().is_some());
+    if !*pending || held {
+        return false;
+    }
+
+    *pending = false;
+    true
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Resource, Default)]
+    struct Setting(f32);
+
+    #[derive(Resource, Default)]
+    struct Saves(usize);
+
+    fn save(mut saves: ResMut<Saves>) {
+        saves.0 += 1;
+    }
+
+    #[test]
+    fn saved_once_the_mouse_is_released() {
+        let mut app = App::new();
+        app.init_resource::<Setting>()
+            .init_resource::<Saves>()
+            .init_resource::<ButtonInput<MouseButton>>()
+            .add_systems(Update, save.run_if(setting_settled::<Setting>));
+        app.update();
+        assert_eq!(app.world().resource::<Saves>().0, 0);
+
+        app.world_mut()
+            .resource_mut::<ButtonInput<MouseButton>>()
+            .press(MouseButton::Left);
+        for step in 1..=3 {
+            app.world_mut().resource_mut::<Setting>().0 = step as f32 / 10.0;
+            app.update();
+        }
+        assert_eq!(app.world().resource::<Saves>().0, 0);
+
+        app.world_mut()
+            .resource_mut::<ButtonInput<MouseButton>>()
+            .release(MouseButton::Left);
+        app.update();
+        app.update();
+        assert_eq!(app.world().resource::<Saves>().0, 1);
+    }
+}
@@ -5,49 +5,78 @@ use bevy_ecs_tilemap::prelude::*;
 
 #[cfg(feature = "sqlite")]
 pub fn save_map(
-    tile_storage: Single<&TileStorage, With<MapTilemap>>,
-    info_q: Query<(&TilePos, &RoomInfo), With<MapTile>>,
+    In(kind): In<SaveKind>,
+    info_q: Query<(&TilePos, Ref<RoomInfo>), With<MapTile>>,
     save_info: Res<SaveGame>,
     db: NonSend<Database>,
 ) -> Result<(), DatabaseError> {
     let game_id = save_info.game_id.0;
+    let floor = save_info.floor;
 
     let query = r#"
             INSERT OR REPLACE INTO RoomInfo(
                 game_id,
+                floor,
                 position_x,
                 position_y,
                 cleared,
                 r_type,
-                rng_seed
+                rng_seed,
+                discovered,
+                guardian_respawn,
+                chest_opened
             )
             VALUES(
                 :game_id,
+                :floor,
                 :position_x,
                 :position_y,
                 :cleared,
                 :r_type,
-                :rng_seed
+                :rng_seed,
+                :discovered,
+                :guardian_respawn,
+                :chest_opened
             );
         "#;
 
+    // Rooms can move, as when the map grows in endless mode, so a full save starts the floor over.
+    if kind == SaveKind::Full {
+        db.connection.execute(
+            "DELETE FROM RoomInfo WHERE game_id = ?1 AND floor = ?2",
+            (game_id, floor),
+        )?;
+    }
+
     let mut query = db.connection.prepare(query)?;
 
-    for (
-        TilePos { x: pos_x, y: pos_y },
-        RoomInfo {
+    for (TilePos { x: pos_x, y: pos_y }, info) in info_q.iter() {
+        if kind == SaveKind::Incremental && !info.is_changed() {
+            continue;
+        }
+
+        let RoomInfo {
             cleared,
             r_type,
             rng_seed,
-        },
-    ) in tile_storage
-        .iter()
-        .filter_map(|entity| *entity)
-        .filter_map(|entity| info_q.get(entity).ok())
-    {
+            discovered,
+            guardian_respawn,
+            chest_opened,
+        } = &*info;
         let r_type = ron::to_string(&r_type).unwrap();
 
-        query.execute((game_id, pos_x, pos_y, cleared, r_type, *rng_seed as i64))?;
+        query.execute((
+            game_id,
+            floor,
+            pos_x,
+            pos_y,
+            cleared,
+            r_type,
+            *rng_seed as i64,
+            discovered,
+            guardian_respawn,
+            chest_opened,
+        ))?;
     }
 
     Ok(())
@@ -60,8 +89,6 @@ pub fn load_map(
     save_game: Res<SaveGame>,
     asset_server: Res<AssetServer>,
 ) -> Result<(), DatabaseError> {
-    let tile_sprite = asset_server.load(MAP_TILE_ASSET_LOAD_PATH);
-
     let game_id = save_game.game_id;
     let query = "
             SELECT
@@ -69,16 +96,17 @@ pub fn load_map(
                 position_y,
                 cleared,
                 r_type,
-                rng_seed
-            FROM RoomInfo WHERE RoomInfo.game_id = :game;
+                rng_seed,
+                discovered,
+                guardian_respawn,
+                chest_opened
+            FROM RoomInfo WHERE RoomInfo.game_id = :game AND RoomInfo.floor = :floor;
         ";
 
-    let tilemap_entity = commands.spawn_empty().id();
-    let mut tile_storage = TileStorage::empty(MAP_SIZE);
-
-    db.connection
+    let rooms = db
+        .connection
         .prepare(query)?
-        .query_map((game_id.0,), |row| {
+        .query_map((game_id.0, save_game.floor), |row| {
             let x = row.get("position_x")?;
             let y = row.get("position_y")?;
             let cleared = row.get("cleared")?;
@@ -86,6 +114,9 @@ pub fn load_map(
             let r_type = ron::from_str(&r_type).unwrap_or(RoomType::EmptyRoom);
             // cast as sqlite can only store i64s
             let rng_seed = row.get::<_, i64>("rng_seed")? as u64;
+            let discovered = row.get("discovered")?;
+            let guardian_respawn = row.get("guardian_respawn")?;
+            let chest_opened = row.get("chest_opened")?;
 
             Ok((
                 TilePos { x, y },
@@ -93,33 +124,69 @@ pub fn load_map(
                     cleared,
                     r_type,
                     rng_seed,
+                    discovered,
+                    guardian_respawn,
+                    chest_opened,
                 },
             ))
         })?
         .map(|c| c.unwrap())
-        .for_each(|(tile_pos, room_info)| {
-            let id = commands
-                .spawn((
-                    room_info,
-                    TileBundle {
-                        position: tile_pos,
-                        tilemap_id: TilemapId(tilemap_entity),
-                        texture_index: TileTextureIndex(FLOOR_TILE_VARIENTS.start),
-                        ..Default::default()
-                    },
-                    MapTile,
-                ))
-                .id();
-            commands.entity(tilemap_entity).add_child(id);
-            tile_storage.set(&tile_pos, id);
-        });
+        .collect::<Vec<_>>();
+
+    spawn_map_rooms(&mut commands, &asset_server, rooms, save_game.shape.radius);
+
+    Ok(())
+}
+
+/// Spawns a map of the rooms, at least `radius` rooms out and sized to fit them, returning the tilemap.
+pub fn spawn_map_rooms(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    rooms: Vec<(TilePos, RoomInfo)>,
+    radius: u32,
+) -> Entity {
+    let tile_sprite = asset_server.load(MAP_TILE_ASSET_LOAD_PATH);
+
+    // The map is bigger than it started once it has grown in endless mode.
+    let extent = rooms
+        .iter()
+        .map(|(pos, _)| pos.x.max(pos.y))
+        .max()
+        .unwrap_or_default();
+    let radius = radius.max(extent.div_ceil(2));
+    let map_size = TilemapSize {
+        x: radius * 2 + 1,
+        y: radius * 2 + 1,
+    };
+
+    let tilemap_entity = commands.spawn_empty().id();
+    let mut tile_storage = TileStorage::empty(map_size);
+
+    rooms.into_iter().for_each(|(tile_pos, room_info)| {
+        let id = commands
+            .spawn((
+                room_info,
+                TileBundle {
+                    position: tile_pos,
+                    tilemap_id: TilemapId(tilemap_entity),
+                    texture_index: TileTextureIndex(FLOOR_TILE_VARIENTS.start),
+                    ..Default::default()
+                },
+                MapTile,
+            ))
+            .id();
+        commands.entity(tilemap_entity).add_child(id);
+        tile_storage.set(&tile_pos, id);
+    });
 
     commands.entity(tilemap_entity).insert((
         MapTilemap,
+        // So rooms can be pinned from their right click menu.
+        Pickable::default(),
         TilemapBundle {
             grid_size: TILE_SIZE.into(),
             map_type: TilemapType::Hexagon(HexCoordSystem::Column),
-            size: MAP_SIZE,
+            size: map_size,
             storage: tile_storage,
             texture: TilemapTexture::Single(tile_sprite.clone()),
             tile_size: TILE_SIZE,
@@ -129,5 +196,5 @@ pub fn load_map(
         },
     ));
 
-    Ok(())
+    tilemap_entity
 }
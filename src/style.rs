@@ -1,4 +1,4 @@
-use crate::controls::Input;
+use crate::controls::{Input, LastInputDevice};
 use crate::embed_asset;
 use crate::prelude::*;
 use bevy::prelude::*;
@@ -12,10 +12,12 @@ const DEFAULT_FONT_PATH: &str = "embedded://assets/fonts/Ithaca/Ithaca-LVB75.ttf
 const DEFAULT_TEXT_COLOR: Color = Color::srgb_u8(0xe0, 0xde, 0xf4);
 const DEFAULT_BACKGROUND_COLOR: Color = Color::srgba_u8(0x26, 0x23, 0x3a, 0xaa);
 const DEFAULT_TITLE_COLOR: Color = Color::srgb_u8(0x26, 0x23, 0x3a);
+const DEFAULT_ACCENT_COLOR: Color = Color::srgb_u8(0xf6, 0xc1, 0x77);
 const DEFAULT_BUTTON_COLOR: Color = Color::srgb_u8(0x26, 0x23, 0x3a);
 const DEFAULT_PRESSED_BUTTON_COLOR: Color = Color::srgb_u8(0x9c, 0xcf, 0xd8);
 const DEFAULT_HOVERED_BUTTON_COLOR: Color = Color::srgb_u8(0x1f, 0x1d, 0x2e);
 const DEFAULT_HOVERED_PRESSED_BUTTON_COLOR: Color = Color::srgb_u8(0x1f, 0x1d, 0x2e);
+const DEFAULT_FOCUS_COLOR: Color = Color::srgb_u8(0xeb, 0xbc, 0xba);
 
 pub struct StylePlugin;
 
@@ -30,10 +32,15 @@ impl Plugin for StylePlugin {
     }
 }
 
-fn sync_to_database(db: NonSend<Database>, style: Res<Style>, asset_server: Res<AssetServer>) {
-    if let Err(err) = style.to_database(&db, &asset_server) {
-        warn!("Failed to sync style settings to database with: {err}");
-    };
+fn sync_to_database(
+    db: NonSend<Database>,
+    style: Res<Style>,
+    asset_server: Res<AssetServer>,
+) -> Result {
+    style
+        .to_database(&db, &asset_server)
+        .map_err(GameError::from)?;
+    Ok(())
 }
 
 pub fn add_style(
@@ -53,10 +60,14 @@ pub struct Style {
     pub background_color: Color,
     pub title_color: Color,
     pub text_color: Color,
+    /// Used to draw attention to something, i.e. modified settings.
+    pub accent_color: Color,
     pub button_color: Color,
     pub pressed_button_color: Color,
     pub hovered_button_color: Color,
     pub hovered_pressed_button_color: Color,
+    /// Outlines whatever the keyboard or gamepad has focused.
+    pub focus_color: Color,
 }
 
 impl Style {
@@ -71,32 +82,57 @@ impl Style {
     /// Spawns Node(s) representing inputs, using glyphs where possible.
     pub fn display_keybind(&self, builder: &mut ChildSpawnerCommands<'_>, keybind: &Keybind) {
         let Keybind(control, key) = keybind;
-        match key {
-            [Some(a), Some(b)] => {
-                builder
-                    .spawn(Node { ..default() })
-                    .with_children(move |builder| {
-                        self.display_input(builder, a);
-                        builder.spawn((
-                            Text::new("/"),
-                            self.font(32.0),
-                            TextColor(self.text_color),
-                            Label,
-                            Pickable::IGNORE,
-                        ));
-                        self.display_input(builder, b);
-                    });
-            }
-            [Some(a), None] | [None, Some(a)] => self.display_input(builder, a),
-            [None, None] => {
-                builder.spawn((
-                    Text::new(format!("{control} Not Bound")),
-                    self.font(32.0),
-                    TextColor(self.text_color),
-                    Label,
-                    Pickable::IGNORE,
-                ));
-            }
+        let mut bound = key.iter().flatten();
+        let Some(first) = bound.next() else {
+            builder.spawn((
+                Text::new(format!("{control} Not Bound")),
+                self.font(32.0),
+                TextColor(self.text_color),
+                Label,
+                Pickable::IGNORE,
+            ));
+            return;
+        };
+
+        let rest: Vec<Input> = bound.copied().collect();
+        if rest.is_empty() {
+            self.display_input(builder, first);
+            return;
+        }
+
+        let first = *first;
+        builder
+            .spawn(Node { ..default() })
+            .with_children(move |builder| {
+                self.display_input(builder, &first);
+                for input in rest.iter() {
+                    builder.spawn((
+                        Text::new("/"),
+                        self.font(32.0),
+                        TextColor(self.text_color),
+                        Label,
+                        Pickable::IGNORE,
+                    ));
+                    self.display_input(builder, input);
+                }
+            });
+    }
+
+    /// Like [`Style::display_keybind`], but only shows the inputs for the given device.
+    /// Falls back to showing all of them if none are bound for that device.
+    pub fn display_keybind_for(
+        &self,
+        builder: &mut ChildSpawnerCommands<'_>,
+        keybind: &Keybind,
+        device: LastInputDevice,
+    ) {
+        let Keybind(control, keys) = keybind;
+        let device_keys = keys.map(|k| k.filter(|k| k.device() == device));
+
+        if device_keys.iter().any(Option::is_some) {
+            self.display_keybind(builder, &Keybind(*control, device_keys));
+        } else {
+            self.display_keybind(builder, keybind);
         }
     }
 
@@ -165,6 +201,7 @@ impl Style {
             ),
             title_color: db.get_kv(STYLE_DB_TABLE, "title_color", DEFAULT_TITLE_COLOR),
             text_color: db.get_kv(STYLE_DB_TABLE, "text_color", DEFAULT_TEXT_COLOR),
+            accent_color: db.get_kv(STYLE_DB_TABLE, "accent_color", DEFAULT_ACCENT_COLOR),
             button_color: db.get_kv(STYLE_DB_TABLE, "normal_button", DEFAULT_BUTTON_COLOR),
             pressed_button_color: db.get_kv(
                 STYLE_DB_TABLE,
@@ -181,6 +218,7 @@ impl Style {
                 "hovered_pressed_button",
                 DEFAULT_HOVERED_PRESSED_BUTTON_COLOR,
             ),
+            focus_color: db.get_kv(STYLE_DB_TABLE, "focus_color", DEFAULT_FOCUS_COLOR),
         }
     }
 
@@ -201,6 +239,7 @@ impl Style {
         db.set_kv(STYLE_DB_TABLE, "background_color", self.background_color)?;
         db.set_kv(STYLE_DB_TABLE, "title_color", self.title_color)?;
         db.set_kv(STYLE_DB_TABLE, "text_color", self.text_color)?;
+        db.set_kv(STYLE_DB_TABLE, "accent_color", self.accent_color)?;
         db.set_kv(STYLE_DB_TABLE, "button_color", self.button_color)?;
         db.set_kv(
             STYLE_DB_TABLE,
@@ -217,6 +256,7 @@ impl Style {
             "hovered_pressed_button_color",
             self.hovered_pressed_button_color,
         )?;
+        db.set_kv(STYLE_DB_TABLE, "focus_color", self.focus_color)?;
 
         Ok(())
     }
@@ -472,8 +512,8 @@ fn input_glyph_info(input: &Input) -> Option<(usize, UVec2, bool)> {
             | K::Katakana,
         ) => None,
         I::Mouse(_) => None,
-        I::MouseWheelAxis(_) => None,
+        I::MouseWheelAxis(..) => None,
         I::Gamepad(_) => None,
-        I::GamepadAxis(_) => None,
+        I::GamepadAxis(..) => None,
     }
 }
@@ -0,0 +1,206 @@
+//! Animated tiles, to make rooms feel alive without costing much.
+//!
+//! The sky cycles through its variants on the GPU with [`AnimatedTile`]. Each variant plays
+//! at its own speed, so neighbouring tiles drift apart instead of flashing together, and a tile
+//! keeps its speed when the sky shifts its texture along. Torches flicker and shrine pools ripple
+//! on the CPU, as there are only a few of them. All of it can be turned off in the display settings.
+
+use crate::prelude::*;
+use crate::room::{Prop, WATER_COLOR, WaterTile};
+use crate::settings::SETTINGS_DB_TABLE;
+use crate::sky::SkyTile;
+use bevy_ecs_tilemap::prelude::*;
+
+/// How many times a second the slowest sky variant goes through all the variants.
+const SKY_CYCLE_SPEED: f32 = 0.05;
+/// How much faster each sky variant plays than the one before it.
+const SKY_SPEED_STEP: f32 = 0.15;
+/// How dark a torch gets at the bottom of its flicker.
+const TORCH_FLICKER_DEPTH: f32 = 0.3;
+/// How dark the water gets in the trough of a ripple.
+const WATER_RIPPLE_DEPTH: f32 = 0.25;
+/// How far behind its neighbour each tile of water ripples, in radians.
+const WATER_RIPPLE_STEP: f32 = 0.9;
+
+pub struct TileAnimationPlugin;
+
+impl Plugin for TileAnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PreStartup, setup_animate_tiles)
+            .add_systems(
+                Update,
+                animate_tiles_sync.run_if(
+                    resource_changed::<AnimateTiles>.and(not(resource_added::<AnimateTiles>)),
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    unanimate_sky.run_if(resource_changed::<AnimateTiles>),
+                    animate_sky,
+                    flicker_torches,
+                    ripple_water,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Whether the sky, torches and water move, set in the display settings.
+#[derive(Resource, Deref, DerefMut, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AnimateTiles(pub bool);
+
+impl AnimateTiles {
+    const DB_KEY: &str = "animate_tiles";
+}
+
+fn setup_animate_tiles(mut commands: Commands, database: NonSend<Database>) {
+    let animate = database.get_kv(SETTINGS_DB_TABLE, AnimateTiles::DB_KEY, true);
+    commands.insert_resource(AnimateTiles(animate));
+}
+
+fn animate_tiles_sync(database: NonSend<Database>, animate: Res<AnimateTiles>) -> Result {
+    database
+        .set_kv(SETTINGS_DB_TABLE, AnimateTiles::DB_KEY, animate.0)
+        .map_err(GameError::from)?;
+    Ok(())
+}
+
+/// How a sky tile showing the texture animates, going through every sky variant.
+pub fn sky_animation(texture: TileTextureIndex) -> AnimatedTile {
+    let variant = texture.0.saturating_sub(SKY_TILE_VARIENTS.start) as f32;
+    AnimatedTile {
+        start: SKY_TILE_VARIENTS.start,
+        end: SKY_TILE_VARIENTS.end,
+        speed: SKY_CYCLE_SPEED * (1.0 + variant * SKY_SPEED_STEP),
+    }
+}
+
+/// How bright a torch is at the time, from `1 - TORCH_FLICKER_DEPTH` to 1.
+/// The seed keeps torches in the same room from flickering together.
+pub fn torch_flicker(seconds: f32, seed: f32) -> f32 {
+    let wave = (seconds * 11.0 + seed).sin() * (seconds * 7.3 + seed * 2.0).sin();
+    1.0 - TORCH_FLICKER_DEPTH * (wave + 1.0) / 2.0
+}
+
+/// How bright the water on the tile is at the time, from `1 - WATER_RIPPLE_DEPTH` to 1.
+/// Each tile is a step behind the last, so the ripples roll across the pool.
+pub fn water_ripple(seconds: f32, pos: &TilePos) -> f32 {
+    let phase = (pos.x + pos.y * 2) as f32 * WATER_RIPPLE_STEP;
+    let wave = (seconds * 2.0 - phase).sin();
+    1.0 - WATER_RIPPLE_DEPTH * (wave + 1.0) / 2.0
+}
+
+fn unanimate_sky(
+    mut commands: Commands,
+    animate: Res<AnimateTiles>,
+    sky: Query<Entity, (With<SkyTile>, With<AnimatedTile>)>,
+) {
+    if animate.0 {
+        return;
+    }
+    for tile in sky.iter() {
+        commands.entity(tile).remove::<AnimatedTile>();
+    }
+}
+
+/// Sky tiles whose animation doesn't match their texture anymore, if they have one at all.
+type StaleSkyTiles = (
+    With<SkyTile>,
+    Or<(Changed<TileTextureIndex>, Without<AnimatedTile>)>,
+);
+
+/// Keeps each sky tile's animation matched to its texture, as the sky moves them along.
+fn animate_sky(
+    mut commands: Commands,
+    animate: Res<AnimateTiles>,
+    sky: Query<(Entity, &TileTextureIndex), StaleSkyTiles>,
+) {
+    if !animate.0 {
+        return;
+    }
+    for (tile, texture) in sky.iter() {
+        commands.entity(tile).insert(sky_animation(*texture));
+    }
+}
+
+fn flicker_torches(
+    time: Res<Time>,
+    animate: Res<AnimateTiles>,
+    mut torches: Query<(Entity, &Prop, &mut Sprite)>,
+) {
+    if !animate.0 && !animate.is_changed() {
+        return;
+    }
+    for (entity, prop, mut sprite) in torches.iter_mut() {
+        if *prop != Prop::Torch {
+            continue;
+        }
+        let brightness = match animate.0 {
+            true => torch_flicker(time.elapsed_secs(), entity.index() as f32),
+            false => 1.0,
+        };
+        let color = prop.color().to_srgba();
+        sprite.color = Color::srgb(
+            color.red * brightness,
+            color.green * brightness,
+            color.blue * brightness,
+        );
+    }
+}
+
+fn ripple_water(
+    time: Res<Time>,
+    animate: Res<AnimateTiles>,
+    mut water: Query<(&TilePos, &mut TileColor), With<WaterTile>>,
+) {
+    if !animate.0 && !animate.is_changed() {
+        return;
+    }
+    for (pos, mut color) in water.iter_mut() {
+        let brightness = match animate.0 {
+            true => water_ripple(time.elapsed_secs(), pos),
+            false => 1.0,
+        };
+        let water = WATER_COLOR.to_srgba();
+        color.0 = Color::srgb(
+            water.red * brightness,
+            water.green * brightness,
+            water.blue * brightness,
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sky_variants_drift_apart_and_torches_stay_lit() {
+        let speeds: Vec<f32> = SKY_TILE_VARIENTS
+            .map(|variant| sky_animation(TileTextureIndex(variant)).speed)
+            .collect();
+        assert!(speeds.windows(2).all(|pair| pair[0] < pair[1]));
+
+        let animation = sky_animation(TileTextureIndex(SKY_TILE_VARIENTS.start));
+        assert_eq!(animation.start..animation.end, SKY_TILE_VARIENTS);
+
+        for step in 0..200 {
+            let seconds = step as f32 * 0.05;
+            let brightness = torch_flicker(seconds, 3.0);
+            assert!((1.0 - TORCH_FLICKER_DEPTH..=1.0).contains(&brightness));
+        }
+        assert_ne!(torch_flicker(1.0, 0.0), torch_flicker(1.0, 5.0));
+    }
+
+    #[test]
+    fn water_ripples_across_the_pool() {
+        let (here, there) = (TilePos::new(4, 4), TilePos::new(5, 4));
+        for step in 0..200 {
+            let seconds = step as f32 * 0.05;
+            let brightness = water_ripple(seconds, &here);
+            assert!((1.0 - WATER_RIPPLE_DEPTH..=1.0).contains(&brightness));
+        }
+        assert_ne!(water_ripple(1.0, &here), water_ripple(1.0, &there));
+    }
+}
@@ -1,5 +1,8 @@
+mod animated;
 mod picking_backend;
 
+pub use animated::AnimateTiles;
+
 use crate::embed_asset;
 #[cfg(feature = "debug")]
 use bevy::dev_tools::picking_debug::{DebugPickingMode, DebugPickingPlugin};
@@ -42,6 +45,7 @@ impl Plugin for TilePlugin {
             )),
         );
         app.add_plugins(picking_backend::TilemapBackend)
+            .add_plugins(animated::TileAnimationPlugin)
             .add_systems(PreStartup, setup_hex_tile_image);
     }
 }
@@ -70,6 +70,7 @@ fn tile_picking(
             &TilemapAnchor,
         ),
     )>,
+    tile_visible_query: Query<&TileVisible>,
     mut output: EventWriter<PointerHits>,
 ) {
     let mut sorted_tile_maps: Vec<_> = tile_map_query
@@ -176,6 +177,14 @@ fn tile_picking(
                         return None;
                     };
 
+                    // Hidden tiles, like doors that lead nowhere, can't be picked.
+                    if tile_visible_query
+                        .get(tile_hovered)
+                        .is_ok_and(|visible| !visible.0)
+                    {
+                        return None;
+                    }
+
                     blocked = pickable.should_block_lower;
 
                     let hit_pos_world = map_transform.transform_point(cursor_pos_tile.extend(0.0));
@@ -0,0 +1,134 @@
+//! Speeds up or slows down the whole game.
+//! Used by the bot to play faster than real time, as a debug fast forward,
+//! and to hold the game still for a moment when an actor is killed.
+
+use crate::prelude::*;
+use bevy::prelude::*;
+use std::time::Duration;
+
+/// The scale the debug fast forward key toggles to.
+#[cfg(feature = "debug")]
+const DEBUG_FAST_FORWARD_SCALE: f32 = 8.0;
+/// How slow the game runs during a hit-stop.
+const HIT_STOP_SCALE: f32 = 0.05;
+/// How long a full strength hit-stop lasts, in real time.
+const HIT_STOP_SECONDS: f32 = 0.12;
+
+pub struct TimeScalePlugin;
+
+impl Plugin for TimeScalePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TimeScale>()
+            .init_resource::<HitStop>()
+            .add_systems(
+                PreUpdate,
+                apply_time_scale.run_if(
+                    resource_changed::<TimeScale>.or(|hit_stop: Res<HitStop>| hit_stop.0.is_some()),
+                ),
+            )
+            .add_observer(hit_stop_on_kill);
+
+        #[cfg(feature = "debug")]
+        app.add_systems(
+            PreUpdate,
+            (|mut scale: ResMut<TimeScale>| {
+                scale.0 = if scale.0 == 1.0 {
+                    DEBUG_FAST_FORWARD_SCALE
+                } else {
+                    1.0
+                };
+                info!("Time scale set to {}x", scale.0);
+            })
+            .before(apply_time_scale)
+            .run_if(bevy::input::common_conditions::input_just_pressed(
+                KeyCode::F4,
+            )),
+        );
+    }
+}
+
+/// How many times faster than real time the game runs.
+///
+/// This scales [`Time<Virtual>`], so every timer and movement
+/// driven by [`Time`] speeds up without being changed itself.
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct TimeScale(pub f32);
+
+impl Default for TimeScale {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+/// A moment where the game is almost held still, to let a kill land.
+/// Ticks in real time, as the game's own time is what it slows down.
+#[derive(Resource, Default, Debug)]
+pub struct HitStop(Option<Timer>);
+
+impl HitStop {
+    /// Holds the game for `strength` of a full hit-stop, unless a longer one is already going.
+    pub fn start(&mut self, strength: f32) {
+        let seconds = HIT_STOP_SECONDS * strength;
+        if self
+            .0
+            .as_ref()
+            .is_none_or(|timer| timer.remaining_secs() < seconds)
+        {
+            self.0 = Some(Timer::from_seconds(seconds, TimerMode::Once));
+        }
+    }
+
+    /// How much the hit-stop slows the game down right now, after `delta` more real time.
+    fn tick(&mut self, delta: Duration) -> f32 {
+        let Some(timer) = self.0.as_mut() else {
+            return 1.0;
+        };
+        if timer.tick(delta).finished() {
+            self.0 = None;
+            1.0
+        } else {
+            HIT_STOP_SCALE
+        }
+    }
+}
+
+fn hit_stop_on_kill(
+    change: Trigger<HealthChange>,
+    budget: EffectsBudget,
+    mut hit_stop: ResMut<HitStop>,
+) {
+    if *change.event() != HealthChange::Killed {
+        return;
+    }
+    let strength = budget.scale(EffectKind::HitStop);
+    if strength > 0.0 {
+        hit_stop.start(strength);
+    }
+}
+
+fn apply_time_scale(
+    scale: Res<TimeScale>,
+    mut hit_stop: ResMut<HitStop>,
+    real_time: Res<Time<Real>>,
+    mut time: ResMut<Time<Virtual>>,
+) {
+    let stop = hit_stop.tick(real_time.delta());
+    time.set_relative_speed(scale.0.max(0.0) * stop);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn hit_stops_hold_the_game_briefly() {
+        let mut hit_stop = HitStop::default();
+        assert_eq!(hit_stop.tick(Duration::from_millis(10)), 1.0);
+
+        hit_stop.start(1.0);
+        hit_stop.start(0.5);
+        assert_eq!(hit_stop.tick(Duration::from_millis(100)), HIT_STOP_SCALE);
+        assert_eq!(hit_stop.tick(Duration::from_millis(100)), 1.0);
+        assert!(hit_stop.0.is_none());
+    }
+}
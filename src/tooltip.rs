@@ -0,0 +1,251 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use std::time::Duration;
+
+/// How long something has to be hovered before the tooltip shows.
+const TOOLTIP_DELAY: Duration = Duration::from_millis(500);
+/// Distance from the cursor to the tooltip.
+const TOOLTIP_OFFSET: Vec2 = Vec2::new(16.0, 16.0);
+const TOOLTIP_MAX_WIDTH: f32 = 300.0;
+const TOOLTIP_Z_INDEX: i32 = 200;
+
+pub struct TooltipPlugin;
+
+impl Plugin for TooltipPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TooltipHover>()
+            .add_observer(tooltip_hover)
+            .add_observer(tooltip_unhover)
+            .add_systems(
+                Update,
+                (show_tooltip, sync_tooltip_content, position_tooltip).chain(),
+            );
+    }
+}
+
+/// Shows a popup after hovering the entity for a short time.
+/// The entity must be pickable.
+#[derive(Component, Clone, Default, Debug)]
+pub struct Tooltip {
+    pub icon: Option<ImageNode>,
+    pub title: String,
+    pub body: String,
+}
+
+impl Tooltip {
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            ..default()
+        }
+    }
+
+    pub fn with_body(mut self, body: impl Into<String>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    pub fn with_icon(mut self, icon: ImageNode) -> Self {
+        self.icon = Some(icon);
+        self
+    }
+}
+
+/// The spawned tooltip popup.
+#[derive(Component)]
+pub struct TooltipNode {
+    target: Entity,
+}
+
+/// What is currently hovered, and whether the tooltip has been shown for it.
+#[derive(Resource)]
+struct TooltipHover {
+    target: Option<Entity>,
+    timer: Timer,
+    popup: Option<Entity>,
+}
+
+impl Default for TooltipHover {
+    fn default() -> Self {
+        Self {
+            target: None,
+            timer: Timer::new(TOOLTIP_DELAY, TimerMode::Once),
+            popup: None,
+        }
+    }
+}
+
+impl TooltipHover {
+    fn clear(&mut self, commands: &mut Commands) {
+        self.target = None;
+        if let Some(popup) = self.popup.take() {
+            commands.entity(popup).try_despawn();
+        }
+    }
+}
+
+fn tooltip_hover(
+    over: Trigger<Pointer<Over>>,
+    mut commands: Commands,
+    mut hover: ResMut<TooltipHover>,
+    tooltips: Query<(), With<Tooltip>>,
+) {
+    let target = over.target();
+    if !tooltips.contains(target) || hover.target == Some(target) {
+        return;
+    }
+
+    hover.clear(&mut commands);
+    hover.target = Some(target);
+    hover.timer.reset();
+}
+
+fn tooltip_unhover(
+    out: Trigger<Pointer<Out>>,
+    mut commands: Commands,
+    mut hover: ResMut<TooltipHover>,
+) {
+    if hover.target == Some(out.target()) {
+        hover.clear(&mut commands);
+    }
+}
+
+fn show_tooltip(
+    mut commands: Commands,
+    mut hover: ResMut<TooltipHover>,
+    tooltips: Query<&Tooltip>,
+    style: Res<Style>,
+    time: Res<Time>,
+) {
+    let Some(target) = hover.target else {
+        return;
+    };
+
+    let Ok(tooltip) = tooltips.get(target) else {
+        // The target was despawned while being hovered.
+        hover.clear(&mut commands);
+        return;
+    };
+
+    if hover.popup.is_some() || !hover.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let popup = commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                max_width: Val::Px(TOOLTIP_MAX_WIDTH),
+                padding: UiRect::all(Val::Px(8.0)),
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            BackgroundColor(style.button_color),
+            GlobalZIndex(TOOLTIP_Z_INDEX),
+            // Hidden until it is laid out and positioned.
+            Visibility::Hidden,
+            Pickable::IGNORE,
+            TooltipNode { target },
+        ))
+        .id();
+
+    commands
+        .entity(popup)
+        .with_children(|builder| spawn_tooltip_content(builder, &style, tooltip));
+
+    hover.popup = Some(popup);
+}
+
+fn spawn_tooltip_content(builder: &mut ChildSpawnerCommands<'_>, style: &Style, tooltip: &Tooltip) {
+    builder
+        .spawn((
+            Node {
+                align_items: AlignItems::Center,
+                column_gap: Val::Px(5.0),
+                ..default()
+            },
+            Pickable::IGNORE,
+        ))
+        .with_children(|builder| {
+            if let Some(icon) = &tooltip.icon {
+                builder.spawn((
+                    Node {
+                        width: Val::Px(32.0),
+                        height: Val::Px(32.0),
+                        ..default()
+                    },
+                    icon.clone(),
+                    Pickable::IGNORE,
+                ));
+            }
+
+            builder.spawn((
+                Text::new(tooltip.title.clone()),
+                style.font(28.0),
+                TextColor(style.text_color),
+                Label,
+                Pickable::IGNORE,
+            ));
+        });
+
+    if !tooltip.body.is_empty() {
+        builder.spawn((
+            Text::new(tooltip.body.clone()),
+            style.font(22.0),
+            TextColor(style.text_color),
+            Label,
+            Pickable::IGNORE,
+        ));
+    }
+}
+
+/// Rebuilds the popup if the tooltip changes while it is shown.
+fn sync_tooltip_content(
+    mut commands: Commands,
+    popups: Query<(Entity, &TooltipNode)>,
+    tooltips: Query<&Tooltip, Changed<Tooltip>>,
+    style: Res<Style>,
+) {
+    for (popup, TooltipNode { target }) in popups.iter() {
+        let Ok(tooltip) = tooltips.get(*target) else {
+            continue;
+        };
+
+        commands
+            .entity(popup)
+            .despawn_related::<Children>()
+            .with_children(|builder| spawn_tooltip_content(builder, &style, tooltip));
+    }
+}
+
+/// Follows the cursor, flipping to the other side of it near the screen edges.
+fn position_tooltip(
+    window: Single<&Window, With<PrimaryWindow>>,
+    mut popups: Query<(&mut Node, &mut Visibility, &ComputedNode), With<TooltipNode>>,
+) {
+    let Some(cursor) = window.cursor_position() else {
+        return;
+    };
+    let screen = window.size();
+
+    for (mut node, mut visibility, computed) in popups.iter_mut() {
+        let size = computed.size() * computed.inverse_scale_factor();
+        if size == Vec2::ZERO {
+            continue;
+        }
+
+        let mut pos = cursor + TOOLTIP_OFFSET;
+        if pos.x + size.x > screen.x {
+            pos.x = cursor.x - TOOLTIP_OFFSET.x - size.x;
+        }
+        if pos.y + size.y > screen.y {
+            pos.y = cursor.y - TOOLTIP_OFFSET.y - size.y;
+        }
+        let pos = pos.max(Vec2::ZERO);
+
+        node.left = Val::Px(pos.x);
+        node.top = Val::Px(pos.y);
+        *visibility = Visibility::Inherited;
+    }
+}